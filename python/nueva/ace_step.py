@@ -20,10 +20,45 @@ import json
 import subprocess
 import tempfile
 from pathlib import Path
-from typing import Any, Optional
+from typing import Any, Callable, Optional
+import inspect
 import shutil
 
 
+class StepProgress:
+    """
+    Turns the pipeline's progress callbacks into diffusion step counts.
+
+    Accepts both fraction-style calls (``progress(0.5, desc=...)``) and
+    per-step callbacks (``callback(step, timestep, latents)``). Steps only
+    move forward, and each is reported once.
+    """
+
+    def __init__(self, total_steps: int, on_step: Callable[[int, int], None]):
+        self.total_steps = max(int(total_steps), 1)
+        self.on_step = on_step
+        self.last_step = 0
+
+    def __call__(self, value=None, *args, **kwargs):
+        if isinstance(value, float) and 0.0 <= value <= 1.0:
+            step = round(value * self.total_steps)
+        elif isinstance(value, int):
+            # Diffusers callbacks count from 0
+            step = value + 1
+        else:
+            step = self.last_step + 1
+        self._report(min(step, self.total_steps))
+
+    def finish(self):
+        """Report the last step if the pipeline never got there."""
+        self._report(self.total_steps)
+
+    def _report(self, step: int):
+        if step > self.last_step:
+            self.last_step = step
+            self.on_step(step, self.total_steps)
+
+
 class AceStepProcessor:
     """ACE-Step 1.5 processor for Nueva."""
 
@@ -183,6 +218,7 @@ class AceStepProcessor:
         output_path: Path,
         prompt: Optional[str] = None,
         params: Optional[dict] = None,
+        on_step: Optional[Callable[[int, int], None]] = None,
     ) -> dict:
         """
         Process audio through ACE-Step.
@@ -192,12 +228,17 @@ class AceStepProcessor:
             output_path: Path where output should be written
             prompt: Text description of desired transformation
             params: Additional parameters (mode, intensity, etc.)
+            on_step: Called with (step, total_steps) as diffusion progresses
 
         Returns:
             dict with processing results
         """
         if not self._installed:
-            return self._process_via_api(input_path, output_path, prompt, params)
+            result = self._process_via_api(input_path, output_path, prompt, params)
+            if on_step:
+                steps = {**self.DEFAULT_PARAMS, **(params or {})}["inference_steps"]
+                StepProgress(steps, on_step).finish()
+            return result
 
         # Merge with defaults
         params = {**self.DEFAULT_PARAMS, **(params or {})}
@@ -213,6 +254,7 @@ class AceStepProcessor:
         preserve_melody = params.get("preserve_melody", True)
         inference_steps = params.get("inference_steps", 8)
         guidance_scale = params.get("guidance_scale", 3.0)
+        seed = params.get("seed")
 
         # Map our modes to ACE-Step task_types
         task_type_map = {
@@ -231,6 +273,21 @@ class AceStepProcessor:
                 self._pipeline.initialize_service()
                 self._pipeline._initialized = True
 
+            # Seed torch so the same seed reproduces the same output
+            if seed is not None:
+                import torch
+                torch.manual_seed(seed)
+
+            # Hook the pipeline's progress reporting, if it has any
+            progress = StepProgress(inference_steps, on_step) if on_step else None
+            progress_kwargs = {}
+            if progress:
+                accepted = inspect.signature(self._pipeline.generate_music).parameters
+                for name in ("progress", "callback"):
+                    if name in accepted:
+                        progress_kwargs[name] = progress
+                        break
+
             # Run ACE-Step processing
             result = self._pipeline.generate_music(
                 captions=prompt,
@@ -240,7 +297,10 @@ class AceStepProcessor:
                 inference_steps=inference_steps,
                 guidance_scale=guidance_scale,
                 audio_cover_strength=intensity,
+                **progress_kwargs,
             )
+            if progress:
+                progress.finish()
 
             # Save output audio
             if result and "audio" in result:
@@ -261,6 +321,7 @@ class AceStepProcessor:
                     "intensity": intensity,
                     "preserve_melody": preserve_melody,
                     "inference_steps": inference_steps,
+                    "seed": seed,
                 },
             }
 
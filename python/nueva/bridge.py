@@ -189,12 +189,16 @@ class AIBridge:
         model = self.models[model_id]
         start_time = time.time()
 
+        def on_step(step: int, total_steps: int):
+            emit_progress(request.request_id, step, total_steps)
+
         try:
             result = model.process(
                 input_path=Path(request.input_path),
                 output_path=Path(request.output_path),
                 prompt=request.prompt,
                 params=request.model_params,
+                on_step=on_step,
             )
 
             processing_time_ms = int((time.time() - start_time) * 1000)
@@ -229,6 +233,14 @@ class AIBridge:
         )
 
 
+def emit_progress(request_id: Optional[str], step: int, total_steps: int):
+    """Write a progress line for a diffusion step, ahead of the final response."""
+    update = {"step": step, "total_steps": total_steps}
+    if request_id:
+        update["request_id"] = request_id
+    print(json.dumps(update), flush=True)
+
+
 def main():
     """Main entry point for bridge - reads JSON from stdin, writes to stdout."""
     bridge = AIBridge()
@@ -0,0 +1,82 @@
+"""Tests for the AI bridge's line protocol."""
+
+import io
+import json
+import sys
+import unittest
+from pathlib import Path
+from unittest import mock
+
+sys.path.insert(0, str(Path(__file__).resolve().parents[1]))
+
+from nueva import bridge  # noqa: E402
+from nueva.ace_step import StepProgress  # noqa: E402
+
+
+class StagedModel:
+    """Fake model that reports each of its diffusion steps."""
+
+    def __init__(self, steps: int):
+        self.steps = steps
+
+    def process(self, input_path, output_path, prompt=None, params=None, on_step=None):
+        for step in range(1, self.steps + 1):
+            on_step(step, self.steps)
+        return {"message": "done"}
+
+
+def run_bridge(requests, models):
+    """Feed request lines to the bridge and return the lines it wrote."""
+    stdin = io.StringIO("".join(json.dumps(r) + "\n" for r in requests))
+    stdout = io.StringIO()
+    load_models = lambda self: self.models.update(models)  # noqa: E731
+    with mock.patch.object(bridge.AIBridge, "_load_models", load_models), \
+            mock.patch.object(sys, "stdin", stdin), \
+            mock.patch.object(sys, "stdout", stdout):
+        bridge.main()
+    return [json.loads(line) for line in stdout.getvalue().splitlines()]
+
+
+class BridgeProgressTest(unittest.TestCase):
+    def test_process_emits_step_lines_before_response(self):
+        lines = run_bridge(
+            [{
+                "action": "process",
+                "request_id": "r1",
+                "model": "staged",
+                "input_path": "in.wav",
+                "output_path": "out.wav",
+            }],
+            {"staged": StagedModel(3)},
+        )
+
+        self.assertEqual(
+            lines[:3],
+            [{"request_id": "r1", "step": s, "total_steps": 3} for s in (1, 2, 3)],
+        )
+        self.assertEqual(len(lines), 4)
+        self.assertTrue(lines[3]["success"])
+        self.assertEqual(lines[3]["request_id"], "r1")
+
+    def test_ping_has_no_progress(self):
+        lines = run_bridge([{"action": "ping", "request_id": "p"}], {})
+        self.assertEqual(lines, [{"success": True, "request_id": "p", "message": "pong"}])
+
+
+class StepProgressTest(unittest.TestCase):
+    def test_steps_are_monotonic_and_reported_once(self):
+        steps = []
+        progress = StepProgress(4, lambda step, total: steps.append((step, total)))
+
+        progress(0.25, desc="denoising")
+        progress(0.25)
+        progress(0.1)
+        progress(2)
+        progress.finish()
+        progress.finish()
+
+        self.assertEqual(steps, [(1, 4), (3, 4), (4, 4)])
+
+
+if __name__ == "__main__":
+    unittest.main()
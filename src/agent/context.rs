@@ -8,7 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-use super::decision::ToolType;
+use super::decision::{confidence, ToolDecision, ToolType};
+use super::intent::{EditIntent, Intent};
 
 /// A message in the conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -191,6 +192,9 @@ pub struct ParameterChange {
     pub new_value: serde_json::Value,
 }
 
+/// Default number of messages kept when a conversation is persisted
+pub const DEFAULT_MAX_HISTORY_MESSAGES: usize = 100;
+
 /// User preferences learned from conversation
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserPreferences {
@@ -203,11 +207,79 @@ pub struct UserPreferences {
     /// Typical genre
     pub typical_genre: Option<String>,
 
+    /// Confidence below which the agent asks a clarifying question
+    /// instead of guessing (defaults to `confidence::ASK_CLARIFICATION`)
+    pub clarification_threshold: Option<f32>,
+
+    /// Maximum number of messages kept when the conversation is persisted
+    /// (defaults to `DEFAULT_MAX_HISTORY_MESSAGES`)
+    pub max_history_messages: Option<usize>,
+
+    /// Append a limiter when a planned chain would clip
+    #[serde(default)]
+    pub safe_mode: bool,
+
     /// Custom preferences
     #[serde(flatten)]
     pub custom: HashMap<String, serde_json::Value>,
 }
 
+impl UserPreferences {
+    /// Effective clarification threshold, falling back to the spec default
+    pub fn clarification_threshold(&self) -> f32 {
+        self.clarification_threshold
+            .unwrap_or(confidence::ASK_CLARIFICATION)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Effective history limit for persisted conversations
+    pub fn max_history_messages(&self) -> usize {
+        self.max_history_messages
+            .unwrap_or(DEFAULT_MAX_HISTORY_MESSAGES)
+    }
+}
+
+/// A clarifying question the agent is waiting on the user to answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingClarification {
+    /// The prompt that was too vague to act on
+    pub original_prompt: String,
+
+    /// The question that was asked
+    pub question: String,
+
+    /// Suggested answers, each usable as a prompt on its own
+    pub options: Vec<String>,
+}
+
+impl PendingClarification {
+    /// Turn the user's answer into a prompt the agent can act on.
+    ///
+    /// Accepts an option number ("2"), an option's text (or part of it),
+    /// or free text, which is appended to the original prompt.
+    pub fn resolve(&self, answer: &str) -> String {
+        let answer_lower = answer.trim().to_lowercase();
+
+        if let Ok(n) = answer_lower.parse::<usize>() {
+            if let Some(option) = n.checked_sub(1).and_then(|i| self.options.get(i)) {
+                return option.clone();
+            }
+        }
+
+        if !answer_lower.is_empty() {
+            if let Some(option) = self
+                .options
+                .iter()
+                .find(|o| o.to_lowercase().contains(&answer_lower))
+            {
+                return option.clone();
+            }
+        }
+
+        format!("{} {}", self.original_prompt, answer.trim())
+    }
+}
+
 /// Full conversation context
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationContext {
@@ -226,6 +298,15 @@ pub struct ConversationContext {
     /// Current effect focus (which effect we're talking about)
     pub effect_focus: Option<EffectFocus>,
 
+    /// Clarifying question awaiting an answer (if any)
+    #[serde(default)]
+    pub pending_clarification: Option<PendingClarification>,
+
+    /// DSP approximation proposed in place of neural processing, applied
+    /// only if the user accepts it
+    #[serde(default)]
+    pub pending_approximation: Option<ToolDecision>,
+
     /// Message index counter
     message_index: usize,
 }
@@ -238,6 +319,8 @@ impl ConversationContext {
             recent_actions: Vec::new(),
             user_preferences: UserPreferences::default(),
             effect_focus: None,
+            pending_clarification: None,
+            pending_approximation: None,
             message_index: 0,
         }
     }
@@ -305,11 +388,32 @@ impl ConversationContext {
         self.message_index
     }
 
+    /// Take the pending clarification, if the agent asked one
+    pub fn take_pending_clarification(&mut self) -> Option<PendingClarification> {
+        self.pending_clarification.take()
+    }
+
+    /// Drop the oldest messages and actions beyond `max_len` each.
+    ///
+    /// The message index keeps counting so effect focus stays consistent.
+    pub fn trim_history(&mut self, max_len: usize) {
+        if self.messages.len() > max_len {
+            let excess = self.messages.len() - max_len;
+            self.messages.drain(..excess);
+        }
+        if self.recent_actions.len() > max_len {
+            let excess = self.recent_actions.len() - max_len;
+            self.recent_actions.drain(..excess);
+        }
+    }
+
     /// Clear conversation but keep preferences
     pub fn clear_conversation(&mut self) {
         self.messages.clear();
         self.recent_actions.clear();
         self.effect_focus = None;
+        self.pending_clarification = None;
+        self.pending_approximation = None;
         self.message_index = 0;
     }
 }
@@ -352,27 +456,31 @@ impl EffectFocus {
             "boost",
         ];
 
+        if self.effect_type == effect_type && Intent::analyze(prompt).edit == EditIntent::Remove {
+            return ModifyOrAdd::Remove;
+        }
+
         let prompt_lower = prompt.to_lowercase();
 
         // If same effect type and has modification signals
-        if self.effect_type == effect_type {
-            if MODIFICATION_SIGNALS
+        if self.effect_type == effect_type
+            && MODIFICATION_SIGNALS
                 .iter()
                 .any(|sig| prompt_lower.contains(sig))
-            {
-                return ModifyOrAdd::Modify;
-            }
+        {
+            return ModifyOrAdd::Modify;
         }
 
         ModifyOrAdd::Add
     }
 }
 
-/// Whether to modify existing effect or add new
+/// Whether to modify existing effect, add new, or take it out
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ModifyOrAdd {
     Modify,
     Add,
+    Remove,
 }
 
 #[cfg(test)]
@@ -444,6 +552,27 @@ mod tests {
             focus.should_modify_vs_add("add some reverb", "reverb"),
             ModifyOrAdd::Add
         );
+
+        // Same type, asked to take it out
+        assert_eq!(
+            focus.should_modify_vs_add("get rid of the compressor", "compressor"),
+            ModifyOrAdd::Remove
+        );
+    }
+
+    #[test]
+    fn test_pending_clarification_resolve() {
+        let pending = PendingClarification {
+            original_prompt: "make it better".to_string(),
+            question: "What would you like to change?".to_string(),
+            options: vec!["make it louder".to_string(), "make it brighter".to_string()],
+        };
+
+        assert_eq!(pending.resolve("2"), "make it brighter");
+        assert_eq!(pending.resolve("Louder"), "make it louder");
+        assert_eq!(pending.resolve("more punch"), "make it better more punch");
+        // Out-of-range numbers fall through to free text
+        assert_eq!(pending.resolve("7"), "make it better 7");
     }
 
     #[test]
@@ -2,9 +2,19 @@
 //!
 //! Implements §5.7 and §6 from the spec.
 
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
+use super::context::{ConversationContext, PendingClarification, UserPreferences};
 use super::intent::Intent;
+use super::safety::{SafetyChecker, SafetyRecommendation};
+use crate::dsp;
+use crate::error::{NuevaError, Result};
+use crate::neural::{AceStepMode, NeuralContextTracker, NeuralModelInfo, NeuralModelParams};
 
 /// Type of tool the agent can select
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -20,6 +30,393 @@ pub enum ToolType {
     AskClarification,
 }
 
+impl ToolType {
+    /// Short name for messages
+    pub fn label(self) -> &'static str {
+        match self {
+            ToolType::Dsp => "DSP",
+            ToolType::Neural => "Neural",
+            ToolType::Both => "DSP + Neural",
+            ToolType::AskClarification => "Clarification",
+        }
+    }
+}
+
+/// Neural processing mode requested with `process --mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessingMode {
+    /// Apply a neural transform to the whole track
+    #[default]
+    Transform,
+    /// Regenerate in a new style while preserving the structure
+    Cover,
+    /// Regenerate only a region of the track
+    Repaint,
+    /// Separate stems/sources
+    Extract,
+}
+
+impl ProcessingMode {
+    /// Every mode, in CLI order
+    pub const ALL: [ProcessingMode; 4] = [
+        ProcessingMode::Transform,
+        ProcessingMode::Cover,
+        ProcessingMode::Repaint,
+        ProcessingMode::Extract,
+    ];
+
+    /// Name used on the command line and in model params
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessingMode::Transform => "transform",
+            ProcessingMode::Cover => "cover",
+            ProcessingMode::Repaint => "repaint",
+            ProcessingMode::Extract => "extract",
+        }
+    }
+
+    /// What the mode does, for messages
+    pub fn description(self) -> &'static str {
+        match self {
+            ProcessingMode::Transform => "transform the whole track",
+            ProcessingMode::Cover => "regenerate in a new style, keeping the structure",
+            ProcessingMode::Repaint => "regenerate a selected region",
+            ProcessingMode::Extract => "separate the track into stems",
+        }
+    }
+
+    /// Model capabilities (any one of them) that provide this mode
+    pub fn capabilities(self) -> &'static [&'static str] {
+        match self {
+            ProcessingMode::Transform => &["text_to_music", "style_change"],
+            ProcessingMode::Cover => &["cover"],
+            ProcessingMode::Repaint => &["repaint"],
+            ProcessingMode::Extract => &["track_extraction", "source_separation"],
+        }
+    }
+
+    /// The capability `info` provides this mode through, if any
+    pub fn capability_in(self, info: &NeuralModelInfo) -> Option<&'static str> {
+        self.capabilities()
+            .iter()
+            .copied()
+            .find(|cap| info.capabilities.iter().any(|c| c == cap))
+    }
+
+    /// Modes a model can run
+    pub fn supported_by(info: &NeuralModelInfo) -> Vec<ProcessingMode> {
+        Self::ALL
+            .into_iter()
+            .filter(|mode| mode.capability_in(info).is_some())
+            .collect()
+    }
+
+    /// The ACE-Step mode that implements this one
+    pub fn ace_step_mode(self) -> AceStepMode {
+        match self {
+            ProcessingMode::Transform => AceStepMode::Transform,
+            ProcessingMode::Cover => AceStepMode::Cover,
+            ProcessingMode::Repaint => AceStepMode::Repaint,
+            ProcessingMode::Extract => AceStepMode::Extract,
+        }
+    }
+
+    /// Route an explicit processing mode to the model operation that runs it
+    ///
+    /// Fails with a message listing what the model can do when it has no
+    /// capability for the mode.
+    pub fn route(self, model: &NeuralModelInfo, prompt: &str, intensity: f32) -> Result<ModeRoute> {
+        let capability = self.capability_in(model).ok_or_else(|| {
+            let supported: Vec<&str> = ProcessingMode::supported_by(model)
+                .into_iter()
+                .map(ProcessingMode::as_str)
+                .collect();
+            NuevaError::InvalidParameter {
+                param: "mode".to_string(),
+                value: self.to_string(),
+                expected: if supported.is_empty() {
+                    format!("{} ({}) supports no processing modes", model.name, model.id)
+                } else {
+                    format!(
+                        "a mode {} ({}) supports: {}",
+                        model.name,
+                        model.id,
+                        supported.join(", ")
+                    )
+                },
+            }
+        })?;
+
+        Ok(ModeRoute {
+            mode: self,
+            model: model.id.clone(),
+            capability,
+            params: NeuralModelParams::new()
+                .with_param("mode", self.ace_step_mode().to_string())
+                .with_param("prompt", prompt)
+                .with_param("intensity", intensity),
+        })
+    }
+}
+
+impl fmt::Display for ProcessingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProcessingMode {
+    type Err = NuevaError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| NuevaError::InvalidParameter {
+                param: "mode".to_string(),
+                value: s.to_string(),
+                expected: "transform, cover, repaint or extract".to_string(),
+            })
+    }
+}
+
+/// A processing mode routed to the model operation that runs it
+#[derive(Debug, Clone)]
+pub struct ModeRoute {
+    /// Requested mode
+    pub mode: ProcessingMode,
+
+    /// Model that runs it
+    pub model: String,
+
+    /// Model capability the mode maps to
+    pub capability: &'static str,
+
+    /// Parameters to pass to the model
+    pub params: NeuralModelParams,
+}
+
+/// Neural intents DSP can approximate: intent, prompt keywords, whether
+/// the keywords name something to take away, and what the approximation
+/// does.
+///
+/// Keywords match whole words. One preceded by a reducing word ("less
+/// bright", "remove the hiss") only matches an entry that takes it away,
+/// and one that isn't only matches an entry that adds it, so "add tape
+/// noise" isn't a denoise. Checked in order, so "lo-fi" claims "vintage
+/// recording" before "warmth" sees "vintage".
+const DSP_APPROXIMATIONS: &[(&str, &[&str], bool, &str)] = &[
+    (
+        "denoise",
+        &["denoise", "noise reduction", "noise removal"],
+        false,
+        DENOISE_DESCRIPTION,
+    ),
+    (
+        "denoise",
+        &["noise", "noisy", "hiss", "hissy"],
+        true,
+        DENOISE_DESCRIPTION,
+    ),
+    (
+        "lo-fi",
+        &[
+            "old recording",
+            "vintage recording",
+            "lo-fi",
+            "lofi",
+            "telephone",
+            "radio",
+        ],
+        false,
+        "a band-limiting EQ and tube saturation",
+    ),
+    (
+        "warmth",
+        &["warm", "warmer", "warmth", "vintage", "analog", "tape"],
+        false,
+        "tube saturation and a low shelf boost",
+    ),
+    (
+        "brighten",
+        &[
+            "bright",
+            "brighten",
+            "brighter",
+            "brightness",
+            "air",
+            "crisp",
+            "crisper",
+            "sparkle",
+        ],
+        false,
+        "a high shelf boost",
+    ),
+];
+
+const DENOISE_DESCRIPTION: &str =
+    "a gate to duck noise between phrases and an EQ that trims rumble and hiss";
+
+/// Words that turn the next few words into something to take away
+const REDUCING_WORDS: &[&str] = &[
+    "less",
+    "fewer",
+    "lower",
+    "remove",
+    "reduce",
+    "cut",
+    "tame",
+    "strip",
+    "eliminate",
+    "kill",
+    "clean",
+    "rid",
+    "lose",
+    "no",
+    "without",
+    "too",
+];
+
+/// Whether `words` contain the (possibly multi-word) `keyword` with the
+/// given reading: taken away when `reduced`, added otherwise
+fn mentions(words: &[&str], keyword: &str, reduced: bool) -> bool {
+    let phrase: Vec<&str> = keyword.split(' ').collect();
+    words
+        .windows(phrase.len())
+        .enumerate()
+        .any(|(i, window)| window == phrase.as_slice() && is_reduced(&words[..i]) == reduced)
+}
+
+/// Whether one of the three words before a keyword reduces it, looking no
+/// further back than the start of its clause
+fn is_reduced(before: &[&str]) -> bool {
+    before
+        .iter()
+        .rev()
+        .take(3)
+        .take_while(|w| !matches!(**w, "and" | "but" | "then"))
+        .any(|w| REDUCING_WORDS.contains(w))
+}
+
+/// A DSP stand-in for a neural request, used when no model can run
+///
+/// Only an approximation: a gate and EQ are not a denoiser, and
+/// saturation is not a style model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DspApproximation {
+    /// Neural intent it stands in for ("denoise", "lo-fi", "warmth",
+    /// "brighten")
+    pub intent: String,
+
+    /// What the approximating chain does
+    pub description: String,
+}
+
+impl DspApproximation {
+    /// The approximation for a prompt, or `None` if its intent has no DSP
+    /// analogue (style transfer, restoration, stem separation, ...)
+    pub fn for_prompt(prompt: &str) -> Option<Self> {
+        let prompt = prompt.to_lowercase();
+        let words: Vec<&str> = prompt
+            .split(|c: char| !c.is_alphanumeric() && c != '-')
+            .filter(|w| !w.is_empty())
+            .collect();
+        DSP_APPROXIMATIONS
+            .iter()
+            .find(|(_, keywords, removes, _)| {
+                keywords.iter().any(|k| mentions(&words, k, *removes))
+            })
+            .map(|&(intent, _, _, description)| Self {
+                intent: intent.to_string(),
+                description: description.to_string(),
+            })
+    }
+
+    /// Build the approximating chain, in default order
+    pub fn build_chain(&self) -> Result<dsp::EffectChain> {
+        use dsp::{EQBand, Effect, Gate, ParametricEQ, Saturation, SaturationType};
+        use std::f32::consts::FRAC_1_SQRT_2;
+
+        let effects: Vec<Box<dyn Effect>> = match self.intent.as_str() {
+            "denoise" => {
+                let mut gate = Gate::new();
+                gate.set_threshold_db(-50.0)?;
+                gate.set_range_db(-20.0)?;
+                vec![
+                    Box::new(gate),
+                    Box::new(ParametricEQ::with_bands(vec![
+                        EQBand::high_pass(80.0, FRAC_1_SQRT_2),
+                        EQBand::high_shelf(8000.0, -4.0, FRAC_1_SQRT_2),
+                    ])?),
+                ]
+            }
+            "lo-fi" => vec![
+                Box::new(ParametricEQ::with_bands(vec![
+                    EQBand::high_pass(300.0, FRAC_1_SQRT_2),
+                    EQBand::low_pass(3500.0, FRAC_1_SQRT_2),
+                ])?),
+                Box::new(Saturation::with_params(
+                    0.4,
+                    SaturationType::Tube,
+                    0.6,
+                    -3.0,
+                )?),
+            ],
+            "warmth" => vec![
+                Box::new(ParametricEQ::with_bands(vec![EQBand::low_shelf(
+                    200.0,
+                    3.0,
+                    FRAC_1_SQRT_2,
+                )])?),
+                Box::new(Saturation::with_params(
+                    0.3,
+                    SaturationType::Tube,
+                    0.5,
+                    -1.5,
+                )?),
+            ],
+            "brighten" => vec![Box::new(ParametricEQ::with_bands(vec![
+                EQBand::high_shelf(6000.0, 4.0, FRAC_1_SQRT_2),
+            ])?)],
+            other => {
+                return Err(NuevaError::InvalidParameter {
+                    param: "intent".to_string(),
+                    value: other.to_string(),
+                    expected: "denoise, lo-fi, warmth or brighten".to_string(),
+                })
+            }
+        };
+
+        let mut chain = dsp::EffectChain::new();
+        for (i, mut effect) in effects.into_iter().enumerate() {
+            effect.set_id(format!("{}-{}", effect.effect_type(), i + 1));
+            chain.add_effect_ordered(effect);
+        }
+        Ok(chain)
+    }
+}
+
+/// Why neural processing can't run here
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cause", content = "reason", rename_all = "snake_case")]
+pub enum NeuralFallback {
+    /// The hardware can't run the model (see
+    /// [`can_run_ace_step`](crate::neural::can_run_ace_step))
+    Hardware(String),
+    /// The model isn't installed
+    NotInstalled(String),
+}
+
+impl NeuralFallback {
+    /// The detail behind the fallback, e.g. "No compatible GPU detected"
+    pub fn reason(&self) -> &str {
+        match self {
+            NeuralFallback::Hardware(reason) | NeuralFallback::NotInstalled(reason) => reason,
+        }
+    }
+}
+
 /// Result of tool decision
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolDecision {
@@ -37,6 +434,22 @@ pub struct ToolDecision {
 
     /// Whether to ask for clarification
     pub ask_clarification: bool,
+
+    /// Explicit neural processing mode, if one was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ProcessingMode>,
+
+    /// What each candidate tool scored on its own for the prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scores: Vec<(ToolType, f32)>,
+
+    /// Why neural processing was swapped for DSP, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub neural_fallback: Option<NeuralFallback>,
+
+    /// DSP stand-in for the neural request, when falling back to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approximation: Option<DspApproximation>,
 }
 
 impl ToolDecision {
@@ -47,6 +460,10 @@ impl ToolDecision {
             recommendations: Vec::new(),
             reasoning: String::new(),
             ask_clarification: false,
+            mode: None,
+            scores: Vec::new(),
+            neural_fallback: None,
+            approximation: None,
         }
     }
 
@@ -64,36 +481,109 @@ impl ToolDecision {
         self.ask_clarification = true;
         self
     }
+
+    pub fn with_mode(mut self, mode: ProcessingMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn with_scores(mut self, scores: Vec<(ToolType, f32)>) -> Self {
+        self.scores = scores;
+        self
+    }
+
+    /// Score of `tool` among the candidates (0 if it wasn't one)
+    pub fn score(&self, tool: ToolType) -> f32 {
+        self.scores
+            .iter()
+            .find(|(t, _)| *t == tool)
+            .map_or(0.0, |&(_, score)| score)
+    }
 }
 
 /// Agent response to a user request
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AgentResponse {
-    /// What action was taken
-    pub action: AgentAction,
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AgentResponse {
+    /// High confidence - executed automatically
+    Executed {
+        message: String,
+        decision: ToolDecision,
+        /// Changes that were made
+        changes: Vec<String>,
+    },
+    /// Medium confidence - proposing changes
+    Propose {
+        message: String,
+        decision: ToolDecision,
+    },
+    /// Low confidence - asking a clarifying question instead of guessing
+    NeedsClarification {
+        question: String,
+        /// Suggested answers, each usable as a prompt on its own
+        options: Vec<String>,
+    },
+    /// Very low confidence - admitting uncertainty
+    Uncertain {
+        message: String,
+        decision: ToolDecision,
+    },
+}
 
+impl AgentResponse {
     /// Message to show the user
-    pub message: String,
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Executed { message, .. }
+            | Self::Propose { message, .. }
+            | Self::Uncertain { message, .. } => message,
+            Self::NeedsClarification { question, .. } => question,
+        }
+    }
 
-    /// Decision details
-    pub decision: Option<ToolDecision>,
+    /// Decision details (not present when asking for clarification)
+    pub fn decision(&self) -> Option<&ToolDecision> {
+        match self {
+            Self::Executed { decision, .. }
+            | Self::Propose { decision, .. }
+            | Self::Uncertain { decision, .. } => Some(decision),
+            Self::NeedsClarification { .. } => None,
+        }
+    }
 
-    /// Changes that were made (for "executed" action)
-    pub changes: Vec<String>,
-}
+    /// Neural processing mode the response acts on, if any
+    pub fn mode(&self) -> Option<ProcessingMode> {
+        self.decision().and_then(|d| d.mode)
+    }
 
-/// Type of action the agent took
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum AgentAction {
-    /// High confidence - executed automatically
-    Executed,
-    /// Medium confidence - proposing changes
-    Propose,
-    /// Low confidence - asking for clarification
-    Clarify,
-    /// Very low confidence - admitting uncertainty
-    Uncertain,
+    /// DSP approximation standing in for neural processing, if any
+    pub fn approximation(&self) -> Option<&DspApproximation> {
+        self.decision().and_then(|d| d.approximation.as_ref())
+    }
+
+    /// Tell the user about something done on top of the response
+    ///
+    /// The note is appended to the message, and to the changes of an
+    /// executed response.
+    pub fn add_note(&mut self, note: &str) {
+        match self {
+            Self::Executed {
+                message, changes, ..
+            } => {
+                message.push(' ');
+                message.push_str(note);
+                changes.push(note.to_string());
+            }
+            Self::Propose { message, .. }
+            | Self::Uncertain { message, .. }
+            | Self::NeedsClarification {
+                question: message, ..
+            } => {
+                message.push(' ');
+                message.push_str(note);
+            }
+        }
+    }
 }
 
 /// Confidence thresholds per spec §6.3
@@ -108,14 +598,254 @@ pub mod confidence {
     pub const REFUSE_GRACEFULLY: f32 = 0.20;
 }
 
+/// What a tool scores for a prompt, by the rule that applies
+mod score {
+    /// The user named the tool
+    pub const EXPLICIT: f32 = 0.95;
+    /// Only a neural model can do it
+    pub const REQUIRES_NEURAL: f32 = 0.85;
+    /// A standard mixing task DSP handles
+    pub const DSP_HANDLES: f32 = 0.80;
+    /// Complex enough to need both tools
+    pub const COMPLEX: f32 = 0.70;
+    /// DSP when nothing more specific applies
+    pub const DSP_DEFAULT: f32 = 0.50;
+    /// Too vague to act on
+    pub const CLARIFY: f32 = 0.20;
+}
+
+/// Map a prompt intensity (0.0 - 1.0) to parameters for a DSP effect
+///
+/// Returns `None` for effects without an intensity curve. Every curve is
+/// linear in the (clamped) intensity, so it is continuous and monotonic;
+/// 0.0 is a near-null setting and 1.0 strong but safe, raising the peak of
+/// typical material by no more than a few dB with no makeup gain:
+///
+/// - reverb: `wet_level` 0 → 0.08, `room_size` 0.3 → 0.8, `dry_level`
+///   1.0 → 0.7 (the reverb's wet path has a lot of gain on sustained
+///   material; the dry dip leaves headroom for it)
+/// - delay: `wet_level` 0 → 0.35, `feedback` 0 → 0.45, `dry_level`
+///   1.0 → 0.7
+/// - saturation: `drive` 0 → 0.8, `mix` 0 → 0.6, `outputGain` 0 → -3 dB
+/// - compressor: `ratio` 1:1 → 6:1, `threshold_db` -6 → -30 dB
+///
+/// Parameter names match what `dsp::build_effect` expects.
+pub fn intensity_params(effect_type: &str, intensity: f32) -> Option<HashMap<String, Value>> {
+    let i = if intensity.is_nan() {
+        0.0
+    } else {
+        intensity.clamp(0.0, 1.0)
+    };
+    let params = match effect_type {
+        "reverb" => vec![
+            ("wet_level", json!(0.08 * i)),
+            ("room_size", json!(0.3 + 0.5 * i)),
+            ("dry_level", json!(1.0 - 0.3 * i)),
+        ],
+        "delay" | "echo" => vec![
+            ("wet_level", json!(0.35 * i)),
+            ("feedback", json!(0.45 * i)),
+            ("dry_level", json!(1.0 - 0.3 * i)),
+        ],
+        "saturation" | "distortion" => vec![
+            ("drive", json!(0.8 * i)),
+            ("mix", json!(0.6 * i)),
+            ("outputGain", json!(-3.0 * i)),
+        ],
+        "compressor" | "compression" => vec![
+            ("ratio", json!(1.0 + 5.0 * i)),
+            ("threshold_db", json!(-6.0 - 24.0 * i)),
+        ],
+        _ => return None,
+    };
+    Some(
+        params
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    )
+}
+
+/// Whether a prompt accepts the agent's proposal
+fn is_confirmation(prompt: &str) -> bool {
+    let answer = prompt.trim().trim_end_matches(['.', '!']).to_lowercase();
+    matches!(
+        answer.as_str(),
+        "y" | "yes"
+            | "yeah"
+            | "yep"
+            | "sure"
+            | "ok"
+            | "okay"
+            | "go ahead"
+            | "do it"
+            | "sounds good"
+    )
+}
+
+/// Suggested answers offered when a prompt is too vague to act on
+const CLARIFICATION_OPTIONS: &[&str] = &[
+    "make it louder",
+    "make it brighter",
+    "make it punchier with compression",
+    "add reverb for more space",
+    "remove noise",
+];
+
 /// The AI Agent for audio processing decisions
 pub struct Agent {
-    // Future: conversation context, user preferences, etc.
+    /// Confidence below which the agent asks instead of guessing
+    clarification_threshold: f32,
+    /// Whether planned chains are guarded against clipping
+    safe_mode: bool,
+    /// Why neural processing can't run here, if it can't
+    neural_unavailable: Option<NeuralFallback>,
+    /// What neural processing left in the audio on purpose
+    neural_context: NeuralContextTracker,
 }
 
 impl Agent {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            clarification_threshold: confidence::ASK_CLARIFICATION,
+            safe_mode: false,
+            neural_unavailable: None,
+            neural_context: NeuralContextTracker::new(),
+        }
+    }
+
+    /// Create an agent that honours the user's preferences
+    pub fn with_preferences(preferences: &UserPreferences) -> Self {
+        Self {
+            clarification_threshold: preferences.clarification_threshold(),
+            safe_mode: preferences.safe_mode,
+            neural_unavailable: None,
+            neural_context: NeuralContextTracker::new(),
+        }
+    }
+
+    /// Record why neural models can't run, if they can't
+    ///
+    /// Decisions that would use neural processing then fall back to DSP
+    /// and say why.
+    pub fn with_neural_fallback(mut self, fallback: Option<NeuralFallback>) -> Self {
+        self.neural_unavailable = fallback;
+        self
+    }
+
+    /// Consult the intentional artifacts of the project's Layer 1 in
+    /// safety checks, so e.g. a requested saturation isn't limited away
+    pub fn with_neural_context(mut self, context: &NeuralContextTracker) -> Self {
+        self.neural_context = context.clone();
+        self
+    }
+
+    /// Confidence below which the agent asks a clarifying question
+    pub fn clarification_threshold(&self) -> f32 {
+        self.clarification_threshold
+    }
+
+    /// Whether planned chains are guarded against clipping
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Check a planned chain in safe mode
+    ///
+    /// Dry-runs `chain` over `input` (see
+    /// [`SafetyChecker::check_chain_headroom`]). If the output would clip,
+    /// returns the recommendation whose mitigation is the limiter to
+    /// append, and explains the limiter in `response`. Does nothing with
+    /// safe mode off.
+    pub fn safe_mode_limiter(
+        &self,
+        chain: &dsp::EffectChain,
+        input: &dsp::AudioBuffer,
+        response: &mut AgentResponse,
+    ) -> Result<Option<SafetyRecommendation>> {
+        if !self.safe_mode {
+            return Ok(None);
+        }
+        let mut checker = SafetyChecker::new();
+        checker.set_neural_context(self.neural_context.clone());
+        let recommendation = checker.check_chain_headroom(chain, input)?;
+        if let Some(rec) = &recommendation {
+            response.add_note(&format!(
+                "Safe mode added a limiter to the end of the chain. Reason: {}. Undo to remove it.",
+                rec.message
+            ));
+        }
+        Ok(recommendation)
+    }
+
+    /// Check a planned chain for DC offset
+    ///
+    /// Dry-runs `chain` over `input` (see
+    /// [`SafetyChecker::check_chain_dc_offset`]). If the output would
+    /// carry an offset, returns the recommendation whose mitigation is the
+    /// high-pass to add. Unlike the safe-mode limiter this always runs: a
+    /// 20 Hz high-pass removes nothing audible.
+    pub fn dc_offset_filter(
+        &self,
+        chain: &dsp::EffectChain,
+        input: &dsp::AudioBuffer,
+    ) -> Result<Option<SafetyRecommendation>> {
+        SafetyChecker::new().check_chain_dc_offset(chain, input)
+    }
+
+    /// Respond to a prompt within a conversation.
+    ///
+    /// If the previous turn asked a clarifying question, the prompt is treated
+    /// as the answer and resolved against the pending question first.
+    ///
+    /// A proposed DSP approximation is only executed once the next prompt
+    /// accepts it ("yes", "go ahead"); any other prompt drops it.
+    pub fn respond(&self, prompt: &str, context: &mut ConversationContext) -> AgentResponse {
+        let accepted = context
+            .pending_approximation
+            .take()
+            .filter(|_| is_confirmation(prompt));
+        if let Some((approx, decision)) = accepted.and_then(|d| Some((d.approximation.clone()?, d)))
+        {
+            context.add_user_message(prompt);
+            let response = AgentResponse::Executed {
+                message: format!(
+                    "Approximating {} with {}.",
+                    approx.intent, approx.description
+                ),
+                changes: vec![approx.description],
+                decision,
+            };
+            context.add_agent_message(response.message());
+            return response;
+        }
+
+        let effective_prompt = match context.take_pending_clarification() {
+            Some(pending) => pending.resolve(prompt),
+            None => prompt.to_string(),
+        };
+
+        context.add_user_message(prompt);
+
+        let decision = self.decide_tool(&effective_prompt);
+        let response = self.handle_decision(&decision);
+
+        match &response {
+            AgentResponse::NeedsClarification { question, options } => {
+                context.pending_clarification = Some(PendingClarification {
+                    original_prompt: effective_prompt,
+                    question: question.clone(),
+                    options: options.clone(),
+                });
+            }
+            AgentResponse::Propose { decision, .. } if decision.approximation.is_some() => {
+                context.pending_approximation = Some(decision.clone());
+            }
+            _ => {}
+        }
+
+        context.add_agent_message(response.message());
+        response
     }
 
     /// Main entry point: decide what tool to use for a prompt
@@ -125,48 +855,207 @@ impl Agent {
     }
 
     /// Decide based on analyzed intent
+    ///
+    /// The decision carries every candidate's score, and falls back to
+    /// DSP when it needs neural processing that can't run.
     pub fn decide_from_intent(&self, intent: &Intent) -> ToolDecision {
-        // Step 1: Check for explicit tool requests
-        if intent.explicit_dsp_request {
-            return ToolDecision::new(ToolType::Dsp, 0.95)
-                .with_reasoning("User explicitly requested DSP tool");
+        let decision = self.select_tool(intent);
+        let scores = decision.scores.clone();
+
+        let Some(fallback) = &self.neural_unavailable else {
+            return decision;
+        };
+        let reason = fallback.reason();
+        if !matches!(decision.tool, ToolType::Neural | ToolType::Both) {
+            return decision;
         }
 
-        if intent.explicit_neural_request {
-            return ToolDecision::new(ToolType::Neural, 0.95)
-                .with_reasoning("User explicitly requested neural/AI processing");
+        let approximation = DspApproximation::for_prompt(&intent.prompt_lower);
+        let mut dsp_decision = match (&approximation, decision.tool) {
+            (Some(approx), _) => ToolDecision::new(ToolType::Dsp, confidence::SUGGEST_FIRST)
+                .with_reasoning(&format!(
+                    "Neural processing is unavailable ({}), so approximating {} with DSP",
+                    reason, approx.intent
+                )),
+            // The DSP half of a mixed request still stands
+            (None, ToolType::Both) => {
+                ToolDecision::new(ToolType::Dsp, decision.score(ToolType::Dsp)).with_reasoning(
+                    &format!(
+                        "Neural processing is unavailable ({}), so falling back to DSP",
+                        reason
+                    ),
+                )
+            }
+            (None, _) => ToolDecision::new(ToolType::Dsp, 0.0).with_reasoning(&format!(
+                "Neural processing is unavailable ({}) and the request has no DSP approximation",
+                reason
+            )),
         }
+        .with_scores(scores);
+        dsp_decision.neural_fallback = Some(fallback.clone());
+        dsp_decision.approximation = approximation;
+        dsp_decision
+    }
 
-        // Step 2: Check for neural-only capabilities
-        if self.requires_neural(intent) {
-            return ToolDecision::new(ToolType::Neural, 0.85).with_reasoning(
-                "Request requires semantic understanding or holistic transformation",
-            );
+    /// What each tool scores on its own for a prompt
+    fn tool_scores(&self, intent: &Intent) -> Vec<(ToolType, f32)> {
+        let dsp = if intent.explicit_dsp_request {
+            score::EXPLICIT
+        } else if self.dsp_can_handle(intent) {
+            score::DSP_HANDLES
+        } else {
+            score::DSP_DEFAULT
+        };
+        let neural = if intent.explicit_neural_request {
+            score::EXPLICIT
+        } else if self.requires_neural(intent) {
+            score::REQUIRES_NEURAL
+        } else {
+            0.0
+        };
+        let both = if intent.is_complex {
+            score::COMPLEX
+        } else {
+            0.0
+        };
+        vec![
+            (ToolType::Dsp, dsp),
+            (ToolType::Neural, neural),
+            (ToolType::Both, both),
+        ]
+    }
+
+    /// Pick a tool: the highest score wins, DSP wins ties, and truly vague
+    /// requests ask for clarification
+    fn select_tool(&self, intent: &Intent) -> ToolDecision {
+        let scores = self.tool_scores(intent);
+        let score_of = |tool| {
+            scores
+                .iter()
+                .find(|&&(t, _)| t == tool)
+                .map_or(0.0, |&(_, score)| score)
+        };
+
+        // Nothing specific matched: vague requests need more details (§6.1)
+        let unmatched =
+            score_of(ToolType::Dsp) == score::DSP_DEFAULT && score_of(ToolType::Neural) == 0.0;
+        if unmatched && self.is_truly_ambiguous(intent) {
+            return ToolDecision::new(ToolType::AskClarification, score::CLARIFY)
+                .with_reasoning("Request is too vague - need more details")
+                .needs_clarification()
+                .with_scores(scores);
         }
 
-        // Step 3: Check if DSP can handle it
-        if self.dsp_can_handle(intent) {
-            return ToolDecision::new(ToolType::Dsp, 0.80)
-                .with_reasoning("Standard mixing task - DSP is fast and tweakable");
+        // DSP is listed first, so it keeps ties
+        let (tool, confidence) =
+            scores
+                .iter()
+                .copied()
+                .fold((ToolType::Dsp, f32::MIN), |best, candidate| {
+                    if candidate.1 > best.1 {
+                        candidate
+                    } else {
+                        best
+                    }
+                });
+        let reasoning = match tool {
+            ToolType::Dsp if confidence == score::EXPLICIT => "User explicitly requested DSP tool",
+            ToolType::Neural if confidence == score::EXPLICIT => {
+                "User explicitly requested neural/AI processing"
+            }
+            ToolType::Neural => {
+                "Request requires semantic understanding or holistic transformation"
+            }
+            ToolType::Dsp if confidence == score::DSP_HANDLES => {
+                "Standard mixing task - DSP is fast and tweakable"
+            }
+            ToolType::Both => "Complex request may benefit from both tools",
+            _ => "Ambiguous request - defaulting to DSP for speed and control",
+        };
+
+        let decision = ToolDecision::new(tool, confidence)
+            .with_reasoning(reasoning)
+            .with_scores(scores);
+        if confidence == score::DSP_DEFAULT {
+            decision.needs_clarification()
+        } else {
+            decision
         }
+    }
 
-        // Step 4: Check for truly vague/ambiguous requests (per spec §6.1)
-        if self.is_truly_ambiguous(intent) {
-            return ToolDecision::new(ToolType::AskClarification, 0.20)
-                .with_reasoning("Request is too vague - need more details")
-                .needs_clarification();
+    /// Explain a decision, including why the other tools weren't chosen
+    ///
+    /// Lists each candidate's score, the deciding factor and, when neural
+    /// processing was unavailable, the DSP fallback it forced.
+    pub fn explain_decision(&self, decision: &ToolDecision) -> String {
+        let mut explanation = format!(
+            "Chose {} ({:.0}% confidence).\n",
+            decision.tool.label(),
+            decision.confidence * 100.0
+        );
+
+        if !decision.scores.is_empty() {
+            explanation.push_str("Candidate scores:\n");
+            for &(tool, score) in &decision.scores {
+                let marker = if tool == decision.tool {
+                    "  <- chosen"
+                } else {
+                    ""
+                };
+                explanation.push_str(&format!(
+                    "  {:<13}{:>4.0}%{}\n",
+                    tool.label(),
+                    score * 100.0,
+                    marker
+                ));
+            }
         }
+        explanation.push_str(&format!("Deciding factor: {}\n", decision.reasoning));
 
-        // Step 5: Complex requests might need both
-        if intent.is_complex {
-            return ToolDecision::new(ToolType::Both, 0.70)
-                .with_reasoning("Complex request may benefit from both tools");
+        let chosen_score = decision.score(decision.tool);
+        for &(tool, score) in &decision.scores {
+            if tool == decision.tool {
+                continue;
+            }
+            let reason = if decision.tool == ToolType::AskClarification {
+                "the request is too vague to act on yet".to_string()
+            } else if decision.neural_fallback.is_some()
+                && matches!(tool, ToolType::Neural | ToolType::Both)
+            {
+                "neural processing is unavailable".to_string()
+            } else if score == 0.0 {
+                match tool {
+                    ToolType::Neural => {
+                        "nothing in the request needs neural processing (style, restoration, denoising)"
+                            .to_string()
+                    }
+                    _ => "the request isn't complex enough to need both".to_string(),
+                }
+            } else if score < chosen_score {
+                format!("scored {:.0}%", score * 100.0)
+            } else {
+                "tied, and DSP is preferred for speed and control".to_string()
+            };
+            explanation.push_str(&format!("Not {}: {}\n", tool.label(), reason));
         }
 
-        // Step 6: Ambiguous - prefer DSP (faster, more control)
-        ToolDecision::new(ToolType::Dsp, 0.50)
-            .with_reasoning("Ambiguous request - defaulting to DSP for speed and control")
-            .needs_clarification()
+        match &decision.neural_fallback {
+            Some(NeuralFallback::Hardware(reason)) => {
+                explanation.push_str(&format!("GPU check forced a DSP fallback: {}\n", reason))
+            }
+            Some(NeuralFallback::NotInstalled(reason)) => explanation.push_str(&format!(
+                "No neural model is installed, so fell back to DSP: {}\n",
+                reason
+            )),
+            None => {}
+        }
+        if let Some(approx) = &decision.approximation {
+            explanation.push_str(&format!(
+                "Approximating {} with {}\n",
+                approx.intent, approx.description
+            ));
+        }
+        explanation
     }
 
     /// Check if the request is truly ambiguous (no clear intent)
@@ -248,18 +1137,85 @@ impl Agent {
             .any(|indicator| prompt_lower.contains(indicator))
     }
 
+    /// Respond to a prompt with an explicit processing mode
+    ///
+    /// Explicit modes skip tool selection: the request goes to `model` as
+    /// a neural operation at `intensity`, or fails if the model can't run
+    /// the mode. Returns the response with the route to run.
+    pub fn respond_to_mode(
+        &self,
+        prompt: &str,
+        mode: ProcessingMode,
+        model: &NeuralModelInfo,
+        intensity: f32,
+    ) -> Result<(AgentResponse, ModeRoute)> {
+        let route = mode.route(model, prompt, intensity)?;
+        let decision = ToolDecision::new(ToolType::Neural, 0.95)
+            .with_mode(mode)
+            .with_recommendations(vec![route.model.clone()])
+            .with_reasoning(&format!(
+                "{} mode: {} ({})",
+                mode,
+                mode.description(),
+                route.capability
+            ));
+
+        let response = AgentResponse::Executed {
+            message: format!("Using {} to {}.", model.name, mode.description()),
+            changes: vec![format!("{} via {}", mode, route.model)],
+            decision,
+        };
+        Ok((response, route))
+    }
+
     /// Handle confidence level and generate appropriate response
     pub fn handle_decision(&self, decision: &ToolDecision) -> AgentResponse {
-        if decision.confidence >= confidence::AUTO_EXECUTE {
-            AgentResponse {
-                action: AgentAction::Executed,
+        if let Some(fallback) = &decision.neural_fallback {
+            if let Some(approx) = &decision.approximation {
+                return AgentResponse::Propose {
+                    message: format!(
+                        "Approximation: no neural model can run here ({}), so I can only approximate \"{}\" with DSP: {}. Should I go ahead?",
+                        fallback.reason(), approx.intent, approx.description
+                    ),
+                    decision: decision.clone(),
+                };
+            }
+            if decision.confidence < confidence::REFUSE_GRACEFULLY {
+                let missing = match fallback {
+                    NeuralFallback::Hardware(_) => "GPU neural processing, which isn't available",
+                    NeuralFallback::NotInstalled(_) => "a neural model, which isn't installed",
+                };
+                return AgentResponse::Uncertain {
+                    message: format!(
+                        "This request requires {} ({}), and DSP has nothing close to it.",
+                        missing,
+                        fallback.reason()
+                    ),
+                    decision: decision.clone(),
+                };
+            }
+        }
+
+        if decision.confidence < confidence::REFUSE_GRACEFULLY {
+            AgentResponse::Uncertain {
+                message: "I'm not quite sure what you're looking for. Could you describe what you want to achieve in different words?".to_string(),
+                decision: decision.clone(),
+            }
+        } else if decision.tool == ToolType::AskClarification
+            || decision.confidence < self.clarification_threshold
+        {
+            AgentResponse::NeedsClarification {
+                question: "What would you like to change about the sound?".to_string(),
+                options: CLARIFICATION_OPTIONS.iter().map(|o| o.to_string()).collect(),
+            }
+        } else if decision.confidence >= confidence::AUTO_EXECUTE {
+            AgentResponse::Executed {
                 message: format!("Done! {}", decision.reasoning),
-                decision: Some(decision.clone()),
+                decision: decision.clone(),
                 changes: decision.recommendations.clone(),
             }
-        } else if decision.confidence >= confidence::SUGGEST_FIRST {
-            AgentResponse {
-                action: AgentAction::Propose,
+        } else {
+            AgentResponse::Propose {
                 message: format!(
                     "I'm thinking of using {} tools. Should I go ahead?",
                     match decision.tool {
@@ -269,22 +1225,7 @@ impl Agent {
                         ToolType::AskClarification => "clarification needed",
                     }
                 ),
-                decision: Some(decision.clone()),
-                changes: Vec::new(),
-            }
-        } else if decision.confidence >= confidence::ASK_CLARIFICATION {
-            AgentResponse {
-                action: AgentAction::Clarify,
-                message: "Could you tell me more about what you're looking for?".to_string(),
-                decision: Some(decision.clone()),
-                changes: Vec::new(),
-            }
-        } else {
-            AgentResponse {
-                action: AgentAction::Uncertain,
-                message: "I'm not quite sure what you're looking for. Could you describe what you want to achieve in different words?".to_string(),
-                decision: Some(decision.clone()),
-                changes: Vec::new(),
+                decision: decision.clone(),
             }
         }
     }
@@ -300,6 +1241,10 @@ impl Default for Agent {
 mod tests {
     use super::*;
 
+    fn hardware_fallback() -> NeuralFallback {
+        NeuralFallback::Hardware("No compatible GPU detected".to_string())
+    }
+
     #[test]
     fn test_explicit_dsp_request() {
         let agent = Agent::new();
@@ -329,4 +1274,501 @@ mod tests {
         assert!(decision.confidence < confidence::AUTO_EXECUTE);
         assert!(decision.ask_clarification);
     }
+
+    #[test]
+    fn test_vague_prompt_needs_clarification() {
+        let agent = Agent::new();
+        let response = agent.handle_decision(&agent.decide_tool("make it better"));
+        match response {
+            AgentResponse::NeedsClarification { question, options } => {
+                assert!(!question.is_empty());
+                assert!(!options.is_empty());
+            }
+            other => panic!("expected clarification, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_specific_prompt_does_not_need_clarification() {
+        let agent = Agent::new();
+        let response = agent.handle_decision(&agent.decide_tool("add reverb"));
+        assert!(matches!(response, AgentResponse::Executed { .. }));
+    }
+
+    #[test]
+    fn test_clarification_threshold_from_preferences() {
+        let prefs = UserPreferences {
+            clarification_threshold: Some(0.90),
+            ..Default::default()
+        };
+        let agent = Agent::with_preferences(&prefs);
+        assert_eq!(agent.clarification_threshold(), 0.90);
+
+        // "make it louder" scores 0.80, below the raised threshold
+        let response = agent.handle_decision(&agent.decide_tool("make it louder"));
+        assert!(matches!(response, AgentResponse::NeedsClarification { .. }));
+    }
+
+    #[test]
+    fn test_decision_matches_scores() {
+        let agent = Agent::new();
+        for prompt in [
+            "add an EQ",
+            "make it sound like a vintage recording",
+            "add reverb",
+            "make it louder",
+            "make it better",
+            "make it warmer and more spacious with some vintage character",
+            "something",
+        ] {
+            let decision = agent.decide_tool(prompt);
+            assert_eq!(decision.scores.len(), 3, "{}", prompt);
+            if decision.tool == ToolType::AskClarification {
+                continue;
+            }
+            let best = decision
+                .scores
+                .iter()
+                .fold(0.0_f32, |best, &(_, score)| best.max(score));
+            assert_eq!(decision.confidence, best, "{}", prompt);
+            assert_eq!(decision.score(decision.tool), best, "{}", prompt);
+        }
+    }
+
+    #[test]
+    fn test_explain_decision() {
+        let agent = Agent::new();
+        let decision = agent.decide_tool("make it louder");
+        let explanation = agent.explain_decision(&decision);
+        assert!(
+            explanation.starts_with("Chose DSP (80% confidence)"),
+            "{}",
+            explanation
+        );
+        assert!(explanation.contains("80%  <- chosen"), "{}", explanation);
+        assert!(explanation.contains(&decision.reasoning));
+        assert!(explanation.contains("Not Neural: nothing in the request needs neural"));
+        assert!(!explanation.contains("GPU"));
+
+        let decision = agent.decide_tool("make it louder and restore the old recording");
+        assert_eq!(decision.tool, ToolType::Neural);
+        assert!(agent
+            .explain_decision(&decision)
+            .contains("Not DSP: scored 80%"));
+    }
+
+    #[test]
+    fn test_unavailable_neural_falls_back_to_dsp() {
+        let agent = Agent::new().with_neural_fallback(Some(hardware_fallback()));
+        let decision = agent.decide_tool("transform it in the style of a jazz band");
+        assert_eq!(decision.tool, ToolType::Dsp);
+        assert_eq!(decision.neural_fallback, Some(hardware_fallback()));
+
+        let explanation = agent.explain_decision(&decision);
+        assert!(explanation.contains("Not Neural: neural processing is unavailable"));
+        assert!(explanation.contains("GPU check forced a DSP fallback: No compatible GPU"));
+
+        // DSP requests are unaffected
+        let decision = agent.decide_tool("add reverb");
+        assert_eq!(decision.tool, ToolType::Dsp);
+        assert!(decision.neural_fallback.is_none());
+
+        // A missing model isn't blamed on the GPU
+        let agent = Agent::new().with_neural_fallback(Some(NeuralFallback::NotInstalled(
+            "ACE-Step is not installed".to_string(),
+        )));
+        let decision = agent.decide_tool("transform it in the style of a jazz band");
+        let explanation = agent.explain_decision(&decision);
+        assert!(!explanation.contains("GPU"), "{}", explanation);
+        assert!(explanation.contains("ACE-Step is not installed"));
+        let response = agent.handle_decision(&decision);
+        assert!(response.message().contains("isn't installed"));
+    }
+
+    #[test]
+    fn test_dsp_approximation_mapping() {
+        let intent = |prompt| DspApproximation::for_prompt(prompt).map(|a| a.intent);
+        assert_eq!(intent("denoise this take").as_deref(), Some("denoise"));
+        assert_eq!(intent("remove the hiss").as_deref(), Some("denoise"));
+        assert_eq!(
+            intent("make it sound like an old recording").as_deref(),
+            Some("lo-fi")
+        );
+        assert_eq!(intent("make it warmer").as_deref(), Some("warmth"));
+        assert_eq!(intent("vintage analog vibe").as_deref(), Some("warmth"));
+        assert_eq!(intent("brighten the vocal").as_deref(), Some("brighten"));
+        assert_eq!(intent("in the style of a jazz band"), None);
+        assert_eq!(intent("separate the stems"), None);
+
+        // Whole words only, and reduced keywords don't add
+        assert_eq!(intent("add tape noise").as_deref(), Some("warmth"));
+        assert_eq!(intent("add some noise"), None);
+        assert_eq!(intent("make it less bright"), None);
+        assert_eq!(intent("it's too warm"), None);
+        assert_eq!(intent("a radiohead-style mix"), None);
+        assert_eq!(intent("get rid of the noise").as_deref(), Some("denoise"));
+        assert_eq!(
+            intent("less noise and more air").as_deref(),
+            Some("denoise")
+        );
+    }
+
+    #[test]
+    fn test_dsp_approximation_chains_are_valid() {
+        let samples = (0..9600).map(|n| 0.3 * (n as f32 * 0.07).sin()).collect();
+        let input = dsp::AudioBuffer::from_interleaved(samples, 2, 48000.0).unwrap();
+
+        for &(intent, keywords, removes, _) in DSP_APPROXIMATIONS {
+            let prompt = if removes {
+                format!("remove the {}", keywords[0])
+            } else {
+                keywords[0].to_string()
+            };
+            let approx = DspApproximation::for_prompt(&prompt).unwrap();
+            assert_eq!(approx.intent, intent);
+
+            let mut chain = approx.build_chain().unwrap();
+            assert!(!chain.is_empty(), "{} built an empty chain", intent);
+            let restored = dsp::EffectChain::from_json(&chain.to_json().unwrap()).unwrap();
+            assert_eq!(restored.len(), chain.len());
+
+            chain.prepare(48000.0, 512);
+            let mut buffer = input.clone();
+            chain.process(&mut buffer);
+            assert!(buffer.samples().iter().all(|s| s.is_finite()), "{}", intent);
+        }
+
+        let unknown = DspApproximation {
+            intent: "stem separation".to_string(),
+            description: String::new(),
+        };
+        assert!(unknown.build_chain().is_err());
+    }
+
+    #[test]
+    fn test_unavailable_neural_uses_labeled_approximation() {
+        let agent = Agent::new().with_neural_fallback(Some(hardware_fallback()));
+
+        let response =
+            agent.handle_decision(&agent.decide_tool("make it sound like a vintage recording"));
+        assert!(response.message().starts_with("Approximation:"));
+        assert!(response.message().contains("No compatible GPU detected"));
+        assert_eq!(response.approximation().unwrap().intent, "lo-fi");
+        let decision = response.decision().unwrap();
+        assert_eq!(decision.tool, ToolType::Dsp);
+        assert_eq!(decision.confidence, confidence::SUGGEST_FIRST);
+        assert!(agent
+            .explain_decision(decision)
+            .contains("Approximating lo-fi with"));
+
+        let response =
+            agent.handle_decision(&agent.decide_tool("transform it in the style of a jazz band"));
+        assert!(matches!(response, AgentResponse::Uncertain { .. }));
+        assert!(response.message().contains("requires GPU"));
+        assert!(response.approximation().is_none());
+
+        // With a model available, the same request stays neural
+        let decision = Agent::new().decide_tool("make it sound like a vintage recording");
+        assert!(decision.approximation.is_none());
+    }
+
+    #[test]
+    fn test_safe_mode_limiter() {
+        let mut chain = dsp::EffectChain::new();
+        chain.add(Box::new(dsp::GainEffect::with_gain(12.0).unwrap()));
+        let samples = (0..4800).map(|n| 0.5 * (n as f32 * 0.05).sin()).collect();
+        let input = dsp::AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap();
+
+        // Off by default: nothing checked, response untouched
+        let agent = Agent::new();
+        assert!(!agent.safe_mode());
+        let mut response = agent.handle_decision(&agent.decide_tool("make it louder"));
+        let message = response.message().to_string();
+        assert!(agent
+            .safe_mode_limiter(&chain, &input, &mut response)
+            .unwrap()
+            .is_none());
+        assert_eq!(response.message(), message);
+
+        let agent = Agent::with_preferences(&UserPreferences {
+            safe_mode: true,
+            ..Default::default()
+        });
+        let rec = agent
+            .safe_mode_limiter(&chain, &input, &mut response)
+            .unwrap()
+            .unwrap();
+        assert!(rec.mitigation.is_some());
+        assert!(response.message().contains("Safe mode added a limiter"));
+        match &response {
+            AgentResponse::Executed { changes, .. } => {
+                assert!(changes.last().unwrap().contains("would peak at"));
+            }
+            other => panic!("expected executed response, got {:?}", other),
+        }
+
+        // Clipping from a requested saturation is left alone
+        let mut context = NeuralContextTracker::new();
+        context.add_artifact(crate::neural::IntentionalArtifact::Saturation);
+        let agent = agent.with_neural_context(&context);
+        assert!(agent
+            .safe_mode_limiter(&chain, &input, &mut response)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_clarification_answer_resolves_from_context() {
+        let agent = Agent::new();
+        let mut context = ConversationContext::new();
+
+        let first = agent.respond("make it better", &mut context);
+        assert!(matches!(first, AgentResponse::NeedsClarification { .. }));
+        assert!(context.pending_clarification.is_some());
+
+        // Answer by option number
+        let second = agent.respond("2", &mut context);
+        let decision = second.decision().expect("answer should produce a decision");
+        assert_eq!(decision.tool, ToolType::Dsp);
+        assert!(context.pending_clarification.is_none());
+        assert_eq!(context.messages.len(), 4);
+    }
+
+    #[test]
+    fn test_approximation_waits_for_confirmation() {
+        let agent = Agent::new().with_neural_fallback(Some(hardware_fallback()));
+        let mut context = ConversationContext::new();
+
+        let proposed = agent.respond("make it sound like a vintage recording", &mut context);
+        assert!(matches!(proposed, AgentResponse::Propose { .. }));
+        assert!(context.pending_approximation.is_some());
+
+        let accepted = agent.respond("Yes!", &mut context);
+        assert!(matches!(accepted, AgentResponse::Executed { .. }));
+        assert_eq!(accepted.approximation().unwrap().intent, "lo-fi");
+        assert!(context.pending_approximation.is_none());
+
+        // Anything else drops the proposal and is handled as a new prompt
+        agent.respond("make it sound like a vintage recording", &mut context);
+        let declined = agent.respond("make it louder", &mut context);
+        assert!(declined.approximation().is_none());
+        assert!(context.pending_approximation.is_none());
+        let stale = agent.respond("yes", &mut context);
+        assert!(stale.approximation().is_none());
+    }
+
+    // --- Intensity mapping ---
+
+    const MAPPED_EFFECTS: [&str; 4] = ["reverb", "delay", "saturation", "compressor"];
+
+    /// One second of 100 ms tone bursts at -6 dBFS, so tails are audible
+    fn bursts() -> crate::dsp::AudioBuffer {
+        let mut buffer = crate::dsp::AudioBuffer::new(2, 48000, 48000.0);
+        for i in 0..48000 {
+            let on = (i / 4800) % 2 == 0;
+            let t = i as f32 / 48000.0;
+            let sample = if on {
+                0.5 * (2.0 * std::f32::consts::PI * 330.0 * t).sin()
+            } else {
+                0.0
+            };
+            buffer.set(i, 0, sample);
+            buffer.set(i, 1, sample);
+        }
+        buffer
+    }
+
+    fn render(effect_type: &str, intensity: f32) -> crate::dsp::AudioBuffer {
+        let params = intensity_params(effect_type, intensity).unwrap();
+        let mut effect = crate::dsp::build_effect(effect_type, "test", true, &params).unwrap();
+        let mut buffer = bursts();
+        effect.prepare(48000.0, buffer.num_samples());
+        effect.process(&mut buffer);
+        buffer
+    }
+
+    /// Level of (output - input) relative to the input, in dB
+    fn change_db(effect_type: &str, intensity: f32) -> f32 {
+        let input = bursts();
+        let output = render(effect_type, intensity);
+        let (mut diff, mut reference) = (0.0_f64, 0.0_f64);
+        for (a, b) in output.samples().iter().zip(input.samples()) {
+            diff += ((a - b) as f64).powi(2);
+            reference += (*b as f64).powi(2);
+        }
+        if diff == 0.0 {
+            return f32::NEG_INFINITY;
+        }
+        (10.0 * (diff / reference).log10()) as f32
+    }
+
+    fn peak_db(buffer: &crate::dsp::AudioBuffer) -> f32 {
+        let peak = buffer.samples().iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        20.0 * peak.log10()
+    }
+
+    #[test]
+    fn test_intensity_zero_is_near_null() {
+        for effect in MAPPED_EFFECTS {
+            let change = change_db(effect, 0.0);
+            assert!(
+                change < -60.0,
+                "{} changed the audio by {} dB",
+                effect,
+                change
+            );
+        }
+    }
+
+    #[test]
+    fn test_intensity_change_is_monotonic() {
+        for effect in ["reverb", "saturation", "delay", "compressor"] {
+            let changes: Vec<f32> = [0.0, 0.25, 0.5, 0.75, 1.0]
+                .iter()
+                .map(|&i| change_db(effect, i))
+                .collect();
+            assert!(
+                changes.windows(2).all(|w| w[1] > w[0]),
+                "{} is not monotonic: {:?}",
+                effect,
+                changes
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_intensity_passes_safety_check() {
+        let input_peak = peak_db(&bursts());
+        let mut checker = super::super::safety::SafetyChecker::new();
+        checker.set_analysis(super::super::safety::AudioAnalysis {
+            peak_db: input_peak,
+            ..Default::default()
+        });
+
+        for effect in MAPPED_EFFECTS {
+            let gain = peak_db(&render(effect, 1.0)) - input_peak;
+            let result = checker.check_gain(gain);
+            assert!(
+                result.issues.is_empty(),
+                "{} at full intensity raised the peak by {} dB",
+                effect,
+                gain
+            );
+        }
+    }
+
+    #[test]
+    fn test_intensity_mapping_is_continuous_and_clamped() {
+        for effect in MAPPED_EFFECTS {
+            let a = intensity_params(effect, 0.5).unwrap();
+            let b = intensity_params(effect, 0.5001).unwrap();
+            for (name, value) in &a {
+                let delta = (value.as_f64().unwrap() - b[name].as_f64().unwrap()).abs();
+                assert!(delta < 0.01, "{}.{} jumped by {}", effect, name, delta);
+            }
+
+            assert_eq!(intensity_params(effect, 2.0), intensity_params(effect, 1.0));
+            assert_eq!(
+                intensity_params(effect, -1.0),
+                intensity_params(effect, 0.0)
+            );
+        }
+        assert!(intensity_params("flanger", 0.5).is_none());
+    }
+
+    fn mock_model(capabilities: &[&str]) -> NeuralModelInfo {
+        serde_json::from_value(json!({
+            "id": "mock",
+            "name": "Mock Model",
+            "version": "1.0",
+            "capabilities": capabilities,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_processing_mode_parsing() {
+        for mode in ProcessingMode::ALL {
+            assert_eq!(mode.as_str().parse::<ProcessingMode>().unwrap(), mode);
+        }
+        assert_eq!(
+            " Cover ".parse::<ProcessingMode>().unwrap(),
+            ProcessingMode::Cover
+        );
+
+        let err = "remix".parse::<ProcessingMode>().unwrap_err().to_string();
+        assert!(err.contains("remix"), "{}", err);
+        assert!(
+            err.contains("transform, cover, repaint or extract"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_modes_route_to_model_operations() {
+        let model = mock_model(&["style_change", "cover", "repaint", "track_extraction"]);
+        let expected = [
+            (ProcessingMode::Transform, "style_change", "transform"),
+            (ProcessingMode::Cover, "cover", "cover"),
+            (ProcessingMode::Repaint, "repaint", "repaint"),
+            (ProcessingMode::Extract, "track_extraction", "extract"),
+        ];
+
+        for (mode, capability, ace_mode) in expected {
+            let route = mode.route(&model, "jazz", 0.5).unwrap();
+            assert_eq!(route.mode, mode);
+            assert_eq!(route.model, "mock");
+            assert_eq!(route.capability, capability);
+            assert_eq!(route.params.get_string("mode").as_deref(), Some(ace_mode));
+            assert_eq!(route.params.get_string("prompt").as_deref(), Some("jazz"));
+        }
+    }
+
+    #[test]
+    fn test_unsupported_mode_is_a_clear_error() {
+        let model = mock_model(&["cover", "repaint"]);
+        let err = ProcessingMode::Extract
+            .route(&model, "", 0.5)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("extract"), "{}", err);
+        assert!(
+            err.contains("Mock Model (mock) supports: cover, repaint"),
+            "{}",
+            err
+        );
+
+        let err = ProcessingMode::Cover
+            .route(&mock_model(&["denoise"]), "", 0.5)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("supports no processing modes"), "{}", err);
+    }
+
+    #[test]
+    fn test_modes_are_distinguishable_in_responses() {
+        let agent = Agent::new();
+        let model = mock_model(&["text_to_music", "cover", "repaint", "source_separation"]);
+
+        let mut messages = Vec::new();
+        for mode in ProcessingMode::ALL {
+            let (response, route) = agent
+                .respond_to_mode("make it lo-fi", mode, &model, 0.4)
+                .unwrap();
+            assert_eq!(response.mode(), Some(mode));
+            assert_eq!(route.params.get_f32("intensity"), Some(0.4));
+            assert_eq!(response.decision().unwrap().tool, ToolType::Neural);
+
+            let json = serde_json::to_value(&response).unwrap();
+            assert_eq!(json["decision"]["mode"], mode.as_str());
+            messages.push(response.message().to_string());
+        }
+        messages.dedup();
+        assert_eq!(messages.len(), ProcessingMode::ALL.len());
+
+        assert!(agent
+            .respond_to_mode("x", ProcessingMode::Extract, &mock_model(&["cover"]), 0.7)
+            .is_err());
+    }
 }
@@ -3,8 +3,9 @@
 //! Users can always ask what the agent did.
 //! Implements §7.5 from the spec.
 
-use super::context::{AgentAction, ConversationContext, EffectRef, ParameterChange};
+use super::context::{ConversationContext, EffectRef};
 use super::decision::ToolType;
+use crate::dsp::{ChainBypass, EffectChain, GraphicEQLayout};
 use std::collections::HashMap;
 
 /// Explain the last action taken by the agent
@@ -76,15 +77,40 @@ pub fn explain_last_action(context: &ConversationContext) -> String {
 }
 
 /// Explain the full effect chain
-pub fn explain_full_chain(dsp_chain: &[EffectRef], effect_params: &HashMap<String, HashMap<String, serde_json::Value>>) -> String {
+///
+/// Effects held back by a global bypass or a solo are marked as such.
+pub fn explain_full_chain(
+    dsp_chain: &[EffectRef],
+    effect_params: &HashMap<String, HashMap<String, serde_json::Value>>,
+    bypass: &ChainBypass,
+) -> String {
     if dsp_chain.is_empty() {
         return "No effects are currently applied. The audio is passing through clean.".to_string();
     }
 
     let mut explanation = format!("Here's your current effect chain ({} effects):\n\n", dsp_chain.len());
 
+    if bypass.global {
+        explanation
+            .push_str("The whole chain is bypassed, so the audio is passing through clean.\n\n");
+    } else if let Some(solo) = bypass.solo.as_deref() {
+        let name = dsp_chain
+            .iter()
+            .find(|e| e.id == solo)
+            .map_or(solo, |e| e.display_name.as_str());
+        explanation.push_str(&format!(
+            "{} is soloed, so it's the only effect running.\n\n",
+            name
+        ));
+    }
+
     for (i, effect) in dsp_chain.iter().enumerate() {
         explanation.push_str(&format!("{}. {}", i + 1, effect.display_name));
+        if bypass.global {
+            explanation.push_str(" (bypassed)");
+        } else if !bypass.allows(&effect.id) {
+            explanation.push_str(" (bypassed by solo)");
+        }
 
         // Check if we have params for this effect
         if let Some(params) = effect_params.get(&effect.id) {
@@ -100,6 +126,252 @@ pub fn explain_full_chain(dsp_chain: &[EffectRef], effect_params: &HashMap<Strin
     explanation
 }
 
+/// Render the chain as a text signal-flow diagram
+///
+/// Effects appear as boxes in processing order, each with its key
+/// parameters and how it treats the stereo image. Disabled effects, and
+/// effects held back by a global bypass or a solo, are drawn with dashed
+/// borders and marked as bypassed.
+pub fn render_flow_diagram(chain: &EffectChain) -> String {
+    if chain.is_empty() {
+        return "Input ───▶ Output\n\nDry passthrough: the chain is empty, so the audio is not processed.\n"
+            .to_string();
+    }
+
+    let bypass = chain.bypass();
+    let active = chain
+        .iter()
+        .filter(|e| e.is_enabled() && bypass.allows(e.id()))
+        .count();
+    let mut diagram = format!(
+        "Signal flow ({} effect{}, {} active)\n",
+        chain.len(),
+        if chain.len() == 1 { "" } else { "s" },
+        active
+    );
+    if bypass.global {
+        diagram.push_str("Whole chain bypassed: dry passthrough\n");
+    }
+    diagram.push_str("\n  Input\n");
+
+    for (i, effect) in chain.iter().enumerate() {
+        let json = effect.to_json().unwrap_or_default();
+        let bypassed = if bypass.global {
+            Some("bypassed")
+        } else if !bypass.allows(effect.id()) {
+            Some("bypassed by solo")
+        } else if !effect.is_enabled() {
+            Some("bypassed, disabled")
+        } else {
+            None
+        };
+
+        let mut title = format!(
+            "{}. {}  [{}]",
+            i + 1,
+            effect.display_name(),
+            stereo_path(effect.effect_type(), &json)
+        );
+        if let Some(reason) = bypassed {
+            title.push_str(&format!("  ({})", reason));
+        }
+        let mut lines = vec![title];
+        let settings = flow_settings(effect.effect_type(), &json);
+        if !settings.is_empty() {
+            lines.push(format!("   {}", settings));
+        }
+
+        let (horizontal, vertical) = if bypassed.is_some() {
+            ('╌', '╎')
+        } else {
+            ('─', '│')
+        };
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) + 2;
+        let border: String = std::iter::repeat_n(horizontal, width).collect();
+
+        diagram.push_str("    │\n    ▼\n");
+        diagram.push_str(&format!("┌{}┐\n", border));
+        for line in &lines {
+            let padding = width - 1 - line.chars().count();
+            diagram.push_str(&format!(
+                "{} {}{}{}\n",
+                vertical,
+                line,
+                " ".repeat(padding),
+                vertical
+            ));
+        }
+        diagram.push_str(&format!("└{}┘\n", border));
+    }
+
+    diagram.push_str("    │\n    ▼\n  Output\n");
+    diagram
+}
+
+/// Summarize each effect's levels from the last block the chain processed
+///
+/// Metered effects show their input and output peaks, plus gain reduction
+/// for dynamics; the rest are listed without levels.
+pub fn render_level_summary(chain: &EffectChain) -> String {
+    if chain.is_empty() {
+        return "No effects to meter.\n".to_string();
+    }
+
+    let mut summary = String::from("Levels (last block):\n");
+    for (i, effect) in chain.iter().enumerate() {
+        let levels = match effect.meter() {
+            Some(meter) => meter.summary(),
+            None => "no meter".to_string(),
+        };
+        summary.push_str(&format!(
+            "  {}. {} ({}): {}\n",
+            i + 1,
+            effect.display_name(),
+            effect.id(),
+            levels
+        ));
+    }
+    summary
+}
+
+/// Look up a parameter in an effect's JSON, nested under "params" or not
+fn json_param<'a>(json: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    json.get("params")
+        .and_then(|p| p.get(key))
+        .or_else(|| json.get(key))
+}
+
+fn json_f64(json: &serde_json::Value, key: &str) -> Option<f64> {
+    json_param(json, key).and_then(|v| v.as_f64())
+}
+
+/// How an effect treats the channels of a stereo signal
+fn stereo_path(effect_type: &str, json: &serde_json::Value) -> &'static str {
+    match effect_type {
+        "reverb" if json_f64(json, "width") == Some(0.0) => "mono",
+        "reverb" => "stereo",
+        "delay" if json_param(json, "ping_pong").and_then(|v| v.as_bool()) == Some(true) => {
+            "stereo"
+        }
+        "haas" => "stereo",
+        "limiter" if json_f64(json, "stereo_link") == Some(0.0) => "dual mono",
+        "compressor" | "gate" | "expander" | "limiter" => "linked",
+        _ => "dual mono",
+    }
+}
+
+/// One-line summary of the parameters that matter most for each effect
+fn flow_settings(effect_type: &str, json: &serde_json::Value) -> String {
+    let num = |key: &str| json_f64(json, key).unwrap_or_default();
+    let percent = |key: &str| (num(key) * 100.0).round();
+
+    match effect_type {
+        "gain" => format!("{:+.1} dB", num("gain_db")),
+        "parametric-eq" => {
+            let bands: Vec<String> = json
+                .get("bands")
+                .and_then(|b| b.as_array())
+                .into_iter()
+                .flatten()
+                .filter(|b| b.get("enabled").and_then(|e| e.as_bool()) != Some(false))
+                .map(|b| {
+                    format!(
+                        "{:+.1} dB @ {} Hz",
+                        b.get("gain_db")
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or_default(),
+                        b.get("frequency")
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or_default()
+                            .round()
+                    )
+                })
+                .collect();
+            if bands.is_empty() {
+                "flat".to_string()
+            } else {
+                bands.join(", ")
+            }
+        }
+        "graphic-eq" => {
+            let gains: Vec<f64> = json
+                .get("gains_db")
+                .and_then(|g| g.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|g| g.as_f64())
+                .collect();
+            let Some(layout) = GraphicEQLayout::from_num_bands(gains.len()) else {
+                return String::new();
+            };
+            let bands: Vec<String> = layout
+                .frequencies()
+                .iter()
+                .zip(&gains)
+                .filter(|(_, gain)| gain.abs() >= 0.01)
+                .map(|(freq, gain)| format!("{:+.1} dB @ {} Hz", gain, freq))
+                .collect();
+            if bands.is_empty() {
+                format!("{} bands, flat", gains.len())
+            } else {
+                format!("{} bands: {}", gains.len(), bands.join(", "))
+            }
+        }
+        "compressor" => format!(
+            "threshold {:.1} dB, ratio {}:1",
+            num("threshold_db"),
+            num("ratio")
+        ),
+        "gate" => format!("threshold {:.1} dB", num("threshold_db")),
+        "expander" => format!(
+            "threshold {:.1} dB, ratio 1:{}",
+            num("threshold_db"),
+            num("ratio")
+        ),
+        "limiter" => format!("ceiling {:.1} dB", num("ceiling_db")),
+        "reverb" => format!(
+            "room {}%, wet {}%",
+            percent("room_size"),
+            percent("wet_level")
+        ),
+        "delay" => format!(
+            "{} ms, feedback {}%, wet {}%",
+            num("delay_time_ms").round(),
+            percent("feedback"),
+            percent("wet_level")
+        ),
+        "saturation" => format!("drive {}%, mix {}%", percent("drive"), percent("mix")),
+        "ring-mod" => format!("carrier {} Hz, mix {}%", num("carrier_hz"), percent("mix")),
+        "tremolo" => format!(
+            "{} Hz {}, depth {}%",
+            num("rate_hz"),
+            json_param(json, "waveform")
+                .and_then(|v| v.as_str())
+                .unwrap_or("sine"),
+            percent("depth")
+        ),
+        "wah" => format!(
+            "{} {}-{} Hz, Q {}",
+            match json_param(json, "mode").and_then(|v| v.as_str()) {
+                Some("auto_wah") => "auto",
+                _ => "envelope",
+            },
+            num("freq_min"),
+            num("freq_max"),
+            num("resonance")
+        ),
+        "pitch-shifter" => format!("{:+} st, mix {}%", num("semitones"), percent("mix")),
+        "haas" => format!(
+            "{} ms on {}",
+            num("delay_ms"),
+            json_param(json, "side")
+                .and_then(|v| v.as_str())
+                .unwrap_or("right")
+        ),
+        _ => String::new(),
+    }
+}
+
 /// Format a JSON value for display
 fn format_value(value: &serde_json::Value) -> String {
     match value {
@@ -262,7 +534,7 @@ fn describe_gate_settings(params: &HashMap<String, serde_json::Value>) -> String
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::agent::context::{ActionType, AgentAction};
+    use crate::agent::context::{ActionType, AgentAction, ParameterChange};
 
     #[test]
     fn test_explain_no_action() {
@@ -347,7 +619,7 @@ mod tests {
         comp_params.insert("ratio".to_string(), serde_json::json!(4.0));
         params.insert("comp-1".to_string(), comp_params);
 
-        let explanation = explain_full_chain(&chain, &params);
+        let explanation = explain_full_chain(&chain, &params, &ChainBypass::default());
         assert!(explanation.contains("2 effects"));
         assert!(explanation.contains("Parametric EQ"));
         assert!(explanation.contains("Compressor"));
@@ -355,13 +627,178 @@ mod tests {
         assert!(explanation.contains("ratio 4:1"));
     }
 
+    #[test]
+    fn test_explain_chain_bypass_and_solo() {
+        let chain: Vec<EffectRef> = [
+            ("eq-1", "eq", "Parametric EQ"),
+            ("comp-1", "compressor", "Compressor"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (id, effect_type, name))| EffectRef {
+            id: id.to_string(),
+            effect_type: effect_type.to_string(),
+            display_name: name.to_string(),
+            chain_index: i,
+        })
+        .collect();
+
+        let plain = explain_full_chain(&chain, &HashMap::new(), &ChainBypass::default());
+        assert!(!plain.contains("bypassed"));
+
+        let solo = ChainBypass {
+            global: false,
+            solo: Some("comp-1".to_string()),
+        };
+        let explanation = explain_full_chain(&chain, &HashMap::new(), &solo);
+        assert!(explanation.contains("Compressor is soloed"));
+        assert!(explanation.contains("1. Parametric EQ (bypassed by solo)"));
+        assert!(explanation.contains("2. Compressor\n"));
+
+        // Global bypass wins over solo
+        let both = ChainBypass {
+            global: true,
+            ..solo
+        };
+        let explanation = explain_full_chain(&chain, &HashMap::new(), &both);
+        assert!(explanation.contains("whole chain is bypassed"));
+        assert!(explanation.contains("2. Compressor (bypassed)"));
+        assert!(!explanation.contains("soloed"));
+    }
+
     #[test]
     fn test_explain_empty_chain() {
-        let explanation = explain_full_chain(&[], &HashMap::new());
+        let explanation = explain_full_chain(&[], &HashMap::new(), &ChainBypass::default());
         assert!(explanation.contains("No effects"));
         assert!(explanation.contains("passing through clean"));
     }
 
+    fn flow_chain() -> EffectChain {
+        let build = |effect_type: &str, id: &str, enabled: bool, params: serde_json::Value| {
+            let params: HashMap<String, serde_json::Value> =
+                serde_json::from_value(params).unwrap();
+            crate::dsp::build_effect(effect_type, id, enabled, &params).unwrap()
+        };
+
+        // Added out of order; the chain places them by default priority
+        let mut chain = EffectChain::new();
+        chain.add(build(
+            "reverb",
+            "rev-1",
+            true,
+            serde_json::json!({"room_size": 0.7, "wet_level": 0.25}),
+        ));
+        chain.add(build(
+            "compressor",
+            "comp-1",
+            false,
+            serde_json::json!({"threshold_db": -18.0, "ratio": 4.0}),
+        ));
+        chain.add(build(
+            "eq",
+            "eq-1",
+            true,
+            serde_json::json!({"bands": [{
+                "frequency": 120.0, "gain_db": 3.0, "q": 0.7,
+                "filter_type": "low_shelf", "enabled": true
+            }]}),
+        ));
+        chain
+    }
+
+    #[test]
+    fn test_flow_diagram_snapshot() {
+        let expected = "\
+Signal flow (3 effects, 2 active)
+
+  Input
+    │
+    ▼
+┌───────────────────────────────┐
+│ 1. Parametric EQ  [dual mono] │
+│    +3.0 dB @ 120 Hz           │
+└───────────────────────────────┘
+    │
+    ▼
+┌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┐
+╎ 2. Compressor  [linked]  (bypassed, disabled) ╎
+╎    threshold -18.0 dB, ratio 4:1              ╎
+└╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌╌┘
+    │
+    ▼
+┌──────────────────────┐
+│ 3. Reverb  [stereo]  │
+│    room 70%, wet 25% │
+└──────────────────────┘
+    │
+    ▼
+  Output
+";
+        assert_eq!(render_flow_diagram(&flow_chain()), expected);
+    }
+
+    #[test]
+    fn test_flow_diagram_follows_reordering() {
+        let mut chain = flow_chain();
+        chain.move_effect("rev-1", 0).unwrap();
+        let diagram = render_flow_diagram(&chain);
+
+        let reverb = diagram.find("1. Reverb").unwrap();
+        let eq = diagram.find("2. Parametric EQ").unwrap();
+        assert!(reverb < eq);
+    }
+
+    #[test]
+    fn test_flow_diagram_bypass_and_solo() {
+        let mut chain = flow_chain();
+        chain.solo_effect(Some("rev-1")).unwrap();
+        let diagram = render_flow_diagram(&chain);
+        assert!(diagram.contains("1 active"));
+        assert!(diagram.contains("Parametric EQ  [dual mono]  (bypassed by solo)"));
+        assert!(diagram.contains("│ 3. Reverb  [stereo]  │"));
+
+        chain.set_global_bypass(true);
+        let diagram = render_flow_diagram(&chain);
+        assert!(diagram.contains("0 active"));
+        assert!(diagram.contains("dry passthrough"));
+        assert!(diagram.contains("╎ 3. Reverb  [stereo]  (bypassed)"));
+    }
+
+    #[test]
+    fn test_flow_diagram_mono_reverb() {
+        let params: HashMap<String, serde_json::Value> =
+            [("width".to_string(), serde_json::json!(0.0))].into();
+        let mut chain = EffectChain::new();
+        chain.add(crate::dsp::build_effect("reverb", "rev-1", true, &params).unwrap());
+        assert!(render_flow_diagram(&chain).contains("Reverb  [mono]"));
+    }
+
+    #[test]
+    fn test_level_summary() {
+        let mut chain = EffectChain::new();
+        for (effect_type, id) in [("parametric-eq", "eq-1"), ("limiter", "lim-1")] {
+            chain.add(crate::dsp::build_effect(effect_type, id, true, &HashMap::new()).unwrap());
+        }
+        assert!(render_level_summary(&chain).contains("Limiter (lim-1): no meter"));
+
+        chain.prepare(48000.0, 512);
+        let mut buffer = crate::dsp::AudioBuffer::new(2, 4800, 48000.0);
+        buffer.samples_mut().fill(1.0);
+        chain.process(&mut buffer);
+
+        let summary = render_level_summary(&chain);
+        assert!(summary.contains("Parametric EQ (eq-1): no meter"));
+        assert!(summary.contains("Limiter (lim-1): in 0.0 dBFS, out -"));
+        assert!(summary.contains(", GR -"));
+    }
+
+    #[test]
+    fn test_flow_diagram_empty_chain() {
+        let diagram = render_flow_diagram(&EffectChain::new());
+        assert!(diagram.contains("Dry passthrough"));
+        assert!(diagram.contains("Input ───▶ Output"));
+    }
+
     #[test]
     fn test_describe_eq_settings() {
         let mut params = HashMap::new();
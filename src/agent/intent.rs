@@ -2,7 +2,63 @@
 //!
 //! Extracts structured intent from natural language.
 
+use super::context::{ActionType, AgentAction, ConversationContext, EffectRef, ParameterChange};
+use super::decision::ToolType;
+use super::reference::{named_effect_type, resolve_reference, ResolvedReference};
+use crate::dsp::EffectChain;
+use crate::layers::generate_effect_id;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Phrases asking to take an effect out of the chain
+const REMOVAL_PHRASES: &[&str] = &[
+    "remove",
+    "get rid of",
+    "delete",
+    "take out",
+    "take off",
+    "drop the",
+    "lose the",
+    "no more",
+    "without the",
+];
+
+/// Phrases asking for less of an effect
+const REDUCTION_PHRASES: &[&str] = &[
+    "less",
+    "reduce",
+    "lower",
+    "turn down",
+    "tone down",
+    "back off",
+    "dial back",
+    "too much",
+];
+
+/// Phrases capping a value ("no more than -1 dB")
+const CEILING_PHRASES: &[&str] = &[
+    "no more than",
+    "not more than",
+    "no higher than",
+    "no louder than",
+    "not above",
+    "never above",
+    "at most",
+    "maximum of",
+];
+
+/// The parameter that sets "how much" of each effect, and the value at
+/// which the effect does nothing
+const AMOUNT_PARAMS: &[(&str, &str, f64)] = &[
+    ("reverb", "wet_level", 0.0),
+    ("delay", "wet_level", 0.0),
+    ("saturation", "mix", 0.0),
+    ("ring-mod", "mix", 0.0),
+    ("pitch-shifter", "mix", 0.0),
+    ("tremolo", "depth", 0.0),
+    ("compressor", "ratio", 1.0),
+    ("limiter", "ceiling_db", 0.0),
+];
 
 /// Analyzed intent from a user prompt
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,16 +86,35 @@ pub struct Intent {
 
     /// Extracted parameters (e.g., "3dB at 1kHz")
     pub extracted_params: Vec<ExtractedParam>,
+
+    /// Whether the prompt adds processing or takes some away
+    #[serde(default)]
+    pub edit: EditIntent,
 }
 
 /// A parameter extracted from natural language
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExtractedParam {
     pub param_type: String,
     pub value: f32,
     pub unit: Option<String>,
 }
 
+/// What a prompt asks to do to the existing chain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditIntent {
+    /// Additive phrasing ("add reverb", "make it brighter")
+    #[default]
+    Add,
+    /// "remove the delay", "get rid of the reverb"
+    Remove,
+    /// "less reverb", "tone down the delay"
+    Reduce,
+    /// "no more than -1 dB", "reverb at most 20%"
+    Ceiling(ExtractedParam),
+}
+
 impl Intent {
     /// Analyze a prompt and extract intent
     pub fn analyze(prompt: &str) -> Self {
@@ -51,6 +126,7 @@ impl Intent {
         let mentioned_effects = Self::extract_effects(&prompt_lower);
         let extracted_params = Self::extract_params(&prompt_lower);
         let is_complex = Self::check_complexity(&prompt_lower, &mentioned_effects);
+        let edit = Self::extract_edit(&prompt_lower);
 
         Self {
             original: prompt.to_string(),
@@ -61,6 +137,7 @@ impl Intent {
             intensity,
             mentioned_effects,
             extracted_params,
+            edit,
         }
     }
 
@@ -138,6 +215,7 @@ impl Intent {
             ("limiter", "limiter"),
             ("limit", "limiter"),
             ("gate", "gate"),
+            ("expander", "expander"),
             ("saturation", "saturation"),
             ("distortion", "saturation"),
         ];
@@ -212,8 +290,7 @@ impl Intent {
             let word_lower = word.to_lowercase();
             if word_lower.ends_with("hz") {
                 let num_part = &word_lower[..word_lower.len() - 2];
-                if num_part.ends_with('k') {
-                    let num = &num_part[..num_part.len() - 1];
+                if let Some(num) = num_part.strip_suffix('k') {
                     if let Ok(val) = num.parse::<f32>() {
                         return Some(val * 1000.0);
                     }
@@ -254,6 +331,71 @@ impl Intent {
         None
     }
 
+    fn extract_edit(prompt: &str) -> EditIntent {
+        // Checked first: "no more than" would otherwise read as a removal
+        for phrase in CEILING_PHRASES {
+            if let Some(end) = find_phrase(prompt, phrase) {
+                if let Some(limit) = Self::extract_ceiling(&prompt[end..]) {
+                    return EditIntent::Ceiling(limit);
+                }
+            }
+        }
+
+        // A ceiling never removes the effect it caps, even when no value
+        // follows it ("no more than a touch of reverb"), but "no more
+        // reverb" does
+        let without_ceilings = CEILING_PHRASES
+            .iter()
+            .fold(prompt.to_string(), |rest, phrase| rest.replace(phrase, " "));
+        if REMOVAL_PHRASES
+            .iter()
+            .any(|p| find_phrase(&without_ceilings, p).is_some())
+        {
+            return EditIntent::Remove;
+        }
+
+        if REDUCTION_PHRASES
+            .iter()
+            .any(|p| find_phrase(prompt, p).is_some())
+        {
+            return EditIntent::Reduce;
+        }
+
+        EditIntent::Add
+    }
+
+    /// Parse the value right after a ceiling phrase: "-1 dB", "2:1",
+    /// "20%" (stored as 0.2) or a bare number
+    fn extract_ceiling(tail: &str) -> Option<ExtractedParam> {
+        let words: Vec<&str> = tail
+            .split_whitespace()
+            .take(2)
+            .map(|w| w.trim_end_matches(['.', ',', '!', '?']))
+            .collect();
+        let first = *words.first()?;
+        let head = words.join(" ");
+        let param = |param_type: &str, value: f32, unit: Option<&str>| ExtractedParam {
+            param_type: param_type.to_string(),
+            value,
+            unit: unit.map(str::to_string),
+        };
+
+        if let Some(ratio) = Self::extract_ratio_value(first) {
+            return Some(param("ratio", ratio, None));
+        }
+        if let Some(db) = Self::extract_db_value(&head) {
+            return Some(param("gain", db, Some("dB")));
+        }
+        if let Some(percent) = first.strip_suffix('%').and_then(|n| n.parse::<f32>().ok()) {
+            return Some(param("level", percent / 100.0, Some("%")));
+        }
+        let value = first.parse::<f32>().ok()?;
+        match words.get(1) {
+            Some(&"%") | Some(&"percent") => Some(param("level", value / 100.0, Some("%"))),
+            _ => Some(param("value", value, None)),
+        }
+    }
+
     fn check_complexity(prompt: &str, effects: &[String]) -> bool {
         // Multiple effects = complex
         if effects.len() > 1 {
@@ -319,6 +461,220 @@ impl IntentAnalyzer {
 
         None
     }
+
+    /// Plan a removal, reduction or ceiling against the existing chain
+    ///
+    /// The target is found by reference resolution, so "remove the delay"
+    /// deletes the delay already in the chain and "less of it" turns down
+    /// whatever the conversation last touched. Returns `None` when the
+    /// prompt is additive or names no effect (so "remove noise" still goes
+    /// through tool selection), and an error message when the target
+    /// can't be pinned to a single effect.
+    pub fn plan_edit(
+        intent: &Intent,
+        context: &ConversationContext,
+        chain: &EffectChain,
+    ) -> Option<std::result::Result<AgentAction, String>> {
+        if intent.edit == EditIntent::Add {
+            return None;
+        }
+
+        let refs: Vec<EffectRef> = chain
+            .iter()
+            .enumerate()
+            .map(|(chain_index, effect)| EffectRef {
+                id: effect.id().to_string(),
+                effect_type: effect.effect_type().to_string(),
+                display_name: effect.display_name().to_string(),
+                chain_index,
+            })
+            .collect();
+
+        let target = match resolve_reference(&intent.original, context, &refs) {
+            ResolvedReference::Effect(effect) => effect,
+            ResolvedReference::Unresolved => {
+                if let Some(effect_type) = named_effect_type(&intent.prompt_lower) {
+                    return Some(Err(format!("There is no {} in the chain", effect_type)));
+                }
+                // A dB ceiling on nothing in particular caps the output
+                return match &intent.edit {
+                    EditIntent::Ceiling(limit) if limit.param_type == "gain" => {
+                        Some(Self::output_ceiling(limit, &refs, chain))
+                    }
+                    _ => None,
+                };
+            }
+            unresolved @ (ResolvedReference::Ambiguous(_)
+            | ResolvedReference::OrdinalOutOfRange { .. }) => {
+                return unresolved.error_message().map(Err);
+            }
+            _ => return None,
+        };
+
+        match &intent.edit {
+            EditIntent::Add => None,
+            EditIntent::Remove => Some(Ok(AgentAction::new(
+                ActionType::Remove,
+                ToolType::Dsp,
+                &format!("Removed {}", target.display_name),
+            )
+            .with_reasoning(&format!(
+                "Removing the existing {} ({}) instead of adding processing",
+                target.display_name, target.id
+            ))
+            .with_effect(target))),
+            EditIntent::Reduce => Some(Self::reduce(intent.intensity, target, chain)),
+            EditIntent::Ceiling(limit) => Some(Self::cap(limit, target, chain)),
+        }
+    }
+
+    /// Move an effect's amount toward "off" by `intensity` (0.5 halves a
+    /// reverb's wet level)
+    fn reduce(
+        intensity: f32,
+        target: EffectRef,
+        chain: &EffectChain,
+    ) -> std::result::Result<AgentAction, String> {
+        let (param, current, neutral) = amount_of(&target, chain)?;
+        let new = current + (neutral - current) * intensity.clamp(0.0, 1.0) as f64;
+        Ok(
+            modify(&target, param, current, new).with_reasoning(&format!(
+                "Turning down the existing {} rather than adding another",
+                target.display_name
+            )),
+        )
+    }
+
+    /// Keep an effect's amount at or below `limit`
+    fn cap(
+        limit: &ExtractedParam,
+        target: EffectRef,
+        chain: &EffectChain,
+    ) -> std::result::Result<AgentAction, String> {
+        let (param, current, _) = amount_of(&target, chain)?;
+        let ceiling = ceiling_value(param, limit).ok_or_else(|| {
+            format!(
+                "A limit of {}{} doesn't apply to the {}'s {}",
+                limit.value,
+                limit.unit.as_deref().unwrap_or(""),
+                target.display_name,
+                param
+            )
+        })?;
+        let action = if current <= ceiling {
+            AgentAction::new(
+                ActionType::Modify,
+                ToolType::Dsp,
+                &format!(
+                    "{} {} is already no more than {}",
+                    target.display_name, param, ceiling
+                ),
+            )
+            .with_effect(target.clone())
+        } else {
+            modify(&target, param, current, ceiling)
+        };
+        Ok(action.with_reasoning(&format!(
+            "Capping the existing {} at {}",
+            target.display_name, ceiling
+        )))
+    }
+
+    /// Cap the output with the last limiter in the chain, or a new one
+    /// when there is none
+    fn output_ceiling(
+        limit: &ExtractedParam,
+        refs: &[EffectRef],
+        chain: &EffectChain,
+    ) -> std::result::Result<AgentAction, String> {
+        if let Some(limiter) = refs.iter().rev().find(|e| e.effect_type == "limiter") {
+            return Self::cap(limit, limiter.clone(), chain);
+        }
+
+        let limiter = EffectRef {
+            id: generate_effect_id("limiter", |id| refs.iter().any(|e| e.id == id)),
+            effect_type: "limiter".to_string(),
+            display_name: "Limiter".to_string(),
+            chain_index: refs.len(),
+        };
+        Ok(AgentAction::new(
+            ActionType::Add,
+            ToolType::Dsp,
+            &format!("Added a limiter with a {} dB ceiling", limit.value),
+        )
+        .with_changes(vec![ParameterChange {
+            effect_name: limiter.display_name.clone(),
+            param: "ceiling_db".to_string(),
+            old_value: Value::Null,
+            new_value: json!(limit.value),
+        }])
+        .with_reasoning("No limiter in the chain to cap the output with")
+        .with_effect(limiter))
+    }
+}
+
+/// Offset just past the first whole-word occurrence of `phrase`
+fn find_phrase(prompt: &str, phrase: &str) -> Option<usize> {
+    let boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+    prompt.match_indices(phrase).find_map(|(at, _)| {
+        let end = at + phrase.len();
+        (boundary(prompt[..at].chars().next_back()) && boundary(prompt[end..].chars().next()))
+            .then_some(end)
+    })
+}
+
+/// The amount parameter of an effect, its current value and its "off" value
+fn amount_of(
+    target: &EffectRef,
+    chain: &EffectChain,
+) -> std::result::Result<(&'static str, f64, f64), String> {
+    let &(_, param, neutral) = AMOUNT_PARAMS
+        .iter()
+        .find(|(effect_type, _, _)| *effect_type == target.effect_type)
+        .ok_or_else(|| {
+            format!(
+                "I can't tell how to turn down the {}; adjust its parameters directly",
+                target.display_name
+            )
+        })?;
+    let current = chain
+        .get(&target.id)
+        .and_then(|effect| effect.get_param(param))
+        .and_then(|value| value.as_f64())
+        .ok_or_else(|| format!("The {} has no {} to change", target.display_name, param))?;
+    Ok((param, current, neutral))
+}
+
+/// A limit in the units of `param`, if it can be one
+fn ceiling_value(param: &str, limit: &ExtractedParam) -> Option<f64> {
+    let value = limit.value as f64;
+    match (param, limit.param_type.as_str()) {
+        ("ceiling_db", "gain" | "value") | ("ratio", "ratio" | "value") => Some(value),
+        ("ceiling_db" | "ratio", _) => None,
+        // Everything else is a 0-1 amount; "at most 20" means 20%
+        (_, "level") => Some(value),
+        (_, "value") => Some(if value > 1.0 { value / 100.0 } else { value }),
+        _ => None,
+    }
+}
+
+/// A one-parameter change to an existing effect
+fn modify(target: &EffectRef, param: &str, old: f64, new: f64) -> AgentAction {
+    AgentAction::new(
+        ActionType::Modify,
+        ToolType::Dsp,
+        &format!(
+            "Set {} {} from {:.2} to {:.2}",
+            target.display_name, param, old, new
+        ),
+    )
+    .with_effect(target.clone())
+    .with_changes(vec![ParameterChange {
+        effect_name: target.display_name.clone(),
+        param: param.to_string(),
+        old_value: json!(old),
+        new_value: json!(new),
+    }])
 }
 
 #[cfg(test)]
@@ -380,6 +736,187 @@ mod tests {
         assert!(intent2.is_complex);
     }
 
+    fn chain_of(effects: &[(&str, &str)]) -> EffectChain {
+        let mut chain = EffectChain::new();
+        for (effect_type, id) in effects {
+            let mut effect = crate::dsp::create_effect(effect_type).unwrap();
+            effect.set_id(id.to_string());
+            chain.add(effect);
+        }
+        chain
+    }
+
+    fn populated_chain() -> EffectChain {
+        chain_of(&[
+            ("compressor", "compressor-1"),
+            ("delay", "delay-1"),
+            ("reverb", "reverb-1"),
+            ("limiter", "limiter-1"),
+        ])
+    }
+
+    fn plan(prompt: &str, chain: &EffectChain) -> Option<std::result::Result<AgentAction, String>> {
+        IntentAnalyzer::plan_edit(&Intent::analyze(prompt), &ConversationContext::new(), chain)
+    }
+
+    fn single_change(action: &AgentAction) -> (&str, f64, f64) {
+        assert_eq!(action.parameter_changes.len(), 1);
+        let change = &action.parameter_changes[0];
+        (
+            change.param.as_str(),
+            change.old_value.as_f64().unwrap_or(f64::NAN),
+            change.new_value.as_f64().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_edit_intent_detection() {
+        assert_eq!(Intent::analyze("remove the delay").edit, EditIntent::Remove);
+        assert_eq!(Intent::analyze("no more reverb").edit, EditIntent::Remove);
+        assert_eq!(Intent::analyze("lose the reverb").edit, EditIntent::Remove);
+        assert_eq!(Intent::analyze("less reverb").edit, EditIntent::Reduce);
+        assert_eq!(Intent::analyze("add reverb").edit, EditIntent::Add);
+        // Whole words only
+        assert_eq!(Intent::analyze("export it lossless").edit, EditIntent::Add);
+        // A ceiling without a value never removes anything
+        assert_eq!(
+            Intent::analyze("no more than a touch of reverb").edit,
+            EditIntent::Add
+        );
+        assert_eq!(
+            Intent::analyze("turn down the reverb, no more than a touch").edit,
+            EditIntent::Reduce
+        );
+        assert_eq!(
+            Intent::analyze("no more delay, and no more than a touch of reverb").edit,
+            EditIntent::Remove
+        );
+
+        match Intent::analyze("no more than -1 dB please").edit {
+            EditIntent::Ceiling(limit) => {
+                assert_eq!(limit.param_type, "gain");
+                assert_eq!(limit.value, -1.0);
+            }
+            other => panic!("expected a ceiling, got {:?}", other),
+        }
+        match Intent::analyze("reverb at most 20%").edit {
+            EditIntent::Ceiling(limit) => {
+                assert_eq!(limit.param_type, "level");
+                assert!((limit.value - 0.2).abs() < 1e-6);
+            }
+            other => panic!("expected a ceiling, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_remove_deletes_existing_effect() {
+        let action = plan("remove the delay", &populated_chain())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(action.action_type, ActionType::Remove);
+        assert_eq!(action.affected_effect.unwrap().id, "delay-1");
+        assert!(action.parameter_changes.is_empty());
+    }
+
+    #[test]
+    fn test_less_reduces_existing_wet_level() {
+        let action = plan("less reverb", &populated_chain()).unwrap().unwrap();
+
+        assert_eq!(action.action_type, ActionType::Modify);
+        assert_eq!(action.affected_effect.as_ref().unwrap().id, "reverb-1");
+        let (param, old, new) = single_change(&action);
+        assert_eq!(param, "wet_level");
+        assert!((old - 0.3).abs() < 1e-6);
+        assert!((new - 0.15).abs() < 1e-6);
+
+        // "a bit" moves it less far
+        let action = plan("a bit less reverb", &populated_chain())
+            .unwrap()
+            .unwrap();
+        let (_, _, new) = single_change(&action);
+        assert!((new - 0.21).abs() < 1e-6);
+
+        // "less of it" follows the conversation
+        let mut context = ConversationContext::new();
+        context.add_agent_message_with_action(
+            "Added delay",
+            AgentAction::new(ActionType::Add, ToolType::Dsp, "Added delay").with_effect(
+                EffectRef {
+                    id: "delay-1".to_string(),
+                    effect_type: "delay".to_string(),
+                    display_name: "Delay".to_string(),
+                    chain_index: 1,
+                },
+            ),
+        );
+        let action =
+            IntentAnalyzer::plan_edit(&Intent::analyze("less of it"), &context, &populated_chain())
+                .unwrap()
+                .unwrap();
+        assert_eq!(action.affected_effect.unwrap().id, "delay-1");
+    }
+
+    #[test]
+    fn test_ceiling_caps_existing_effect() {
+        let action = plan("keep the reverb at no more than 10%", &populated_chain())
+            .unwrap()
+            .unwrap();
+        assert_eq!(action.affected_effect.as_ref().unwrap().id, "reverb-1");
+        let (param, _, new) = single_change(&action);
+        assert_eq!(param, "wet_level");
+        assert!((new - 0.1).abs() < 1e-6);
+
+        // A ceiling above the current value changes nothing
+        let action = plan("reverb at most 50%", &populated_chain())
+            .unwrap()
+            .unwrap();
+        assert!(action.parameter_changes.is_empty());
+
+        // A bare dB ceiling goes to the limiter already in the chain
+        let action = plan("no louder than -3 dB", &populated_chain())
+            .unwrap()
+            .unwrap();
+        assert_eq!(action.action_type, ActionType::Modify);
+        assert_eq!(action.affected_effect.as_ref().unwrap().id, "limiter-1");
+        let (param, old, new) = single_change(&action);
+        assert_eq!(param, "ceiling_db");
+        assert_eq!((old, new), (-1.0, -3.0));
+
+        // ... or to a new one
+        let chain = chain_of(&[("reverb", "reverb-1")]);
+        let action = plan("no more than -2 dB", &chain).unwrap().unwrap();
+        assert_eq!(action.action_type, ActionType::Add);
+        assert_eq!(action.affected_effect.as_ref().unwrap().id, "limiter-1");
+        let (param, _, new) = single_change(&action);
+        assert_eq!(param, "ceiling_db");
+        assert_eq!(new, -2.0);
+
+        // Units have to fit the parameter
+        assert!(plan("reverb no more than -3 dB", &populated_chain())
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_edits_that_do_not_target_the_chain() {
+        let chain = populated_chain();
+
+        // Additive and effect-less prompts go through tool selection
+        assert!(plan("add reverb", &chain).is_none());
+        assert!(plan("remove noise", &chain).is_none());
+        assert!(plan("reduce the harsh frequencies", &chain).is_none());
+
+        // Named effects that aren't there, or aren't unique, are errors
+        let err = plan("remove the chorus", &chain).unwrap().unwrap_err();
+        assert!(err.contains("no chorus"), "{}", err);
+        let doubled = chain_of(&[("delay", "delay-1"), ("delay", "delay-2")]);
+        let err = plan("remove the delay", &doubled).unwrap().unwrap_err();
+        assert!(err.contains("Which one"), "{}", err);
+        let action = plan("remove the second delay", &doubled).unwrap().unwrap();
+        assert_eq!(action.affected_effect.unwrap().id, "delay-2");
+    }
+
     #[test]
     fn test_effect_extraction() {
         let intent = Intent::analyze("add compression and reverb");
@@ -7,6 +7,7 @@
 //! - Conversation context management
 //! - Reference resolution
 //! - Undo/redo management
+//! - Auto-tuning a chain towards a target sound
 //! - Explanation generation
 
 mod context;
@@ -14,19 +15,30 @@ mod decision;
 mod explain;
 mod intent;
 mod reference;
+mod refine;
 mod safety;
 mod undo;
 
 pub use context::{
     ActionType, AgentAction, ConversationContext, EffectFocus, EffectRef, Message, MessageRole,
-    ModifyOrAdd, ParameterChange, UserPreferences,
+    ModifyOrAdd, ParameterChange, PendingClarification, UserPreferences,
+    DEFAULT_MAX_HISTORY_MESSAGES,
 };
-pub use decision::{Agent, AgentResponse, ToolDecision, ToolType};
-pub use explain::{explain_full_chain, explain_last_action};
-pub use intent::{Intent, IntentAnalyzer};
-pub use reference::resolve_reference;
+pub use decision::{
+    confidence, intensity_params, Agent, AgentResponse, DspApproximation, ModeRoute,
+    NeuralFallback, ProcessingMode, ToolDecision, ToolType,
+};
+pub use explain::{
+    explain_full_chain, explain_last_action, render_flow_diagram, render_level_summary,
+};
+pub use intent::{EditIntent, Intent, IntentAnalyzer};
+pub use reference::{
+    effect_refs_from_layer2, parse_intensity_modifier, resolve_in_chain, resolve_marker,
+    resolve_reference, IntensityModifier, ResolvedReference,
+};
+pub use refine::Refinement;
 pub use safety::{
-    AudioAnalysis, RecommendationPriority, SafetyCheckResult, SafetyChecker, SafetyIssue,
-    SafetyMitigation, SafetyRecommendation,
+    AudioAnalysis, HeadroomReport, RecommendationPriority, SafetyCheckResult, SafetyChecker,
+    SafetyIssue, SafetyMitigation, SafetyRecommendation,
 };
 pub use undo::{UndoManager, UndoableAction};
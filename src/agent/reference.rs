@@ -4,6 +4,8 @@
 //! Implements §7.2 from the spec.
 
 use super::context::{ConversationContext, EffectRef};
+use crate::engine::Marker;
+use crate::layers::Layer2;
 
 /// Known effect types for reference resolution
 const EFFECT_TYPES: &[&str] = &[
@@ -14,22 +16,51 @@ const EFFECT_TYPES: &[&str] = &[
     "reverb",
     "delay",
     "echo",
+    "haas",
     "gate",
+    "expander",
     "limiter",
     "saturation",
     "distortion",
+    "ring-mod",
+    "tremolo",
+    "wah",
+    "pitch-shifter",
     "chorus",
     "flanger",
     "phaser",
 ];
 
+/// Ordinal words and their 1-based positions
+const ORDINALS: &[(&str, usize)] = &[
+    ("first", 1),
+    ("1st", 1),
+    ("second", 2),
+    ("2nd", 2),
+    ("third", 3),
+    ("3rd", 3),
+    ("fourth", 4),
+    ("4th", 4),
+    ("fifth", 5),
+    ("5th", 5),
+    ("sixth", 6),
+    ("6th", 6),
+    ("seventh", 7),
+    ("7th", 7),
+    ("eighth", 8),
+    ("8th", 8),
+];
+
 /// Canonical effect type mapping
 fn canonicalize_effect_type(effect_type: &str) -> &'static str {
     match effect_type {
-        "equalizer" => "eq",
+        "equalizer" | "parametric-eq" | "parametric_eq" | "graphic-eq" | "graphic_eq" => "eq",
         "compression" => "compressor",
         "echo" => "delay",
         "distortion" => "saturation",
+        "ring_mod" => "ring-mod",
+        "auto-wah" | "auto_wah" => "wah",
+        "pitch_shifter" | "pitch-shift" => "pitch-shifter",
         _ => {
             // Return static str for known types
             for &t in EFFECT_TYPES {
@@ -60,10 +91,60 @@ pub enum ResolvedReference {
     /// Resolved to "explain last action"
     ExplainLast,
 
+    /// Resolved to a single band of an EQ ("the loudest band")
+    Band {
+        effect: EffectRef,
+        band_index: usize,
+    },
+
+    /// Several effects match; the user needs to pick one
+    Ambiguous(Vec<EffectRef>),
+
+    /// An ordinal pointed past the end of the matching effects
+    OrdinalOutOfRange {
+        ordinal: usize,
+        effect_type: Option<String>,
+        available: usize,
+    },
+
     /// Could not resolve
     Unresolved,
 }
 
+impl ResolvedReference {
+    /// User-facing explanation when the reference could not be resolved
+    /// to a single target
+    pub fn error_message(&self) -> Option<String> {
+        match self {
+            Self::OrdinalOutOfRange {
+                ordinal,
+                effect_type,
+                available,
+            } => {
+                let what = effect_type.as_deref().unwrap_or("effect");
+                Some(format!(
+                    "There is no #{} {} - the chain only has {} {}{}",
+                    ordinal,
+                    what,
+                    available,
+                    what,
+                    if *available == 1 { "" } else { "s" }
+                ))
+            }
+            Self::Ambiguous(candidates) => Some(format!(
+                "Which one did you mean? {}",
+                candidates
+                    .iter()
+                    .map(|e| format!("{} (position {})", e.display_name, e.chain_index + 1))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Self::Unresolved => Some("I couldn't tell which effect you meant".to_string()),
+            _ => None,
+        }
+    }
+}
+
 /// Resolve a reference in the user's prompt
 ///
 /// # Arguments
@@ -97,41 +178,221 @@ pub fn resolve_reference(
         return ResolvedReference::ExplainLast;
     }
 
+    let words = tokenize(&ref_lower);
+    let ordinal = parse_ordinal(&words);
+
     // Check for specific effect type reference ("the EQ", "that compressor")
-    for &effect_type in EFFECT_TYPES {
-        if ref_lower.contains(effect_type) {
-            let canonical = canonicalize_effect_type(effect_type);
-            if let Some(effect) = find_most_recent_effect_by_type(context, dsp_chain, canonical) {
-                return ResolvedReference::Effect(effect);
-            }
+    if let Some(canonical) = find_effect_type(&words) {
+        let candidates: Vec<&EffectRef> = dsp_chain
+            .iter()
+            .filter(|e| canonicalize_effect_type(&e.effect_type.to_lowercase()) == canonical)
+            .collect();
+
+        // "the first EQ", "the last compressor"
+        if let Some(ordinal) = ordinal {
+            return pick_ordinal(&candidates, ordinal, Some(canonical));
         }
-    }
 
-    // Check for ordinal reference ("first effect", "last one")
-    if ref_lower.contains("first") {
-        if let Some(effect) = dsp_chain.first() {
-            return ResolvedReference::Effect(effect.clone());
+        // "that delay" / "the EQ" - prefer whatever the conversation last touched
+        if let Some(effect) = find_most_recent_effect_by_type(context, dsp_chain, canonical) {
+            return ResolvedReference::Effect(effect);
         }
+
+        return match candidates.as_slice() {
+            [] => ResolvedReference::Unresolved,
+            [only] => ResolvedReference::Effect((*only).clone()),
+            _ => ResolvedReference::Ambiguous(candidates.into_iter().cloned().collect()),
+        };
     }
 
-    if ref_lower.contains("last") {
-        if let Some(effect) = dsp_chain.last() {
-            return ResolvedReference::Effect(effect.clone());
-        }
+    // Check for ordinal reference ("first effect", "last one")
+    if let Some(ordinal) = ordinal {
+        let candidates: Vec<&EffectRef> = dsp_chain.iter().collect();
+        return pick_ordinal(&candidates, ordinal, None);
     }
 
     // Check for generic reference ("it", "that", "this")
     if is_generic_reference(&ref_lower) {
-        if let Some(action) = context.last_action() {
-            if let Some(effect) = &action.affected_effect {
-                return ResolvedReference::Effect(effect.clone());
-            }
+        if let Some(effect) = most_recently_referenced(context) {
+            return ResolvedReference::Effect(effect);
         }
     }
 
     ResolvedReference::Unresolved
 }
 
+/// Find the timeline marker a prompt refers to ("the chorus marker",
+/// "jump to verse 2").
+///
+/// A marker matches when all words of its name appear consecutively in
+/// the prompt, ignoring case; the longest matching name wins, so
+/// "Verse 2" beats "Verse".
+pub fn resolve_marker<'a>(prompt: &str, markers: &'a [Marker]) -> Option<&'a Marker> {
+    let prompt_lower = prompt.to_lowercase();
+    let words = tokenize(&prompt_lower);
+
+    markers
+        .iter()
+        .filter_map(|marker| {
+            let name_lower = marker.name.to_lowercase();
+            let name_words = tokenize(&name_lower);
+            let found = !name_words.is_empty()
+                && words
+                    .windows(name_words.len())
+                    .any(|window| window == name_words.as_slice());
+            found.then_some((name_words.len(), marker))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, marker)| marker)
+}
+
+/// Resolve a reference against the live Layer 2 chain.
+///
+/// Adds band-level targeting ("the loudest band") on top of
+/// [`resolve_reference`], which needs effect parameters to answer.
+pub fn resolve_in_chain(
+    reference: &str,
+    context: &ConversationContext,
+    layer2: &Layer2,
+) -> ResolvedReference {
+    let dsp_chain = effect_refs_from_layer2(layer2);
+    let ref_lower = reference.to_lowercase();
+
+    if !ref_lower.contains("loudest band") {
+        return resolve_reference(reference, context, &dsp_chain);
+    }
+
+    // Narrow to a specific EQ if the phrase names one ("the loudest band of the first EQ")
+    let eqs: Vec<EffectRef> = match resolve_reference(reference, context, &dsp_chain) {
+        ResolvedReference::Effect(effect) => vec![effect],
+        ResolvedReference::OrdinalOutOfRange { .. } => {
+            return resolve_reference(reference, context, &dsp_chain)
+        }
+        _ => dsp_chain
+            .iter()
+            .filter(|e| canonicalize_effect_type(&e.effect_type.to_lowercase()) == "eq")
+            .cloned()
+            .collect(),
+    };
+
+    let mut loudest: Option<(EffectRef, usize, f64)> = None;
+    for eq in &eqs {
+        let Some(bands) = layer2
+            .get_effect(&eq.id)
+            .and_then(|state| state.get_param("bands"))
+            .and_then(|bands| bands.as_array())
+        else {
+            continue;
+        };
+
+        for (band_index, band) in bands.iter().enumerate() {
+            let gain = band.get("gain_db").and_then(|g| g.as_f64()).unwrap_or(0.0);
+            if loudest.as_ref().is_none_or(|(_, _, best)| gain > *best) {
+                loudest = Some((eq.clone(), band_index, gain));
+            }
+        }
+    }
+
+    match loudest {
+        Some((effect, band_index, _)) => ResolvedReference::Band { effect, band_index },
+        None => ResolvedReference::Unresolved,
+    }
+}
+
+/// Build effect references for every effect in a Layer 2 chain
+pub fn effect_refs_from_layer2(layer2: &Layer2) -> Vec<EffectRef> {
+    layer2
+        .iter()
+        .enumerate()
+        .map(|(chain_index, state)| EffectRef {
+            id: state.id.clone(),
+            effect_type: state.effect_type.clone(),
+            display_name: state.id.clone(),
+            chain_index,
+        })
+        .collect()
+}
+
+/// The canonical effect type a prompt names as a whole word, if any
+pub(crate) fn named_effect_type(prompt: &str) -> Option<&'static str> {
+    let prompt_lower = prompt.to_lowercase();
+    find_effect_type(&tokenize(&prompt_lower))
+}
+
+/// Split a lowercase reference into bare words
+fn tokenize(ref_lower: &str) -> Vec<&str> {
+    ref_lower
+        .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Find an effect type named in the words (singular or plural)
+fn find_effect_type(words: &[&str]) -> Option<&'static str> {
+    words.iter().find_map(|word| {
+        let singular = word.strip_suffix('s').unwrap_or(word);
+        EFFECT_TYPES
+            .iter()
+            .find(|&&t| t == *word || t == singular)
+            .map(|&t| canonicalize_effect_type(t))
+    })
+}
+
+/// Which position an ordinal refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ordinal {
+    /// 1-based position from the start of the chain
+    Nth(usize),
+    /// The last matching effect
+    Last,
+}
+
+/// Parse an ordinal ("first", "2nd", "last") from the words
+fn parse_ordinal(words: &[&str]) -> Option<Ordinal> {
+    words.iter().find_map(|word| {
+        if *word == "last" {
+            return Some(Ordinal::Last);
+        }
+        ORDINALS
+            .iter()
+            .find(|(name, _)| name == word)
+            .map(|&(_, n)| Ordinal::Nth(n))
+    })
+}
+
+/// Pick an effect by ordinal from the candidates
+fn pick_ordinal(
+    candidates: &[&EffectRef],
+    ordinal: Ordinal,
+    effect_type: Option<&str>,
+) -> ResolvedReference {
+    let picked = match ordinal {
+        Ordinal::Nth(n) => candidates.get(n - 1),
+        Ordinal::Last => candidates.last(),
+    };
+
+    match picked {
+        Some(effect) => ResolvedReference::Effect((*effect).clone()),
+        None => ResolvedReference::OrdinalOutOfRange {
+            ordinal: match ordinal {
+                Ordinal::Nth(n) => n,
+                Ordinal::Last => 1,
+            },
+            effect_type: effect_type.map(str::to_string),
+            available: candidates.len(),
+        },
+    }
+}
+
+/// The effect most recently touched by an agent action
+fn most_recently_referenced(context: &ConversationContext) -> Option<EffectRef> {
+    context
+        .recent_actions
+        .iter()
+        .rev()
+        .find_map(|a| a.affected_effect.clone())
+}
+
 /// Check if reference is about undoing
 fn is_undo_reference(ref_lower: &str) -> bool {
     ref_lower.contains("undo") || ref_lower == "go back" || ref_lower == "revert"
@@ -165,30 +426,24 @@ fn is_generic_reference(ref_lower: &str) -> bool {
     words.contains(&"it") || words.contains(&"that") || words.contains(&"this")
 }
 
-/// Find the most recent effect of a given type
+/// Find the effect of a given type the conversation touched most recently
 fn find_most_recent_effect_by_type(
     context: &ConversationContext,
     dsp_chain: &[EffectRef],
     effect_type: &str,
 ) -> Option<EffectRef> {
-    // First check recent actions for this effect type
     for action in context.recent_actions.iter().rev() {
         if let Some(ref effect) = action.affected_effect {
-            if effect.effect_type == effect_type {
-                // Verify it's still in the chain
-                if dsp_chain.iter().any(|e| e.id == effect.id) {
-                    return Some(effect.clone());
+            if canonicalize_effect_type(&effect.effect_type.to_lowercase()) == effect_type {
+                // Verify it's still in the chain (and pick up its current position)
+                if let Some(current) = dsp_chain.iter().find(|e| e.id == effect.id) {
+                    return Some(current.clone());
                 }
             }
         }
     }
 
-    // Fall back to finding any effect of this type in the chain
-    dsp_chain
-        .iter()
-        .rev()
-        .find(|e| e.effect_type == effect_type)
-        .cloned()
+    None
 }
 
 /// Parse intensity modifiers from reference
@@ -235,6 +490,39 @@ mod tests {
     use crate::agent::context::{AgentAction, ActionType};
     use crate::agent::decision::ToolType;
 
+    fn marker(name: &str, sample: u64) -> Marker {
+        Marker {
+            name: name.to_string(),
+            sample,
+        }
+    }
+
+    #[test]
+    fn test_resolve_marker() {
+        let markers = vec![
+            marker("Intro", 0),
+            marker("Verse", 1000),
+            marker("Verse 2", 5000),
+            marker("Chorus", 3000),
+        ];
+
+        assert_eq!(
+            resolve_marker("loop the chorus marker", &markers)
+                .unwrap()
+                .name,
+            "Chorus"
+        );
+        assert_eq!(
+            resolve_marker("jump to verse 2", &markers).unwrap().name,
+            "Verse 2"
+        );
+        assert_eq!(
+            resolve_marker("start at the verse", &markers).unwrap().name,
+            "Verse"
+        );
+        assert!(resolve_marker("add more reverb", &markers).is_none());
+    }
+
     fn make_effect(id: &str, effect_type: &str, index: usize) -> EffectRef {
         EffectRef {
             id: id.to_string(),
@@ -302,6 +590,137 @@ mod tests {
         }
     }
 
+    fn two_eqs_and_compressor() -> Vec<EffectRef> {
+        vec![
+            make_effect("eq-1", "eq", 0),
+            make_effect("comp-1", "compressor", 1),
+            make_effect("eq-2", "eq", 2),
+        ]
+    }
+
+    #[test]
+    fn test_resolve_ordinal_with_type() {
+        let ctx = ConversationContext::new();
+        let dsp_chain = two_eqs_and_compressor();
+
+        match resolve_reference("the first EQ", &ctx, &dsp_chain) {
+            ResolvedReference::Effect(e) => assert_eq!(e.id, "eq-1"),
+            other => panic!("Expected first EQ, got {:?}", other),
+        }
+
+        match resolve_reference("The Second eq", &ctx, &dsp_chain) {
+            ResolvedReference::Effect(e) => assert_eq!(e.id, "eq-2"),
+            other => panic!("Expected second EQ, got {:?}", other),
+        }
+
+        match resolve_reference("the last compressor", &ctx, &dsp_chain) {
+            ResolvedReference::Effect(e) => assert_eq!(e.id, "comp-1"),
+            other => panic!("Expected compressor, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_ordinal_out_of_range() {
+        let ctx = ConversationContext::new();
+        let dsp_chain = two_eqs_and_compressor();
+
+        let result = resolve_reference("the third EQ", &ctx, &dsp_chain);
+        match &result {
+            ResolvedReference::OrdinalOutOfRange {
+                ordinal,
+                effect_type,
+                available,
+            } => {
+                assert_eq!(*ordinal, 3);
+                assert_eq!(effect_type.as_deref(), Some("eq"));
+                assert_eq!(*available, 2);
+            }
+            other => panic!("Expected out of range, got {:?}", other),
+        }
+        assert!(result.error_message().unwrap().contains("only has 2"));
+    }
+
+    #[test]
+    fn test_resolve_ambiguous_type() {
+        let ctx = ConversationContext::new();
+        let dsp_chain = two_eqs_and_compressor();
+
+        match resolve_reference("the EQ", &ctx, &dsp_chain) {
+            ResolvedReference::Ambiguous(candidates) => {
+                let ids: Vec<&str> = candidates.iter().map(|e| e.id.as_str()).collect();
+                assert_eq!(ids, vec!["eq-1", "eq-2"]);
+            }
+            other => panic!("Expected ambiguity, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_that_uses_most_recent_reference() {
+        let mut ctx = ConversationContext::new();
+        let dsp_chain = two_eqs_and_compressor();
+
+        let action = AgentAction::new(ActionType::Modify, ToolType::Dsp, "Cut mud")
+            .with_effect(dsp_chain[2].clone());
+        ctx.add_agent_message_with_action("Cut 300Hz", action);
+        let action = AgentAction::new(ActionType::Modify, ToolType::Dsp, "Slower attack")
+            .with_effect(dsp_chain[1].clone());
+        ctx.add_agent_message_with_action("Slowed the attack", action);
+
+        match resolve_reference("THAT EQ", &ctx, &dsp_chain) {
+            ResolvedReference::Effect(e) => assert_eq!(e.id, "eq-2"),
+            other => panic!("Expected recent EQ, got {:?}", other),
+        }
+
+        match resolve_reference("make that punchier", &ctx, &dsp_chain) {
+            ResolvedReference::Effect(e) => assert_eq!(e.id, "comp-1"),
+            other => panic!("Expected compressor, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_eq_not_matched_inside_words() {
+        let ctx = ConversationContext::new();
+        let dsp_chain = two_eqs_and_compressor();
+
+        let result = resolve_reference("lower the frequency", &ctx, &dsp_chain);
+        assert!(matches!(result, ResolvedReference::Unresolved));
+    }
+
+    #[test]
+    fn test_resolve_loudest_band() {
+        use crate::layers::EffectState;
+
+        let ctx = ConversationContext::new();
+        let mut layer2 = Layer2::new();
+        layer2.add_effect(EffectState::with_params(
+            "eq-1",
+            "eq",
+            serde_json::json!({"bands": [{"gain_db": 2.0}, {"gain_db": -4.0}]}),
+        ));
+        layer2.add_effect(EffectState::new("comp-1", "compressor"));
+        layer2.add_effect(EffectState::with_params(
+            "eq-2",
+            "eq",
+            serde_json::json!({"bands": [{"gain_db": 1.0}, {"gain_db": 5.5}]}),
+        ));
+
+        match resolve_in_chain("the loudest band", &ctx, &layer2) {
+            ResolvedReference::Band { effect, band_index } => {
+                assert_eq!(effect.id, "eq-2");
+                assert_eq!(band_index, 1);
+            }
+            other => panic!("Expected band, got {:?}", other),
+        }
+
+        match resolve_in_chain("the loudest band of the first EQ", &ctx, &layer2) {
+            ResolvedReference::Band { effect, band_index } => {
+                assert_eq!(effect.id, "eq-1");
+                assert_eq!(band_index, 0);
+            }
+            other => panic!("Expected band, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_resolve_undo() {
         let ctx = ConversationContext::new();
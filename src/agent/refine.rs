@@ -0,0 +1,495 @@
+//! Parameter auto-tuning towards a target sound
+//!
+//! "Make it sound like X": a three-band EQ and an output gain are tuned by
+//! coordinate descent so the processed source moves closer to a target
+//! recording. Distance is the tonal difference (third-octave band levels
+//! with their mean removed, as in the reference comparison) plus the
+//! integrated loudness difference. The search is gradient-free: each
+//! iteration nudges every parameter up and down by the current step and
+//! keeps whatever helps, halving the step when nothing does.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::decision::Agent;
+use super::safety::thresholds;
+use super::undo::{EffectState, UndoableAction};
+use crate::dsp::{self, Effect};
+use crate::engine::compare::{band_levels, downmix};
+use crate::engine::integrated_loudness;
+use crate::error::{NuevaError, Result};
+
+/// First step of the search, in dB
+const INITIAL_STEP_DB: f32 = 6.0;
+
+/// The search has stalled once the step falls below this (dB)
+const MIN_STEP_DB: f32 = 0.25;
+
+/// Smallest drop in distance that counts as an improvement
+const MIN_IMPROVEMENT: f32 = 0.01;
+
+/// Largest boost or cut of an EQ band (dB)
+const MAX_BAND_GAIN_DB: f32 = 18.0;
+
+/// Range of the output gain (dB)
+const OUTPUT_GAIN_RANGE_DB: (f32, f32) = (-24.0, 12.0);
+
+/// The tuned EQ bands: type, frequency and Q
+const REFINE_BANDS: [(dsp::FilterType, f32, f32); 3] = [
+    (dsp::FilterType::LowShelf, 200.0, 0.707),
+    (dsp::FilterType::Peak, 1000.0, 0.7),
+    (dsp::FilterType::HighShelf, 5000.0, 0.707),
+];
+
+/// IDs of the effects the refinement adds
+const EQ_ID: &str = "refine-eq";
+const GAIN_ID: &str = "refine-gain";
+
+/// Result of [`Agent::refine_to_target`]
+pub struct Refinement {
+    /// The tuned chain: the EQ followed by the output gain
+    pub chain: dsp::EffectChain,
+
+    /// Distance before the first iteration, then after each one
+    pub distances: Vec<f32>,
+
+    /// Whether the search stopped because improvement stalled, rather
+    /// than running out of iterations
+    pub stalled: bool,
+
+    /// The chain as undoable actions, one per effect, adding it to the
+    /// chain it refines
+    pub actions: Vec<UndoableAction>,
+}
+
+impl Refinement {
+    /// Distance of the tuned output from the target
+    pub fn final_distance(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Number of iterations run
+    pub fn iterations(&self) -> usize {
+        self.distances.len().saturating_sub(1)
+    }
+}
+
+impl Agent {
+    /// Tune an EQ and output gain so `source` sounds like `target`
+    ///
+    /// The tuned effects go at the end of `current`, the chain `source`
+    /// has already been processed by; the undo actions start from it. If
+    /// `current` holds an earlier refinement, `source` already carries it,
+    /// so the search tunes a correction on top and adds it to the earlier
+    /// settings, updating those effects rather than adding a second set.
+    ///
+    /// Runs at most `max_iters` iterations of coordinate descent and stops
+    /// early once improvement stalls. Settings whose output would go over
+    /// the true peak ceiling are never taken, so the result doesn't clip;
+    /// if the source itself is over the ceiling, the search starts from a
+    /// gain that brings it under.
+    pub fn refine_to_target(
+        &self,
+        current: &dsp::EffectChain,
+        source: &dsp::AudioBuffer,
+        target: &dsp::AudioBuffer,
+        max_iters: usize,
+    ) -> Result<Refinement> {
+        if source.num_samples() == 0 || target.num_samples() == 0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "target".to_string(),
+                value: "empty audio".to_string(),
+                expected: "a source and target with samples".to_string(),
+            });
+        }
+        let target = Profile::measure(target);
+
+        // Low, mid and high band gains, then the output gain, starting from
+        // an earlier refinement's
+        let previous = previous_params(current);
+        let mut params = previous;
+        let source_peak = dsp::true_peak_db(source);
+        if source_peak > thresholds::TRUE_PEAK_CEILING {
+            params[3] = clamp_param(3, params[3] + thresholds::TRUE_PEAK_CEILING - source_peak);
+        }
+        let mut distance = render(&params, &previous, source)?
+            .map(|output| target.distance(&output))
+            .unwrap_or(f32::INFINITY);
+        let mut distances = vec![distance];
+
+        let mut step = INITIAL_STEP_DB;
+        let mut stalled = false;
+        for _ in 0..max_iters {
+            let mut improved = false;
+            for index in 0..params.len() {
+                for direction in [1.0, -1.0] {
+                    let mut candidate = params;
+                    candidate[index] = clamp_param(index, candidate[index] + direction * step);
+                    if candidate[index] == params[index] {
+                        continue;
+                    }
+                    let Some(output) = render(&candidate, &previous, source)? else {
+                        continue;
+                    };
+                    let candidate_distance = target.distance(&output);
+                    if candidate_distance < distance - MIN_IMPROVEMENT {
+                        params = candidate;
+                        distance = candidate_distance;
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+            distances.push(distance);
+
+            if !improved {
+                step /= 2.0;
+                if step < MIN_STEP_DB {
+                    stalled = true;
+                    break;
+                }
+            }
+        }
+
+        let chain = build_chain(&params, source.sample_rate())?;
+        let actions = chain_actions(current, &chain, &params)?;
+        Ok(Refinement {
+            chain,
+            distances,
+            stalled,
+            actions,
+        })
+    }
+}
+
+/// What the distance is measured on
+struct Profile {
+    /// Third-octave band levels (dB)
+    bands: Vec<(f64, f64)>,
+    /// Integrated loudness (LUFS)
+    lufs: f32,
+}
+
+impl Profile {
+    fn measure(buffer: &dsp::AudioBuffer) -> Self {
+        let engine = buffer.to_engine();
+        Self {
+            bands: band_levels(&downmix(&engine), buffer.sample_rate()),
+            lufs: integrated_loudness(&engine),
+        }
+    }
+
+    /// Tonal distance (RMS band difference once the mean difference is
+    /// removed) plus loudness difference, both in dB
+    ///
+    /// Bands only one side has energy in are ignored; silence against
+    /// audio is infinitely far.
+    fn distance(&self, other: &dsp::AudioBuffer) -> f32 {
+        let other = Self::measure(other);
+        if !self.lufs.is_finite() || !other.lufs.is_finite() {
+            return if self.lufs == other.lufs {
+                0.0
+            } else {
+                f32::INFINITY
+            };
+        }
+
+        let differences: Vec<f64> = self
+            .bands
+            .iter()
+            .filter_map(|(centre, level)| {
+                other
+                    .bands
+                    .iter()
+                    .find(|(c, _)| c == centre)
+                    .map(|(_, other_level)| other_level - level)
+            })
+            .collect();
+        let tonal = if differences.is_empty() {
+            0.0
+        } else {
+            let n = differences.len() as f64;
+            let mean = differences.iter().sum::<f64>() / n;
+            (differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n).sqrt()
+        };
+        tonal as f32 + (other.lufs - self.lufs).abs()
+    }
+}
+
+/// Keep parameter `index` within its range
+fn clamp_param(index: usize, value: f32) -> f32 {
+    if index < REFINE_BANDS.len() {
+        value.clamp(-MAX_BAND_GAIN_DB, MAX_BAND_GAIN_DB)
+    } else {
+        value.clamp(OUTPUT_GAIN_RANGE_DB.0, OUTPUT_GAIN_RANGE_DB.1)
+    }
+}
+
+/// The EQ and output gain for `params`
+fn build_chain(params: &[f32; 4], sample_rate: f64) -> Result<dsp::EffectChain> {
+    let bands = REFINE_BANDS
+        .iter()
+        .zip(params)
+        .map(|(&(filter_type, frequency, q), &gain)| {
+            dsp::EQBand::new(frequency, gain, q, filter_type)
+        })
+        .collect();
+    let mut eq = dsp::ParametricEQ::with_bands(bands)?;
+    eq.set_id(EQ_ID.to_string());
+    let mut gain = dsp::GainEffect::with_gain(params[3])?;
+    gain.set_id(GAIN_ID.to_string());
+
+    let mut chain = dsp::EffectChain::new();
+    chain.add(Box::new(eq));
+    chain.add(Box::new(gain));
+    chain.prepare(sample_rate, 512);
+    Ok(chain)
+}
+
+/// Settings of an earlier refinement in `current`, or all zero
+fn previous_params(current: &dsp::EffectChain) -> [f32; 4] {
+    let mut params = [0.0; 4];
+    if let Some(bands) = current.get(EQ_ID).and_then(|eq| eq.get_param("bands")) {
+        for (param, band) in params
+            .iter_mut()
+            .zip(bands.as_array().into_iter().flatten())
+        {
+            *param = band["gain_db"].as_f64().unwrap_or(0.0) as f32;
+        }
+    }
+    if let Some(gain_db) = current
+        .get(GAIN_ID)
+        .and_then(|gain| gain.get_param("gain_db"))
+        .and_then(|value| value.as_f64())
+    {
+        params[3] = gain_db as f32;
+    }
+    params
+}
+
+/// Process `source`, which already carries `previous`, with the change
+/// from `previous` to `params`, or `None` if the output would go over the
+/// true peak ceiling
+fn render(
+    params: &[f32; 4],
+    previous: &[f32; 4],
+    source: &dsp::AudioBuffer,
+) -> Result<Option<dsp::AudioBuffer>> {
+    let mut change = *params;
+    for (param, previous) in change.iter_mut().zip(previous) {
+        *param -= previous;
+    }
+    let mut chain = build_chain(&change, source.sample_rate())?;
+    let mut output = source.clone();
+    chain.process(&mut output);
+    Ok((dsp::true_peak_db(&output) <= thresholds::TRUE_PEAK_CEILING).then_some(output))
+}
+
+/// One action per effect in `chain`, each adding it to `current` and the
+/// ones before, or updating the effect with its id already there
+fn chain_actions(
+    current: &dsp::EffectChain,
+    chain: &dsp::EffectChain,
+    params: &[f32; 4],
+) -> Result<Vec<UndoableAction>> {
+    let descriptions = [
+        format!(
+            "Refine EQ towards target (low {:+.1} dB, mid {:+.1} dB, high {:+.1} dB)",
+            params[0], params[1], params[2]
+        ),
+        format!("Refine output gain towards target ({:+.1} dB)", params[3]),
+    ];
+
+    let mut states = current
+        .iter()
+        .map(effect_state)
+        .collect::<Result<Vec<_>>>()?;
+    let mut actions = Vec::new();
+    for (effect, description) in chain.iter().zip(descriptions) {
+        let before = states.clone();
+        let state = effect_state(effect)?;
+        match states.iter_mut().find(|s| s.id == state.id) {
+            Some(existing) => *existing = state,
+            None => states.push(state),
+        }
+        actions.push(UndoableAction::new(&description).with_dsp_states(before, states.clone()));
+    }
+    Ok(actions)
+}
+
+/// Undo state of one effect
+fn effect_state(effect: &dyn Effect) -> Result<EffectState> {
+    let json = dsp::effect_to_json(effect)?;
+    let params: HashMap<String, Value> = match json.get("params") {
+        Some(Value::Object(map)) => map.clone().into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    Ok(EffectState {
+        id: effect.id().to_string(),
+        effect_type: effect.effect_type().to_string(),
+        enabled: effect.is_enabled(),
+        params,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Half a second of seeded white noise at 48kHz
+    fn noise(amplitude: f32) -> dsp::AudioBuffer {
+        let mut state = 7u32;
+        let samples: Vec<f32> = (0..24000)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                amplitude * ((state >> 8) as f32 / (1u32 << 23) as f32 - 1.0)
+            })
+            .collect();
+        dsp::AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap()
+    }
+
+    fn empty_chain() -> dsp::EffectChain {
+        dsp::EffectChain::new()
+    }
+
+    #[test]
+    fn test_refine_converges_on_known_eq() {
+        let source = noise(0.2);
+        let mut target = source.clone();
+        build_chain(&[4.0, 0.0, -5.0, -2.0], 48000.0)
+            .unwrap()
+            .process(&mut target);
+
+        let refinement = Agent::new()
+            .refine_to_target(&empty_chain(), &source, &target, 40)
+            .unwrap();
+        let distances = &refinement.distances;
+        assert!(distances.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(
+            refinement.final_distance() < distances[0] * 0.15,
+            "{:?}",
+            distances
+        );
+
+        // Stopped early once the step bottomed out
+        assert!(refinement.stalled);
+        assert!(refinement.iterations() < 40);
+
+        // The chain comes back as one undoable action per effect
+        let actions = &refinement.actions;
+        assert_eq!(actions.len(), 2);
+        assert!(actions[0].dsp_chain_before.is_empty());
+        assert_eq!(actions[0].dsp_chain_after[0].id, EQ_ID);
+        assert_eq!(actions[1].dsp_chain_after.len(), 2);
+        assert_eq!(actions[1].dsp_chain_after[1].effect_type, "gain");
+    }
+
+    #[test]
+    fn test_refine_never_clips() {
+        // A target far louder than the ceiling allows
+        let source = noise(0.3);
+        let mut target = source.clone();
+        for sample in target.samples_mut() {
+            *sample *= 4.0;
+        }
+
+        let mut refinement = Agent::new()
+            .refine_to_target(&empty_chain(), &source, &target, 20)
+            .unwrap();
+        let mut output = source.clone();
+        refinement.chain.process(&mut output);
+        assert!(dsp::true_peak_db(&output) <= thresholds::TRUE_PEAK_CEILING);
+        assert!(refinement.final_distance() < refinement.distances[0]);
+
+        // Stopping after the iteration budget isn't a stall
+        let short = Agent::new()
+            .refine_to_target(&empty_chain(), &source, &target, 1)
+            .unwrap();
+        assert_eq!(short.iterations(), 1);
+        assert!(!short.stalled);
+
+        let empty = dsp::AudioBuffer::from_interleaved(Vec::new(), 1, 48000.0).unwrap();
+        assert!(Agent::new()
+            .refine_to_target(&empty_chain(), &empty, &target, 5)
+            .is_err());
+    }
+
+    #[test]
+    fn test_refine_actions_start_from_current_chain() {
+        let source = noise(0.2);
+        let mut target = source.clone();
+        build_chain(&[3.0, 0.0, 0.0, -1.0], 48000.0)
+            .unwrap()
+            .process(&mut target);
+
+        let mut current = empty_chain();
+        let mut comp = dsp::Compressor::new();
+        comp.set_id("comp-1".to_string());
+        current.add(Box::new(comp));
+
+        let first = Agent::new()
+            .refine_to_target(&current, &source, &target, 5)
+            .unwrap();
+        let ids = |states: &[EffectState]| -> Vec<String> {
+            states.iter().map(|s| s.id.clone()).collect()
+        };
+        assert_eq!(ids(&first.actions[0].dsp_chain_before), ["comp-1"]);
+        assert_eq!(ids(&first.actions[0].dsp_chain_after), ["comp-1", EQ_ID]);
+        assert_eq!(
+            ids(&first.actions[1].dsp_chain_after),
+            ["comp-1", EQ_ID, GAIN_ID]
+        );
+
+        // Refining again updates the earlier refinement's effects in place
+        for effect in first.chain.iter() {
+            current.add(dsp::effect_from_json(&dsp::effect_to_json(effect).unwrap()).unwrap());
+        }
+        let second = Agent::new()
+            .refine_to_target(&current, &source, &target, 5)
+            .unwrap();
+        let current_ids: Vec<String> = current.iter().map(|e| e.id().to_string()).collect();
+        assert_eq!(current_ids.len(), 3);
+        for action in &second.actions {
+            assert_eq!(ids(&action.dsp_chain_before), current_ids);
+            assert_eq!(ids(&action.dsp_chain_after), current_ids);
+        }
+    }
+
+    #[test]
+    fn test_second_refinement_keeps_the_first() {
+        let source = noise(0.2);
+        let mut target = source.clone();
+        build_chain(&[6.0, 0.0, -6.0, -3.0], 48000.0)
+            .unwrap()
+            .process(&mut target);
+        let profile = Profile::measure(&target);
+        let rendered = |chain: &dsp::EffectChain| {
+            let mut chain = chain.try_clone().unwrap();
+            chain.prepare(48000.0, 512);
+            let mut output = source.clone();
+            chain.process(&mut output);
+            output
+        };
+
+        // A short first pass leaves something for the second to do
+        let first = Agent::new()
+            .refine_to_target(&empty_chain(), &source, &target, 1)
+            .unwrap();
+        let once = rendered(&first.chain);
+        let first_distance = profile.distance(&once);
+
+        let second = Agent::new()
+            .refine_to_target(&first.chain, &once, &target, 40)
+            .unwrap();
+        let twice = rendered(&second.chain);
+        let second_distance = profile.distance(&twice);
+        assert!(
+            second_distance <= first_distance,
+            "{} after the second pass, {} after the first",
+            second_distance,
+            first_distance
+        );
+        assert!(second_distance < first_distance * 0.5);
+    }
+}
@@ -2,12 +2,24 @@
 //!
 //! Implements the "Do No Harm" rules from the spec:
 //! - Clipping prevention (auto-limiter)
+//! - Inter-sample clipping detection (true peak) before export
+//! - Headroom prediction: dry-run a chain to see if it will clip, and
+//!   recommend a limiter if it will
 //! - Phase protection (warn if correlation < 0.2)
 //! - Loudness sanity (warn if LUFS > -5)
 //! - Duration validation (output matches input within 0.1s)
+//! - DC offset: suggest (or auto-apply) a 20 Hz high-pass
+//! - Dynamics: warn when the crest factor shows brickwall limiting
+//! - Intentional artifacts from neural processing are not "fixed"
 
 use serde::{Deserialize, Serialize};
 
+use crate::dsp;
+use crate::engine::buffer::{calculate_mean, calculate_peak, calculate_rms, DC_OFFSET_THRESHOLD};
+use crate::engine::loudness_range;
+use crate::error::Result;
+use crate::neural::NeuralContextTracker;
+
 /// Safety thresholds per spec
 pub mod thresholds {
     /// Peak level that triggers clipping warning (dBFS)
@@ -19,6 +31,15 @@ pub mod thresholds {
     /// Default limiter ceiling (dBFS)
     pub const LIMITER_CEILING: f32 = -1.0;
 
+    /// True peak above which inter-sample clipping is reported (dBTP)
+    pub const TRUE_PEAK_LIMIT: f32 = 0.0;
+
+    /// True peak at which inter-sample overs become a high priority (dBTP)
+    pub const TRUE_PEAK_SEVERE: f32 = 1.0;
+
+    /// Limiter ceiling suggested for true peak safety (dBTP)
+    pub const TRUE_PEAK_CEILING: f32 = -1.0;
+
     /// Stereo correlation below which we warn (phase issues)
     pub const PHASE_WARN: f32 = 0.3;
 
@@ -39,6 +60,21 @@ pub mod thresholds {
 
     /// Maximum allowed duration difference (seconds)
     pub const DURATION_TOLERANCE: f32 = 0.1;
+
+    /// DC offset (mean sample value) that makes the fix a medium priority
+    pub const DC_OFFSET_MEDIUM: f32 = 0.05;
+
+    /// DC offset that makes the fix a high priority: a tenth of full
+    /// scale or more
+    pub const DC_OFFSET_SEVERE: f32 = 0.1;
+
+    /// Corner of the high-pass suggested to remove DC offset (Hz)
+    pub const DC_HIGH_PASS_HZ: f32 = 20.0;
+
+    /// Crest factor (peak to RMS) below which audio counts as
+    /// over-compressed (dB). Dynamic mixes sit well above 10 dB and even
+    /// loud masters keep 6 dB or more; a sine is 3 dB and a square 0 dB.
+    pub const CREST_FACTOR_BRICKWALL: f32 = 5.0;
 }
 
 /// Audio analysis results (matches spec §5.5)
@@ -63,9 +99,13 @@ pub struct AudioAnalysis {
     /// Momentary LUFS max
     pub lufs_momentary_max: f32,
 
-    /// Loudness range (LRA) in dB
+    /// Loudness range (LRA per EBU Tech 3342) in LU
     pub dynamic_range_db: f32,
 
+    /// Crest factor (sample peak to RMS) in dB; `None` until measured
+    #[serde(default)]
+    pub crest_factor_db: Option<f32>,
+
     // Clipping
     /// Percentage of samples that are clipped
     pub clip_percentage: f32,
@@ -125,11 +165,33 @@ pub struct AudioAnalysis {
 }
 
 impl AudioAnalysis {
+    /// Measure loudness range and crest factor from `buffer`
+    ///
+    /// Silence has no crest factor and no range.
+    pub fn measure_dynamics(&mut self, buffer: &crate::engine::AudioBuffer) {
+        self.dynamic_range_db = loudness_range(buffer);
+        let crest = calculate_peak(buffer) - calculate_rms(buffer);
+        self.crest_factor_db = crest.is_finite().then_some(crest);
+    }
+
+    /// Check if audio is over-compressed ("brickwalled"): its crest
+    /// factor is below [`thresholds::CREST_FACTOR_BRICKWALL`]
+    pub fn is_brickwalled(&self) -> bool {
+        self.crest_factor_db
+            .is_some_and(|crest| crest < thresholds::CREST_FACTOR_BRICKWALL)
+    }
+
     /// Check if audio has clipping
     pub fn has_clipping(&self) -> bool {
         self.clip_percentage > 0.0 || self.peak_db >= thresholds::CLIPPING_LIMIT
     }
 
+    /// Check if the true peak is over full scale while the samples are not
+    /// (the overs only appear on reconstruction, e.g. in a DAC or codec)
+    pub fn has_intersample_clipping(&self) -> bool {
+        self.true_peak_db > thresholds::TRUE_PEAK_LIMIT && self.peak_db < thresholds::CLIPPING_LIMIT
+    }
+
     /// Check if audio is near clipping
     pub fn is_near_clipping(&self) -> bool {
         self.peak_db > thresholds::CLIPPING_WARN
@@ -209,6 +271,13 @@ impl AudioAnalysis {
             ));
         }
 
+        if let Some(crest) = self.crest_factor_db.filter(|_| self.is_brickwalled()) {
+            issues.push(format!(
+                "Over-compressed: {:.1} dB crest factor, {:.1} LU loudness range",
+                crest, self.dynamic_range_db
+            ));
+        }
+
         if self.is_noisy() {
             issues.push(format!(
                 "Noisy: {:.1} dB noise floor",
@@ -345,6 +414,32 @@ pub enum SafetyMitigation {
         operation: String,
         reason: String,
     },
+
+    /// High-pass filter to remove DC offset
+    HighPass { frequency_hz: i32 },
+}
+
+impl SafetyMitigation {
+    /// The effect that carries out this mitigation, ready to append to a
+    /// chain; `None` for mitigations that aren't an effect
+    pub fn to_effect(&self) -> Option<Box<dyn dsp::Effect>> {
+        match self {
+            SafetyMitigation::AutoLimiter { ceiling_db } => {
+                let mut limiter = dsp::Limiter::new();
+                limiter.set_ceiling_db(*ceiling_db as f32);
+                Some(Box::new(limiter))
+            }
+            SafetyMitigation::HighPass { frequency_hz } => {
+                let band =
+                    dsp::EQBand::high_pass(*frequency_hz as f32, std::f32::consts::FRAC_1_SQRT_2);
+                dsp::ParametricEQ::with_bands(vec![band])
+                    .ok()
+                    .map(|eq| Box::new(eq) as Box<dyn dsp::Effect>)
+            }
+            SafetyMitigation::ReducedIntensity { .. }
+            | SafetyMitigation::SkippedOperation { .. } => None,
+        }
+    }
 }
 
 /// Main safety checker
@@ -354,6 +449,9 @@ pub struct SafetyChecker {
 
     /// Whether to auto-apply mitigations
     auto_mitigate: bool,
+
+    /// Neural context, so intentional artifacts aren't "fixed"
+    neural_context: NeuralContextTracker,
 }
 
 impl SafetyChecker {
@@ -361,6 +459,7 @@ impl SafetyChecker {
         Self {
             analysis: None,
             auto_mitigate: true,
+            neural_context: NeuralContextTracker::new(),
         }
     }
 
@@ -374,6 +473,21 @@ impl SafetyChecker {
         self.auto_mitigate = enable;
     }
 
+    /// Set the neural context consulted for intentional artifacts
+    pub fn set_neural_context(&mut self, context: NeuralContextTracker) {
+        self.neural_context = context;
+    }
+
+    /// Neural context consulted for intentional artifacts
+    pub fn neural_context(&self) -> &NeuralContextTracker {
+        &self.neural_context
+    }
+
+    /// Mutable neural context, e.g. to flag or clear an artifact
+    pub fn neural_context_mut(&mut self) -> &mut NeuralContextTracker {
+        &mut self.neural_context
+    }
+
     /// Check if a gain change would cause clipping
     pub fn check_gain(&self, gain_db: f32) -> SafetyCheckResult {
         let mut result = SafetyCheckResult::safe();
@@ -459,31 +573,184 @@ impl SafetyChecker {
 
         let diff = (new_seconds - original_seconds).abs();
         if diff > thresholds::DURATION_TOLERANCE {
-            result = result
-                .with_issue(SafetyIssue::DurationMismatch {
-                    expected_seconds: original_seconds as i32,
-                    actual_seconds: new_seconds as i32,
-                })
-                .with_warning(&format!(
-                    "Duration changed by {:.2}s (from {:.2}s to {:.2}s)",
-                    diff, original_seconds, new_seconds
+            let issue = SafetyIssue::DurationMismatch {
+                expected_seconds: original_seconds as i32,
+                actual_seconds: new_seconds as i32,
+            };
+            if self.neural_context.is_intentional(&issue) {
+                return result.with_warning(&format!(
+                    "Duration changed by {:.2}s (intentional tempo change)",
+                    diff
                 ));
+            }
+            result = result.with_issue(issue).with_warning(&format!(
+                "Duration changed by {:.2}s (from {:.2}s to {:.2}s)",
+                diff, original_seconds, new_seconds
+            ));
         }
 
         result
     }
 
+    /// Check rendered audio for inter-sample clipping before export
+    ///
+    /// Runs true peak detection (the limiter's oversampling detector) and
+    /// returns a recommendation when the true peak exceeds 0 dBTP while
+    /// every sample stays below full scale. Sample clipping is reported by
+    /// the regular clipping checks instead.
+    pub fn check_true_peak(&self, buffer: &dsp::AudioBuffer) -> Option<SafetyRecommendation> {
+        let sample_peak = buffer
+            .samples()
+            .iter()
+            .fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        let sample_peak_db = if sample_peak > 0.0 {
+            20.0 * sample_peak.log10()
+        } else {
+            return None;
+        };
+        let true_peak_db = dsp::true_peak_db(buffer);
+
+        if true_peak_db > thresholds::TRUE_PEAK_LIMIT && sample_peak_db < thresholds::CLIPPING_LIMIT
+        {
+            Some(true_peak_recommendation(sample_peak_db, true_peak_db))
+        } else {
+            None
+        }
+    }
+
+    /// Check audio for DC offset
+    ///
+    /// Returns a recommendation when the mean sample value exceeds
+    /// [`DC_OFFSET_THRESHOLD`]; its mitigation is a 20 Hz high-pass the
+    /// agent can append to the chain. Priority rises with the offset.
+    pub fn check_dc_offset(
+        &self,
+        buffer: &crate::engine::AudioBuffer,
+    ) -> Option<SafetyRecommendation> {
+        let mean = calculate_mean(buffer);
+        (mean.abs() > DC_OFFSET_THRESHOLD).then(|| dc_offset_recommendation(mean))
+    }
+
+    /// Dry-run a chain on a copy of the input and report its output level
+    ///
+    /// Neither the chain nor the input is touched: the chain is rebuilt
+    /// from its serialized state and the input copy is padded by the
+    /// chain's tail and latency so delay and reverb overs are caught too.
+    pub fn predict_headroom(
+        &self,
+        chain: &dsp::EffectChain,
+        input: &dsp::AudioBuffer,
+    ) -> Result<HeadroomReport> {
+        let (output, tail_samples) = dry_run(chain, input)?;
+
+        let sample_peak = output
+            .samples()
+            .iter()
+            .fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        let peak_db = if sample_peak > 0.0 {
+            20.0 * sample_peak.log10()
+        } else {
+            -96.0
+        };
+        let true_peak_db = dsp::true_peak_db(&output);
+        let exceeds_full_scale = true_peak_db > thresholds::CLIPPING_LIMIT;
+
+        Ok(HeadroomReport {
+            peak_db,
+            true_peak_db,
+            exceeds_full_scale,
+            suggested_trim_db: if exceeds_full_scale {
+                thresholds::TRUE_PEAK_CEILING - true_peak_db
+            } else {
+                0.0
+            },
+            tail_samples,
+        })
+    }
+
+    /// Dry-run a chain and recommend a limiter if its output would clip
+    ///
+    /// The recommendation's mitigation is a limiter at the true peak
+    /// ceiling, ready to append to the chain. A chain that already ends in
+    /// an enabled limiter gets no recommendation: limiting twice only adds
+    /// pumping, so a clipping limiter should be turned down instead.
+    /// Nor does clipping that the neural context marks as intentional.
+    pub fn check_chain_headroom(
+        &self,
+        chain: &dsp::EffectChain,
+        input: &dsp::AudioBuffer,
+    ) -> Result<Option<SafetyRecommendation>> {
+        let ends_in_limiter = chain
+            .iter()
+            .last()
+            .is_some_and(|effect| effect.effect_type() == "limiter" && effect.is_enabled());
+        if ends_in_limiter {
+            return Ok(None);
+        }
+
+        let report = self.predict_headroom(chain, input)?;
+        let clipping = SafetyIssue::Clipping {
+            predicted_peak_db: report.true_peak_db as i32,
+        };
+        if report.is_safe() || self.neural_context.is_intentional(&clipping) {
+            return Ok(None);
+        }
+        Ok(Some(SafetyRecommendation {
+            priority: RecommendationPriority::High,
+            message: format!(
+                "Chain output would peak at {:+.1} dBTP and clip",
+                report.true_peak_db
+            ),
+            suggested_action: Some(format!(
+                "Add a limiter at {:.0} dBTP",
+                thresholds::TRUE_PEAK_CEILING
+            )),
+            mitigation: Some(SafetyMitigation::AutoLimiter {
+                ceiling_db: thresholds::TRUE_PEAK_CEILING as i32,
+            }),
+        }))
+    }
+
+    /// Dry-run a chain and recommend a high-pass if its output has a DC
+    /// offset
+    ///
+    /// Checking the output rather than the input means a chain that
+    /// already removes the offset gets no recommendation.
+    pub fn check_chain_dc_offset(
+        &self,
+        chain: &dsp::EffectChain,
+        input: &dsp::AudioBuffer,
+    ) -> Result<Option<SafetyRecommendation>> {
+        let (output, _) = dry_run(chain, input)?;
+        Ok(self.check_dc_offset(&output.to_engine()))
+    }
+
     /// Get recommendations based on current analysis
+    ///
+    /// Clipping recommendations are left out while the neural context
+    /// marks the clipping as intentional.
     pub fn get_recommendations(&self) -> Vec<SafetyRecommendation> {
         let mut recommendations = Vec::new();
 
         if let Some(ref analysis) = self.analysis {
-            if analysis.has_clipping() {
+            let clipping_intentional = self.neural_context.is_intentional(&SafetyIssue::Clipping {
+                predicted_peak_db: analysis.true_peak_db.max(analysis.peak_db) as i32,
+            });
+
+            if analysis.has_intersample_clipping() && !clipping_intentional {
+                recommendations.push(true_peak_recommendation(
+                    analysis.peak_db,
+                    analysis.true_peak_db,
+                ));
+            }
+
+            if analysis.has_clipping() && !clipping_intentional {
                 recommendations.push(SafetyRecommendation {
                     priority: RecommendationPriority::High,
                     message: "Audio has clipping - consider restore/declip before other processing"
                         .to_string(),
                     suggested_action: Some("Use 'restore' model in declip mode".to_string()),
+                    mitigation: None,
                 });
             }
 
@@ -495,6 +762,7 @@ impl SafetyChecker {
                         analysis.lufs_integrated
                     ),
                     suggested_action: None,
+                    mitigation: None,
                 });
             }
 
@@ -506,6 +774,25 @@ impl SafetyChecker {
                         analysis.lufs_integrated
                     ),
                     suggested_action: Some("Consider adding gain and/or limiter".to_string()),
+                    mitigation: None,
+                });
+            }
+
+            if let Some(crest) = analysis
+                .crest_factor_db
+                .filter(|_| analysis.is_brickwalled())
+            {
+                recommendations.push(SafetyRecommendation {
+                    priority: RecommendationPriority::Medium,
+                    message: format!(
+                        "Audio looks brickwalled (crest factor {:.1} dB, loudness range {:.1} LU)",
+                        crest, analysis.dynamic_range_db
+                    ),
+                    suggested_action: Some(
+                        "Reduce limiting/compression (lower the limiter's drive or the compressor's ratio) rather than adding more"
+                            .to_string(),
+                    ),
+                    mitigation: None,
                 });
             }
 
@@ -517,6 +804,7 @@ impl SafetyChecker {
                         analysis.noise_floor_db
                     ),
                     suggested_action: Some("Consider using 'denoise' model".to_string()),
+                    mitigation: None,
                 });
             }
 
@@ -528,15 +816,12 @@ impl SafetyChecker {
                         analysis.stereo_correlation
                     ),
                     suggested_action: Some("Be cautious with stereo widening effects".to_string()),
+                    mitigation: None,
                 });
             }
 
             if analysis.has_dc_offset {
-                recommendations.push(SafetyRecommendation {
-                    priority: RecommendationPriority::Low,
-                    message: "DC offset detected".to_string(),
-                    suggested_action: Some("Add high-pass filter at 20-30Hz".to_string()),
-                });
+                recommendations.push(dc_offset_recommendation(analysis.dc_offset_value));
             }
         }
 
@@ -550,6 +835,38 @@ impl Default for SafetyChecker {
     }
 }
 
+/// Predicted output level of an effect chain (see
+/// [`SafetyChecker::predict_headroom`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadroomReport {
+    /// Highest sample peak of the output (dBFS)
+    pub peak_db: f32,
+
+    /// True peak of the output (dBTP)
+    pub true_peak_db: f32,
+
+    /// Whether the output goes over 0 dBFS, between samples or on them
+    pub exceeds_full_scale: bool,
+
+    /// Gain that brings the true peak down to the safe ceiling (dB, <= 0)
+    pub suggested_trim_db: f32,
+
+    /// Samples of tail rendered after the input
+    pub tail_samples: usize,
+}
+
+impl HeadroomReport {
+    /// Distance from the true peak to full scale (negative when over)
+    pub fn headroom_db(&self) -> f32 {
+        -self.true_peak_db
+    }
+
+    /// Whether the chain can be applied without clipping
+    pub fn is_safe(&self) -> bool {
+        !self.exceeds_full_scale
+    }
+}
+
 /// A recommendation from the safety system
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SafetyRecommendation {
@@ -561,6 +878,78 @@ pub struct SafetyRecommendation {
 
     /// Suggested action (if any)
     pub suggested_action: Option<String>,
+
+    /// Fix the agent can apply automatically (if any)
+    #[serde(default)]
+    pub mitigation: Option<SafetyMitigation>,
+}
+
+/// Recommendation for a DC offset of `mean`
+///
+/// Priority follows the size of the offset, since it costs headroom and
+/// can thump at edits.
+fn dc_offset_recommendation(mean: f32) -> SafetyRecommendation {
+    let offset = mean.abs();
+    let priority = if offset >= thresholds::DC_OFFSET_SEVERE {
+        RecommendationPriority::High
+    } else if offset >= thresholds::DC_OFFSET_MEDIUM {
+        RecommendationPriority::Medium
+    } else {
+        RecommendationPriority::Low
+    };
+    SafetyRecommendation {
+        priority,
+        message: format!("DC offset detected (mean {:+.3})", mean),
+        suggested_action: Some(format!(
+            "Add a {:.0} Hz high-pass filter",
+            thresholds::DC_HIGH_PASS_HZ
+        )),
+        mitigation: Some(SafetyMitigation::HighPass {
+            frequency_hz: thresholds::DC_HIGH_PASS_HZ as i32,
+        }),
+    }
+}
+
+/// Run a copy of `chain` over a copy of `input`, padded by the chain's
+/// tail and latency; returns the output and the tail length
+fn dry_run(
+    chain: &dsp::EffectChain,
+    input: &dsp::AudioBuffer,
+) -> Result<(dsp::AudioBuffer, usize)> {
+    let mut chain = chain.try_clone()?;
+    chain.prepare(input.sample_rate(), input.num_samples().max(1));
+
+    let tail_samples = chain.tail_samples();
+    let mut output = input.clone();
+    // Latency pushes the end of the input past its length as well
+    output.append_silence(tail_samples + chain.latency_samples());
+    chain.process(&mut output);
+    Ok((output, tail_samples))
+}
+
+/// Recommendation for true peaks over 0 dBTP
+///
+/// Overs of a dB or more distort audibly once encoded, so they are high
+/// priority; smaller ones are medium.
+fn true_peak_recommendation(sample_peak_db: f32, true_peak_db: f32) -> SafetyRecommendation {
+    let priority = if true_peak_db >= thresholds::TRUE_PEAK_SEVERE {
+        RecommendationPriority::High
+    } else {
+        RecommendationPriority::Medium
+    };
+    SafetyRecommendation {
+        priority,
+        message: format!(
+            "Inter-sample clipping: true peak {:+.1} dBTP although samples peak at {:.1} dBFS",
+            true_peak_db, sample_peak_db
+        ),
+        suggested_action: Some(format!(
+            "Add a limiter with true peak enabled (or lower the existing one) with ceiling_db {:.1} ({:.1} dBTP)",
+            thresholds::TRUE_PEAK_CEILING,
+            thresholds::TRUE_PEAK_CEILING
+        )),
+        mitigation: None,
+    }
 }
 
 /// Priority levels for recommendations
@@ -574,6 +963,8 @@ pub enum RecommendationPriority {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::neural::IntentionalArtifact;
+    use dsp::Effect;
 
     fn make_analysis() -> AudioAnalysis {
         AudioAnalysis {
@@ -703,6 +1094,235 @@ mod tests {
         assert!(recs.iter().any(|r| r.message.contains("noise")));
     }
 
+    /// fs/4 sine at 45 degrees: every sample lands at 0.707 of the peak
+    fn quarter_rate_sine(amplitude: f32) -> dsp::AudioBuffer {
+        let samples: Vec<f32> = (0..4800)
+            .map(|n| {
+                let phase = std::f32::consts::FRAC_PI_2 * n as f32 + std::f32::consts::FRAC_PI_4;
+                amplitude * phase.sin()
+            })
+            .collect();
+        dsp::AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap()
+    }
+
+    #[test]
+    fn test_intersample_clipping_warns() {
+        // Samples peak at -0.5 dBFS, the waveform at about +2.5 dBTP
+        let amplitude = 10.0_f32.powf(-0.5 / 20.0) * std::f32::consts::SQRT_2;
+        let buffer = quarter_rate_sine(amplitude);
+        assert!(buffer.samples().iter().all(|s| s.abs() < 1.0));
+
+        let rec = SafetyChecker::new()
+            .check_true_peak(&buffer)
+            .expect("inter-sample overs should be reported");
+        assert_eq!(rec.priority, RecommendationPriority::High);
+        assert!(rec.message.contains("Inter-sample"));
+        let action = rec.suggested_action.unwrap();
+        assert!(action.contains("ceiling_db -1.0"), "{}", action);
+    }
+
+    #[test]
+    fn test_clean_material_has_no_true_peak_warning() {
+        let checker = SafetyChecker::new();
+        assert!(checker.check_true_peak(&quarter_rate_sine(0.5)).is_none());
+        assert!(checker
+            .check_true_peak(&dsp::AudioBuffer::new(2, 512, 48000.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_intersample_recommendation_from_analysis() {
+        let mut checker = SafetyChecker::new();
+        let mut analysis = make_analysis();
+        analysis.peak_db = -0.5;
+        analysis.true_peak_db = 0.4;
+        assert!(analysis.has_intersample_clipping());
+        checker.set_analysis(analysis);
+
+        let recs = checker.get_recommendations();
+        let rec = recs
+            .iter()
+            .find(|r| r.message.contains("Inter-sample"))
+            .unwrap();
+        assert_eq!(rec.priority, RecommendationPriority::Medium);
+
+        checker.set_analysis(make_analysis());
+        assert!(!checker
+            .get_recommendations()
+            .iter()
+            .any(|r| r.message.contains("Inter-sample")));
+    }
+
+    /// One second of stereo 220 Hz sine riding on a DC offset
+    fn offset_sine(offset: f32) -> crate::engine::AudioBuffer {
+        let channel: Vec<f32> = (0..48000)
+            .map(|n| offset + 0.5 * (std::f32::consts::TAU * 220.0 * n as f32 / 48000.0).sin())
+            .collect();
+        crate::engine::AudioBuffer {
+            samples: vec![channel.clone(), channel],
+            sample_rate: 48000,
+        }
+    }
+
+    #[test]
+    fn test_clean_audio_has_no_dc_offset_recommendation() {
+        let checker = SafetyChecker::new();
+        assert!(checker.check_dc_offset(&offset_sine(0.0)).is_none());
+        assert!(checker.check_dc_offset(&offset_sine(0.005)).is_none());
+    }
+
+    #[test]
+    fn test_dc_offset_priority_follows_severity() {
+        let checker = SafetyChecker::new();
+        let priority = |offset: f32| {
+            checker
+                .check_dc_offset(&offset_sine(offset))
+                .unwrap()
+                .priority
+        };
+        assert_eq!(priority(0.02), RecommendationPriority::Low);
+        assert_eq!(priority(-0.07), RecommendationPriority::Medium);
+        assert_eq!(priority(0.3), RecommendationPriority::High);
+
+        let mut analysis = make_analysis();
+        analysis.has_dc_offset = true;
+        analysis.dc_offset_value = 0.07;
+        let mut checker = SafetyChecker::new();
+        checker.set_analysis(analysis);
+        let recs = checker.get_recommendations();
+        let rec = recs
+            .iter()
+            .find(|r| r.message.contains("DC offset"))
+            .unwrap();
+        assert_eq!(rec.priority, RecommendationPriority::Medium);
+        assert!(rec.mitigation.is_some());
+    }
+
+    #[test]
+    fn test_dc_offset_mitigation_removes_offset() {
+        let checker = SafetyChecker::new();
+        let audio = offset_sine(0.15);
+        let rec = checker.check_dc_offset(&audio).unwrap();
+        assert_eq!(
+            rec.mitigation,
+            Some(SafetyMitigation::HighPass { frequency_hz: 20 })
+        );
+
+        let mut high_pass = rec.mitigation.unwrap().to_effect().unwrap();
+        assert_eq!(high_pass.effect_type(), "parametric-eq");
+        let mut buffer = dsp::AudioBuffer::from_engine(&audio).unwrap();
+        high_pass.prepare(48000.0, buffer.num_samples());
+        high_pass.process(&mut buffer);
+
+        let fixed = buffer.to_engine();
+        assert!(calculate_mean(&fixed).abs() < DC_OFFSET_THRESHOLD);
+        assert!(checker.check_dc_offset(&fixed).is_none());
+
+        let input = dsp::AudioBuffer::from_engine(&audio).unwrap();
+        let mut chain = dsp::EffectChain::new();
+        chain.add(Box::new(dsp::GainEffect::with_gain(0.0).unwrap()));
+        assert!(checker
+            .check_chain_dc_offset(&chain, &input)
+            .unwrap()
+            .is_some());
+        chain.add(high_pass);
+        assert!(checker
+            .check_chain_dc_offset(&chain, &input)
+            .unwrap()
+            .is_none());
+
+        let limiter = SafetyMitigation::AutoLimiter { ceiling_db: -1 };
+        assert_eq!(limiter.to_effect().unwrap().effect_type(), "limiter");
+        let skipped = SafetyMitigation::SkippedOperation {
+            operation: "widen".to_string(),
+            reason: "phase".to_string(),
+        };
+        assert!(skipped.to_effect().is_none());
+    }
+
+    /// Mono 16kHz buffer of `secs` seconds from `signal(t)`
+    fn render(secs: f32, signal: impl Fn(f32) -> f32) -> crate::engine::AudioBuffer {
+        let len = (secs * 16000.0) as usize;
+        let mut buffer = crate::engine::AudioBuffer::new(len, crate::engine::ChannelLayout::Mono);
+        buffer.sample_rate = 16000;
+        for (i, sample) in buffer.samples[0].iter_mut().enumerate() {
+            *sample = signal(i as f32 / 16000.0);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_dynamic_material_has_wide_range() {
+        // Five-second passages from pianissimo to fortissimo, with a
+        // decaying drum hit every 2.5 s
+        let levels_db = [-40.0, -20.0, -30.0, -8.0, -35.0, -12.0];
+        let orchestral = render(30.0, |t| {
+            let level = 10f32.powf(levels_db[(t / 5.0) as usize] / 20.0);
+            let tone: f32 = (1..=4)
+                .map(|h| (std::f32::consts::TAU * 220.0 * h as f32 * t).sin() / h as f32)
+                .sum();
+            let since_hit = t % 2.5;
+            let hit = 0.9 * (-since_hit * 60.0).exp() * (std::f32::consts::TAU * 60.0 * t).sin();
+            0.5 * level * tone + hit
+        });
+
+        let mut analysis = make_analysis();
+        analysis.measure_dynamics(&orchestral);
+        assert!(
+            analysis.dynamic_range_db > 10.0,
+            "{}",
+            analysis.dynamic_range_db
+        );
+        assert!(analysis.crest_factor_db.unwrap() > 10.0);
+        assert!(!analysis.is_brickwalled());
+
+        let mut checker = SafetyChecker::new();
+        checker.set_analysis(analysis);
+        assert!(!checker
+            .get_recommendations()
+            .iter()
+            .any(|r| r.message.contains("brickwalled")));
+    }
+
+    #[test]
+    fn test_limited_material_is_brickwalled() {
+        // A hard-driven, square-ish tone at a constant level
+        let limited = render(10.0, |t| {
+            0.9 * (10.0 * (std::f32::consts::TAU * 110.0 * t).sin()).tanh()
+        });
+
+        let mut analysis = make_analysis();
+        analysis.measure_dynamics(&limited);
+        assert!(
+            analysis.dynamic_range_db < 1.0,
+            "{}",
+            analysis.dynamic_range_db
+        );
+        assert!(analysis.crest_factor_db.unwrap() < 2.0);
+        assert!(analysis.is_brickwalled());
+        assert!(analysis.to_human_summary().contains("Over-compressed"));
+
+        let mut checker = SafetyChecker::new();
+        checker.set_analysis(analysis);
+        let recommendations = checker.get_recommendations();
+        let brickwall = recommendations
+            .iter()
+            .find(|r| r.message.contains("brickwalled"))
+            .unwrap();
+        assert!(brickwall
+            .suggested_action
+            .as_deref()
+            .unwrap()
+            .contains("Reduce limiting/compression"));
+
+        // Unmeasured analyses and silence never warn
+        assert!(!make_analysis().is_brickwalled());
+        let mut silent = make_analysis();
+        silent.measure_dynamics(&render(1.0, |_| 0.0));
+        assert_eq!(silent.crest_factor_db, None);
+        assert!(!silent.is_brickwalled());
+    }
+
     #[test]
     fn test_human_summary() {
         let mut analysis = make_analysis();
@@ -713,4 +1333,161 @@ mod tests {
         assert!(summary.contains("VERY LOUD"));
         assert!(summary.contains("Phase issues"));
     }
+
+    fn sine(amplitude: f32, len: usize) -> dsp::AudioBuffer {
+        let samples: Vec<f32> = (0..len)
+            .map(|n| amplitude * (n as f32 * 2.0 * std::f32::consts::PI * 440.0 / 48000.0).sin())
+            .collect();
+        dsp::AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap()
+    }
+
+    fn gain_chain(gain_db: f32) -> dsp::EffectChain {
+        let mut chain = dsp::EffectChain::new();
+        let mut gain = dsp::GainEffect::with_gain(gain_db).unwrap();
+        gain.set_id("boost".to_string());
+        chain.add(Box::new(gain));
+        chain
+    }
+
+    #[test]
+    fn test_predict_headroom_catches_gain_boost() {
+        let input = sine(0.5, 4800);
+        let chain = gain_chain(12.0);
+
+        let report = SafetyChecker::new()
+            .predict_headroom(&chain, &input)
+            .unwrap();
+        assert!(report.exceeds_full_scale);
+        assert!(!report.is_safe());
+        // 0.5 (-6 dBFS) boosted by 12 dB peaks near +6 dBFS
+        assert!((report.peak_db - 6.0).abs() < 0.2, "{:?}", report);
+        assert!(report.true_peak_db >= report.peak_db);
+        assert!((report.suggested_trim_db - (-1.0 - report.true_peak_db)).abs() < 1e-6);
+
+        // Neither the input nor the chain was changed
+        assert_eq!(input.samples(), sine(0.5, 4800).samples());
+        assert_eq!(
+            chain.get("boost").unwrap().to_json().unwrap()["gain_db"],
+            12.0
+        );
+    }
+
+    #[test]
+    fn test_predict_headroom_with_final_limiter_is_safe() {
+        let mut chain = gain_chain(12.0);
+        chain.add(Box::new(dsp::Limiter::new()));
+
+        let report = SafetyChecker::new()
+            .predict_headroom(&chain, &sine(0.5, 4800))
+            .unwrap();
+        assert!(report.is_safe(), "{:?}", report);
+        assert!(report.headroom_db() > 0.0);
+        assert_eq!(report.suggested_trim_db, 0.0);
+    }
+
+    #[test]
+    fn test_predict_headroom_includes_tails() {
+        // Only the echo of the last sample, which lands after the input
+        // ends, goes over full scale
+        let mut samples = vec![0.0; 480];
+        samples[479] = 0.6;
+        let input = dsp::AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap();
+
+        let mut chain = gain_chain(6.0);
+        chain.add(Box::new(dsp::Delay::with_params(dsp::DelayParams {
+            delay_time_ms: 50.0,
+            feedback: 0.0,
+            wet_level: 1.0,
+            dry_level: 0.3,
+            filter_freq: 20000.0,
+            ..Default::default()
+        })));
+
+        let report = SafetyChecker::new()
+            .predict_headroom(&chain, &input)
+            .unwrap();
+        assert!(report.tail_samples >= 2400);
+        assert!(report.exceeds_full_scale, "{:?}", report);
+    }
+
+    #[test]
+    fn test_check_chain_headroom_recommends_limiter() {
+        let checker = SafetyChecker::new();
+        let input = sine(0.5, 4800);
+        assert!(checker
+            .check_chain_headroom(&gain_chain(3.0), &input)
+            .unwrap()
+            .is_none());
+
+        let mut chain = gain_chain(12.0);
+        let rec = checker
+            .check_chain_headroom(&chain, &input)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rec.priority, RecommendationPriority::High);
+        assert!(rec.message.contains("and clip"), "{}", rec.message);
+        let limiter = rec.mitigation.unwrap().to_effect().unwrap();
+        chain.add(limiter);
+        assert!(checker.predict_headroom(&chain, &input).unwrap().is_safe());
+
+        // A chain that ends in a limiter is never limited twice
+        chain.add(Box::new(dsp::GainEffect::with_gain(6.0).unwrap()));
+        chain.add(Box::new(dsp::Limiter::new()));
+        assert!(checker
+            .check_chain_headroom(&chain, &input)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_intentional_clipping_suppresses_only_clipping() {
+        let mut checker = SafetyChecker::new();
+        let mut analysis = make_analysis();
+        analysis.peak_db = 0.0;
+        analysis.clip_percentage = 2.0;
+        analysis.noise_floor_db = -40.0;
+        checker.set_analysis(analysis);
+
+        let clipping_warned = |checker: &SafetyChecker| {
+            checker
+                .get_recommendations()
+                .iter()
+                .any(|r| r.message.contains("clipping"))
+        };
+        let noise_warned = |checker: &SafetyChecker| {
+            checker
+                .get_recommendations()
+                .iter()
+                .any(|r| r.message.contains("noise"))
+        };
+        assert!(clipping_warned(&checker));
+
+        checker
+            .neural_context_mut()
+            .add_artifact(IntentionalArtifact::Saturation);
+        assert!(!clipping_warned(&checker));
+        assert!(noise_warned(&checker));
+
+        checker
+            .neural_context_mut()
+            .remove_artifact(&IntentionalArtifact::Saturation);
+        assert!(clipping_warned(&checker));
+        assert!(noise_warned(&checker));
+    }
+
+    #[test]
+    fn test_intentional_tempo_change_downgrades_duration_issue() {
+        let mut checker = SafetyChecker::new();
+        assert!(checker.check_duration(10.0, 8.0).has_issues());
+
+        checker
+            .neural_context_mut()
+            .add_artifact(IntentionalArtifact::TempoChange);
+        let result = checker.check_duration(10.0, 8.0);
+        assert!(!result.has_issues());
+        assert!(result.warnings[0].contains("intentional"));
+
+        checker.set_neural_context(NeuralContextTracker::new());
+        assert!(checker.check_duration(10.0, 8.0).has_issues());
+    }
 }
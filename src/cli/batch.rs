@@ -0,0 +1,467 @@
+//! Batch Processing
+//!
+//! Runs one job (a prompt through a neural model, or a DSP chain preset)
+//! over every audio file in a directory. Outputs go to an `out/` subfolder
+//! mirroring the input layout, alongside a JSON report of what happened to
+//! each file. Unsupported files are skipped and one failed file never stops
+//! the rest of the batch.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::dsp;
+use crate::engine::io::{export_audio, import_audio_native, ExportFormat};
+use crate::neural::{NeuralModel, NeuralModelParams};
+use crate::state::error::{NuevaError, Result};
+use crate::state::project::{Effect, Layer2};
+
+/// Output folder created inside the batch directory
+pub const BATCH_OUTPUT_DIR: &str = "out";
+
+/// Report written inside the output folder
+pub const BATCH_REPORT_FILE: &str = "batch_report.json";
+
+/// File extensions the batch can decode
+const SUPPORTED_EXTENSIONS: &[&str] = &["wav"];
+
+/// What to do to each file
+pub enum BatchJob {
+    /// Run a neural model with fixed parameters
+    Model {
+        model: Box<dyn NeuralModel>,
+        params: NeuralModelParams,
+    },
+    /// Render a DSP chain
+    Chain(Layer2),
+}
+
+/// Outcome for a single input file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchFileResult {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub success: bool,
+    /// Model description on success, error text on failure
+    pub message: String,
+}
+
+/// Summary of a batch run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchReport {
+    /// Every file that was attempted
+    pub files: Vec<BatchFileResult>,
+    /// Files skipped as unsupported
+    pub skipped: Vec<PathBuf>,
+}
+
+impl BatchReport {
+    /// Number of files processed successfully
+    pub fn succeeded(&self) -> usize {
+        self.files.iter().filter(|f| f.success).count()
+    }
+
+    /// Number of files that failed
+    pub fn failed(&self) -> usize {
+        self.files.len() - self.succeeded()
+    }
+}
+
+/// One entry in a chain preset file
+#[derive(Debug, Deserialize)]
+struct PresetEffect {
+    #[serde(rename = "type")]
+    effect_type: String,
+    #[serde(default)]
+    params: std::collections::HashMap<String, serde_json::Value>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Load a chain preset: a JSON list of `{"type", "params", "enabled"}`
+/// entries, applied in order
+pub fn load_chain_preset(path: &Path) -> Result<Layer2> {
+    let content = fs::read_to_string(path).map_err(|e| NuevaError::FileReadError {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let entries: Vec<PresetEffect> = serde_json::from_str(&content)?;
+
+    let chain = entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            if dsp::create_effect(&entry.effect_type).is_none() {
+                return Err(NuevaError::InvalidProjectStructure {
+                    reason: format!("unknown effect type in preset: {}", entry.effect_type),
+                });
+            }
+            Ok(Effect {
+                id: format!("{}-{}", entry.effect_type, i + 1),
+                effect_type: entry.effect_type,
+                enabled: entry.enabled,
+                params: entry.params,
+                added_at: Utc::now(),
+                added_by: "user".to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Layer2 { chain })
+}
+
+/// Audio files in `dir`, in path order
+///
+/// Unsupported files are logged and returned separately. The batch output
+/// folder is never treated as input.
+pub fn collect_inputs(dir: &Path, recursive: bool) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let output_dir = dir.join(BATCH_OUTPUT_DIR);
+    let max_depth = if recursive { usize::MAX } else { 1 };
+
+    let mut inputs = Vec::new();
+    let mut skipped = Vec::new();
+    let entries = WalkDir::new(dir)
+        .max_depth(max_depth)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.path() != output_dir)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+
+    for entry in entries {
+        let path = entry.into_path();
+        let supported = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if supported {
+            inputs.push(path);
+        } else {
+            warn!("Skipping unsupported file: {}", path.display());
+            skipped.push(path);
+        }
+    }
+
+    (inputs, skipped)
+}
+
+/// Process every supported file in `dir` and write the report
+pub fn run_batch(dir: &Path, job: &BatchJob, recursive: bool) -> Result<BatchReport> {
+    if !dir.is_dir() {
+        return Err(NuevaError::FileNotFound {
+            path: dir.to_path_buf(),
+        });
+    }
+
+    // Bad model parameters would fail every file; reject them up front
+    if let BatchJob::Model { model, params } = job {
+        model
+            .validate_params(params)
+            .map_err(|e| NuevaError::ProcessingFailed {
+                reason: e.to_string(),
+            })?;
+    }
+
+    let output_dir = dir.join(BATCH_OUTPUT_DIR);
+    let (inputs, skipped) = collect_inputs(dir, recursive);
+    let mut report = BatchReport {
+        files: Vec::with_capacity(inputs.len()),
+        skipped,
+    };
+
+    for input in inputs {
+        let relative = input.strip_prefix(dir).unwrap_or(&input);
+        let output = output_dir.join(relative);
+        info!("Batch processing: {}", input.display());
+
+        let outcome = output
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(NuevaError::from)
+            .and_then(|_| process_file(&input, &output, job));
+
+        let (success, message) = match outcome {
+            Ok(message) => (true, message),
+            Err(e) => {
+                warn!("Batch file failed: {}: {}", input.display(), e);
+                (false, e.to_string())
+            }
+        };
+        report.files.push(BatchFileResult {
+            input,
+            output,
+            success,
+            message,
+        });
+    }
+
+    fs::create_dir_all(&output_dir)?;
+    fs::write(
+        output_dir.join(BATCH_REPORT_FILE),
+        serde_json::to_string_pretty(&report)?,
+    )?;
+
+    Ok(report)
+}
+
+/// Run the job on one file, returning a short description
+fn process_file(input: &Path, output: &Path, job: &BatchJob) -> Result<String> {
+    match job {
+        BatchJob::Model { model, params } => {
+            let result = model
+                .prepare_params(params)
+                .and_then(|params| model.process(input, output, &params))
+                .map_err(|e| NuevaError::ProcessingFailed {
+                    reason: e.to_string(),
+                })?;
+            if !result.success {
+                return Err(NuevaError::ProcessingFailed {
+                    reason: result.description,
+                });
+            }
+            Ok(result.description)
+        }
+        BatchJob::Chain(layer2) => {
+            let source =
+                import_audio_native(input).map_err(|e| NuevaError::InvalidAudioFormat {
+                    reason: e.to_string(),
+                })?;
+            let mut audio = dsp::AudioBuffer::from_engine(&source).map_err(|e| {
+                NuevaError::InvalidAudioFormat {
+                    reason: e.to_string(),
+                }
+            })?;
+            layer2.render(&mut audio)?;
+
+            let rendered = audio.to_engine();
+            export_audio(
+                &rendered,
+                output,
+                ExportFormat::new(rendered.sample_rate, 24),
+            )
+            .map_err(|e| NuevaError::ProcessingFailed {
+                reason: e.to_string(),
+            })?;
+            Ok(format!("Rendered {} effect(s)", layer2.chain.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::calculate_peak;
+    use crate::engine::io::{generate_test_tone, import_audio};
+    use crate::neural::{NeuralModelInfo, ProcessingResult};
+    use tempfile::TempDir;
+
+    fn write_tone(path: &Path) {
+        export_audio(
+            &generate_test_tone(440.0, 0.1, 48000),
+            path,
+            ExportFormat::default(),
+        )
+        .unwrap();
+    }
+
+    /// Layout: a.wav, b.wav, notes.txt, broken.wav, sub/c.wav
+    fn setup() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        write_tone(&temp.path().join("a.wav"));
+        write_tone(&temp.path().join("b.wav"));
+        fs::write(temp.path().join("notes.txt"), "not audio").unwrap();
+        fs::write(temp.path().join("broken.wav"), "not a wav either").unwrap();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        write_tone(&temp.path().join("sub").join("c.wav"));
+        temp
+    }
+
+    fn gain_chain() -> BatchJob {
+        BatchJob::Chain(Layer2 {
+            chain: vec![Effect {
+                id: "gain-1".to_string(),
+                effect_type: "gain".to_string(),
+                enabled: true,
+                params: [("gain_db".to_string(), serde_json::json!(-6.0))].into(),
+                added_at: Utc::now(),
+                added_by: "user".to_string(),
+            }],
+        })
+    }
+
+    #[test]
+    fn test_skips_unsupported_and_continues_past_failures() {
+        let temp = setup();
+        let report = run_batch(temp.path(), &gain_chain(), false).unwrap();
+
+        assert_eq!(report.skipped, vec![temp.path().join("notes.txt")]);
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(report.succeeded(), 2);
+        assert_eq!(report.failed(), 1);
+
+        let broken = report.files.iter().find(|f| !f.success).unwrap();
+        assert_eq!(broken.input, temp.path().join("broken.wav"));
+        assert!(!broken.message.is_empty());
+
+        let out = temp.path().join(BATCH_OUTPUT_DIR);
+        assert!(out.join("a.wav").exists());
+        assert!(out.join("b.wav").exists());
+        assert!(!out.join("sub").exists());
+    }
+
+    #[test]
+    fn test_chain_is_applied() {
+        let temp = setup();
+        run_batch(temp.path(), &gain_chain(), false).unwrap();
+
+        let input = import_audio(&temp.path().join("a.wav")).unwrap();
+        let output = import_audio(&temp.path().join(BATCH_OUTPUT_DIR).join("a.wav")).unwrap();
+        let change_db = calculate_peak(&output) - calculate_peak(&input);
+        assert!(
+            (change_db + 6.0).abs() < 0.01,
+            "peak changed by {} dB",
+            change_db
+        );
+    }
+
+    #[test]
+    fn test_recursive_mirrors_subdirectories() {
+        let temp = setup();
+        let report = run_batch(temp.path(), &gain_chain(), true).unwrap();
+
+        assert_eq!(report.files.len(), 4);
+        assert!(temp
+            .path()
+            .join(BATCH_OUTPUT_DIR)
+            .join("sub")
+            .join("c.wav")
+            .exists());
+
+        // A second run must not pick up its own outputs
+        let again = run_batch(temp.path(), &gain_chain(), true).unwrap();
+        assert_eq!(again.files.len(), 4);
+    }
+
+    #[test]
+    fn test_report_written() {
+        let temp = setup();
+        run_batch(temp.path(), &gain_chain(), false).unwrap();
+
+        let json =
+            fs::read_to_string(temp.path().join(BATCH_OUTPUT_DIR).join(BATCH_REPORT_FILE)).unwrap();
+        let report: BatchReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(report.succeeded(), 2);
+        assert_eq!(report.failed(), 1);
+    }
+
+    /// Fails on files whose name contains "b"
+    struct PickyModel {
+        info: NeuralModelInfo,
+    }
+
+    impl NeuralModel for PickyModel {
+        fn info(&self) -> &NeuralModelInfo {
+            &self.info
+        }
+
+        fn process(
+            &self,
+            input_path: &Path,
+            output_path: &Path,
+            _params: &NeuralModelParams,
+        ) -> crate::error::Result<ProcessingResult> {
+            let name = input_path.file_name().unwrap_or_default();
+            if name.to_string_lossy().contains('b') {
+                return Ok(ProcessingResult::failure("model refused".to_string()));
+            }
+            fs::copy(input_path, output_path)?;
+            Ok(ProcessingResult::success(
+                output_path.to_string_lossy().to_string(),
+                "copied".to_string(),
+                0,
+            ))
+        }
+    }
+
+    #[test]
+    fn test_model_job_reports_per_file() {
+        let temp = setup();
+        let job = BatchJob::Model {
+            model: Box::new(PickyModel {
+                info: NeuralModelInfo {
+                    id: "picky".to_string(),
+                    name: "Picky".to_string(),
+                    version: "1.0".to_string(),
+                    description: "Refuses some files".to_string(),
+                    capabilities: vec![],
+                    use_when: vec![],
+                    limitations: vec![],
+                    known_artifacts: vec![],
+                    vram_requirement_gb: 0.0,
+                    param_count: None,
+                    inference_time: "instant".to_string(),
+                    supported_params: vec![],
+                },
+            }),
+            params: NeuralModelParams::new(),
+        };
+        let report = run_batch(temp.path(), &job, false).unwrap();
+
+        let a = report
+            .files
+            .iter()
+            .find(|f| f.input.ends_with("a.wav"))
+            .unwrap();
+        assert!(a.success);
+        assert_eq!(a.message, "copied");
+
+        // broken.wav and b.wav both contain a "b"
+        assert_eq!(report.failed(), 2);
+        assert!(report
+            .files
+            .iter()
+            .filter(|f| !f.success)
+            .all(|f| f.message.contains("model refused")));
+    }
+
+    #[test]
+    fn test_invalid_model_params_fail_before_processing() {
+        let temp = setup();
+        let job = BatchJob::Model {
+            model: Box::new(crate::neural::MockDenoise::new()),
+            params: NeuralModelParams::new().with_param("strength", 2.0),
+        };
+
+        let err = run_batch(temp.path(), &job, false).unwrap_err();
+        assert!(err.to_string().contains("strength"), "{}", err);
+        assert!(!temp.path().join(BATCH_OUTPUT_DIR).exists());
+    }
+
+    #[test]
+    fn test_load_chain_preset() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("preset.json");
+        fs::write(
+            &path,
+            r#"[{"type": "gain", "params": {"gain_db": -3.0}}, {"type": "limiter", "enabled": false}]"#,
+        )
+        .unwrap();
+
+        let layer2 = load_chain_preset(&path).unwrap();
+        assert_eq!(layer2.chain.len(), 2);
+        assert_eq!(layer2.chain[0].id, "gain-1");
+        assert_eq!(layer2.chain[0].params["gain_db"], serde_json::json!(-3.0));
+        assert!(!layer2.chain[1].enabled);
+
+        fs::write(&path, r#"[{"type": "flanger"}]"#).unwrap();
+        assert!(load_chain_preset(&path).is_err());
+    }
+}
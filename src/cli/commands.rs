@@ -4,21 +4,44 @@
 
 use std::path::Path;
 
+use chrono::Utc;
 use log::{info, warn};
 
-use crate::agent::{Agent, ToolType};
-use crate::neural::{AceStep, AceStepMode, NeuralModel, NeuralModelParams};
-use crate::state::error::Result;
-use crate::state::{recover_from_crash, Project, UndoManager};
-
-/// Create a new project directory.
-pub fn create_project(path: &Path, input: Option<&Path>) -> Result<()> {
+use super::batch::{load_chain_preset, run_batch, BatchJob, BATCH_OUTPUT_DIR, BATCH_REPORT_FILE};
+use super::diff::StateDiff;
+use super::health::HealthReport;
+use super::{BatchArgs, MarkerAction, RenderArgs};
+
+use crate::agent::{
+    intensity_params, resolve_reference, ActionType as AgentActionType, Agent, AgentAction,
+    AgentResponse, ConversationContext, DspApproximation, Intent, IntentAnalyzer, NeuralFallback,
+    ProcessingMode, ResolvedReference, ToolType,
+};
+use crate::dsp::{self, create_effect, EFFECT_TYPES};
+use crate::engine::io::{
+    export_audio_as, export_audio_with_metadata, AudioFileFormat, ExportFormat,
+};
+use crate::engine::normalize_loudness;
+use crate::neural::{
+    can_run_ace_step, AceStep, CachedModel, NeuralCache, NeuralModel, NeuralModelInfo,
+    NeuralModelParams, ParamSpec, ParamType, ProcessingResult, TimeoutModel, NEURAL_CACHE_DIR,
+};
+use crate::state::error::{NuevaError, Result};
+use crate::state::project::{Effect, CACHE_DIR};
+use crate::state::undo::{ActionNode, ActionTree, ActionType, UndoAction};
+use crate::state::{
+    load_conversation, recover_from_crash, save_conversation, Layer1StorageManager, Project,
+    UndoManager,
+};
+
+/// Create a new project directory that processes at `sample_rate`.
+pub fn create_project(path: &Path, input: Option<&Path>, sample_rate: u32) -> Result<()> {
     info!("Creating new project at: {}", path.display());
 
-    let mut project = Project::create(path, input)?;
+    let mut project = Project::create_with_sample_rate(path, input, sample_rate)?;
     project.save()?;
 
-    println!("Project created: {}", path.display());
+    println!("Project created: {} ({} Hz)", path.display(), sample_rate);
     if let Some(input_path) = input {
         println!("Imported audio: {}", input_path.display());
     }
@@ -67,20 +90,49 @@ pub fn save_state(path: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Undo the last action.
-pub fn undo(path: &Path) -> Result<()> {
+/// Undo the last action, or the most recent action matching `pattern`.
+pub fn undo(path: &Path, pattern: Option<&str>) -> Result<()> {
     info!("Undoing last action in: {}", path.display());
 
     let mut project = Project::load(path)?;
     let mut undo_manager = UndoManager::load(&project.history_dir())?;
 
-    let action = undo_manager.undo(&mut project)?;
-    project.save()?;
-    undo_manager.save(&project.history_dir())?;
+    if undo_project(&mut project, &mut undo_manager, pattern)? {
+        project.save()?;
+        undo_manager.save(&project.history_dir())?;
+    }
+
+    Ok(())
+}
+
+/// Undo against an already-loaded project and history (nothing is saved).
+///
+/// A `pattern` that matches no action is reported and leaves everything
+/// as it was. Returns whether an action was undone.
+pub fn undo_project(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    pattern: Option<&str>,
+) -> Result<bool> {
+    let action = match pattern {
+        Some(pattern) => {
+            let pattern_lower = pattern.to_lowercase();
+            match undo_manager.undo_action_matching(project, |a| {
+                a.description.to_lowercase().contains(&pattern_lower)
+            }) {
+                Err(NuevaError::NoMatchingAction) => {
+                    println!("No action matching \"{}\" found in undo history.", pattern);
+                    return Ok(false);
+                }
+                result => result?,
+            }
+        }
+        None => undo_manager.undo(project)?,
+    };
 
     println!("Undone: {}", action.description);
 
-    Ok(())
+    Ok(true)
 }
 
 /// Redo the last undone action.
@@ -90,62 +142,290 @@ pub fn redo(path: &Path) -> Result<()> {
     let mut project = Project::load(path)?;
     let mut undo_manager = UndoManager::load(&project.history_dir())?;
 
-    let action = undo_manager.redo(&mut project)?;
+    redo_project(&mut project, &mut undo_manager)?;
     project.save()?;
     undo_manager.save(&project.history_dir())?;
 
+    Ok(())
+}
+
+/// Redo against an already-loaded project and history (nothing is saved).
+pub fn redo_project(project: &mut Project, undo_manager: &mut UndoManager) -> Result<()> {
+    let action = undo_manager.redo(project)?;
     println!("Redone: {}", action.description);
 
     Ok(())
 }
 
-/// Show action history.
+/// Show action history as a tree.
+///
+/// The main line of history is printed flush left; actions recorded after
+/// an undo appear as indented branches under the action they forked from.
 pub fn show_history(path: &Path) -> Result<()> {
     info!("Showing history for: {}", path.display());
 
     let project = Project::load(path)?;
     let undo_manager = UndoManager::load(&project.history_dir())?;
 
-    let history = undo_manager.get_history();
+    print_history(&undo_manager);
 
-    if history.is_empty() {
+    Ok(())
+}
+
+/// Print a loaded undo history as a tree.
+pub fn print_history(undo_manager: &UndoManager) {
+    let tree = undo_manager.tree();
+
+    if tree.is_empty() {
         println!("No actions in history.");
-        return Ok(());
+        return;
     }
 
     println!("Action History:");
     println!("{:-<60}", "");
 
-    for (i, action) in history.iter().enumerate() {
-        let marker = if i == undo_manager.current_position() {
+    let roots: Vec<&ActionNode> = tree.children(None).collect();
+    for (i, root) in roots.iter().enumerate() {
+        print_history_line(tree, root, usize::from(i > 0));
+    }
+
+    println!("{:-<60}", "");
+    println!(
+        "Undo stack: {} | Redo stack: {}",
+        undo_manager.undo_count(),
+        undo_manager.redo_count()
+    );
+    let branch_count = undo_manager.branches().len();
+    if branch_count > 1 {
+        println!(
+            "Branches: {} (use 'nueva branches' to switch)",
+            branch_count
+        );
+    }
+}
+
+/// Print a line of history starting at `start`.
+///
+/// Each action's first child continues the line; later children are
+/// alternate branches, printed one level deeper right after their fork point.
+fn print_history_line(tree: &ActionTree, start: &ActionNode, depth: usize) {
+    let mut node = start;
+
+    loop {
+        let action = &node.action;
+        let marker = if tree.current() == Some(action.id.as_str()) {
             ">>> "
         } else {
             "    "
         };
+        let indent = match depth {
+            0 => String::new(),
+            _ if std::ptr::eq(node, start) => format!("{}+- ", "   ".repeat(depth - 1)),
+            _ => "   ".repeat(depth),
+        };
         println!(
-            "{}{}: {} ({})",
+            "{}{}{}: {} ({})",
             marker,
+            indent,
             action.id,
             action.description,
             action.timestamp.format("%Y-%m-%d %H:%M:%S")
         );
+        // A grouped action lists the changes it undoes together
+        if action.steps.len() > 1 {
+            let continuation = "   ".repeat(depth);
+            for step in &action.steps {
+                println!("    {}    - {}", continuation, step);
+            }
+        }
+
+        let children: Vec<&ActionNode> = tree.children(Some(&action.id)).collect();
+        let Some((next, alternates)) = children.split_first() else {
+            break;
+        };
+        for alternate in alternates {
+            print_history_line(tree, alternate, depth + 1);
+        }
+        node = next;
     }
+}
 
-    println!("{:-<60}", "");
-    println!(
-        "Undo stack: {} | Redo stack: {}",
-        undo_manager.undo_count(),
-        undo_manager.redo_count()
-    );
+/// List the branches of the undo history, or switch to one.
+pub fn branches(path: &Path, switch: Option<&str>) -> Result<()> {
+    let mut project = Project::load(path)?;
+    let mut undo_manager = UndoManager::load(&project.history_dir())?;
+
+    branches_project(&mut project, &mut undo_manager, switch)?;
+    if switch.is_some() {
+        project.save()?;
+        undo_manager.save(&project.history_dir())?;
+    }
 
     Ok(())
 }
 
-/// Bake all layers (destructive flatten).
-pub fn bake(path: &Path) -> Result<()> {
+/// List or switch branches of an already-loaded history (nothing is saved).
+pub fn branches_project(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    switch: Option<&str>,
+) -> Result<()> {
+    if let Some(action_id) = switch {
+        let action = undo_manager.switch_branch(project, action_id)?;
+        println!("Switched to: {}", action.description);
+        return Ok(());
+    }
+
+    let branches = undo_manager.branches();
+    if branches.is_empty() {
+        println!("No actions in history.");
+        return Ok(());
+    }
+
+    println!("Branches:");
+    for branch in branches {
+        let marker = if branch.is_active { "* " } else { "  " };
+        println!(
+            "{}{}: {} ({} action(s))",
+            marker, branch.tip_id, branch.description, branch.depth
+        );
+    }
+
+    Ok(())
+}
+
+/// Print what changed between two states of a project.
+///
+/// A state is `current` (the saved project), an undo action ID or unique
+/// ID prefix (the state after that action), the same followed by `^` (the
+/// state before it), or the path of a project JSON file.
+pub fn diff(path: &Path, from: &str, to: &str) -> Result<()> {
+    let project = Project::load(path)?;
+    let undo_manager = UndoManager::load(&project.history_dir())?;
+
+    diff_project(&project, &undo_manager, from, to)
+}
+
+/// Print the diff between two states of an already-loaded project.
+pub fn diff_project(
+    project: &Project,
+    undo_manager: &UndoManager,
+    from: &str,
+    to: &str,
+) -> Result<()> {
+    print!("{}", format_state_diff(project, undo_manager, from, to)?);
+    Ok(())
+}
+
+/// The `diff` report between two states of a loaded project.
+pub fn format_state_diff(
+    project: &Project,
+    undo_manager: &UndoManager,
+    from: &str,
+    to: &str,
+) -> Result<String> {
+    let before = resolve_state(project, undo_manager, from)?;
+    let after = resolve_state(project, undo_manager, to)?;
+
+    Ok(StateDiff::between(&before, &after).to_string())
+}
+
+/// Look up the serialized project state a `diff` argument names.
+fn resolve_state(
+    project: &Project,
+    undo_manager: &UndoManager,
+    spec: &str,
+) -> Result<serde_json::Value> {
+    if spec == "current" {
+        return Ok(serde_json::to_value(project)?);
+    }
+
+    let file = Path::new(spec);
+    if file.is_file() {
+        let contents = std::fs::read_to_string(file).map_err(|e| NuevaError::FileReadError {
+            path: file.to_path_buf(),
+            source: e,
+        })?;
+        return Ok(serde_json::from_str(&contents)?);
+    }
+
+    let (prefix, before) = match spec.strip_suffix('^') {
+        Some(prefix) => (prefix, true),
+        None => (spec, false),
+    };
+    let matches: Vec<&UndoAction> = undo_manager
+        .tree()
+        .nodes()
+        .iter()
+        .map(|node| &node.action)
+        .filter(|action| action.id.starts_with(prefix))
+        .collect();
+
+    match matches.as_slice() {
+        [action] if before => Ok(action.state_before.clone()),
+        [action] => Ok(action.state_after.clone()),
+        [] => Err(NuevaError::UndoActionNotFound {
+            action_id: prefix.to_string(),
+        }),
+        _ => Err(NuevaError::ProcessingFailed {
+            reason: format!(
+                "'{}' matches {} actions in the history; use a longer ID",
+                prefix,
+                matches.len()
+            ),
+        }),
+    }
+}
+
+/// Compare the rendered project against a reference track.
+pub fn compare(path: &Path, reference: &Path) -> Result<()> {
+    let mut project = Project::load(path)?;
+    compare_project(&mut project, reference)
+}
+
+/// Compare an already-loaded project against a reference track.
+pub fn compare_project(project: &mut Project, reference: &Path) -> Result<()> {
+    print!("{}", format_comparison(project, reference)?);
+    Ok(())
+}
+
+/// The `compare` report for a loaded project and a reference file.
+///
+/// The reference stays loaded in the project afterwards.
+pub fn format_comparison(project: &mut Project, reference: &Path) -> Result<String> {
+    project.load_reference(reference)?;
+    let report = project.compare_to_reference()?;
+    Ok(format!("Reference: {}\n{}", reference.display(), report))
+}
+
+/// Bake all layers (destructive flatten), or only the chain up to
+/// `through` into Layer 1.
+pub fn bake(path: &Path, through: Option<&str>) -> Result<()> {
     info!("Baking project: {}", path.display());
 
     let mut project = Project::load(path)?;
+    let mut undo_manager = UndoManager::load(&project.history_dir())?;
+
+    bake_project(&mut project, &mut undo_manager, through)?;
+    if through.is_some() {
+        undo_manager.save(&project.history_dir())?;
+    }
+
+    Ok(())
+}
+
+/// Bake an already-loaded project.
+///
+/// A full bake writes to disk immediately; a partial bake only records
+/// the action in `undo_manager`.
+pub fn bake_project(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    through: Option<&str>,
+) -> Result<()> {
+    if let Some(effect_id) = through {
+        return bake_through(project, undo_manager, effect_id);
+    }
 
     // Pre-bake validation
     project.validate_for_bake()?;
@@ -153,7 +433,10 @@ pub fn bake(path: &Path) -> Result<()> {
     // Confirm with user (in real CLI, would be interactive)
     println!("WARNING: Bake is a destructive operation!");
     println!("This will flatten all layers into a new source.");
-    println!("Layer 0 will be backed up to: {}/backups/", path.display());
+    println!(
+        "Layer 0 will be backed up to: {}/backups/",
+        project.project_path.display()
+    );
 
     project.bake()?;
 
@@ -163,23 +446,214 @@ pub fn bake(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Bake a prefix of the effect chain into Layer 1 and record it for undo.
+fn bake_through(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    effect_id: &str,
+) -> Result<()> {
+    let state_before = serde_json::to_value(&*project)?;
+
+    let layer1_path = project.bake_through(effect_id)?;
+
+    let action = UndoAction::new(
+        ActionType::Bake,
+        format!("Bake effects through {} into Layer 1", effect_id),
+        state_before,
+        serde_json::to_value(&*project)?,
+    );
+    Layer1StorageManager::new(&project.project_path)
+        .record_new_layer1(&project.project_path.join(&layer1_path), &action.id)?;
+    undo_manager.push(action);
+
+    println!("Baked effects through '{}' into Layer 1.", effect_id);
+    println!(
+        "{} effect(s) remain in the chain.",
+        project.layer2.chain.len()
+    );
+
+    Ok(())
+}
+
+/// Edit or list a project's markers, recording edits for undo.
+pub fn marker(path: &Path, action: &MarkerAction) -> Result<()> {
+    let mut project = Project::load(path)?;
+    let mut undo_manager = UndoManager::load(&project.history_dir())?;
+
+    if marker_project(&mut project, &mut undo_manager, action)? {
+        project.save()?;
+        undo_manager.save(&project.history_dir())?;
+    }
+
+    Ok(())
+}
+
+/// Edit or list the markers of an already-loaded project (nothing is
+/// saved). Returns whether the project changed.
+pub fn marker_project(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    action: &MarkerAction,
+) -> Result<bool> {
+    let state_before = serde_json::to_value(&*project)?;
+    let description = match action {
+        MarkerAction::List => {
+            print_markers(project);
+            return Ok(false);
+        }
+        MarkerAction::Add { name, at } => {
+            let sample = seconds_to_samples(project, *at)?;
+            project.add_marker(name, sample)?;
+            println!("Added marker '{}' at {:.3}s.", name.trim(), at);
+            format!("Add marker {}", name.trim())
+        }
+        MarkerAction::Remove { name } => {
+            let marker = project.remove_marker(name)?;
+            println!("Removed marker '{}'.", marker.name);
+            format!("Remove marker {}", marker.name)
+        }
+        MarkerAction::Automate {
+            marker,
+            effect,
+            param,
+            value,
+        } => {
+            let value = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
+            project.schedule_at_marker(marker, effect, param, value.clone())?;
+            println!("At '{}', {}.{} = {}.", marker, effect, param, value);
+            format!("Set {}.{} at marker {}", effect, param, marker)
+        }
+    };
+
+    undo_manager.push(UndoAction::new(
+        ActionType::Marker,
+        description,
+        state_before,
+        serde_json::to_value(&*project)?,
+    ));
+    Ok(true)
+}
+
+/// Print markers with their positions and scheduled changes.
+fn print_markers(project: &Project) {
+    if project.markers.is_empty() {
+        println!("No markers.");
+        return;
+    }
+    let sample_rate = f64::from(project.layer0.sample_rate);
+    for marker in &project.markers {
+        println!(
+            "{:>10.3}s  {}",
+            marker.sample as f64 / sample_rate,
+            marker.name
+        );
+        for change in project.marker_schedule.changes_at(&marker.name) {
+            println!(
+                "             {}.{} = {}",
+                change.effect_id, change.param, change.value
+            );
+        }
+    }
+}
+
+/// Convert a position in seconds to samples at the project rate.
+fn seconds_to_samples(project: &Project, seconds: f64) -> Result<u64> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(NuevaError::ProcessingFailed {
+            reason: format!(
+                "Position must be a non-negative number of seconds, got {}",
+                seconds
+            ),
+        });
+    }
+    Ok((seconds * f64::from(project.layer0.sample_rate)).round() as u64)
+}
+
+/// Trim a project's audio to `start..end` seconds and record it for undo.
+pub fn trim(path: &Path, start: f64, end: Option<f64>) -> Result<()> {
+    let mut project = Project::load(path)?;
+    let mut undo_manager = UndoManager::load(&project.history_dir())?;
+
+    trim_project(&mut project, &mut undo_manager, start, end)?;
+    undo_manager.save(&project.history_dir())?;
+
+    Ok(())
+}
+
+/// Trim an already-loaded project; `end` defaults to the end of the
+/// audio. The trimmed audio is written immediately, the action only
+/// recorded in `undo_manager`.
+pub fn trim_project(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    start: f64,
+    end: Option<f64>,
+) -> Result<()> {
+    let end = end.unwrap_or(project.layer0.duration_seconds);
+    let start_sample = seconds_to_samples(project, start)?;
+    let end_sample = seconds_to_samples(project, end)?;
+    let state_before = serde_json::to_value(&*project)?;
+
+    let layer1_path = project.trim(start_sample, end_sample)?;
+
+    let action = UndoAction::new(
+        ActionType::Trim,
+        format!("Trim to {:.3}s-{:.3}s", start, end),
+        state_before,
+        serde_json::to_value(&*project)?,
+    );
+    Layer1StorageManager::new(&project.project_path)
+        .record_new_layer1(&project.project_path.join(&layer1_path), &action.id)?;
+    undo_manager.push(action);
+
+    println!(
+        "Trimmed to {:.3}s-{:.3}s ({:.3}s).",
+        start, end, project.layer0.duration_seconds
+    );
+
+    Ok(())
+}
+
 /// Print current project state.
-pub fn print_state(path: &Path) -> Result<()> {
+pub fn print_state(path: &Path, json: bool) -> Result<()> {
     let project = Project::load(path)?;
+    print_project_state(&project, json)
+}
 
-    let json = serde_json::to_string_pretty(&project)?;
-    println!("{}", json);
-
-    // Also print storage info
+/// Print the state of a loaded project, with a health summary of its
+/// audio and chain.
+///
+/// With `json`, everything is printed as one JSON object with `project`,
+/// `health`, `storage` and `warnings` keys.
+pub fn print_project_state(project: &Project, json: bool) -> Result<()> {
+    let health = HealthReport::for_project(project);
     let storage_manager = crate::state::Layer1StorageManager::new(&project.project_path);
     let usage = storage_manager.get_storage_usage()?;
+    let warnings = crate::state::storage::check_storage_health(project)?;
+
+    if json {
+        let state = serde_json::json!({
+            "project": project,
+            "health": health,
+            "storage": {
+                "layer1_files": usage.file_count,
+                "layer1_size_mb": usage.total_size_mb,
+            },
+            "warnings": warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&state)?);
+        return Ok(());
+    }
+
+    println!("{}", serde_json::to_string_pretty(&project)?);
+    println!();
+    print!("{}", health);
 
     println!("\n--- Storage Info ---");
     println!("Layer 1 files: {}", usage.file_count);
     println!("Layer 1 size: {:.1} MB", usage.total_size_mb);
 
-    // Check for warnings
-    let warnings = crate::state::storage::check_storage_health(&project)?;
     if !warnings.is_empty() {
         println!("\n--- Warnings ---");
         for warning in warnings {
@@ -190,15 +664,293 @@ pub fn print_state(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Print the parameters of one effect type, or of every effect.
+pub fn list_params(effect: Option<&str>) -> Result<()> {
+    print!("{}", format_param_list(effect)?);
+    Ok(())
+}
+
+/// The `--list-params` listing, generated from each effect's parameter specs.
+pub fn format_param_list(effect: Option<&str>) -> Result<String> {
+    let effect_types = match effect {
+        Some(name) => vec![name],
+        None => EFFECT_TYPES.to_vec(),
+    };
+
+    let mut out = String::new();
+    for name in effect_types {
+        let effect = create_effect(name).ok_or_else(|| NuevaError::ProcessingFailed {
+            reason: format!(
+                "unknown effect '{}'; expected one of: {}",
+                name,
+                EFFECT_TYPES.join(", ")
+            ),
+        })?;
+
+        out.push_str(&format!(
+            "{} ({})\n",
+            effect.effect_type(),
+            effect.display_name()
+        ));
+        let specs = effect.param_specs();
+        if specs.is_empty() {
+            out.push_str("  no parameters in its default state\n");
+        }
+        let width = specs.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        for spec in &specs {
+            out.push_str(&format!(
+                "  {:width$}  {}\n",
+                spec.name,
+                describe_param(spec),
+                width = width
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Range, unit, default and description of one parameter, on one line.
+fn describe_param(spec: &ParamSpec) -> String {
+    let unit = spec
+        .unit
+        .as_deref()
+        .map(|u| format!(" {}", u))
+        .unwrap_or_default();
+    let mut line = match &spec.param_type {
+        ParamType::Float { min, max } => format!("{} to {}{}", min, max, unit),
+        ParamType::Int { min, max } => format!("{} to {}{}", min, max, unit),
+        ParamType::Bool => "true or false".to_string(),
+        ParamType::String => "text".to_string(),
+        ParamType::Enum { options } => options.join(" | "),
+    };
+
+    match &spec.default {
+        Some(serde_json::Value::Number(n)) => {
+            line.push_str(&format!(" (default {})", n.as_f64().unwrap_or(0.0) as f32))
+        }
+        Some(serde_json::Value::String(s)) => line.push_str(&format!(" (default {})", s)),
+        Some(value) => line.push_str(&format!(" (default {})", value)),
+        None => {}
+    }
+    if !spec.description.is_empty() {
+        line.push_str(&format!(" - {}", spec.description));
+    }
+    line
+}
+
+/// Render a project to a file without changing it.
+pub fn render(path: &Path, args: &RenderArgs) -> Result<()> {
+    info!("Rendering project: {}", path.display());
+
+    // Reject bad format/bit-depth combinations before doing any work
+    render_format(args)?;
+
+    let project = Project::load(path)?;
+    render_project(&project, args)
+}
+
+/// Render a loaded project to a file, and its stems to a directory if
+/// asked.
+pub fn render_project(project: &Project, args: &RenderArgs) -> Result<()> {
+    let file_format = render_format(args)?;
+
+    if let Some(output) = &args.output {
+        let mut audio = project.render_output()?;
+        if let Some(target) = args.normalize_lufs {
+            match normalize_loudness(&mut audio, target) {
+                Some(gain_db) => println!("Normalized to {:.1} LUFS ({:+.1} dB)", target, gain_db),
+                None => println!("Output is silent; skipping loudness normalization."),
+            }
+        }
+
+        // Markers, loop and tempo travel with the mix
+        let metadata = project.wav_metadata()?;
+        let format = ExportFormat::new(audio.sample_rate, args.bit_depth);
+        match file_format {
+            AudioFileFormat::Wav => export_audio_with_metadata(&audio, output, format, &metadata),
+            // Markers are stored as WAV chunks, which FLAC can't carry
+            AudioFileFormat::Flac => export_audio_as(&audio, output, file_format, format),
+        }
+        .map_err(failed)?;
+
+        println!(
+            "Rendered {} effect(s) to: {}",
+            project.layer2.chain.len(),
+            output.display()
+        );
+        println!(
+            "Duration: {:.2}s, {}-bit {}",
+            audio.duration_secs(),
+            args.bit_depth,
+            file_format.extension().to_uppercase()
+        );
+    }
+
+    if let Some(dir) = &args.stems {
+        render_stems(project, dir, file_format, args.bit_depth)?;
+    }
+
+    Ok(())
+}
+
+/// Write each layer of a project to `{project}-{stem}.{ext}` in `dir`.
+///
+/// Empty layers are skipped with a note rather than written as a copy of
+/// the layer below or as silence.
+fn render_stems(
+    project: &Project,
+    dir: &Path,
+    file_format: AudioFileFormat,
+    bit_depth: u16,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let project_name = project
+        .project_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+
+    println!("Stems in {}:", dir.display());
+    for stem in project.stems()? {
+        let Some(audio) = stem.audio else {
+            println!(
+                "  {}: skipped ({})",
+                stem.name,
+                stem.note.unwrap_or("empty")
+            );
+            continue;
+        };
+
+        let path = dir.join(format!(
+            "{}-{}.{}",
+            project_name,
+            stem.name,
+            file_format.extension()
+        ));
+        let format = ExportFormat::new(audio.sample_rate, bit_depth);
+        export_audio_as(&audio, &path, file_format, format).map_err(failed)?;
+        println!(
+            "  {}: {} ({} channel(s))",
+            stem.name,
+            path.display(),
+            audio.num_channels()
+        );
+    }
+
+    Ok(())
+}
+
+/// Parse and validate the output format of a render.
+fn render_format(args: &RenderArgs) -> Result<AudioFileFormat> {
+    let invalid = |e: crate::error::NuevaError| NuevaError::InvalidAudioFormat {
+        reason: e.to_string(),
+    };
+    let file_format = AudioFileFormat::parse(&args.format).map_err(invalid)?;
+    file_format
+        .validate_bit_depth(args.bit_depth)
+        .map_err(invalid)?;
+    Ok(file_format)
+}
+
 /// Process audio with AI agent (project-based).
+///
+/// The conversation context is loaded from the project before the prompt
+/// and saved afterwards, so references carry across invocations.
 pub fn agent_process(path: &Path, prompt: &str, tool: &str, dry_run: bool) -> Result<()> {
     info!("Agent processing: {} with prompt: {}", path.display(), prompt);
 
-    let project = Project::load(path)?;
-    let agent = Agent::new();
+    let mut project = Project::load(path)?;
+    let mut undo_manager = UndoManager::load(&project.history_dir())?;
+
+    let loaded = load_conversation(&project.history_dir())?;
+    if let Some(warning) = &loaded.warning {
+        warn!("{}", warning);
+    }
+    let mut context = loaded.context;
+
+    let result = run_agent(
+        &mut project,
+        &mut undo_manager,
+        &mut context,
+        prompt,
+        tool,
+        dry_run,
+    );
+    save_conversation(&project.history_dir(), &mut context)?;
+    if result? {
+        project.save()?;
+        undo_manager.save(&project.history_dir())?;
+    }
+    Ok(())
+}
 
-    // Decide which tool to use
-    let decision = agent.decide_tool(prompt);
+/// Run one agent prompt against a loaded project and conversation.
+///
+/// Everything the prompt changes is recorded as a single undoable
+/// action labelled with the prompt, including the changes that were
+/// applied before a later step failed. A dry run works on a copy of the
+/// conversation, so it leaves no trace there either. Returns whether the
+/// project changed.
+pub fn run_agent(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    context: &mut ConversationContext,
+    prompt: &str,
+    tool: &str,
+    dry_run: bool,
+) -> Result<bool> {
+    let mut scratch;
+    let context = if dry_run {
+        scratch = context.clone();
+        &mut scratch
+    } else {
+        context
+    };
+
+    undo_manager.begin_group(format!("Agent: \"{}\"", prompt));
+    let result = run_agent_prompt(project, undo_manager, context, prompt, tool, dry_run);
+    undo_manager.end_group();
+    result
+}
+
+fn run_agent_prompt(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    context: &mut ConversationContext,
+    prompt: &str,
+    tool: &str,
+    dry_run: bool,
+) -> Result<bool> {
+    let path = &project.project_path;
+    let (gpu_ok, _, gpu_reason) = can_run_ace_step();
+    let neural_fallback = if !gpu_ok {
+        Some(NeuralFallback::Hardware(gpu_reason))
+    } else if !AceStep::new().is_available() {
+        Some(NeuralFallback::NotInstalled(
+            "ACE-Step is not installed".to_string(),
+        ))
+    } else {
+        None
+    };
+    let agent = Agent::with_preferences(&context.user_preferences)
+        .with_neural_fallback(neural_fallback)
+        .with_neural_context(&project.layer1.neural_context);
+
+    // Respond within the conversation (resolves pending clarifications)
+    let mut response = agent.respond(prompt, context);
+
+    // Decide which tool to use, honouring an explicit override
+    let mut decision = match response.decision() {
+        Some(decision) => decision.clone(),
+        None => agent.decide_tool(prompt),
+    };
+    let explanation = agent.explain_decision(&decision);
+    let agent_choice = decision.tool;
+    match tool {
+        "dsp" => decision.tool = ToolType::Dsp,
+        "neural" => decision.tool = ToolType::Neural,
+        _ => {}
+    }
 
     println!("=== Nueva AI Agent ===");
     println!("Project: {}", path.display());
@@ -212,53 +964,154 @@ pub fn agent_process(path: &Path, prompt: &str, tool: &str, dry_run: bool) -> Re
     if !decision.recommendations.is_empty() {
         println!("  Recommendations: {:?}", decision.recommendations);
     }
+    if let Some(approx) = &decision.approximation {
+        println!("  Approximation: DSP stand-in for neural {}", approx.intent);
+    }
 
-    if decision.ask_clarification {
-        println!();
-        println!("Agent needs clarification. Please provide more details.");
-        return Ok(());
+    // "remove the delay", "less reverb": edit what is already in the chain
+    // instead of adding to it
+    let edit = project
+        .layer2
+        .effect_chain()
+        .ok()
+        .and_then(|chain| IntentAnalyzer::plan_edit(&Intent::analyze(prompt), context, &chain));
+    match edit {
+        Some(Ok(action)) => {
+            println!("  Edit: {}", action.description);
+            if dry_run {
+                println!();
+                println!("[Dry run - no changes made]");
+                return Ok(false);
+            }
+            let changed = apply_edit(project, undo_manager, &action)?;
+            let message = action.description.clone();
+            context.add_agent_message_with_action(&message, action);
+            if changed {
+                apply_dc_filter(project, undo_manager, &agent)?;
+                apply_safe_mode(project, undo_manager, &agent, &mut response)?;
+            }
+            return Ok(changed);
+        }
+        Some(Err(message)) => {
+            println!("  {}", message);
+            return Ok(false);
+        }
+        None => {}
+    }
+
+    // Resolve "that", "the reverb", ... against the chain; recording the
+    // target lets later prompts in the conversation refer back to it
+    let target = resolve_reference(prompt, context, &project.layer2.effect_refs());
+    match &target {
+        ResolvedReference::Effect(effect) => {
+            println!("  Target: {} ({})", effect.display_name, effect.id);
+            let action = AgentAction::new(AgentActionType::Modify, decision.tool, prompt)
+                .with_effect(effect.clone())
+                .with_reasoning(&decision.reasoning);
+            context.add_agent_message_with_action(
+                &format!("Targeting {}", effect.display_name),
+                action,
+            );
+        }
+        ResolvedReference::Ambiguous(_) | ResolvedReference::OrdinalOutOfRange { .. } => {
+            if let Some(message) = target.error_message() {
+                println!("  {}", message);
+            }
+        }
+        _ => {}
+    }
+
+    if tool == "auto" {
+        if let AgentResponse::NeedsClarification { question, options } = &response {
+            println!();
+            println!("{}", question);
+            for (i, option) in options.iter().enumerate() {
+                println!("  {}. {}", i + 1, option);
+            }
+            return Ok(false);
+        }
     }
 
     if dry_run {
+        println!();
+        println!("Why this tool:");
+        for line in explanation.lines() {
+            println!("  {}", line);
+        }
+        if decision.tool != agent_choice {
+            println!("  (overridden by --tool {})", tool);
+        }
         println!();
         println!("[Dry run - no changes made]");
-        return Ok(());
+        return Ok(false);
     }
 
     // Execute based on tool type
+    let mut changed = false;
     match decision.tool {
         ToolType::Neural => {
             println!();
             println!("Invoking ACE-Step 1.5...");
 
-            let ace_step = AceStep::new();
+            let ace_step = project_model(project, AceStep::new());
 
             if !ace_step.is_available() {
                 println!("ERROR: ACE-Step not available.");
                 println!("Install with: .\\scripts\\install-ace-step.ps1");
-                return Ok(());
+                return Ok(false);
             }
 
-            // Get Layer 0 audio path from project
-            let layer0_path = project.project_path.join("layer0").join("source.wav");
-            let output_path = project.project_path.join("layer1").join("ai_output.wav");
-
-            // Create layer1 directory if needed
-            std::fs::create_dir_all(output_path.parent().unwrap())?;
+            // The model writes to the cache; Layer 1 gets its own copy
+            let layer0_path = project.project_path.join(&project.layer0.path);
+            let output_path = project.project_path.join(CACHE_DIR).join("ai_output.wav");
+            std::fs::create_dir_all(project.project_path.join(CACHE_DIR))?;
 
             let params = NeuralModelParams::new()
                 .with_param("mode", "transform")
                 .with_param("prompt", prompt)
                 .with_param("intensity", 0.7);
 
-            match ace_step.process(&layer0_path, &output_path, &params) {
-                Ok(result) => {
+            match ace_step.prepare_params(&params).and_then(|params| {
+                ace_step
+                    .process(&layer0_path, &output_path, &params)
+                    .map(|result| (params, result))
+            }) {
+                Ok((params, result)) => {
+                    record_neural_result(
+                        project,
+                        undo_manager,
+                        ace_step.info(),
+                        prompt,
+                        &params,
+                        &result,
+                        &output_path,
+                    )?;
                     println!("Processing complete!");
                     println!("  Message: {}", result.description);
                     if !result.intentional_artifacts.is_empty() {
                         println!("  Intentional artifacts: {:?}", result.intentional_artifacts);
                     }
-                    println!("  Output: {}", output_path.display());
+                    if let Some(seed) = result.seed {
+                        println!("  Seed: {}", seed);
+                    }
+                    println!("  Layer 1: {}", project.layer1.path.display());
+                    changed = true;
+                }
+                Err(e @ crate::error::NuevaError::AceStepTimeout { .. }) => {
+                    println!("{}", e);
+                    println!("Layer 1 is unchanged.");
+                    // Fall back to DSP where the request has a stand-in
+                    match DspApproximation::for_prompt(prompt) {
+                        Some(approx) => {
+                            println!(
+                                "Applying a DSP approximation of {} instead: {}",
+                                approx.intent, approx.description
+                            );
+                            apply_approximation(project, undo_manager, &approx)?;
+                            changed = true;
+                        }
+                        None => println!("Try again with --tool dsp."),
+                    }
                 }
                 Err(e) => {
                     println!("Processing failed: {}", e);
@@ -267,8 +1120,33 @@ pub fn agent_process(path: &Path, prompt: &str, tool: &str, dry_run: bool) -> Re
         }
         ToolType::Dsp => {
             println!();
-            println!("Applying DSP effects...");
-            println!("(DSP chain execution not yet wired to CLI)");
+            if let Some(approx) = &decision.approximation {
+                if !matches!(response, AgentResponse::Executed { .. }) {
+                    println!("{}", response.message());
+                    println!("Reply \"yes\" to apply it.");
+                    return Ok(false);
+                }
+                println!(
+                    "Applying a DSP approximation of {}: {}",
+                    approx.intent, approx.description
+                );
+                println!("  {}", decision.reasoning);
+                apply_approximation(project, undo_manager, approx)?;
+                changed = true;
+            } else if let AgentResponse::Uncertain { message, .. } = &response {
+                println!("{}", message);
+                return Ok(false);
+            } else {
+                let intent = Intent::analyze(prompt);
+                println!(
+                    "Applying DSP effects at {:.0}% intensity...",
+                    intent.intensity * 100.0
+                );
+                changed = apply_intensity_effects(project, undo_manager, &intent)?;
+                if !changed {
+                    println!("  No effect with an intensity setting was asked for.");
+                }
+            }
         }
         ToolType::Both => {
             println!();
@@ -279,12 +1157,267 @@ pub fn agent_process(path: &Path, prompt: &str, tool: &str, dry_run: bool) -> Re
         ToolType::AskClarification => {
             println!();
             println!("Please provide more details about what you want to do.");
+            return Ok(false);
+        }
+    }
+
+    // Only a changed chain needs checking again
+    if changed {
+        apply_dc_filter(project, undo_manager, &agent)?;
+        apply_safe_mode(project, undo_manager, &agent, &mut response)?;
+    }
+    Ok(changed)
+}
+
+/// `model` as the agent runs it on a project: each run times out (see
+/// [`TimeoutModel`]) and seeded results are cached in the project.
+///
+/// A cache that can't be opened only costs the speed-up, so the model
+/// then runs uncached.
+fn project_model<M: NeuralModel + 'static>(project: &Project, model: M) -> Box<dyn NeuralModel> {
+    let model = TimeoutModel::from_env(model);
+    let dir = project.project_path.join(CACHE_DIR).join(NEURAL_CACHE_DIR);
+    let cache = NeuralCache::new(dir).and_then(|cache| {
+        cache
+            .sync_source(&project.layer0.hash_sha256)
+            .map(|_| cache)
+    });
+    match cache {
+        Ok(cache) => Box::new(CachedModel::new(model, cache)),
+        Err(e) => {
+            warn!("Neural cache unavailable: {}", e);
+            Box::new(model)
+        }
+    }
+}
+
+/// Make a neural model's output the new Layer 1 and record it for undo.
+///
+/// The model, its version, parameters, seed and time go into the Layer 1
+/// metadata; the model's output file is removed once stored.
+fn record_neural_result(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    model: &NeuralModelInfo,
+    prompt: &str,
+    params: &NeuralModelParams,
+    result: &ProcessingResult,
+    output: &Path,
+) -> Result<()> {
+    let state_before = serde_json::to_value(&*project)?;
+
+    let layer1_path = project.apply_ai_result(model, prompt, params, result, output)?;
+
+    let action = UndoAction::new(
+        ActionType::AiProcessing,
+        format!("{}: \"{}\"", model.name, prompt),
+        state_before,
+        serde_json::to_value(&*project)?,
+    );
+    Layer1StorageManager::new(&project.project_path)
+        .record_new_layer1(&project.project_path.join(&layer1_path), &action.id)?;
+    undo_manager.push(action);
+
+    if let Err(e) = std::fs::remove_file(output) {
+        warn!("Could not remove {}: {}", output.display(), e);
+    }
+    Ok(())
+}
+
+/// Append a limiter to the chain when safe mode predicts clipping.
+///
+/// The chain is dry-run over the Layer 1 audio. An added limiter is an
+/// ordinary chain edit: it is recorded for undo and can be removed like
+/// any other effect. The agent notes why in `response`, which is printed.
+/// Returns whether one was added.
+fn apply_safe_mode(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    agent: &Agent,
+    response: &mut AgentResponse,
+) -> Result<bool> {
+    if !agent.safe_mode() {
+        return Ok(false);
+    }
+    let Some((chain, input)) = chain_and_input(project, "Safe mode")? else {
+        return Ok(false);
+    };
+    let Some(rec) = agent
+        .safe_mode_limiter(&chain, &input, response)
+        .map_err(failed)?
+    else {
+        return Ok(false);
+    };
+    let Some(limiter) = rec.mitigation.as_ref().and_then(|m| m.to_effect()) else {
+        return Ok(false);
+    };
+
+    let effect = agent_effect(project, limiter.as_ref())?;
+    println!();
+    println!("Safe mode: added {} at the end of the chain.", effect.id);
+    println!("  {}", response.message());
+    project.add_effect(undo_manager, effect)?;
+    Ok(true)
+}
+
+/// Add a high-pass to the chain when its output would carry a DC offset.
+///
+/// Like the safe-mode limiter, the filter is an ordinary chain edit
+/// recorded for undo; it goes where an EQ belongs in the chain. Returns
+/// whether one was added.
+fn apply_dc_filter(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    agent: &Agent,
+) -> Result<bool> {
+    let Some((chain, input)) = chain_and_input(project, "DC offset check")? else {
+        return Ok(false);
+    };
+    let Some(rec) = agent.dc_offset_filter(&chain, &input).map_err(failed)? else {
+        return Ok(false);
+    };
+    let Some(high_pass) = rec.mitigation.as_ref().and_then(|m| m.to_effect()) else {
+        return Ok(false);
+    };
+
+    let effect = agent_effect(project, high_pass.as_ref())?;
+    let id = effect.id.clone();
+    let index = project.add_effect_ordered(undo_manager, effect)?;
+    println!();
+    println!("Added {} at position {} to remove a DC offset.", id, index);
+    println!("  Reason: {}", rec.message);
+    println!("  Undo to remove it.");
+    Ok(true)
+}
+
+/// The project's chain and its Layer 1 input, for a dry run.
+///
+/// Audio that can't be loaded skips the `check` (with a warning) rather
+/// than failing the prompt.
+fn chain_and_input(
+    project: &Project,
+    check: &str,
+) -> Result<Option<(dsp::EffectChain, dsp::AudioBuffer)>> {
+    let source = match project.load_layer1() {
+        Ok(source) => source,
+        Err(e) => {
+            warn!("{} skipped: {}", check, e);
+            return Ok(None);
+        }
+    };
+    let input = dsp::AudioBuffer::from_engine(&source).map_err(failed)?;
+    let chain = project.layer2.effect_chain().map_err(failed)?;
+    Ok(Some((chain, input)))
+}
+
+/// Report an engine error as a failed processing step.
+fn failed(e: crate::error::NuevaError) -> NuevaError {
+    NuevaError::ProcessingFailed {
+        reason: e.to_string(),
+    }
+}
+
+/// Apply a planned removal, reduction or ceiling to the chain. Each
+/// change is recorded for undo; returns whether anything changed.
+fn apply_edit(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    action: &AgentAction,
+) -> Result<bool> {
+    let Some(target) = &action.affected_effect else {
+        return Ok(false);
+    };
+    match action.action_type {
+        AgentActionType::Remove => {
+            project.remove_effect(undo_manager, &target.id)?;
+            Ok(true)
+        }
+        AgentActionType::Add => {
+            let mut dsp_effect =
+                create_effect(&target.effect_type).ok_or_else(|| NuevaError::ProcessingFailed {
+                    reason: format!("unknown effect '{}'", target.effect_type),
+                })?;
+            for change in &action.parameter_changes {
+                dsp_effect
+                    .set_param(&change.param, change.new_value.clone())
+                    .map_err(failed)?;
+            }
+            let effect = agent_effect(project, dsp_effect.as_ref())?;
+            project.add_effect(undo_manager, effect)?;
+            Ok(true)
+        }
+        _ => {
+            let mut changed = false;
+            for change in &action.parameter_changes {
+                changed |= project.set_effect_param(
+                    undo_manager,
+                    &target.id,
+                    &change.param,
+                    change.new_value.clone(),
+                )?;
+            }
+            Ok(changed)
         }
     }
+}
 
+/// Add the effects of a DSP approximation to the chain, each in its
+/// default position.
+fn apply_approximation(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    approx: &DspApproximation,
+) -> Result<()> {
+    let chain = approx.build_chain().map_err(failed)?;
+    for dsp_effect in chain.iter() {
+        let effect = agent_effect(project, dsp_effect)?;
+        println!("  Added {}", effect.id);
+        project.add_effect_ordered(undo_manager, effect)?;
+    }
     Ok(())
 }
 
+/// Add each effect the prompt mentions, set by its intensity curve (see
+/// [`intensity_params`]); effects without a curve are skipped. Returns
+/// whether any effect was added.
+fn apply_intensity_effects(
+    project: &mut Project,
+    undo_manager: &mut UndoManager,
+    intent: &Intent,
+) -> Result<bool> {
+    let mut added = false;
+    for effect_type in &intent.mentioned_effects {
+        let Some(params) = intensity_params(effect_type, intent.intensity) else {
+            continue;
+        };
+        let dsp_effect =
+            dsp::build_effect(effect_type, effect_type, true, &params).map_err(failed)?;
+        let effect = agent_effect(project, dsp_effect.as_ref())?;
+        println!("  Added {}", effect.id);
+        project.add_effect_ordered(undo_manager, effect)?;
+        added = true;
+    }
+    Ok(added)
+}
+
+/// A project effect for a DSP effect the agent added
+fn agent_effect(project: &Project, effect: &dyn dsp::Effect) -> Result<Effect> {
+    let state = dsp::effect_to_json(effect).map_err(failed)?;
+    Ok(Effect {
+        id: project.layer2.generate_id(effect.effect_type()),
+        effect_type: effect.effect_type().to_string(),
+        enabled: true,
+        params: state["params"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+        added_at: Utc::now(),
+        added_by: "agent".to_string(),
+    })
+}
+
 /// Process a standalone audio file with ACE-Step.
 pub fn process_audio(
     input: &Path,
@@ -300,6 +1433,13 @@ pub fn process_audio(
         println!("ERROR: Input file not found: {}", input.display());
         return Ok(());
     }
+    let mode = match mode.parse::<ProcessingMode>() {
+        Ok(mode) => mode,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return Ok(());
+        }
+    };
 
     // Determine output path
     let output_path = match output {
@@ -320,7 +1460,7 @@ pub fn process_audio(
     println!();
 
     // Initialize ACE-Step
-    let ace_step = AceStep::new();
+    let ace_step = TimeoutModel::from_env(AceStep::new());
 
     if !ace_step.is_available() {
         println!("ERROR: ACE-Step not available.");
@@ -332,26 +1472,22 @@ pub fn process_audio(
         return Ok(());
     }
 
+    let params = match route_mode(&ace_step, prompt, mode, intensity) {
+        Ok(params) => params,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return Ok(());
+        }
+    };
+
     println!("Using: {}", ace_step.info().name);
     println!("Processing...");
     println!();
 
-    // Map mode string to AceStepMode
-    let ace_mode = match mode {
-        "cover" => AceStepMode::Cover,
-        "repaint" => AceStepMode::Repaint,
-        "extract" => AceStepMode::Extract,
-        "layer" => AceStepMode::Layer,
-        "complete" => AceStepMode::Complete,
-        _ => AceStepMode::Transform,
-    };
-
-    let params = NeuralModelParams::new()
-        .with_param("mode", ace_mode.to_string())
-        .with_param("prompt", prompt)
-        .with_param("intensity", intensity);
-
-    match ace_step.process(input, &output_path, &params) {
+    match ace_step
+        .prepare_params(&params)
+        .and_then(|params| ace_step.process(input, &output_path, &params))
+    {
         Ok(result) => {
             println!("=== Processing Complete ===");
             println!("Message: {}", result.description);
@@ -375,3 +1511,72 @@ pub fn process_audio(
 
     Ok(())
 }
+
+/// Have the agent route a prompt in an explicit mode to `model`,
+/// returning the parameters to run it with.
+///
+/// Fails if the model can't run the mode.
+fn route_mode(
+    model: &dyn NeuralModel,
+    prompt: &str,
+    mode: ProcessingMode,
+    intensity: f32,
+) -> Result<NeuralModelParams> {
+    let (response, route) = Agent::new()
+        .respond_to_mode(prompt, mode, model.info(), intensity)
+        .map_err(failed)?;
+    println!("{}", response.message());
+    Ok(route.params)
+}
+
+/// Process every audio file in a directory with a prompt or chain preset.
+pub fn batch(args: &BatchArgs) -> Result<()> {
+    let dir = args.dir.as_path();
+    info!("Batch processing directory: {}", dir.display());
+
+    let job = match (&args.prompt, &args.chain) {
+        (_, Some(preset)) => BatchJob::Chain(load_chain_preset(preset)?),
+        (Some(prompt), None) => {
+            let mode = args.mode.parse::<ProcessingMode>().map_err(failed)?;
+            let ace_step = TimeoutModel::from_env(AceStep::new());
+            if !ace_step.is_available() {
+                println!("ERROR: ACE-Step not available.");
+                println!("Set NUEVA_ACE_STEP_PATH or use --chain for DSP-only processing.");
+                return Ok(());
+            }
+            let params = route_mode(&ace_step, prompt, mode, args.intensity)?;
+            BatchJob::Model {
+                model: Box::new(ace_step),
+                params,
+            }
+        }
+        (None, None) => {
+            println!("ERROR: Provide either --prompt or --chain.");
+            return Ok(());
+        }
+    };
+
+    let report = run_batch(dir, &job, args.recursive)?;
+
+    println!("=== Batch Complete ===");
+    for file in &report.files {
+        let status = if file.success { "OK  " } else { "FAIL" };
+        println!("  [{}] {}: {}", status, file.input.display(), file.message);
+    }
+    for path in &report.skipped {
+        println!("  [SKIP] {}: unsupported format", path.display());
+    }
+    println!();
+    println!(
+        "{} succeeded, {} failed, {} skipped",
+        report.succeeded(),
+        report.failed(),
+        report.skipped.len()
+    );
+    println!(
+        "Report saved to: {}",
+        dir.join(BATCH_OUTPUT_DIR).join(BATCH_REPORT_FILE).display()
+    );
+
+    Ok(())
+}
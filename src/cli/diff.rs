@@ -0,0 +1,545 @@
+//! Project state diffs
+//!
+//! Compares two serialized project states (`project.json`, or a snapshot
+//! from the undo history) and describes what changed in the Layer 2 effect
+//! chain and in the layer status. Effects are matched by ID, so a reordered
+//! chain reads as moves rather than removals and additions.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::dsp::{build_effect, create_effect, effect_to_json};
+
+/// Layer sections whose fields are compared
+const LAYER_SECTIONS: [&str; 2] = ["layer0", "layer1"];
+
+/// One change to the effect chain
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainChange {
+    /// An effect only in the new state, at its (0-based) position there
+    Added {
+        id: String,
+        effect_type: String,
+        position: usize,
+    },
+    /// An effect only in the old state
+    Removed { id: String, effect_type: String },
+    /// An effect whose position changed relative to the effects around it
+    Moved {
+        id: String,
+        effect_type: String,
+        from: usize,
+        to: usize,
+    },
+    /// An effect that was enabled or bypassed
+    Enabled {
+        id: String,
+        effect_type: String,
+        enabled: bool,
+    },
+    /// A parameter value change; `None` means the parameter was not set
+    Param {
+        id: String,
+        effect_type: String,
+        param: String,
+        old: Option<Value>,
+        new: Option<Value>,
+        unit: Option<String>,
+    },
+}
+
+/// A changed layer status field, e.g. `layer1.is_processed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerChange {
+    pub field: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Differences between two project states
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    pub chain: Vec<ChainChange>,
+    pub layers: Vec<LayerChange>,
+}
+
+/// An effect entry as read from a state's `layer2.chain`
+struct ChainEntry {
+    id: String,
+    effect_type: String,
+    enabled: bool,
+    params: Map<String, Value>,
+}
+
+impl StateDiff {
+    /// Compare two serialized project states.
+    pub fn between(from: &Value, to: &Value) -> Self {
+        let old_chain = chain_entries(from);
+        let new_chain = chain_entries(to);
+        let old_index: HashMap<&str, usize> = old_chain
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.id.as_str(), i))
+            .collect();
+        let new_index: HashMap<&str, usize> = new_chain
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.id.as_str(), i))
+            .collect();
+
+        let mut chain = Vec::new();
+
+        for entry in &old_chain {
+            if !new_index.contains_key(entry.id.as_str()) {
+                chain.push(ChainChange::Removed {
+                    id: entry.id.clone(),
+                    effect_type: entry.effect_type.clone(),
+                });
+            }
+        }
+        for (position, entry) in new_chain.iter().enumerate() {
+            if !old_index.contains_key(entry.id.as_str()) {
+                chain.push(ChainChange::Added {
+                    id: entry.id.clone(),
+                    effect_type: entry.effect_type.clone(),
+                    position,
+                });
+            }
+        }
+
+        // Effects outside the longest run kept in the same relative order
+        // are the ones that moved; the rest only shifted around them
+        let old_common: Vec<&str> = old_chain
+            .iter()
+            .map(|e| e.id.as_str())
+            .filter(|id| new_index.contains_key(id))
+            .collect();
+        let new_common: Vec<&str> = new_chain
+            .iter()
+            .map(|e| e.id.as_str())
+            .filter(|id| old_index.contains_key(id))
+            .collect();
+        let in_place = longest_common_subsequence(&old_common, &new_common);
+        for id in new_common.iter().filter(|id| !in_place.contains(id)) {
+            let entry = &new_chain[new_index[id]];
+            chain.push(ChainChange::Moved {
+                id: entry.id.clone(),
+                effect_type: entry.effect_type.clone(),
+                from: old_index[id],
+                to: new_index[id],
+            });
+        }
+
+        for new in &new_chain {
+            let Some(&i) = old_index.get(new.id.as_str()) else {
+                continue;
+            };
+            let old = &old_chain[i];
+            if old.enabled != new.enabled {
+                chain.push(ChainChange::Enabled {
+                    id: new.id.clone(),
+                    effect_type: new.effect_type.clone(),
+                    enabled: new.enabled,
+                });
+            }
+            chain.extend(param_changes(old, new));
+        }
+
+        let layers = LAYER_SECTIONS
+            .iter()
+            .flat_map(|section| {
+                let old = flatten(section, &from[*section]);
+                let new = flatten(section, &to[*section]);
+                changed_fields(&old, &new)
+                    .into_iter()
+                    .map(|(field, old, new)| LayerChange { field, old, new })
+            })
+            .collect();
+
+        Self { chain, layers }
+    }
+
+    /// Whether the two states were the same
+    pub fn is_empty(&self) -> bool {
+        self.chain.is_empty() && self.layers.is_empty()
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes.");
+        }
+
+        if !self.chain.is_empty() {
+            writeln!(f, "Effect chain:")?;
+            for change in &self.chain {
+                match change {
+                    ChainChange::Added {
+                        id,
+                        effect_type,
+                        position,
+                    } => writeln!(
+                        f,
+                        "  + {} ({}) added at position {}",
+                        id,
+                        effect_type,
+                        position + 1
+                    )?,
+                    ChainChange::Removed { id, effect_type } => {
+                        writeln!(f, "  - {} ({}) removed", id, effect_type)?
+                    }
+                    ChainChange::Moved {
+                        id,
+                        effect_type,
+                        from,
+                        to,
+                    } => writeln!(
+                        f,
+                        "  > {} ({}) moved from position {} to {}",
+                        id,
+                        effect_type,
+                        from + 1,
+                        to + 1
+                    )?,
+                    ChainChange::Enabled {
+                        id,
+                        effect_type,
+                        enabled,
+                    } => writeln!(
+                        f,
+                        "  ~ {} ({}) {}",
+                        id,
+                        effect_type,
+                        if *enabled { "enabled" } else { "bypassed" }
+                    )?,
+                    ChainChange::Param {
+                        id,
+                        effect_type,
+                        param,
+                        old,
+                        new,
+                        unit,
+                    } => writeln!(
+                        f,
+                        "  ~ {} ({}) {}: {} -> {}",
+                        id,
+                        effect_type,
+                        param,
+                        format_value(old.as_ref(), unit.as_deref()),
+                        format_value(new.as_ref(), unit.as_deref())
+                    )?,
+                }
+            }
+        }
+
+        if !self.layers.is_empty() {
+            writeln!(f, "Layers:")?;
+            for change in &self.layers {
+                writeln!(
+                    f,
+                    "  ~ {}: {} -> {}",
+                    change.field,
+                    format_value(change.old.as_ref(), None),
+                    format_value(change.new.as_ref(), None)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the effect records of a state's Layer 2 chain
+fn chain_entries(state: &Value) -> Vec<ChainEntry> {
+    state["layer2"]["chain"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|effect| {
+            let effect_type = effect["type"].as_str().unwrap_or_default().to_string();
+            let enabled = effect["enabled"].as_bool().unwrap_or(true);
+            let raw = effect["params"].as_object().cloned().unwrap_or_default();
+            ChainEntry {
+                id: effect["id"].as_str().unwrap_or_default().to_string(),
+                params: effective_params(&effect_type, enabled, &raw),
+                effect_type,
+                enabled,
+            }
+        })
+        .collect()
+}
+
+/// The full parameter set an effect runs with, so an unset parameter and
+/// its default compare equal. Records the factory can't build are compared
+/// as stored.
+fn effective_params(
+    effect_type: &str,
+    enabled: bool,
+    raw: &Map<String, Value>,
+) -> Map<String, Value> {
+    build_effect(effect_type, "", enabled, raw)
+        .and_then(|effect| effect_to_json(effect.as_ref()))
+        .ok()
+        .and_then(|json| json["params"].as_object().cloned())
+        .unwrap_or_else(|| raw.clone())
+}
+
+/// Parameter changes between two versions of one effect, with units from
+/// the effect's parameter specs
+fn param_changes(old: &ChainEntry, new: &ChainEntry) -> Vec<ChainChange> {
+    let old_params = flatten_params(&old.params);
+    let new_params = flatten_params(&new.params);
+    let changes = changed_fields(&old_params, &new_params);
+    if changes.is_empty() {
+        return Vec::new();
+    }
+
+    let specs = build_effect(&new.effect_type, "", new.enabled, &new.params)
+        .ok()
+        .or_else(|| create_effect(&new.effect_type))
+        .map(|effect| effect.param_specs())
+        .unwrap_or_default();
+    let units: HashMap<String, String> = specs
+        .into_iter()
+        .filter_map(|spec| spec.unit.map(|unit| (spec.name, unit)))
+        .collect();
+
+    changes
+        .into_iter()
+        .map(|(param, old, new_value)| ChainChange::Param {
+            id: new.id.clone(),
+            effect_type: new.effect_type.clone(),
+            unit: units.get(&param).cloned(),
+            param,
+            old,
+            new: new_value,
+        })
+        .collect()
+}
+
+/// Flatten effect params into dotted names, matching the param spec names
+/// of nested parameters such as `bands.0.gain_db`
+fn flatten_params(params: &Map<String, Value>) -> Vec<(String, Value)> {
+    params
+        .iter()
+        .flat_map(|(name, value)| flatten(name, value))
+        .collect()
+}
+
+/// Flatten a value into `(dotted path, scalar)` pairs
+fn flatten(prefix: &str, value: &Value) -> Vec<(String, Value)> {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        Value::Null => return Vec::new(),
+        scalar => return vec![(prefix.to_string(), scalar.clone())],
+    };
+    children
+        .into_iter()
+        .flat_map(|(key, child)| flatten(&format!("{}.{}", prefix, key), child))
+        .collect()
+}
+
+/// Fields whose values differ, in the order they first appear
+fn changed_fields(
+    old: &[(String, Value)],
+    new: &[(String, Value)],
+) -> Vec<(String, Option<Value>, Option<Value>)> {
+    let old_map: HashMap<&str, &Value> = old.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    let new_map: HashMap<&str, &Value> = new.iter().map(|(k, v)| (k.as_str(), v)).collect();
+
+    let mut fields: Vec<&str> = old.iter().map(|(k, _)| k.as_str()).collect();
+    fields.extend(
+        new.iter()
+            .map(|(k, _)| k.as_str())
+            .filter(|k| !old_map.contains_key(k)),
+    );
+
+    fields
+        .into_iter()
+        .filter(|field| old_map.get(field) != new_map.get(field))
+        .map(|field| {
+            (
+                field.to_string(),
+                old_map.get(field).map(|v| (*v).clone()),
+                new_map.get(field).map(|v| (*v).clone()),
+            )
+        })
+        .collect()
+}
+
+/// IDs in the longest common subsequence of two orderings
+fn longest_common_subsequence<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<&'a str> {
+    // lengths[i][j]: LCS length of a[i..] and b[j..]
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut common = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            common.push(a[i]);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] > lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    common
+}
+
+/// A value for display: numbers at f32 precision, strings unquoted
+fn format_value(value: Option<&Value>, unit: Option<&str>) -> String {
+    let text = match value {
+        None => return "(unset)".to_string(),
+        Some(Value::Number(n)) => match n.as_i64() {
+            Some(i) => i.to_string(),
+            None => (n.as_f64().unwrap_or(0.0) as f32).to_string(),
+        },
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    };
+    match unit {
+        Some(unit) => format!("{} {}", text, unit),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn effect(id: &str, effect_type: &str, params: Value) -> Value {
+        json!({"id": id, "type": effect_type, "enabled": true, "params": params})
+    }
+
+    fn state(chain: Vec<Value>) -> Value {
+        json!({
+            "layer0": {"path": "audio/layer0.wav", "duration_seconds": 2.0},
+            "layer1": {"path": "audio/layer1.wav", "is_processed": false},
+            "layer2": {"chain": chain},
+        })
+    }
+
+    #[test]
+    fn test_identical_states_have_no_changes() {
+        let a = state(vec![
+            effect("gain-1", "gain", json!({"gain_db": -6.0})),
+            effect("comp-1", "compressor", json!({})),
+        ]);
+        let diff = StateDiff::between(&a, &a.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No changes.\n");
+    }
+
+    #[test]
+    fn test_unset_param_matches_its_default() {
+        let a = state(vec![effect("gain-1", "gain", json!({}))]);
+        let b = state(vec![effect("gain-1", "gain", json!({"gain_db": 0.0}))]);
+        assert!(StateDiff::between(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_reorder_is_a_single_move() {
+        let a = state(vec![
+            effect("eq-1", "parametric_eq", json!({})),
+            effect("gain-1", "gain", json!({})),
+            effect("comp-1", "compressor", json!({})),
+            effect("lim-1", "limiter", json!({})),
+        ]);
+        let b = state(vec![
+            effect("gain-1", "gain", json!({})),
+            effect("comp-1", "compressor", json!({})),
+            effect("eq-1", "parametric_eq", json!({})),
+            effect("lim-1", "limiter", json!({})),
+        ]);
+
+        let diff = StateDiff::between(&a, &b);
+        assert_eq!(
+            diff.chain,
+            vec![ChainChange::Moved {
+                id: "eq-1".to_string(),
+                effect_type: "parametric_eq".to_string(),
+                from: 0,
+                to: 2,
+            }]
+        );
+        assert!(diff
+            .to_string()
+            .contains("eq-1 (parametric_eq) moved from position 1 to 3"));
+    }
+
+    #[test]
+    fn test_added_removed_and_changed_effects() {
+        let a = state(vec![
+            effect("gain-1", "gain", json!({"gain_db": -6.0})),
+            effect("gate-1", "gate", json!({})),
+        ]);
+        let mut b = state(vec![
+            effect("gain-1", "gain", json!({"gain_db": -3.5})),
+            effect("verb-1", "reverb", json!({})),
+        ]);
+        b["layer2"]["chain"][0]["enabled"] = json!(false);
+        b["layer1"]["is_processed"] = json!(true);
+
+        let text = StateDiff::between(&a, &b).to_string();
+        assert!(text.contains("- gate-1 (gate) removed"), "{}", text);
+        assert!(
+            text.contains("+ verb-1 (reverb) added at position 2"),
+            "{}",
+            text
+        );
+        assert!(text.contains("~ gain-1 (gain) bypassed"), "{}", text);
+        assert!(
+            text.contains("~ gain-1 (gain) gain_db: -6 dB -> -3.5 dB"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("layer1.is_processed: false -> true"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("layer0"), "{}", text);
+    }
+
+    #[test]
+    fn test_nested_params_use_spec_units() {
+        let band = |gain_db: f64| {
+            json!({"bands": [{"frequency": 1000.0, "gain_db": gain_db, "q": 1.0,
+                              "filter_type": "peak", "enabled": true}]})
+        };
+        let a = state(vec![effect("eq-1", "parametric_eq", band(0.0))]);
+        let b = state(vec![effect("eq-1", "parametric_eq", band(4.0))]);
+
+        let diff = StateDiff::between(&a, &b);
+        assert_eq!(diff.chain.len(), 1);
+        match &diff.chain[0] {
+            ChainChange::Param {
+                param, unit, new, ..
+            } => {
+                assert_eq!(param, "bands.0.gain_db");
+                assert_eq!(unit.as_deref(), Some("dB"));
+                assert_eq!(new, &Some(json!(4.0)));
+            }
+            other => panic!("expected a param change, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Project health summary
+//!
+//! The at-a-glance part of `print-state`: levels, validation, true peak
+//! and stereo correlation of the active audio, and the effect chain with
+//! its latency and tail. The active audio is Layer 1, or Layer 0 when
+//! Layer 1 can't be loaded.
+//!
+//! The text form prints one `key: value` per line with fixed keys, and
+//! levels that don't exist (silence, empty audio) print as `n/a`. The
+//! JSON form serializes the same report, with `null` for those levels.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::dsp::{self, true_peak_db};
+use crate::engine::buffer::{calculate_peak, calculate_rms, AudioBuffer};
+use crate::engine::{import_audio_at, integrated_loudness};
+use crate::state::project::Project;
+
+/// Health of a project's active audio and effect chain
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub audio: AudioHealth,
+    pub chain: ChainHealth,
+}
+
+/// Whether there is audio to measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioStatus {
+    /// Audio with signal in it
+    Ok,
+    /// Audio that is all (or nearly all) silence
+    Silent,
+    /// Audio with no samples
+    Empty,
+    /// Neither layer could be loaded
+    Unavailable,
+}
+
+/// Levels and validation of the active audio
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioHealth {
+    /// Layer the audio came from ("layer1" or "layer0")
+    pub source: Option<&'static str>,
+    pub status: AudioStatus,
+    /// Why the audio couldn't be loaded
+    pub error: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<usize>,
+    pub duration_secs: Option<f64>,
+    pub peak_dbfs: Option<f32>,
+    pub rms_dbfs: Option<f32>,
+    pub lufs: Option<f32>,
+    pub true_peak_dbtp: Option<f32>,
+    /// Correlation of the first two channels; `None` for mono
+    pub correlation: Option<f64>,
+    pub valid: bool,
+    pub failed_checks: Vec<&'static str>,
+}
+
+/// One effect in the chain
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectHealth {
+    pub id: String,
+    pub effect_type: String,
+    pub enabled: bool,
+    /// `None` if the effect couldn't be built
+    pub latency_samples: Option<usize>,
+    pub tail_samples: Option<usize>,
+}
+
+/// The effect chain and its totals over enabled effects
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainHealth {
+    pub effects: Vec<EffectHealth>,
+    pub latency_samples: Option<usize>,
+    pub tail_samples: Option<usize>,
+    /// Why the chain couldn't be built
+    pub error: Option<String>,
+}
+
+impl HealthReport {
+    /// Measure a project's active audio and chain
+    ///
+    /// Never fails: audio or a chain that can't be loaded is reported in
+    /// the summary instead.
+    pub fn for_project(project: &Project) -> Self {
+        Self {
+            audio: AudioHealth::for_project(project),
+            chain: ChainHealth::for_project(project),
+        }
+    }
+}
+
+impl AudioHealth {
+    fn for_project(project: &Project) -> Self {
+        let layer0 = || {
+            import_audio_at(
+                &project.project_path.join(&project.layer0.path),
+                project.layer0.sample_rate,
+            )
+        };
+        match project.load_layer1() {
+            Ok(buffer) => Self::measure("layer1", &buffer),
+            Err(layer1_error) => match layer0() {
+                Ok(buffer) => Self::measure("layer0", &buffer),
+                Err(e) => Self::unavailable(format!("layer1: {}; layer0: {}", layer1_error, e)),
+            },
+        }
+    }
+
+    /// Measure loaded audio from `source`
+    pub fn measure(source: &'static str, buffer: &AudioBuffer) -> Self {
+        let validation = buffer.get_validation();
+        let status = if buffer.is_empty() {
+            AudioStatus::Empty
+        } else if validation.not_silent {
+            AudioStatus::Ok
+        } else {
+            AudioStatus::Silent
+        };
+        let level = |db: f32| Some(db).filter(|db| db.is_finite());
+
+        let mut health = Self {
+            source: Some(source),
+            status,
+            error: None,
+            sample_rate: Some(buffer.sample_rate),
+            channels: Some(buffer.num_channels()),
+            duration_secs: Some(buffer.duration_secs()),
+            peak_dbfs: level(calculate_peak(buffer)),
+            rms_dbfs: level(calculate_rms(buffer)),
+            lufs: level(integrated_loudness(buffer)),
+            true_peak_dbtp: None,
+            correlation: None,
+            valid: validation.is_valid(),
+            failed_checks: validation.failed_checks(),
+        };
+
+        // True peak and correlation only mean something with signal
+        if status == AudioStatus::Ok {
+            if let Ok(interleaved) = dsp::AudioBuffer::from_engine(buffer) {
+                health.true_peak_dbtp = Some(true_peak_db(&interleaved));
+                if interleaved.num_channels() > 1 {
+                    health.correlation = Some(interleaved.stereo_correlation());
+                }
+            }
+        }
+        health
+    }
+
+    fn unavailable(error: String) -> Self {
+        Self {
+            source: None,
+            status: AudioStatus::Unavailable,
+            error: Some(error),
+            sample_rate: None,
+            channels: None,
+            duration_secs: None,
+            peak_dbfs: None,
+            rms_dbfs: None,
+            lufs: None,
+            true_peak_dbtp: None,
+            correlation: None,
+            valid: false,
+            failed_checks: Vec::new(),
+        }
+    }
+}
+
+impl ChainHealth {
+    fn for_project(project: &Project) -> Self {
+        let chain = project.layer2.effect_chain();
+        let built = chain.as_ref().ok();
+        let effects = project
+            .layer2
+            .chain
+            .iter()
+            .map(|effect| {
+                let dsp_effect = built.and_then(|chain| chain.get(&effect.id));
+                EffectHealth {
+                    id: effect.id.clone(),
+                    effect_type: effect.effect_type.clone(),
+                    enabled: effect.enabled,
+                    latency_samples: dsp_effect.map(|e| e.latency_samples()),
+                    tail_samples: dsp_effect.map(|e| e.tail_samples()),
+                }
+            })
+            .collect();
+
+        Self {
+            effects,
+            latency_samples: built.map(|chain| chain.latency_samples()),
+            tail_samples: built.map(|chain| chain.tail_samples()),
+            error: chain.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// An optional value, or `n/a`
+fn or_na<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "n/a".to_string(), |v| v.to_string())
+}
+
+/// An optional level to one decimal place, or `n/a`
+fn db_or_na(value: Option<f32>) -> String {
+    or_na(value.map(|db| format!("{:.1}", db)))
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let audio = &self.audio;
+        writeln!(f, "--- Audio ---")?;
+        writeln!(f, "source: {}", or_na(audio.source))?;
+        let status = match audio.status {
+            AudioStatus::Ok => "ok",
+            AudioStatus::Silent => "silent",
+            AudioStatus::Empty => "empty",
+            AudioStatus::Unavailable => "unavailable",
+        };
+        writeln!(f, "status: {}", status)?;
+        if let Some(error) = &audio.error {
+            writeln!(f, "error: {}", error)?;
+        }
+        writeln!(f, "sample_rate: {}", or_na(audio.sample_rate))?;
+        writeln!(f, "channels: {}", or_na(audio.channels))?;
+        writeln!(
+            f,
+            "duration_secs: {}",
+            or_na(audio.duration_secs.map(|d| format!("{:.3}", d)))
+        )?;
+        writeln!(f, "peak_dbfs: {}", db_or_na(audio.peak_dbfs))?;
+        writeln!(f, "rms_dbfs: {}", db_or_na(audio.rms_dbfs))?;
+        writeln!(f, "lufs: {}", db_or_na(audio.lufs))?;
+        writeln!(f, "true_peak_dbtp: {}", db_or_na(audio.true_peak_dbtp))?;
+        writeln!(
+            f,
+            "correlation: {}",
+            or_na(audio.correlation.map(|c| format!("{:.2}", c)))
+        )?;
+        if audio.valid {
+            writeln!(f, "validation: ok")?;
+        } else if audio.failed_checks.is_empty() {
+            writeln!(f, "validation: n/a")?;
+        } else {
+            writeln!(f, "validation: failed ({})", audio.failed_checks.join(", "))?;
+        }
+
+        let chain = &self.chain;
+        writeln!(f, "\n--- Effect Chain ---")?;
+        if let Some(error) = &chain.error {
+            writeln!(f, "error: {}", error)?;
+        }
+        writeln!(f, "effects: {}", chain.effects.len())?;
+        for (i, effect) in chain.effects.iter().enumerate() {
+            writeln!(
+                f,
+                "{}. {} ({}) {} latency={} tail={}",
+                i + 1,
+                effect.id,
+                effect.effect_type,
+                if effect.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                },
+                or_na(effect.latency_samples),
+                or_na(effect.tail_samples)
+            )?;
+        }
+        writeln!(f, "latency_samples: {}", or_na(chain.latency_samples))?;
+        writeln!(f, "tail_samples: {}", or_na(chain.tail_samples))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::ChannelLayout;
+
+    #[test]
+    fn test_measures_stereo_tone() {
+        let mut buffer = AudioBuffer::new(48000, ChannelLayout::Stereo);
+        for ch in 0..2 {
+            for (i, sample) in buffer.samples[ch].iter_mut().enumerate() {
+                *sample = 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48000.0).sin();
+            }
+        }
+        let health = AudioHealth::measure("layer0", &buffer);
+
+        assert_eq!(health.status, AudioStatus::Ok);
+        assert!((health.peak_dbfs.unwrap() + 6.02).abs() < 0.1);
+        assert!((health.rms_dbfs.unwrap() + 9.03).abs() < 0.1);
+        assert!(health.lufs.is_some());
+        assert!(health.true_peak_dbtp.unwrap() >= health.peak_dbfs.unwrap() - 0.01);
+        assert!((health.correlation.unwrap() - 1.0).abs() < 1e-6);
+        assert!(health.valid);
+    }
+
+    #[test]
+    fn test_silent_and_empty_audio_report_clearly() {
+        let silent = AudioHealth::measure("layer1", &AudioBuffer::new(48000, ChannelLayout::Mono));
+        assert_eq!(silent.status, AudioStatus::Silent);
+        assert_eq!(silent.peak_dbfs, None);
+        assert_eq!(silent.correlation, None);
+        assert!(silent.failed_checks.contains(&"audio is silent"));
+
+        let empty = AudioHealth::measure("layer1", &AudioBuffer::new(0, ChannelLayout::Stereo));
+        assert_eq!(empty.status, AudioStatus::Empty);
+        assert!(!empty.valid);
+
+        let json = serde_json::to_value(&empty).unwrap();
+        assert_eq!(json["status"], "empty");
+        assert!(json["lufs"].is_null());
+
+        let report = HealthReport {
+            audio: silent,
+            chain: ChainHealth {
+                effects: Vec::new(),
+                latency_samples: Some(0),
+                tail_samples: Some(0),
+                error: None,
+            },
+        };
+        let text = report.to_string();
+        assert!(text.contains("status: silent\n"), "{}", text);
+        assert!(text.contains("peak_dbfs: n/a\n"));
+        assert!(text.contains("validation: failed (audio is silent)\n"));
+    }
+}
@@ -2,11 +2,17 @@
 //!
 //! Command-line interface for Nueva audio processing system.
 
+pub mod batch;
 pub mod commands;
+pub mod diff;
+pub mod health;
+pub mod repl;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::engine::buffer::INTERNAL_SAMPLE_RATE;
+
 /// Nueva Audio Processor - AI-powered audio processing system
 #[derive(Parser, Debug)]
 #[command(name = "nueva")]
@@ -16,6 +22,10 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// List effect parameters (all effects, or just EFFECT) and exit
+    #[arg(long, value_name = "EFFECT")]
+    pub list_params: Option<Option<String>>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -31,6 +41,11 @@ pub enum Commands {
         /// Input audio file (optional)
         #[arg(short, long)]
         input: Option<PathBuf>,
+
+        /// Processing sample rate in Hz (44100, 48000, 88200, 96000, 176400
+        /// or 192000); imported audio is resampled to it
+        #[arg(long, default_value_t = INTERNAL_SAMPLE_RATE)]
+        sample_rate: u32,
     },
 
     /// Load an existing project
@@ -53,6 +68,11 @@ pub enum Commands {
         /// Path to the project
         #[arg(short, long)]
         path: PathBuf,
+
+        /// Undo the most recent action whose description contains this text,
+        /// keeping everything done after it
+        #[arg(long = "match")]
+        pattern: Option<String>,
     },
 
     /// Redo the last undone action
@@ -71,12 +91,95 @@ pub enum Commands {
         path: PathBuf,
     },
 
+    /// List undo history branches, or switch to one
+    #[command(name = "branches")]
+    Branches {
+        /// Path to the project
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// Action ID to switch to (e.g. a branch tip from the list)
+        #[arg(long)]
+        switch: Option<String>,
+    },
+
+    /// Show what changed between two project states
+    #[command(name = "diff")]
+    Diff {
+        /// Path to the project
+        path: PathBuf,
+
+        /// Old state: `current`, an undo action ID (or unique prefix) for the
+        /// state after it, the ID followed by `^` for the state before it, or
+        /// a project JSON file
+        #[arg(long)]
+        from: String,
+
+        /// New state, in the same forms as --from
+        #[arg(long, default_value = "current")]
+        to: String,
+    },
+
+    /// Compare the rendered mix against a reference track (loudness, tonal
+    /// tilt and correlation)
+    #[command(name = "compare")]
+    Compare {
+        /// Path to the project
+        path: PathBuf,
+
+        /// Reference audio file; only the length both cover is compared
+        #[arg(long)]
+        reference: PathBuf,
+    },
+
     /// Bake all layers (destructive flatten)
     #[command(name = "bake")]
     Bake {
         /// Path to the project
         #[arg(short, long)]
         path: PathBuf,
+
+        /// Only bake effects up to and including this effect ID into
+        /// Layer 1, keeping the rest of the chain live (undoable)
+        #[arg(long)]
+        through: Option<String>,
+    },
+
+    /// Add, remove or list timeline markers, or automate effects at them
+    #[command(name = "marker")]
+    Marker {
+        /// Path to the project
+        #[arg(short, long)]
+        path: PathBuf,
+
+        #[command(subcommand)]
+        action: MarkerAction,
+    },
+
+    /// Trim the project audio to a range, keeping markers in place
+    #[command(name = "trim")]
+    Trim {
+        /// Path to the project
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// Start of the range to keep, in seconds
+        #[arg(long, default_value = "0")]
+        start: f64,
+
+        /// End of the range to keep, in seconds (default: end of audio)
+        #[arg(long)]
+        end: Option<f64>,
+    },
+
+    /// Render the project (Layer 2 over the current audio) to a file
+    #[command(name = "render")]
+    Render {
+        /// Path to the project
+        path: PathBuf,
+
+        #[command(flatten)]
+        args: RenderArgs,
     },
 
     /// Print current project state
@@ -84,6 +187,10 @@ pub enum Commands {
     PrintState {
         /// Path to the project
         path: PathBuf,
+
+        /// Print one JSON object for scripts instead of text
+        #[arg(long)]
+        json: bool,
     },
 
     /// Process audio with AI agent
@@ -108,24 +215,136 @@ pub enum Commands {
 
     /// Process a standalone audio file (no project)
     #[command(name = "process")]
-    Process {
-        /// Input audio file
-        input: PathBuf,
+    Process(ProcessArgs),
 
-        /// Output audio file
-        #[arg(short, long)]
-        output: Option<PathBuf>,
+    /// Process every audio file in a directory into an `out/` subfolder
+    #[command(name = "batch")]
+    Batch(BatchArgs),
 
-        /// Natural language prompt
-        #[arg(short = 'm', long)]
-        prompt: String,
+    /// Start an interactive session, optionally with a project loaded
+    #[command(name = "repl")]
+    Repl {
+        /// Path to the project
+        path: Option<PathBuf>,
+    },
+}
+
+/// Actions for `marker`
+#[derive(Subcommand, Debug, Clone)]
+pub enum MarkerAction {
+    /// Add a named marker
+    Add {
+        /// Marker name (unique, ignoring case)
+        name: String,
+
+        /// Position in seconds
+        #[arg(long)]
+        at: f64,
+    },
+
+    /// Remove a marker and the changes scheduled at it
+    Remove {
+        /// Marker name
+        name: String,
+    },
+
+    /// List markers and the changes scheduled at them
+    List,
+
+    /// Set an effect parameter from a marker on when rendering
+    Automate {
+        /// Marker name
+        marker: String,
 
-        /// Processing mode: transform, cover, repaint, extract
-        #[arg(long, default_value = "transform")]
-        mode: String,
+        /// Effect ID in the chain
+        effect: String,
 
-        /// Transformation intensity (0.0 - 1.0)
-        #[arg(short, long, default_value = "0.7")]
-        intensity: f32,
+        /// Parameter name
+        param: String,
+
+        /// New value (JSON, e.g. 0.5 or true; anything else is a string)
+        #[arg(allow_hyphen_values = true)]
+        value: String,
     },
 }
+
+/// Arguments for `process`
+#[derive(Args, Debug)]
+pub struct ProcessArgs {
+    /// Input audio file
+    pub input: PathBuf,
+
+    /// Output audio file
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Natural language prompt
+    #[arg(short = 'm', long)]
+    pub prompt: String,
+
+    /// Processing mode: transform, cover, repaint, extract
+    #[arg(long, default_value = "transform")]
+    pub mode: String,
+
+    /// Transformation intensity (0.0 - 1.0)
+    #[arg(short, long, default_value = "0.7")]
+    pub intensity: f32,
+}
+
+/// Output options for `render`
+#[derive(Args, Debug)]
+pub struct RenderArgs {
+    /// Output audio file
+    #[arg(short, long, required_unless_present = "stems")]
+    pub output: Option<PathBuf>,
+
+    /// Also write each layer (dry, AI, DSP) to its own file in this
+    /// directory
+    #[arg(long)]
+    pub stems: Option<PathBuf>,
+
+    /// Container format: wav, flac
+    #[arg(long, default_value = "wav")]
+    pub format: String,
+
+    /// Bit depth: 16, 24 or 32 (32 is float, WAV only)
+    #[arg(long, default_value = "24")]
+    pub bit_depth: u16,
+
+    /// Normalize the mix to this integrated loudness (LUFS) before
+    /// export; stems keep their levels
+    #[arg(long, allow_negative_numbers = true)]
+    pub normalize_lufs: Option<f32>,
+}
+
+/// Arguments for `batch`
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// Directory of input files
+    pub dir: PathBuf,
+
+    /// Natural language prompt (processed with ACE-Step)
+    #[arg(
+        short = 'm',
+        long,
+        conflicts_with = "chain",
+        required_unless_present = "chain"
+    )]
+    pub prompt: Option<String>,
+
+    /// DSP chain preset (JSON list of effects) to apply instead of a prompt
+    #[arg(long)]
+    pub chain: Option<PathBuf>,
+
+    /// Processing mode for prompts: transform, cover, repaint, extract
+    #[arg(long, default_value = "transform")]
+    pub mode: String,
+
+    /// Transformation intensity for prompts (0.0 - 1.0)
+    #[arg(short, long, default_value = "0.7")]
+    pub intensity: f32,
+
+    /// Also process files in subdirectories
+    #[arg(short, long)]
+    pub recursive: bool,
+}
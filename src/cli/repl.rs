@@ -0,0 +1,845 @@
+//! Interactive REPL
+//!
+//! Loads a project once and keeps it, its undo history and the agent
+//! conversation in memory between commands, so a prompt like "make that
+//! louder" can refer back to an earlier one. Changes are written on
+//! `save`, when switching projects, and on exit, including EOF and Ctrl-C.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use log::warn;
+
+use super::commands;
+use super::{BatchArgs, MarkerAction, ProcessArgs, RenderArgs};
+use crate::agent::ConversationContext;
+use crate::engine::buffer::INTERNAL_SAMPLE_RATE;
+use crate::state::error::Result;
+use crate::state::{load_conversation, save_conversation, Project, UndoManager};
+
+/// Prompt printed before each line of input
+pub const REPL_PROMPT: &str = "nueva> ";
+
+/// One line of REPL input
+#[derive(Parser, Debug)]
+#[command(
+    name = "nueva",
+    about = "Commands available in an interactive session",
+    no_binary_name = true,
+    disable_version_flag = true
+)]
+struct ReplLine {
+    #[command(subcommand)]
+    command: ReplCommand,
+}
+
+/// The CLI's subcommands, minus the project path the session already holds
+#[derive(Subcommand, Debug)]
+enum ReplCommand {
+    /// Create a new project and switch to it
+    #[command(name = "create-project")]
+    CreateProject {
+        /// Path for the new project
+        path: std::path::PathBuf,
+
+        /// Input audio file (optional)
+        #[arg(short, long)]
+        input: Option<std::path::PathBuf>,
+
+        /// Processing sample rate in Hz
+        #[arg(long, default_value_t = INTERNAL_SAMPLE_RATE)]
+        sample_rate: u32,
+    },
+
+    /// Switch to an existing project
+    #[command(name = "project")]
+    LoadProject {
+        /// Path to the project
+        path: std::path::PathBuf,
+    },
+
+    /// Save the project, history and conversation
+    #[command(name = "save-state", alias = "save")]
+    SaveState,
+
+    /// Undo the last action
+    #[command(name = "undo")]
+    Undo {
+        /// Undo the most recent action whose description contains this text
+        #[arg(long = "match")]
+        pattern: Option<String>,
+    },
+
+    /// Redo the last undone action
+    #[command(name = "redo")]
+    Redo,
+
+    /// Show action history
+    #[command(name = "history")]
+    History,
+
+    /// List undo history branches, or switch to one
+    #[command(name = "branches")]
+    Branches {
+        /// Action ID to switch to
+        #[arg(long)]
+        switch: Option<String>,
+    },
+
+    /// Show what changed between two project states
+    #[command(name = "diff")]
+    Diff {
+        /// Old state: `current`, an action ID (state after it) or ID^
+        /// (state before it), or a project JSON file
+        #[arg(long)]
+        from: String,
+
+        /// New state, in the same forms as --from
+        #[arg(long, default_value = "current")]
+        to: String,
+    },
+
+    /// Compare the rendered mix against a reference track
+    #[command(name = "compare")]
+    Compare {
+        /// Reference audio file
+        #[arg(long)]
+        reference: std::path::PathBuf,
+    },
+
+    /// Bake all layers, or the chain through an effect into Layer 1
+    #[command(name = "bake")]
+    Bake {
+        /// Only bake effects up to and including this effect ID
+        #[arg(long)]
+        through: Option<String>,
+    },
+
+    /// Add, remove or list timeline markers, or automate effects at them
+    #[command(name = "marker", subcommand)]
+    Marker(MarkerAction),
+
+    /// Trim the audio to a range, keeping markers in place
+    #[command(name = "trim")]
+    Trim {
+        /// Start of the range to keep, in seconds
+        #[arg(long, default_value = "0")]
+        start: f64,
+
+        /// End of the range to keep, in seconds (default: end of audio)
+        #[arg(long)]
+        end: Option<f64>,
+    },
+
+    /// Render the project to a file without changing it
+    #[command(name = "render")]
+    Render(RenderArgs),
+
+    /// Print current project state
+    #[command(name = "print-state")]
+    PrintState {
+        /// Print one JSON object for scripts instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Send a prompt to the AI agent
+    #[command(name = "agent")]
+    Agent {
+        /// Natural language prompt (quotes optional)
+        #[arg(required = true, num_args = 1..)]
+        prompt: Vec<String>,
+
+        /// Force tool type: auto, dsp, neural
+        #[arg(short, long, default_value = "auto")]
+        tool: String,
+
+        /// Show what would be done without executing
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Process a standalone audio file (no project)
+    #[command(name = "process")]
+    Process(ProcessArgs),
+
+    /// Process every audio file in a directory
+    #[command(name = "batch")]
+    Batch(BatchArgs),
+
+    /// Save and leave the REPL
+    #[command(name = "exit", alias = "quit")]
+    Exit {
+        /// Leave without saving
+        #[arg(long)]
+        discard: bool,
+    },
+}
+
+/// Whether the REPL should keep reading input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplControl {
+    Continue,
+    Exit,
+}
+
+/// A loaded project with its undo history and agent conversation
+pub struct Session {
+    project: Project,
+    undo_manager: UndoManager,
+    context: ConversationContext,
+    /// Unsaved changes since the last save
+    dirty: bool,
+}
+
+impl Session {
+    /// Load a project and its history from disk
+    pub fn open(path: &Path) -> Result<Self> {
+        let project = Project::load(path)?;
+        Self::from_project(project)
+    }
+
+    /// Create a new project and start a session on it
+    pub fn create(path: &Path, input: Option<&Path>, sample_rate: u32) -> Result<Self> {
+        let mut project = Project::create_with_sample_rate(path, input, sample_rate)?;
+        project.save()?;
+        Self::from_project(project)
+    }
+
+    fn from_project(project: Project) -> Result<Self> {
+        let undo_manager = UndoManager::load(&project.history_dir())?;
+        let loaded = load_conversation(&project.history_dir())?;
+        if let Some(warning) = &loaded.warning {
+            warn!("{}", warning);
+        }
+        Ok(Self {
+            project,
+            undo_manager,
+            context: loaded.context,
+            dirty: false,
+        })
+    }
+
+    /// The loaded project
+    pub fn project(&self) -> &Project {
+        &self.project
+    }
+
+    /// The conversation carried between prompts
+    pub fn context(&self) -> &ConversationContext {
+        &self.context
+    }
+
+    /// Whether there are unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Write the project, undo history and conversation to disk
+    pub fn save(&mut self) -> Result<()> {
+        let history_dir = self.project.history_dir();
+        self.project.save()?;
+        self.undo_manager.save(&history_dir)?;
+        save_conversation(&history_dir, &mut self.context)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// End the session, saving first unless `discard` is set
+    pub fn close(mut self, discard: bool) -> Result<()> {
+        if self.dirty && !discard {
+            self.save()?;
+            println!("Saved: {}", self.project.project_path.display());
+        }
+        self.project.release_lock()
+    }
+}
+
+/// Interactive command loop state
+#[derive(Default)]
+pub struct Repl {
+    session: Option<Session>,
+}
+
+impl Repl {
+    /// A REPL with no project loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A REPL with the project at `path` loaded
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            session: Some(Session::open(path)?),
+        })
+    }
+
+    /// The current session, if a project is loaded
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Run one line of input
+    ///
+    /// Never fails: parse errors print help and command errors are
+    /// reported, leaving the session as it was.
+    pub fn execute(&mut self, line: &str) -> ReplControl {
+        let Some(words) = shlex::split(line) else {
+            println!("ERROR: Unbalanced quotes");
+            return ReplControl::Continue;
+        };
+        if words.is_empty() {
+            return ReplControl::Continue;
+        }
+
+        let command = match ReplLine::try_parse_from(words) {
+            Ok(parsed) => parsed.command,
+            Err(e) => {
+                let _ = e.print();
+                if e.kind() == ErrorKind::InvalidSubcommand {
+                    println!();
+                    println!("{}", ReplLine::command().render_help());
+                }
+                return ReplControl::Continue;
+            }
+        };
+
+        match self.dispatch(command) {
+            Ok(control) => control,
+            Err(e) => {
+                println!("ERROR: {}", e);
+                if let Some(suggestion) = e.recovery_suggestion() {
+                    println!("{}", suggestion);
+                }
+                ReplControl::Continue
+            }
+        }
+    }
+
+    /// Close the session, saving any changes
+    pub fn shutdown(&mut self) {
+        if let Some(session) = self.session.take() {
+            if let Err(e) = session.close(false) {
+                println!("ERROR: Failed to save project: {}", e);
+            }
+        }
+    }
+
+    fn dispatch(&mut self, command: ReplCommand) -> Result<ReplControl> {
+        match command {
+            ReplCommand::CreateProject {
+                path,
+                input,
+                sample_rate,
+            } => {
+                let session = Session::create(&path, input.as_deref(), sample_rate)?;
+                self.shutdown();
+                self.session = Some(session);
+                println!("Project created: {}", path.display());
+            }
+            ReplCommand::LoadProject { path } => {
+                self.shutdown();
+                commands::load_project(&path)?;
+                self.session = Some(Session::open(&path)?);
+            }
+            ReplCommand::Process(args) => commands::process_audio(
+                &args.input,
+                args.output.as_deref(),
+                &args.prompt,
+                &args.mode,
+                args.intensity,
+            )?,
+            ReplCommand::Batch(args) => commands::batch(&args)?,
+            ReplCommand::Exit { discard } => {
+                if let Some(session) = self.session.take() {
+                    session.close(discard)?;
+                }
+                return Ok(ReplControl::Exit);
+            }
+            command => {
+                let Some(session) = self.session.as_mut() else {
+                    println!("No project loaded. Use 'project <path>' or 'create-project <path>'.");
+                    return Ok(ReplControl::Continue);
+                };
+                run_session_command(session, command)?;
+            }
+        }
+        Ok(ReplControl::Continue)
+    }
+}
+
+/// Commands that act on the loaded project
+fn run_session_command(session: &mut Session, command: ReplCommand) -> Result<()> {
+    if let ReplCommand::SaveState = command {
+        session.save()?;
+        println!("Project saved: {}", session.project.project_path.display());
+        return Ok(());
+    }
+
+    let Session {
+        project,
+        undo_manager,
+        context,
+        dirty,
+    } = session;
+
+    match command {
+        ReplCommand::Undo { pattern } => {
+            if commands::undo_project(project, undo_manager, pattern.as_deref())? {
+                *dirty = true;
+            }
+        }
+        ReplCommand::Redo => {
+            commands::redo_project(project, undo_manager)?;
+            *dirty = true;
+        }
+        ReplCommand::History => commands::print_history(undo_manager),
+        ReplCommand::Branches { switch } => {
+            commands::branches_project(project, undo_manager, switch.as_deref())?;
+            *dirty |= switch.is_some();
+        }
+        ReplCommand::Diff { from, to } => {
+            commands::diff_project(project, undo_manager, &from, &to)?
+        }
+        ReplCommand::Compare { reference } => commands::compare_project(project, &reference)?,
+        ReplCommand::Bake { through } => {
+            commands::bake_project(project, undo_manager, through.as_deref())?;
+            *dirty = true;
+        }
+        ReplCommand::Marker(action) => {
+            *dirty |= commands::marker_project(project, undo_manager, &action)?;
+        }
+        ReplCommand::Trim { start, end } => {
+            commands::trim_project(project, undo_manager, start, end)?;
+            *dirty = true;
+        }
+        ReplCommand::Render(args) => commands::render_project(project, &args)?,
+        ReplCommand::PrintState { json } => commands::print_project_state(project, json)?,
+        ReplCommand::Agent {
+            prompt,
+            tool,
+            dry_run,
+        } => {
+            // Mark before running: the conversation changes even on error
+            *dirty = true;
+            commands::run_agent(
+                project,
+                undo_manager,
+                context,
+                &prompt.join(" "),
+                &tool,
+                dry_run,
+            )?;
+        }
+        _ => unreachable!("project-free commands are handled by Repl::dispatch"),
+    }
+    Ok(())
+}
+
+/// Run the REPL on stdin, optionally starting with a project loaded
+pub fn run(path: Option<&Path>) -> Result<()> {
+    let repl = match path {
+        Some(path) => {
+            commands::load_project(path)?;
+            Repl::open(path)?
+        }
+        None => Repl::new(),
+    };
+    let repl = Arc::new(Mutex::new(repl));
+
+    // Ctrl-C saves and exits instead of dropping unsaved work
+    let handler_repl = Arc::clone(&repl);
+    if let Err(e) = ctrlc::set_handler(move || {
+        println!();
+        handler_repl
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .shutdown();
+        std::process::exit(130);
+    }) {
+        warn!("Could not install Ctrl-C handler: {}", e);
+    }
+
+    println!("Nueva Audio Processor v{}", env!("CARGO_PKG_VERSION"));
+    println!("Type 'help' for commands, 'exit' to save and quit.");
+
+    run_loop(&repl, io::stdin().lock())
+}
+
+/// Read and execute lines until `exit` or end of input
+fn run_loop(repl: &Mutex<Repl>, input: impl BufRead) -> Result<()> {
+    let mut lines = input.lines();
+    loop {
+        print!("{}", REPL_PROMPT);
+        io::stdout().flush()?;
+
+        let Some(line) = lines.next() else {
+            // EOF (Ctrl-D): save and leave
+            println!();
+            break;
+        };
+        let control = repl
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .execute(&line?);
+        if control == ReplControl::Exit {
+            return Ok(());
+        }
+    }
+
+    repl.lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .shutdown();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::intensity_params;
+    use crate::engine::io::{export_audio, generate_test_tone, ExportFormat};
+    use crate::state::conversation::CONVERSATION_FILE;
+    use crate::state::project::{Effect, LOCK_FILE};
+    use chrono::Utc;
+    use std::fs;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    fn effect(id: &str, effect_type: &str) -> Effect {
+        Effect {
+            id: id.to_string(),
+            effect_type: effect_type.to_string(),
+            enabled: true,
+            params: Default::default(),
+            added_at: Utc::now(),
+            added_by: "user".to_string(),
+        }
+    }
+
+    /// A saved project whose chain holds a compressor and a reverb
+    fn setup() -> (TempDir, Repl) {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("project");
+        let mut project = Project::create(&path, None).unwrap();
+        project.layer2.chain = vec![effect("comp-1", "compressor"), effect("rev-1", "reverb")];
+        project.save().unwrap();
+        (temp, Repl::open(&path).unwrap())
+    }
+
+    fn project_path(temp: &TempDir) -> std::path::PathBuf {
+        temp.path().join("project")
+    }
+
+    #[test]
+    fn test_invalid_commands_do_not_fail() {
+        let (_temp, mut repl) = setup();
+        for line in [
+            "",
+            "frobnicate",
+            "undo --bogus",
+            "agent",
+            "\"unterminated",
+            "help",
+        ] {
+            assert_eq!(repl.execute(line), ReplControl::Continue, "{:?}", line);
+        }
+        assert!(repl.session().is_some());
+    }
+
+    #[test]
+    fn test_reference_resolves_within_session() {
+        let (_temp, mut repl) = setup();
+        repl.execute("agent make the reverb bigger");
+        repl.execute("agent make that louder");
+
+        let actions = &repl.session().unwrap().context().recent_actions;
+        assert_eq!(actions.len(), 2);
+        for action in actions {
+            assert_eq!(action.affected_effect.as_ref().unwrap().id, "rev-1");
+        }
+    }
+
+    #[test]
+    fn test_dry_run_leaves_conversation_untouched() {
+        let (_temp, mut repl) = setup();
+        repl.execute("agent make the reverb bigger --dry-run");
+
+        let context = repl.session().unwrap().context();
+        assert!(context.recent_actions.is_empty());
+        assert!(context.messages.is_empty());
+    }
+
+    #[test]
+    fn test_undo_match_without_a_match_changes_nothing() {
+        let (_temp, mut repl) = setup();
+        {
+            let session = repl.session.as_mut().unwrap();
+            session
+                .project
+                .move_effect(&mut session.undo_manager, "rev-1", 0)
+                .unwrap();
+        }
+
+        assert_eq!(
+            repl.execute("undo --match no-such-action"),
+            ReplControl::Continue
+        );
+        let session = repl.session().unwrap();
+        assert_eq!(session.project().layer2.chain[0].id, "rev-1");
+        assert!(!session.is_dirty());
+    }
+
+    #[test]
+    fn test_undo_stays_in_memory_until_saved() {
+        let (temp, mut repl) = setup();
+        {
+            let session = repl.session.as_mut().unwrap();
+            session
+                .project
+                .move_effect(&mut session.undo_manager, "rev-1", 0)
+                .unwrap();
+        }
+
+        repl.execute("undo");
+        let session = repl.session().unwrap();
+        assert_eq!(session.project().layer2.chain[0].id, "comp-1");
+        assert!(session.is_dirty());
+
+        // Disk still has the original order
+        let on_disk = Project::load(&project_path(&temp)).unwrap();
+        assert_eq!(on_disk.layer2.chain[0].id, "comp-1");
+
+        repl.execute("redo");
+        repl.execute("save");
+        assert!(!repl.session().unwrap().is_dirty());
+        let on_disk = Project::load(&project_path(&temp)).unwrap();
+        assert_eq!(on_disk.layer2.chain[0].id, "rev-1");
+    }
+
+    #[test]
+    fn test_render_writes_markers() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("input.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 48000),
+            &input,
+            ExportFormat::new(48000, 32),
+        )
+        .unwrap();
+        let path = temp.path().join("project");
+        let mut project = Project::create(&path, Some(&input)).unwrap();
+        project.add_marker("Drop", 12000).unwrap();
+        project.save().unwrap();
+        project.release_lock().unwrap();
+
+        let output = temp.path().join("mix.wav");
+        let mut repl = Repl::open(&path).unwrap();
+        repl.execute(&format!("render --output {}", output.display()));
+
+        let rendered = crate::engine::io::import_audio_with_metadata(&output).unwrap();
+        assert_eq!(rendered.metadata.markers, project.markers);
+    }
+
+    #[test]
+    fn test_safe_mode_limiter_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("input.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 48000),
+            &input,
+            ExportFormat::new(48000, 32),
+        )
+        .unwrap();
+        let path = temp.path().join("project");
+        let mut project = Project::create(&path, Some(&input)).unwrap();
+        let mut boost = effect("gain-1", "gain");
+        boost.params.insert("gain_db".to_string(), 12.0.into());
+        project.layer2.chain = vec![boost];
+        project.save().unwrap();
+        project.release_lock().unwrap();
+
+        let limiters = |repl: &Repl| {
+            repl.session()
+                .unwrap()
+                .project()
+                .layer2
+                .effects_of_type("limiter")
+                .len()
+        };
+
+        // Off by default: the clipping chain is left alone
+        let mut repl = Repl::open(&path).unwrap();
+        repl.execute("agent add a bit of saturation --tool dsp");
+        assert_eq!(repl.session().unwrap().project().layer2.chain.len(), 2);
+        assert_eq!(limiters(&repl), 0);
+
+        repl.session
+            .as_mut()
+            .unwrap()
+            .context
+            .user_preferences
+            .safe_mode = true;
+        repl.execute("agent add a bit of saturation --tool dsp");
+        let chain = &repl.session().unwrap().project().layer2.chain;
+        assert_eq!(chain.len(), 4);
+        assert_eq!(chain[3].effect_type, "limiter");
+        assert_eq!(chain[3].added_by, "agent");
+        assert_eq!(chain[3].params["ceiling_db"], -1.0);
+
+        // Already limited, so nothing more is added
+        repl.execute("agent add a bit of saturation --tool dsp");
+        assert_eq!(repl.session().unwrap().project().layer2.chain.len(), 5);
+        assert_eq!(limiters(&repl), 1);
+
+        // A prompt that changes nothing isn't checked again
+        repl.execute("agent make it louder --tool dsp");
+        assert_eq!(repl.session().unwrap().project().layer2.chain.len(), 5);
+
+        repl.execute("undo");
+        repl.execute("undo");
+        assert_eq!(limiters(&repl), 0);
+    }
+
+    #[test]
+    fn test_dc_offset_gets_a_high_pass_once() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("input.wav");
+        let mut tone = generate_test_tone(440.0, 1.0, 48000);
+        for channel in tone.samples.iter_mut() {
+            channel.iter_mut().for_each(|s| *s = *s * 0.5 + 0.15);
+        }
+        export_audio(&tone, &input, ExportFormat::new(48000, 32)).unwrap();
+        let path = temp.path().join("project");
+        let mut project = Project::create(&path, Some(&input)).unwrap();
+        project.layer2.chain = vec![effect("comp-1", "compressor")];
+        project.save().unwrap();
+        project.release_lock().unwrap();
+
+        let mut repl = Repl::open(&path).unwrap();
+        repl.execute("agent add a bit of saturation --tool dsp");
+        let eqs = |repl: &Repl| {
+            repl.session()
+                .unwrap()
+                .project()
+                .layer2
+                .effects_of_type("parametric-eq")
+                .len()
+        };
+        assert_eq!(eqs(&repl), 1);
+        // The EQ goes before the compressor
+        assert_eq!(
+            repl.session().unwrap().project().layer2.chain[0].effect_type,
+            "parametric-eq"
+        );
+
+        repl.execute("agent add a bit of saturation --tool dsp");
+        assert_eq!(eqs(&repl), 1);
+
+        repl.execute("undo");
+        repl.execute("undo");
+        assert_eq!(eqs(&repl), 0);
+    }
+
+    #[test]
+    fn test_dsp_prompt_adds_effects_at_its_intensity() {
+        let (_temp, mut repl) = setup();
+
+        repl.execute("agent add a bit of saturation --tool dsp");
+        let chain = &repl.session().unwrap().project().layer2.chain;
+        let saturation = chain
+            .iter()
+            .find(|e| e.effect_type == "saturation")
+            .unwrap();
+        assert_eq!(saturation.added_by, "agent");
+        let expected = intensity_params("saturation", 0.3).unwrap();
+        for (name, value) in &expected {
+            let actual = saturation.params[name].as_f64().unwrap();
+            assert!((actual - value.as_f64().unwrap()).abs() < 1e-6, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_markers_automate_and_trim_are_undoable() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("input.wav");
+        export_audio(
+            &generate_test_tone(440.0, 1.0, 48000),
+            &input,
+            ExportFormat::new(48000, 32),
+        )
+        .unwrap();
+        let path = temp.path().join("project");
+        let mut project = Project::create(&path, Some(&input)).unwrap();
+        project.layer2.chain = vec![effect("gain-1", "gain")];
+        project.save().unwrap();
+        project.release_lock().unwrap();
+
+        let mut repl = Repl::open(&path).unwrap();
+        repl.execute("marker add drop --at 0.5");
+        repl.execute("marker automate drop gain-1 gain_db -6");
+        repl.execute("marker automate drop gain-1 missing 1");
+        repl.execute("marker add late --at 2");
+        let project = repl.session().unwrap().project();
+        assert_eq!(project.markers.len(), 1);
+        assert_eq!(project.markers[0].sample, 24000);
+        assert_eq!(project.marker_schedule.changes()[0].value, -6.0);
+
+        repl.execute("trim --start 0.25");
+        let project = repl.session().unwrap().project();
+        assert!((project.layer0.duration_seconds - 0.75).abs() < 1e-6);
+        assert_eq!(project.markers[0].sample, 12000);
+        assert_eq!(project.load_layer1().unwrap().len(), 36000);
+
+        repl.execute("undo");
+        let project = repl.session().unwrap().project();
+        assert!((project.layer0.duration_seconds - 1.0).abs() < 1e-6);
+        assert_eq!(project.markers[0].sample, 24000);
+        assert_eq!(project.load_layer1().unwrap().len(), 48000);
+
+        repl.execute("undo");
+        assert!(repl.session().unwrap().project().marker_schedule.is_empty());
+    }
+
+    #[test]
+    fn test_eof_saves_and_releases_lock() {
+        let (temp, repl) = setup();
+        let repl = Mutex::new(repl);
+        let input = Cursor::new("agent make the reverb bigger\n");
+        run_loop(&repl, input).unwrap();
+
+        let history = Project::load(&project_path(&temp)).unwrap().history_dir();
+        let loaded = load_conversation(&history).unwrap();
+        assert!(history.join(CONVERSATION_FILE).exists());
+        assert_eq!(loaded.context.recent_actions.len(), 1);
+        assert!(repl.lock().unwrap().session().is_none());
+    }
+
+    #[test]
+    fn test_exit_discard_skips_save() {
+        let (temp, mut repl) = setup();
+        repl.execute("agent make the reverb bigger --dry-run");
+        assert_eq!(repl.execute("exit --discard"), ReplControl::Exit);
+
+        let path = project_path(&temp);
+        assert!(!path.join(LOCK_FILE).exists());
+        let history = Project::load(&path).unwrap().history_dir();
+        assert!(!history.join(CONVERSATION_FILE).exists());
+    }
+
+    #[test]
+    fn test_commands_need_a_project() {
+        let mut repl = Repl::new();
+        assert_eq!(repl.execute("print-state"), ReplControl::Continue);
+        assert_eq!(repl.execute("undo"), ReplControl::Continue);
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("new");
+        repl.execute(&format!("create-project {}", path.display()));
+        assert!(repl.session().is_some());
+        assert!(path.join(crate::state::project::PROJECT_FILE).exists());
+        fs::metadata(path.join(LOCK_FILE)).unwrap();
+    }
+}
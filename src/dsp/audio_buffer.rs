@@ -32,7 +32,7 @@ impl AudioBuffer {
         num_channels: usize,
         sample_rate: f64,
     ) -> Result<Self> {
-        if samples.len() % num_channels != 0 {
+        if !samples.len().is_multiple_of(num_channels) {
             return Err(NuevaError::InvalidAudioFile {
                 details: format!(
                     "Sample count {} is not divisible by channel count {}",
@@ -48,6 +48,31 @@ impl AudioBuffer {
         })
     }
 
+    /// Interleave a planar engine buffer for DSP processing
+    pub fn from_engine(buffer: &crate::engine::AudioBuffer) -> Result<Self> {
+        Self::from_interleaved(
+            buffer.to_interleaved(),
+            buffer.channels().max(1),
+            buffer.sample_rate as f64,
+        )
+    }
+
+    /// De-interleave back into a planar engine buffer
+    pub fn to_engine(&self) -> crate::engine::AudioBuffer {
+        let num_samples = self.num_samples();
+        let samples = (0..self.num_channels)
+            .map(|ch| {
+                (0..num_samples)
+                    .map(|i| self.samples[i * self.num_channels + ch])
+                    .collect()
+            })
+            .collect();
+        crate::engine::AudioBuffer {
+            samples,
+            sample_rate: self.sample_rate.round() as u32,
+        }
+    }
+
     /// Number of channels
     pub fn num_channels(&self) -> usize {
         self.num_channels
@@ -94,6 +119,48 @@ impl AudioBuffer {
         }
     }
 
+    /// Extend the buffer with `num_samples` frames of silence
+    pub fn append_silence(&mut self, num_samples: usize) {
+        self.samples
+            .resize(self.samples.len() + num_samples * self.num_channels, 0.0);
+    }
+
+    /// Drop the first `num_samples` frames (all of them if there are fewer)
+    pub fn trim_start(&mut self, num_samples: usize) {
+        let end = (num_samples * self.num_channels).min(self.samples.len());
+        self.samples.drain(..end);
+    }
+
+    /// Duplicate a mono buffer into two identical channels
+    ///
+    /// Buffers with two or more channels are left unchanged.
+    pub fn upmix_to_stereo(&mut self) {
+        if self.num_channels != 1 {
+            return;
+        }
+        self.samples = self.samples.iter().flat_map(|&s| [s, s]).collect();
+        self.num_channels = 2;
+    }
+
+    /// Make this buffer a copy of `other`, reusing its allocation
+    pub fn copy_from(&mut self, other: &AudioBuffer) {
+        self.samples.clear();
+        self.samples.extend_from_slice(&other.samples);
+        self.num_channels = other.num_channels;
+        self.sample_rate = other.sample_rate;
+    }
+
+    /// Give this buffer `other`'s channel count, length and sample rate,
+    /// reusing its allocation
+    ///
+    /// Samples already in the buffer are kept where they fit and new ones
+    /// are silent; callers are expected to overwrite them all.
+    pub fn resize_to_match(&mut self, other: &AudioBuffer) {
+        self.samples.resize(other.samples.len(), 0.0);
+        self.num_channels = other.num_channels;
+        self.sample_rate = other.sample_rate;
+    }
+
     /// Create a copy of this buffer (for rollback support per spec §9.4)
     pub fn create_copy(&self) -> Self {
         self.clone()
@@ -172,6 +239,31 @@ impl AudioBuffer {
 
         sum / self.num_samples() as f64
     }
+
+    /// Correlation between the first two channels (-1 to 1)
+    ///
+    /// +1 means the channels are identical (mono-compatible), 0 unrelated,
+    /// -1 that they cancel when summed to mono. Mono buffers and silent
+    /// channels report 1.0.
+    pub fn stereo_correlation(&self) -> f64 {
+        if self.num_channels < 2 {
+            return 1.0;
+        }
+
+        let (mut lr, mut ll, mut rr) = (0.0f64, 0.0f64, 0.0f64);
+        for frame in self.samples.chunks_exact(self.num_channels) {
+            let (l, r) = (frame[0] as f64, frame[1] as f64);
+            lr += l * r;
+            ll += l * l;
+            rr += r * r;
+        }
+
+        if ll == 0.0 || rr == 0.0 {
+            1.0
+        } else {
+            (lr / (ll * rr).sqrt()).clamp(-1.0, 1.0)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -186,6 +278,25 @@ mod tests {
         assert_eq!(buf.sample_rate(), 44100.0);
     }
 
+    #[test]
+    fn test_engine_round_trip() {
+        let planar = crate::engine::AudioBuffer {
+            samples: vec![vec![0.1, 0.2, 0.3], vec![-0.1, -0.2, -0.3]],
+            sample_rate: 48000,
+        };
+        let buf = AudioBuffer::from_engine(&planar).unwrap();
+        assert_eq!(buf.samples(), &[0.1, -0.1, 0.2, -0.2, 0.3, -0.3]);
+        assert_eq!(buf.to_engine().samples, planar.samples);
+    }
+
+    #[test]
+    fn test_append_silence() {
+        let mut buf = AudioBuffer::from_interleaved(vec![0.1, 0.2], 2, 44100.0).unwrap();
+        buf.append_silence(2);
+        assert_eq!(buf.num_samples(), 3);
+        assert_eq!(buf.samples(), &[0.1, 0.2, 0.0, 0.0, 0.0, 0.0]);
+    }
+
     #[test]
     fn test_get_set() {
         let mut buf = AudioBuffer::new(2, 100, 44100.0);
@@ -216,4 +327,24 @@ mod tests {
         buf.set(50, 0, f32::NAN);
         assert!(!buf.is_valid());
     }
+
+    #[test]
+    fn test_upmix_to_stereo() {
+        let mut buf = AudioBuffer::from_interleaved(vec![0.1, 0.2, 0.3], 1, 44100.0).unwrap();
+        buf.upmix_to_stereo();
+        assert_eq!(buf.num_channels(), 2);
+        assert_eq!(buf.samples(), &[0.1, 0.1, 0.2, 0.2, 0.3, 0.3]);
+    }
+
+    #[test]
+    fn test_stereo_correlation() {
+        let same = AudioBuffer::from_interleaved(vec![0.5, 0.5, -0.25, -0.25], 2, 44100.0).unwrap();
+        assert!((same.stereo_correlation() - 1.0).abs() < 1e-9);
+
+        let inverted =
+            AudioBuffer::from_interleaved(vec![0.5, -0.5, -0.25, 0.25], 2, 44100.0).unwrap();
+        assert!((inverted.stereo_correlation() + 1.0).abs() < 1e-9);
+
+        assert_eq!(AudioBuffer::new(1, 10, 44100.0).stereo_correlation(), 1.0);
+    }
 }
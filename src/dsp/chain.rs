@@ -11,8 +11,11 @@
 //! 7. Reverb (almost always last among time-based)
 //! 8. Limiter (always last)
 
-use super::{AudioBuffer, Effect, ProcessResult};
+use super::{effect_from_json, effect_to_json, AudioBuffer, Effect, ProcessResult};
 use crate::error::{NuevaError, Result};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 /// Order priority constants (spec §4.3)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -31,11 +34,14 @@ impl EffectPosition {
     /// Get recommended position for an effect type
     pub fn for_effect_type(effect_type: &str) -> Self {
         match effect_type {
-            "gate" => EffectPosition::Gate,
-            "eq" | "parametric-eq" => EffectPosition::EqCorrective,
+            "gate" | "expander" => EffectPosition::Gate,
+            "eq" | "parametric-eq" | "parametric_eq" => EffectPosition::EqCorrective,
             "compressor" => EffectPosition::Compressor,
-            "saturation" => EffectPosition::Saturation,
-            "delay" => EffectPosition::Delay,
+            "graphic-eq" | "graphic_eq" => EffectPosition::EqCreative,
+            "saturation" | "ring-mod" | "ring_mod" | "tremolo" | "wah" | "pitch-shifter" => {
+                EffectPosition::Saturation
+            }
+            "delay" | "haas" => EffectPosition::Delay,
             "reverb" => EffectPosition::Reverb,
             "limiter" => EffectPosition::Limiter,
             _ => EffectPosition::Saturation, // Default to middle
@@ -43,11 +49,56 @@ impl EffectPosition {
     }
 }
 
+/// Default order priority for an effect type (lower runs earlier)
+pub fn get_default_order_priority(effect_type: &str) -> u32 {
+    EffectPosition::for_effect_type(effect_type) as u32
+}
+
+/// Chain-level monitoring overrides
+///
+/// These sit on top of each effect's own `enabled` flag without changing
+/// it, so clearing them restores the chain exactly as it was.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainBypass {
+    /// Bypass every effect (overrides solo)
+    pub global: bool,
+    /// Only run the effect with this ID
+    pub solo: Option<String>,
+}
+
+impl ChainBypass {
+    /// Whether the overrides let `effect_id` run
+    pub fn allows(&self, effect_id: &str) -> bool {
+        !self.global && self.solo.as_deref().is_none_or(|solo| solo == effect_id)
+    }
+
+    /// Whether any override is in effect
+    pub fn is_active(&self) -> bool {
+        self.global || self.solo.is_some()
+    }
+}
+
+/// Time since an arbitrary fixed point, used to profile effects
+type ProfileClock = fn() -> Duration;
+
+/// Monotonic wall-clock time since the first call
+fn monotonic_clock() -> Duration {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed()
+}
+
 /// Chain of effects for processing
 pub struct EffectChain {
     effects: Vec<Box<dyn Effect>>,
     sample_rate: f64,
     samples_per_block: usize,
+    bypass: ChainBypass,
+    /// Whether `process` times each effect
+    profiling: bool,
+    /// Accumulated processing time per effect ID since the last `prepare`
+    profile: HashMap<String, Duration>,
+    /// Where profiling reads the time
+    clock: ProfileClock,
 }
 
 impl EffectChain {
@@ -57,13 +108,20 @@ impl EffectChain {
             effects: Vec::new(),
             sample_rate: 44100.0,
             samples_per_block: 512,
+            bypass: ChainBypass::default(),
+            profiling: false,
+            profile: HashMap::new(),
+            clock: monotonic_clock,
         }
     }
 
     /// Prepare all effects for processing
+    ///
+    /// Also clears any accumulated profiling data.
     pub fn prepare(&mut self, sample_rate: f64, samples_per_block: usize) {
         self.sample_rate = sample_rate;
         self.samples_per_block = samples_per_block;
+        self.profile.clear();
         for effect in &mut self.effects {
             effect.prepare(sample_rate, samples_per_block);
         }
@@ -77,12 +135,30 @@ impl EffectChain {
     }
 
     /// Add an effect at the recommended position (spec §4.3)
-    pub fn add(&mut self, mut effect: Box<dyn Effect>) {
+    ///
+    /// Same as [`add_effect_ordered`](Self::add_effect_ordered).
+    pub fn add(&mut self, effect: Box<dyn Effect>) {
+        self.add_effect_ordered(effect);
+    }
+
+    /// Insert an effect where its default order priority puts it
+    ///
+    /// The effect goes before the first effect with a higher priority, so
+    /// a gate added after a reverb still lands ahead of it, and effects of
+    /// equal priority stay in the order they were added. The effect is
+    /// prepared at the chain's current sample rate.
+    pub fn add_effect_ordered(&mut self, mut effect: Box<dyn Effect>) {
         effect.prepare(self.sample_rate, self.samples_per_block);
         let position = self.get_recommended_position(effect.effect_type());
         self.effects.insert(position, effect);
     }
 
+    /// Append an effect to the end of the chain, whatever its type
+    pub fn add_effect(&mut self, effect: Box<dyn Effect>) {
+        let end = self.effects.len();
+        self.add_at(effect, end);
+    }
+
     /// Add an effect at a specific index
     pub fn add_at(&mut self, mut effect: Box<dyn Effect>, index: usize) {
         effect.prepare(self.sample_rate, self.samples_per_block);
@@ -100,6 +176,9 @@ impl EffectChain {
                 effect_id: effect_id.to_string(),
             })?;
 
+        if self.bypass.solo.as_deref() == Some(effect_id) {
+            self.bypass.solo = None;
+        }
         Ok(self.effects.remove(index))
     }
 
@@ -122,6 +201,9 @@ impl EffectChain {
     }
 
     /// Move an effect to a new position
+    ///
+    /// The effect instance itself is moved, so its DSP state (delay lines,
+    /// envelopes) carries over. `new_index` must be a valid index.
     pub fn move_effect(&mut self, effect_id: &str, new_index: usize) -> Result<()> {
         let current_index = self
             .effects
@@ -130,18 +212,126 @@ impl EffectChain {
             .ok_or_else(|| NuevaError::EffectNotFound {
                 effect_id: effect_id.to_string(),
             })?;
+        if new_index >= self.effects.len() {
+            return Err(NuevaError::InvalidParameter {
+                param: "new_index".to_string(),
+                value: new_index.to_string(),
+                expected: format!("an index below {}", self.effects.len()),
+            });
+        }
 
         let effect = self.effects.remove(current_index);
-        let new_index = new_index.min(self.effects.len());
         self.effects.insert(new_index, effect);
         Ok(())
     }
 
+    /// All effects of the given type, in chain order
+    pub fn effects_of_type(&self, effect_type: &str) -> Vec<&dyn Effect> {
+        self.effects
+            .iter()
+            .filter(|e| e.effect_type() == effect_type)
+            .map(|e| e.as_ref())
+            .collect()
+    }
+
+    /// Sort the chain into the recommended order (spec §4.3)
+    ///
+    /// The sort is stable: effects with the same priority keep their
+    /// relative order. Returns true if anything moved.
+    pub fn reorder_by_default_priority(&mut self) -> bool {
+        let before: Vec<String> = self.effects.iter().map(|e| e.id().to_string()).collect();
+        self.effects
+            .sort_by_key(|e| get_default_order_priority(e.effect_type()));
+        self.effects.iter().zip(&before).any(|(e, id)| e.id() != id)
+    }
+
+    /// Bypass the whole chain (pure passthrough) or restore it
+    pub fn set_global_bypass(&mut self, bypass: bool) {
+        self.bypass.global = bypass;
+    }
+
+    /// Whether the whole chain is bypassed
+    pub fn is_globally_bypassed(&self) -> bool {
+        self.bypass.global
+    }
+
+    /// Solo one effect so only it runs, or clear the solo with `None`
+    ///
+    /// A soloed effect that is itself disabled still doesn't run.
+    pub fn solo_effect(&mut self, effect_id: Option<&str>) -> Result<()> {
+        if let Some(id) = effect_id {
+            if self.get(id).is_none() {
+                return Err(NuevaError::EffectNotFound {
+                    effect_id: id.to_string(),
+                });
+            }
+        }
+        self.bypass.solo = effect_id.map(str::to_string);
+        Ok(())
+    }
+
+    /// ID of the soloed effect, if any
+    pub fn soloed_effect(&self) -> Option<&str> {
+        self.bypass.solo.as_deref()
+    }
+
+    /// Current bypass/solo overrides
+    pub fn bypass(&self) -> &ChainBypass {
+        &self.bypass
+    }
+
+    /// Whether an effect will run on the next `process` call
+    pub fn is_effect_active(&self, effect_id: &str) -> bool {
+        self.get(effect_id)
+            .is_some_and(|e| e.is_enabled() && self.bypass.allows(effect_id))
+    }
+
+    /// Turn per-effect timing on or off
+    ///
+    /// When off (the default) `process` does no timing at all.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+    }
+
+    /// Whether per-effect timing is on
+    pub fn is_profiling(&self) -> bool {
+        self.profiling
+    }
+
+    /// Total processing time per effect ID since the last `prepare`,
+    /// most expensive first
+    pub fn profile_report(&self) -> Vec<(String, Duration)> {
+        let mut report: Vec<(String, Duration)> = self
+            .profile
+            .iter()
+            .map(|(id, total)| (id.clone(), *total))
+            .collect();
+        report.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        report
+    }
+
+    /// Discard accumulated profiling data
+    pub fn reset_profile(&mut self) {
+        self.profile.clear();
+    }
+
     /// Process the entire chain
+    ///
+    /// Effects held back by bypass or solo report success without
+    /// touching the buffer.
     pub fn process(&mut self, buffer: &mut AudioBuffer) -> Vec<ProcessResult> {
         let mut results = Vec::with_capacity(self.effects.len());
         for effect in &mut self.effects {
-            results.push(effect.process_safe(buffer));
+            if !self.bypass.allows(effect.id()) {
+                results.push(ProcessResult::Success);
+            } else if self.profiling {
+                let start = (self.clock)();
+                results.push(effect.process_safe(buffer));
+                *self.profile.entry(effect.id().to_string()).or_default() +=
+                    (self.clock)().saturating_sub(start);
+            } else {
+                results.push(effect.process_safe(buffer));
+            }
         }
         results
     }
@@ -175,10 +365,56 @@ impl EffectChain {
         self.effects.len()
     }
 
-    /// Serialize chain state to JSON
+    /// Total tail of the active effects, in samples
+    ///
+    /// Tails run in series (a delay's last echo still feeds the reverb), so
+    /// they add up.
+    pub fn tail_samples(&self) -> usize {
+        self.effects
+            .iter()
+            .filter(|e| e.is_enabled() && self.bypass.allows(e.id()))
+            .map(|e| e.tail_samples())
+            .sum()
+    }
+
+    /// Total latency of the active effects, in samples
+    pub fn latency_samples(&self) -> usize {
+        self.effects
+            .iter()
+            .filter(|e| e.is_enabled() && self.bypass.allows(e.id()))
+            .map(|e| e.latency_samples())
+            .sum()
+    }
+
+    /// Build an independent copy with the same effects, parameters and
+    /// bypass state
+    ///
+    /// Effects are rebuilt from their serialized state, so the copy starts
+    /// with cleared delay lines and envelopes. Fails for effect types the
+    /// factory doesn't know.
+    pub fn try_clone(&self) -> Result<EffectChain> {
+        let mut copy = EffectChain::new();
+        copy.sample_rate = self.sample_rate;
+        copy.samples_per_block = self.samples_per_block;
+        copy.bypass = self.bypass.clone();
+
+        for effect in &self.effects {
+            let clone = effect_from_json(&effect_to_json(effect.as_ref())?)?;
+            copy.add_at(clone, copy.len());
+        }
+        Ok(copy)
+    }
+
+    /// Serialize the chain to JSON
+    ///
+    /// Each entry carries its `effect_type`, `id`, `enabled` flag and
+    /// parameters (see [`effect_to_json`]), in chain order.
     pub fn to_json(&self) -> Result<serde_json::Value> {
-        let effects: Result<Vec<serde_json::Value>> =
-            self.effects.iter().map(|e| e.to_json()).collect();
+        let effects: Result<Vec<serde_json::Value>> = self
+            .effects
+            .iter()
+            .map(|e| effect_to_json(e.as_ref()))
+            .collect();
 
         Ok(serde_json::json!({
             "effects": effects?,
@@ -186,6 +422,39 @@ impl EffectChain {
             "samples_per_block": self.samples_per_block,
         }))
     }
+
+    /// Rebuild a chain written by [`EffectChain::to_json`]
+    ///
+    /// Effects keep their order and IDs and are prepared at the stored
+    /// sample rate. An unknown `effect_type` or an out-of-range parameter
+    /// fails the whole chain, naming the offending entry.
+    pub fn from_json(json: &serde_json::Value) -> Result<EffectChain> {
+        let entries = json
+            .get("effects")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| NuevaError::SerializationError {
+                details: "effect chain has no effects array".to_string(),
+            })?;
+
+        let mut chain = EffectChain::new();
+        if let Some(sample_rate) = json.get("sample_rate").and_then(|v| v.as_f64()) {
+            chain.sample_rate = sample_rate;
+        }
+        if let Some(block) = json.get("samples_per_block").and_then(|v| v.as_u64()) {
+            chain.samples_per_block = block as usize;
+        }
+
+        for (index, entry) in entries.iter().enumerate() {
+            let effect = effect_from_json(entry).map_err(|e| {
+                let id = entry.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+                NuevaError::SerializationError {
+                    details: format!("effect {} ({}): {}", index, id, e),
+                }
+            })?;
+            chain.add_at(effect, chain.len());
+        }
+        Ok(chain)
+    }
 }
 
 impl Default for EffectChain {
@@ -197,6 +466,7 @@ impl Default for EffectChain {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
 
     #[test]
     fn test_effect_position_ordering() {
@@ -211,4 +481,477 @@ mod tests {
         assert!(chain.is_empty());
         assert_eq!(chain.len(), 0);
     }
+
+    use crate::dsp::{
+        Compressor, Delay, GainEffect, Gate, Limiter, ParametricEQ, PitchShifter, Reverb,
+        Saturation,
+    };
+
+    fn with_id(mut effect: Box<dyn Effect>, id: &str) -> Box<dyn Effect> {
+        effect.set_id(id.to_string());
+        effect
+    }
+
+    fn ids(chain: &EffectChain) -> Vec<&str> {
+        chain.iter().map(|e| e.id()).collect()
+    }
+
+    #[test]
+    fn test_move_effect() {
+        let mut chain = EffectChain::new();
+        chain.add_at(with_id(Box::new(GainEffect::new()), "a"), 0);
+        chain.add_at(with_id(Box::new(GainEffect::new()), "b"), 1);
+        chain.add_at(with_id(Box::new(GainEffect::new()), "c"), 2);
+
+        chain.move_effect("a", 2).unwrap();
+        assert_eq!(ids(&chain), ["b", "c", "a"]);
+        chain.move_effect("a", 0).unwrap();
+        assert_eq!(ids(&chain), ["a", "b", "c"]);
+        chain.move_effect("c", 1).unwrap();
+        assert_eq!(ids(&chain), ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_move_effect_invalid() {
+        let mut chain = EffectChain::new();
+        chain.add_at(with_id(Box::new(GainEffect::new()), "a"), 0);
+        chain.add_at(with_id(Box::new(GainEffect::new()), "b"), 1);
+
+        assert!(matches!(
+            chain.move_effect("a", 2),
+            Err(NuevaError::InvalidParameter { .. })
+        ));
+        assert!(matches!(
+            chain.move_effect("missing", 0),
+            Err(NuevaError::EffectNotFound { .. })
+        ));
+        assert_eq!(ids(&chain), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_move_preserves_dsp_state() {
+        let build = || {
+            let mut chain = EffectChain::new();
+            chain.prepare(44100.0, 512);
+            chain.add_at(with_id(Box::new(GainEffect::new()), "gain"), 0);
+            chain.add_at(with_id(Box::new(Delay::new()), "delay"), 1);
+            chain
+        };
+        let mut moved = build();
+        let mut reference = build();
+
+        // Load the delay line, then move the delay ahead of the (unity) gain
+        let mut impulse = AudioBuffer::new(2, 512, 44100.0);
+        impulse.set(0, 0, 1.0);
+        impulse.set(0, 1, 1.0);
+        moved.process(&mut impulse.clone());
+        reference.process(&mut impulse);
+        moved.move_effect("delay", 0).unwrap();
+
+        // The echo still arrives after the move
+        let mut out_moved = AudioBuffer::new(2, 44100, 44100.0);
+        let mut out_reference = out_moved.clone();
+        moved.process(&mut out_moved);
+        reference.process(&mut out_reference);
+
+        assert!(out_reference.samples().iter().any(|s| s.abs() > 0.01));
+        assert_eq!(out_moved.samples(), out_reference.samples());
+    }
+
+    #[test]
+    fn test_effects_of_type() {
+        let mut chain = EffectChain::new();
+        chain.add_at(with_id(Box::new(Reverb::new()), "rev-1"), 0);
+        chain.add_at(with_id(Box::new(GainEffect::new()), "gain-1"), 1);
+        chain.add_at(with_id(Box::new(Reverb::new()), "rev-2"), 2);
+
+        let reverbs: Vec<&str> = chain
+            .effects_of_type("reverb")
+            .iter()
+            .map(|e| e.id())
+            .collect();
+        assert_eq!(reverbs, ["rev-1", "rev-2"]);
+        assert!(chain.effects_of_type("limiter").is_empty());
+    }
+
+    #[test]
+    fn test_reorder_by_default_priority() {
+        let mut chain = EffectChain::new();
+        let effects: Vec<Box<dyn Effect>> = vec![
+            with_id(Box::new(Limiter::new()), "limiter"),
+            with_id(Box::new(Reverb::new()), "reverb"),
+            with_id(Box::new(Saturation::new()), "saturation"),
+            with_id(Box::new(Compressor::new()), "compressor"),
+            with_id(Box::new(Delay::new()), "delay"),
+            with_id(Box::new(ParametricEQ::new()), "eq"),
+            with_id(Box::new(Gate::new()), "gate"),
+        ];
+        for (i, effect) in effects.into_iter().enumerate() {
+            chain.add_at(effect, i);
+        }
+
+        assert!(chain.reorder_by_default_priority());
+        assert_eq!(
+            ids(&chain),
+            [
+                "gate",
+                "eq",
+                "compressor",
+                "saturation",
+                "delay",
+                "reverb",
+                "limiter"
+            ]
+        );
+
+        // Already in order: nothing moves
+        assert!(!chain.reorder_by_default_priority());
+    }
+
+    #[test]
+    fn test_add_effect_ordered_gives_spec_order() {
+        let mut chain = EffectChain::new();
+        let effects: Vec<Box<dyn Effect>> = vec![
+            with_id(Box::new(Reverb::new()), "reverb"),
+            with_id(Box::new(Limiter::new()), "limiter"),
+            with_id(Box::new(Delay::new()), "delay-1"),
+            with_id(Box::new(Compressor::new()), "compressor"),
+            with_id(Box::new(Gate::new()), "gate"),
+            with_id(Box::new(Saturation::new()), "saturation"),
+            with_id(Box::new(Delay::new()), "delay-2"),
+            with_id(Box::new(ParametricEQ::new()), "eq"),
+        ];
+        for effect in effects {
+            chain.add_effect_ordered(effect);
+        }
+
+        // Equal priorities keep insertion order
+        assert_eq!(
+            ids(&chain),
+            [
+                "gate",
+                "eq",
+                "compressor",
+                "saturation",
+                "delay-1",
+                "delay-2",
+                "reverb",
+                "limiter"
+            ]
+        );
+        assert!(!chain.reorder_by_default_priority());
+
+        // Explicit append ignores the order
+        chain.add_effect(with_id(Box::new(Gate::new()), "late-gate"));
+        assert_eq!(ids(&chain).last(), Some(&"late-gate"));
+    }
+
+    #[test]
+    fn test_add_effect_ordered_prepares_at_chain_rate() {
+        let mut chain = EffectChain::new();
+        chain.prepare(96000.0, 512);
+        chain.add_effect_ordered(Box::new(Delay::with_params(crate::dsp::DelayParams {
+            delay_time_ms: 10.0,
+            feedback: 0.0,
+            wet_level: 1.0,
+            dry_level: 0.0,
+            filter_freq: 20000.0,
+            ..Default::default()
+        })));
+
+        let mut impulse = AudioBuffer::new(1, 2048, 96000.0);
+        impulse.set(0, 0, 1.0);
+        chain.process(&mut impulse);
+        let echo = impulse
+            .samples()
+            .iter()
+            .position(|s| s.abs() > 0.1)
+            .unwrap();
+        assert!((echo as i64 - 960).abs() <= 2, "echo at {}", echo);
+    }
+
+    fn gain_chain() -> EffectChain {
+        let mut chain = EffectChain::new();
+        for (i, id) in ["a", "b", "c"].into_iter().enumerate() {
+            let mut gain = GainEffect::with_gain(-6.0).unwrap();
+            gain.set_id(id.to_string());
+            chain.add_at(Box::new(gain), i);
+        }
+        chain.get_mut("b").unwrap().set_enabled(false);
+        chain
+    }
+
+    fn output_level(chain: &mut EffectChain) -> f32 {
+        let mut buffer = AudioBuffer::new(1, 4, 44100.0);
+        buffer.samples_mut().fill(1.0);
+        chain.process(&mut buffer);
+        buffer.get(0, 0).unwrap()
+    }
+
+    fn enabled_flags(chain: &EffectChain) -> Vec<bool> {
+        chain.iter().map(|e| e.is_enabled()).collect()
+    }
+
+    #[test]
+    fn test_solo_toggling() {
+        let mut chain = gain_chain();
+        let half = GainEffect::with_gain(-6.0).unwrap().gain_linear();
+        assert!((output_level(&mut chain) - half * half).abs() < 1e-6);
+
+        chain.solo_effect(Some("c")).unwrap();
+        assert!((output_level(&mut chain) - half).abs() < 1e-6);
+        assert!(chain.is_effect_active("c"));
+        assert!(!chain.is_effect_active("a"));
+        assert_eq!(enabled_flags(&chain), [true, false, true]);
+
+        // Soloing a disabled effect doesn't force it on
+        chain.solo_effect(Some("b")).unwrap();
+        assert_eq!(output_level(&mut chain), 1.0);
+        assert_eq!(enabled_flags(&chain), [true, false, true]);
+
+        chain.solo_effect(None).unwrap();
+        assert!((output_level(&mut chain) - half * half).abs() < 1e-6);
+        assert_eq!(enabled_flags(&chain), [true, false, true]);
+        assert!(chain.is_effect_active("a"));
+        assert!(!chain.is_effect_active("b"));
+    }
+
+    #[test]
+    fn test_global_bypass_overrides_solo() {
+        let mut chain = gain_chain();
+        chain.solo_effect(Some("a")).unwrap();
+        chain.set_global_bypass(true);
+
+        assert_eq!(output_level(&mut chain), 1.0);
+        assert!(!chain.is_effect_active("a"));
+
+        chain.set_global_bypass(false);
+        let half = GainEffect::with_gain(-6.0).unwrap().gain_linear();
+        assert!((output_level(&mut chain) - half).abs() < 1e-6);
+        assert_eq!(enabled_flags(&chain), [true, false, true]);
+    }
+
+    #[test]
+    fn test_solo_unknown_effect_errors() {
+        let mut chain = gain_chain();
+        assert!(matches!(
+            chain.solo_effect(Some("missing")),
+            Err(NuevaError::EffectNotFound { .. })
+        ));
+        assert_eq!(chain.soloed_effect(), None);
+
+        // Removing the soloed effect clears the solo
+        chain.solo_effect(Some("c")).unwrap();
+        chain.remove("c").unwrap();
+        assert_eq!(chain.soloed_effect(), None);
+    }
+
+    thread_local! {
+        /// Time reported by `fake_clock`; only `SlowEffect` advances it
+        static FAKE_NOW: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    }
+
+    fn fake_clock() -> Duration {
+        FAKE_NOW.with(|now| now.get())
+    }
+
+    /// Passes audio through, advancing `fake_clock` by 20 ms
+    struct SlowEffect {
+        id: String,
+    }
+
+    impl Effect for SlowEffect {
+        fn process(&mut self, _buffer: &mut AudioBuffer) {
+            FAKE_NOW.with(|now| now.set(now.get() + Duration::from_millis(20)));
+        }
+        fn prepare(&mut self, _sample_rate: f64, _samples_per_block: usize) {}
+        fn reset(&mut self) {}
+        fn to_json(&self) -> Result<serde_json::Value> {
+            Ok(serde_json::json!({ "id": self.id }))
+        }
+        fn from_json(&mut self, _json: &serde_json::Value) -> Result<()> {
+            Ok(())
+        }
+        fn effect_type(&self) -> &'static str {
+            "slow"
+        }
+        fn display_name(&self) -> &'static str {
+            "Slow"
+        }
+        fn metadata(&self) -> crate::dsp::EffectMetadata {
+            crate::dsp::EffectMetadata {
+                effect_type: "slow".to_string(),
+                display_name: "Slow".to_string(),
+                category: "utility".to_string(),
+                order_priority: 0,
+            }
+        }
+        fn is_enabled(&self) -> bool {
+            true
+        }
+        fn set_enabled(&mut self, _enabled: bool) {}
+        fn id(&self) -> &str {
+            &self.id
+        }
+        fn set_id(&mut self, id: String) {
+            self.id = id;
+        }
+        fn param_specs(&self) -> Vec<crate::neural::ParamSpec> {
+            Vec::new()
+        }
+    }
+
+    fn profiled_chain() -> EffectChain {
+        let mut chain = EffectChain::new();
+        chain.add_at(with_id(Box::new(GainEffect::new()), "gain-1"), 0);
+        chain.add_at(
+            Box::new(SlowEffect {
+                id: "slow-1".to_string(),
+            }),
+            1,
+        );
+        chain.add_at(with_id(Box::new(Reverb::new()), "reverb-1"), 2);
+        chain.clock = fake_clock;
+        chain
+    }
+
+    #[test]
+    fn test_profile_report_attributes_time() {
+        let mut chain = profiled_chain();
+        chain.prepare(44100.0, 512);
+        assert!(chain.profile_report().is_empty());
+
+        chain.set_profiling(true);
+        for _ in 0..3 {
+            chain.process(&mut AudioBuffer::new(2, 512, 44100.0));
+        }
+
+        let report = chain.profile_report();
+        let ids: Vec<&str> = report.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], "slow-1");
+        assert_eq!(report[0].1, Duration::from_millis(60));
+        // The other effects take no time on the fake clock
+        assert!(report[1..].iter().all(|(_, total)| total.is_zero()));
+        assert!(ids.contains(&"gain-1") && ids.contains(&"reverb-1"));
+
+        // prepare starts a fresh profile
+        chain.prepare(44100.0, 512);
+        assert!(chain.profile_report().is_empty());
+    }
+
+    #[test]
+    fn test_profiling_does_not_change_output() {
+        let input = {
+            let mut buffer = AudioBuffer::new(2, 2048, 44100.0);
+            for i in 0..2048 {
+                let x = (i as f32 * 0.05).sin() * 0.5;
+                buffer.set(i, 0, x);
+                buffer.set(i, 1, -x);
+            }
+            buffer
+        };
+
+        let mut plain = profiled_chain();
+        let mut profiled = profiled_chain();
+        plain.prepare(44100.0, 2048);
+        profiled.prepare(44100.0, 2048);
+        profiled.set_profiling(true);
+
+        let mut a = input.clone();
+        let mut b = input;
+        plain.process(&mut a);
+        profiled.process(&mut b);
+
+        assert_eq!(a.samples(), b.samples());
+        assert!(plain.profile_report().is_empty());
+    }
+
+    #[test]
+    fn test_try_clone_matches_original() {
+        let mut chain = EffectChain::new();
+        chain.prepare(44100.0, 512);
+        chain.add(with_id(Box::new(Compressor::new()), "comp"));
+        chain.add(with_id(Box::new(Delay::new()), "delay"));
+        chain.add(with_id(
+            Box::new(GainEffect::with_gain(-3.0).unwrap()),
+            "gain",
+        ));
+        chain.solo_effect(Some("delay")).unwrap();
+
+        let mut copy = chain.try_clone().unwrap();
+        assert_eq!(ids(&copy), ids(&chain));
+        assert_eq!(copy.soloed_effect(), Some("delay"));
+        assert_eq!(copy.tail_samples(), chain.tail_samples());
+
+        let mut a = AudioBuffer::new(1, 4096, 44100.0);
+        a.set(0, 0, 1.0);
+        let mut b = a.clone();
+        chain.process(&mut a);
+        copy.process(&mut b);
+        assert_eq!(a.samples(), b.samples());
+    }
+
+    #[test]
+    fn test_json_round_trip_reproduces_output() {
+        let mut chain = EffectChain::new();
+        chain.prepare(48000.0, 256);
+        chain.add(with_id(Box::new(Compressor::new()), "comp"));
+        chain.add(with_id(Box::new(Delay::new()), "delay"));
+        chain.add_at(
+            with_id(Box::new(GainEffect::with_gain(-3.0).unwrap()), "trim"),
+            0,
+        );
+        chain.get_mut("delay").unwrap().set_enabled(false);
+
+        let json = chain.to_json().unwrap();
+        assert_eq!(json["effects"][0]["effect_type"], "gain");
+        assert_eq!(json["effects"][0]["params"]["gain_db"], -3.0);
+
+        let mut restored = EffectChain::from_json(&json).unwrap();
+        assert_eq!(ids(&restored), vec!["trim", "comp", "delay"]);
+        assert!(!restored.get("delay").unwrap().is_enabled());
+        assert_eq!(restored.to_json().unwrap(), json);
+
+        let mut a = AudioBuffer::new(2, 4096, 48000.0);
+        for i in 0..4096 {
+            let s = (i as f32 * 0.05).sin() * 0.8;
+            a.set(i, 0, s);
+            a.set(i, 1, -s);
+        }
+        let mut b = a.clone();
+        chain.reset();
+        chain.process(&mut a);
+        restored.process(&mut b);
+        assert_eq!(a.samples(), b.samples());
+    }
+
+    #[test]
+    fn test_from_json_rejects_unknown_effect() {
+        let json = serde_json::json!({
+            "effects": [
+                {"effect_type": "gain", "id": "g", "params": {"gain_db": 1.0}},
+                {"effect_type": "flanger", "id": "fl", "params": {}},
+            ],
+            "sample_rate": 48000.0,
+        });
+        let err = EffectChain::from_json(&json).err().unwrap().to_string();
+        assert!(err.contains("effect 1 (fl)"), "{}", err);
+        assert!(err.contains("flanger"), "{}", err);
+
+        assert!(EffectChain::from_json(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_latency_counts_active_effects() {
+        let mut chain = EffectChain::new();
+        chain.add(with_id(Box::new(PitchShifter::new()), "pitch-1"));
+        chain.add(with_id(Box::new(PitchShifter::new()), "pitch-2"));
+        chain.add(with_id(Box::new(Compressor::new()), "comp"));
+        chain.prepare(48000.0, 512);
+        assert_eq!(chain.latency_samples(), 4096);
+
+        chain.get_mut("pitch-2").unwrap().set_enabled(false);
+        assert_eq!(chain.latency_samples(), 2048);
+    }
 }
@@ -3,11 +3,32 @@
 //! A dynamics processor that reduces the dynamic range of audio signals.
 //! Features envelope follower, gain computer with soft knee, attack/release
 //! smoothing, and optional auto makeup gain.
+//!
+//! Auto release adds a slow release stage that follows the gain reduction
+//! over several release times. Reduction held by sustained material
+//! recovers at the slow rate; a short transient barely charges the slow
+//! stage, so its reduction recovers at the fast rate and the material
+//! around it isn't pumped.
+//!
+//! The parallel mix blends the compressed signal with the untouched input
+//! for New York style compression in one instance. Both paths see the same
+//! samples with no delay between them, so the blend can't comb filter.
 
-use super::{AudioBuffer, Effect, EffectMetadata};
+use super::{AudioBuffer, Effect, EffectMetadata, EffectMeter};
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 
+/// Auto release: fast stage time as a fraction of `release_ms`
+pub const AUTO_RELEASE_FAST_RATIO: f32 = 0.25;
+
+/// Auto release: slow stage time as a multiple of `release_ms`
+pub const AUTO_RELEASE_SLOW_RATIO: f32 = 4.0;
+
+fn default_parallel_mix() -> f32 {
+    1.0
+}
+
 /// Compressor parameters with validation ranges from spec section 4.2.3
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompressorParams {
@@ -25,6 +46,14 @@ pub struct CompressorParams {
     pub makeup_gain_db: f32,
     /// Enable automatic makeup gain calculation
     pub auto_makeup: bool,
+    /// Program-dependent release: fast after transients, slow after
+    /// sustained compression
+    #[serde(default)]
+    pub auto_release: bool,
+    /// Share of the compressed signal blended with the dry input (0.0 =
+    /// dry passthrough, 1.0 = fully compressed)
+    #[serde(default = "default_parallel_mix")]
+    pub parallel_mix: f32,
 }
 
 impl Default for CompressorParams {
@@ -37,6 +66,8 @@ impl Default for CompressorParams {
             knee_db: 0.0,
             makeup_gain_db: 0.0,
             auto_makeup: false,
+            auto_release: false,
+            parallel_mix: 1.0,
         }
     }
 }
@@ -86,6 +117,13 @@ impl CompressorParams {
                 expected: "0 to 24 dB".to_string(),
             });
         }
+        if !(0.0..=1.0).contains(&self.parallel_mix) {
+            return Err(NuevaError::InvalidParameter {
+                param: "parallel_mix".to_string(),
+                value: self.parallel_mix.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
         Ok(())
     }
 
@@ -97,6 +135,7 @@ impl CompressorParams {
         self.release_ms = self.release_ms.clamp(10.0, 1000.0);
         self.knee_db = self.knee_db.clamp(0.0, 12.0);
         self.makeup_gain_db = self.makeup_gain_db.clamp(0.0, 24.0);
+        self.parallel_mix = self.parallel_mix.clamp(0.0, 1.0);
     }
 }
 
@@ -123,10 +162,18 @@ pub struct Compressor {
     attack_coeff: f32,
     /// Release coefficient for envelope smoothing
     release_coeff: f32,
+    /// Auto release: fast stage release coefficient
+    auto_fast_coeff: f32,
+    /// Auto release: slow stage coefficient
+    auto_slow_coeff: f32,
+    /// Auto release: slow stage gain reduction (linear)
+    slow_gain_reduction: f32,
     /// Current envelope level per channel (linear)
     envelope: Vec<f32>,
     /// Current gain reduction per channel (linear)
     gain_reduction: Vec<f32>,
+    /// Levels of the last processed block
+    meter: Option<EffectMeter>,
 }
 
 impl Compressor {
@@ -140,8 +187,12 @@ impl Compressor {
             samples_per_block: 512,
             attack_coeff: 0.0,
             release_coeff: 0.0,
+            auto_fast_coeff: 0.0,
+            auto_slow_coeff: 0.0,
+            slow_gain_reduction: 1.0,
             envelope: vec![0.0; 2],
             gain_reduction: vec![1.0; 2],
+            meter: None,
         }
     }
 
@@ -202,6 +253,16 @@ impl Compressor {
         self.params.auto_makeup = auto_makeup;
     }
 
+    /// Enable or disable program-dependent release
+    pub fn set_auto_release(&mut self, auto_release: bool) {
+        self.params.auto_release = auto_release;
+    }
+
+    /// Set how much compressed signal is blended with the dry input
+    pub fn set_parallel_mix(&mut self, parallel_mix: f32) {
+        self.params.parallel_mix = parallel_mix.clamp(0.0, 1.0);
+    }
+
     /// Get the current gain reduction in dB for metering
     pub fn gain_reduction_db(&self) -> f32 {
         // Return the average gain reduction across channels
@@ -238,6 +299,9 @@ impl Compressor {
         } else {
             0.0
         };
+
+        self.auto_fast_coeff = (-1.0 / (release_samples * AUTO_RELEASE_FAST_RATIO)).exp();
+        self.auto_slow_coeff = (-1.0 / (release_samples * AUTO_RELEASE_SLOW_RATIO)).exp();
     }
 
     /// Calculate auto makeup gain based on threshold and ratio
@@ -324,6 +388,7 @@ impl Effect for Compressor {
     fn process(&mut self, buffer: &mut AudioBuffer) {
         let num_channels = buffer.num_channels();
         let num_samples = buffer.num_samples();
+        let input_db = EffectMeter::peak_db(buffer);
 
         // Ensure we have envelope state for each channel
         if self.envelope.len() < num_channels {
@@ -338,6 +403,8 @@ impl Effect for Compressor {
             self.params.makeup_gain_db
         };
         let makeup_linear = Self::db_to_linear(makeup_db);
+        let wet = self.params.parallel_mix;
+        let dry = 1.0 - wet;
 
         // Process each sample
         for frame in 0..num_samples {
@@ -360,27 +427,45 @@ impl Effect for Compressor {
             // We use the first channel's envelope for linked stereo operation
             let current_gr = self.gain_reduction[0];
 
-            let smoothed_gr = if target_gr_linear < current_gr {
+            let mut smoothed_gr = if target_gr_linear < current_gr {
                 // Attacking (gain going down, GR increasing)
                 self.attack_coeff * current_gr + (1.0 - self.attack_coeff) * target_gr_linear
+            } else if self.params.auto_release {
+                // Releasing on the fast stage
+                self.auto_fast_coeff * current_gr + (1.0 - self.auto_fast_coeff) * target_gr_linear
             } else {
                 // Releasing (gain going up, GR decreasing)
                 self.release_coeff * current_gr + (1.0 - self.release_coeff) * target_gr_linear
             };
 
+            if self.params.auto_release {
+                // The slow stage charges and discharges at the same slow
+                // rate; whichever stage holds more reduction wins
+                self.slow_gain_reduction = self.auto_slow_coeff * self.slow_gain_reduction
+                    + (1.0 - self.auto_slow_coeff) * target_gr_linear;
+                smoothed_gr = smoothed_gr.min(self.slow_gain_reduction);
+            }
+
             // Store for metering
             for ch in 0..num_channels.min(self.gain_reduction.len()) {
                 self.gain_reduction[ch] = smoothed_gr;
             }
 
-            // Apply gain reduction and makeup to all channels
-            let total_gain = smoothed_gr * makeup_linear;
+            // Apply gain reduction and makeup to all channels, blended
+            // with the dry input; at a mix of 1.0 this is exactly the
+            // compressed gain
+            let total_gain = dry + wet * smoothed_gr * makeup_linear;
             for ch in 0..num_channels {
                 if let Some(sample) = buffer.get(frame, ch) {
                     buffer.set(frame, ch, sample * total_gain);
                 }
             }
         }
+
+        self.meter = Some(
+            EffectMeter::new(input_db, EffectMeter::peak_db(buffer))
+                .with_gain_reduction(self.gain_reduction_db()),
+        );
     }
 
     fn prepare(&mut self, sample_rate: f64, samples_per_block: usize) {
@@ -397,6 +482,12 @@ impl Effect for Compressor {
         for gr in &mut self.gain_reduction {
             *gr = 1.0;
         }
+        self.slow_gain_reduction = 1.0;
+        self.meter = None;
+    }
+
+    fn meter(&self) -> Option<EffectMeter> {
+        self.meter
     }
 
     fn to_json(&self) -> Result<serde_json::Value> {
@@ -456,6 +547,20 @@ impl Effect for Compressor {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("threshold_db", -60.0, 0.0, -18.0).with_unit("dB"),
+            ParamSpec::float("ratio", 1.0, 20.0, 4.0),
+            ParamSpec::float("attack_ms", 0.1, 100.0, 10.0).with_unit("ms"),
+            ParamSpec::float("release_ms", 10.0, 1000.0, 100.0).with_unit("ms"),
+            ParamSpec::float("knee_db", 0.0, 12.0, 0.0).with_unit("dB"),
+            ParamSpec::float("makeup_gain_db", 0.0, 24.0, 0.0).with_unit("dB"),
+            ParamSpec::boolean("auto_makeup", false),
+            ParamSpec::boolean("auto_release", false),
+            ParamSpec::float("parallel_mix", 0.0, 1.0, 1.0),
+        ]
+    }
 }
 
 /// Serializable state for the compressor
@@ -531,6 +636,8 @@ mod tests {
             knee_db: 20.0,
             makeup_gain_db: 50.0,
             auto_makeup: false,
+            auto_release: false,
+            parallel_mix: 1.5,
         };
 
         params.clamp();
@@ -541,6 +648,7 @@ mod tests {
         assert_eq!(params.release_ms, 1000.0);
         assert_eq!(params.knee_db, 12.0);
         assert_eq!(params.makeup_gain_db, 24.0);
+        assert_eq!(params.parallel_mix, 1.0);
     }
 
     #[test]
@@ -595,7 +703,7 @@ mod tests {
         // At knee start (-23 dB): should be transitioning
         let gr = comp.compute_gain_reduction_db(-23.0);
         assert!(
-            gr <= 0.0 && gr >= -1.0,
+            (-1.0..=0.0).contains(&gr),
             "Expected small GR at knee start, got {}",
             gr
         );
@@ -732,6 +840,8 @@ mod tests {
             knee_db: 3.0,
             makeup_gain_db: 4.0,
             auto_makeup: false,
+            auto_release: true,
+            parallel_mix: 0.4,
         });
         comp.set_id("test-compressor-1".to_string());
         comp.set_enabled(false);
@@ -754,6 +864,22 @@ mod tests {
         assert_eq!(comp2.params().release_ms, 200.0);
         assert_eq!(comp2.params().knee_db, 3.0);
         assert_eq!(comp2.params().makeup_gain_db, 4.0);
+        assert!(comp2.params().auto_release);
+        assert_eq!(comp2.params().parallel_mix, 0.4);
+
+        // Older projects have no auto_release or parallel_mix field
+        let mut legacy = json.clone();
+        legacy["params"]
+            .as_object_mut()
+            .unwrap()
+            .remove("auto_release");
+        legacy["params"]
+            .as_object_mut()
+            .unwrap()
+            .remove("parallel_mix");
+        comp2.from_json(&legacy).unwrap();
+        assert!(!comp2.params().auto_release);
+        assert_eq!(comp2.params().parallel_mix, 1.0);
     }
 
     #[test]
@@ -888,6 +1014,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_meter_reports_gain_reduction() {
+        let mut comp = Compressor::with_params(CompressorParams {
+            threshold_db: -20.0,
+            ratio: 4.0,
+            attack_ms: 0.1,
+            ..Default::default()
+        });
+        comp.prepare(44100.0, 512);
+        assert!(comp.meter().is_none());
+
+        let mut buffer = AudioBuffer::new(2, 1000, 44100.0);
+        for i in 0..1000 {
+            buffer.set(i, 0, 0.5);
+            buffer.set(i, 1, 0.5);
+        }
+        comp.process(&mut buffer);
+
+        let meter = comp.meter().expect("meter after processing");
+        assert!((meter.input_db - 20.0 * 0.5f32.log10()).abs() < 0.01);
+        assert!(meter.output_db < meter.input_db);
+        // About 14 dB over the threshold at 4:1
+        let gr = meter.gain_reduction_db.unwrap();
+        assert_eq!(gr, comp.gain_reduction_db());
+        assert!(gr < -6.0, "gain reduction {} dB", gr);
+
+        // Below the threshold nothing is reduced once the release settles
+        let mut quiet = AudioBuffer::new(2, 44100, 44100.0);
+        for i in 0..44100 {
+            quiet.set(i, 0, 0.01);
+            quiet.set(i, 1, 0.01);
+        }
+        comp.process(&mut quiet);
+        let gr = comp.meter().unwrap().gain_reduction_db.unwrap();
+        assert!(gr > -0.1, "gain reduction {} dB", gr);
+
+        comp.reset();
+        assert!(comp.meter().is_none());
+    }
+
     #[test]
     fn test_stereo_linked_detection() {
         let mut comp = Compressor::with_params(CompressorParams {
@@ -920,6 +1086,14 @@ mod tests {
             "Right channel should be compressed due to linked detection: {}",
             right_peak
         );
+        // The same gain on both keeps the 19 dB between them
+        let original_difference = 20.0 * (0.9f64 / 0.1).log10();
+        assert!(
+            (left_peak - right_peak - original_difference).abs() < 0.1,
+            "Channels compressed unequally: left {} dB, right {} dB",
+            left_peak,
+            right_peak
+        );
     }
 
     #[test]
@@ -968,5 +1142,232 @@ mod tests {
             release_96k > release_44k,
             "Higher sample rate should give larger release coefficient"
         );
+
+        // The auto release stages track the sample rate too
+        let slow_96k = comp.auto_slow_coeff;
+        comp.prepare(44100.0, 512);
+        assert!(slow_96k > comp.auto_slow_coeff);
+        assert!(comp.auto_fast_coeff < comp.release_coeff);
+        assert!(comp.auto_slow_coeff > comp.release_coeff);
+    }
+
+    fn release_test_comp(auto_release: bool) -> Compressor {
+        let mut comp = Compressor::with_params(CompressorParams {
+            threshold_db: -20.0,
+            ratio: 10.0,
+            attack_ms: 0.1,
+            release_ms: 100.0,
+            auto_release,
+            ..Default::default()
+        });
+        comp.prepare(48000.0, 48);
+        comp
+    }
+
+    /// Gain reduction in dB after each 1 ms block of `signal`
+    fn gr_trace(comp: &mut Compressor, signal: &[f32]) -> Vec<f32> {
+        signal
+            .chunks(48)
+            .map(|block| {
+                let mut buf = AudioBuffer::from_interleaved(block.to_vec(), 1, 48000.0).unwrap();
+                comp.process(&mut buf);
+                comp.gain_reduction_db()
+            })
+            .collect()
+    }
+
+    /// Milliseconds after `from` until the reduction is within 1 dB of zero
+    fn recovery_ms(trace: &[f32], from: usize) -> usize {
+        trace[from..].iter().position(|&gr| gr > -1.0).unwrap()
+    }
+
+    /// `loud_ms` of a loud tone followed by a quiet tail
+    fn burst(loud_ms: usize) -> Vec<f32> {
+        (0..48 * (loud_ms + 2000))
+            .map(|i| {
+                let level = if i < 48 * loud_ms { 0.9 } else { 0.0 };
+                level * (i as f32 * 0.13).sin().signum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_release_is_single_pole() {
+        let mut comp = release_test_comp(false);
+        let mut loud = AudioBuffer::from_interleaved(vec![0.9; 4800], 1, 48000.0).unwrap();
+        comp.process(&mut loud);
+        let start = comp.gain_reduction[0];
+
+        let mut silence = AudioBuffer::new(1, 480, 48000.0);
+        comp.process(&mut silence);
+        let coeff = (-1.0 / (0.1 * 48000.0_f32)).exp();
+        let expected = 1.0 - (1.0 - start) * coeff.powi(480);
+        assert!((comp.gain_reduction[0] - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_auto_release_depends_on_program() {
+        let transient = burst(5);
+        let sustained = burst(1000);
+
+        let mut comp = release_test_comp(true);
+        let after_transient = recovery_ms(&gr_trace(&mut comp, &transient), 5);
+        comp.reset();
+        let after_sustained = recovery_ms(&gr_trace(&mut comp, &sustained), 1000);
+        assert!(
+            after_sustained > 3 * after_transient,
+            "sustained {} ms vs transient {} ms",
+            after_sustained,
+            after_transient
+        );
+
+        let mut fixed = release_test_comp(false);
+        let fixed_transient = recovery_ms(&gr_trace(&mut fixed, &transient), 5);
+        assert!(after_transient < fixed_transient);
+    }
+
+    #[test]
+    fn test_auto_release_pumps_less() {
+        // A steady bass line, alone and with a short click every 250 ms
+        let bass = |i: usize| 0.3 * (std::f32::consts::TAU * 55.0 * i as f32 / 48000.0).sin();
+        let plain: Vec<f32> = (0..48000 * 2).map(bass).collect();
+        let clicked: Vec<f32> = (0..48000 * 2)
+            .map(|i| bass(i) + if i % 12000 < 96 { 0.6 } else { 0.0 })
+            .collect();
+
+        // Mean extra reduction the clicks put on the bass over the last second
+        let pumping = |auto_release: bool| {
+            let reference = gr_trace(&mut release_test_comp(auto_release), &plain);
+            let trace = gr_trace(&mut release_test_comp(auto_release), &clicked);
+            let dips: Vec<f32> = trace[1000..]
+                .iter()
+                .zip(&reference[1000..])
+                .map(|(gr, base)| (base - gr).max(0.0))
+                .collect();
+            dips.iter().sum::<f32>() / dips.len() as f32
+        };
+        let (fixed, auto) = (pumping(false), pumping(true));
+        assert!(auto < 0.5 * fixed, "auto {} dB vs fixed {} dB", auto, fixed);
+    }
+
+    #[test]
+    fn test_set_params_atomic() {
+        let mut comp = Compressor::new();
+        comp.prepare(48000.0, 512);
+        let before = comp.to_json().unwrap();
+
+        let batch = serde_json::json!({ "threshold_db": -30.0, "ratio": 50.0 });
+        assert!(comp.set_params_atomic(&batch).is_err());
+        assert_eq!(comp.to_json().unwrap(), before);
+        assert_eq!(comp.params().threshold_db, -18.0);
+
+        let batch = serde_json::json!({
+            "threshold_db": -30.0,
+            "ratio": 8.0,
+            "attack_ms": 2.0,
+            "auto_makeup": true,
+        });
+        comp.set_params_atomic(&batch).unwrap();
+        let params = comp.params();
+        assert_eq!(params.threshold_db, -30.0);
+        assert_eq!(params.ratio, 8.0);
+        assert_eq!(params.attack_ms, 2.0);
+        assert!(params.auto_makeup);
+
+        // The batch lands the same as setting each value in turn
+        let mut one_by_one = Compressor::new();
+        one_by_one.prepare(48000.0, 512);
+        for (name, value) in batch.as_object().unwrap() {
+            one_by_one.set_param(name, value.clone()).unwrap();
+        }
+        assert_eq!(one_by_one.to_json().unwrap(), comp.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_parallel_mix() {
+        // A quiet sustained tone with a loud, fast-decaying hit every 250 ms
+        let samples: Vec<f32> = (0..48000)
+            .map(|i| {
+                let t = i as f32 / 48000.0;
+                let tone = 0.1 * (std::f32::consts::TAU * 220.0 * t).sin();
+                let since_hit = (i % 12000) as f32 / 48000.0;
+                let hit = 0.8
+                    * (-since_hit / 0.01).exp()
+                    * (std::f32::consts::TAU * 1000.0 * since_hit).sin();
+                tone + hit
+            })
+            .collect();
+        let input = AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap();
+        let compress = |mix: f32| {
+            let mut comp = Compressor::with_params(CompressorParams {
+                threshold_db: -30.0,
+                ratio: 8.0,
+                attack_ms: 0.1,
+                release_ms: 80.0,
+                makeup_gain_db: 12.0,
+                parallel_mix: mix,
+                ..Default::default()
+            });
+            comp.prepare(48000.0, 512);
+            let mut buffer = input.clone();
+            comp.process(&mut buffer);
+            buffer
+        };
+
+        // The ends of the knob are passthrough and the plain compressor
+        assert_eq!(compress(0.0).samples(), input.samples());
+        let mut plain = Compressor::with_params(CompressorParams {
+            threshold_db: -30.0,
+            ratio: 8.0,
+            attack_ms: 0.1,
+            release_ms: 80.0,
+            makeup_gain_db: 12.0,
+            ..Default::default()
+        });
+        plain.prepare(48000.0, 512);
+        let mut full = input.clone();
+        plain.process(&mut full);
+        assert_eq!(compress(1.0).samples(), full.samples());
+
+        // Hit peak and sustained body level, skipping the first hit
+        // while the envelope settles
+        let levels = |buffer: &AudioBuffer| {
+            let settled = &buffer.samples()[12000..];
+            let hit = settled
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| i % 12000 < 960)
+                .fold(0.0_f32, |peak, (_, s)| peak.max(s.abs()));
+            let body: Vec<f32> = settled
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| i % 12000 >= 3000)
+                .map(|(_, s)| *s)
+                .collect();
+            let body_rms = (body.iter().map(|s| s * s).sum::<f32>() / body.len() as f32).sqrt();
+            (hit, body_rms)
+        };
+        let db = |linear: f32| 20.0 * linear.log10();
+        let (dry_hit, _) = levels(&input);
+        let (full_hit, full_body) = levels(&full);
+
+        // More mix brings the body up, while the hits stand further above
+        // it than they do fully compressed
+        let mut previous_body = 0.0;
+        for mix in [0.0, 0.25, 0.5, 0.75] {
+            let (hit, body) = levels(&compress(mix));
+            assert!(body > previous_body, "mix {}: body {} dB", mix, db(body));
+            previous_body = body;
+            assert!(
+                db(hit) - db(body) > db(full_hit) - db(full_body) + 1.0,
+                "mix {}: hit {} dB over body {} dB",
+                mix,
+                db(hit),
+                db(body)
+            );
+            // The two paths add without cancelling
+            assert!(hit >= (1.0 - mix) * dry_hit, "mix {}: hit {}", mix, hit);
+        }
+        assert!(full_body > previous_body);
     }
 }
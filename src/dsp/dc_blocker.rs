@@ -0,0 +1,144 @@
+//! DC blocking filter
+//!
+//! A one-pole high-pass, `y[n] = x[n] - x[n-1] + R * y[n-1]`, with the
+//! pole placed from a cutoff frequency. Used after nonlinear stages that
+//! can shift the signal's mean (e.g. asymmetric saturation).
+
+use std::f64::consts::PI;
+
+/// Default cutoff in Hz, well below the audible range
+pub const DC_BLOCKER_CUTOFF_HZ: f64 = 10.0;
+
+/// Single-channel DC blocker
+#[derive(Debug, Clone)]
+pub struct DcBlocker {
+    /// Cutoff frequency in Hz
+    cutoff_hz: f64,
+    /// Pole radius
+    r: f32,
+    /// Previous input sample
+    x1: f32,
+    /// Previous output sample
+    y1: f32,
+}
+
+impl DcBlocker {
+    /// Create a blocker with the given cutoff at the given sample rate
+    pub fn new(cutoff_hz: f64, sample_rate: f64) -> Self {
+        let mut blocker = Self {
+            cutoff_hz,
+            r: 0.0,
+            x1: 0.0,
+            y1: 0.0,
+        };
+        blocker.set_sample_rate(sample_rate);
+        blocker
+    }
+
+    /// Recompute the pole for a new sample rate (state is kept)
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.r = if sample_rate > 0.0 {
+            (-2.0 * PI * self.cutoff_hz / sample_rate).exp() as f32
+        } else {
+            0.0
+        };
+    }
+
+    /// Cutoff frequency in Hz
+    pub fn cutoff_hz(&self) -> f64 {
+        self.cutoff_hz
+    }
+
+    /// Filter one sample
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = x - self.x1 + self.r * self.y1;
+        self.x1 = x;
+        self.y1 = y;
+        y
+    }
+
+    /// Clear the filter state
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.y1 = 0.0;
+    }
+}
+
+impl Default for DcBlocker {
+    fn default() -> Self {
+        Self::new(DC_BLOCKER_CUTOFF_HZ, 44100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::{calculate_mean, DC_OFFSET_THRESHOLD};
+    use crate::engine::AudioBuffer;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    fn sine(freq: f32, amplitude: f32, offset: f32) -> Vec<f32> {
+        (0..SAMPLE_RATE)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                offset + amplitude * (2.0 * std::f32::consts::PI * freq * t).sin()
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_removes_dc_offset() {
+        let input = sine(220.0, 0.3, 0.2);
+        let mut blocker = DcBlocker::new(DC_BLOCKER_CUTOFF_HZ, SAMPLE_RATE as f64);
+        let output: Vec<f32> = input.iter().map(|&x| blocker.process(x)).collect();
+
+        let before = AudioBuffer {
+            samples: vec![input],
+            sample_rate: SAMPLE_RATE,
+        };
+        let after = AudioBuffer {
+            samples: vec![output],
+            sample_rate: SAMPLE_RATE,
+        };
+        assert!(calculate_mean(&before).abs() > DC_OFFSET_THRESHOLD);
+        assert!(
+            calculate_mean(&after).abs() < DC_OFFSET_THRESHOLD,
+            "mean after blocking: {}",
+            calculate_mean(&after)
+        );
+    }
+
+    #[test]
+    fn test_passband_unaffected() {
+        let input = sine(1000.0, 0.5, 0.0);
+        let mut blocker = DcBlocker::new(DC_BLOCKER_CUTOFF_HZ, SAMPLE_RATE as f64);
+        let output: Vec<f32> = input.iter().map(|&x| blocker.process(x)).collect();
+
+        // Skip the first 100 ms while the filter settles
+        let skip = SAMPLE_RATE as usize / 10;
+        let change_db = 20.0 * (rms(&output[skip..]) / rms(&input[skip..])).log10();
+        assert!(
+            change_db.abs() < 0.01,
+            "1 kHz level changed by {} dB",
+            change_db
+        );
+    }
+
+    #[test]
+    fn test_reset_clears_state() {
+        let mut blocker = DcBlocker::default();
+        for _ in 0..100 {
+            blocker.process(1.0);
+        }
+        blocker.reset();
+
+        let mut fresh = DcBlocker::default();
+        assert_eq!(blocker.process(0.5), fresh.process(0.5));
+    }
+}
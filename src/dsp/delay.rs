@@ -2,13 +2,16 @@
 //!
 //! Features:
 //! - Circular buffer with fractional delay via cubic interpolation
-//! - Feedback with low-pass filter in feedback path
+//! - Feedback with low-pass and high-pass filters in feedback path
 //! - Ping-pong mode for stereo
+//! - Ducking: the wet signal is pulled down while the input is playing
+//! - Multi-tap mode: independent taps, each with its own time, gain and pan
 //! - Wet/dry mixing
 
-use super::effect::{Effect, EffectMetadata};
+use super::effect::{repeats_to_decay, Effect, EffectMetadata, MixMode};
 use super::AudioBuffer;
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 
 /// Maximum delay time in milliseconds (2 seconds)
@@ -20,6 +23,47 @@ const MIN_DELAY_MS: f32 = 1.0;
 /// Maximum feedback (less than 1.0 to prevent infinite buildup)
 const MAX_FEEDBACK: f32 = 0.95;
 
+/// Lowest feedback high-pass frequency; at this setting the filter is off
+pub const MIN_FEEDBACK_HIGHPASS_HZ: f32 = 20.0;
+
+/// Input envelope (-20 dBFS peak) at which ducking reaches full depth
+const DUCK_FULL_LEVEL: f32 = 0.1;
+
+/// Ducking envelope attack time in milliseconds
+const DUCK_ATTACK_MS: f64 = 10.0;
+
+/// Ducking envelope release time in milliseconds
+const DUCK_RELEASE_MS: f64 = 250.0;
+
+/// One tap of a multi-tap delay
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DelayTap {
+    /// Time of this tap in milliseconds (1 to 2000 ms)
+    pub time_ms: f32,
+    /// Level of this tap (0 to 1)
+    pub gain: f32,
+    /// Stereo position (-1 = left, 0 = centre, 1 = right)
+    pub pan: f32,
+}
+
+impl DelayTap {
+    /// Create a tap
+    pub fn new(time_ms: f32, gain: f32, pan: f32) -> Self {
+        Self { time_ms, gain, pan }
+    }
+
+    /// Left and right gains
+    ///
+    /// A balance law: the centre leaves both sides at `gain`, and panning
+    /// turns the opposite side down, to silence at the extreme.
+    fn channel_gains(&self) -> [f32; 2] {
+        [
+            self.gain * (1.0 - self.pan).min(1.0),
+            self.gain * (1.0 + self.pan).min(1.0),
+        ]
+    }
+}
+
 /// Delay effect parameters (spec section 4.2.5)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DelayParams {
@@ -35,6 +79,24 @@ pub struct DelayParams {
     pub ping_pong: bool,
     /// Low-pass filter frequency in feedback path (Hz)
     pub filter_freq: f32,
+    /// High-pass filter frequency in feedback path (Hz, 20 = off)
+    #[serde(default = "default_feedback_highpass_freq")]
+    pub feedback_highpass_freq: f32,
+    /// How far the wet signal is pulled down while the input is playing
+    /// (0 = off, 1 = silent under a -20 dBFS or louder input)
+    #[serde(default)]
+    pub ducking: f32,
+    /// How dry and wet levels are mixed
+    #[serde(default)]
+    pub mix_mode: MixMode,
+    /// Taps summed into the wet output instead of the main line; empty for
+    /// a single delay. Feedback still runs on the main line.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub taps: Vec<DelayTap>,
+}
+
+fn default_feedback_highpass_freq() -> f32 {
+    MIN_FEEDBACK_HIGHPASS_HZ
 }
 
 impl Default for DelayParams {
@@ -46,6 +108,10 @@ impl Default for DelayParams {
             dry_level: 1.0,
             ping_pong: false,
             filter_freq: 8000.0,
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         }
     }
 }
@@ -88,10 +154,62 @@ impl DelayParams {
                 expected: "20 to 20000 Hz".to_string(),
             });
         }
+        if self.feedback_highpass_freq < MIN_FEEDBACK_HIGHPASS_HZ
+            || self.feedback_highpass_freq > 20000.0
+        {
+            return Err(NuevaError::InvalidParameter {
+                param: "feedback_highpass_freq".to_string(),
+                value: self.feedback_highpass_freq.to_string(),
+                expected: "20 to 20000 Hz".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.ducking) {
+            return Err(NuevaError::InvalidParameter {
+                param: "ducking".to_string(),
+                value: self.ducking.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
+        for (i, tap) in self.taps.iter().enumerate() {
+            if tap.time_ms < MIN_DELAY_MS || tap.time_ms > MAX_DELAY_MS {
+                return Err(NuevaError::InvalidParameter {
+                    param: format!("taps[{}].time_ms", i),
+                    value: tap.time_ms.to_string(),
+                    expected: format!("{} to {} ms", MIN_DELAY_MS, MAX_DELAY_MS),
+                });
+            }
+            if !(0.0..=1.0).contains(&tap.gain) {
+                return Err(NuevaError::InvalidParameter {
+                    param: format!("taps[{}].gain", i),
+                    value: tap.gain.to_string(),
+                    expected: "0.0 to 1.0".to_string(),
+                });
+            }
+            if !(-1.0..=1.0).contains(&tap.pan) {
+                return Err(NuevaError::InvalidParameter {
+                    param: format!("taps[{}].pan", i),
+                    value: tap.pan.to_string(),
+                    expected: "-1.0 to 1.0".to_string(),
+                });
+            }
+        }
         Ok(())
     }
 }
 
+/// Cubic Hermite interpolation between `y0` and `y1`
+///
+/// `y_m1` and `y2` are the neighbours on either side; `frac` runs from 0
+/// (at `y0`) to 1 (at `y1`).
+pub(crate) fn cubic_hermite(y_m1: f32, y0: f32, y1: f32, y2: f32, frac: f32) -> f32 {
+    let c0 = y0;
+    let c1 = 0.5 * (y1 - y_m1);
+    let c2 = y_m1 - 2.5 * y0 + 2.0 * y1 - 0.5 * y2;
+    let c3 = 0.5 * (y2 - y_m1) + 1.5 * (y0 - y1);
+
+    ((c3 * frac + c2) * frac + c1) * frac + c0
+}
+
 /// Circular delay buffer with interpolation
 #[derive(Debug, Clone)]
 struct DelayBuffer {
@@ -155,13 +273,7 @@ impl DelayBuffer {
         let y1 = self.buffer[idx_1];
         let y2 = self.buffer[idx_2];
 
-        // Cubic Hermite interpolation
-        let c0 = y0;
-        let c1 = 0.5 * (y1 - y_m1);
-        let c2 = y_m1 - 2.5 * y0 + 2.0 * y1 - 0.5 * y2;
-        let c3 = 0.5 * (y2 - y_m1) + 1.5 * (y0 - y1);
-
-        ((c3 * frac + c2) * frac + c1) * frac + c0
+        cubic_hermite(y_m1, y0, y1, y2, frac)
     }
 
     /// Read a sample with linear interpolation at a fractional delay
@@ -219,6 +331,11 @@ impl OnePoleFilter {
         self.z1
     }
 
+    /// Process a single sample as a high-pass (input minus its low-pass)
+    fn process_highpass(&mut self, input: f32) -> f32 {
+        input - self.process(input)
+    }
+
     /// Reset filter state
     fn reset(&mut self) {
         self.z1 = 0.0;
@@ -227,7 +344,7 @@ impl OnePoleFilter {
 
 /// Delay effect (spec section 4.2.5)
 ///
-/// Implements a digital delay with feedback, low-pass filtering,
+/// Implements a digital delay with filtered feedback, optional ducking,
 /// and optional stereo ping-pong mode.
 #[derive(Debug, Clone)]
 pub struct Delay {
@@ -247,6 +364,16 @@ pub struct Delay {
     filter_left: OnePoleFilter,
     /// Low-pass filter for right channel feedback
     filter_right: OnePoleFilter,
+    /// High-pass filter for left channel feedback
+    highpass_left: OnePoleFilter,
+    /// High-pass filter for right channel feedback
+    highpass_right: OnePoleFilter,
+    /// Input envelope driving the ducker
+    duck_envelope: f32,
+    /// Ducking envelope attack coefficient
+    duck_attack: f32,
+    /// Ducking envelope release coefficient
+    duck_release: f32,
     /// Feedback sample for left channel (for ping-pong)
     feedback_left: f32,
     /// Feedback sample for right channel (for ping-pong)
@@ -263,7 +390,7 @@ impl Delay {
     pub fn with_params(params: DelayParams) -> Self {
         // Initialize with reasonable default buffer size (will be resized in prepare)
         let buffer_size = 88200; // ~2 seconds at 44.1kHz
-        Self {
+        let mut delay = Self {
             params,
             id: String::new(),
             enabled: true,
@@ -272,9 +399,16 @@ impl Delay {
             delay_right: DelayBuffer::new(buffer_size),
             filter_left: OnePoleFilter::new(),
             filter_right: OnePoleFilter::new(),
+            highpass_left: OnePoleFilter::new(),
+            highpass_right: OnePoleFilter::new(),
+            duck_envelope: 0.0,
+            duck_attack: 0.0,
+            duck_release: 0.0,
             feedback_left: 0.0,
             feedback_right: 0.0,
-        }
+        };
+        delay.update_filters();
+        delay
     }
 
     /// Get a reference to the current parameters
@@ -332,12 +466,70 @@ impl Delay {
         Ok(())
     }
 
+    /// Set the feedback high-pass frequency (20 Hz turns it off)
+    pub fn set_feedback_highpass_freq(&mut self, freq: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.feedback_highpass_freq = freq;
+        self.set_params(params)
+    }
+
+    /// Set how far the wet signal ducks under the input (0 = off)
+    pub fn set_ducking(&mut self, ducking: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.ducking = ducking;
+        self.set_params(params)
+    }
+
     /// Update filter coefficients
     fn update_filters(&mut self) {
         self.filter_left
             .set_frequency(self.params.filter_freq, self.sample_rate);
         self.filter_right
             .set_frequency(self.params.filter_freq, self.sample_rate);
+        self.highpass_left
+            .set_frequency(self.params.feedback_highpass_freq, self.sample_rate);
+        self.highpass_right
+            .set_frequency(self.params.feedback_highpass_freq, self.sample_rate);
+        self.duck_attack = (-1000.0 / (DUCK_ATTACK_MS * self.sample_rate)).exp() as f32;
+        self.duck_release = (-1000.0 / (DUCK_RELEASE_MS * self.sample_rate)).exp() as f32;
+    }
+
+    /// Run the feedback filters on a delayed sample
+    ///
+    /// The high-pass is skipped at its 20 Hz minimum so existing settings
+    /// render exactly as before it existed.
+    fn filter_feedback(
+        delayed: f32,
+        lowpass: &mut OnePoleFilter,
+        highpass: &mut OnePoleFilter,
+        highpass_on: bool,
+    ) -> f32 {
+        let filtered = lowpass.process(delayed);
+        if highpass_on {
+            highpass.process_highpass(filtered)
+        } else {
+            filtered
+        }
+    }
+
+    /// Whether the feedback high-pass is engaged
+    fn highpass_on(&self) -> bool {
+        self.params.feedback_highpass_freq > MIN_FEEDBACK_HIGHPASS_HZ
+    }
+
+    /// Advance the ducking envelope by one frame with input `peak` and
+    /// return the wet gain
+    fn duck_gain(&mut self, peak: f32) -> f32 {
+        if self.params.ducking <= 0.0 {
+            return 1.0;
+        }
+        let coeff = if peak > self.duck_envelope {
+            self.duck_attack
+        } else {
+            self.duck_release
+        };
+        self.duck_envelope = peak + coeff * (self.duck_envelope - peak);
+        1.0 - self.params.ducking * (self.duck_envelope / DUCK_FULL_LEVEL).min(1.0)
     }
 
     /// Calculate delay in samples
@@ -345,26 +537,77 @@ impl Delay {
         (self.params.delay_time_ms / 1000.0) * self.sample_rate as f32
     }
 
+    /// Each tap's delay in samples and left/right gains (pan is ignored
+    /// for `mono` output)
+    fn tap_gains(&self, mono: bool) -> Vec<(f32, [f32; 2])> {
+        let sample_rate = self.sample_rate as f32;
+        self.params
+            .taps
+            .iter()
+            .map(|tap| {
+                let gains = if mono {
+                    [tap.gain; 2]
+                } else {
+                    tap.channel_gains()
+                };
+                (tap.time_ms / 1000.0 * sample_rate, gains)
+            })
+            .collect()
+    }
+
+    /// Wet sample for `channel`: the main line's `delayed` sample, or the
+    /// sum of the taps read from `line`
+    fn wet_sample(
+        taps: &[(f32, [f32; 2])],
+        line: &DelayBuffer,
+        delayed: f32,
+        channel: usize,
+    ) -> f32 {
+        if taps.is_empty() {
+            return delayed;
+        }
+        taps.iter()
+            .map(|(delay, gains)| line.read_cubic(*delay) * gains[channel])
+            .sum()
+    }
+
+    /// Dry and wet gains for the current mix mode
+    fn mix_gains(&self) -> (f32, f32) {
+        self.params
+            .mix_mode
+            .gains(self.params.dry_level, self.params.wet_level)
+    }
+
     /// Process mono audio
     fn process_mono(&mut self, buffer: &mut AudioBuffer) {
         let delay_samples = self.delay_samples();
+        let (dry, wet) = self.mix_gains();
+        let highpass_on = self.highpass_on();
+        let taps = self.tap_gains(true);
         let num_samples = buffer.num_samples();
 
         for i in 0..num_samples {
             let input = buffer.get(i, 0).unwrap_or(0.0);
+            let wet = wet * self.duck_gain(input.abs());
 
             // Read from delay line with interpolation
             let delayed = self.delay_left.read_cubic(delay_samples);
+            let tapped = Self::wet_sample(&taps, &self.delay_left, delayed, 0);
 
-            // Apply feedback filter
-            let filtered_feedback = self.filter_left.process(delayed);
+            // Apply feedback filters
+            let filtered_feedback = Self::filter_feedback(
+                delayed,
+                &mut self.filter_left,
+                &mut self.highpass_left,
+                highpass_on,
+            );
 
             // Write input plus filtered feedback to delay line
             self.delay_left
                 .write(input + filtered_feedback * self.params.feedback);
 
             // Mix dry and wet
-            let output = input * self.params.dry_level + delayed * self.params.wet_level;
+            let output = input * dry + tapped * wet;
             buffer.set(i, 0, output);
         }
     }
@@ -372,19 +615,35 @@ impl Delay {
     /// Process stereo audio (standard mode)
     fn process_stereo(&mut self, buffer: &mut AudioBuffer) {
         let delay_samples = self.delay_samples();
+        let (dry, wet) = self.mix_gains();
+        let highpass_on = self.highpass_on();
+        let taps = self.tap_gains(false);
         let num_samples = buffer.num_samples();
 
         for i in 0..num_samples {
             let input_left = buffer.get(i, 0).unwrap_or(0.0);
             let input_right = buffer.get(i, 1).unwrap_or(0.0);
+            let wet = wet * self.duck_gain(input_left.abs().max(input_right.abs()));
 
             // Read from delay lines
             let delayed_left = self.delay_left.read_cubic(delay_samples);
             let delayed_right = self.delay_right.read_cubic(delay_samples);
+            let tapped_left = Self::wet_sample(&taps, &self.delay_left, delayed_left, 0);
+            let tapped_right = Self::wet_sample(&taps, &self.delay_right, delayed_right, 1);
 
             // Apply feedback filters
-            let filtered_left = self.filter_left.process(delayed_left);
-            let filtered_right = self.filter_right.process(delayed_right);
+            let filtered_left = Self::filter_feedback(
+                delayed_left,
+                &mut self.filter_left,
+                &mut self.highpass_left,
+                highpass_on,
+            );
+            let filtered_right = Self::filter_feedback(
+                delayed_right,
+                &mut self.filter_right,
+                &mut self.highpass_right,
+                highpass_on,
+            );
 
             // Write to delay lines
             self.delay_left
@@ -393,10 +652,8 @@ impl Delay {
                 .write(input_right + filtered_right * self.params.feedback);
 
             // Mix dry and wet
-            let output_left =
-                input_left * self.params.dry_level + delayed_left * self.params.wet_level;
-            let output_right =
-                input_right * self.params.dry_level + delayed_right * self.params.wet_level;
+            let output_left = input_left * dry + tapped_left * wet;
+            let output_right = input_right * dry + tapped_right * wet;
 
             buffer.set(i, 0, output_left);
             buffer.set(i, 1, output_right);
@@ -406,19 +663,36 @@ impl Delay {
     /// Process stereo audio in ping-pong mode
     fn process_ping_pong(&mut self, buffer: &mut AudioBuffer) {
         let delay_samples = self.delay_samples();
+        let (dry, wet) = self.mix_gains();
+        let highpass_on = self.highpass_on();
+        let taps = self.tap_gains(false);
         let num_samples = buffer.num_samples();
 
         for i in 0..num_samples {
             let input_left = buffer.get(i, 0).unwrap_or(0.0);
             let input_right = buffer.get(i, 1).unwrap_or(0.0);
+            let wet = wet * self.duck_gain(input_left.abs().max(input_right.abs()));
 
             // Read from delay lines
             let delayed_left = self.delay_left.read_cubic(delay_samples);
             let delayed_right = self.delay_right.read_cubic(delay_samples);
+            // Taps read the left line, which carries the input
+            let tapped_left = Self::wet_sample(&taps, &self.delay_left, delayed_left, 0);
+            let tapped_right = Self::wet_sample(&taps, &self.delay_left, delayed_right, 1);
 
             // Apply feedback filters
-            let filtered_left = self.filter_left.process(delayed_left);
-            let filtered_right = self.filter_right.process(delayed_right);
+            let filtered_left = Self::filter_feedback(
+                delayed_left,
+                &mut self.filter_left,
+                &mut self.highpass_left,
+                highpass_on,
+            );
+            let filtered_right = Self::filter_feedback(
+                delayed_right,
+                &mut self.filter_right,
+                &mut self.highpass_right,
+                highpass_on,
+            );
 
             // In ping-pong mode:
             // - Left delay feeds from: mono input + right delay feedback
@@ -431,10 +705,8 @@ impl Delay {
             self.delay_right.write(filtered_left * self.params.feedback);
 
             // Mix dry and wet
-            let output_left =
-                input_left * self.params.dry_level + delayed_left * self.params.wet_level;
-            let output_right =
-                input_right * self.params.dry_level + delayed_right * self.params.wet_level;
+            let output_left = input_left * dry + tapped_left * wet;
+            let output_right = input_right * dry + tapped_right * wet;
 
             buffer.set(i, 0, output_left);
             buffer.set(i, 1, output_right);
@@ -492,6 +764,9 @@ impl Effect for Delay {
         self.delay_right.clear();
         self.filter_left.reset();
         self.filter_right.reset();
+        self.highpass_left.reset();
+        self.highpass_right.reset();
+        self.duck_envelope = 0.0;
         self.feedback_left = 0.0;
         self.feedback_right = 0.0;
     }
@@ -508,6 +783,10 @@ impl Effect for Delay {
                 "dry_level": self.params.dry_level,
                 "ping_pong": self.params.ping_pong,
                 "filter_freq": self.params.filter_freq,
+                "feedback_highpass_freq": self.params.feedback_highpass_freq,
+                "ducking": self.params.ducking,
+                "mix_mode": self.params.mix_mode,
+                "taps": self.params.taps,
             }
         }))
     }
@@ -542,6 +821,25 @@ impl Effect for Delay {
             if let Some(v) = params.get("filter_freq").and_then(|v| v.as_f64()) {
                 new_params.filter_freq = v as f32;
             }
+            if let Some(v) = params
+                .get("feedback_highpass_freq")
+                .and_then(|v| v.as_f64())
+            {
+                new_params.feedback_highpass_freq = v as f32;
+            }
+            if let Some(v) = params.get("ducking").and_then(|v| v.as_f64()) {
+                new_params.ducking = v as f32;
+            }
+            if let Some(v) = params.get("mix_mode") {
+                new_params.mix_mode = MixMode::from_json(v)?;
+            }
+            if let Some(v) = params.get("taps") {
+                new_params.taps = serde_json::from_value(v.clone()).map_err(|e| {
+                    NuevaError::SerializationError {
+                        details: format!("taps: {}", e),
+                    }
+                })?;
+            }
 
             self.set_params(new_params)?;
         }
@@ -581,6 +879,43 @@ impl Effect for Delay {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("delay_time_ms", MIN_DELAY_MS, MAX_DELAY_MS, 250.0).with_unit("ms"),
+            ParamSpec::float("feedback", 0.0, MAX_FEEDBACK, 0.3),
+            ParamSpec::float("wet_level", 0.0, 1.0, 0.3),
+            ParamSpec::float("dry_level", 0.0, 1.0, 1.0),
+            ParamSpec::boolean("ping_pong", false),
+            ParamSpec::float("filter_freq", 20.0, 20000.0, 8000.0).with_unit("Hz"),
+            ParamSpec::float(
+                "feedback_highpass_freq",
+                MIN_FEEDBACK_HIGHPASS_HZ,
+                20000.0,
+                MIN_FEEDBACK_HIGHPASS_HZ,
+            )
+            .with_unit("Hz"),
+            ParamSpec::float("ducking", 0.0, 1.0, 0.0),
+            MixMode::param_spec(),
+        ]
+    }
+
+    fn tail_samples(&self) -> usize {
+        if self.params.wet_level <= 0.0 {
+            return 0;
+        }
+        // Every repeat until feedback has decayed it (the feedback filter
+        // only makes repeats quieter), heard through the longest tap or the
+        // main line; a few extra samples cover the interpolation taps
+        let repeats = repeats_to_decay(self.params.feedback).ceil();
+        let taps = self.tap_gains(true);
+        let longest = if taps.is_empty() {
+            self.delay_samples()
+        } else {
+            taps.iter().map(|(delay, _)| *delay).fold(0.0, f32::max)
+        };
+        (self.delay_samples() * repeats + longest).ceil() as usize + 4
+    }
 }
 
 #[cfg(test)]
@@ -607,8 +942,10 @@ mod tests {
         assert!(params.validate().is_ok());
 
         // Invalid delay time (too short)
-        let mut params = DelayParams::default();
-        params.delay_time_ms = 0.5;
+        let mut params = DelayParams {
+            delay_time_ms: 0.5,
+            ..Default::default()
+        };
         assert!(params.validate().is_err());
 
         // Invalid delay time (too long)
@@ -633,6 +970,16 @@ mod tests {
         params = DelayParams::default();
         params.filter_freq = 10.0;
         assert!(params.validate().is_err());
+
+        // Invalid feedback high-pass frequency
+        params = DelayParams::default();
+        params.feedback_highpass_freq = 10.0;
+        assert!(params.validate().is_err());
+
+        // Invalid ducking
+        params = DelayParams::default();
+        params.ducking = 1.5;
+        assert!(params.validate().is_err());
     }
 
     #[test]
@@ -702,6 +1049,10 @@ mod tests {
             dry_level: 0.0, // Only wet signal
             ping_pong: false,
             filter_freq: 20000.0, // High frequency = minimal filtering
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         });
         delay.prepare(44100.0, 512);
 
@@ -733,6 +1084,10 @@ mod tests {
             dry_level: 0.0,
             ping_pong: false,
             filter_freq: 20000.0,
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         });
         delay.prepare(44100.0, 512);
 
@@ -765,6 +1120,10 @@ mod tests {
             dry_level: 0.0,
             ping_pong: false,
             filter_freq: 20000.0,
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         });
         delay.prepare(44100.0, 512);
 
@@ -797,6 +1156,10 @@ mod tests {
             dry_level: 0.0,
             ping_pong: true,
             filter_freq: 20000.0,
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         });
         delay.prepare(44100.0, 512);
 
@@ -859,6 +1222,10 @@ mod tests {
                 dry_level: 0.8,
                 ping_pong: true,
                 filter_freq: 5000.0,
+                feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+                ducking: 0.0,
+                mix_mode: MixMode::Linear,
+                taps: Vec::new(),
             })
             .unwrap();
 
@@ -900,6 +1267,10 @@ mod tests {
             dry_level: 0.0,
             ping_pong: false,
             filter_freq: 20000.0,
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         });
         delay.prepare(44100.0, 512);
 
@@ -948,6 +1319,10 @@ mod tests {
             dry_level: 0.5,
             ping_pong: false,
             filter_freq: 20000.0,
+            feedback_highpass_freq: MIN_FEEDBACK_HIGHPASS_HZ,
+            ducking: 0.0,
+            mix_mode: MixMode::Linear,
+            taps: Vec::new(),
         });
         delay.prepare(44100.0, 512);
 
@@ -964,4 +1339,411 @@ mod tests {
         let first = buffer.get(0, 0).unwrap();
         assert!((first - 0.5).abs() < 0.01);
     }
+
+    #[test]
+    fn test_delay_tail_covers_echoes() {
+        let mut delay = Delay::with_params(DelayParams {
+            delay_time_ms: 100.0,
+            feedback: 0.5,
+            dry_level: 0.0,
+            ..Default::default()
+        });
+        delay.prepare(48000.0, 512);
+        let tail = delay.tail_samples();
+
+        let mut buffer = AudioBuffer::new(1, tail * 2, 48000.0);
+        buffer.set(0, 0, 1.0);
+        delay.process(&mut buffer);
+
+        // Last echo within 60 dB of the first
+        let first = (0..buffer.num_samples())
+            .map(|i| buffer.get(i, 0).unwrap().abs())
+            .fold(0.0_f32, f32::max);
+        let last = (0..buffer.num_samples())
+            .rposition(|i| buffer.get(i, 0).unwrap().abs() > first * 0.001)
+            .unwrap();
+        assert!(
+            tail >= last,
+            "estimated {} samples, last echo at {}",
+            tail,
+            last
+        );
+    }
+
+    #[test]
+    fn test_delay_tail_without_feedback_or_wet() {
+        let mut delay = Delay::with_params(DelayParams {
+            delay_time_ms: 100.0,
+            feedback: 0.0,
+            ..Default::default()
+        });
+        delay.prepare(48000.0, 512);
+        // A single echo
+        assert_eq!(delay.tail_samples(), 4800 + 4);
+
+        delay.set_wet_level(0.0).unwrap();
+        assert_eq!(delay.tail_samples(), 0);
+    }
+
+    /// Output RMS in dB of a delayed noise burst at a given dry/wet crossfade
+    fn crossfade_level_db(mix_mode: MixMode, wet: f32) -> f64 {
+        let mut delay = Delay::with_params(DelayParams {
+            delay_time_ms: 20.0,
+            feedback: 0.0,
+            wet_level: wet,
+            dry_level: 1.0 - wet,
+            filter_freq: 20000.0,
+            mix_mode,
+            ..Default::default()
+        });
+        delay.prepare(48000.0, 512);
+
+        let mut buffer = AudioBuffer::new(1, 48000, 48000.0);
+        let mut seed = 7u32;
+        for i in 0..48000 {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            buffer.set(i, 0, (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5);
+        }
+        delay.process(&mut buffer);
+        buffer.rms_db(0)
+    }
+
+    #[test]
+    fn test_linear_mix_mode_matches_levels() {
+        let params = DelayParams {
+            wet_level: 0.4,
+            dry_level: 0.7,
+            ..Default::default()
+        };
+        assert_eq!(params.mix_mode, MixMode::Linear);
+
+        let mut linear = Delay::with_params(params.clone());
+        let mut buffer = AudioBuffer::new(1, 1000, 44100.0);
+        buffer.set(0, 0, 1.0);
+        linear.prepare(44100.0, 512);
+        linear.process(&mut buffer);
+        assert_eq!(buffer.get(0, 0), Some(0.7));
+
+        // Projects saved before mix_mode existed load as linear
+        let mut json = linear.to_json().unwrap();
+        json["params"].as_object_mut().unwrap().remove("mix_mode");
+        let mut restored = Delay::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.params().mix_mode, MixMode::Linear);
+    }
+
+    #[test]
+    fn test_equal_power_mix_holds_level_across_crossfade() {
+        for mode in [MixMode::Linear, MixMode::EqualPower] {
+            let dry = crossfade_level_db(mode, 0.0);
+            let wet = crossfade_level_db(mode, 1.0);
+            let half = crossfade_level_db(mode, 0.5);
+            let dip = half - (dry + wet) / 2.0;
+            match mode {
+                MixMode::Linear => assert!(dip < -2.5, "linear dip {}", dip),
+                MixMode::EqualPower => assert!(dip.abs() < 0.5, "equal-power dip {}", dip),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_mode_serializes() {
+        let mut delay = Delay::new();
+        delay
+            .set_params(DelayParams {
+                mix_mode: MixMode::EqualPower,
+                ..Default::default()
+            })
+            .unwrap();
+        let json = delay.to_json().unwrap();
+        assert_eq!(json["params"]["mix_mode"], "equal_power");
+
+        let mut restored = Delay::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.params().mix_mode, MixMode::EqualPower);
+
+        let bad = serde_json::json!({"params": {"mix_mode": "loud"}});
+        assert!(restored.from_json(&bad).is_err());
+    }
+
+    fn sine(frequency: f32, amplitude: f32, num_samples: usize) -> AudioBuffer {
+        let samples = (0..num_samples)
+            .map(|i| {
+                amplitude * (2.0 * std::f32::consts::PI * frequency * i as f32 / 48000.0).sin()
+            })
+            .collect();
+        AudioBuffer::from_interleaved(samples, 1, 48000.0).unwrap()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_feedback_highpass_thins_echoes() {
+        let echo_level = |highpass: f32| {
+            let mut delay = Delay::with_params(DelayParams {
+                delay_time_ms: 50.0,
+                feedback: 0.8,
+                wet_level: 1.0,
+                dry_level: 0.0,
+                filter_freq: 20000.0,
+                feedback_highpass_freq: highpass,
+                ..Default::default()
+            });
+            delay.prepare(48000.0, 512);
+            // 100 ms of 60 Hz, then silence: later echoes are all feedback
+            let mut buffer = sine(60.0, 0.5, 48000);
+            buffer.samples_mut()[4800..].fill(0.0);
+            delay.process(&mut buffer);
+            rms(&buffer.samples()[24000..])
+        };
+
+        let unfiltered = echo_level(MIN_FEEDBACK_HIGHPASS_HZ);
+        let thinned = echo_level(1000.0);
+        assert!(
+            thinned < unfiltered * 0.1,
+            "thinned {} vs unfiltered {}",
+            thinned,
+            unfiltered
+        );
+    }
+
+    #[test]
+    fn test_ducking_lowers_wet_under_input() {
+        let wet_level = |ducking: f32| {
+            let mut delay = Delay::with_params(DelayParams {
+                delay_time_ms: 100.0,
+                wet_level: 1.0,
+                dry_level: 0.0,
+                ducking,
+                ..Default::default()
+            });
+            delay.prepare(48000.0, 512);
+            let mut buffer = sine(440.0, 0.1, 48000);
+            delay.process(&mut buffer);
+            rms(&buffer.samples()[24000..])
+        };
+
+        let open = wet_level(0.0);
+        let half = wet_level(0.5);
+        let full = wet_level(1.0);
+        assert!(open > 0.05);
+        assert!(half < open * 0.75, "half {} vs open {}", half, open);
+        assert!(full < half * 0.5, "full {} vs half {}", full, half);
+    }
+
+    #[test]
+    fn test_highpass_and_ducking_serialize() {
+        let mut delay = Delay::with_params(DelayParams {
+            feedback_highpass_freq: 250.0,
+            ducking: 0.6,
+            ..Default::default()
+        });
+        let json = delay.to_json().unwrap();
+        assert_eq!(json["params"]["feedback_highpass_freq"], 250.0);
+
+        let mut restored = Delay::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.params().feedback_highpass_freq, 250.0);
+        assert!((restored.params().ducking - 0.6).abs() < 1e-6);
+
+        // Settings saved before these existed load with both off
+        let old: DelayParams = serde_json::from_value(serde_json::json!({
+            "delay_time_ms": 250.0,
+            "feedback": 0.3,
+            "wet_level": 0.3,
+            "dry_level": 1.0,
+            "ping_pong": false,
+            "filter_freq": 8000.0,
+        }))
+        .unwrap();
+        assert_eq!(old.feedback_highpass_freq, MIN_FEEDBACK_HIGHPASS_HZ);
+        assert_eq!(old.ducking, 0.0);
+
+        delay.set_ducking(0.0).unwrap();
+        assert!(delay.set_feedback_highpass_freq(5.0).is_err());
+    }
+
+    #[test]
+    fn test_feedback_stable_with_both_filters() {
+        for ping_pong in [false, true] {
+            let mut delay = Delay::with_params(DelayParams {
+                delay_time_ms: 5.0,
+                feedback: MAX_FEEDBACK,
+                wet_level: 1.0,
+                ping_pong,
+                filter_freq: 300.0,
+                feedback_highpass_freq: 8000.0,
+                ducking: 1.0,
+                ..Default::default()
+            });
+            delay.prepare(48000.0, 512);
+
+            let mut seed = 1u32;
+            let samples = (0..96000)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+                })
+                .collect();
+            let mut buffer = AudioBuffer::from_interleaved(samples, 2, 48000.0).unwrap();
+            delay.process(&mut buffer);
+
+            assert!(buffer
+                .samples()
+                .iter()
+                .all(|s| s.is_finite() && s.abs() < 4.0));
+        }
+    }
+
+    /// Wet-only, feedback-free delay at 48kHz with the given taps
+    fn tapped_delay(taps: Vec<DelayTap>) -> Delay {
+        let mut delay = Delay::with_params(DelayParams {
+            delay_time_ms: 10.0,
+            feedback: 0.0,
+            wet_level: 1.0,
+            dry_level: 0.0,
+            filter_freq: 20000.0,
+            taps,
+            ..Default::default()
+        });
+        delay.prepare(48000.0, 512);
+        delay
+    }
+
+    #[test]
+    fn test_taps_land_at_their_offsets() {
+        let mut delay = tapped_delay(vec![
+            DelayTap::new(5.0, 0.8, 0.0),
+            DelayTap::new(12.0, 0.5, 0.0),
+        ]);
+        let mut buffer = AudioBuffer::new(1, 1000, 48000.0);
+        buffer.set(0, 0, 1.0);
+        delay.process(&mut buffer);
+
+        // 5 ms and 12 ms at 48kHz
+        for i in 0..1000 {
+            let expected = match i {
+                240 => 0.8,
+                576 => 0.5,
+                _ => 0.0,
+            };
+            let sample = buffer.get(i, 0).unwrap();
+            assert!((sample - expected).abs() < 1e-4, "sample {}: {}", i, sample);
+        }
+    }
+
+    #[test]
+    fn test_taps_pan_per_channel() {
+        let mut delay = tapped_delay(vec![
+            DelayTap::new(5.0, 1.0, -1.0),
+            DelayTap::new(10.0, 1.0, 1.0),
+            DelayTap::new(15.0, 1.0, 0.5),
+        ]);
+        let mut buffer = AudioBuffer::new(2, 1000, 48000.0);
+        buffer.set(0, 0, 1.0);
+        buffer.set(0, 1, 1.0);
+        delay.process(&mut buffer);
+
+        let at = |i: usize| (buffer.get(i, 0).unwrap(), buffer.get(i, 1).unwrap());
+        let (left, right) = at(240);
+        assert!((left - 1.0).abs() < 1e-4 && right.abs() < 1e-4);
+        let (left, right) = at(480);
+        assert!(left.abs() < 1e-4 && (right - 1.0).abs() < 1e-4);
+        let (left, right) = at(720);
+        assert!((left - 0.5).abs() < 1e-4 && (right - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_empty_taps_are_a_single_delay() {
+        let params = DelayParams {
+            delay_time_ms: 7.0,
+            feedback: 0.6,
+            filter_freq: 6000.0,
+            ..Default::default()
+        };
+        let render = |taps: Vec<DelayTap>| {
+            let mut delay = Delay::with_params(DelayParams {
+                taps,
+                ..params.clone()
+            });
+            delay.prepare(48000.0, 512);
+            let mut buffer = AudioBuffer::new(2, 4000, 48000.0);
+            buffer.set(0, 0, 1.0);
+            buffer.set(10, 1, -0.5);
+            delay.process(&mut buffer);
+            (buffer, delay.tail_samples())
+        };
+
+        // One centred, full-level tap at the main delay time is the same
+        // as no taps at all
+        let (single, single_tail) = render(Vec::new());
+        let (tapped, tapped_tail) = render(vec![DelayTap::new(7.0, 1.0, 0.0)]);
+        assert_eq!(single_tail, tapped_tail);
+        for i in 0..4000 {
+            for ch in 0..2 {
+                let a = single.get(i, ch).unwrap();
+                let b = tapped.get(i, ch).unwrap();
+                assert!((a - b).abs() < 1e-6, "sample {} ch {}", i, ch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tap_validation() {
+        let mut delay = Delay::new();
+        let with_tap = |tap: DelayTap| DelayParams {
+            taps: vec![DelayTap::new(100.0, 0.5, 0.0), tap],
+            ..Default::default()
+        };
+
+        assert!(delay
+            .set_params(with_tap(DelayTap::new(MAX_DELAY_MS, 1.0, 1.0)))
+            .is_ok());
+        for bad in [
+            DelayTap::new(MAX_DELAY_MS + 1.0, 0.5, 0.0),
+            DelayTap::new(0.5, 0.5, 0.0),
+            DelayTap::new(100.0, 1.5, 0.0),
+            DelayTap::new(100.0, 0.5, -1.5),
+        ] {
+            assert!(delay.set_params(with_tap(bad)).is_err(), "{:?}", bad);
+        }
+        assert_eq!(delay.params().taps.len(), 2);
+
+        // A long tap lengthens the tail
+        let short = Delay::with_params(with_tap(DelayTap::new(100.0, 0.5, 0.0))).tail_samples();
+        let long = Delay::with_params(with_tap(DelayTap::new(1500.0, 0.5, 0.0))).tail_samples();
+        assert!(long > short);
+    }
+
+    #[test]
+    fn test_taps_serialize() {
+        let taps = vec![
+            DelayTap::new(120.0, 0.7, -0.5),
+            DelayTap::new(360.0, 0.4, 0.5),
+        ];
+        let delay = Delay::with_params(DelayParams {
+            taps: taps.clone(),
+            ..Default::default()
+        });
+        let json = delay.to_json().unwrap();
+        assert_eq!(json["params"]["taps"][1]["pan"], 0.5);
+
+        let mut restored = Delay::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.params().taps, taps);
+
+        // Params without taps load as a single delay, and don't write them
+        let params: DelayParams =
+            serde_json::from_value(serde_json::to_value(DelayParams::default()).unwrap()).unwrap();
+        assert!(params.taps.is_empty());
+        assert!(serde_json::to_value(&params).unwrap().get("taps").is_none());
+
+        let bad =
+            serde_json::json!({"params": {"taps": [{"time_ms": 3000.0, "gain": 0.5, "pan": 0.0}]}});
+        assert!(restored.from_json(&bad).is_err());
+        let bad = serde_json::json!({"params": {"taps": [{"time_ms": 100.0}]}});
+        assert!(restored.from_json(&bad).is_err());
+    }
 }
@@ -1,9 +1,67 @@
 //! Effect trait and types (spec §4.1)
 
 use super::AudioBuffer;
-use crate::error::Result;
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 
+/// Level, relative to the first output, at which an effect's tail is
+/// considered to have died away
+pub const TAIL_DECAY_DB: f32 = -60.0;
+
+/// Passes through a feedback loop with gain `loop_gain` before the signal
+/// has fallen by [`TAIL_DECAY_DB`]
+///
+/// Zero for no feedback, infinite for a loop that never decays.
+pub(crate) fn repeats_to_decay(loop_gain: f32) -> f32 {
+    let loop_gain = loop_gain.abs();
+    if loop_gain == 0.0 {
+        0.0
+    } else if loop_gain >= 1.0 {
+        f32::INFINITY
+    } else {
+        (TAIL_DECAY_DB / 20.0 * std::f32::consts::LN_10) / loop_gain.ln()
+    }
+}
+
+/// How an effect's dry and wet levels turn into gains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixMode {
+    /// Levels are used as gains directly
+    #[default]
+    Linear,
+    /// Levels follow a sine law, so a crossfade (dry = 1 - wet) keeps the
+    /// combined power of uncorrelated dry and wet signals constant:
+    /// 50/50 plays both at -3 dB instead of -6 dB
+    EqualPower,
+}
+
+impl MixMode {
+    /// Gains for the dry and wet signals
+    pub fn gains(self, dry_level: f32, wet_level: f32) -> (f32, f32) {
+        match self {
+            MixMode::Linear => (dry_level, wet_level),
+            MixMode::EqualPower => {
+                let law = |level: f32| (level.clamp(0.0, 1.0) * std::f32::consts::FRAC_PI_2).sin();
+                (law(dry_level), law(wet_level))
+            }
+        }
+    }
+
+    /// Spec for an effect's `mix_mode` parameter
+    pub(crate) fn param_spec() -> ParamSpec {
+        ParamSpec::options("mix_mode", &["linear", "equal_power"], "linear")
+    }
+
+    /// Parse from a serialized value ("linear" or "equal_power")
+    pub(crate) fn from_json(value: &serde_json::Value) -> Result<Self> {
+        serde_json::from_value(value.clone()).map_err(|e| NuevaError::SerializationError {
+            details: format!("mix_mode: {}", e),
+        })
+    }
+}
+
 /// Result of processing an effect
 #[derive(Debug, Clone)]
 pub enum ProcessResult {
@@ -33,6 +91,60 @@ impl ProcessResult {
     }
 }
 
+/// Levels an effect measured over the most recent block it processed
+///
+/// Levels are sample peaks in dBFS, negative infinity for silence. Gain
+/// reduction is in dB (0 or negative) and only dynamics effects report it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectMeter {
+    /// Peak level going in
+    pub input_db: f32,
+    /// Peak level coming out
+    pub output_db: f32,
+    /// Gain reduction at the end of the block, for dynamics effects
+    pub gain_reduction_db: Option<f32>,
+}
+
+impl EffectMeter {
+    /// Meter a block from its peak levels before and after processing
+    pub fn new(input_db: f32, output_db: f32) -> Self {
+        Self {
+            input_db,
+            output_db,
+            gain_reduction_db: None,
+        }
+    }
+
+    /// Add a gain reduction reading
+    pub fn with_gain_reduction(mut self, gain_reduction_db: f32) -> Self {
+        self.gain_reduction_db = Some(gain_reduction_db);
+        self
+    }
+
+    /// Peak level of a buffer across all channels, in dBFS
+    pub fn peak_db(buffer: &AudioBuffer) -> f32 {
+        (0..buffer.num_channels())
+            .map(|channel| buffer.peak_db(channel) as f32)
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// One-line level summary, e.g. "in -1.2 dBFS, out -3.0 dBFS, GR -1.8 dB"
+    pub fn summary(&self) -> String {
+        let level = |db: f32| {
+            if db.is_finite() {
+                format!("{:.1} dBFS", db)
+            } else {
+                "silent".to_string()
+            }
+        };
+        let mut summary = format!("in {}, out {}", level(self.input_db), level(self.output_db));
+        if let Some(gr) = self.gain_reduction_db {
+            summary.push_str(&format!(", GR {:.1} dB", gr));
+        }
+        summary
+    }
+}
+
 /// Metadata about an effect type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EffectMetadata {
@@ -53,6 +165,17 @@ pub trait Effect: Send + Sync {
     /// Process audio in-place
     fn process(&mut self, buffer: &mut AudioBuffer);
 
+    /// Process `input` into `output`, leaving `input` untouched
+    ///
+    /// `output` takes the input's shape (its old contents are discarded but
+    /// its allocation is reused) and ends up exactly as if `input` had been
+    /// cloned and passed to `process`. The default does just that; effects
+    /// that can write straight into `output` override it to skip the copy.
+    fn process_to(&mut self, input: &AudioBuffer, output: &mut AudioBuffer) {
+        output.copy_from(input);
+        self.process(output);
+    }
+
     /// Prepare the effect for processing at the given sample rate and block size
     fn prepare(&mut self, sample_rate: f64, samples_per_block: usize);
 
@@ -63,6 +186,7 @@ pub trait Effect: Send + Sync {
     fn to_json(&self) -> Result<serde_json::Value>;
 
     /// Deserialize effect state from JSON
+    #[allow(clippy::wrong_self_convention)]
     fn from_json(&mut self, json: &serde_json::Value) -> Result<()>;
 
     /// Get effect type identifier (kebab-case per spec)
@@ -86,6 +210,123 @@ pub trait Effect: Send + Sync {
     /// Set the unique instance ID
     fn set_id(&mut self, id: String);
 
+    /// Parameters a UI can offer for this effect
+    ///
+    /// Names are the keys of the effect's serialized parameters; values
+    /// inside arrays use dotted paths (`bands.0.gain_db`). Ranges and
+    /// defaults match what `from_json` accepts.
+    fn param_specs(&self) -> Vec<ParamSpec>;
+
+    /// Set one parameter by name
+    ///
+    /// The value is checked against [`Effect::param_specs`], then applied
+    /// through the serialized state so the effect's own validation runs.
+    fn set_param(&mut self, name: &str, value: serde_json::Value) -> Result<()> {
+        let spec = self
+            .param_specs()
+            .into_iter()
+            .find(|spec| spec.name == name)
+            .ok_or_else(|| NuevaError::InvalidParameter {
+                param: name.to_string(),
+                value: value.to_string(),
+                expected: format!("a parameter of {}", self.effect_type()),
+            })?;
+        spec.validate(&value)?;
+
+        let mut json = self.to_json()?;
+        let slot = param_slot(&mut json, name).ok_or_else(|| NuevaError::SerializationError {
+            details: format!(
+                "{} has no serialized value for {}",
+                self.effect_type(),
+                name
+            ),
+        })?;
+        *slot = value;
+        self.from_json(&json)
+    }
+
+    /// Set several parameters at once, all or nothing
+    ///
+    /// `params` maps parameter names (as for [`Effect::set_param`]) to
+    /// values. Every value is checked before any is applied, and they are
+    /// then applied together through one `from_json`, so coefficients are
+    /// recomputed once. If anything fails, the effect is left exactly as
+    /// it was.
+    fn set_params_atomic(&mut self, params: &serde_json::Value) -> Result<()> {
+        let params = params
+            .as_object()
+            .ok_or_else(|| NuevaError::InvalidParameter {
+                param: "params".to_string(),
+                value: params.to_string(),
+                expected: "an object of parameter names to values".to_string(),
+            })?;
+        let specs = self.param_specs();
+        for (name, value) in params {
+            let spec = specs
+                .iter()
+                .find(|spec| spec.name == *name)
+                .ok_or_else(|| NuevaError::InvalidParameter {
+                    param: name.clone(),
+                    value: value.to_string(),
+                    expected: format!("a parameter of {}", self.effect_type()),
+                })?;
+            spec.validate(value)?;
+        }
+
+        let original = self.to_json()?;
+        let mut json = original.clone();
+        for (name, value) in params {
+            let slot =
+                param_slot(&mut json, name).ok_or_else(|| NuevaError::SerializationError {
+                    details: format!(
+                        "{} has no serialized value for {}",
+                        self.effect_type(),
+                        name
+                    ),
+                })?;
+            *slot = value.clone();
+        }
+        if let Err(error) = self.from_json(&json) {
+            // Undo anything the failed load changed before it gave up
+            self.from_json(&original)?;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Current value of one parameter, as serialized
+    fn get_param(&self, name: &str) -> Option<serde_json::Value> {
+        let mut json = self.to_json().ok()?;
+        param_slot(&mut json, name).map(serde_json::Value::take)
+    }
+
+    /// Samples of output that continue after the input ends
+    ///
+    /// Time-based effects (reverb, delay) ring on past their input; a
+    /// renderer appends this much silence so the tail isn't cut off. The
+    /// estimate runs to [`TAIL_DECAY_DB`] and is only valid after
+    /// `prepare`. Effects without a tail report 0.
+    fn tail_samples(&self) -> usize {
+        0
+    }
+
+    /// Samples by which the output lags the input
+    ///
+    /// Block-based effects (e.g. the pitch shifter's STFT) delay the
+    /// signal; hosts use this to line the output back up. Only valid
+    /// after `prepare`. Effects that process sample by sample report 0.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
+    /// Levels measured over the most recent processed block
+    ///
+    /// Effects without meters return `None`, as do metered effects that
+    /// have not processed anything since the last `reset`.
+    fn meter(&self) -> Option<EffectMeter> {
+        None
+    }
+
     /// Process with safety wrapper (spec §9.4)
     ///
     /// Validates output and rolls back if invalid.
@@ -94,21 +335,18 @@ pub trait Effect: Send + Sync {
             return ProcessResult::Success;
         }
 
-        // Create backup for rollback
-        let backup = buffer.create_copy();
-
-        // Process
-        self.process(buffer);
+        // Process into a separate buffer so the input survives for rollback
+        let mut output = AudioBuffer::new(0, 0, buffer.sample_rate());
+        self.process_to(buffer, &mut output);
 
-        // Validate output
-        if !buffer.is_valid() {
-            // Rollback
-            *buffer = backup;
+        // Validate output; on failure the input is left as it was
+        if !output.is_valid() {
             return ProcessResult::failure(format!(
                 "Effect '{}' produced invalid audio (NaN/Inf/extreme values)",
                 self.id()
             ));
         }
+        *buffer = output;
 
         // Check for clipping warning
         let clipping = buffer.clipping_ratio();
@@ -124,6 +362,29 @@ pub trait Effect: Send + Sync {
     }
 }
 
+/// The value a parameter name refers to in an effect's serialized state
+///
+/// Looks inside `"params"` when the effect nests its parameters there,
+/// and follows dotted paths through objects and arrays.
+pub(crate) fn param_slot<'a>(
+    json: &'a mut serde_json::Value,
+    name: &str,
+) -> Option<&'a mut serde_json::Value> {
+    let nested = json.get("params").is_some_and(|p| p.is_object());
+    let mut slot = if nested {
+        json.get_mut("params")?
+    } else {
+        json
+    };
+    for key in name.split('.') {
+        slot = match slot {
+            serde_json::Value::Array(items) => items.get_mut(key.parse::<usize>().ok()?)?,
+            other => other.get_mut(key)?,
+        };
+    }
+    Some(slot)
+}
+
 /// Helper to generate unique effect IDs
 #[allow(dead_code)]
 pub fn generate_effect_id(effect_type: &str, index: usize) -> String {
@@ -141,8 +402,44 @@ mod tests {
         assert!(!ProcessResult::failure("test").is_success());
     }
 
+    #[test]
+    fn test_repeats_to_decay() {
+        assert_eq!(repeats_to_decay(0.0), 0.0);
+        assert_eq!(repeats_to_decay(1.0), f32::INFINITY);
+        // 0.5^10 is about -60 dB
+        assert!((repeats_to_decay(0.5) - 9.97).abs() < 0.01);
+    }
+
     #[test]
     fn test_generate_id() {
         assert_eq!(generate_effect_id("parametric-eq", 1), "parametric-eq-1");
     }
+
+    #[test]
+    fn test_param_slot_paths() {
+        let mut nested = serde_json::json!({"id": "x", "params": {"mix": 0.5}});
+        assert_eq!(param_slot(&mut nested, "mix").cloned(), Some(0.5.into()));
+        assert!(param_slot(&mut nested, "id").is_none());
+
+        let mut flat = serde_json::json!({"bands": [{"q": 1.0}], "gains_db": [0.0, 3.0]});
+        assert_eq!(
+            param_slot(&mut flat, "bands.0.q").cloned(),
+            Some(1.0.into())
+        );
+        assert_eq!(
+            param_slot(&mut flat, "gains_db.1").cloned(),
+            Some(3.0.into())
+        );
+        assert!(param_slot(&mut flat, "bands.1.q").is_none());
+        assert!(param_slot(&mut flat, "gains_db.x").is_none());
+    }
+
+    #[test]
+    fn test_mix_mode_gains() {
+        assert_eq!(MixMode::Linear.gains(0.3, 0.8), (0.3, 0.8));
+
+        let (dry, wet) = MixMode::EqualPower.gains(0.5, 0.5);
+        assert!((dry * dry + wet * wet - 1.0).abs() < 1e-6);
+        assert_eq!(MixMode::EqualPower.gains(1.0, 0.0), (1.0, 0.0));
+    }
 }
@@ -2,15 +2,31 @@
 //!
 //! Implements a multi-band parametric equalizer with cascaded biquad filters.
 //! Supports peak, shelf, and pass filters.
+//!
+//! With auto gain on, the output is scaled by the inverse of the band
+//! settings' average power gain over a pink spectrum (equal weight per
+//! octave, 20 Hz to 20 kHz), so switching the EQ in doesn't change the
+//! overall level.
+//!
+//! A single band can be soloed to audition it on its own, and
+//! [`ParametricEQ::frequency_response`] evaluates the combined curve
+//! straight from the biquad coefficients.
 
 use super::{AudioBuffer, Effect, EffectMetadata};
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
 /// Maximum number of EQ bands
 pub const MAX_BANDS: usize = 8;
 
+/// Largest boost or cut auto gain applies, in dB
+pub const MAX_AUTO_GAIN_DB: f64 = 24.0;
+
+/// Log-spaced frequencies the auto gain measurement averages over
+const AUTO_GAIN_POINTS: usize = 256;
+
 /// Filter type for EQ bands
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -26,13 +42,16 @@ pub enum FilterType {
     LowPass,
     /// Remove below frequency (high-pass filter)
     HighPass,
+    /// Raise highs by the gain and lower lows by the same amount,
+    /// pivoting around the frequency (negative gain darkens)
+    Tilt,
 }
 
 /// Biquad filter coefficients
 /// Transfer function: H(z) = (b0 + b1*z^-1 + b2*z^-2) / (a0 + a1*z^-1 + a2*z^-2)
 /// Normalized: all coefficients divided by a0
 #[derive(Debug, Clone, Copy, Default)]
-struct BiquadCoeffs {
+pub(super) struct BiquadCoeffs {
     b0: f64,
     b1: f64,
     b2: f64,
@@ -43,7 +62,7 @@ struct BiquadCoeffs {
 impl BiquadCoeffs {
     /// Calculate biquad coefficients using Audio EQ Cookbook formulas
     /// Reference: https://www.w3.org/2011/audio/audio-eq-cookbook.html
-    fn calculate(
+    pub(super) fn calculate(
         filter_type: FilterType,
         sample_rate: f64,
         frequency: f64,
@@ -62,6 +81,19 @@ impl BiquadCoeffs {
         // For shelf filters, calculate A (amplitude)
         let a = (10.0_f64).powf(gain_db / 40.0);
 
+        // High shelf for amplitude `a`
+        let high_shelf = |a: f64| {
+            let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+            (
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+                (a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                (a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+            )
+        };
+
         let (b0, b1, b2, a0, a1, a2) = match filter_type {
             FilterType::Peak => {
                 // Peaking EQ (constant-Q)
@@ -87,17 +119,15 @@ impl BiquadCoeffs {
                     (a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
                 )
             }
-            FilterType::HighShelf => {
-                // High shelf filter
-                let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
-                (
-                    a * ((a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
-                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
-                    a * ((a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
-                    (a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
-                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
-                    (a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
-                )
+            // High shelf filter
+            FilterType::HighShelf => high_shelf(a),
+            FilterType::Tilt => {
+                // A high shelf of twice the gain, pulled down by the gain:
+                // a low-shelf cut and high-shelf boost that meet at 0 dB
+                // at the pivot
+                let shelf = a * a;
+                let (b0, b1, b2, a0, a1, a2) = high_shelf(shelf);
+                (b0 / shelf, b1 / shelf, b2 / shelf, a0, a1, a2)
             }
             FilterType::LowPass => {
                 // Low-pass filter (Butterworth-style)
@@ -133,8 +163,35 @@ impl BiquadCoeffs {
         }
     }
 
+    /// Band-pass with 0 dB gain at `frequency` (cookbook constant peak gain)
+    pub(super) fn band_pass(sample_rate: f64, frequency: f64, q: f64) -> Self {
+        let freq = frequency.clamp(20.0, sample_rate / 2.0 - 1.0);
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let alpha = w0.sin() / (2.0 * q.clamp(0.1, 10.0));
+        let a0 = 1.0 + alpha;
+
+        Self {
+            b0: alpha / a0,
+            b1: 0.0,
+            b2: -alpha / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+
+    /// Squared magnitude response at normalized angular frequency `w`
+    pub(super) fn power_response(&self, w: f64) -> f64 {
+        let (c1, s1) = (w.cos(), w.sin());
+        let (c2, s2) = ((2.0 * w).cos(), (2.0 * w).sin());
+        let num_re = self.b0 + self.b1 * c1 + self.b2 * c2;
+        let num_im = -(self.b1 * s1 + self.b2 * s2);
+        let den_re = 1.0 + self.a1 * c1 + self.a2 * c2;
+        let den_im = -(self.a1 * s1 + self.a2 * s2);
+        (num_re * num_re + num_im * num_im) / (den_re * den_re + den_im * den_im)
+    }
+
     /// Check if coefficients represent a bypass (unity gain, no filtering)
-    fn is_bypass(&self) -> bool {
+    pub(super) fn is_bypass(&self) -> bool {
         (self.b0 - 1.0).abs() < 1e-10
             && self.b1.abs() < 1e-10
             && self.b2.abs() < 1e-10
@@ -145,7 +202,7 @@ impl BiquadCoeffs {
 
 /// Biquad filter state for one channel
 #[derive(Debug, Clone, Copy, Default)]
-struct BiquadState {
+pub(super) struct BiquadState {
     x1: f64, // x[n-1]
     x2: f64, // x[n-2]
     y1: f64, // y[n-1]
@@ -155,7 +212,7 @@ struct BiquadState {
 impl BiquadState {
     /// Process a single sample through the biquad filter
     /// Direct Form II implementation
-    fn process(&mut self, input: f64, coeffs: &BiquadCoeffs) -> f64 {
+    pub(super) fn process(&mut self, input: f64, coeffs: &BiquadCoeffs) -> f64 {
         let output = coeffs.b0 * input + coeffs.b1 * self.x1 + coeffs.b2 * self.x2
             - coeffs.a1 * self.y1
             - coeffs.a2 * self.y2;
@@ -170,7 +227,7 @@ impl BiquadState {
     }
 
     /// Reset filter state
-    fn reset(&mut self) {
+    pub(super) fn reset(&mut self) {
         self.x1 = 0.0;
         self.x2 = 0.0;
         self.y1 = 0.0;
@@ -233,6 +290,12 @@ impl EQBand {
         Self::new(frequency, gain_db, q, FilterType::HighShelf)
     }
 
+    /// Create a tilt band pivoting around `frequency`: +`gain_db` above
+    /// it and -`gain_db` below
+    pub fn tilt(frequency: f32, gain_db: f32, q: f32) -> Self {
+        Self::new(frequency, gain_db, q, FilterType::Tilt)
+    }
+
     /// Create a low-pass filter band
     pub fn low_pass(frequency: f32, q: f32) -> Self {
         Self::new(frequency, 0.0, q, FilterType::LowPass)
@@ -273,12 +336,13 @@ impl EQBand {
     }
 
     /// Check if this band should be bypassed (no effect on audio)
-    fn is_bypass(&self) -> bool {
+    pub(super) fn is_bypass(&self) -> bool {
         !self.enabled
             || match self.filter_type {
-                FilterType::Peak | FilterType::LowShelf | FilterType::HighShelf => {
-                    self.gain_db.abs() < 0.01
-                }
+                FilterType::Peak
+                | FilterType::LowShelf
+                | FilterType::HighShelf
+                | FilterType::Tilt => self.gain_db.abs() < 0.01,
                 FilterType::LowPass | FilterType::HighPass => false,
             }
     }
@@ -300,6 +364,12 @@ pub struct ParametricEQ {
     enabled: bool,
     /// EQ bands (max 8)
     bands: Vec<EQBand>,
+    /// Compensate the level change of the band settings
+    #[serde(default)]
+    auto_gain: bool,
+    /// Linear output gain applied by auto gain (not serialized)
+    #[serde(skip, default = "unity_gain")]
+    makeup_gain: f64,
     /// Sample rate (not serialized)
     #[serde(skip)]
     sample_rate: f64,
@@ -312,6 +382,31 @@ pub struct ParametricEQ {
     /// Whether coefficients need recalculation (not serialized)
     #[serde(skip)]
     coeffs_dirty: bool,
+    /// Band being auditioned on its own (not serialized)
+    #[serde(skip)]
+    solo: Option<usize>,
+}
+
+fn unity_gain() -> f64 {
+    1.0
+}
+
+/// Linear gain that undoes the bands' average power change over a pink
+/// spectrum, limited to [`MAX_AUTO_GAIN_DB`]
+fn measure_makeup_gain(coeffs: &[BiquadCoeffs], sample_rate: f64) -> f64 {
+    let low = 20.0_f64;
+    let high = 20000.0_f64.min(sample_rate * 0.45);
+    let mean_power = (0..AUTO_GAIN_POINTS)
+        .map(|i| {
+            let t = i as f64 / (AUTO_GAIN_POINTS - 1) as f64;
+            let w = 2.0 * PI * low * (high / low).powf(t) / sample_rate;
+            coeffs.iter().map(|c| c.power_response(w)).product::<f64>()
+        })
+        .sum::<f64>()
+        / AUTO_GAIN_POINTS as f64;
+
+    let makeup_db = (-10.0 * mean_power.log10()).clamp(-MAX_AUTO_GAIN_DB, MAX_AUTO_GAIN_DB);
+    10f64.powf(makeup_db / 20.0)
 }
 
 impl Default for ParametricEQ {
@@ -320,10 +415,13 @@ impl Default for ParametricEQ {
             id: String::new(),
             enabled: true,
             bands: Vec::new(),
+            auto_gain: false,
+            makeup_gain: 1.0,
             sample_rate: 48000.0,
             num_channels: 2,
             band_states: Vec::new(),
             coeffs_dirty: true,
+            solo: None,
         }
     }
 }
@@ -375,6 +473,11 @@ impl ParametricEQ {
     pub fn remove_band(&mut self, index: usize) -> Option<EQBand> {
         if index < self.bands.len() {
             self.coeffs_dirty = true;
+            self.solo = match self.solo {
+                Some(solo) if solo == index => None,
+                Some(solo) if solo > index => Some(solo - 1),
+                solo => solo,
+            };
             Some(self.bands.remove(index))
         } else {
             None
@@ -396,9 +499,98 @@ impl ParametricEQ {
     pub fn clear_bands(&mut self) {
         self.bands.clear();
         self.band_states.clear();
+        self.solo = None;
+        self.coeffs_dirty = true;
+    }
+
+    /// Process only the band at `index`, or all bands again with `None`
+    pub fn solo_band(&mut self, index: Option<usize>) -> Result<()> {
+        if let Some(index) = index.filter(|&i| i >= self.bands.len()) {
+            return Err(NuevaError::InvalidParameter {
+                param: "solo_band".to_string(),
+                value: index.to_string(),
+                expected: format!("band index below {}", self.bands.len()),
+            });
+        }
+        self.solo = index;
+        self.coeffs_dirty = true;
+        Ok(())
+    }
+
+    /// The soloed band, if any
+    pub fn soloed_band(&self) -> Option<usize> {
+        self.solo
+    }
+
+    /// Magnitude response in dB at each of `freqs` (Hz)
+    ///
+    /// Computed from the biquad coefficients of the current settings,
+    /// including solo and auto gain, so no audio has to be processed
+    /// first. A disabled EQ is flat.
+    pub fn frequency_response(&self, freqs: &[f32]) -> Vec<f32> {
+        if !self.enabled {
+            return vec![0.0; freqs.len()];
+        }
+
+        let coeffs: Vec<BiquadCoeffs> =
+            (0..self.bands.len()).map(|i| self.band_coeffs(i)).collect();
+        let makeup_db = if self.auto_gain {
+            20.0 * measure_makeup_gain(&coeffs, self.sample_rate).log10()
+        } else {
+            0.0
+        };
+
+        freqs
+            .iter()
+            .map(|&freq| {
+                let w = 2.0 * PI * freq as f64 / self.sample_rate;
+                let power: f64 = coeffs.iter().map(|c| c.power_response(w)).product();
+                (10.0 * power.log10() + makeup_db) as f32
+            })
+            .collect()
+    }
+
+    /// Whether auto gain is on
+    pub fn auto_gain(&self) -> bool {
+        self.auto_gain
+    }
+
+    /// Turn level-compensating output gain on or off
+    pub fn set_auto_gain(&mut self, auto_gain: bool) {
+        self.auto_gain = auto_gain;
         self.coeffs_dirty = true;
     }
 
+    /// Output gain auto gain currently applies, in dB (0 when off)
+    pub fn makeup_gain_db(&mut self) -> f64 {
+        self.update_coefficients();
+        20.0 * self.makeup_gain.log10()
+    }
+
+    /// Coefficients for the band at `index`, unity if it is bypassed or
+    /// another band is soloed
+    fn band_coeffs(&self, index: usize) -> BiquadCoeffs {
+        let band = &self.bands[index];
+        let muted = self.solo.is_some_and(|solo| solo != index);
+        if muted || band.is_bypass() {
+            BiquadCoeffs {
+                b0: 1.0,
+                b1: 0.0,
+                b2: 0.0,
+                a1: 0.0,
+                a2: 0.0,
+            }
+        } else {
+            BiquadCoeffs::calculate(
+                band.filter_type,
+                self.sample_rate,
+                band.frequency as f64,
+                band.gain_db as f64,
+                band.q as f64,
+            )
+        }
+    }
+
     /// Update filter coefficients if needed
     fn update_coefficients(&mut self) {
         if !self.coeffs_dirty {
@@ -409,33 +601,20 @@ impl ParametricEQ {
         self.band_states
             .resize_with(self.bands.len(), BandState::default);
 
-        for (i, band) in self.bands.iter().enumerate() {
+        for i in 0..self.bands.len() {
             // Resize channel states
             self.band_states[i]
                 .states
                 .resize_with(self.num_channels, BiquadState::default);
-
-            // Calculate coefficients
-            if band.is_bypass() {
-                // Create unity/bypass coefficients
-                self.band_states[i].coeffs = BiquadCoeffs {
-                    b0: 1.0,
-                    b1: 0.0,
-                    b2: 0.0,
-                    a1: 0.0,
-                    a2: 0.0,
-                };
-            } else {
-                self.band_states[i].coeffs = BiquadCoeffs::calculate(
-                    band.filter_type,
-                    self.sample_rate,
-                    band.frequency as f64,
-                    band.gain_db as f64,
-                    band.q as f64,
-                );
-            }
+            self.band_states[i].coeffs = self.band_coeffs(i);
         }
 
+        self.makeup_gain = if self.auto_gain {
+            let coeffs: Vec<BiquadCoeffs> = self.band_states.iter().map(|b| b.coeffs).collect();
+            measure_makeup_gain(&coeffs, self.sample_rate)
+        } else {
+            1.0
+        };
         self.coeffs_dirty = false;
     }
 
@@ -451,7 +630,7 @@ impl ParametricEQ {
             }
         }
 
-        output as f32
+        (output * self.makeup_gain) as f32
     }
 }
 
@@ -511,6 +690,9 @@ impl Effect for ParametricEQ {
         self.id = deserialized.id;
         self.enabled = deserialized.enabled;
         self.bands = deserialized.bands;
+        self.auto_gain = deserialized.auto_gain;
+        // A solo is kept across loads only while its band still exists
+        self.solo = self.solo.filter(|&solo| solo < self.bands.len());
         self.coeffs_dirty = true;
 
         Ok(())
@@ -548,6 +730,40 @@ impl Effect for ParametricEQ {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        let defaults = EQBand::default();
+        (0..self.bands.len())
+            .flat_map(|i| {
+                [
+                    ParamSpec::float(
+                        &format!("bands.{}.frequency", i),
+                        20.0,
+                        20000.0,
+                        defaults.frequency,
+                    )
+                    .with_unit("Hz"),
+                    ParamSpec::float(&format!("bands.{}.gain_db", i), -24.0, 24.0, 0.0)
+                        .with_unit("dB"),
+                    ParamSpec::float(&format!("bands.{}.q", i), 0.1, 10.0, defaults.q),
+                    ParamSpec::options(
+                        &format!("bands.{}.filter_type", i),
+                        &[
+                            "peak",
+                            "low_shelf",
+                            "high_shelf",
+                            "low_pass",
+                            "high_pass",
+                            "tilt",
+                        ],
+                        "peak",
+                    ),
+                    ParamSpec::boolean(&format!("bands.{}.enabled", i), true),
+                ]
+            })
+            .chain([ParamSpec::boolean("auto_gain", false)])
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -578,6 +794,106 @@ mod tests {
         (sum_sq / buffer.num_samples() as f64).sqrt()
     }
 
+    /// Pink-ish noise from Paul Kellet's filter over an LCG, high-passed
+    /// at 20 Hz like any real program material
+    fn pink_noise(num_samples: usize, sample_rate: f64) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(1, num_samples, sample_rate);
+        let mut seed = 0x1234_5678u32;
+        let mut b = [0.0f64; 7];
+        for i in 0..num_samples {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let white = (seed >> 8) as f64 / (1u32 << 24) as f64 - 0.5;
+            b[0] = 0.99886 * b[0] + white * 0.0555179;
+            b[1] = 0.99332 * b[1] + white * 0.0750759;
+            b[2] = 0.96900 * b[2] + white * 0.1538520;
+            b[3] = 0.86650 * b[3] + white * 0.3104856;
+            b[4] = 0.55000 * b[4] + white * 0.5329522;
+            b[5] = -0.7616 * b[5] - white * 0.0168980;
+            let pink = b[..6].iter().sum::<f64>() + b[6] + white * 0.5362;
+            b[6] = white * 0.115926;
+            buffer.set(i, 0, (pink * 0.1) as f32);
+        }
+        let mut highpass = ParametricEQ::with_bands(vec![EQBand::high_pass(20.0, 0.7)]).unwrap();
+        highpass.prepare(sample_rate, num_samples);
+        highpass.process(&mut buffer);
+        buffer
+    }
+
+    fn level_change_db(eq: &mut ParametricEQ) -> f64 {
+        let dry = pink_noise(96000, 48000.0);
+        let mut wet = dry.clone();
+        eq.reset();
+        eq.process(&mut wet);
+        // Skip the filter settling time
+        let tail = |b: &AudioBuffer| {
+            let samples = &b.samples()[4800..];
+            (samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt()
+        };
+        20.0 * (tail(&wet) / tail(&dry)).log10()
+    }
+
+    #[test]
+    fn test_auto_gain_preserves_level() {
+        let settings = [
+            vec![EQBand::peak(1000.0, 12.0, 0.7)],
+            vec![
+                EQBand::low_shelf(200.0, 12.0, 0.7),
+                EQBand::high_shelf(6000.0, 9.0, 0.7),
+            ],
+            vec![
+                EQBand::peak(300.0, -12.0, 1.0),
+                EQBand::peak(3000.0, -8.0, 2.0),
+            ],
+            vec![
+                EQBand::high_shelf(4000.0, -15.0, 0.7),
+                EQBand::peak(120.0, -6.0, 1.5),
+            ],
+        ];
+        for bands in settings {
+            let mut eq = ParametricEQ::with_bands(bands.clone()).unwrap();
+            eq.prepare(48000.0, 512);
+            let raw = level_change_db(&mut eq);
+
+            eq.set_auto_gain(true);
+            let compensated = level_change_db(&mut eq);
+            assert!(raw.abs() > 1.5, "{:?}: raw change {:.2} dB", bands, raw);
+            assert!(
+                compensated.abs() < 1.0,
+                "{:?}: compensated change {:.2} dB",
+                bands,
+                compensated
+            );
+
+            eq.set_auto_gain(false);
+            assert!((level_change_db(&mut eq) - raw).abs() < 1e-9);
+            assert_eq!(eq.makeup_gain_db(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_auto_gain_follows_band_changes() {
+        let mut eq = ParametricEQ::with_bands(vec![EQBand::peak(1000.0, 6.0, 1.0)]).unwrap();
+        eq.prepare(48000.0, 512);
+        eq.set_auto_gain(true);
+        let small = eq.makeup_gain_db();
+        assert!(small < 0.0);
+
+        eq.band_mut(0).unwrap().gain_db = 18.0;
+        let large = eq.makeup_gain_db();
+        assert!(large < small - 1.0, "{} vs {}", large, small);
+        assert!(level_change_db(&mut eq).abs() < 1.0);
+
+        eq.band_mut(0).unwrap().gain_db = -18.0;
+        assert!(eq.makeup_gain_db() > 0.0);
+        assert!(level_change_db(&mut eq).abs() < 1.0);
+
+        // Extreme settings are only partly made up
+        eq.clear_bands();
+        eq.add_band(EQBand::low_shelf(20000.0, 24.0, 0.7)).unwrap();
+        eq.add_band(EQBand::low_shelf(20000.0, 24.0, 0.7)).unwrap();
+        assert_eq!(eq.makeup_gain_db(), -MAX_AUTO_GAIN_DB);
+    }
+
     #[test]
     fn test_band_validation() {
         // Valid band
@@ -924,12 +1240,58 @@ mod tests {
         assert!(!channels_equal, "Stereo channels should remain independent");
     }
 
+    #[test]
+    fn test_tilt_pivots_around_frequency() {
+        let rms_gain = |band: EQBand, frequency: f64| {
+            let mut eq = ParametricEQ::with_bands(vec![band]).unwrap();
+            eq.prepare(48000.0, 512);
+            let mut buffer = create_sine_buffer(frequency, 48000.0, 0.2);
+            let before = calculate_rms(&buffer, 0);
+            eq.process(&mut buffer);
+            20.0 * (calculate_rms(&buffer, 0) / before).log10()
+        };
+
+        for frequency in [50.0, 1000.0, 15000.0] {
+            assert!(rms_gain(EQBand::tilt(1000.0, 0.0, 0.7), frequency).abs() < 0.01);
+        }
+
+        let bright = EQBand::tilt(1000.0, 6.0, 0.7);
+        let (low, pivot, high) = (
+            rms_gain(bright.clone(), 50.0),
+            rms_gain(bright.clone(), 1000.0),
+            rms_gain(bright, 15000.0),
+        );
+        assert!((low + 6.0).abs() < 0.5, "LF {:.2} dB", low);
+        assert!(pivot.abs() < 0.1, "pivot {:.2} dB", pivot);
+        assert!((high - 6.0).abs() < 0.5, "HF {:.2} dB", high);
+
+        // Negative gain darkens, mirroring the curve
+        let dark = EQBand::tilt(1000.0, -6.0, 0.7);
+        assert!((rms_gain(dark.clone(), 50.0) - 6.0).abs() < 0.5);
+        assert!((rms_gain(dark, 15000.0) + 6.0).abs() < 0.5);
+
+        // Close to a separate low-shelf cut and high-shelf boost
+        let mut tilt = ParametricEQ::with_bands(vec![EQBand::tilt(1000.0, 4.0, 0.7)]).unwrap();
+        let shelves = ParametricEQ::with_bands(vec![
+            EQBand::low_shelf(1000.0, -4.0, 0.7),
+            EQBand::high_shelf(1000.0, 4.0, 0.7),
+        ])
+        .unwrap();
+        let freqs = [30.0, 300.0, 1000.0, 3000.0, 18000.0];
+        let expected = shelves.frequency_response(&freqs);
+        tilt.prepare(48000.0, 512);
+        for (got, want) in tilt.frequency_response(&freqs).iter().zip(&expected) {
+            assert!((got - want).abs() < 1.0, "{} vs {}", got, want);
+        }
+    }
+
     #[test]
     fn test_serialization() {
         let mut eq = ParametricEQ::new();
         eq.set_id("test-eq-1".to_string());
         eq.add_band(EQBand::peak(1000.0, 6.0, 1.0)).unwrap();
         eq.add_band(EQBand::low_shelf(200.0, 3.0, 0.7)).unwrap();
+        eq.add_band(EQBand::tilt(800.0, -2.0, 0.7)).unwrap();
 
         // Serialize
         let json = eq.to_json().expect("Serialization should succeed");
@@ -940,10 +1302,20 @@ mod tests {
             .expect("Deserialization should succeed");
 
         assert_eq!(eq2.id(), "test-eq-1");
-        assert_eq!(eq2.bands().len(), 2);
+        assert_eq!(eq2.bands().len(), 3);
         assert_eq!(eq2.bands()[0].frequency, 1000.0);
         assert_eq!(eq2.bands()[0].gain_db, 6.0);
         assert_eq!(eq2.bands()[1].filter_type, FilterType::LowShelf);
+        assert_eq!(json["bands"][2]["filter_type"], "tilt");
+        assert_eq!(eq2.bands()[2].filter_type, FilterType::Tilt);
+        assert_eq!(eq2.bands()[2].gain_db, -2.0);
+        assert!(!eq2.auto_gain());
+
+        eq.set_auto_gain(true);
+        let json = eq.to_json().unwrap();
+        assert_eq!(json["auto_gain"], true);
+        eq2.from_json(&json).unwrap();
+        assert!(eq2.auto_gain());
     }
 
     #[test]
@@ -1095,4 +1467,171 @@ mod tests {
             gain_ratio
         );
     }
+
+    #[test]
+    fn test_frequency_response_peak_band() {
+        // Not prepared and never processed: the default 48 kHz applies
+        let eq = ParametricEQ::with_bands(vec![EQBand::peak(1000.0, 6.0, 1.0)]).unwrap();
+        let response = eq.frequency_response(&[20.0, 1000.0, 20000.0]);
+
+        assert!((response[1] - 6.0).abs() < 0.01, "center: {}", response[1]);
+        assert!(response[0].abs() < 0.1, "20 Hz: {}", response[0]);
+        assert!(response[2].abs() < 0.1, "20 kHz: {}", response[2]);
+    }
+
+    #[test]
+    fn test_frequency_response_known_filters() {
+        // Butterworth (Q = 1/sqrt(2)) low-pass is 3.01 dB down at cutoff
+        let eq = ParametricEQ::with_bands(vec![EQBand::low_pass(
+            1000.0,
+            std::f32::consts::FRAC_1_SQRT_2,
+        )])
+        .unwrap();
+        let response = eq.frequency_response(&[1000.0, 10000.0]);
+        assert!((response[0] + 3.01).abs() < 0.02, "cutoff: {}", response[0]);
+        // 12 dB/octave: more than 36 dB down over 3.3 octaves
+        assert!(response[1] < -36.0, "10 kHz: {}", response[1]);
+
+        // Cookbook shelves sit at half their gain at the corner frequency
+        let eq = ParametricEQ::with_bands(vec![EQBand::low_shelf(200.0, 12.0, 0.707)]).unwrap();
+        let response = eq.frequency_response(&[20.0, 200.0, 10000.0]);
+        assert!((response[0] - 12.0).abs() < 0.5);
+        assert!((response[1] - 6.0).abs() < 0.05, "corner: {}", response[1]);
+        assert!(response[2].abs() < 0.05);
+
+        // Bands in series add in dB
+        let eq = ParametricEQ::with_bands(vec![
+            EQBand::peak(1000.0, 6.0, 1.0),
+            EQBand::peak(1000.0, -4.0, 1.0),
+        ])
+        .unwrap();
+        assert!((eq.frequency_response(&[1000.0])[0] - 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_frequency_response_matches_processing() {
+        let mut eq = ParametricEQ::with_bands(vec![
+            EQBand::high_pass(80.0, 0.7),
+            EQBand::peak(2500.0, -7.0, 2.0),
+        ])
+        .unwrap();
+        eq.prepare(48000.0, 512);
+
+        for freq in [100.0, 2500.0, 5000.0] {
+            let predicted = eq.frequency_response(&[freq as f32])[0] as f64;
+            let mut buffer = create_sine_buffer(freq, 48000.0, 0.5);
+            let dry = calculate_rms(&buffer, 0);
+            eq.reset();
+            eq.process(&mut buffer);
+            let measured = 20.0 * (calculate_rms(&buffer, 0) / dry).log10();
+            assert!(
+                (measured - predicted).abs() < 0.2,
+                "{} Hz: measured {:.2} dB, predicted {:.2} dB",
+                freq,
+                measured,
+                predicted
+            );
+        }
+    }
+
+    #[test]
+    fn test_solo_band() {
+        let mut eq = ParametricEQ::with_bands(vec![
+            EQBand::low_shelf(100.0, 6.0, 0.7),
+            EQBand::peak(1000.0, 9.0, 1.0),
+            EQBand::high_shelf(8000.0, -6.0, 0.7),
+        ])
+        .unwrap();
+        eq.prepare(48000.0, 512);
+        let freqs = [50.0, 1000.0, 15000.0];
+        let all_bands = eq.frequency_response(&freqs);
+
+        eq.solo_band(Some(1)).unwrap();
+        assert_eq!(eq.soloed_band(), Some(1));
+        let alone = ParametricEQ::with_bands(vec![EQBand::peak(1000.0, 9.0, 1.0)]).unwrap();
+        assert_eq!(
+            eq.frequency_response(&freqs),
+            alone.frequency_response(&freqs)
+        );
+
+        // Processing follows the solo too
+        let mut buffer = create_sine_buffer(50.0, 48000.0, 0.2);
+        let dry = calculate_rms(&buffer, 0);
+        eq.process(&mut buffer);
+        assert!((calculate_rms(&buffer, 0) / dry - 1.0).abs() < 0.05);
+
+        eq.solo_band(None).unwrap();
+        assert_eq!(eq.soloed_band(), None);
+        assert_eq!(eq.frequency_response(&freqs), all_bands);
+
+        assert!(eq.solo_band(Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_remove_band_keeps_solo() {
+        let mut eq = ParametricEQ::with_bands(vec![
+            EQBand::peak(100.0, 3.0, 1.0),
+            EQBand::peak(1000.0, 6.0, 1.0),
+        ])
+        .unwrap();
+        eq.solo_band(Some(1)).unwrap();
+
+        eq.remove_band(0);
+        assert_eq!(eq.soloed_band(), Some(0));
+        eq.remove_band(0);
+        assert_eq!(eq.soloed_band(), None);
+    }
+
+    #[test]
+    fn test_from_json_clears_solo_past_last_band() {
+        let mut eq = ParametricEQ::with_bands(vec![
+            EQBand::peak(100.0, 3.0, 1.0),
+            EQBand::peak(1000.0, 6.0, 1.0),
+        ])
+        .unwrap();
+        let one_band = ParametricEQ::with_bands(vec![EQBand::peak(100.0, 3.0, 1.0)])
+            .unwrap()
+            .to_json()
+            .unwrap();
+
+        eq.solo_band(Some(1)).unwrap();
+        eq.from_json(&one_band).unwrap();
+        assert_eq!(eq.soloed_band(), None);
+        // The remaining band is audible again
+        assert!((eq.frequency_response(&[100.0])[0] - 3.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_set_params_atomic() {
+        let mut eq = ParametricEQ::with_bands(vec![
+            EQBand::new(200.0, 0.0, 0.707, FilterType::LowShelf),
+            EQBand::new(3000.0, 0.0, 1.0, FilterType::Peak),
+        ])
+        .unwrap();
+        let before = eq.to_json().unwrap();
+
+        // One bad value and nothing changes, the valid ones included
+        let batch = serde_json::json!({
+            "bands.0.gain_db": 6.0,
+            "bands.1.frequency": 50000.0,
+        });
+        assert!(eq.set_params_atomic(&batch).is_err());
+        assert_eq!(eq.to_json().unwrap(), before);
+        let unknown = serde_json::json!({ "bands.0.gain_db": 6.0, "bands.5.q": 1.0 });
+        assert!(eq.set_params_atomic(&unknown).is_err());
+        assert!(eq.set_params_atomic(&serde_json::json!(6.0)).is_err());
+        assert_eq!(eq.to_json().unwrap(), before);
+
+        let batch = serde_json::json!({
+            "bands.0.gain_db": 6.0,
+            "bands.1.frequency": 5000.0,
+            "bands.1.gain_db": -3.0,
+            "auto_gain": true,
+        });
+        eq.set_params_atomic(&batch).unwrap();
+        assert_eq!(eq.bands()[0].gain_db, 6.0);
+        assert_eq!(eq.bands()[1].frequency, 5000.0);
+        assert_eq!(eq.bands()[1].gain_db, -3.0);
+        assert_eq!(eq.get_param("auto_gain"), Some(serde_json::json!(true)));
+    }
 }
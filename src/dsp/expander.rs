@@ -0,0 +1,566 @@
+//! Expander effect
+//!
+//! Downward expansion, the inverse of compression: signals below the
+//! threshold are pushed further down by the expansion ratio, up to a
+//! maximum attenuation set by the range. Gentler than a gate, it lowers
+//! noise and bleed between phrases without chopping tails off.
+//!
+//! Uses the same linked peak detection and one-pole attack/release
+//! smoothing as the compressor, applied to the detected level.
+
+use super::{AudioBuffer, Effect, EffectMetadata, EffectMeter};
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+
+/// Expander parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpanderParams {
+    /// Threshold level in dB (-80 to 0 dB)
+    pub threshold_db: f32,
+    /// Expansion ratio (1.0 to 20.0, representing 1:1 to 1:20)
+    pub ratio: f32,
+    /// Attack time in milliseconds (0.1 to 100 ms)
+    pub attack_ms: f32,
+    /// Release time in milliseconds (10 to 1000 ms)
+    pub release_ms: f32,
+    /// Maximum attenuation in dB (-80 to 0 dB)
+    pub range_db: f32,
+}
+
+impl Default for ExpanderParams {
+    fn default() -> Self {
+        Self {
+            threshold_db: -40.0,
+            ratio: 2.0,
+            attack_ms: 1.0,
+            release_ms: 100.0,
+            range_db: -40.0,
+        }
+    }
+}
+
+impl ExpanderParams {
+    /// Validate parameters against their ranges
+    pub fn validate(&self) -> Result<()> {
+        if self.threshold_db < -80.0 || self.threshold_db > 0.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "threshold_db".to_string(),
+                value: self.threshold_db.to_string(),
+                expected: "-80 to 0 dB".to_string(),
+            });
+        }
+        if self.ratio < 1.0 || self.ratio > 20.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "ratio".to_string(),
+                value: self.ratio.to_string(),
+                expected: "1.0 to 20.0".to_string(),
+            });
+        }
+        if self.attack_ms < 0.1 || self.attack_ms > 100.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "attack_ms".to_string(),
+                value: self.attack_ms.to_string(),
+                expected: "0.1 to 100 ms".to_string(),
+            });
+        }
+        if self.release_ms < 10.0 || self.release_ms > 1000.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "release_ms".to_string(),
+                value: self.release_ms.to_string(),
+                expected: "10 to 1000 ms".to_string(),
+            });
+        }
+        if self.range_db < -80.0 || self.range_db > 0.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "range_db".to_string(),
+                value: self.range_db.to_string(),
+                expected: "-80 to 0 dB".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Clamp parameters to valid ranges
+    pub fn clamp(&mut self) {
+        self.threshold_db = self.threshold_db.clamp(-80.0, 0.0);
+        self.ratio = self.ratio.clamp(1.0, 20.0);
+        self.attack_ms = self.attack_ms.clamp(0.1, 100.0);
+        self.release_ms = self.release_ms.clamp(10.0, 1000.0);
+        self.range_db = self.range_db.clamp(-80.0, 0.0);
+    }
+}
+
+/// Downward expander
+///
+/// Feed-forward design with:
+/// - Linked peak envelope detection
+/// - Attack (level rising) / release (level falling) smoothing
+/// - Attenuation limited by `range_db`
+#[derive(Debug, Clone)]
+pub struct Expander {
+    /// Unique instance identifier
+    id: String,
+    /// Whether the effect is enabled
+    enabled: bool,
+    /// Expander parameters
+    params: ExpanderParams,
+    /// Sample rate in Hz
+    sample_rate: f64,
+    /// Attack coefficient for envelope smoothing
+    attack_coeff: f32,
+    /// Release coefficient for envelope smoothing
+    release_coeff: f32,
+    /// Detected level (linear), linked across channels
+    envelope: f32,
+    /// Gain applied to the last sample (linear), for metering
+    current_gain: f32,
+    /// Levels of the last processed block
+    meter: Option<EffectMeter>,
+}
+
+impl Expander {
+    /// Create a new expander with default parameters
+    pub fn new() -> Self {
+        let mut expander = Self {
+            id: String::new(),
+            enabled: true,
+            params: ExpanderParams::default(),
+            sample_rate: 44100.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            envelope: 0.0,
+            current_gain: 1.0,
+            meter: None,
+        };
+        expander.update_coefficients();
+        expander
+    }
+
+    /// Create a new expander with custom parameters
+    pub fn with_params(params: ExpanderParams) -> Self {
+        let mut expander = Self::new();
+        expander.set_params(params);
+        expander
+    }
+
+    /// Get the current parameters
+    pub fn params(&self) -> &ExpanderParams {
+        &self.params
+    }
+
+    /// Set the parameters (clamped to valid ranges)
+    pub fn set_params(&mut self, params: ExpanderParams) {
+        self.params = params;
+        self.params.clamp();
+        self.update_coefficients();
+    }
+
+    /// Set threshold in dB
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.params.threshold_db = threshold_db.clamp(-80.0, 0.0);
+    }
+
+    /// Set expansion ratio (1:1 to 1:20)
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.params.ratio = ratio.clamp(1.0, 20.0);
+    }
+
+    /// Set attack time in milliseconds
+    pub fn set_attack_ms(&mut self, attack_ms: f32) {
+        self.params.attack_ms = attack_ms.clamp(0.1, 100.0);
+        self.update_coefficients();
+    }
+
+    /// Set release time in milliseconds
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.params.release_ms = release_ms.clamp(10.0, 1000.0);
+        self.update_coefficients();
+    }
+
+    /// Set maximum attenuation in dB
+    pub fn set_range_db(&mut self, range_db: f32) {
+        self.params.range_db = range_db.clamp(-80.0, 0.0);
+    }
+
+    /// Get the current gain reduction in dB for metering
+    pub fn gain_reduction_db(&self) -> f32 {
+        Self::linear_to_db(self.current_gain)
+    }
+
+    /// Update attack/release coefficients based on sample rate and time constants
+    fn update_coefficients(&mut self) {
+        let attack_samples = (self.params.attack_ms / 1000.0) * self.sample_rate as f32;
+        let release_samples = (self.params.release_ms / 1000.0) * self.sample_rate as f32;
+
+        self.attack_coeff = if attack_samples > 0.0 {
+            (-1.0 / attack_samples).exp()
+        } else {
+            0.0
+        };
+
+        self.release_coeff = if release_samples > 0.0 {
+            (-1.0 / release_samples).exp()
+        } else {
+            0.0
+        };
+    }
+
+    /// Compute the gain change for a given level in dB
+    /// Returns 0 dB above threshold, otherwise a negative value
+    fn compute_gain_db(&self, level_db: f32) -> f32 {
+        let threshold = self.params.threshold_db;
+        if level_db >= threshold {
+            return 0.0;
+        }
+        // output = threshold + (level - threshold) * ratio
+        // gain = output - level
+        let gain = (level_db - threshold) * (self.params.ratio - 1.0);
+        gain.max(self.params.range_db)
+    }
+
+    /// Convert linear amplitude to dB
+    fn linear_to_db(linear: f32) -> f32 {
+        if linear > 0.0 {
+            20.0 * linear.log10()
+        } else {
+            -96.0 // Floor at -96 dB
+        }
+    }
+
+    /// Convert dB to linear amplitude
+    fn db_to_linear(db: f32) -> f32 {
+        10.0_f32.powf(db / 20.0)
+    }
+}
+
+impl Default for Expander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Expander {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled {
+            return;
+        }
+
+        let num_channels = buffer.num_channels();
+        let input_db = EffectMeter::peak_db(buffer);
+        for frame in buffer.samples_mut().chunks_mut(num_channels.max(1)) {
+            // Linked detection: the loudest channel drives all of them
+            let level = frame.iter().fold(0.0_f32, |max, s| max.max(s.abs()));
+
+            let coeff = if level > self.envelope {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            self.envelope = coeff * self.envelope + (1.0 - coeff) * level;
+
+            let gain_db = self.compute_gain_db(Self::linear_to_db(self.envelope));
+            self.current_gain = if gain_db == 0.0 {
+                1.0
+            } else {
+                Self::db_to_linear(gain_db)
+            };
+
+            for sample in frame.iter_mut() {
+                *sample *= self.current_gain;
+            }
+        }
+
+        self.meter = Some(
+            EffectMeter::new(input_db, EffectMeter::peak_db(buffer))
+                .with_gain_reduction(self.gain_reduction_db()),
+        );
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        self.sample_rate = sample_rate;
+        self.update_coefficients();
+    }
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.current_gain = 1.0;
+        self.meter = None;
+    }
+
+    fn meter(&self) -> Option<EffectMeter> {
+        self.meter
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        serde_json::to_value(&ExpanderState {
+            id: self.id.clone(),
+            enabled: self.enabled,
+            params: self.params.clone(),
+        })
+        .map_err(|e| NuevaError::SerializationError {
+            details: e.to_string(),
+        })
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        let state: ExpanderState =
+            serde_json::from_value(json.clone()).map_err(|e| NuevaError::SerializationError {
+                details: e.to_string(),
+            })?;
+
+        self.id = state.id;
+        self.enabled = state.enabled;
+        self.set_params(state.params);
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "expander"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Expander"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: "expander".to_string(),
+            display_name: "Expander".to_string(),
+            category: "dynamics".to_string(),
+            order_priority: 0, // Cleans up noise first, like the gate
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("threshold_db", -80.0, 0.0, -40.0).with_unit("dB"),
+            ParamSpec::float("ratio", 1.0, 20.0, 2.0),
+            ParamSpec::float("attack_ms", 0.1, 100.0, 1.0).with_unit("ms"),
+            ParamSpec::float("release_ms", 10.0, 1000.0, 100.0).with_unit("ms"),
+            ParamSpec::float("range_db", -80.0, 0.0, -40.0).with_unit("dB"),
+        ]
+    }
+}
+
+/// Serializable state for the expander
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExpanderState {
+    id: String,
+    enabled: bool,
+    params: ExpanderParams,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    /// Half a second of quiet noise followed by half a second of a loud tone
+    fn noise_then_tone() -> AudioBuffer {
+        let half = SAMPLE_RATE as usize / 2;
+        let mut buffer = AudioBuffer::new(2, half * 2, SAMPLE_RATE);
+        let mut seed: u32 = 12345;
+        for i in 0..half * 2 {
+            let sample = if i < half {
+                // Deterministic noise around -50 dBFS
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * 0.003
+            } else {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin()
+            };
+            buffer.set(i, 0, sample);
+            buffer.set(i, 1, sample);
+        }
+        buffer
+    }
+
+    fn rms_db(buffer: &AudioBuffer, range: std::ops::Range<usize>) -> f32 {
+        let len = range.len() as f32;
+        let power: f32 = range.map(|i| buffer.get(i, 0).unwrap().powi(2)).sum();
+        20.0 * (power / len).sqrt().log10()
+    }
+
+    fn expander(threshold_db: f32, ratio: f32, range_db: f32) -> Expander {
+        let mut expander = Expander::with_params(ExpanderParams {
+            threshold_db,
+            ratio,
+            range_db,
+            ..Default::default()
+        });
+        expander.prepare(SAMPLE_RATE, 512);
+        expander
+    }
+
+    #[test]
+    fn test_default_params_valid() {
+        let params = ExpanderParams::default();
+        assert!(params.validate().is_ok());
+        assert_eq!(params.threshold_db, -40.0);
+        assert_eq!(params.ratio, 2.0);
+    }
+
+    #[test]
+    fn test_parameter_validation_and_clamping() {
+        let mut params = ExpanderParams {
+            threshold_db: -100.0,
+            ratio: 0.5,
+            attack_ms: 0.0,
+            release_ms: 5000.0,
+            range_db: 6.0,
+        };
+        assert!(params.validate().is_err());
+
+        params.clamp();
+        assert!(params.validate().is_ok());
+        assert_eq!(params.threshold_db, -80.0);
+        assert_eq!(params.ratio, 1.0);
+        assert_eq!(params.attack_ms, 0.1);
+        assert_eq!(params.release_ms, 1000.0);
+        assert_eq!(params.range_db, 0.0);
+    }
+
+    #[test]
+    fn test_gain_computer() {
+        let exp = expander(-30.0, 3.0, -40.0);
+
+        // At or above threshold: unchanged
+        assert_eq!(exp.compute_gain_db(-30.0), 0.0);
+        assert_eq!(exp.compute_gain_db(-6.0), 0.0);
+
+        // 5 dB below threshold at 1:3 -> 10 dB more attenuation
+        assert!((exp.compute_gain_db(-35.0) + 10.0).abs() < 1e-4);
+
+        // Far below threshold: limited by the range
+        assert_eq!(exp.compute_gain_db(-90.0), -40.0);
+    }
+
+    #[test]
+    fn test_reduces_noise_preserves_tone() {
+        let mut exp = expander(-30.0, 4.0, -40.0);
+        let original = noise_then_tone();
+        let mut buffer = original.clone();
+        exp.process(&mut buffer);
+
+        // Skip the settling time at the start of each section
+        let half = SAMPLE_RATE as usize / 2;
+        let noise = 4800..half;
+        let tone = half + 4800..half * 2;
+
+        let noise_change = rms_db(&buffer, noise.clone()) - rms_db(&original, noise);
+        assert!(
+            noise_change < -30.0,
+            "noise only reduced by {} dB",
+            noise_change
+        );
+
+        for i in tone {
+            assert_eq!(buffer.get(i, 0), original.get(i, 0));
+        }
+    }
+
+    #[test]
+    fn test_unity_ratio_is_passthrough() {
+        let mut exp = expander(-20.0, 1.0, -80.0);
+        let original = noise_then_tone();
+        let mut buffer = original.clone();
+        exp.process(&mut buffer);
+        assert_eq!(buffer.samples(), original.samples());
+    }
+
+    #[test]
+    fn test_reset_clears_envelope() {
+        let mut exp = expander(-30.0, 4.0, -40.0);
+        let mut loud = noise_then_tone();
+        exp.process(&mut loud);
+        exp.reset();
+        assert_eq!(exp.gain_reduction_db(), 0.0);
+
+        let mut first = noise_then_tone();
+        exp.process(&mut first);
+        let mut fresh = expander(-30.0, 4.0, -40.0);
+        let mut second = noise_then_tone();
+        fresh.process(&mut second);
+        assert_eq!(first.samples(), second.samples());
+    }
+
+    #[test]
+    fn test_meter_reports_gain_reduction() {
+        let mut exp = expander(-30.0, 4.0, -40.0);
+        assert!(exp.meter().is_none());
+
+        // Ends on the tone, above the threshold
+        let mut buffer = noise_then_tone();
+        exp.process(&mut buffer);
+        let meter = exp.meter().expect("meter after processing");
+        assert!((meter.input_db - meter.output_db).abs() < 0.01);
+        assert!(meter.gain_reduction_db.unwrap() > -0.1);
+
+        // Noise alone is pushed down once the envelope releases
+        let half = SAMPLE_RATE as usize / 2;
+        let mut noise = AudioBuffer::new(2, half, SAMPLE_RATE);
+        let source = noise_then_tone();
+        for i in 0..half {
+            noise.set(i, 0, source.get(i, 0).unwrap());
+            noise.set(i, 1, source.get(i, 1).unwrap());
+        }
+        exp.process(&mut noise);
+        let meter = exp.meter().unwrap();
+        assert!(meter.output_db < meter.input_db);
+        let gr = meter.gain_reduction_db.unwrap();
+        assert_eq!(gr, exp.gain_reduction_db());
+        assert!(gr < -30.0, "gain reduction {} dB", gr);
+
+        exp.reset();
+        assert!(exp.meter().is_none());
+    }
+
+    #[test]
+    fn test_bypassed() {
+        let mut exp = expander(-30.0, 4.0, -40.0);
+        exp.set_enabled(false);
+        let original = noise_then_tone();
+        let mut buffer = original.clone();
+        exp.process(&mut buffer);
+        assert_eq!(buffer.samples(), original.samples());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut exp = expander(-35.0, 3.0, -20.0);
+        exp.set_id("exp-1".to_string());
+        let json = exp.to_json().unwrap();
+
+        let mut restored = Expander::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "exp-1");
+        assert_eq!(restored.params().threshold_db, -35.0);
+        assert_eq!(restored.params().ratio, 3.0);
+        assert_eq!(restored.params().range_db, -20.0);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let exp = Expander::new();
+        assert_eq!(exp.effect_type(), "expander");
+        assert_eq!(exp.metadata().category, "dynamics");
+    }
+}
@@ -0,0 +1,386 @@
+//! Effect factory
+//!
+//! Builds effect instances from the type strings and flat parameter maps
+//! stored in a project's Layer 2 chain, and turns live effects back into
+//! that tagged form.
+
+use super::{
+    Compressor, Delay, Effect, Expander, GainEffect, Gate, GraphicEQ, Haas, Limiter, ParametricEQ,
+    PitchShifter, Reverb, RingMod, Saturation, Tremolo, Wah,
+};
+use crate::error::{NuevaError, Result};
+
+/// Canonical type names of every effect [`create_effect`] can build
+pub const EFFECT_TYPES: &[&str] = &[
+    "gain",
+    "parametric-eq",
+    "graphic-eq",
+    "compressor",
+    "gate",
+    "expander",
+    "limiter",
+    "reverb",
+    "delay",
+    "saturation",
+    "haas",
+    "ring-mod",
+    "tremolo",
+    "wah",
+    "pitch-shifter",
+];
+
+/// Create a default-configured effect for a type string
+///
+/// Accepts both the DSP names ("parametric-eq") and the project file
+/// names ("parametric_eq", "eq").
+pub fn create_effect(effect_type: &str) -> Option<Box<dyn Effect>> {
+    let effect: Box<dyn Effect> = match effect_type {
+        "gain" => Box::new(GainEffect::new()),
+        "eq" | "parametric-eq" | "parametric_eq" => Box::new(ParametricEQ::new()),
+        "graphic-eq" | "graphic_eq" => Box::new(GraphicEQ::new()),
+        "compressor" => Box::new(Compressor::new()),
+        "gate" => Box::new(Gate::new()),
+        "expander" => Box::new(Expander::new()),
+        "limiter" => Box::new(Limiter::new()),
+        "reverb" => Box::new(Reverb::new()),
+        "delay" => Box::new(Delay::new()),
+        "saturation" => Box::new(Saturation::new()),
+        "haas" => Box::new(Haas::new()),
+        "ring-mod" | "ring_mod" => Box::new(RingMod::new()),
+        "tremolo" => Box::new(Tremolo::new()),
+        "wah" | "auto-wah" | "auto_wah" => Box::new(Wah::new()),
+        "pitch-shifter" | "pitch_shifter" | "pitch-shift" => Box::new(PitchShifter::new()),
+        _ => return None,
+    };
+    Some(effect)
+}
+
+/// Build an effect from its stored type, id, enabled flag and parameters
+///
+/// Parameters missing from `params` keep their defaults.
+pub fn build_effect<'a>(
+    effect_type: &str,
+    id: &str,
+    enabled: bool,
+    params: impl IntoIterator<Item = (&'a String, &'a serde_json::Value)>,
+) -> Result<Box<dyn Effect>> {
+    let mut effect = create_effect(effect_type).ok_or_else(|| unknown_effect_type(effect_type))?;
+
+    // Effects serialize their parameters either under "params" or at the
+    // top level; overlay the stored values onto whichever shape this one uses
+    let mut json = effect.to_json()?;
+    let target = match json.get_mut("params") {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => json
+            .as_object_mut()
+            .ok_or_else(|| NuevaError::SerializationError {
+                details: format!("{} did not serialize to an object", effect_type),
+            })?,
+    };
+    for (key, value) in params {
+        target.insert(key.clone(), value.clone());
+    }
+
+    effect.from_json(&json)?;
+    effect.set_id(id.to_string());
+    effect.set_enabled(enabled);
+    Ok(effect)
+}
+
+/// Serialize an effect as `{"effect_type", "id", "enabled", "params"}`
+///
+/// `params` is the flat map [`build_effect`] takes, whichever shape the
+/// effect's own JSON uses.
+pub fn effect_to_json(effect: &dyn Effect) -> Result<serde_json::Value> {
+    let mut state = effect.to_json()?;
+    let params = match state.get_mut("params") {
+        Some(serde_json::Value::Object(map)) => std::mem::take(map),
+        _ => {
+            let mut map = match state {
+                serde_json::Value::Object(map) => map,
+                _ => {
+                    return Err(NuevaError::SerializationError {
+                        details: format!("{} did not serialize to an object", effect.effect_type()),
+                    })
+                }
+            };
+            for key in ["effect_type", "id", "enabled"] {
+                map.remove(key);
+            }
+            map
+        }
+    };
+
+    Ok(serde_json::json!({
+        "effect_type": effect.effect_type(),
+        "id": effect.id(),
+        "enabled": effect.is_enabled(),
+        "params": params,
+    }))
+}
+
+/// Rebuild an effect from the tagged form written by [`effect_to_json`]
+///
+/// `enabled` defaults to true and `params` to the effect's defaults.
+pub fn effect_from_json(json: &serde_json::Value) -> Result<Box<dyn Effect>> {
+    let effect_type = json
+        .get("effect_type")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| NuevaError::SerializationError {
+            details: "effect entry has no effect_type".to_string(),
+        })?;
+    let id = json.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+    let enabled = json
+        .get("enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let params = match json.get("params") {
+        None => serde_json::Map::new(),
+        Some(serde_json::Value::Object(map)) => map.clone(),
+        Some(other) => {
+            return Err(NuevaError::SerializationError {
+                details: format!("params of {} must be an object, got {}", id, other),
+            })
+        }
+    };
+    build_effect(effect_type, id, enabled, &params)
+}
+
+fn unknown_effect_type(effect_type: &str) -> NuevaError {
+    NuevaError::InvalidParameter {
+        param: "effect_type".to_string(),
+        value: effect_type.to_string(),
+        expected: format!("one of: {}", EFFECT_TYPES.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::effect::param_slot;
+    use crate::dsp::{AudioBuffer, EQBand};
+    use crate::neural::ParamType;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// A default effect; the parametric EQ gets a band so it has parameters
+    fn make(effect_type: &str) -> Box<dyn Effect> {
+        if effect_type == "parametric-eq" {
+            return Box::new(ParametricEQ::with_bands(vec![EQBand::default()]).unwrap());
+        }
+        create_effect(effect_type).unwrap()
+    }
+
+    /// Apply a value through the serialized state, bypassing the spec
+    fn apply_raw(effect: &mut dyn Effect, name: &str, value: serde_json::Value) -> Result<()> {
+        let mut state = effect.to_json()?;
+        *param_slot(&mut state, name).unwrap() = value;
+        effect.from_json(&state)
+    }
+
+    fn read_f64(effect: &dyn Effect, name: &str) -> f64 {
+        effect.get_param(name).and_then(|v| v.as_f64()).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-6 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_effect_types_resolve() {
+        for effect_type in EFFECT_TYPES {
+            assert_eq!(
+                create_effect(effect_type).unwrap().effect_type(),
+                *effect_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_param_specs_match_validation() {
+        for effect_type in EFFECT_TYPES {
+            let specs = make(effect_type).param_specs();
+            assert!(!specs.is_empty(), "{} declares no parameters", effect_type);
+
+            for spec in specs {
+                let name = spec.name.as_str();
+                let ctx = format!("{} {}", effect_type, name);
+
+                let current = make(effect_type)
+                    .get_param(name)
+                    .unwrap_or_else(|| panic!("{}: not serialized", ctx));
+                let default = spec.default.clone().unwrap();
+                match (current.as_f64(), default.as_f64()) {
+                    (Some(a), Some(b)) => assert!(close(a, b), "{}: default {} vs {}", ctx, a, b),
+                    _ => assert_eq!(current, default, "{}: default", ctx),
+                }
+
+                match &spec.param_type {
+                    ParamType::Float { min, max } => {
+                        let (min, max) = (*min as f64, *max as f64);
+                        for v in [min, max] {
+                            let mut effect = make(effect_type);
+                            effect.set_param(name, json!(v)).expect(&ctx);
+                            assert!(close(read_f64(effect.as_ref(), name), v), "{}", ctx);
+                        }
+                        // Beyond the declared range the effect must reject or clamp
+                        let margin = ((max - min) * 0.01).max(0.01);
+                        for v in [min - margin, max + margin] {
+                            assert!(make(effect_type).set_param(name, json!(v)).is_err());
+                            let mut effect = make(effect_type);
+                            if apply_raw(effect.as_mut(), name, json!(v)).is_ok() {
+                                let got = read_f64(effect.as_ref(), name);
+                                assert!(got >= min - 1e-6 && got <= max + 1e-6, "{}: {}", ctx, v);
+                            }
+                        }
+                    }
+                    ParamType::Int { min, max } => {
+                        for v in [*min, *max] {
+                            let mut effect = make(effect_type);
+                            effect.set_param(name, json!(v)).expect(&ctx);
+                            assert_eq!(read_f64(effect.as_ref(), name), v as f64, "{}", ctx);
+                        }
+                        for v in [*min - 1, *max + 1] {
+                            assert!(make(effect_type).set_param(name, json!(v)).is_err());
+                            let mut effect = make(effect_type);
+                            if apply_raw(effect.as_mut(), name, json!(v)).is_ok() {
+                                let got = read_f64(effect.as_ref(), name);
+                                assert!((*min as f64..=*max as f64).contains(&got), "{}", ctx);
+                            }
+                        }
+                    }
+                    ParamType::Enum { options } => {
+                        for option in options {
+                            let mut effect = make(effect_type);
+                            effect.set_param(name, json!(option)).expect(&ctx);
+                            assert_eq!(effect.get_param(name), Some(json!(option)), "{}", ctx);
+                        }
+                        let mut effect = make(effect_type);
+                        assert!(apply_raw(effect.as_mut(), name, json!("bogus")).is_err());
+                    }
+                    ParamType::Bool => {
+                        for v in [true, false] {
+                            let mut effect = make(effect_type);
+                            effect.set_param(name, json!(v)).expect(&ctx);
+                            assert_eq!(effect.get_param(name), Some(json!(v)), "{}", ctx);
+                        }
+                    }
+                    ParamType::String => {}
+                }
+            }
+
+            let mut effect = make(effect_type);
+            assert!(effect.set_param("no_such_param", json!(1.0)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_aliases_resolve() {
+        for name in ["eq", "parametric-eq", "parametric_eq"] {
+            assert_eq!(create_effect(name).unwrap().effect_type(), "parametric-eq");
+        }
+        for name in ["graphic-eq", "graphic_eq"] {
+            assert_eq!(create_effect(name).unwrap().effect_type(), "graphic-eq");
+        }
+        for name in ["ring-mod", "ring_mod"] {
+            assert_eq!(create_effect(name).unwrap().effect_type(), "ring-mod");
+        }
+        for name in ["wah", "auto-wah", "auto_wah"] {
+            assert_eq!(create_effect(name).unwrap().effect_type(), "wah");
+        }
+        for name in ["pitch-shifter", "pitch_shifter", "pitch-shift"] {
+            assert_eq!(create_effect(name).unwrap().effect_type(), "pitch-shifter");
+        }
+        assert!(create_effect("flanger").is_none());
+    }
+
+    #[test]
+    fn test_build_applies_params() {
+        let params: HashMap<String, serde_json::Value> =
+            [("gain_db".to_string(), serde_json::json!(-6.0))].into();
+        let effect = build_effect("gain", "gain-1", false, &params).unwrap();
+
+        assert_eq!(effect.id(), "gain-1");
+        assert!(!effect.is_enabled());
+        assert_eq!(
+            effect.to_json().unwrap()["gain_db"],
+            serde_json::json!(-6.0)
+        );
+    }
+
+    #[test]
+    fn test_build_nested_params() {
+        let params: HashMap<String, serde_json::Value> =
+            [("room_size".to_string(), serde_json::json!(0.9))].into();
+        let effect = build_effect("reverb", "rev-1", true, &params).unwrap();
+
+        let room = effect.to_json().unwrap()["params"]["room_size"]
+            .as_f64()
+            .unwrap();
+        assert!((room - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unknown_type_errors() {
+        let params = HashMap::new();
+        let err = build_effect("flanger", "f-1", true, &params).err().unwrap();
+        assert!(err.to_string().contains("flanger"), "{}", err);
+        assert!(err.to_string().contains("compressor"), "{}", err);
+    }
+
+    #[test]
+    fn test_process_to_matches_process() {
+        let mut seed = 11u32;
+        let samples: Vec<f32> = (0..4000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect();
+        let input = AudioBuffer::from_interleaved(samples, 2, 48000.0).unwrap();
+
+        for effect_type in EFFECT_TYPES {
+            let mut in_place = make(effect_type);
+            let mut separate = make(effect_type);
+            in_place.prepare(48000.0, 2000);
+            separate.prepare(48000.0, 2000);
+
+            // A stale output of the wrong shape, reused across blocks
+            let mut output = AudioBuffer::new(1, 10, 44100.0);
+            for _ in 0..2 {
+                let mut expected = input.clone();
+                in_place.process(&mut expected);
+                separate.process_to(&input, &mut output);
+
+                assert_eq!(output.num_channels(), expected.num_channels());
+                assert_eq!(output.sample_rate(), expected.sample_rate());
+                assert_eq!(output.samples(), expected.samples(), "{}", effect_type);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tagged_json_round_trip() {
+        for effect_type in EFFECT_TYPES {
+            let mut effect = make(effect_type);
+            effect.set_id(format!("{}-7", effect_type));
+            effect.set_enabled(false);
+
+            let json = effect_to_json(effect.as_ref()).unwrap();
+            assert_eq!(json["effect_type"], *effect_type);
+            assert!(json["params"].get("id").is_none(), "{}", effect_type);
+
+            let restored = effect_from_json(&json).unwrap();
+            assert_eq!(restored.effect_type(), *effect_type);
+            assert_eq!(restored.id(), format!("{}-7", effect_type));
+            assert!(!restored.is_enabled());
+            assert_eq!(restored.to_json().unwrap(), effect.to_json().unwrap());
+        }
+
+        let err = effect_from_json(&json!({"effect_type": "flanger"}))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("flanger"));
+        assert!(effect_from_json(&json!({"id": "x"})).is_err());
+    }
+}
@@ -5,6 +5,7 @@
 
 use super::{AudioBuffer, Effect, EffectMetadata};
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 
 /// Minimum gain in dB
@@ -112,6 +113,19 @@ impl Effect for GainEffect {
         }
     }
 
+    fn process_to(&mut self, input: &AudioBuffer, output: &mut AudioBuffer) {
+        if !self.enabled {
+            output.copy_from(input);
+            return;
+        }
+
+        output.resize_to_match(input);
+        let gain = self.gain_linear;
+        for (out, &sample) in output.samples_mut().iter_mut().zip(input.samples()) {
+            *out = sample * gain;
+        }
+    }
+
     fn prepare(&mut self, sample_rate: f64, samples_per_block: usize) {
         self.sample_rate = sample_rate;
         self.samples_per_block = samples_per_block;
@@ -184,6 +198,10 @@ impl Effect for GainEffect {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![ParamSpec::float("gain_db", GAIN_MIN_DB, GAIN_MAX_DB, 0.0).with_unit("dB")]
+    }
 }
 
 #[cfg(test)]
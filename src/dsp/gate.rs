@@ -3,12 +3,27 @@
 //! A gate attenuates audio below a threshold, useful for removing noise
 //! during silent passages. Features envelope follower with hysteresis
 //! to prevent chattering.
+//!
+//! The open/close decision can also follow a separate key (sidechain)
+//! signal via [`Gate::process_with_key`], e.g. to gate a pad with a kick.
+//!
+//! With lookahead the audio runs through a short delay line while the
+//! detector sees the undelayed signal, so the gate is already opening when
+//! a transient reaches the output instead of chopping its attack.
 
-use super::effect::{Effect, EffectMetadata};
+use super::effect::{Effect, EffectMetadata, EffectMeter};
 use super::AudioBuffer;
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 
+/// Longest lookahead in milliseconds
+pub const MAX_GATE_LOOKAHEAD_MS: f32 = 10.0;
+
+/// Deepest range in dB; at 96 dB of attenuation a closed gate sits at the
+/// 16-bit noise floor, i.e. effectively mutes
+pub const MIN_GATE_RANGE_DB: f32 = -96.0;
+
 /// Gate state for the envelope follower
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum GateState {
@@ -35,8 +50,12 @@ pub struct GateParams {
     pub release_ms: f32,
     /// Hold time in ms (0 to 100)
     pub hold_ms: f32,
-    /// Range/attenuation in dB (-80 = full gate, 0 = no effect)
+    /// Gain when closed in dB (-96 = full mute, -20 = quiet bleed, 0 = no
+    /// effect); the release envelope glides down to it
     pub range_db: f32,
+    /// Lookahead in ms (0 to 10); delays the output by this much
+    #[serde(default)]
+    pub lookahead_ms: f32,
 }
 
 impl Default for GateParams {
@@ -47,6 +66,7 @@ impl Default for GateParams {
             release_ms: 50.0,
             hold_ms: 10.0,
             range_db: -80.0,
+            lookahead_ms: 0.0,
         }
     }
 }
@@ -82,11 +102,18 @@ impl GateParams {
                 expected: "0 to 100 ms".to_string(),
             });
         }
-        if !(-80.0..=0.0).contains(&self.range_db) {
+        if !(MIN_GATE_RANGE_DB..=0.0).contains(&self.range_db) {
             return Err(NuevaError::InvalidParameter {
                 param: "range_db".to_string(),
                 value: self.range_db.to_string(),
-                expected: "-80 to 0 dB".to_string(),
+                expected: format!("{} to 0 dB", MIN_GATE_RANGE_DB),
+            });
+        }
+        if !(0.0..=MAX_GATE_LOOKAHEAD_MS).contains(&self.lookahead_ms) {
+            return Err(NuevaError::InvalidParameter {
+                param: "lookahead_ms".to_string(),
+                value: self.lookahead_ms.to_string(),
+                expected: format!("0 to {} ms", MAX_GATE_LOOKAHEAD_MS),
             });
         }
         Ok(())
@@ -98,7 +125,8 @@ impl GateParams {
         self.attack_ms = self.attack_ms.clamp(0.1, 50.0);
         self.release_ms = self.release_ms.clamp(10.0, 500.0);
         self.hold_ms = self.hold_ms.clamp(0.0, 100.0);
-        self.range_db = self.range_db.clamp(-80.0, 0.0);
+        self.range_db = self.range_db.clamp(MIN_GATE_RANGE_DB, 0.0);
+        self.lookahead_ms = self.lookahead_ms.clamp(0.0, MAX_GATE_LOOKAHEAD_MS);
     }
 }
 
@@ -145,6 +173,16 @@ pub struct Gate {
     threshold_linear: f32,
     /// Hysteresis threshold (lower) as linear value
     threshold_low_linear: f32,
+    /// Last key level seen, held when the key runs out
+    last_key_level: f32,
+    /// Lookahead in samples
+    lookahead_samples: usize,
+    /// Interleaved delay line for the audio path (lookahead frames)
+    delay_line: Vec<f32>,
+    /// Next frame to read and overwrite in the delay line
+    delay_pos: usize,
+    /// Levels of the last processed block
+    meter: Option<EffectMeter>,
 }
 
 impl Gate {
@@ -173,6 +211,11 @@ impl Gate {
             range_linear: 0.0,
             threshold_linear: 0.0,
             threshold_low_linear: 0.0,
+            last_key_level: 0.0,
+            lookahead_samples: 0,
+            delay_line: Vec::new(),
+            delay_pos: 0,
+            meter: None,
         };
         gate.update_coefficients();
         gate
@@ -247,13 +290,13 @@ impl Gate {
         Ok(())
     }
 
-    /// Set range/attenuation in dB
+    /// Set the closed gain (range) in dB
     pub fn set_range_db(&mut self, range_db: f32) -> Result<()> {
-        if !(-80.0..=0.0).contains(&range_db) {
+        if !(MIN_GATE_RANGE_DB..=0.0).contains(&range_db) {
             return Err(NuevaError::InvalidParameter {
                 param: "range_db".to_string(),
                 value: range_db.to_string(),
-                expected: "-80 to 0 dB".to_string(),
+                expected: format!("{} to 0 dB", MIN_GATE_RANGE_DB),
             });
         }
         self.params.range_db = range_db;
@@ -261,6 +304,20 @@ impl Gate {
         Ok(())
     }
 
+    /// Set lookahead in ms
+    pub fn set_lookahead_ms(&mut self, lookahead_ms: f32) -> Result<()> {
+        if !(0.0..=MAX_GATE_LOOKAHEAD_MS).contains(&lookahead_ms) {
+            return Err(NuevaError::InvalidParameter {
+                param: "lookahead_ms".to_string(),
+                value: lookahead_ms.to_string(),
+                expected: format!("0 to {} ms", MAX_GATE_LOOKAHEAD_MS),
+            });
+        }
+        self.params.lookahead_ms = lookahead_ms;
+        self.update_coefficients();
+        Ok(())
+    }
+
     /// Set hysteresis in dB (default is 2 dB)
     pub fn set_hysteresis_db(&mut self, hysteresis_db: f32) {
         self.hysteresis_db = hysteresis_db.max(0.0);
@@ -293,6 +350,42 @@ impl Gate {
 
         // Convert hold time to samples
         self.hold_samples = (self.params.hold_ms * self.sample_rate as f32 / 1000.0) as usize;
+
+        self.lookahead_samples =
+            (self.params.lookahead_ms as f64 / 1000.0 * self.sample_rate).round() as usize;
+    }
+
+    /// Size the delay line for the lookahead and channel count, clearing it
+    /// if either changed
+    fn prepare_delay_line(&mut self, num_channels: usize) {
+        let len = self.lookahead_samples * num_channels;
+        if self.delay_line.len() != len {
+            self.delay_line = vec![0.0; len];
+            self.delay_pos = 0;
+        }
+    }
+
+    /// Apply `gain` to the frame leaving the delay line
+    ///
+    /// The frame at `frame` enters the line and the one that entered
+    /// `lookahead_samples` frames ago is written out in its place, so the
+    /// gain (computed from the incoming frame) leads the audio.
+    fn apply_delayed(&mut self, buffer: &mut AudioBuffer, frame: usize, gain: f32) {
+        let num_channels = buffer.num_channels();
+        for channel in 0..num_channels {
+            if let Some(sample) = buffer.get(frame, channel) {
+                let out = if self.delay_line.is_empty() {
+                    sample
+                } else {
+                    let slot = self.delay_pos * num_channels + channel;
+                    std::mem::replace(&mut self.delay_line[slot], sample)
+                };
+                buffer.set(frame, channel, out * gain);
+            }
+        }
+        if !self.delay_line.is_empty() {
+            self.delay_pos = (self.delay_pos + 1) % self.lookahead_samples;
+        }
     }
 
     /// Process a single sample and return the gain to apply
@@ -372,6 +465,41 @@ impl Gate {
 
         self.current_gain
     }
+
+    /// Process `buffer`, opening and closing on the level of `key`
+    ///
+    /// The key is summed to mono and its level drives the gate; the gain
+    /// is applied to `buffer` only. If the key is shorter than the buffer,
+    /// its last level is held for the remaining frames.
+    pub fn process_with_key(&mut self, buffer: &mut AudioBuffer, key: &AudioBuffer) {
+        let key_channels = key.num_channels().max(1);
+        let input_db = EffectMeter::peak_db(buffer);
+        self.prepare_delay_line(buffer.num_channels());
+
+        for frame in 0..buffer.num_samples() {
+            if frame < key.num_samples() {
+                let sum: f32 = (0..key_channels).filter_map(|ch| key.get(frame, ch)).sum();
+                self.last_key_level = (sum / key_channels as f32).abs();
+            }
+
+            let gain = self.process_sample(self.last_key_level);
+            self.apply_delayed(buffer, frame, gain);
+        }
+        self.update_meter(input_db, buffer);
+    }
+
+    /// Get the current gain reduction in dB for metering
+    pub fn gain_reduction_db(&self) -> f32 {
+        linear_to_db(self.current_gain)
+    }
+
+    /// Record the levels of the block just processed
+    fn update_meter(&mut self, input_db: f32, output: &AudioBuffer) {
+        self.meter = Some(
+            EffectMeter::new(input_db, EffectMeter::peak_db(output))
+                .with_gain_reduction(self.gain_reduction_db()),
+        );
+    }
 }
 
 impl Default for Gate {
@@ -384,6 +512,8 @@ impl Effect for Gate {
     fn process(&mut self, buffer: &mut AudioBuffer) {
         let num_channels = buffer.num_channels();
         let num_samples = buffer.num_samples();
+        let input_db = EffectMeter::peak_db(buffer);
+        self.prepare_delay_line(num_channels);
 
         for frame in 0..num_samples {
             // Calculate peak level across all channels for this frame
@@ -394,16 +524,11 @@ impl Effect for Gate {
                 }
             }
 
-            // Get gain for this sample
+            // Get gain for this sample and apply it to the delayed frame
             let gain = self.process_sample(peak);
-
-            // Apply gain to all channels
-            for channel in 0..num_channels {
-                if let Some(sample) = buffer.get(frame, channel) {
-                    buffer.set(frame, channel, sample * gain);
-                }
-            }
+            self.apply_delayed(buffer, frame, gain);
         }
+        self.update_meter(input_db, buffer);
     }
 
     fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
@@ -416,6 +541,14 @@ impl Effect for Gate {
         self.envelope = 0.0;
         self.current_gain = self.range_linear;
         self.hold_counter = 0;
+        self.last_key_level = 0.0;
+        self.delay_line.fill(0.0);
+        self.delay_pos = 0;
+        self.meter = None;
+    }
+
+    fn meter(&self) -> Option<EffectMeter> {
+        self.meter
     }
 
     fn to_json(&self) -> Result<serde_json::Value> {
@@ -464,6 +597,21 @@ impl Effect for Gate {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("threshold_db", -80.0, 0.0, -40.0).with_unit("dB"),
+            ParamSpec::float("attack_ms", 0.1, 50.0, 1.0).with_unit("ms"),
+            ParamSpec::float("release_ms", 10.0, 500.0, 50.0).with_unit("ms"),
+            ParamSpec::float("hold_ms", 0.0, 100.0, 10.0).with_unit("ms"),
+            ParamSpec::float("range_db", MIN_GATE_RANGE_DB, 0.0, -80.0).with_unit("dB"),
+            ParamSpec::float("lookahead_ms", 0.0, MAX_GATE_LOOKAHEAD_MS, 0.0).with_unit("ms"),
+        ]
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.lookahead_samples
+    }
 }
 
 /// Convert decibels to linear amplitude
@@ -528,6 +676,7 @@ mod tests {
             release_ms: 1000.0,
             hold_ms: -10.0,
             range_db: -100.0,
+            lookahead_ms: 20.0,
         };
         params.clamp();
 
@@ -535,7 +684,8 @@ mod tests {
         assert_eq!(params.attack_ms, 0.1);
         assert_eq!(params.release_ms, 500.0);
         assert_eq!(params.hold_ms, 0.0);
-        assert_eq!(params.range_db, -80.0);
+        assert_eq!(params.range_db, MIN_GATE_RANGE_DB);
+        assert_eq!(params.lookahead_ms, MAX_GATE_LOOKAHEAD_MS);
     }
 
     #[test]
@@ -700,6 +850,126 @@ mod tests {
         assert_eq!(gate.hold_counter, 0);
     }
 
+    #[test]
+    fn test_gate_meter_reports_attenuation() {
+        let mut gate = Gate::new();
+        gate.prepare(44100.0, 512);
+
+        // Well below the threshold, so the gate stays closed
+        let mut buffer = AudioBuffer::new(1, 4410, 44100.0);
+        for i in 0..4410 {
+            buffer.set(i, 0, 0.001);
+        }
+        gate.process(&mut buffer);
+
+        let meter = gate.meter().expect("meter after processing");
+        assert!(meter.output_db < meter.input_db);
+        assert!(meter.gain_reduction_db.unwrap() < -1.0);
+    }
+
+    /// 500 ms sustained tone and a key with a 20 ms burst every 250 ms
+    fn pad_and_kick(sample_rate: f64) -> (AudioBuffer, AudioBuffer) {
+        let frames = (sample_rate * 0.5) as usize;
+        let beat = (sample_rate * 0.25) as usize;
+        let burst = (sample_rate * 0.02) as usize;
+
+        let mut pad = AudioBuffer::new(2, frames, sample_rate);
+        let mut kick = AudioBuffer::new(1, frames, sample_rate);
+        for i in 0..frames {
+            let tone = 0.5 * (i as f32 * 0.05).sin();
+            pad.set(i, 0, tone);
+            pad.set(i, 1, tone);
+            if i % beat < burst {
+                kick.set(i, 0, 0.3);
+            }
+        }
+        (pad, kick)
+    }
+
+    fn peak(buffer: &AudioBuffer, range: std::ops::Range<usize>) -> f32 {
+        range
+            .flat_map(|i| (0..buffer.num_channels()).map(move |ch| (i, ch)))
+            .map(|(i, ch)| buffer.get(i, ch).unwrap().abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_key_gates_sustained_tone_rhythmically() {
+        let sample_rate = 48000.0;
+        let (mut pad, kick) = pad_and_kick(sample_rate);
+        let mut gate = Gate::new();
+        gate.set_threshold_db(-20.0).unwrap();
+        gate.set_hold_ms(0.0).unwrap();
+        gate.set_release_ms(10.0).unwrap();
+        gate.prepare(sample_rate, 512);
+
+        gate.process_with_key(&mut pad, &kick);
+
+        // Open during each burst, closed well after it
+        let ms = |t: f64| (sample_rate * t / 1000.0) as usize;
+        for beat_start in [0.0, 250.0] {
+            let open = peak(&pad, ms(beat_start + 5.0)..ms(beat_start + 20.0));
+            let closed = peak(&pad, ms(beat_start + 150.0)..ms(beat_start + 245.0));
+            assert!(open > 0.4, "open at {} ms: {}", beat_start, open);
+            assert!(closed < 0.01, "closed at {} ms: {}", beat_start, closed);
+        }
+    }
+
+    #[test]
+    fn test_key_mono_sum_and_underrun_hold() {
+        let sample_rate = 48000.0;
+        let mut gate = Gate::new();
+        gate.set_threshold_db(-20.0).unwrap();
+        gate.prepare(sample_rate, 512);
+
+        // Stereo key in antiphase sums to silence: the gate stays closed
+        let mut key = AudioBuffer::new(2, 480, sample_rate);
+        for i in 0..480 {
+            key.set(i, 0, 0.8);
+            key.set(i, 1, -0.8);
+        }
+        let mut buffer = AudioBuffer::new(1, 480, sample_rate);
+        (0..480).for_each(|i| buffer.set(i, 0, 0.5));
+        gate.process_with_key(&mut buffer, &key);
+        assert!(peak(&buffer, 0..480) < 0.01);
+
+        // A short loud key is held past its end
+        gate.reset();
+        let mut key = AudioBuffer::new(1, 100, sample_rate);
+        (0..100).for_each(|i| key.set(i, 0, 0.8));
+        let mut buffer = AudioBuffer::new(1, 4800, sample_rate);
+        (0..4800).for_each(|i| buffer.set(i, 0, 0.5));
+        gate.process_with_key(&mut buffer, &key);
+        assert!(peak(&buffer, 4700..4800) > 0.45);
+
+        gate.reset();
+        assert_eq!(gate.last_key_level, 0.0);
+    }
+
+    #[test]
+    fn test_self_key_matches_plain_process() {
+        let sample_rate = 48000.0;
+        let (pad, _) = pad_and_kick(sample_rate);
+
+        // Keying a gate with its own input matches plain processing for mono
+        let mono: Vec<f32> = (0..pad.num_samples())
+            .map(|i| pad.get(i, 0).unwrap())
+            .collect();
+        let mono = AudioBuffer::from_interleaved(mono, 1, sample_rate).unwrap();
+
+        let mut plain = Gate::new();
+        plain.prepare(sample_rate, 512);
+        let mut expected = mono.clone();
+        plain.process(&mut expected);
+
+        let mut keyed = Gate::new();
+        keyed.prepare(sample_rate, 512);
+        let mut actual = mono.clone();
+        keyed.process_with_key(&mut actual, &mono);
+
+        assert_eq!(expected.samples(), actual.samples());
+    }
+
     #[test]
     fn test_gate_stereo_processing() {
         let mut gate = Gate::new();
@@ -785,4 +1055,171 @@ mod tests {
         assert!(gate.set_hold_ms(-10.0).is_err());
         assert!(gate.set_range_db(10.0).is_err());
     }
+
+    /// A gate with a -20 dB threshold and the given range, prepared at 48 kHz
+    fn range_gate(range_db: f32) -> Gate {
+        let mut gate = Gate::with_params(GateParams {
+            threshold_db: -20.0,
+            range_db,
+            ..GateParams::default()
+        });
+        gate.prepare(48000.0, 512);
+        gate.reset();
+        gate
+    }
+
+    /// `loud_frames` at 0.5, then a quiet 0.01 (-40 dB) bleed
+    fn burst_then_bleed(loud_frames: usize, frames: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(1, frames, 48000.0);
+        for i in 0..frames {
+            buffer.set(i, 0, if i < loud_frames { 0.5 } else { 0.01 });
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_closed_level_is_input_plus_range() {
+        let mut gate = range_gate(-20.0);
+        let mut buffer = burst_then_bleed(0, 4800);
+        gate.process(&mut buffer);
+
+        // Closed: the bleed comes through 20 dB down rather than muted
+        let level_db = linear_to_db(buffer.get(4799, 0).unwrap());
+        let input_db = linear_to_db(0.01);
+        assert!(
+            (level_db - (input_db - 20.0)).abs() < 0.01,
+            "closed level {} dB, input {} dB",
+            level_db,
+            input_db
+        );
+    }
+
+    #[test]
+    fn test_full_range_mutes() {
+        let mut gate = range_gate(MIN_GATE_RANGE_DB);
+        let mut buffer = burst_then_bleed(0, 4800);
+        gate.process(&mut buffer);
+        assert!(buffer.get(4799, 0).unwrap() < 0.01 * 2e-5);
+    }
+
+    #[test]
+    fn test_release_glides_to_range() {
+        let mut gate = range_gate(-20.0);
+        let mut buffer = burst_then_bleed(4800, 48000);
+        gate.process(&mut buffer);
+
+        // After the burst the bleed never drops out, and the gain falls to
+        // the floor without a step
+        let gains: Vec<f32> = (4800..48000)
+            .map(|i| buffer.get(i, 0).unwrap() / 0.01)
+            .collect();
+        assert!(gains.iter().all(|&g| g >= 0.1 - 1e-4));
+        let max_step = gains
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0_f32, f32::max);
+        assert!(max_step < 0.01, "max gain step {}", max_step);
+        assert!((gains.last().unwrap() - 0.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_range_validates_and_serializes() {
+        let mut gate = Gate::new();
+        assert!(gate.set_range_db(MIN_GATE_RANGE_DB).is_ok());
+        assert!(gate.set_range_db(-100.0).is_err());
+
+        gate.set_range_db(-20.0).unwrap();
+        let json = gate.to_json().unwrap();
+        let mut restored = Gate::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.params().range_db, -20.0);
+    }
+
+    /// 100 ms of silence, then a decaying 200 Hz hit
+    fn drum_hit(sample_rate: f64) -> AudioBuffer {
+        let onset = (sample_rate * 0.1) as usize;
+        let frames = onset * 3;
+        let mut buffer = AudioBuffer::new(2, frames, sample_rate);
+        for i in onset..frames {
+            let t = (i - onset) as f64 / sample_rate;
+            let s = ((std::f64::consts::TAU * 200.0 * t).sin() * (-t * 30.0).exp()) as f32;
+            buffer.set(i, 0, s);
+            buffer.set(i, 1, s);
+        }
+        buffer
+    }
+
+    fn lookahead_gate(lookahead_ms: f32) -> Gate {
+        let mut gate = Gate::with_params(GateParams {
+            lookahead_ms,
+            ..GateParams::default()
+        });
+        gate.prepare(48000.0, 512);
+        gate.reset();
+        gate
+    }
+
+    #[test]
+    fn test_lookahead_keeps_transient() {
+        let sample_rate = 48000.0;
+        let dry = drum_hit(sample_rate);
+        let onset = (sample_rate * 0.1) as usize;
+        // The first half cycle of the hit, where its first peak lies
+        let first_peak = peak(&dry, onset..onset + 120);
+
+        let mut plain = lookahead_gate(0.0);
+        assert_eq!(plain.latency_samples(), 0);
+        let mut wet = dry.clone();
+        plain.process(&mut wet);
+        // Without lookahead the 1 ms attack is still rising at the peak
+        assert!(peak(&wet, onset..onset + 120) < 0.8 * first_peak);
+
+        let mut ahead = lookahead_gate(5.0);
+        let latency = ahead.latency_samples();
+        assert_eq!(latency, 240);
+        let mut wet = dry.clone();
+        ahead.process(&mut wet);
+        let delayed = onset + latency;
+        assert!(peak(&wet, delayed..delayed + 120) > 0.99 * first_peak);
+        // Nothing leaks out before the delayed onset
+        assert!(peak(&wet, 0..delayed) < 1e-6);
+    }
+
+    #[test]
+    fn test_lookahead_delays_whole_signal_once_open() {
+        let mut gate = lookahead_gate(2.0);
+        gate.set_range_db(0.0).unwrap();
+        gate.reset();
+        let latency = gate.latency_samples();
+
+        let dry = drum_hit(48000.0);
+        let mut wet = dry.clone();
+        gate.process(&mut wet);
+        for i in latency..dry.num_samples() {
+            assert!((wet.get(i, 1).unwrap() - dry.get(i - latency, 1).unwrap()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_lookahead_reset_between_runs() {
+        let dry = drum_hit(48000.0);
+        let mut gate = lookahead_gate(3.0);
+
+        let mut first = dry.clone();
+        gate.process(&mut first);
+        gate.reset();
+        let mut second = dry.clone();
+        gate.process(&mut second);
+        assert_eq!(first.samples(), second.samples());
+
+        // Block-wise processing matches one pass
+        gate.reset();
+        let mut out = Vec::new();
+        for chunk in dry.samples().chunks(2 * 257) {
+            let mut block = AudioBuffer::from_interleaved(chunk.to_vec(), 2, 48000.0).unwrap();
+            gate.process(&mut block);
+            out.extend_from_slice(block.samples());
+        }
+        assert_eq!(out, first.samples());
+    }
 }
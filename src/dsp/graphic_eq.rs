@@ -0,0 +1,519 @@
+//! Graphic EQ Effect
+//!
+//! Fixed-band equalizer with one gain slider per ISO center frequency.
+//! Each slider drives a peak [`EQBand`] whose Q matches the band spacing
+//! (one octave for 10 bands, a third of an octave for 31), so a boost on
+//! one band blends into its neighbours the way a hardware graphic does.
+
+use super::eq::{BiquadCoeffs, BiquadState, EQBand};
+use super::{AudioBuffer, Effect, EffectMetadata};
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+
+/// Maximum boost or cut per band in dB
+pub const GRAPHIC_EQ_MAX_GAIN_DB: f32 = 12.0;
+
+/// ISO octave center frequencies (10-band)
+const OCTAVE_FREQUENCIES: [f32; 10] = [
+    31.5, 63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// ISO third-octave center frequencies (31-band)
+const THIRD_OCTAVE_FREQUENCIES: [f32; 31] = [
+    20.0, 25.0, 31.5, 40.0, 50.0, 63.0, 80.0, 100.0, 125.0, 160.0, 200.0, 250.0, 315.0, 400.0,
+    500.0, 630.0, 800.0, 1000.0, 1250.0, 1600.0, 2000.0, 2500.0, 3150.0, 4000.0, 5000.0, 6300.0,
+    8000.0, 10000.0, 12500.0, 16000.0, 20000.0,
+];
+
+/// Band layout of a graphic EQ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphicEQLayout {
+    /// 10 bands, one octave apart
+    #[default]
+    TenBand,
+    /// 31 bands, a third of an octave apart
+    ThirtyOneBand,
+}
+
+impl GraphicEQLayout {
+    /// Layout with the given number of bands (10 or 31)
+    pub fn from_num_bands(num_bands: usize) -> Option<Self> {
+        match num_bands {
+            10 => Some(GraphicEQLayout::TenBand),
+            31 => Some(GraphicEQLayout::ThirtyOneBand),
+            _ => None,
+        }
+    }
+
+    /// Band center frequencies in Hz
+    pub fn frequencies(self) -> &'static [f32] {
+        match self {
+            GraphicEQLayout::TenBand => &OCTAVE_FREQUENCIES,
+            GraphicEQLayout::ThirtyOneBand => &THIRD_OCTAVE_FREQUENCIES,
+        }
+    }
+
+    /// Number of bands
+    pub fn num_bands(self) -> usize {
+        self.frequencies().len()
+    }
+
+    /// Band spacing in octaves
+    pub fn bandwidth_octaves(self) -> f64 {
+        match self {
+            GraphicEQLayout::TenBand => 1.0,
+            GraphicEQLayout::ThirtyOneBand => 1.0 / 3.0,
+        }
+    }
+
+    /// Fixed Q of every band
+    ///
+    /// Each bell's half-gain points land on the midpoints to its
+    /// neighbours, so adjacent bands cross over instead of leaving gaps.
+    pub fn q(self) -> f32 {
+        let bw = self.bandwidth_octaves();
+        (2f64.powf(bw / 2.0) / (2f64.powf(bw) - 1.0)) as f32
+    }
+}
+
+/// Per-band filter state (one biquad per channel)
+#[derive(Debug, Clone, Default)]
+struct GraphicBandState {
+    coeffs: Option<BiquadCoeffs>,
+    states: Vec<BiquadState>,
+}
+
+/// Serialized form: the layout is implied by the number of gains
+#[derive(Serialize, Deserialize)]
+struct GraphicEQJson {
+    id: String,
+    enabled: bool,
+    gains_db: Vec<f32>,
+}
+
+/// Graphic EQ with fixed 10- or 31-band layouts
+#[derive(Debug, Clone)]
+pub struct GraphicEQ {
+    id: String,
+    enabled: bool,
+    layout: GraphicEQLayout,
+    /// One gain per band in dB
+    gains_db: Vec<f32>,
+    sample_rate: f64,
+    num_channels: usize,
+    band_states: Vec<GraphicBandState>,
+    coeffs_dirty: bool,
+}
+
+impl Default for GraphicEQ {
+    fn default() -> Self {
+        Self::with_layout(GraphicEQLayout::default())
+    }
+}
+
+impl GraphicEQ {
+    /// Create a flat 10-band graphic EQ
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a flat graphic EQ with the given layout
+    pub fn with_layout(layout: GraphicEQLayout) -> Self {
+        Self {
+            id: String::new(),
+            enabled: true,
+            layout,
+            gains_db: vec![0.0; layout.num_bands()],
+            sample_rate: 48000.0,
+            num_channels: 2,
+            band_states: Vec::new(),
+            coeffs_dirty: true,
+        }
+    }
+
+    /// Create a graphic EQ from one gain per band; 10 or 31 gains select
+    /// the layout
+    pub fn with_gains(gains_db: Vec<f32>) -> Result<Self> {
+        let layout = Self::layout_for(&gains_db)?;
+        let mut eq = Self::with_layout(layout);
+        eq.set_gains(gains_db)?;
+        Ok(eq)
+    }
+
+    /// Band layout
+    pub fn layout(&self) -> GraphicEQLayout {
+        self.layout
+    }
+
+    /// Band center frequencies in Hz
+    pub fn frequencies(&self) -> &'static [f32] {
+        self.layout.frequencies()
+    }
+
+    /// Current gain of every band in dB
+    pub fn gains_db(&self) -> &[f32] {
+        &self.gains_db
+    }
+
+    /// Set the gain of one band
+    pub fn set_gain(&mut self, band: usize, gain_db: f32) -> Result<()> {
+        let num_bands = self.layout.num_bands();
+        let slot = self
+            .gains_db
+            .get_mut(band)
+            .ok_or_else(|| NuevaError::InvalidParameter {
+                param: "band".to_string(),
+                value: band.to_string(),
+                expected: format!("0-{}", num_bands - 1),
+            })?;
+        validate_gain(gain_db)?;
+        *slot = gain_db;
+        self.coeffs_dirty = true;
+        Ok(())
+    }
+
+    /// Set every band's gain; the count must match the current layout
+    pub fn set_gains(&mut self, gains_db: Vec<f32>) -> Result<()> {
+        if gains_db.len() != self.layout.num_bands() {
+            return Err(NuevaError::InvalidParameter {
+                param: "gains_db".to_string(),
+                value: format!("{} gains", gains_db.len()),
+                expected: format!("{} gains", self.layout.num_bands()),
+            });
+        }
+        for &gain_db in &gains_db {
+            validate_gain(gain_db)?;
+        }
+        self.gains_db = gains_db;
+        self.coeffs_dirty = true;
+        Ok(())
+    }
+
+    /// Reset every band to 0 dB
+    pub fn flatten(&mut self) {
+        self.gains_db.fill(0.0);
+        self.coeffs_dirty = true;
+    }
+
+    /// The peak bands this EQ runs, one per slider
+    pub fn bands(&self) -> Vec<EQBand> {
+        let q = self.layout.q();
+        self.frequencies()
+            .iter()
+            .zip(&self.gains_db)
+            .map(|(&frequency, &gain_db)| EQBand::peak(frequency, gain_db, q))
+            .collect()
+    }
+
+    fn layout_for(gains_db: &[f32]) -> Result<GraphicEQLayout> {
+        GraphicEQLayout::from_num_bands(gains_db.len()).ok_or_else(|| {
+            NuevaError::InvalidParameter {
+                param: "gains_db".to_string(),
+                value: format!("{} gains", gains_db.len()),
+                expected: "10 or 31 gains".to_string(),
+            }
+        })
+    }
+
+    /// Update filter coefficients if needed
+    fn update_coefficients(&mut self) {
+        if !self.coeffs_dirty {
+            return;
+        }
+
+        let bands = self.bands();
+        self.band_states
+            .resize_with(bands.len(), GraphicBandState::default);
+
+        for (band, state) in bands.iter().zip(&mut self.band_states) {
+            state
+                .states
+                .resize_with(self.num_channels, BiquadState::default);
+            state.coeffs = (!band.is_bypass()).then(|| {
+                BiquadCoeffs::calculate(
+                    band.filter_type,
+                    self.sample_rate,
+                    band.frequency as f64,
+                    band.gain_db as f64,
+                    band.q as f64,
+                )
+            });
+        }
+
+        self.coeffs_dirty = false;
+    }
+}
+
+fn validate_gain(gain_db: f32) -> Result<()> {
+    if !(-GRAPHIC_EQ_MAX_GAIN_DB..=GRAPHIC_EQ_MAX_GAIN_DB).contains(&gain_db) {
+        return Err(NuevaError::InvalidParameter {
+            param: "gain_db".to_string(),
+            value: gain_db.to_string(),
+            expected: format!(
+                "-{} to +{} dB",
+                GRAPHIC_EQ_MAX_GAIN_DB, GRAPHIC_EQ_MAX_GAIN_DB
+            ),
+        });
+    }
+    Ok(())
+}
+
+impl Effect for GraphicEQ {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled {
+            return;
+        }
+
+        let num_channels = buffer.num_channels();
+        if num_channels > self.num_channels {
+            self.num_channels = num_channels;
+            self.coeffs_dirty = true;
+        }
+        self.update_coefficients();
+
+        // Flat bands are skipped entirely, so an all-zero EQ is a passthrough
+        for band in &mut self.band_states {
+            let Some(coeffs) = band.coeffs else {
+                continue;
+            };
+            for frame in 0..buffer.num_samples() {
+                for channel in 0..num_channels {
+                    if let Some(sample) = buffer.get(frame, channel) {
+                        let output = band.states[channel].process(sample as f64, &coeffs);
+                        buffer.set(frame, channel, output as f32);
+                    }
+                }
+            }
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        self.sample_rate = sample_rate;
+        self.coeffs_dirty = true;
+    }
+
+    fn reset(&mut self) {
+        for band in &mut self.band_states {
+            for state in &mut band.states {
+                state.reset();
+            }
+        }
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        serde_json::to_value(GraphicEQJson {
+            id: self.id.clone(),
+            enabled: self.enabled,
+            gains_db: self.gains_db.clone(),
+        })
+        .map_err(|e| NuevaError::SerializationError {
+            details: e.to_string(),
+        })
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        let deserialized: GraphicEQJson =
+            serde_json::from_value(json.clone()).map_err(|e| NuevaError::SerializationError {
+                details: e.to_string(),
+            })?;
+
+        let layout = Self::layout_for(&deserialized.gains_db)?;
+        for &gain_db in &deserialized.gains_db {
+            validate_gain(gain_db)?;
+        }
+
+        if layout != self.layout {
+            self.band_states.clear();
+        }
+        self.id = deserialized.id;
+        self.enabled = deserialized.enabled;
+        self.layout = layout;
+        self.gains_db = deserialized.gains_db;
+        self.coeffs_dirty = true;
+
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "graphic-eq"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Graphic EQ"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: self.effect_type().to_string(),
+            display_name: self.display_name().to_string(),
+            category: "eq".to_string(),
+            order_priority: 20,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        self.frequencies()
+            .iter()
+            .enumerate()
+            .map(|(i, freq)| {
+                ParamSpec::float(
+                    &format!("gains_db.{}", i),
+                    -GRAPHIC_EQ_MAX_GAIN_DB,
+                    GRAPHIC_EQ_MAX_GAIN_DB,
+                    0.0,
+                )
+                .with_unit("dB")
+                .with_description(&format!("{} Hz band", freq))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const SAMPLE_RATE: f64 = 48000.0;
+
+    fn sine_buffer(frequency: f64, num_samples: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(1, num_samples, SAMPLE_RATE);
+        for i in 0..num_samples {
+            let t = i as f64 / SAMPLE_RATE;
+            buffer.set(i, 0, (2.0 * PI * frequency * t).sin() as f32);
+        }
+        buffer
+    }
+
+    /// Steady-state gain in dB at a frequency (skips the settling time)
+    fn response_db(eq: &mut GraphicEQ, frequency: f64) -> f64 {
+        let rms = |buffer: &AudioBuffer| {
+            let skip = buffer.num_samples() / 2;
+            let sum: f64 = (skip..buffer.num_samples())
+                .filter_map(|i| buffer.get(i, 0))
+                .map(|s| (s as f64).powi(2))
+                .sum();
+            (sum / (buffer.num_samples() - skip) as f64).sqrt()
+        };
+
+        let dry = sine_buffer(frequency, SAMPLE_RATE as usize / 2);
+        let mut wet = dry.clone();
+        eq.prepare(SAMPLE_RATE, 512);
+        eq.reset();
+        eq.process(&mut wet);
+        20.0 * (rms(&wet) / rms(&dry)).log10()
+    }
+
+    #[test]
+    fn test_flat_is_passthrough() {
+        for layout in [GraphicEQLayout::TenBand, GraphicEQLayout::ThirtyOneBand] {
+            let mut eq = GraphicEQ::with_layout(layout);
+            eq.prepare(SAMPLE_RATE, 512);
+
+            let dry = sine_buffer(1000.0, 4800);
+            let mut wet = dry.clone();
+            eq.process(&mut wet);
+            assert_eq!(wet.samples(), dry.samples());
+        }
+    }
+
+    #[test]
+    fn test_layouts() {
+        assert_eq!(GraphicEQ::new().gains_db().len(), 10);
+        assert_eq!(GraphicEQLayout::TenBand.frequencies()[5], 1000.0);
+
+        let eq = GraphicEQ::with_layout(GraphicEQLayout::ThirtyOneBand);
+        assert_eq!(eq.frequencies().len(), 31);
+        assert_eq!(eq.frequencies()[0], 20.0);
+        assert_eq!(eq.frequencies()[30], 20000.0);
+
+        assert!((GraphicEQLayout::TenBand.q() - 1.414).abs() < 0.01);
+        assert!((GraphicEQLayout::ThirtyOneBand.q() - 4.32).abs() < 0.01);
+        assert!(eq.bands().iter().all(|b| b.validate().is_ok()));
+    }
+
+    #[test]
+    fn test_single_band_boost_matches_spacing() {
+        for (layout, band) in [
+            (GraphicEQLayout::TenBand, 5),
+            (GraphicEQLayout::ThirtyOneBand, 17),
+        ] {
+            let mut eq = GraphicEQ::with_layout(layout);
+            eq.set_gain(band, 12.0).unwrap();
+            let center = eq.frequencies()[band] as f64;
+            let spacing = 2f64.powf(layout.bandwidth_octaves());
+
+            let peak = response_db(&mut eq, center);
+            let midpoint = response_db(&mut eq, center * spacing.sqrt());
+            let neighbour = response_db(&mut eq, center * spacing);
+            let far = response_db(&mut eq, center * spacing.powi(3));
+
+            assert!((peak - 12.0).abs() < 0.5, "{:?} peak {}", layout, peak);
+            // Half the boost remains halfway to the next band...
+            assert!(
+                (midpoint - 6.0).abs() < 1.0,
+                "{:?} mid {}",
+                layout,
+                midpoint
+            );
+            // ...and the neighbour still hears some of it
+            assert!(
+                neighbour > 1.5 && neighbour < 6.0,
+                "{:?} {}",
+                layout,
+                neighbour
+            );
+            assert!(far < 1.0, "{:?} far {}", layout, far);
+        }
+    }
+
+    #[test]
+    fn test_gain_validation() {
+        let mut eq = GraphicEQ::new();
+        assert!(eq.set_gain(0, 13.0).is_err());
+        assert!(eq.set_gain(10, 3.0).is_err());
+        assert!(eq.set_gains(vec![0.0; 31]).is_err());
+        assert!(GraphicEQ::with_gains(vec![0.0; 12]).is_err());
+        assert_eq!(
+            GraphicEQ::with_gains(vec![0.0; 31]).unwrap().layout(),
+            GraphicEQLayout::ThirtyOneBand
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut gains = vec![0.0; 31];
+        gains[3] = -4.5;
+        let mut eq = GraphicEQ::with_gains(gains.clone()).unwrap();
+        eq.set_id("geq-1".to_string());
+
+        let json = eq.to_json().unwrap();
+        assert_eq!(json["gains_db"].as_array().unwrap().len(), 31);
+
+        let mut restored = GraphicEQ::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "geq-1");
+        assert_eq!(restored.layout(), GraphicEQLayout::ThirtyOneBand);
+        assert_eq!(restored.gains_db(), gains.as_slice());
+
+        let bad = serde_json::json!({"id": "x", "enabled": true, "gains_db": [0.0, 1.0]});
+        assert!(restored.from_json(&bad).is_err());
+    }
+}
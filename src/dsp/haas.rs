@@ -0,0 +1,427 @@
+//! Haas effect
+//!
+//! Widens a source by delaying one channel by a few milliseconds. The
+//! ear fuses the two copies and localizes toward the earlier side, so the
+//! image spreads without any modulation. Mono input is upmixed to stereo
+//! first.
+
+use super::effect::{Effect, EffectMetadata};
+use super::AudioBuffer;
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+
+/// Maximum Haas delay in milliseconds; longer delays are heard as echoes
+const MAX_HAAS_DELAY_MS: f32 = 40.0;
+
+/// Output correlation below which the mono sum largely cancels
+pub const HAAS_CANCELLATION_CORRELATION: f64 = -0.5;
+
+/// Channel that receives the delay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaasSide {
+    Left,
+    #[default]
+    Right,
+}
+
+impl HaasSide {
+    fn channel(self) -> usize {
+        match self {
+            HaasSide::Left => 0,
+            HaasSide::Right => 1,
+        }
+    }
+}
+
+/// Haas effect parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaasParams {
+    /// Delay in milliseconds (0 to 40 ms; 0 is a passthrough)
+    pub delay_ms: f32,
+    /// Channel that is delayed
+    pub side: HaasSide,
+}
+
+impl Default for HaasParams {
+    fn default() -> Self {
+        Self {
+            delay_ms: 15.0,
+            side: HaasSide::Right,
+        }
+    }
+}
+
+impl HaasParams {
+    /// Validate all parameters are within range
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=MAX_HAAS_DELAY_MS).contains(&self.delay_ms) {
+            return Err(NuevaError::InvalidParameter {
+                param: "delay_ms".to_string(),
+                value: self.delay_ms.to_string(),
+                expected: format!("0 to {} ms", MAX_HAAS_DELAY_MS),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Haas (precedence-effect) stereo widener
+#[derive(Debug, Clone)]
+pub struct Haas {
+    params: HaasParams,
+    id: String,
+    enabled: bool,
+    sample_rate: f64,
+    /// Whole-sample delay line for the delayed channel
+    line: Vec<f32>,
+    write_pos: usize,
+    /// Correlation of the last processed block
+    correlation: Option<f64>,
+    /// Whether the mono-cancellation warning has been logged
+    warned: bool,
+}
+
+impl Haas {
+    /// Create a Haas effect with default parameters
+    pub fn new() -> Self {
+        Self::with_params(HaasParams::default())
+    }
+
+    /// Create a Haas effect with the given parameters
+    pub fn with_params(params: HaasParams) -> Self {
+        let mut haas = Self {
+            params,
+            id: String::new(),
+            enabled: true,
+            sample_rate: 48000.0,
+            line: Vec::new(),
+            write_pos: 0,
+            correlation: None,
+            warned: false,
+        };
+        haas.resize_line();
+        haas
+    }
+
+    /// Get a reference to the current parameters
+    pub fn params(&self) -> &HaasParams {
+        &self.params
+    }
+
+    /// Set parameters with validation
+    pub fn set_params(&mut self, params: HaasParams) -> Result<()> {
+        params.validate()?;
+        self.params = params;
+        self.resize_line();
+        Ok(())
+    }
+
+    /// Set the delay in milliseconds
+    pub fn set_delay_ms(&mut self, ms: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.delay_ms = ms;
+        self.set_params(params)
+    }
+
+    /// Set which channel is delayed
+    pub fn set_side(&mut self, side: HaasSide) {
+        self.params.side = side;
+        self.reset();
+    }
+
+    /// Delay in whole samples at the current sample rate
+    pub fn delay_samples(&self) -> usize {
+        (self.params.delay_ms as f64 / 1000.0 * self.sample_rate).round() as usize
+    }
+
+    /// Stereo correlation of the last processed block
+    ///
+    /// Values near -1 mean the output collapses when summed to mono.
+    pub fn correlation(&self) -> Option<f64> {
+        self.correlation
+    }
+
+    fn resize_line(&mut self) {
+        let len = self.delay_samples();
+        if self.line.len() != len {
+            self.line = vec![0.0; len];
+            self.write_pos = 0;
+        }
+    }
+}
+
+impl Default for Haas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Haas {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled || self.line.is_empty() {
+            return;
+        }
+
+        buffer.upmix_to_stereo();
+        let channel = self.params.side.channel();
+        for frame in 0..buffer.num_samples() {
+            if let Some(sample) = buffer.get(frame, channel) {
+                buffer.set(frame, channel, self.line[self.write_pos]);
+                self.line[self.write_pos] = sample;
+                self.write_pos = (self.write_pos + 1) % self.line.len();
+            }
+        }
+
+        let correlation = buffer.stereo_correlation();
+        self.correlation = Some(correlation);
+        if correlation < HAAS_CANCELLATION_CORRELATION && !self.warned {
+            log::warn!(
+                "Haas delay of {} ms cancels in mono (correlation {:.2}); try a different delay",
+                self.params.delay_ms,
+                correlation
+            );
+            self.warned = true;
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        self.sample_rate = sample_rate;
+        self.resize_line();
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.line.fill(0.0);
+        self.write_pos = 0;
+        self.correlation = None;
+        self.warned = false;
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "effect_type": self.effect_type(),
+            "id": self.id,
+            "enabled": self.enabled,
+            "params": {
+                "delay_ms": self.params.delay_ms,
+                "side": self.params.side,
+            }
+        }))
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+            self.id = id.to_string();
+        }
+
+        if let Some(enabled) = json.get("enabled").and_then(|v| v.as_bool()) {
+            self.enabled = enabled;
+        }
+
+        if let Some(params) = json.get("params") {
+            let mut new_params = self.params.clone();
+
+            if let Some(v) = params.get("delay_ms").and_then(|v| v.as_f64()) {
+                new_params.delay_ms = v as f32;
+            }
+            if let Some(v) = params.get("side") {
+                new_params.side = serde_json::from_value(v.clone()).map_err(|e| {
+                    NuevaError::SerializationError {
+                        details: format!("side: {}", e),
+                    }
+                })?;
+            }
+
+            self.set_params(new_params)?;
+        }
+
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "haas"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Haas"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: "haas".to_string(),
+            display_name: "Haas".to_string(),
+            category: "time".to_string(),
+            order_priority: 5,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("delay_ms", 0.0, MAX_HAAS_DELAY_MS, 15.0).with_unit("ms"),
+            ParamSpec::options("side", &["left", "right"], "right"),
+        ]
+    }
+
+    fn tail_samples(&self) -> usize {
+        self.line.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(num_channels: usize, num_samples: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(num_channels, num_samples, 48000.0);
+        for i in 0..num_samples {
+            for ch in 0..num_channels {
+                buffer.set(i, ch, (i + 1) as f32 / num_samples as f32);
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_delays_one_channel_by_whole_samples() {
+        let mut haas = Haas::with_params(HaasParams {
+            delay_ms: 10.0,
+            side: HaasSide::Right,
+        });
+        haas.prepare(48000.0, 512);
+        assert_eq!(haas.delay_samples(), 480);
+
+        let dry = ramp(2, 2000);
+        let mut wet = dry.clone();
+        haas.process(&mut wet);
+
+        for i in 0..2000 {
+            assert_eq!(wet.get(i, 0), dry.get(i, 0));
+            let expected = if i < 480 {
+                Some(0.0)
+            } else {
+                dry.get(i - 480, 1)
+            };
+            assert_eq!(wet.get(i, 1), expected, "frame {}", i);
+        }
+    }
+
+    #[test]
+    fn test_delay_continues_across_blocks() {
+        let mut haas = Haas::with_params(HaasParams {
+            delay_ms: 1.0,
+            side: HaasSide::Left,
+        });
+        haas.prepare(48000.0, 32);
+
+        let dry = ramp(2, 200);
+        let mut whole = dry.clone();
+        Haas::with_params(haas.params().clone()).process(&mut whole);
+
+        let mut out = Vec::new();
+        for block in dry.samples().chunks(64) {
+            let mut buf = AudioBuffer::from_interleaved(block.to_vec(), 2, 48000.0).unwrap();
+            haas.process(&mut buf);
+            out.extend_from_slice(buf.samples());
+        }
+        assert_eq!(out, whole.samples());
+    }
+
+    #[test]
+    fn test_mono_input_is_upmixed() {
+        let mut haas = Haas::new();
+        haas.prepare(48000.0, 512);
+
+        let mut buffer = ramp(1, 1000);
+        haas.process(&mut buffer);
+        assert_eq!(buffer.num_channels(), 2);
+        assert_eq!(buffer.get(999, 0), Some(1.0));
+        assert_eq!(buffer.get(0, 1), Some(0.0));
+    }
+
+    #[test]
+    fn test_zero_delay_is_passthrough() {
+        let mut haas = Haas::with_params(HaasParams {
+            delay_ms: 0.0,
+            side: HaasSide::Right,
+        });
+        haas.prepare(48000.0, 512);
+
+        let dry = ramp(1, 100);
+        let mut wet = dry.clone();
+        haas.process(&mut wet);
+        assert_eq!(wet.num_channels(), 1);
+        assert_eq!(wet.samples(), dry.samples());
+        assert_eq!(haas.tail_samples(), 0);
+    }
+
+    #[test]
+    fn test_mono_cancellation_is_reported() {
+        // A 1 kHz tone delayed by half a period is inverted on one side
+        let mut haas = Haas::with_params(HaasParams {
+            delay_ms: 0.5,
+            side: HaasSide::Right,
+        });
+        haas.prepare(48000.0, 512);
+
+        let mut buffer = AudioBuffer::new(2, 4800, 48000.0);
+        for i in 0..4800 {
+            let s = (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin();
+            buffer.set(i, 0, s);
+            buffer.set(i, 1, s);
+        }
+        haas.process(&mut buffer);
+        assert!(haas.correlation().unwrap() < HAAS_CANCELLATION_CORRELATION);
+
+        // A broadband source keeps most of its mono energy
+        haas.set_delay_ms(15.0).unwrap();
+        haas.prepare(48000.0, 512);
+        let mut noise = AudioBuffer::new(2, 4800, 48000.0);
+        let mut seed = 1u32;
+        for i in 0..4800 {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let s = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+            noise.set(i, 0, s);
+            noise.set(i, 1, s);
+        }
+        haas.process(&mut noise);
+        assert!(haas.correlation().unwrap() > HAAS_CANCELLATION_CORRELATION);
+    }
+
+    #[test]
+    fn test_validation_and_serialization() {
+        assert!(Haas::new().set_delay_ms(41.0).is_err());
+        assert!(Haas::new().set_delay_ms(-1.0).is_err());
+
+        let mut haas = Haas::with_params(HaasParams {
+            delay_ms: 22.0,
+            side: HaasSide::Left,
+        });
+        haas.set_id("haas-1".to_string());
+        let json = haas.to_json().unwrap();
+        assert_eq!(json["params"]["side"], "left");
+
+        let mut restored = Haas::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "haas-1");
+        assert_eq!(restored.params().delay_ms, 22.0);
+        assert_eq!(restored.params().side, HaasSide::Left);
+    }
+}
@@ -3,40 +3,69 @@
 //! Provides traditional parameter-based audio effects:
 //! - Gain
 //! - Parametric EQ (with shelf and filter types)
+//! - Graphic EQ (10 and 31 bands)
 //! - Compressor
 //! - Gate
+//! - Expander
 //! - Limiter
 //! - Reverb
 //! - Delay
+//! - Haas (single-channel micro-delay widener)
 //! - Saturation
+//! - Ring modulator
+//! - Tremolo
+//! - Wah (LFO or envelope swept band-pass)
+//! - Pitch shifter (phase vocoder)
 
 mod audio_buffer;
+mod dc_blocker;
 mod effect;
+mod oversampler;
 
 // Effect implementations
 mod compressor;
 mod delay;
 mod eq;
+mod expander;
 mod gain;
 mod gate;
+mod graphic_eq;
+mod haas;
 mod limiter;
+mod pitch_shifter;
 mod reverb;
+mod ring_mod;
 mod saturation;
+mod tremolo;
+mod wah;
 
 // Effect chain
 mod chain;
+mod factory;
 
 // Re-exports
 pub use audio_buffer::AudioBuffer;
-pub use chain::{EffectChain, EffectPosition};
-pub use effect::{Effect, EffectMetadata, ProcessResult};
+pub use chain::{get_default_order_priority, ChainBypass, EffectChain, EffectPosition};
+pub use dc_blocker::{DcBlocker, DC_BLOCKER_CUTOFF_HZ};
+pub use effect::{Effect, EffectMetadata, EffectMeter, MixMode, ProcessResult, TAIL_DECAY_DB};
+pub use factory::{build_effect, create_effect, effect_from_json, effect_to_json, EFFECT_TYPES};
+pub use oversampler::{OversampleQuality, Oversampler, OVERSAMPLE_FACTORS, OVERSAMPLING_LATENCY};
 
 // Individual effects
 pub use compressor::Compressor;
-pub use delay::Delay;
+pub(crate) use delay::cubic_hermite;
+pub use delay::{Delay, DelayParams, DelayTap};
 pub use eq::{EQBand, FilterType, ParametricEQ};
+pub use expander::{Expander, ExpanderParams};
 pub use gain::GainEffect;
 pub use gate::Gate;
-pub use limiter::Limiter;
+pub use graphic_eq::{GraphicEQ, GraphicEQLayout, GRAPHIC_EQ_MAX_GAIN_DB};
+pub use haas::{Haas, HaasParams, HaasSide, HAAS_CANCELLATION_CORRELATION};
+pub use limiter::{true_peak_db, Limiter};
+pub(crate) use pitch_shifter::{fft, frame_size, stretch_channel};
+pub use pitch_shifter::{PitchShifter, PitchShifterParams, MAX_PITCH_SHIFT_SEMITONES};
 pub use reverb::{Reverb, ReverbParams};
+pub use ring_mod::{RingMod, RingModParams};
 pub use saturation::{Saturation, SaturationType};
+pub use tremolo::{LfoWaveform, Tremolo, TremoloParams, TREMOLO_SQUARE_SMOOTHING_MS};
+pub use wah::{Wah, WahMode, WahParams, WAH_MAX_SENSITIVITY_GAIN};
@@ -0,0 +1,756 @@
+//! Pitch shifter (phase vocoder)
+//!
+//! Each channel is cut into Hann-windowed frames with 4x overlap. Every
+//! frame is analyzed with an FFT, each bin's true frequency is estimated
+//! from its phase advance since the previous frame, and the bins are
+//! moved up or down by the pitch ratio. Phases are then propagated from
+//! the shifted frequencies and the frames resynthesized by overlap-add.
+//!
+//! The whole spectrum is scaled, so formants move with the pitch: vocals
+//! shifted far from their original key sound chipmunk-like or hollow.
+//! Formants are not preserved.
+//!
+//! A hop of output is finished only once the last frame overlapping it
+//! has been analyzed, so the output lags the input by one frame (see
+//! [`Effect::latency_samples`]). The dry signal is delayed to match.
+//!
+//! The same analysis drives offline time-stretching ([`stretch_channel`]):
+//! there the bins keep their frequencies and the frames are resynthesized
+//! further apart (or closer together) than they were analyzed.
+
+use super::effect::{Effect, EffectMetadata};
+use super::AudioBuffer;
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+/// Frame overlap (frames per frame length)
+const OVERSAMPLING: usize = 4;
+
+/// Approximate frame length in seconds, rounded up to a power of two in samples
+const FRAME_SECONDS: f64 = 0.04;
+
+/// Maximum shift in semitones, either way
+pub const MAX_PITCH_SHIFT_SEMITONES: f32 = 24.0;
+
+/// Pitch shifter parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PitchShifterParams {
+    /// Shift in semitones (-24 to 24)
+    pub semitones: f32,
+    /// Wet/dry mix (0 = dry, 1 = fully shifted)
+    pub mix: f32,
+}
+
+impl Default for PitchShifterParams {
+    fn default() -> Self {
+        Self {
+            semitones: 0.0,
+            mix: 1.0,
+        }
+    }
+}
+
+impl PitchShifterParams {
+    /// Validate all parameters are within range
+    pub fn validate(&self) -> Result<()> {
+        if !(-MAX_PITCH_SHIFT_SEMITONES..=MAX_PITCH_SHIFT_SEMITONES).contains(&self.semitones) {
+            return Err(NuevaError::InvalidParameter {
+                param: "semitones".to_string(),
+                value: self.semitones.to_string(),
+                expected: format!(
+                    "-{} to {} semitones",
+                    MAX_PITCH_SHIFT_SEMITONES, MAX_PITCH_SHIFT_SEMITONES
+                ),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.mix) {
+            return Err(NuevaError::InvalidParameter {
+                param: "mix".to_string(),
+                value: self.mix.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Frequency ratio of the shift
+    pub fn ratio(&self) -> f64 {
+        2.0_f64.powf(self.semitones as f64 / 12.0)
+    }
+}
+
+/// Frame length for `sample_rate`: [`FRAME_SECONDS`] rounded up to a power
+/// of two
+pub(crate) fn frame_size(sample_rate: f64) -> usize {
+    ((sample_rate * FRAME_SECONDS) as usize)
+        .next_power_of_two()
+        .max(OVERSAMPLING * 16)
+}
+
+/// In-place radix-2 FFT (length must be a power of two)
+///
+/// `inverse` flips the twiddle sign; no 1/N scaling is applied.
+pub(crate) fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * TAU / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Wrap a phase to -pi..pi
+fn wrap_phase(phase: f64) -> f64 {
+    phase - TAU * (phase / TAU).round()
+}
+
+/// Overlap-add state of one channel
+#[derive(Debug, Clone)]
+struct ChannelState {
+    /// Input of the current frame
+    input: Vec<f64>,
+    /// Finished output waiting to be read
+    output: Vec<f64>,
+    /// Overlap-add accumulator
+    accum: Vec<f64>,
+    /// Analysis phase of each bin in the previous frame
+    last_phase: Vec<f64>,
+    /// Synthesis phase of each bin
+    sum_phase: Vec<f64>,
+    /// Dry signal delayed by the latency
+    dry: Vec<f64>,
+}
+
+impl ChannelState {
+    fn new(fft_size: usize) -> Self {
+        Self {
+            input: vec![0.0; fft_size],
+            output: vec![0.0; fft_size],
+            accum: vec![0.0; fft_size],
+            last_phase: vec![0.0; fft_size / 2 + 1],
+            sum_phase: vec![0.0; fft_size / 2 + 1],
+            dry: vec![0.0; fft_size],
+        }
+    }
+}
+
+/// FFT buffers shared by all channels
+#[derive(Debug, Clone, Default)]
+struct Spectrum {
+    window: Vec<f64>,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    magnitude: Vec<f64>,
+    frequency: Vec<f64>,
+    shifted_magnitude: Vec<f64>,
+    shifted_frequency: Vec<f64>,
+    /// Overlap-add gain of the squared window
+    window_gain: f64,
+}
+
+impl Spectrum {
+    fn new(fft_size: usize) -> Self {
+        let hop = fft_size / OVERSAMPLING;
+        let window: Vec<f64> = (0..fft_size)
+            .map(|k| 0.5 - 0.5 * (TAU * k as f64 / fft_size as f64).cos())
+            .collect();
+        let window_gain = window.iter().map(|w| w * w).sum::<f64>() / hop as f64;
+        let bins = fft_size / 2 + 1;
+        Self {
+            window,
+            re: vec![0.0; fft_size],
+            im: vec![0.0; fft_size],
+            magnitude: vec![0.0; bins],
+            frequency: vec![0.0; bins],
+            shifted_magnitude: vec![0.0; bins],
+            shifted_frequency: vec![0.0; bins],
+            window_gain,
+        }
+    }
+
+    /// Shift one frame of `channel` by `ratio` and overlap-add it
+    ///
+    /// Frequencies are in bins, so nothing here depends on the sample rate.
+    fn shift_frame(&mut self, channel: &mut ChannelState, ratio: f64) {
+        let n = self.window.len();
+        let hop = n / OVERSAMPLING;
+        let bins = n / 2 + 1;
+        let expected_advance = TAU / OVERSAMPLING as f64;
+
+        for k in 0..n {
+            self.re[k] = channel.input[k] * self.window[k];
+            self.im[k] = 0.0;
+        }
+        fft(&mut self.re, &mut self.im, false);
+
+        // Analysis: true frequency of each bin from its phase advance
+        for k in 0..bins {
+            let phase = self.im[k].atan2(self.re[k]);
+            let deviation = wrap_phase(phase - channel.last_phase[k] - k as f64 * expected_advance);
+            channel.last_phase[k] = phase;
+            self.magnitude[k] = self.re[k].hypot(self.im[k]);
+            self.frequency[k] = k as f64 + deviation / expected_advance;
+        }
+
+        // Move the bins
+        self.shifted_magnitude.fill(0.0);
+        self.shifted_frequency.fill(0.0);
+        for k in 0..bins {
+            let target = (k as f64 * ratio).round() as usize;
+            if target < bins {
+                self.shifted_magnitude[target] += self.magnitude[k];
+                self.shifted_frequency[target] = self.frequency[k] * ratio;
+            }
+        }
+
+        // Synthesis: propagate phases and rebuild a conjugate-symmetric spectrum
+        for k in 0..bins {
+            channel.sum_phase[k] += self.shifted_frequency[k] * expected_advance;
+            let (sin, cos) = channel.sum_phase[k].sin_cos();
+            self.re[k] = self.shifted_magnitude[k] * cos;
+            self.im[k] = self.shifted_magnitude[k] * sin;
+        }
+        for k in 1..n / 2 {
+            self.re[n - k] = self.re[k];
+            self.im[n - k] = -self.im[k];
+        }
+        fft(&mut self.re, &mut self.im, true);
+
+        let scale = 1.0 / (n as f64 * self.window_gain);
+        for k in 0..n {
+            channel.accum[k] += self.re[k] * self.window[k] * scale;
+        }
+
+        channel.output[..hop].copy_from_slice(&channel.accum[..hop]);
+        channel.accum.copy_within(hop.., 0);
+        channel.accum[n - hop..].fill(0.0);
+        channel.input.copy_within(hop.., 0);
+    }
+}
+
+/// Stretch one channel to `ratio` times its length, keeping its pitch
+///
+/// Synthesis frames are laid out every hop of the output. Each is built
+/// from the input frame at the matching input time. The frequency of each
+/// spectral peak is measured from its phase advance between that frame and
+/// the one a hop before it and integrated into a running synthesis phase;
+/// the bins around a peak keep their analyzed phase relative to it
+/// (identity phase locking), so partials stay coherent rather than
+/// phasey. At a ratio of 1 the output phases equal the input's and the
+/// input is reconstructed. The output has `round(len * ratio)` samples.
+///
+/// Sines and other steady partials come out at their original
+/// frequencies. Transients are smeared over up to one frame (`fft_size`
+/// samples) and lose some attack, increasingly so as the ratio moves away
+/// from 1, and each channel is processed on its own, so stereo image can
+/// soften.
+pub(crate) fn stretch_channel(input: &[f32], ratio: f64, fft_size: usize) -> Vec<f32> {
+    let n = fft_size;
+    let hop = n / OVERSAMPLING;
+    let bins = n / 2 + 1;
+    let expected_advance = TAU / OVERSAMPLING as f64;
+    let out_len = (input.len() as f64 * ratio).round() as usize;
+
+    let mut spectrum = Spectrum::new(n);
+    let mut previous_phase = vec![0.0; bins];
+    let mut phase = vec![0.0; bins];
+    let mut synthesis_phase = vec![0.0; bins];
+    let mut locked_phase = vec![0.0; bins];
+    let mut peaks = Vec::with_capacity(bins);
+    let mut output = vec![0.0; out_len];
+
+    let analyze = |spectrum: &mut Spectrum, start: i64| {
+        for k in 0..n {
+            let index = start + k as i64;
+            let sample = usize::try_from(index)
+                .ok()
+                .and_then(|i| input.get(i))
+                .copied()
+                .unwrap_or(0.0);
+            spectrum.re[k] = sample as f64 * spectrum.window[k];
+            spectrum.im[k] = 0.0;
+        }
+        fft(&mut spectrum.re, &mut spectrum.im, false);
+    };
+
+    // Synthesis frame m covers output [m * hop - n, m * hop), so every
+    // output sample gets the full overlap of OVERSAMPLING frames
+    let frames = (out_len + n).div_ceil(hop) + 1;
+    for m in 0..frames {
+        let out_start = (m * hop) as i64 - n as i64;
+        let center = (out_start as f64 + n as f64 / 2.0) / ratio;
+        let in_start = (center - n as f64 / 2.0).round() as i64;
+
+        analyze(&mut spectrum, in_start - hop as i64);
+        for (k, previous) in previous_phase.iter_mut().enumerate() {
+            *previous = spectrum.im[k].atan2(spectrum.re[k]);
+        }
+        analyze(&mut spectrum, in_start);
+
+        for (k, phase) in phase.iter_mut().enumerate() {
+            *phase = spectrum.im[k].atan2(spectrum.re[k]);
+            spectrum.magnitude[k] = spectrum.re[k].hypot(spectrum.im[k]);
+        }
+
+        if m == 0 {
+            synthesis_phase.copy_from_slice(&phase);
+        } else {
+            let magnitude = &spectrum.magnitude;
+            peaks.clear();
+            peaks.extend((0..bins).filter(|&k| {
+                (k == 0 || magnitude[k] > magnitude[k - 1])
+                    && (k + 1 == bins || magnitude[k] >= magnitude[k + 1])
+            }));
+
+            // Advance each peak by its measured frequency
+            for &peak in &peaks {
+                let deviation =
+                    wrap_phase(phase[peak] - previous_phase[peak] - peak as f64 * expected_advance);
+                locked_phase[peak] =
+                    synthesis_phase[peak] + peak as f64 * expected_advance + deviation;
+            }
+
+            // Every other bin keeps its analyzed offset from the nearest peak
+            let mut nearest = 0;
+            for k in 0..bins {
+                while nearest + 1 < peaks.len()
+                    && peaks[nearest + 1].abs_diff(k) < peaks[nearest].abs_diff(k)
+                {
+                    nearest += 1;
+                }
+                let peak = peaks[nearest];
+                if k != peak {
+                    locked_phase[k] = locked_phase[peak] + phase[k] - phase[peak];
+                }
+            }
+            std::mem::swap(&mut synthesis_phase, &mut locked_phase);
+        }
+
+        for (k, phase) in synthesis_phase.iter().enumerate() {
+            let (sin, cos) = phase.sin_cos();
+            spectrum.re[k] = spectrum.magnitude[k] * cos;
+            spectrum.im[k] = spectrum.magnitude[k] * sin;
+        }
+        for k in 1..n / 2 {
+            spectrum.re[n - k] = spectrum.re[k];
+            spectrum.im[n - k] = -spectrum.im[k];
+        }
+        fft(&mut spectrum.re, &mut spectrum.im, true);
+
+        let scale = 1.0 / (n as f64 * spectrum.window_gain);
+        for k in 0..n {
+            let index = out_start + k as i64;
+            if let Some(sample) = usize::try_from(index).ok().and_then(|i| output.get_mut(i)) {
+                *sample += spectrum.re[k] * spectrum.window[k] * scale;
+            }
+        }
+    }
+
+    output.into_iter().map(|s| s as f32).collect()
+}
+
+/// Phase-vocoder pitch shifter
+#[derive(Debug, Clone)]
+pub struct PitchShifter {
+    params: PitchShifterParams,
+    id: String,
+    enabled: bool,
+    fft_size: usize,
+    spectrum: Spectrum,
+    channels: Vec<ChannelState>,
+    /// Write position in the current frame, shared by all channels
+    position: usize,
+    /// Position in the dry delay lines
+    dry_position: usize,
+}
+
+impl PitchShifter {
+    /// Create a pitch shifter with default parameters
+    pub fn new() -> Self {
+        Self::with_params(PitchShifterParams::default())
+    }
+
+    /// Create a pitch shifter with the given parameters
+    pub fn with_params(params: PitchShifterParams) -> Self {
+        let mut shifter = Self {
+            params,
+            id: String::new(),
+            enabled: true,
+            fft_size: 0,
+            spectrum: Spectrum::default(),
+            channels: Vec::new(),
+            position: 0,
+            dry_position: 0,
+        };
+        shifter.prepare(48000.0, 512);
+        shifter
+    }
+
+    /// Get a reference to the current parameters
+    pub fn params(&self) -> &PitchShifterParams {
+        &self.params
+    }
+
+    /// Set parameters with validation
+    pub fn set_params(&mut self, params: PitchShifterParams) -> Result<()> {
+        params.validate()?;
+        self.params = params;
+        Ok(())
+    }
+
+    /// Set the shift in semitones
+    pub fn set_semitones(&mut self, semitones: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.semitones = semitones;
+        self.set_params(params)
+    }
+
+    /// Set the wet/dry mix
+    pub fn set_mix(&mut self, mix: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.mix = mix;
+        self.set_params(params)
+    }
+
+    /// FFT frame length in samples
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    fn hop(&self) -> usize {
+        self.fft_size / OVERSAMPLING
+    }
+
+    /// First write position of a frame; the rest of the frame is the
+    /// overlap kept from the previous one
+    fn frame_start(&self) -> usize {
+        self.fft_size - self.hop()
+    }
+}
+
+impl Default for PitchShifter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for PitchShifter {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled {
+            return;
+        }
+
+        let num_channels = buffer.num_channels().max(1);
+        if self.channels.len() != num_channels {
+            self.channels = vec![ChannelState::new(self.fft_size); num_channels];
+            self.position = self.frame_start();
+            self.dry_position = 0;
+        }
+
+        let frame_start = self.frame_start();
+        let ratio = self.params.ratio();
+        let wet = self.params.mix as f64;
+        let dry = 1.0 - wet;
+
+        for frame in buffer.samples_mut().chunks_mut(num_channels) {
+            let read = self.position - frame_start;
+            for (sample, channel) in frame.iter_mut().zip(&mut self.channels) {
+                let input = *sample as f64;
+                let delayed = std::mem::replace(&mut channel.dry[self.dry_position], input);
+                channel.input[self.position] = input;
+                *sample = (delayed * dry + channel.output[read] * wet) as f32;
+            }
+
+            self.dry_position = (self.dry_position + 1) % self.fft_size;
+            self.position += 1;
+            if self.position == self.fft_size {
+                for channel in &mut self.channels {
+                    self.spectrum.shift_frame(channel, ratio);
+                }
+                self.position = frame_start;
+            }
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        let fft_size = frame_size(sample_rate);
+        if fft_size != self.fft_size {
+            self.fft_size = fft_size;
+            self.spectrum = Spectrum::new(fft_size);
+        }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.channels.clear();
+        self.position = self.frame_start();
+        self.dry_position = 0;
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "effect_type": self.effect_type(),
+            "id": self.id,
+            "enabled": self.enabled,
+            "params": {
+                "semitones": self.params.semitones,
+                "mix": self.params.mix,
+            }
+        }))
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+            self.id = id.to_string();
+        }
+
+        if let Some(enabled) = json.get("enabled").and_then(|v| v.as_bool()) {
+            self.enabled = enabled;
+        }
+
+        if let Some(params) = json.get("params") {
+            let mut new_params = self.params.clone();
+
+            if let Some(v) = params.get("semitones").and_then(|v| v.as_f64()) {
+                new_params.semitones = v as f32;
+            }
+            if let Some(v) = params.get("mix").and_then(|v| v.as_f64()) {
+                new_params.mix = v as f32;
+            }
+
+            self.set_params(new_params)?;
+        }
+
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "pitch-shifter"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Pitch Shifter"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: "pitch-shifter".to_string(),
+            display_name: "Pitch Shifter".to_string(),
+            category: "pitch".to_string(),
+            order_priority: 4, // Creative color, alongside saturation
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float(
+                "semitones",
+                -MAX_PITCH_SHIFT_SEMITONES,
+                MAX_PITCH_SHIFT_SEMITONES,
+                0.0,
+            )
+            .with_unit("st"),
+            ParamSpec::float("mix", 0.0, 1.0, 1.0),
+        ]
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.fft_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, num_samples: usize, num_channels: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(num_channels, num_samples, 48000.0);
+        for i in 0..num_samples {
+            let s = (2.0 * std::f32::consts::PI * freq * i as f32 / 48000.0).sin() * 0.5;
+            for ch in 0..num_channels {
+                buffer.set(i, ch, s);
+            }
+        }
+        buffer
+    }
+
+    /// Frequency of the strongest FFT bin of one channel after `skip` frames
+    fn peak_frequency(buffer: &AudioBuffer, channel: usize, skip: usize) -> f64 {
+        let n = 8192;
+        let mut re: Vec<f64> = (0..n)
+            .map(|i| {
+                let w = 0.5 - 0.5 * (TAU * i as f64 / n as f64).cos();
+                buffer.get(skip + i, channel).unwrap() as f64 * w
+            })
+            .collect();
+        let mut im = vec![0.0; n];
+        fft(&mut re, &mut im, false);
+        let peak = (1..n / 2)
+            .max_by(|&a, &b| re[a].hypot(im[a]).total_cmp(&re[b].hypot(im[b])))
+            .unwrap();
+        peak as f64 * 48000.0 / n as f64
+    }
+
+    #[test]
+    fn test_fft_round_trip() {
+        let input: Vec<f64> = (0..64).map(|i| (i as f64 * 0.3).sin()).collect();
+        let mut re = input.clone();
+        let mut im = vec![0.0; 64];
+        fft(&mut re, &mut im, false);
+        fft(&mut re, &mut im, true);
+        for (a, b) in re.iter().zip(&input) {
+            assert!((a / 64.0 - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_zero_semitones_is_passthrough() {
+        let mut shifter = PitchShifter::new();
+        shifter.prepare(48000.0, 512);
+        let latency = shifter.latency_samples();
+        assert_eq!(shifter.fft_size(), 2048);
+        assert_eq!(latency, 2048);
+
+        let dry = sine(440.0, 24000, 1);
+        let mut wet = dry.clone();
+        shifter.process(&mut wet);
+
+        for i in 0..24000 - latency {
+            let error = (wet.get(i + latency, 0).unwrap() - dry.get(i, 0).unwrap()).abs();
+            assert!(error < 1e-4, "sample {}: error {}", i, error);
+        }
+    }
+
+    #[test]
+    fn test_octave_up_moves_peak_to_880_hz() {
+        let mut shifter = PitchShifter::with_params(PitchShifterParams {
+            semitones: 12.0,
+            mix: 1.0,
+        });
+        shifter.prepare(48000.0, 512);
+
+        let mut buffer = sine(440.0, 24000, 1);
+        shifter.process(&mut buffer);
+
+        let peak = peak_frequency(&buffer, 0, 8000);
+        assert!((peak - 880.0).abs() < 10.0, "peak at {} Hz", peak);
+    }
+
+    #[test]
+    fn test_stereo_channels_are_shifted_independently() {
+        let mut shifter = PitchShifter::with_params(PitchShifterParams {
+            semitones: -12.0,
+            mix: 1.0,
+        });
+        shifter.prepare(48000.0, 512);
+
+        let mut buffer = AudioBuffer::new(2, 24000, 48000.0);
+        for i in 0..24000 {
+            let t = i as f32 / 48000.0;
+            buffer.set(i, 0, (2.0 * std::f32::consts::PI * 880.0 * t).sin() * 0.5);
+            buffer.set(i, 1, (2.0 * std::f32::consts::PI * 1320.0 * t).sin() * 0.5);
+        }
+        shifter.process(&mut buffer);
+
+        assert!((peak_frequency(&buffer, 0, 8000) - 440.0).abs() < 10.0);
+        assert!((peak_frequency(&buffer, 1, 8000) - 660.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_block_size_does_not_change_output() {
+        let params = PitchShifterParams {
+            semitones: 5.0,
+            mix: 0.6,
+        };
+        let input = sine(300.0, 12000, 2);
+
+        let mut whole = input.clone();
+        let mut reference = PitchShifter::with_params(params.clone());
+        reference.prepare(48000.0, 12000);
+        reference.process(&mut whole);
+
+        let mut blocked = PitchShifter::with_params(params);
+        blocked.prepare(48000.0, 100);
+        let mut out = Vec::new();
+        for chunk in input.samples().chunks(200) {
+            let mut buf = AudioBuffer::from_interleaved(chunk.to_vec(), 2, 48000.0).unwrap();
+            blocked.process(&mut buf);
+            out.extend_from_slice(buf.samples());
+        }
+        assert_eq!(out, whole.samples());
+    }
+
+    #[test]
+    fn test_prepare_sizes_frames_from_sample_rate() {
+        let mut shifter = PitchShifter::new();
+        shifter.prepare(96000.0, 512);
+        assert_eq!(shifter.fft_size(), 4096);
+        assert_eq!(shifter.latency_samples(), 4096);
+        shifter.prepare(44100.0, 512);
+        assert_eq!(shifter.fft_size(), 2048);
+    }
+
+    #[test]
+    fn test_validation_and_serialization() {
+        assert!(PitchShifter::new().set_semitones(25.0).is_err());
+        assert!(PitchShifter::new().set_mix(1.5).is_err());
+
+        let mut shifter = PitchShifter::with_params(PitchShifterParams {
+            semitones: -7.0,
+            mix: 0.4,
+        });
+        shifter.set_id("pitch-1".to_string());
+        let json = shifter.to_json().unwrap();
+
+        let mut restored = PitchShifter::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "pitch-1");
+        assert_eq!(restored.params().semitones, -7.0);
+        assert_eq!(restored.params().mix, 0.4);
+    }
+}
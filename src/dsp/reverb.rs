@@ -1,14 +1,19 @@
 //! Reverb effect implementation (spec section 4.2.4)
 //!
 //! Implements the Freeverb algorithm:
-//! - 8 parallel comb filters for early reflections
+//! - 8 parallel comb filters for the late tail
 //! - 4 series allpass filters for diffusion
 //! - Stereo width control
 //! - Pre-delay buffer
+//!
+//! An early-reflection tap network sits alongside the comb bank. It reads
+//! discrete taps from the pre-delayed input, all landing before the first
+//! comb delay, and is mixed with the tail via `early_level`/`late_level`.
 
-use super::effect::{Effect, EffectMetadata};
+use super::effect::{repeats_to_decay, Effect, EffectMetadata, MixMode};
 use super::AudioBuffer;
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -36,12 +41,37 @@ const ROOM_SCALE: f32 = 0.28;
 /// Offset for room size parameter to feedback
 const ROOM_OFFSET: f32 = 0.7;
 
+/// Comb feedback ceiling; unity is only reached in freeze mode, where the
+/// loop is lossless and receives no new input
+const MAX_FEEDBACK: f32 = 1.0;
+
 /// Scale factor for damping parameter
 const DAMP_SCALE: f32 = 0.4;
 
 /// Maximum pre-delay time in milliseconds
 const MAX_PRE_DELAY_MS: f32 = 100.0;
 
+/// Early reflection tap times as fractions of `early_time_ms`
+const EARLY_TAP_TIMES: [f32; 8] = [0.12, 0.21, 0.30, 0.41, 0.52, 0.66, 0.81, 1.0];
+
+/// Early reflection tap gains (alternating sign for decorrelation)
+const EARLY_TAP_GAINS: [f32; 8] = [0.30, -0.22, 0.18, -0.14, 0.12, -0.10, 0.08, -0.06];
+
+/// Minimum early reflection time in milliseconds
+const MIN_EARLY_TIME_MS: f32 = 1.0;
+
+/// Maximum early reflection time in milliseconds (the first comb delay
+/// is ~25 ms, so reflections always arrive ahead of the tail)
+const MAX_EARLY_TIME_MS: f32 = 24.0;
+
+fn default_early_time_ms() -> f32 {
+    15.0
+}
+
+fn default_late_level() -> f32 {
+    1.0
+}
+
 // ============================================================================
 // Parameter Structs
 // ============================================================================
@@ -61,6 +91,21 @@ pub struct ReverbParams {
     pub width: f32,
     /// Pre-delay in milliseconds: 0 to 100
     pub pre_delay_ms: f32,
+    /// Early reflection level: 0 to 1
+    #[serde(default)]
+    pub early_level: f32,
+    /// Time of the last early reflection in milliseconds: 1 to 24
+    #[serde(default = "default_early_time_ms")]
+    pub early_time_ms: f32,
+    /// Late tail (comb/allpass) level: 0 to 1
+    #[serde(default = "default_late_level")]
+    pub late_level: f32,
+    /// Freeze: hold the current tail indefinitely and ignore new input
+    #[serde(default)]
+    pub freeze: bool,
+    /// How dry and wet levels are mixed
+    #[serde(default)]
+    pub mix_mode: MixMode,
 }
 
 impl Default for ReverbParams {
@@ -72,6 +117,11 @@ impl Default for ReverbParams {
             dry_level: 1.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            early_level: 0.0,
+            early_time_ms: default_early_time_ms(),
+            late_level: default_late_level(),
+            freeze: false,
+            mix_mode: MixMode::Linear,
         }
     }
 }
@@ -121,6 +171,27 @@ impl ReverbParams {
                 expected: format!("0.0 to {} ms", MAX_PRE_DELAY_MS),
             });
         }
+        if self.early_level < 0.0 || self.early_level > 1.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "early_level".to_string(),
+                value: self.early_level.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
+        if self.early_time_ms < MIN_EARLY_TIME_MS || self.early_time_ms > MAX_EARLY_TIME_MS {
+            return Err(NuevaError::InvalidParameter {
+                param: "early_time_ms".to_string(),
+                value: self.early_time_ms.to_string(),
+                expected: format!("{} to {} ms", MIN_EARLY_TIME_MS, MAX_EARLY_TIME_MS),
+            });
+        }
+        if self.late_level < 0.0 || self.late_level > 1.0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "late_level".to_string(),
+                value: self.late_level.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
         Ok(())
     }
 }
@@ -294,6 +365,59 @@ impl PreDelayBuffer {
     }
 }
 
+/// Early reflection tap network
+///
+/// A delay line read at several discrete taps, each with its own gain.
+#[derive(Debug, Clone)]
+struct EarlyReflections {
+    /// Circular buffer for samples
+    buffer: Vec<f32>,
+    /// Current write position
+    write_pos: usize,
+    /// Buffer size mask for efficient wrapping
+    mask: usize,
+    /// Tap delays in samples
+    tap_delays: [usize; 8],
+}
+
+impl EarlyReflections {
+    /// Create a new tap network with the given maximum delay
+    fn new(max_size: usize) -> Self {
+        let size = (max_size + 1).next_power_of_two();
+        Self {
+            buffer: vec![0.0; size],
+            write_pos: 0,
+            mask: size - 1,
+            tap_delays: [1; 8],
+        }
+    }
+
+    /// Set the tap delays, limited to the buffer length
+    fn set_tap_delays(&mut self, delays: [usize; 8]) {
+        self.tap_delays = delays.map(|d| d.clamp(1, self.mask));
+    }
+
+    /// Write a sample and return the weighted sum of the taps
+    fn process(&mut self, input: f32) -> f32 {
+        self.buffer[self.write_pos] = input;
+
+        let mut output = 0.0;
+        for (&delay, &gain) in self.tap_delays.iter().zip(&EARLY_TAP_GAINS) {
+            let read_pos = (self.write_pos + self.mask + 1 - delay) & self.mask;
+            output += self.buffer[read_pos] * gain;
+        }
+
+        self.write_pos = (self.write_pos + 1) & self.mask;
+        output
+    }
+
+    /// Clear the buffer
+    fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_pos = 0;
+    }
+}
+
 // ============================================================================
 // Main Reverb Effect
 // ============================================================================
@@ -333,6 +457,11 @@ pub struct Reverb {
     /// Pre-delay buffer for right channel
     pre_delay_right: PreDelayBuffer,
 
+    /// Early reflection taps for left channel
+    early_left: EarlyReflections,
+    /// Early reflection taps for right channel
+    early_right: EarlyReflections,
+
     /// Scaled comb filter delays for current sample rate
     scaled_comb_delays_left: [usize; 8],
     scaled_comb_delays_right: [usize; 8],
@@ -365,6 +494,11 @@ impl Reverb {
         let pre_delay_left = PreDelayBuffer::new(10000);
         let pre_delay_right = PreDelayBuffer::new(10000);
 
+        // Early reflection buffers sized for 96kHz
+        let max_early = early_buffer_size(96000.0);
+        let early_left = EarlyReflections::new(max_early);
+        let early_right = EarlyReflections::new(max_early);
+
         let mut reverb = Self {
             params,
             id: String::new(),
@@ -376,6 +510,8 @@ impl Reverb {
             allpass_right,
             pre_delay_left,
             pre_delay_right,
+            early_left,
+            early_right,
             scaled_comb_delays_left: COMB_DELAYS,
             scaled_comb_delays_right: std::array::from_fn(|i| COMB_DELAYS[i] + STEREO_SPREAD),
             scaled_allpass_delays_left: ALLPASS_DELAYS,
@@ -384,6 +520,7 @@ impl Reverb {
         };
 
         reverb.update_coefficients();
+        reverb.update_early_taps();
         reverb
     }
 
@@ -398,6 +535,7 @@ impl Reverb {
         self.params = params;
         self.update_coefficients();
         self.update_pre_delay();
+        self.update_early_taps();
         Ok(())
     }
 
@@ -443,14 +581,47 @@ impl Reverb {
         self.set_params(params)
     }
 
+    /// Set early reflection level (0 to 1)
+    pub fn set_early_level(&mut self, early_level: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.early_level = early_level;
+        self.set_params(params)
+    }
+
+    /// Set early reflection time in milliseconds (1 to 24)
+    pub fn set_early_time(&mut self, early_time_ms: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.early_time_ms = early_time_ms;
+        self.set_params(params)
+    }
+
+    /// Set late tail level (0 to 1)
+    pub fn set_late_level(&mut self, late_level: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.late_level = late_level;
+        self.set_params(params)
+    }
+
+    /// Enable or disable freeze (infinite sustain)
+    pub fn set_freeze(&mut self, freeze: bool) {
+        self.params.freeze = freeze;
+        self.update_coefficients();
+    }
+
     /// Update filter coefficients based on current parameters
     fn update_coefficients(&mut self) {
-        // Calculate feedback from room size
-        let feedback = self.params.room_size * ROOM_SCALE + ROOM_OFFSET;
-
-        // Calculate damping coefficients
-        let damp1 = 1.0 - self.params.damping * DAMP_SCALE;
-        let damp2 = self.params.damping * DAMP_SCALE;
+        // Freeze turns the combs into lossless loops: unity feedback and
+        // no damping, so the tail neither decays nor grows
+        let (feedback, damp1, damp2) = if self.params.freeze {
+            (MAX_FEEDBACK, 1.0, 0.0)
+        } else {
+            (
+                self.params.room_size * ROOM_SCALE + ROOM_OFFSET,
+                1.0 - self.params.damping * DAMP_SCALE,
+                self.params.damping * DAMP_SCALE,
+            )
+        };
+        let feedback = feedback.min(MAX_FEEDBACK);
 
         // Update all comb filters
         for comb in &mut self.comb_left {
@@ -467,23 +638,34 @@ impl Reverb {
             ((self.params.pre_delay_ms / 1000.0) * self.sample_rate as f32) as usize;
     }
 
+    /// Place the early reflection taps for the current time and sample rate
+    ///
+    /// The right channel's taps are offset by the stereo spread.
+    fn update_early_taps(&mut self) {
+        let span = (self.params.early_time_ms / 1000.0) * self.sample_rate as f32;
+        let spread = (STEREO_SPREAD as f64 * self.sample_rate / REFERENCE_SAMPLE_RATE) as f32;
+        let left = EARLY_TAP_TIMES.map(|t| ((t * span) as usize).max(1));
+        let right = EARLY_TAP_TIMES.map(|t| ((t * span + spread) as usize).max(1));
+        self.early_left.set_tap_delays(left);
+        self.early_right.set_tap_delays(right);
+    }
+
     /// Scale filter delays for the current sample rate
     fn scale_delays(&mut self) {
         let scale = self.sample_rate / REFERENCE_SAMPLE_RATE;
 
         // Scale comb delays
-        for i in 0..8 {
-            self.scaled_comb_delays_left[i] = ((COMB_DELAYS[i] as f64 * scale) as usize).max(1);
+        for (i, &delay) in COMB_DELAYS.iter().enumerate() {
+            self.scaled_comb_delays_left[i] = ((delay as f64 * scale) as usize).max(1);
             self.scaled_comb_delays_right[i] =
-                (((COMB_DELAYS[i] + STEREO_SPREAD) as f64 * scale) as usize).max(1);
+                (((delay + STEREO_SPREAD) as f64 * scale) as usize).max(1);
         }
 
         // Scale allpass delays
-        for i in 0..4 {
-            self.scaled_allpass_delays_left[i] =
-                ((ALLPASS_DELAYS[i] as f64 * scale) as usize).max(1);
+        for (i, &delay) in ALLPASS_DELAYS.iter().enumerate() {
+            self.scaled_allpass_delays_left[i] = ((delay as f64 * scale) as usize).max(1);
             self.scaled_allpass_delays_right[i] =
-                (((ALLPASS_DELAYS[i] + STEREO_SPREAD) as f64 * scale) as usize).max(1);
+                (((delay + STEREO_SPREAD) as f64 * scale) as usize).max(1);
         }
     }
 
@@ -492,18 +674,17 @@ impl Reverb {
         let scale = self.sample_rate / REFERENCE_SAMPLE_RATE;
 
         // Resize comb filters
-        for i in 0..8 {
-            let left_size = ((COMB_DELAYS[i] as f64 * scale) as usize + 1).max(16);
-            let right_size = (((COMB_DELAYS[i] + STEREO_SPREAD) as f64 * scale) as usize + 1).max(16);
+        for (i, &delay) in COMB_DELAYS.iter().enumerate() {
+            let left_size = ((delay as f64 * scale) as usize + 1).max(16);
+            let right_size = (((delay + STEREO_SPREAD) as f64 * scale) as usize + 1).max(16);
             self.comb_left[i] = CombFilter::new(left_size);
             self.comb_right[i] = CombFilter::new(right_size);
         }
 
         // Resize allpass filters
-        for i in 0..4 {
-            let left_size = ((ALLPASS_DELAYS[i] as f64 * scale) as usize + 1).max(16);
-            let right_size =
-                (((ALLPASS_DELAYS[i] + STEREO_SPREAD) as f64 * scale) as usize + 1).max(16);
+        for (i, &delay) in ALLPASS_DELAYS.iter().enumerate() {
+            let left_size = ((delay as f64 * scale) as usize + 1).max(16);
+            let right_size = (((delay + STEREO_SPREAD) as f64 * scale) as usize + 1).max(16);
             self.allpass_left[i] = AllpassFilter::new(left_size);
             self.allpass_right[i] = AllpassFilter::new(right_size);
         }
@@ -513,6 +694,11 @@ impl Reverb {
         self.pre_delay_left = PreDelayBuffer::new(max_pre_delay);
         self.pre_delay_right = PreDelayBuffer::new(max_pre_delay);
 
+        // Resize early reflection buffers
+        let max_early = early_buffer_size(self.sample_rate);
+        self.early_left = EarlyReflections::new(max_early);
+        self.early_right = EarlyReflections::new(max_early);
+
         // Update coefficients after resizing
         self.update_coefficients();
     }
@@ -520,8 +706,13 @@ impl Reverb {
     /// Process mono audio
     fn process_mono(&mut self, buffer: &mut AudioBuffer) {
         let num_samples = buffer.num_samples();
-        let wet_level = self.params.wet_level;
-        let dry_level = self.params.dry_level;
+        let (dry_level, wet_level) = self
+            .params
+            .mix_mode
+            .gains(self.params.dry_level, self.params.wet_level);
+        let early_level = self.params.early_level;
+        let late_level = self.params.late_level;
+        let freeze = self.params.freeze;
 
         for i in 0..num_samples {
             let input = buffer.get(i, 0).unwrap_or(0.0);
@@ -534,10 +725,10 @@ impl Reverb {
             };
 
             // Sum outputs from all comb filters in parallel
+            let comb_input = if freeze { 0.0 } else { delayed_input };
             let mut comb_sum = 0.0;
             for j in 0..8 {
-                comb_sum +=
-                    self.comb_left[j].process(delayed_input, self.scaled_comb_delays_left[j]);
+                comb_sum += self.comb_left[j].process(comb_input, self.scaled_comb_delays_left[j]);
             }
 
             // Process through allpass filters in series
@@ -546,6 +737,10 @@ impl Reverb {
                 output = self.allpass_left[j].process(output, self.scaled_allpass_delays_left[j]);
             }
 
+            // Blend the late tail with the early reflections
+            let early = self.early_left.process(delayed_input);
+            let output = output * late_level + early * early_level;
+
             // Mix dry and wet
             let mixed = input * dry_level + output * wet_level;
             buffer.set(i, 0, mixed);
@@ -555,9 +750,14 @@ impl Reverb {
     /// Process stereo audio
     fn process_stereo(&mut self, buffer: &mut AudioBuffer) {
         let num_samples = buffer.num_samples();
-        let wet_level = self.params.wet_level;
-        let dry_level = self.params.dry_level;
+        let (dry_level, wet_level) = self
+            .params
+            .mix_mode
+            .gains(self.params.dry_level, self.params.wet_level);
         let width = self.params.width;
+        let early_level = self.params.early_level;
+        let late_level = self.params.late_level;
+        let freeze = self.params.freeze;
 
         // Width coefficients: at width=0, both channels get mono sum
         // at width=1, full stereo separation
@@ -584,13 +784,18 @@ impl Reverb {
             };
 
             // Process through comb filters (parallel)
+            let (comb_in_left, comb_in_right) = if freeze {
+                (0.0, 0.0)
+            } else {
+                (delayed_left, delayed_right)
+            };
             let mut comb_left_sum = 0.0;
             let mut comb_right_sum = 0.0;
             for j in 0..8 {
                 comb_left_sum +=
-                    self.comb_left[j].process(delayed_left, self.scaled_comb_delays_left[j]);
+                    self.comb_left[j].process(comb_in_left, self.scaled_comb_delays_left[j]);
                 comb_right_sum +=
-                    self.comb_right[j].process(delayed_right, self.scaled_comb_delays_right[j]);
+                    self.comb_right[j].process(comb_in_right, self.scaled_comb_delays_right[j]);
             }
 
             // Process through allpass filters (series)
@@ -603,6 +808,12 @@ impl Reverb {
                     self.allpass_right[j].process(output_right, self.scaled_allpass_delays_right[j]);
             }
 
+            // Blend the late tail with the early reflections
+            let early_left = self.early_left.process(delayed_left);
+            let early_right = self.early_right.process(delayed_right);
+            let output_left = output_left * late_level + early_left * early_level;
+            let output_right = output_right * late_level + early_right * early_level;
+
             // Apply width and mix
             // wet1 controls same-side contribution, wet2 controls cross-side contribution
             let wet_left = output_left * wet1 + output_right * wet2;
@@ -617,6 +828,11 @@ impl Reverb {
     }
 }
 
+/// Early reflection buffer length in samples for a sample rate
+fn early_buffer_size(sample_rate: f64) -> usize {
+    ((MAX_EARLY_TIME_MS / 1000.0) as f64 * sample_rate) as usize + 1
+}
+
 impl Default for Reverb {
     fn default() -> Self {
         Self::new()
@@ -640,6 +856,7 @@ impl Effect for Reverb {
         self.resize_buffers();
         self.scale_delays();
         self.update_pre_delay();
+        self.update_early_taps();
     }
 
     fn reset(&mut self) {
@@ -662,6 +879,10 @@ impl Effect for Reverb {
         // Clear pre-delay buffers
         self.pre_delay_left.clear();
         self.pre_delay_right.clear();
+
+        // Clear early reflection buffers
+        self.early_left.clear();
+        self.early_right.clear();
     }
 
     fn to_json(&self) -> Result<serde_json::Value> {
@@ -676,6 +897,11 @@ impl Effect for Reverb {
                 "dry_level": self.params.dry_level,
                 "width": self.params.width,
                 "pre_delay_ms": self.params.pre_delay_ms,
+                "early_level": self.params.early_level,
+                "early_time_ms": self.params.early_time_ms,
+                "late_level": self.params.late_level,
+                "freeze": self.params.freeze,
+                "mix_mode": self.params.mix_mode,
             }
         }))
     }
@@ -710,6 +936,21 @@ impl Effect for Reverb {
             if let Some(v) = params.get("pre_delay_ms").and_then(|v| v.as_f64()) {
                 new_params.pre_delay_ms = v as f32;
             }
+            if let Some(v) = params.get("early_level").and_then(|v| v.as_f64()) {
+                new_params.early_level = v as f32;
+            }
+            if let Some(v) = params.get("early_time_ms").and_then(|v| v.as_f64()) {
+                new_params.early_time_ms = v as f32;
+            }
+            if let Some(v) = params.get("late_level").and_then(|v| v.as_f64()) {
+                new_params.late_level = v as f32;
+            }
+            if let Some(v) = params.get("freeze").and_then(|v| v.as_bool()) {
+                new_params.freeze = v;
+            }
+            if let Some(v) = params.get("mix_mode") {
+                new_params.mix_mode = MixMode::from_json(v)?;
+            }
 
             self.set_params(new_params)?;
         }
@@ -749,6 +990,66 @@ impl Effect for Reverb {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("room_size", 0.0, 1.0, 0.5),
+            ParamSpec::float("damping", 0.0, 1.0, 0.5),
+            ParamSpec::float("wet_level", 0.0, 1.0, 0.3),
+            ParamSpec::float("dry_level", 0.0, 1.0, 1.0),
+            ParamSpec::float("width", 0.0, 1.0, 1.0),
+            ParamSpec::float("pre_delay_ms", 0.0, MAX_PRE_DELAY_MS, 0.0).with_unit("ms"),
+            ParamSpec::float("early_level", 0.0, 1.0, 0.0),
+            ParamSpec::float(
+                "early_time_ms",
+                MIN_EARLY_TIME_MS,
+                MAX_EARLY_TIME_MS,
+                default_early_time_ms(),
+            )
+            .with_unit("ms"),
+            ParamSpec::float("late_level", 0.0, 1.0, default_late_level()),
+            ParamSpec::boolean("freeze", false),
+            MixMode::param_spec(),
+        ]
+    }
+
+    fn tail_samples(&self) -> usize {
+        // A frozen tail never decays, so there is no finite length to render
+        if self.params.wet_level <= 0.0 || self.params.freeze {
+            return 0;
+        }
+
+        let mut tail = self.pre_delay_samples;
+        if self.params.early_level > 0.0 {
+            tail += ((self.params.early_time_ms / 1000.0) * self.sample_rate as f32) as usize;
+        }
+        if self.params.late_level > 0.0 {
+            // The longest comb decays slowest: the damping filter has unity
+            // gain at DC, so each pass loses exactly the feedback. The
+            // allpass diffusers then ring on at their own gain.
+            let feedback = (self.params.room_size * ROOM_SCALE + ROOM_OFFSET).min(MAX_FEEDBACK);
+            let longest_comb = self
+                .scaled_comb_delays_left
+                .iter()
+                .chain(&self.scaled_comb_delays_right)
+                .max()
+                .copied()
+                .unwrap_or(0);
+            let allpass_total = self
+                .scaled_allpass_delays_left
+                .iter()
+                .chain(&self.scaled_allpass_delays_right)
+                .max()
+                .copied()
+                .unwrap_or(0)
+                * ALLPASS_DELAYS.len();
+
+            let comb_tail = repeats_to_decay(feedback) * longest_comb as f32;
+            let allpass_tail = repeats_to_decay(ALLPASS_GAIN) * allpass_total as f32;
+            tail += (comb_tail + allpass_tail).ceil() as usize;
+        }
+        tail
+    }
 }
 
 // ============================================================================
@@ -779,8 +1080,10 @@ mod tests {
         assert!(params.validate().is_ok());
 
         // Invalid room_size (too low)
-        let mut params = ReverbParams::default();
-        params.room_size = -0.1;
+        let mut params = ReverbParams {
+            room_size: -0.1,
+            ..Default::default()
+        };
         assert!(params.validate().is_err());
 
         // Invalid room_size (too high)
@@ -953,6 +1256,7 @@ mod tests {
             dry_level: 0.0,
             width: 0.0, // Mono
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_mono.prepare(44100.0, 512);
 
@@ -993,6 +1297,7 @@ mod tests {
             dry_level: 0.0,
             width: 1.0, // Full stereo
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_stereo.prepare(44100.0, 512);
 
@@ -1028,6 +1333,7 @@ mod tests {
             dry_level: 0.0, // Only wet
             width: 1.0,
             pre_delay_ms: 50.0, // 50ms pre-delay
+            ..Default::default()
         });
         reverb.prepare(44100.0, 512);
 
@@ -1062,6 +1368,7 @@ mod tests {
             dry_level: 0.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_small.prepare(44100.0, 512);
 
@@ -1073,6 +1380,7 @@ mod tests {
             dry_level: 0.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_large.prepare(44100.0, 512);
 
@@ -1117,6 +1425,7 @@ mod tests {
             dry_level: 0.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_bright.prepare(44100.0, 512);
 
@@ -1127,6 +1436,7 @@ mod tests {
             dry_level: 0.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_dark.prepare(44100.0, 512);
 
@@ -1187,6 +1497,7 @@ mod tests {
                 dry_level: 0.8,
                 width: 0.6,
                 pre_delay_ms: 25.0,
+                ..Default::default()
             })
             .unwrap();
 
@@ -1228,6 +1539,7 @@ mod tests {
             dry_level: 0.0, // Only wet
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb.prepare(44100.0, 512);
 
@@ -1282,6 +1594,7 @@ mod tests {
             dry_level: 1.0,
             width: 1.0,
             pre_delay_ms: 100.0, // Maximum pre-delay
+            ..Default::default()
         });
         reverb.prepare(44100.0, 512);
 
@@ -1352,6 +1665,7 @@ mod tests {
             dry_level: 1.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_dry.prepare(44100.0, 512);
 
@@ -1383,6 +1697,7 @@ mod tests {
             dry_level: 0.0,
             width: 1.0,
             pre_delay_ms: 0.0,
+            ..Default::default()
         });
         reverb_wet.prepare(44100.0, 512);
 
@@ -1405,4 +1720,353 @@ mod tests {
         }
         assert!(has_reverb, "No reverb tail detected");
     }
+
+    /// Process a stereo impulse and return the wet left channel
+    fn impulse_response(params: ReverbParams, len: usize) -> Vec<f32> {
+        let mut reverb = Reverb::with_params(ReverbParams {
+            dry_level: 0.0,
+            ..params
+        });
+        reverb.prepare(44100.0, 512);
+
+        let mut buffer = AudioBuffer::new(2, len, 44100.0);
+        buffer.set(0, 0, 1.0);
+        buffer.set(0, 1, 1.0);
+        reverb.process(&mut buffer);
+        (0..len).map(|i| buffer.get(i, 0).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_early_level_zero_matches_tail_only() {
+        let reference = impulse_response(ReverbParams::default(), 6000);
+        let with_taps = impulse_response(
+            ReverbParams {
+                early_level: 0.0,
+                early_time_ms: 5.0,
+                ..Default::default()
+            },
+            6000,
+        );
+        assert_eq!(reference, with_taps);
+    }
+
+    #[test]
+    fn test_early_reflections_precede_tail() {
+        let first_nonzero = |ir: &[f32]| ir.iter().position(|s| s.abs() > 1e-9);
+
+        let tail = impulse_response(ReverbParams::default(), 4000);
+        assert!(first_nonzero(&tail).unwrap() >= COMB_DELAYS[0]);
+
+        let early = impulse_response(
+            ReverbParams {
+                early_level: 1.0,
+                late_level: 0.0,
+                ..Default::default()
+            },
+            4000,
+        );
+        let taps: Vec<usize> = (0..early.len())
+            .filter(|&i| early[i].abs() > 1e-9)
+            .collect();
+        assert!(!taps.is_empty());
+        assert!(
+            taps.iter().all(|&i| i < COMB_DELAYS[0]),
+            "early taps should land before the comb tail: {:?}",
+            taps
+        );
+
+        // With both enabled the reflections arrive first, then the tail
+        let both = impulse_response(
+            ReverbParams {
+                early_level: 1.0,
+                ..Default::default()
+            },
+            4000,
+        );
+        assert_eq!(first_nonzero(&both), first_nonzero(&early));
+    }
+
+    #[test]
+    fn test_early_time_moves_taps() {
+        let last_tap = |early_time_ms: f32| {
+            let ir = impulse_response(
+                ReverbParams {
+                    early_level: 1.0,
+                    early_time_ms,
+                    late_level: 0.0,
+                    ..Default::default()
+                },
+                2000,
+            );
+            ir.iter().rposition(|s| s.abs() > 1e-9).unwrap()
+        };
+        assert!(last_tap(5.0) < last_tap(20.0));
+    }
+
+    #[test]
+    fn test_early_late_extremes_stay_finite() {
+        for (early_level, early_time_ms, late_level) in [
+            (1.0, MIN_EARLY_TIME_MS, 1.0),
+            (1.0, MAX_EARLY_TIME_MS, 1.0),
+            (0.0, 1.0, 0.0),
+        ] {
+            let mut reverb = Reverb::with_params(ReverbParams {
+                room_size: 1.0,
+                damping: 0.0,
+                wet_level: 1.0,
+                pre_delay_ms: MAX_PRE_DELAY_MS,
+                early_level,
+                early_time_ms,
+                late_level,
+                ..Default::default()
+            });
+            reverb.prepare(96000.0, 512);
+
+            let mut buffer = AudioBuffer::new(2, 20000, 96000.0);
+            for i in (0..20000).step_by(100) {
+                buffer.set(i, 0, 1.0);
+                buffer.set(i, 1, -1.0);
+            }
+            reverb.process(&mut buffer);
+            assert!(buffer.samples().iter().all(|s| s.is_finite()));
+        }
+    }
+
+    #[test]
+    fn test_early_late_validation_and_json() {
+        let mut params = ReverbParams {
+            early_level: 1.5,
+            ..Default::default()
+        };
+        assert!(params.validate().is_err());
+        params = ReverbParams::default();
+        params.early_time_ms = MAX_EARLY_TIME_MS + 1.0;
+        assert!(params.validate().is_err());
+        params = ReverbParams::default();
+        params.late_level = -0.1;
+        assert!(params.validate().is_err());
+
+        let mut reverb = Reverb::new();
+        reverb.set_early_level(0.4).unwrap();
+        reverb.set_early_time(10.0).unwrap();
+        reverb.set_late_level(0.6).unwrap();
+
+        let mut restored = Reverb::new();
+        restored.from_json(&reverb.to_json().unwrap()).unwrap();
+        assert_eq!(restored.params().early_level, 0.4);
+        assert_eq!(restored.params().early_time_ms, 10.0);
+        assert_eq!(restored.params().late_level, 0.6);
+    }
+
+    /// RMS of the left channel of an interleaved stereo block
+    fn left_rms(buffer: &AudioBuffer) -> f32 {
+        let n = buffer.num_samples();
+        let sum: f32 = (0..n).map(|i| buffer.get(i, 0).unwrap().powi(2)).sum();
+        (sum / n as f32).sqrt()
+    }
+
+    #[test]
+    fn test_freeze_sustains_then_decays() {
+        let sr = 44100.0;
+        let mut reverb = Reverb::with_params(ReverbParams {
+            dry_level: 0.0,
+            ..Default::default()
+        });
+        reverb.prepare(sr, 4410);
+
+        // Excite the tail with a short noise burst
+        let mut state: u32 = 12345;
+        let mut burst = AudioBuffer::new(2, 4410, sr);
+        for i in 0..4410 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let x = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+            burst.set(i, 0, x * 0.5);
+            burst.set(i, 1, x * 0.5);
+        }
+        reverb.process(&mut burst);
+
+        // Freeze, then feed loud input that must not enter the tail
+        reverb.set_freeze(true);
+        let mut levels = Vec::new();
+        for block in 0..100 {
+            let mut buffer = AudioBuffer::new(2, 4410, sr);
+            if block % 10 == 0 {
+                buffer.set(0, 0, 1.0);
+                buffer.set(0, 1, 1.0);
+            }
+            reverb.process(&mut buffer);
+            levels.push(left_rms(&buffer));
+        }
+        // Skip the first second while the allpass diffusion settles
+        let settled = &levels[10..];
+        let max = settled.iter().cloned().fold(0.0_f32, f32::max);
+        let min = settled.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(min > 0.0);
+        assert!(
+            20.0 * (max / min).log10() < 3.0,
+            "frozen tail should hold steady: {} .. {}",
+            min,
+            max
+        );
+
+        // Unfreeze and the tail decays normally
+        reverb.set_freeze(false);
+        let mut buffer = AudioBuffer::new(2, 44100 * 3, sr);
+        reverb.process(&mut buffer);
+        let tail: Vec<f32> = (44100 * 2..44100 * 3)
+            .map(|i| buffer.get(i, 0).unwrap())
+            .collect();
+        let tail_rms = (tail.iter().map(|x| x * x).sum::<f32>() / tail.len() as f32).sqrt();
+        assert!(
+            tail_rms < min * 0.01,
+            "tail should decay after unfreezing: {} vs {}",
+            tail_rms,
+            min
+        );
+    }
+
+    #[test]
+    fn test_freeze_serializes() {
+        let mut reverb = Reverb::new();
+        reverb.set_freeze(true);
+
+        let json = reverb.to_json().unwrap();
+        assert_eq!(json["params"]["freeze"], serde_json::json!(true));
+
+        let mut restored = Reverb::new();
+        restored.from_json(&json).unwrap();
+        assert!(restored.params().freeze);
+
+        // Older JSON without the flag is not frozen
+        let params: ReverbParams = serde_json::from_value(serde_json::json!({
+            "room_size": 0.5,
+            "damping": 0.5,
+            "wet_level": 0.3,
+            "dry_level": 1.0,
+            "width": 1.0,
+            "pre_delay_ms": 0.0
+        }))
+        .unwrap();
+        assert!(!params.freeze);
+    }
+
+    /// Index of the last sample above `TAIL_DECAY_DB` relative to the peak
+    fn last_audible_sample(buffer: &AudioBuffer) -> usize {
+        let samples = buffer.samples();
+        let peak = samples.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        let floor = peak * 10.0_f32.powf(crate::dsp::TAIL_DECAY_DB / 20.0);
+        samples.iter().rposition(|s| s.abs() > floor).unwrap_or(0) / buffer.num_channels()
+    }
+
+    #[test]
+    fn test_tail_covers_measured_decay() {
+        let mut reverb = Reverb::with_params(ReverbParams {
+            dry_level: 0.0,
+            ..Default::default()
+        });
+        reverb.prepare(48000.0, 512);
+        let tail = reverb.tail_samples();
+        assert!(tail > 0);
+
+        // Impulse in, with room to spare after the estimate
+        let mut buffer = AudioBuffer::new(2, tail * 2, 48000.0);
+        buffer.set(0, 0, 1.0);
+        buffer.set(0, 1, 1.0);
+        reverb.process(&mut buffer);
+
+        let measured = last_audible_sample(&buffer);
+        assert!(
+            tail >= measured,
+            "estimated {} samples, measured {}",
+            tail,
+            measured
+        );
+        // ...without being wildly pessimistic
+        assert!(
+            tail < measured * 3,
+            "estimated {} samples, measured {}",
+            tail,
+            measured
+        );
+    }
+
+    #[test]
+    fn test_tail_grows_with_room_size() {
+        let mut small = Reverb::with_params(ReverbParams {
+            room_size: 0.1,
+            ..Default::default()
+        });
+        let mut large = Reverb::with_params(ReverbParams {
+            room_size: 0.9,
+            ..Default::default()
+        });
+        small.prepare(48000.0, 512);
+        large.prepare(48000.0, 512);
+        assert!(large.tail_samples() > small.tail_samples());
+    }
+
+    #[test]
+    fn test_dry_or_frozen_reverb_has_no_tail() {
+        let mut dry = Reverb::with_params(ReverbParams {
+            wet_level: 0.0,
+            ..Default::default()
+        });
+        dry.prepare(48000.0, 512);
+        assert_eq!(dry.tail_samples(), 0);
+
+        let mut frozen = Reverb::new();
+        frozen.set_freeze(true);
+        assert_eq!(frozen.tail_samples(), 0);
+    }
+
+    #[test]
+    fn test_equal_power_mix_holds_level_across_crossfade() {
+        let level_db = |mix_mode: MixMode, wet: f32| {
+            let mut reverb = Reverb::with_params(ReverbParams {
+                wet_level: wet,
+                dry_level: 1.0 - wet,
+                mix_mode,
+                ..Default::default()
+            });
+            reverb.prepare(48000.0, 512);
+            let mut buffer = AudioBuffer::new(2, 48000, 48000.0);
+            let mut seed = 11u32;
+            for i in 0..48000 {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let s = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+                buffer.set(i, 0, s);
+                buffer.set(i, 1, s);
+            }
+            reverb.process(&mut buffer);
+            buffer.rms_db(0)
+        };
+
+        // Crossfading dry noise into its uncorrelated reverb: equal power
+        // lands on the average of the endpoints, linear dips ~3 dB below
+        for mode in [MixMode::Linear, MixMode::EqualPower] {
+            let endpoints =
+                10f64.powf(level_db(mode, 0.0) / 10.0) + 10f64.powf(level_db(mode, 1.0) / 10.0);
+            let dip = level_db(mode, 0.5) - 10.0 * (endpoints / 2.0).log10();
+            match mode {
+                MixMode::Linear => assert!(dip < -2.0, "linear dip {}", dip),
+                MixMode::EqualPower => assert!(dip.abs() < 1.0, "equal-power dip {}", dip),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_mode_serializes() {
+        let reverb = Reverb::with_params(ReverbParams {
+            mix_mode: MixMode::EqualPower,
+            ..Default::default()
+        });
+        let json = reverb.to_json().unwrap();
+        assert_eq!(json["params"]["mix_mode"], "equal_power");
+
+        let mut restored = Reverb::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.params().mix_mode, MixMode::EqualPower);
+    }
 }
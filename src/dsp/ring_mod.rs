@@ -0,0 +1,329 @@
+//! Ring modulator
+//!
+//! Multiplies the signal by a sine carrier, replacing each partial with
+//! sum and difference frequencies for metallic, bell-like tones. The
+//! carrier phase runs on across process calls, so block boundaries are
+//! seamless.
+
+use super::effect::{Effect, EffectMetadata};
+use super::AudioBuffer;
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+/// Ring modulator parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RingModParams {
+    /// Carrier frequency in Hz (1 to 5000)
+    pub carrier_hz: f32,
+    /// Wet/dry mix (0 = dry passthrough, 1 = fully modulated)
+    pub mix: f32,
+}
+
+impl Default for RingModParams {
+    fn default() -> Self {
+        Self {
+            carrier_hz: 440.0,
+            mix: 0.5,
+        }
+    }
+}
+
+impl RingModParams {
+    /// Validate all parameters are within range
+    pub fn validate(&self) -> Result<()> {
+        if !(1.0..=5000.0).contains(&self.carrier_hz) {
+            return Err(NuevaError::InvalidParameter {
+                param: "carrier_hz".to_string(),
+                value: self.carrier_hz.to_string(),
+                expected: "1 to 5000 Hz".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.mix) {
+            return Err(NuevaError::InvalidParameter {
+                param: "mix".to_string(),
+                value: self.mix.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Sine-carrier ring modulator
+#[derive(Debug, Clone)]
+pub struct RingMod {
+    params: RingModParams,
+    id: String,
+    enabled: bool,
+    sample_rate: f64,
+    /// Carrier phase (0 to 1), carried across blocks
+    phase: f64,
+}
+
+impl RingMod {
+    /// Create a ring modulator with default parameters
+    pub fn new() -> Self {
+        Self::with_params(RingModParams::default())
+    }
+
+    /// Create a ring modulator with the given parameters
+    pub fn with_params(params: RingModParams) -> Self {
+        Self {
+            params,
+            id: String::new(),
+            enabled: true,
+            sample_rate: 48000.0,
+            phase: 0.0,
+        }
+    }
+
+    /// Get a reference to the current parameters
+    pub fn params(&self) -> &RingModParams {
+        &self.params
+    }
+
+    /// Set parameters with validation; the carrier phase is kept
+    pub fn set_params(&mut self, params: RingModParams) -> Result<()> {
+        params.validate()?;
+        self.params = params;
+        Ok(())
+    }
+
+    /// Set the carrier frequency in Hz
+    pub fn set_carrier_hz(&mut self, carrier_hz: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.carrier_hz = carrier_hz;
+        self.set_params(params)
+    }
+
+    /// Set the wet/dry mix
+    pub fn set_mix(&mut self, mix: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.mix = mix;
+        self.set_params(params)
+    }
+
+    /// Gain for the next frame, advancing the carrier
+    fn next_gain(&mut self) -> f64 {
+        let mix = self.params.mix as f64;
+        let gain = 1.0 - mix + mix * (TAU * self.phase).sin();
+        self.phase = (self.phase + self.params.carrier_hz as f64 / self.sample_rate).fract();
+        gain
+    }
+}
+
+impl Default for RingMod {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for RingMod {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled || self.params.mix == 0.0 {
+            return;
+        }
+
+        let num_channels = buffer.num_channels().max(1);
+        for frame in buffer.samples_mut().chunks_mut(num_channels) {
+            let gain = self.next_gain();
+            for sample in frame {
+                *sample = (*sample as f64 * gain) as f32;
+            }
+        }
+    }
+
+    fn process_to(&mut self, input: &AudioBuffer, output: &mut AudioBuffer) {
+        if !self.enabled || self.params.mix == 0.0 {
+            output.copy_from(input);
+            return;
+        }
+
+        output.resize_to_match(input);
+        let num_channels = input.num_channels().max(1);
+        let frames = input.samples().chunks(num_channels);
+        for (out, frame) in output.samples_mut().chunks_mut(num_channels).zip(frames) {
+            let gain = self.next_gain();
+            for (o, &sample) in out.iter_mut().zip(frame) {
+                *o = (sample as f64 * gain) as f32;
+            }
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "effect_type": self.effect_type(),
+            "id": self.id,
+            "enabled": self.enabled,
+            "params": {
+                "carrier_hz": self.params.carrier_hz,
+                "mix": self.params.mix,
+            }
+        }))
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+            self.id = id.to_string();
+        }
+
+        if let Some(enabled) = json.get("enabled").and_then(|v| v.as_bool()) {
+            self.enabled = enabled;
+        }
+
+        if let Some(params) = json.get("params") {
+            let mut new_params = self.params.clone();
+
+            if let Some(v) = params.get("carrier_hz").and_then(|v| v.as_f64()) {
+                new_params.carrier_hz = v as f32;
+            }
+            if let Some(v) = params.get("mix").and_then(|v| v.as_f64()) {
+                new_params.mix = v as f32;
+            }
+
+            self.set_params(new_params)?;
+        }
+
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "ring-mod"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Ring Modulator"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: "ring-mod".to_string(),
+            display_name: "Ring Modulator".to_string(),
+            category: "modulation".to_string(),
+            order_priority: 4, // Creative color, alongside saturation
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("carrier_hz", 1.0, 5000.0, 440.0).with_unit("Hz"),
+            ParamSpec::float("mix", 0.0, 1.0, 0.5),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(num_samples: usize) -> AudioBuffer {
+        let samples = (0..num_samples * 2)
+            .map(|i| ((i / 2) as f32 * 0.37).sin() * 0.5)
+            .collect();
+        AudioBuffer::from_interleaved(samples, 2, 48000.0).unwrap()
+    }
+
+    #[test]
+    fn test_zero_mix_is_passthrough() {
+        let mut ring = RingMod::with_params(RingModParams {
+            carrier_hz: 300.0,
+            mix: 0.0,
+        });
+        ring.prepare(48000.0, 512);
+
+        let dry = ramp(4800);
+        let mut wet = dry.clone();
+        ring.process(&mut wet);
+        assert_eq!(wet.samples(), dry.samples());
+    }
+
+    #[test]
+    fn test_full_mix_multiplies_by_carrier() {
+        let mut ring = RingMod::with_params(RingModParams {
+            carrier_hz: 1000.0,
+            mix: 1.0,
+        });
+        ring.prepare(48000.0, 512);
+
+        let mut buffer = AudioBuffer::from_interleaved(vec![1.0; 96], 2, 48000.0).unwrap();
+        ring.process(&mut buffer);
+        for i in 0..48 {
+            let carrier = (TAU * 1000.0 * i as f64 / 48000.0).sin() as f32;
+            assert!((buffer.get(i, 0).unwrap() - carrier).abs() < 1e-6);
+            assert_eq!(buffer.get(i, 0), buffer.get(i, 1));
+        }
+    }
+
+    #[test]
+    fn test_carrier_phase_is_continuous_across_blocks() {
+        let params = RingModParams {
+            carrier_hz: 523.0,
+            mix: 0.7,
+        };
+        let input = ramp(10_000);
+
+        let mut whole = input.clone();
+        let mut reference = RingMod::with_params(params.clone());
+        reference.prepare(48000.0, 10_000);
+        reference.process(&mut whole);
+
+        let mut blocked = RingMod::with_params(params);
+        blocked.prepare(48000.0, 257);
+        let mut out = Vec::new();
+        for chunk in input.samples().chunks(257 * 2) {
+            let mut buf = AudioBuffer::from_interleaved(chunk.to_vec(), 2, 48000.0).unwrap();
+            blocked.process(&mut buf);
+            out.extend_from_slice(buf.samples());
+        }
+
+        for (a, b) in out.iter().zip(whole.samples()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_validation_and_serialization() {
+        assert!(RingMod::new().set_mix(-0.1).is_err());
+        assert!(RingMod::new().set_carrier_hz(0.0).is_err());
+
+        let mut ring = RingMod::with_params(RingModParams {
+            carrier_hz: 90.0,
+            mix: 0.25,
+        });
+        ring.set_id("ring-1".to_string());
+        let json = ring.to_json().unwrap();
+
+        let mut restored = RingMod::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "ring-1");
+        assert_eq!(restored.params().carrier_hz, 90.0);
+        assert_eq!(restored.params().mix, 0.25);
+    }
+}
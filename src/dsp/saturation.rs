@@ -5,11 +5,37 @@
 //! - TUBE: Even harmonics emphasis
 //! - TRANSISTOR: Odd harmonics, harder edge
 //! - HARD_CLIP: Digital clipping
+//!
+//! The wet path runs through a DC blocker, since even-order shaping
+//! (tube) shifts the signal's mean.
+//!
+//! A tone control tilts the spectrum around a pivot before the shaper and
+//! applies the exact inverse tilt after it. In the linear region the two
+//! cancel, so tone changes which frequencies get driven (and so the
+//! harmonic character) rather than the overall balance.
+//!
+//! The shaper can run oversampled (see [`Oversampler`]) to keep its
+//! harmonics from aliasing. The anti-alias filter quality is chosen
+//! independently of the factor; while oversampling, the effect has a fixed
+//! latency and the dry signal is delayed to match.
 
+use super::dc_blocker::{DcBlocker, DC_BLOCKER_CUTOFF_HZ};
 use super::effect::{Effect, EffectMetadata};
+use super::oversampler::{
+    OversampleQuality, Oversampler, OVERSAMPLE_FACTORS, OVERSAMPLING_LATENCY,
+};
 use super::AudioBuffer;
 use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+/// Pivot of the tone tilt in Hz (unity gain at this frequency)
+pub const TONE_PIVOT_HZ: f32 = 800.0;
+
+/// Tilt at full tone in dB: half below the pivot, half above
+pub const TONE_TILT_DB: f32 = 12.0;
 
 /// Saturation type enum (spec §4.2.6)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -60,6 +86,19 @@ struct SaturationParams {
     mix: f32,
     /// Output gain compensation in dB, default 0.0
     output_gain: f32,
+    /// Tone (-1.0 darker to 1.0 brighter), default 0.0
+    #[serde(default)]
+    tone: f32,
+    /// Oversampling factor (1 = off), default 1
+    #[serde(default = "default_oversample_factor")]
+    oversample_factor: usize,
+    /// Anti-alias filter quality when oversampling
+    #[serde(default)]
+    oversample_quality: OversampleQuality,
+}
+
+fn default_oversample_factor() -> usize {
+    1
 }
 
 impl Default for SaturationParams {
@@ -69,10 +108,49 @@ impl Default for SaturationParams {
             saturation_type: SaturationType::Tape,
             mix: 0.5,
             output_gain: 0.0,
+            tone: 0.0,
+            oversample_factor: default_oversample_factor(),
+            oversample_quality: OversampleQuality::default(),
         }
     }
 }
 
+/// First-order tilt filter (transposed direct form II)
+///
+/// Bilinear transform of `H(s) = k (s + w/k) / (s + w k)`: gain `1/k` at
+/// DC, `k` at Nyquist and unity at the pivot `w`. Swapping `k` for `1/k`
+/// gives the exact inverse, so a pre/post pair cancels.
+#[derive(Debug, Clone, Copy, Default)]
+struct TiltFilter {
+    b0: f32,
+    b1: f32,
+    a1: f32,
+    z1: f32,
+}
+
+impl TiltFilter {
+    /// Set the tilt (dB from DC to Nyquist) keeping the filter state
+    fn set_tilt(&mut self, tilt_db: f32, sample_rate: f64) {
+        let k = 10.0_f32.powf(tilt_db / 40.0);
+        let w = (PI * TONE_PIVOT_HZ / sample_rate as f32).tan();
+        let norm = 1.0 + w * k;
+        self.b0 = (k + w) / norm;
+        self.b1 = (w - k) / norm;
+        self.a1 = (w * k - 1.0) / norm;
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+    }
+}
+
 /// Saturation effect (spec §4.2.6)
 ///
 /// Waveshaping-based saturation with multiple algorithms.
@@ -86,6 +164,15 @@ pub struct Saturation {
     enabled: bool,
     /// Sample rate (set via prepare)
     sample_rate: f64,
+    /// DC blocker per channel on the wet path
+    dc_blockers: Vec<DcBlocker>,
+    /// Tone filters per channel: (before, after) the shaper
+    tone_filters: Vec<(TiltFilter, TiltFilter)>,
+    /// Oversampler per channel around the shaper
+    oversamplers: Vec<Oversampler>,
+    /// Recent dry input per channel, for delaying the dry path to match
+    /// the oversampler (newest last)
+    dry_history: Vec<VecDeque<f32>>,
 }
 
 impl Default for Saturation {
@@ -102,6 +189,10 @@ impl Saturation {
             id: String::from("saturation-0"),
             enabled: true,
             sample_rate: 44100.0,
+            dc_blockers: Vec::new(),
+            tone_filters: Vec::new(),
+            oversamplers: Vec::new(),
+            dry_history: Vec::new(),
         }
     }
 
@@ -142,6 +233,21 @@ impl Saturation {
         self.params.output_gain
     }
 
+    /// Get the tone (-1.0 to 1.0)
+    pub fn tone(&self) -> f32 {
+        self.params.tone
+    }
+
+    /// Get the oversampling factor (1 = off)
+    pub fn oversample_factor(&self) -> usize {
+        self.params.oversample_factor
+    }
+
+    /// Get the anti-alias filter quality
+    pub fn oversample_quality(&self) -> OversampleQuality {
+        self.params.oversample_quality
+    }
+
     // --- Parameter setters with validation ---
 
     /// Set the drive amount (0.0 to 1.0)
@@ -188,6 +294,52 @@ impl Saturation {
         Ok(())
     }
 
+    /// Set the tone (-1.0 darker to 1.0 brighter)
+    ///
+    /// Positive tone drives the lows harder and lifts the harmonics they
+    /// generate; negative tone does the opposite. Values outside the range
+    /// are clamped.
+    pub fn set_tone(&mut self, tone: f32) {
+        self.params.tone = Self::clamp_tone(tone);
+    }
+
+    /// Set the oversampling factor (1, 2, 4 or 8; 1 turns it off)
+    ///
+    /// Turning oversampling on or off changes the latency; changing the
+    /// factor or quality while on does not.
+    pub fn set_oversample_factor(&mut self, factor: usize) -> Result<()> {
+        Self::validate_oversample_factor(factor)?;
+        self.params.oversample_factor = factor;
+        Ok(())
+    }
+
+    /// Set the anti-alias filter quality, trading CPU for alias suppression
+    ///
+    /// Safe to change while processing: the new filters start primed with
+    /// recent input.
+    pub fn set_oversample_quality(&mut self, quality: OversampleQuality) {
+        self.params.oversample_quality = quality;
+    }
+
+    fn validate_oversample_factor(factor: usize) -> Result<()> {
+        if !OVERSAMPLE_FACTORS.contains(&factor) {
+            return Err(NuevaError::InvalidParameter {
+                param: "oversample_factor".to_string(),
+                value: factor.to_string(),
+                expected: "1, 2, 4 or 8".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn clamp_tone(tone: f32) -> f32 {
+        if tone.is_nan() {
+            0.0
+        } else {
+            tone.clamp(-1.0, 1.0)
+        }
+    }
+
     // --- Waveshaping functions ---
 
     /// Apply tape saturation: tanh(x * drive) with subtle asymmetry
@@ -268,21 +420,90 @@ impl Effect for Saturation {
         let mix = self.params.mix;
         let dry_mix = 1.0 - mix;
 
-        for sample in buffer.samples_mut().iter_mut() {
-            let dry = *sample;
-            let wet = self.saturate_sample(dry);
-            // Apply wet/dry mix and output gain
-            *sample = (dry * dry_mix + wet * mix) * output_gain_linear;
+        let num_channels = buffer.num_channels();
+        if num_channels == 0 {
+            return;
+        }
+        if self.dc_blockers.len() != num_channels {
+            self.dc_blockers =
+                vec![DcBlocker::new(DC_BLOCKER_CUTOFF_HZ, self.sample_rate); num_channels];
         }
+
+        let factor = self.params.oversample_factor;
+        let quality = self.params.oversample_quality;
+        let mut oversamplers = std::mem::take(&mut self.oversamplers);
+        oversamplers.resize_with(num_channels, || Oversampler::new(factor, quality));
+        for oversampler in &mut oversamplers {
+            oversampler.configure(factor, quality, |x| self.saturate_sample(x));
+        }
+        self.dry_history.resize(
+            num_channels,
+            VecDeque::from(vec![0.0; OVERSAMPLING_LATENCY + 1]),
+        );
+        let dry_tap = OVERSAMPLING_LATENCY - self.latency_samples();
+
+        // Pre-shaper tilt is darker for positive tone, post-shaper brighter
+        let tilt_db = -self.params.tone * TONE_TILT_DB;
+        let use_tone = tilt_db != 0.0;
+        if use_tone {
+            self.tone_filters
+                .resize(num_channels, (TiltFilter::default(), TiltFilter::default()));
+            for (pre, post) in &mut self.tone_filters {
+                pre.set_tilt(tilt_db, self.sample_rate);
+                post.set_tilt(-tilt_db, self.sample_rate);
+            }
+        }
+
+        let mut dc_blockers = std::mem::take(&mut self.dc_blockers);
+        let mut tone_filters = std::mem::take(&mut self.tone_filters);
+        let mut dry_history = std::mem::take(&mut self.dry_history);
+        for frame in buffer.samples_mut().chunks_mut(num_channels) {
+            for (ch, (sample, blocker)) in frame.iter_mut().zip(dc_blockers.iter_mut()).enumerate()
+            {
+                let input = *sample;
+                let shape = |x| self.saturate_sample(x);
+                let shaped = if use_tone {
+                    let (pre, post) = &mut tone_filters[ch];
+                    post.process(oversamplers[ch].process(pre.process(input), shape))
+                } else {
+                    oversamplers[ch].process(input, shape)
+                };
+                let wet = blocker.process(shaped);
+
+                let history = &mut dry_history[ch];
+                history.pop_front();
+                history.push_back(input);
+                let dry = history[dry_tap];
+                // Apply wet/dry mix and output gain
+                *sample = (dry * dry_mix + wet * mix) * output_gain_linear;
+            }
+        }
+        self.dc_blockers = dc_blockers;
+        self.tone_filters = tone_filters;
+        self.oversamplers = oversamplers;
+        self.dry_history = dry_history;
     }
 
     fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
         self.sample_rate = sample_rate;
+        for blocker in &mut self.dc_blockers {
+            blocker.set_sample_rate(sample_rate);
+        }
     }
 
     fn reset(&mut self) {
-        // Saturation is stateless (no delay lines or envelope followers)
-        // Nothing to reset
+        // The only state is in the filters and delay lines
+        for blocker in &mut self.dc_blockers {
+            blocker.reset();
+        }
+        for (pre, post) in &mut self.tone_filters {
+            pre.reset();
+            post.reset();
+        }
+        for oversampler in &mut self.oversamplers {
+            oversampler.reset();
+        }
+        self.dry_history.clear();
     }
 
     fn to_json(&self) -> Result<serde_json::Value> {
@@ -292,7 +513,7 @@ impl Effect for Saturation {
     }
 
     fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
-        let params: SaturationParams =
+        let mut params: SaturationParams =
             serde_json::from_value(json.clone()).map_err(|e| NuevaError::SerializationError {
                 details: e.to_string(),
             })?;
@@ -320,6 +541,9 @@ impl Effect for Saturation {
             });
         }
 
+        Self::validate_oversample_factor(params.oversample_factor)?;
+
+        params.tone = Self::clamp_tone(params.tone);
         self.params = params;
         Ok(())
     }
@@ -356,6 +580,30 @@ impl Effect for Saturation {
     fn set_id(&mut self, id: String) {
         self.id = id;
     }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("drive", 0.0, 1.0, 0.3),
+            ParamSpec::options(
+                "saturationType",
+                &["TAPE", "TUBE", "TRANSISTOR", "HARD_CLIP"],
+                "TAPE",
+            ),
+            ParamSpec::float("mix", 0.0, 1.0, 0.5),
+            ParamSpec::float("outputGain", -24.0, 24.0, 0.0).with_unit("dB"),
+            ParamSpec::float("tone", -1.0, 1.0, 0.0),
+            ParamSpec::int("oversampleFactor", 1, 8, 1),
+            ParamSpec::options("oversampleQuality", &["LINEAR", "FIR16", "FIR64"], "FIR16"),
+        ]
+    }
+
+    fn latency_samples(&self) -> usize {
+        if self.params.oversample_factor > 1 {
+            OVERSAMPLING_LATENCY
+        } else {
+            0
+        }
+    }
 }
 
 #[cfg(test)]
@@ -459,7 +707,7 @@ mod tests {
     fn test_hard_clip() {
         // Hard clip should clamp to [-1, 1]
         let result = Saturation::saturate_hard_clip(0.5, 1.0);
-        assert!(result >= -1.0 && result <= 1.0);
+        assert!((-1.0..=1.0).contains(&result));
 
         // With high drive, should clip
         let clipped = Saturation::saturate_hard_clip(0.5, 1.0);
@@ -619,8 +867,55 @@ mod tests {
         sat.prepare(48000.0, 512);
         assert_eq!(sat.sample_rate, 48000.0);
 
-        // reset should not panic (saturation is stateless)
+        // reset should not panic
+        sat.reset();
+    }
+
+    /// Mean of one channel of an interleaved buffer
+    fn channel_mean(buffer: &AudioBuffer, ch: usize) -> f32 {
+        let n = buffer.num_samples();
+        (0..n).map(|i| buffer.get(i, ch).unwrap()).sum::<f32>() / n as f32
+    }
+
+    #[test]
+    fn test_asymmetric_shaping_has_no_dc() {
+        use crate::engine::buffer::DC_OFFSET_THRESHOLD;
+
+        for sat_type in [SaturationType::Tape, SaturationType::Tube] {
+            let mut sat = Saturation::with_params(1.0, sat_type, 1.0, 0.0).unwrap();
+            sat.prepare(48000.0, 512);
+
+            let mut buffer = AudioBuffer::new(1, 48000, 48000.0);
+            for i in 0..48000 {
+                let t = i as f32 / 48000.0;
+                buffer.set(i, 0, 0.8 * (2.0 * std::f32::consts::PI * 100.0 * t).sin());
+            }
+            sat.process(&mut buffer);
+
+            let mean = channel_mean(&buffer, 0);
+            assert!(
+                mean.abs() < DC_OFFSET_THRESHOLD,
+                "{:?} left a DC offset of {}",
+                sat_type,
+                mean
+            );
+        }
+    }
+
+    #[test]
+    fn test_reset_clears_dc_blocker() {
+        let mut sat = Saturation::with_params(0.5, SaturationType::Tape, 1.0, 0.0).unwrap();
+        let mut first = AudioBuffer::new(1, 64, 44100.0);
+        for i in 0..64 {
+            first.set(i, 0, 0.5);
+        }
+        let mut again = first.clone();
+
+        sat.process(&mut first);
         sat.reset();
+        sat.process(&mut again);
+
+        assert_eq!(first.samples(), again.samples());
     }
 
     #[test]
@@ -736,4 +1031,294 @@ mod tests {
         // -20 dB = 0.1
         assert!((Saturation::db_to_linear(-20.0) - 0.1).abs() < 0.01);
     }
+
+    // --- Tone tests ---
+
+    /// Mono 48 kHz buffer holding a sine
+    fn sine_buffer(freq: f32, amplitude: f32, len: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(1, len, 48000.0);
+        for i in 0..len {
+            let t = i as f32 / 48000.0;
+            buffer.set(i, 0, amplitude * (2.0 * PI * freq * t).sin());
+        }
+        buffer
+    }
+
+    /// DFT magnitude of channel 0 at one frequency
+    fn magnitude_at(buffer: &AudioBuffer, freq: f32) -> f32 {
+        let n = buffer.num_samples();
+        let (mut re, mut im) = (0.0_f64, 0.0_f64);
+        for i in 0..n {
+            let phase = 2.0 * std::f64::consts::PI * freq as f64 * i as f64 / 48000.0;
+            let x = buffer.get(i, 0).unwrap() as f64;
+            re += x * phase.cos();
+            im -= x * phase.sin();
+        }
+        ((re * re + im * im).sqrt() / n as f64) as f32
+    }
+
+    /// Energy in harmonics 5-15 of a 200 Hz tone, relative to the fundamental
+    fn high_harmonic_ratio(tone: f32) -> f32 {
+        let mut sat = Saturation::with_params(1.0, SaturationType::Tape, 1.0, 0.0).unwrap();
+        sat.set_tone(tone);
+        sat.prepare(48000.0, 512);
+
+        // Whole periods of 200 Hz so every harmonic falls on a bin
+        let mut buffer = sine_buffer(200.0, 0.5, 48000);
+        sat.process(&mut buffer);
+
+        let fundamental = magnitude_at(&buffer, 200.0);
+        let high: f32 = (5..=15)
+            .map(|h| magnitude_at(&buffer, 200.0 * h as f32).powi(2))
+            .sum();
+        high.sqrt() / fundamental
+    }
+
+    #[test]
+    fn test_tone_default_and_clamping() {
+        let mut sat = Saturation::new();
+        assert_eq!(sat.tone(), 0.0);
+
+        sat.set_tone(0.4);
+        assert_eq!(sat.tone(), 0.4);
+        sat.set_tone(3.0);
+        assert_eq!(sat.tone(), 1.0);
+        sat.set_tone(-3.0);
+        assert_eq!(sat.tone(), -1.0);
+        sat.set_tone(f32::NAN);
+        assert_eq!(sat.tone(), 0.0);
+    }
+
+    #[test]
+    fn test_zero_tone_matches_untoned_output() {
+        // Reference: the shaper and DC blocker alone
+        let input = sine_buffer(300.0, 0.7, 4800);
+        let mut expected = input.clone();
+        let mut blocker = DcBlocker::new(DC_BLOCKER_CUTOFF_HZ, 48000.0);
+        for i in 0..expected.num_samples() {
+            let dry = expected.get(i, 0).unwrap();
+            let wet = blocker.process(Saturation::saturate_tube(dry, 0.8));
+            expected.set(i, 0, dry * (1.0 - 0.6) + wet * 0.6);
+        }
+
+        let mut sat = Saturation::with_params(0.8, SaturationType::Tube, 0.6, 0.0).unwrap();
+        sat.prepare(48000.0, 512);
+        sat.set_tone(0.7);
+        sat.set_tone(0.0);
+        let mut buffer = input;
+        sat.process(&mut buffer);
+
+        assert_eq!(buffer.samples(), expected.samples());
+    }
+
+    #[test]
+    fn test_positive_tone_adds_high_harmonics() {
+        let neutral = high_harmonic_ratio(0.0);
+        let bright = high_harmonic_ratio(1.0);
+        let dark = high_harmonic_ratio(-1.0);
+
+        assert!(
+            bright > neutral * 1.2,
+            "bright {} vs neutral {}",
+            bright,
+            neutral
+        );
+        assert!(dark < neutral, "dark {} vs neutral {}", dark, neutral);
+    }
+
+    #[test]
+    fn test_tone_filters_cancel() {
+        for freq in [50.0, 800.0, 5000.0] {
+            let mut pre = TiltFilter::default();
+            let mut post = TiltFilter::default();
+            pre.set_tilt(-TONE_TILT_DB, 48000.0);
+            post.set_tilt(TONE_TILT_DB, 48000.0);
+
+            let input = sine_buffer(freq, 0.5, 48000);
+            let mut output = input.clone();
+            for i in 0..output.num_samples() {
+                let x = output.get(i, 0).unwrap();
+                output.set(i, 0, post.process(pre.process(x)));
+            }
+
+            let change_db =
+                20.0 * (magnitude_at(&output, freq) / magnitude_at(&input, freq)).log10();
+            assert!(
+                change_db.abs() < 0.01,
+                "{} Hz changed by {} dB",
+                freq,
+                change_db
+            );
+        }
+    }
+
+    #[test]
+    fn test_tone_serialization() {
+        let mut sat = Saturation::new();
+        sat.set_tone(-0.25);
+        let json = sat.to_json().unwrap();
+        assert_eq!(json["tone"], -0.25);
+
+        let mut restored = Saturation::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.tone(), -0.25);
+
+        // Older presets have no tone; out-of-range values are clamped
+        let legacy = serde_json::json!({
+            "drive": 0.5,
+            "saturationType": "TAPE",
+            "mix": 0.5,
+            "outputGain": 0.0
+        });
+        restored.from_json(&legacy).unwrap();
+        assert_eq!(restored.tone(), 0.0);
+
+        let loud = serde_json::json!({
+            "drive": 0.5,
+            "saturationType": "TAPE",
+            "mix": 0.5,
+            "outputGain": 0.0,
+            "tone": 4.0
+        });
+        restored.from_json(&loud).unwrap();
+        assert_eq!(restored.tone(), 1.0);
+    }
+
+    /// A bin-centred sine shaped by `sat`, returning aliased energy relative
+    /// to the total in dB
+    ///
+    /// Harmonics and their aliases all land on exact bins, so everything
+    /// outside the harmonic bins (and DC) is aliasing.
+    fn aliased_energy_db(sat: &mut Saturation) -> f64 {
+        const N: usize = 8192;
+        const BIN: usize = 1301; // ~7 kHz at 44.1 kHz, coprime with N
+        let sample_rate = 44100.0;
+        let len = 2 * N + OVERSAMPLING_LATENCY;
+        let mut buffer = AudioBuffer::new(1, len, sample_rate);
+        for i in 0..len {
+            let phase = 2.0 * std::f64::consts::PI * (BIN * i) as f64 / N as f64;
+            buffer.set(i, 0, 0.7 * phase.sin() as f32);
+        }
+        sat.prepare(sample_rate, len);
+        sat.process(&mut buffer);
+
+        let mut re: Vec<f64> = buffer.samples()[len - N..]
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / N as f64).cos();
+                x as f64 * w
+            })
+            .collect();
+        let mut im = vec![0.0; N];
+        crate::dsp::fft(&mut re, &mut im, false);
+
+        let (mut total, mut aliased) = (0.0, 0.0);
+        for bin in 4..N / 2 {
+            let power = re[bin] * re[bin] + im[bin] * im[bin];
+            total += power;
+            // Hann spreads each component over its bin and the neighbours
+            let harmonic = (1..=N / 2 / BIN).any(|k| bin.abs_diff(k * BIN) <= 1);
+            if !harmonic {
+                aliased += power;
+            }
+        }
+        10.0 * (aliased / total).log10()
+    }
+
+    fn driven(factor: usize, quality: OversampleQuality) -> Saturation {
+        let mut sat = Saturation::with_params(1.0, SaturationType::Tube, 1.0, 0.0).unwrap();
+        sat.set_oversample_factor(factor).unwrap();
+        sat.set_oversample_quality(quality);
+        sat
+    }
+
+    #[test]
+    fn test_oversampling_quality_reduces_aliasing() {
+        let plain = aliased_energy_db(&mut driven(1, OversampleQuality::Fir64));
+        let linear = aliased_energy_db(&mut driven(4, OversampleQuality::Linear));
+        let fir16 = aliased_energy_db(&mut driven(4, OversampleQuality::Fir16));
+        let fir64 = aliased_energy_db(&mut driven(4, OversampleQuality::Fir64));
+
+        assert!(
+            plain > linear + 3.0 && linear > fir16 + 3.0 && fir16 > fir64 + 3.0,
+            "aliasing: plain {:.1} dB, linear {:.1} dB, fir16 {:.1} dB, fir64 {:.1} dB",
+            plain,
+            linear,
+            fir16,
+            fir64
+        );
+    }
+
+    #[test]
+    fn test_oversampling_params_serialize_and_delay_dry() {
+        let mut sat = Saturation::new();
+        assert_eq!(sat.oversample_factor(), 1);
+        assert_eq!(sat.latency_samples(), 0);
+        assert!(sat.set_oversample_factor(3).is_err());
+
+        sat.set_oversample_factor(8).unwrap();
+        sat.set_oversample_quality(OversampleQuality::Fir64);
+        assert_eq!(sat.latency_samples(), OVERSAMPLING_LATENCY);
+        let json = sat.to_json().unwrap();
+        assert_eq!(json["oversampleFactor"], 8);
+        assert_eq!(json["oversampleQuality"], "FIR64");
+
+        let mut restored = Saturation::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.oversample_factor(), 8);
+        assert_eq!(restored.oversample_quality(), OversampleQuality::Fir64);
+
+        let mut bad = json.clone();
+        bad["oversampleFactor"] = serde_json::json!(5);
+        assert!(restored.from_json(&bad).is_err());
+
+        // A fully dry mix is the input delayed by the reported latency
+        restored.set_mix(0.0).unwrap();
+        restored.prepare(44100.0, 256);
+        let mut buffer = AudioBuffer::new(1, 256, 44100.0);
+        buffer.set(10, 0, 0.5);
+        restored.process(&mut buffer);
+        assert_eq!(buffer.get(10 + OVERSAMPLING_LATENCY, 0), Some(0.5));
+        assert_eq!(buffer.get(10, 0), Some(0.0));
+    }
+
+    #[test]
+    fn test_quality_switch_mid_stream_is_continuous() {
+        let sample_rate = 44100.0;
+        let mut sat = driven(4, OversampleQuality::Linear);
+        sat.prepare(sample_rate, 512);
+
+        let mut output = Vec::new();
+        for block in 0..8 {
+            if block == 4 {
+                sat.set_oversample_quality(OversampleQuality::Fir64);
+            }
+            let mut buffer = AudioBuffer::new(1, 512, sample_rate);
+            for i in 0..512 {
+                let t = (block * 512 + i) as f64 / sample_rate;
+                let x = 0.7 * (2.0 * std::f64::consts::PI * 220.0 * t).sin();
+                buffer.set(i, 0, x as f32);
+            }
+            sat.process(&mut buffer);
+            output.extend_from_slice(buffer.samples());
+        }
+
+        // The largest step across the switch is no bigger than the
+        // waveform's own steepest step
+        let step = |range: std::ops::Range<usize>| {
+            output[range]
+                .windows(2)
+                .map(|w| (w[1] - w[0]).abs())
+                .fold(0.0_f32, f32::max)
+        };
+        let steady = step(512..2048);
+        let across = step(2040..2056);
+        assert!(
+            across <= steady * 1.1,
+            "step across switch {} vs steady {}",
+            across,
+            steady
+        );
+    }
 }
@@ -0,0 +1,456 @@
+//! Tremolo effect
+//!
+//! Amplitude modulation by a low-frequency oscillator. The LFO phase runs
+//! on across process calls so the modulation has no seams at block
+//! boundaries. The square wave's steps are smoothed over a couple of
+//! milliseconds, since an instantaneous gain jump clicks.
+
+use super::effect::{Effect, EffectMetadata};
+use super::AudioBuffer;
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+/// Time constant of the square-wave gain smoothing in milliseconds
+pub const TREMOLO_SQUARE_SMOOTHING_MS: f64 = 2.0;
+
+/// LFO waveform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LfoWaveform {
+    #[default]
+    Sine,
+    Triangle,
+    Square,
+}
+
+impl LfoWaveform {
+    /// Unipolar LFO value (0 to 1) at `phase` (0 to 1), starting at 0
+    fn value(self, phase: f64) -> f64 {
+        match self {
+            LfoWaveform::Sine => 0.5 - 0.5 * (TAU * phase).cos(),
+            LfoWaveform::Triangle => 1.0 - (2.0 * phase - 1.0).abs(),
+            LfoWaveform::Square => {
+                if phase < 0.5 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// Tremolo parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TremoloParams {
+    /// LFO rate in Hz (0.1 to 20)
+    pub rate_hz: f32,
+    /// Modulation depth (0 = passthrough, 1 = full cut at the LFO peak)
+    pub depth: f32,
+    /// LFO shape
+    pub waveform: LfoWaveform,
+}
+
+impl Default for TremoloParams {
+    fn default() -> Self {
+        Self {
+            rate_hz: 5.0,
+            depth: 0.5,
+            waveform: LfoWaveform::Sine,
+        }
+    }
+}
+
+impl TremoloParams {
+    /// Validate all parameters are within range
+    pub fn validate(&self) -> Result<()> {
+        if !(0.1..=20.0).contains(&self.rate_hz) {
+            return Err(NuevaError::InvalidParameter {
+                param: "rate_hz".to_string(),
+                value: self.rate_hz.to_string(),
+                expected: "0.1 to 20 Hz".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.depth) {
+            return Err(NuevaError::InvalidParameter {
+                param: "depth".to_string(),
+                value: self.depth.to_string(),
+                expected: "0.0 to 1.0".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// LFO amplitude modulation
+#[derive(Debug, Clone)]
+pub struct Tremolo {
+    params: TremoloParams,
+    id: String,
+    enabled: bool,
+    sample_rate: f64,
+    /// LFO phase (0 to 1), carried across blocks
+    phase: f64,
+    /// Smoothed gain for the square wave
+    gain: f64,
+}
+
+impl Tremolo {
+    /// Create a tremolo with default parameters
+    pub fn new() -> Self {
+        Self::with_params(TremoloParams::default())
+    }
+
+    /// Create a tremolo with the given parameters
+    pub fn with_params(params: TremoloParams) -> Self {
+        Self {
+            params,
+            id: String::new(),
+            enabled: true,
+            sample_rate: 48000.0,
+            phase: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Get a reference to the current parameters
+    pub fn params(&self) -> &TremoloParams {
+        &self.params
+    }
+
+    /// Set parameters with validation; the LFO phase is kept
+    pub fn set_params(&mut self, params: TremoloParams) -> Result<()> {
+        params.validate()?;
+        self.params = params;
+        Ok(())
+    }
+
+    /// Set the LFO rate in Hz
+    pub fn set_rate_hz(&mut self, rate_hz: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.rate_hz = rate_hz;
+        self.set_params(params)
+    }
+
+    /// Set the modulation depth
+    pub fn set_depth(&mut self, depth: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.depth = depth;
+        self.set_params(params)
+    }
+
+    /// Set the LFO waveform
+    pub fn set_waveform(&mut self, waveform: LfoWaveform) {
+        self.params.waveform = waveform;
+    }
+
+    /// Per-sample coefficient of the square-wave gain smoothing
+    fn square_smoothing(&self) -> f64 {
+        1.0 - (-1000.0 / (TREMOLO_SQUARE_SMOOTHING_MS * self.sample_rate)).exp()
+    }
+
+    /// Gain for the next frame, advancing the LFO
+    fn next_gain(&mut self, smoothing: f64) -> f64 {
+        let target = 1.0 - self.params.depth as f64 * self.params.waveform.value(self.phase);
+        self.gain = match self.params.waveform {
+            LfoWaveform::Square => self.gain + (target - self.gain) * smoothing,
+            _ => target,
+        };
+        self.phase = (self.phase + self.params.rate_hz as f64 / self.sample_rate).fract();
+        self.gain
+    }
+}
+
+impl Default for Tremolo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Tremolo {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled {
+            return;
+        }
+
+        let smoothing = self.square_smoothing();
+        let num_channels = buffer.num_channels().max(1);
+        for frame in buffer.samples_mut().chunks_mut(num_channels) {
+            let gain = self.next_gain(smoothing);
+            for sample in frame {
+                *sample = (*sample as f64 * gain) as f32;
+            }
+        }
+    }
+
+    fn process_to(&mut self, input: &AudioBuffer, output: &mut AudioBuffer) {
+        if !self.enabled {
+            output.copy_from(input);
+            return;
+        }
+
+        output.resize_to_match(input);
+        let smoothing = self.square_smoothing();
+        let num_channels = input.num_channels().max(1);
+        let frames = input.samples().chunks(num_channels);
+        for (out, frame) in output.samples_mut().chunks_mut(num_channels).zip(frames) {
+            let gain = self.next_gain(smoothing);
+            for (o, &sample) in out.iter_mut().zip(frame) {
+                *o = (sample as f64 * gain) as f32;
+            }
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.gain = 1.0;
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "effect_type": self.effect_type(),
+            "id": self.id,
+            "enabled": self.enabled,
+            "params": {
+                "rate_hz": self.params.rate_hz,
+                "depth": self.params.depth,
+                "waveform": self.params.waveform,
+            }
+        }))
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+            self.id = id.to_string();
+        }
+
+        if let Some(enabled) = json.get("enabled").and_then(|v| v.as_bool()) {
+            self.enabled = enabled;
+        }
+
+        if let Some(params) = json.get("params") {
+            let mut new_params = self.params.clone();
+
+            if let Some(v) = params.get("rate_hz").and_then(|v| v.as_f64()) {
+                new_params.rate_hz = v as f32;
+            }
+            if let Some(v) = params.get("depth").and_then(|v| v.as_f64()) {
+                new_params.depth = v as f32;
+            }
+            if let Some(v) = params.get("waveform") {
+                new_params.waveform = serde_json::from_value(v.clone()).map_err(|e| {
+                    NuevaError::SerializationError {
+                        details: format!("waveform: {}", e),
+                    }
+                })?;
+            }
+
+            self.set_params(new_params)?;
+        }
+
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "tremolo"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Tremolo"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: "tremolo".to_string(),
+            display_name: "Tremolo".to_string(),
+            category: "modulation".to_string(),
+            order_priority: 4, // Creative color, alongside saturation
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("rate_hz", 0.1, 20.0, 5.0).with_unit("Hz"),
+            ParamSpec::float("depth", 0.0, 1.0, 0.5),
+            ParamSpec::options("waveform", &["sine", "triangle", "square"], "sine"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(num_samples: usize) -> AudioBuffer {
+        AudioBuffer::from_interleaved(vec![0.5; num_samples * 2], 2, 48000.0).unwrap()
+    }
+
+    fn process_in_blocks(tremolo: &mut Tremolo, input: &AudioBuffer, block: usize) -> Vec<f32> {
+        let mut out = Vec::new();
+        for chunk in input.samples().chunks(block * 2) {
+            let mut buf = AudioBuffer::from_interleaved(chunk.to_vec(), 2, 48000.0).unwrap();
+            tremolo.process(&mut buf);
+            out.extend_from_slice(buf.samples());
+        }
+        out
+    }
+
+    #[test]
+    fn test_zero_depth_is_passthrough() {
+        let mut tremolo = Tremolo::with_params(TremoloParams {
+            rate_hz: 7.0,
+            depth: 0.0,
+            waveform: LfoWaveform::Square,
+        });
+        tremolo.prepare(48000.0, 512);
+
+        let dry = constant(4800);
+        let mut wet = dry.clone();
+        tremolo.process(&mut wet);
+        assert_eq!(wet.samples(), dry.samples());
+    }
+
+    #[test]
+    fn test_modulation_follows_lfo() {
+        let mut tremolo = Tremolo::with_params(TremoloParams {
+            rate_hz: 10.0,
+            depth: 1.0,
+            waveform: LfoWaveform::Triangle,
+        });
+        tremolo.prepare(48000.0, 512);
+
+        let mut buffer = constant(4800);
+        tremolo.process(&mut buffer);
+        // Unity at the start of each period, silent half way through
+        assert_eq!(buffer.get(0, 0), Some(0.5));
+        assert!(buffer.get(2400, 1).unwrap().abs() < 1e-6);
+        assert!((buffer.get(4800 - 2400 / 2, 0).unwrap() - 0.25).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_phase_is_continuous_across_blocks() {
+        for waveform in [
+            LfoWaveform::Sine,
+            LfoWaveform::Triangle,
+            LfoWaveform::Square,
+        ] {
+            let params = TremoloParams {
+                rate_hz: 3.3,
+                depth: 0.8,
+                waveform,
+            };
+            let input = constant(10_000);
+
+            let mut whole = input.clone();
+            let mut reference = Tremolo::with_params(params.clone());
+            reference.prepare(48000.0, 10_000);
+            reference.process(&mut whole);
+
+            let mut blocked = Tremolo::with_params(params);
+            blocked.prepare(48000.0, 333);
+            let out = process_in_blocks(&mut blocked, &input, 333);
+
+            for (a, b) in out.iter().zip(whole.samples()) {
+                assert!((a - b).abs() < 1e-6, "{:?}", waveform);
+            }
+        }
+    }
+
+    #[test]
+    fn test_square_wave_is_ramped() {
+        let mut tremolo = Tremolo::with_params(TremoloParams {
+            rate_hz: 10.0,
+            depth: 1.0,
+            waveform: LfoWaveform::Square,
+        });
+        tremolo.prepare(48000.0, 512);
+
+        let mut buffer = constant(9600);
+        tremolo.process(&mut buffer);
+
+        let left: Vec<f32> = buffer.samples().iter().step_by(2).copied().collect();
+        let max_step = left
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0_f32, f32::max);
+        // An unsmoothed step would jump the full 0.5 in one sample
+        assert!(max_step < 0.02, "max step {}", max_step);
+        // ...but the gain still settles at both extremes
+        assert!(left[2399] > 0.499);
+        assert!(left[4799] < 0.001);
+    }
+
+    #[test]
+    fn test_process_to_leaves_input_alone() {
+        let params = TremoloParams {
+            rate_hz: 6.0,
+            depth: 1.0,
+            waveform: LfoWaveform::Square,
+        };
+        let input = constant(10_000);
+
+        let mut reference = Tremolo::with_params(params.clone());
+        reference.prepare(48000.0, 333);
+        let expected = process_in_blocks(&mut reference, &input, 333);
+
+        let mut tremolo = Tremolo::with_params(params);
+        tremolo.prepare(48000.0, 333);
+        let mut output = AudioBuffer::new(2, 0, 48000.0);
+        let mut out = Vec::new();
+        for chunk in input.samples().chunks(333 * 2) {
+            let block = AudioBuffer::from_interleaved(chunk.to_vec(), 2, 48000.0).unwrap();
+            tremolo.process_to(&block, &mut output);
+            assert!(block.samples().iter().all(|&s| s == 0.5));
+            out.extend_from_slice(output.samples());
+        }
+        assert_eq!(out, expected);
+
+        tremolo.set_enabled(false);
+        tremolo.process_to(&input, &mut output);
+        assert_eq!(output.samples(), input.samples());
+    }
+
+    #[test]
+    fn test_validation_and_serialization() {
+        assert!(Tremolo::new().set_depth(1.5).is_err());
+        assert!(Tremolo::new().set_rate_hz(0.0).is_err());
+
+        let mut tremolo = Tremolo::with_params(TremoloParams {
+            rate_hz: 4.0,
+            depth: 0.3,
+            waveform: LfoWaveform::Triangle,
+        });
+        tremolo.set_id("trem-1".to_string());
+        let json = tremolo.to_json().unwrap();
+        assert_eq!(json["params"]["waveform"], "triangle");
+
+        let mut restored = Tremolo::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "trem-1");
+        assert_eq!(restored.params().rate_hz, 4.0);
+        assert_eq!(restored.params().waveform, LfoWaveform::Triangle);
+    }
+}
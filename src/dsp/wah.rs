@@ -0,0 +1,537 @@
+//! Wah effect
+//!
+//! A resonant band-pass whose center frequency sweeps between `freq_min`
+//! and `freq_max`, driven either by a sine LFO (auto wah) or by an
+//! envelope follower on the input level (envelope wah), so louder playing
+//! opens the filter. The sweep is exponential in frequency, and the
+//! coefficients are recomputed every few samples rather than per sample.
+
+use super::effect::{Effect, EffectMetadata};
+use super::eq::{BiquadCoeffs, BiquadState};
+use super::AudioBuffer;
+use crate::error::{NuevaError, Result};
+use crate::neural::ParamSpec;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+/// Samples between filter coefficient updates
+const WAH_UPDATE_INTERVAL: usize = 16;
+
+/// Envelope follower attack time in milliseconds
+const WAH_ATTACK_MS: f64 = 5.0;
+
+/// Envelope follower release time in milliseconds
+const WAH_RELEASE_MS: f64 = 80.0;
+
+/// Envelope gain at full sensitivity: a peak level of 1/20 (-26 dBFS)
+/// then reaches the top of the sweep
+pub const WAH_MAX_SENSITIVITY_GAIN: f64 = 20.0;
+
+/// What moves the filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WahMode {
+    /// Swept by a sine LFO at `rate_hz`
+    AutoWah,
+    /// Swept by the input level
+    #[default]
+    EnvelopeWah,
+}
+
+/// Wah parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WahParams {
+    /// Sweep source
+    pub mode: WahMode,
+    /// Bottom of the sweep in Hz (100 to 1000)
+    pub freq_min: f32,
+    /// Top of the sweep in Hz (1000 to 8000)
+    pub freq_max: f32,
+    /// Filter Q (0.5 to 10)
+    pub resonance: f32,
+    /// How far the input level pushes the sweep in envelope mode (0 to 1)
+    pub sensitivity: f32,
+    /// LFO rate in Hz for auto mode (0.1 to 10)
+    pub rate_hz: f32,
+}
+
+impl Default for WahParams {
+    fn default() -> Self {
+        Self {
+            mode: WahMode::EnvelopeWah,
+            freq_min: 400.0,
+            freq_max: 2500.0,
+            resonance: 3.0,
+            sensitivity: 0.5,
+            rate_hz: 1.5,
+        }
+    }
+}
+
+impl WahParams {
+    /// Validate all parameters are within range
+    pub fn validate(&self) -> Result<()> {
+        let checks = [
+            ("freq_min", self.freq_min, 100.0, 1000.0, "100 to 1000 Hz"),
+            ("freq_max", self.freq_max, 1000.0, 8000.0, "1000 to 8000 Hz"),
+            ("resonance", self.resonance, 0.5, 10.0, "0.5 to 10"),
+            ("sensitivity", self.sensitivity, 0.0, 1.0, "0.0 to 1.0"),
+            ("rate_hz", self.rate_hz, 0.1, 10.0, "0.1 to 10 Hz"),
+        ];
+        for (param, value, min, max, expected) in checks {
+            if !(min..=max).contains(&value) {
+                return Err(NuevaError::InvalidParameter {
+                    param: param.to_string(),
+                    value: value.to_string(),
+                    expected: expected.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Swept band-pass filter
+#[derive(Debug, Clone)]
+pub struct Wah {
+    params: WahParams,
+    id: String,
+    enabled: bool,
+    sample_rate: f64,
+    coeffs: BiquadCoeffs,
+    /// One filter state per channel
+    states: Vec<BiquadState>,
+    /// LFO phase (0 to 1), carried across blocks
+    phase: f64,
+    /// Envelope follower level (linear peak)
+    envelope: f64,
+    /// Samples until the next coefficient update
+    countdown: usize,
+}
+
+impl Wah {
+    /// Create a wah with default parameters
+    pub fn new() -> Self {
+        Self::with_params(WahParams::default())
+    }
+
+    /// Create a wah with the given parameters
+    pub fn with_params(params: WahParams) -> Self {
+        Self {
+            params,
+            id: String::new(),
+            enabled: true,
+            sample_rate: 48000.0,
+            coeffs: BiquadCoeffs::default(),
+            states: Vec::new(),
+            phase: 0.0,
+            envelope: 0.0,
+            countdown: 0,
+        }
+    }
+
+    /// Get a reference to the current parameters
+    pub fn params(&self) -> &WahParams {
+        &self.params
+    }
+
+    /// Set parameters with validation; the sweep state is kept
+    pub fn set_params(&mut self, params: WahParams) -> Result<()> {
+        params.validate()?;
+        self.params = params;
+        self.countdown = 0;
+        Ok(())
+    }
+
+    /// Set the sweep source
+    pub fn set_mode(&mut self, mode: WahMode) {
+        self.params.mode = mode;
+    }
+
+    /// Set the sweep range in Hz
+    pub fn set_range(&mut self, freq_min: f32, freq_max: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.freq_min = freq_min;
+        params.freq_max = freq_max;
+        self.set_params(params)
+    }
+
+    /// Set the filter Q
+    pub fn set_resonance(&mut self, resonance: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.resonance = resonance;
+        self.set_params(params)
+    }
+
+    /// Set the envelope sensitivity
+    pub fn set_sensitivity(&mut self, sensitivity: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.sensitivity = sensitivity;
+        self.set_params(params)
+    }
+
+    /// Set the LFO rate in Hz
+    pub fn set_rate_hz(&mut self, rate_hz: f32) -> Result<()> {
+        let mut params = self.params.clone();
+        params.rate_hz = rate_hz;
+        self.set_params(params)
+    }
+
+    /// Current filter center frequency in Hz
+    pub fn center_frequency(&self) -> f64 {
+        let min = self.params.freq_min as f64;
+        let max = self.params.freq_max as f64;
+        min * (max / min).powf(self.sweep_position())
+    }
+
+    /// Sweep position from 0 (`freq_min`) to 1 (`freq_max`)
+    fn sweep_position(&self) -> f64 {
+        match self.params.mode {
+            WahMode::AutoWah => 0.5 - 0.5 * (TAU * self.phase).cos(),
+            WahMode::EnvelopeWah => {
+                let gain = WAH_MAX_SENSITIVITY_GAIN.powf(self.params.sensitivity as f64);
+                (self.envelope * gain).min(1.0)
+            }
+        }
+    }
+}
+
+impl Default for Wah {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Wah {
+    fn process(&mut self, buffer: &mut AudioBuffer) {
+        if !self.enabled {
+            return;
+        }
+
+        let num_channels = buffer.num_channels().max(1);
+        self.states.resize_with(num_channels, BiquadState::default);
+
+        let increment = self.params.rate_hz as f64 / self.sample_rate;
+        let attack = (-1000.0 / (WAH_ATTACK_MS * self.sample_rate)).exp();
+        let release = (-1000.0 / (WAH_RELEASE_MS * self.sample_rate)).exp();
+
+        for frame in buffer.samples_mut().chunks_mut(num_channels) {
+            let peak = frame.iter().fold(0.0_f64, |m, &s| m.max((s as f64).abs()));
+            let coeff = if peak > self.envelope {
+                attack
+            } else {
+                release
+            };
+            self.envelope = peak + coeff * (self.envelope - peak);
+
+            if self.countdown == 0 {
+                self.coeffs = BiquadCoeffs::band_pass(
+                    self.sample_rate,
+                    self.center_frequency(),
+                    self.params.resonance as f64,
+                );
+                self.countdown = WAH_UPDATE_INTERVAL;
+            }
+            self.countdown -= 1;
+
+            for (sample, state) in frame.iter_mut().zip(&mut self.states) {
+                *sample = state.process(*sample as f64, &self.coeffs) as f32;
+            }
+            self.phase = (self.phase + increment).fract();
+        }
+    }
+
+    fn prepare(&mut self, sample_rate: f64, _samples_per_block: usize) {
+        self.sample_rate = sample_rate;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        for state in &mut self.states {
+            state.reset();
+        }
+        self.phase = 0.0;
+        self.envelope = 0.0;
+        self.countdown = 0;
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "effect_type": self.effect_type(),
+            "id": self.id,
+            "enabled": self.enabled,
+            "params": {
+                "mode": self.params.mode,
+                "freq_min": self.params.freq_min,
+                "freq_max": self.params.freq_max,
+                "resonance": self.params.resonance,
+                "sensitivity": self.params.sensitivity,
+                "rate_hz": self.params.rate_hz,
+            }
+        }))
+    }
+
+    fn from_json(&mut self, json: &serde_json::Value) -> Result<()> {
+        if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+            self.id = id.to_string();
+        }
+
+        if let Some(enabled) = json.get("enabled").and_then(|v| v.as_bool()) {
+            self.enabled = enabled;
+        }
+
+        if let Some(params) = json.get("params") {
+            let mut new_params = self.params.clone();
+
+            if let Some(v) = params.get("mode") {
+                new_params.mode = serde_json::from_value(v.clone()).map_err(|e| {
+                    NuevaError::SerializationError {
+                        details: format!("mode: {}", e),
+                    }
+                })?;
+            }
+            if let Some(v) = params.get("freq_min").and_then(|v| v.as_f64()) {
+                new_params.freq_min = v as f32;
+            }
+            if let Some(v) = params.get("freq_max").and_then(|v| v.as_f64()) {
+                new_params.freq_max = v as f32;
+            }
+            if let Some(v) = params.get("resonance").and_then(|v| v.as_f64()) {
+                new_params.resonance = v as f32;
+            }
+            if let Some(v) = params.get("sensitivity").and_then(|v| v.as_f64()) {
+                new_params.sensitivity = v as f32;
+            }
+            if let Some(v) = params.get("rate_hz").and_then(|v| v.as_f64()) {
+                new_params.rate_hz = v as f32;
+            }
+
+            self.set_params(new_params)?;
+        }
+
+        Ok(())
+    }
+
+    fn effect_type(&self) -> &'static str {
+        "wah"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Wah"
+    }
+
+    fn metadata(&self) -> EffectMetadata {
+        EffectMetadata {
+            effect_type: "wah".to_string(),
+            display_name: "Wah".to_string(),
+            category: "modulation".to_string(),
+            order_priority: 4, // Creative color, alongside saturation
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::options("mode", &["auto_wah", "envelope_wah"], "envelope_wah"),
+            ParamSpec::float("freq_min", 100.0, 1000.0, 400.0).with_unit("Hz"),
+            ParamSpec::float("freq_max", 1000.0, 8000.0, 2500.0).with_unit("Hz"),
+            ParamSpec::float("resonance", 0.5, 10.0, 3.0),
+            ParamSpec::float("sensitivity", 0.0, 1.0, 0.5),
+            ParamSpec::float("rate_hz", 0.1, 10.0, 1.5).with_unit("Hz"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quiet noise with a loud burst in the middle
+    fn burst(num_samples: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(1, num_samples, 48000.0);
+        let mut seed = 7u32;
+        for i in 0..num_samples {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let white = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+            let level = if (num_samples / 2..num_samples / 2 + 4800).contains(&i) {
+                1.6
+            } else {
+                0.02
+            };
+            buffer.set(i, 0, white * level);
+        }
+        buffer
+    }
+
+    /// Zero-crossing rate in crossings per second, a cheap centroid proxy
+    fn crossing_rate(samples: &[f32]) -> f64 {
+        let crossings = samples
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count();
+        crossings as f64 * 48000.0 / samples.len() as f64
+    }
+
+    /// Power-weighted mean frequency of `samples` from a direct DFT
+    fn spectral_centroid(samples: &[f32]) -> f64 {
+        let n = samples.len();
+        let (mut weighted, mut total) = (0.0, 0.0);
+        for k in (1..n / 2).step_by(4) {
+            let (mut re, mut im) = (0.0, 0.0);
+            for (i, &s) in samples.iter().enumerate() {
+                let angle = TAU * (k * i) as f64 / n as f64;
+                re += s as f64 * angle.cos();
+                im -= s as f64 * angle.sin();
+            }
+            let power = re * re + im * im;
+            weighted += power * k as f64 * 48000.0 / n as f64;
+            total += power;
+        }
+        weighted / total
+    }
+
+    #[test]
+    fn test_envelope_sweeps_up_on_transients() {
+        let mut wah = Wah::with_params(WahParams {
+            sensitivity: 0.0,
+            ..WahParams::default()
+        });
+        wah.prepare(48000.0, 512);
+
+        let mut buffer = burst(48000);
+        wah.process(&mut buffer);
+
+        let quiet = &buffer.samples()[18000..20048];
+        let loud = &buffer.samples()[26000..28048];
+        let quiet_centroid = spectral_centroid(quiet);
+        let loud_centroid = spectral_centroid(loud);
+        assert!(
+            loud_centroid > quiet_centroid * 1.5,
+            "quiet {:.0} Hz, loud {:.0} Hz",
+            quiet_centroid,
+            loud_centroid
+        );
+        assert!(crossing_rate(loud) > crossing_rate(quiet));
+    }
+
+    #[test]
+    fn test_sensitivity_scales_the_sweep() {
+        let mut dull = Wah::with_params(WahParams {
+            sensitivity: 0.0,
+            ..WahParams::default()
+        });
+        let mut keen = Wah::with_params(WahParams {
+            sensitivity: 1.0,
+            ..WahParams::default()
+        });
+        for wah in [&mut dull, &mut keen] {
+            wah.prepare(48000.0, 512);
+            let mut buffer = AudioBuffer::from_interleaved(vec![0.1; 4800], 1, 48000.0).unwrap();
+            wah.process(&mut buffer);
+        }
+        assert!((dull.center_frequency() - 400.0 * 6.25f64.powf(0.1)).abs() < 5.0);
+        assert!((keen.center_frequency() - 2500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_auto_wah_follows_lfo() {
+        let mut wah = Wah::with_params(WahParams {
+            mode: WahMode::AutoWah,
+            rate_hz: 2.0,
+            ..WahParams::default()
+        });
+        wah.prepare(48000.0, 512);
+        assert!((wah.center_frequency() - 400.0).abs() < 1e-6);
+
+        // Half an LFO period later the filter is at the top, even in silence
+        let mut silence = AudioBuffer::new(2, 12000, 48000.0);
+        wah.process(&mut silence);
+        assert!((wah.center_frequency() - 2500.0).abs() < 1.0);
+        assert!(silence.samples().iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_max_resonance_is_stable() {
+        for mode in [WahMode::AutoWah, WahMode::EnvelopeWah] {
+            let mut wah = Wah::with_params(WahParams {
+                mode,
+                freq_min: 100.0,
+                freq_max: 8000.0,
+                resonance: 10.0,
+                sensitivity: 1.0,
+                rate_hz: 10.0,
+            });
+            wah.prepare(48000.0, 512);
+
+            // Full-scale square wave: the harshest steps the sweep can see
+            let samples = (0..96000)
+                .map(|i| if (i / 37) % 2 == 0 { 1.0 } else { -1.0 })
+                .collect();
+            let mut buffer = AudioBuffer::from_interleaved(samples, 2, 48000.0).unwrap();
+            wah.process(&mut buffer);
+            assert!(buffer
+                .samples()
+                .iter()
+                .all(|s| s.is_finite() && s.abs() < 4.0));
+        }
+    }
+
+    #[test]
+    fn test_reset_clears_sweep_state() {
+        let mut wah = Wah::new();
+        wah.prepare(48000.0, 512);
+        let input = burst(12000);
+
+        let mut first = input.clone();
+        wah.process(&mut first);
+        wah.reset();
+        let mut second = input.clone();
+        wah.process(&mut second);
+        assert_eq!(first.samples(), second.samples());
+    }
+
+    #[test]
+    fn test_validation_and_serialization() {
+        assert!(Wah::new().set_resonance(20.0).is_err());
+        assert!(Wah::new().set_range(50.0, 2000.0).is_err());
+        assert!(Wah::new().set_sensitivity(1.5).is_err());
+
+        let mut wah = Wah::with_params(WahParams {
+            mode: WahMode::AutoWah,
+            freq_min: 300.0,
+            freq_max: 3000.0,
+            resonance: 6.0,
+            sensitivity: 0.8,
+            rate_hz: 4.0,
+        });
+        wah.set_id("wah-1".to_string());
+        let json = wah.to_json().unwrap();
+        assert_eq!(json["params"]["mode"], "auto_wah");
+
+        let mut restored = Wah::new();
+        restored.from_json(&json).unwrap();
+        assert_eq!(restored.id(), "wah-1");
+        assert_eq!(restored.params().mode, WahMode::AutoWah);
+        assert_eq!(restored.params().freq_max, 3000.0);
+        assert_eq!(restored.params().resonance, 6.0);
+
+        let mut bad = json.clone();
+        bad["params"]["mode"] = serde_json::json!("pedal");
+        assert!(Wah::new().from_json(&bad).is_err());
+    }
+}
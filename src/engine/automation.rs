@@ -0,0 +1,295 @@
+//! Gain Automation
+//!
+//! Sample-accurate gain envelopes for fades and rides. An [`Automation`]
+//! is a list of `(sample_index, gain_db)` breakpoints; gain between two
+//! breakpoints is interpolated either in dB or in linear amplitude, and
+//! held flat before the first and after the last breakpoint.
+
+use serde::{Deserialize, Serialize};
+
+use super::buffer::db_to_linear;
+
+/// Gains at or below this are treated as silence
+///
+/// Also stands in for negative infinity, which JSON cannot store.
+pub const AUTOMATION_FLOOR_DB: f32 = -144.0;
+
+/// How gain moves between breakpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationCurve {
+    /// Straight line in dB (sounds even for rides)
+    #[default]
+    LinearDb,
+    /// Straight line in amplitude (the classic linear fade)
+    LinearAmplitude,
+}
+
+/// A gain value at a sample position
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub sample_index: usize,
+    pub gain_db: f32,
+}
+
+/// Serialized form, normalized on the way in
+#[derive(Deserialize)]
+struct RawAutomation {
+    breakpoints: Vec<Breakpoint>,
+    #[serde(default)]
+    curve: AutomationCurve,
+}
+
+impl From<RawAutomation> for Automation {
+    fn from(raw: RawAutomation) -> Self {
+        Automation::new(raw.breakpoints, raw.curve)
+    }
+}
+
+/// Gain envelope over an audio buffer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawAutomation")]
+pub struct Automation {
+    /// Sorted by sample index, one breakpoint per index
+    breakpoints: Vec<Breakpoint>,
+    curve: AutomationCurve,
+}
+
+impl Automation {
+    /// Build an automation from breakpoints in any order
+    ///
+    /// Breakpoints are sorted by position; when several share a sample
+    /// index the last one given wins. Gains below [`AUTOMATION_FLOOR_DB`]
+    /// (including negative infinity) are stored as the floor, and NaN
+    /// gains are dropped.
+    pub fn new(breakpoints: impl IntoIterator<Item = Breakpoint>, curve: AutomationCurve) -> Self {
+        let mut sorted: Vec<Breakpoint> = breakpoints
+            .into_iter()
+            .filter(|b| !b.gain_db.is_nan())
+            .map(|b| Breakpoint {
+                sample_index: b.sample_index,
+                gain_db: b.gain_db.max(AUTOMATION_FLOOR_DB),
+            })
+            .collect();
+
+        // Stable sort keeps input order within an index; keep the last
+        sorted.sort_by_key(|b| b.sample_index);
+        let mut breakpoints: Vec<Breakpoint> = Vec::with_capacity(sorted.len());
+        for breakpoint in sorted {
+            match breakpoints.last_mut() {
+                Some(last) if last.sample_index == breakpoint.sample_index => *last = breakpoint,
+                _ => breakpoints.push(breakpoint),
+            }
+        }
+
+        Self { breakpoints, curve }
+    }
+
+    /// A flat gain over the whole buffer
+    pub fn constant(gain_db: f32) -> Self {
+        Self::new(
+            [Breakpoint {
+                sample_index: 0,
+                gain_db,
+            }],
+            AutomationCurve::LinearDb,
+        )
+    }
+
+    /// A ramp between two gains over `[start, end]`
+    pub fn ramp(
+        start: usize,
+        start_db: f32,
+        end: usize,
+        end_db: f32,
+        curve: AutomationCurve,
+    ) -> Self {
+        Self::new(
+            [
+                Breakpoint {
+                    sample_index: start,
+                    gain_db: start_db,
+                },
+                Breakpoint {
+                    sample_index: end,
+                    gain_db: end_db,
+                },
+            ],
+            curve,
+        )
+    }
+
+    /// The normalized breakpoints
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Interpolation curve
+    pub fn curve(&self) -> AutomationCurve {
+        self.curve
+    }
+
+    /// Whether there are no breakpoints (unity gain everywhere)
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// Linear gain at a sample position
+    ///
+    /// Before the first and after the last breakpoint the nearest gain is
+    /// held, so breakpoints outside a buffer simply shape the part of the
+    /// ramp that falls inside it.
+    pub fn gain_at(&self, sample_index: usize) -> f32 {
+        let next = self
+            .breakpoints
+            .partition_point(|b| b.sample_index <= sample_index);
+        match (
+            next.checked_sub(1).map(|i| &self.breakpoints[i]),
+            self.breakpoints.get(next),
+        ) {
+            (None, None) => 1.0,
+            (Some(only), None) | (None, Some(only)) => to_linear(only.gain_db),
+            (Some(a), Some(b)) => self.interpolate(a, b, sample_index),
+        }
+    }
+
+    /// Gain for every sample of a buffer of length `len`
+    pub fn gains(&self, len: usize) -> Vec<f32> {
+        (0..len).map(|i| self.gain_at(i)).collect()
+    }
+
+    fn interpolate(&self, a: &Breakpoint, b: &Breakpoint, sample_index: usize) -> f32 {
+        let t = (sample_index - a.sample_index) as f32 / (b.sample_index - a.sample_index) as f32;
+        match self.curve {
+            AutomationCurve::LinearDb => {
+                // Gains at the floor are silence; fading from or to them in
+                // dB would jump at the endpoint, so ramp in amplitude
+                if a.gain_db <= AUTOMATION_FLOOR_DB || b.gain_db <= AUTOMATION_FLOOR_DB {
+                    return lerp(to_linear(a.gain_db), to_linear(b.gain_db), t);
+                }
+                db_to_linear(lerp(a.gain_db, b.gain_db, t))
+            }
+            AutomationCurve::LinearAmplitude => lerp(to_linear(a.gain_db), to_linear(b.gain_db), t),
+        }
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// dB to linear, with the floor mapping to true silence
+fn to_linear(gain_db: f32) -> f32 {
+    if gain_db <= AUTOMATION_FLOOR_DB {
+        0.0
+    } else {
+        db_to_linear(gain_db)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::{AudioBuffer, ChannelLayout};
+
+    fn ones(len: usize) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(len, ChannelLayout::Stereo);
+        for channel in &mut buffer.samples {
+            channel.fill(1.0);
+        }
+        buffer
+    }
+
+    fn bp(sample_index: usize, gain_db: f32) -> Breakpoint {
+        Breakpoint {
+            sample_index,
+            gain_db,
+        }
+    }
+
+    #[test]
+    fn test_constant_matches_apply_gain() {
+        let mut automated = ones(256);
+        let mut gained = ones(256);
+        automated.apply_automation(&Automation::constant(-7.5));
+        gained.apply_gain(-7.5);
+        assert_eq!(automated.samples, gained.samples);
+    }
+
+    #[test]
+    fn test_fade_in_from_silence() {
+        for curve in [AutomationCurve::LinearDb, AutomationCurve::LinearAmplitude] {
+            let mut buffer = ones(1000);
+            buffer.apply_automation(&Automation::ramp(0, f32::NEG_INFINITY, 999, 0.0, curve));
+
+            for channel in &buffer.samples {
+                assert_eq!(channel[0], 0.0);
+                assert_eq!(channel[999], 1.0);
+                assert!(channel.windows(2).all(|w| w[1] >= w[0]), "{:?}", curve);
+                assert!(channel[500] > 0.0 && channel[500] < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolation_curves() {
+        let db = Automation::ramp(0, -20.0, 100, 0.0, AutomationCurve::LinearDb);
+        assert!((db.gain_at(50) - db_to_linear(-10.0)).abs() < 1e-6);
+
+        let amp = Automation::ramp(0, -20.0, 100, 0.0, AutomationCurve::LinearAmplitude);
+        assert!((amp.gain_at(50) - 0.55).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_breakpoints_outside_buffer_are_held() {
+        let auto = Automation::new([bp(100, -6.0), bp(300, 0.0)], AutomationCurve::LinearDb);
+        assert_eq!(auto.gain_at(0), db_to_linear(-6.0));
+        assert_eq!(auto.gain_at(10_000), 1.0);
+
+        // A ramp ending past the buffer is cut off mid-way
+        let mut buffer = ones(200);
+        buffer.apply_automation(&auto);
+        assert!((buffer.samples[0][199] - db_to_linear(-3.0 - 0.03)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_unsorted_and_duplicate_breakpoints_normalized() {
+        let auto = Automation::new(
+            [bp(50, -3.0), bp(0, -12.0), bp(50, -6.0), bp(25, f32::NAN)],
+            AutomationCurve::LinearDb,
+        );
+        assert_eq!(auto.breakpoints(), &[bp(0, -12.0), bp(50, -6.0)]);
+    }
+
+    #[test]
+    fn test_empty_is_unity() {
+        let mut buffer = ones(10);
+        buffer.apply_automation(&Automation::default());
+        assert!(buffer.samples.iter().flatten().all(|&s| s == 1.0));
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let auto = Automation::ramp(
+            0,
+            f32::NEG_INFINITY,
+            480,
+            -3.0,
+            AutomationCurve::LinearAmplitude,
+        );
+        let json = serde_json::to_string(&auto).unwrap();
+        let restored: Automation = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, auto);
+
+        // Stored data is normalized on load
+        let restored: Automation = serde_json::from_value(serde_json::json!({
+            "breakpoints": [
+                {"sample_index": 10, "gain_db": 0.0},
+                {"sample_index": 0, "gain_db": -6.0}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(restored.breakpoints()[0].sample_index, 0);
+        assert_eq!(restored.curve(), AutomationCurve::LinearDb);
+    }
+}
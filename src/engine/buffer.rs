@@ -1,17 +1,22 @@
 //! Audio Buffer Management
 //!
 //! Provides the core audio buffer type and validation utilities for Nueva.
-//! All internal processing uses 48kHz/32-bit float format per spec 3.2.
+//! All internal processing uses 32-bit float per spec 3.2, at 48kHz unless
+//! a project chooses one of the other supported rates.
 
+use super::automation::Automation;
 use crate::error::{NuevaError, Result};
 
 // ============================================================================
 // Constants (spec 3.2)
 // ============================================================================
 
-/// Internal sample rate for all processing (48kHz)
+/// Default internal sample rate for processing (48kHz)
 pub const INTERNAL_SAMPLE_RATE: u32 = 48000;
 
+/// Sample rates a project can process at
+pub const SUPPORTED_SAMPLE_RATES: [u32; 6] = [44100, 48000, 88200, 96000, 176400, 192000];
+
 /// Minimum audio duration in seconds (100ms)
 pub const MIN_DURATION_SECS: f64 = 0.1;
 
@@ -30,10 +35,54 @@ pub const CLIP_RATIO_THRESHOLD: f32 = 0.01;
 /// Clipping detection threshold (samples at or above this are clipped)
 pub const CLIP_SAMPLE_THRESHOLD: f32 = 1.0;
 
+/// Largest time-stretch ratio; the output is allocated up front, so this
+/// bounds it to eight times the input
+pub const MAX_TIME_STRETCH_RATIO: f32 = 8.0;
+
+/// Click detection: second difference, relative to its local RMS, that is
+/// flagged at sensitivity 1.0 (lower sensitivities divide into this)
+pub const CLICK_BASE_RATIO: f32 = 4.0;
+
+/// Click detection: second differences below this are never clicks
+pub const CLICK_FLOOR: f32 = 1e-3;
+
+/// Click detection: half-width in samples of the local level window
+const CLICK_LEVEL_WINDOW: usize = 128;
+
+/// Click detection: samples either side left out of the local level, so a
+/// click doesn't raise its own threshold
+const CLICK_LEVEL_EXCLUDE: usize = 2;
+
+/// Binary format: magic bytes at the start of [`AudioBuffer::to_bytes`]
+pub const BUFFER_BYTES_MAGIC: &[u8; 4] = b"NVAB";
+
+/// Binary format: current version
+pub const BUFFER_BYTES_VERSION: u16 = 1;
+
+/// Binary format: header size, magic + version (u16) + sample rate (u32)
+/// + channels (u16) + frames (u64)
+const BUFFER_BYTES_HEADER_LEN: usize = 4 + 2 + 4 + 2 + 8;
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+/// Check that `sample_rate` is one a project can process at
+pub fn validate_sample_rate(sample_rate: u32) -> Result<()> {
+    if SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+        return Ok(());
+    }
+    let supported: Vec<String> = SUPPORTED_SAMPLE_RATES
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    Err(NuevaError::InvalidParameter {
+        param: "sample_rate".to_string(),
+        value: sample_rate.to_string(),
+        expected: format!("one of {} Hz", supported.join(", ")),
+    })
+}
+
 /// Convert decibels to linear amplitude
 ///
 /// # Arguments
@@ -154,6 +203,17 @@ pub fn calculate_clip_ratio(buffer: &AudioBuffer) -> f32 {
     clipped_count as f32 / total_samples as f32
 }
 
+/// What [`AudioBuffer::level_series`] measures in each window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelMetric {
+    /// Sample peak across all channels, in dBFS
+    Peak,
+    /// RMS across all channels, in dBFS
+    Rms,
+    /// Ungated K-weighted loudness in LUFS (momentary at a 400 ms window)
+    Lufs,
+}
+
 // ============================================================================
 // Channel Layout
 // ============================================================================
@@ -303,11 +363,16 @@ impl AudioBuffer {
     /// # Returns
     /// A new AudioBuffer with zeroed samples
     pub fn new(num_samples: usize, layout: ChannelLayout) -> Self {
+        Self::with_sample_rate(num_samples, layout, INTERNAL_SAMPLE_RATE)
+    }
+
+    /// Create a silent audio buffer at the given sample rate
+    pub fn with_sample_rate(num_samples: usize, layout: ChannelLayout, sample_rate: u32) -> Self {
         let num_channels = layout.num_channels();
         let samples = vec![vec![0.0_f32; num_samples]; num_channels];
         Self {
             samples,
-            sample_rate: INTERNAL_SAMPLE_RATE,
+            sample_rate,
         }
     }
 
@@ -334,7 +399,7 @@ impl AudioBuffer {
             });
         }
 
-        if interleaved.len() % num_channels != 0 {
+        if !interleaved.len().is_multiple_of(num_channels) {
             return Err(NuevaError::InvalidAudio {
                 reason: format!(
                     "Interleaved data length {} is not divisible by channel count {}",
@@ -360,6 +425,69 @@ impl AudioBuffer {
         })
     }
 
+    /// White noise: uniform in [-1, 1), flat spectrum
+    ///
+    /// Deterministic for a given seed; each channel is independent.
+    pub fn white_noise(duration_secs: f32, layout: ChannelLayout, seed: u64) -> Self {
+        Self::noise(duration_secs, layout, seed, |rng, channel| {
+            for sample in channel.iter_mut() {
+                *sample = rng.next_bipolar();
+            }
+        })
+    }
+
+    /// Pink noise: falls 3 dB per octave, equal energy per octave
+    ///
+    /// White noise through Paul Kellet's refined filter, which tracks
+    /// -3 dB/oct to within 0.05 dB above 9.2 Hz at 44.1 kHz.
+    pub fn pink_noise(duration_secs: f32, layout: ChannelLayout, seed: u64) -> Self {
+        Self::noise(duration_secs, layout, seed, |rng, channel| {
+            let mut b = [0.0_f32; 7];
+            for sample in channel.iter_mut() {
+                let white = rng.next_bipolar();
+                b[0] = 0.99886 * b[0] + white * 0.055_517_9;
+                b[1] = 0.99332 * b[1] + white * 0.075_075_9;
+                b[2] = 0.96900 * b[2] + white * 0.153_852;
+                b[3] = 0.86650 * b[3] + white * 0.310_485_6;
+                b[4] = 0.55000 * b[4] + white * 0.532_952_2;
+                b[5] = -0.7616 * b[5] - white * 0.016_898;
+                let pink = b[..6].iter().sum::<f32>() + b[6] + white * 0.5362;
+                b[6] = white * 0.115_926;
+                *sample = (pink * 0.11).clamp(-1.0, 1.0);
+            }
+        })
+    }
+
+    /// Brown (red) noise: falls 6 dB per octave
+    ///
+    /// Leaky-integrated white noise; the leak keeps it from drifting off
+    /// to DC, flattening the spectrum below roughly 150 Hz.
+    pub fn brown_noise(duration_secs: f32, layout: ChannelLayout, seed: u64) -> Self {
+        Self::noise(duration_secs, layout, seed, |rng, channel| {
+            let mut last = 0.0_f32;
+            for sample in channel.iter_mut() {
+                last = (last + 0.02 * rng.next_bipolar()) / 1.02;
+                *sample = (last * 3.5).clamp(-1.0, 1.0);
+            }
+        })
+    }
+
+    /// A buffer whose channels are filled in turn from one seeded stream
+    fn noise(
+        duration_secs: f32,
+        layout: ChannelLayout,
+        seed: u64,
+        fill: impl Fn(&mut NoiseRng, &mut [f32]),
+    ) -> Self {
+        let num_samples = (duration_secs.max(0.0) * INTERNAL_SAMPLE_RATE as f32) as usize;
+        let mut buffer = Self::new(num_samples, layout);
+        let mut rng = NoiseRng::new(seed);
+        for channel in buffer.samples.iter_mut() {
+            fill(&mut rng, channel);
+        }
+        buffer
+    }
+
     /// Convert the buffer to interleaved format
     ///
     /// # Returns
@@ -427,6 +555,66 @@ impl AudioBuffer {
         ChannelLayout::from_count(self.channels())
     }
 
+    /// Downmix to a single channel by averaging all channels
+    ///
+    /// Averaging is a -6 dB pan law: content identical in both channels
+    /// (centre-panned) keeps its level, while uncorrelated content loses
+    /// about 3 dB of power and content in only one channel drops 6 dB.
+    /// This keeps mono -> stereo -> mono lossless.
+    pub fn to_mono(&self) -> AudioBuffer {
+        if self.channels() == 1 {
+            return self.clone();
+        }
+
+        let scale = 1.0 / self.channels().max(1) as f32;
+        let mut mono = vec![0.0_f32; self.len()];
+        for channel in &self.samples {
+            for (out, &sample) in mono.iter_mut().zip(channel) {
+                *out += sample;
+            }
+        }
+        for sample in &mut mono {
+            *sample *= scale;
+        }
+
+        AudioBuffer {
+            samples: vec![mono],
+            sample_rate: self.sample_rate,
+        }
+    }
+
+    /// Convert to two channels
+    ///
+    /// Mono is copied to both channels at its original level, so it sounds
+    /// as loud as it did centre-panned. Buffers with more than two channels
+    /// keep only the first two.
+    pub fn to_stereo(&self) -> AudioBuffer {
+        let samples = match self.channels() {
+            0 => vec![Vec::new(); 2],
+            1 => vec![self.samples[0].clone(); 2],
+            _ => self.samples[..2].to_vec(),
+        };
+
+        AudioBuffer {
+            samples,
+            sample_rate: self.sample_rate,
+        }
+    }
+
+    /// Convert to the given channel layout
+    ///
+    /// Converting to the current layout is a plain clone.
+    pub fn convert_layout(&self, target: ChannelLayout) -> AudioBuffer {
+        if self.channel_layout() == Some(target) {
+            return self.clone();
+        }
+
+        match target {
+            ChannelLayout::Mono => self.to_mono(),
+            ChannelLayout::Stereo => self.to_stereo(),
+        }
+    }
+
     /// Get immutable access to a channel's samples
     ///
     /// # Arguments
@@ -592,6 +780,30 @@ impl AudioBuffer {
         }
     }
 
+    /// Softly limit all samples to the range [-1.0, 1.0]
+    ///
+    /// A gentler safety net than [`clamp`](Self::clamp): samples below
+    /// `1 - knee` pass unchanged, and above that a tanh curve bends them
+    /// smoothly towards full scale without ever reaching it. `knee` is
+    /// clamped to [0, 1]; 0 is a hard clip and 1 saturates everything.
+    pub fn soft_clip(&mut self, knee: f32) {
+        let knee = knee.clamp(0.0, 1.0);
+        let threshold = 1.0 - knee;
+        for channel in &mut self.samples {
+            for sample in channel.iter_mut() {
+                let magnitude = sample.abs();
+                if magnitude > threshold {
+                    let bent = if knee > 0.0 {
+                        threshold + knee * ((magnitude - threshold) / knee).tanh()
+                    } else {
+                        1.0
+                    };
+                    *sample = bent.min(1.0).copysign(*sample);
+                }
+            }
+        }
+    }
+
     /// Apply gain to all samples
     ///
     /// # Arguments
@@ -604,6 +816,485 @@ impl AudioBuffer {
             }
         }
     }
+
+    /// Apply a gain envelope, sample by sample
+    ///
+    /// Every channel gets the same gain at a given sample index.
+    pub fn apply_automation(&mut self, automation: &Automation) {
+        if automation.is_empty() {
+            return;
+        }
+        let gains = automation.gains(self.len());
+        for channel in &mut self.samples {
+            for (sample, gain) in channel.iter_mut().zip(&gains) {
+                *sample *= gain;
+            }
+        }
+    }
+
+    /// Replace every sample with `f(channel, index, value)`
+    ///
+    /// A building block for prototyping effects without implementing the
+    /// `Effect` trait. Samples are visited channel-major: all of channel
+    /// 0 in index order, then all of channel 1, and so on. Whatever `f`
+    /// returns is stored as is, so run [`is_finite`](Self::is_finite)
+    /// afterwards if the closure can produce NaN or infinity. The closure
+    /// is inlined, so an identity map costs nothing beyond the loop.
+    pub fn map_samples(&mut self, mut f: impl FnMut(usize, usize, f32) -> f32) {
+        for (channel, samples) in self.samples.iter_mut().enumerate() {
+            for (index, sample) in samples.iter_mut().enumerate() {
+                *sample = f(channel, index, *sample);
+            }
+        }
+    }
+
+    /// Serialize to a compact binary form
+    ///
+    /// A header of [`BUFFER_BYTES_MAGIC`], the format version, sample
+    /// rate, channel count and frame count, then the samples as f32,
+    /// channel by channel. Every field is little-endian whatever the
+    /// host, so the bytes are portable, and [`from_bytes`](Self::from_bytes)
+    /// restores the buffer bit for bit (NaN payloads included).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let frames = self.len();
+        let mut bytes =
+            Vec::with_capacity(BUFFER_BYTES_HEADER_LEN + self.num_channels() * frames * 4);
+        bytes.extend_from_slice(BUFFER_BYTES_MAGIC);
+        bytes.extend_from_slice(&BUFFER_BYTES_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(self.num_channels() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(frames as u64).to_le_bytes());
+        for channel in &self.samples {
+            for sample in channel {
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Read a buffer written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// # Errors
+    /// Returns `InvalidAudioFile` if the bytes are truncated, have trailing
+    /// data or don't start with [`BUFFER_BYTES_MAGIC`], and
+    /// `UnsupportedFormat` for a version other than
+    /// [`BUFFER_BYTES_VERSION`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let invalid = |details: String| NuevaError::InvalidAudioFile {
+            details: format!("audio buffer bytes: {}", details),
+        };
+
+        if bytes.len() < BUFFER_BYTES_HEADER_LEN {
+            return Err(invalid(format!(
+                "truncated header ({} of {} bytes)",
+                bytes.len(),
+                BUFFER_BYTES_HEADER_LEN
+            )));
+        }
+        if &bytes[..4] != BUFFER_BYTES_MAGIC {
+            return Err(invalid(format!(
+                "wrong magic {:02x?} (expected {:?})",
+                &bytes[..4],
+                std::str::from_utf8(BUFFER_BYTES_MAGIC).unwrap_or_default()
+            )));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != BUFFER_BYTES_VERSION {
+            return Err(NuevaError::UnsupportedFormat {
+                format: format!(
+                    "audio buffer format version {} (supported: {})",
+                    version, BUFFER_BYTES_VERSION
+                ),
+            });
+        }
+        let sample_rate = u32::from_le_bytes(bytes[6..10].try_into().unwrap());
+        let channels = u16::from_le_bytes([bytes[10], bytes[11]]) as usize;
+        let frames = u64::from_le_bytes(bytes[12..20].try_into().unwrap());
+
+        let data = &bytes[BUFFER_BYTES_HEADER_LEN..];
+        let expected = usize::try_from(frames)
+            .ok()
+            .and_then(|frames| frames.checked_mul(channels))
+            .and_then(|samples| samples.checked_mul(4));
+        if expected != Some(data.len()) {
+            return Err(invalid(format!(
+                "{} channels of {} frames need {} bytes of samples, found {}",
+                channels,
+                frames,
+                expected.map_or("too many".to_string(), |n| n.to_string()),
+                data.len()
+            )));
+        }
+
+        let frames = frames as usize;
+        let samples = (0..channels)
+            .map(|channel| {
+                data[channel * frames * 4..(channel + 1) * frames * 4]
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            samples,
+            sample_rate,
+        })
+    }
+
+    /// Flip the polarity of every sample, in place
+    ///
+    /// Negation is exact, so inverting twice restores the original bit
+    /// for bit.
+    pub fn invert_polarity(&mut self) {
+        for channel in &mut self.samples {
+            for sample in channel.iter_mut() {
+                *sample = -*sample;
+            }
+        }
+    }
+
+    /// Swap the left and right channels, in place
+    ///
+    /// Only the channel vectors are exchanged, so no samples are copied.
+    /// Mono buffers are left as they are.
+    pub fn swap_channels(&mut self) {
+        if self.samples.len() == 2 {
+            self.samples.swap(0, 1);
+        }
+    }
+
+    /// Copy the samples in `start..end` into a new buffer
+    ///
+    /// # Arguments
+    /// * `start` - First sample index (inclusive)
+    /// * `end` - Last sample index (exclusive); must be greater than `start`
+    ///   and no greater than `len()`
+    pub fn slice(&self, start: usize, end: usize) -> Result<AudioBuffer> {
+        if start >= end || end > self.len() {
+            return Err(NuevaError::InvalidParameter {
+                param: "range".to_string(),
+                value: format!("{}..{}", start, end),
+                expected: format!("start < end <= {}", self.len()),
+            });
+        }
+
+        Ok(AudioBuffer {
+            samples: self
+                .samples
+                .iter()
+                .map(|ch| ch[start..end].to_vec())
+                .collect(),
+            sample_rate: self.sample_rate,
+        })
+    }
+
+    /// Append another buffer's samples to the end of this one
+    ///
+    /// Both buffers must have the same channel count and sample rate.
+    pub fn append(&mut self, other: &AudioBuffer) -> Result<()> {
+        if other.sample_rate != self.sample_rate {
+            return Err(NuevaError::InvalidAudio {
+                reason: format!(
+                    "Cannot append {} Hz audio to a {} Hz buffer",
+                    other.sample_rate, self.sample_rate
+                ),
+                source: None,
+            });
+        }
+        if other.channels() != self.channels() {
+            return Err(NuevaError::InvalidAudio {
+                reason: format!(
+                    "Cannot append {}-channel audio to a {}-channel buffer",
+                    other.channels(),
+                    self.channels()
+                ),
+                source: None,
+            });
+        }
+
+        for (channel, tail) in self.samples.iter_mut().zip(&other.samples) {
+            channel.extend_from_slice(tail);
+        }
+        Ok(())
+    }
+
+    /// Add another buffer into this one, scaled by `gain`
+    ///
+    /// Both buffers must have the same channel count and sample rate. A
+    /// shorter `other` is mixed into the start of this buffer; a longer one
+    /// is cut to this buffer's length.
+    pub fn mix(&mut self, other: &AudioBuffer, gain: f32) -> Result<()> {
+        if other.sample_rate != self.sample_rate {
+            return Err(NuevaError::InvalidAudio {
+                reason: format!(
+                    "Cannot mix {} Hz audio into a {} Hz buffer",
+                    other.sample_rate, self.sample_rate
+                ),
+                source: None,
+            });
+        }
+        if other.channels() != self.channels() {
+            return Err(NuevaError::InvalidAudio {
+                reason: format!(
+                    "Cannot mix {}-channel audio into a {}-channel buffer",
+                    other.channels(),
+                    self.channels()
+                ),
+                source: None,
+            });
+        }
+
+        for (channel, added) in self.samples.iter_mut().zip(&other.samples) {
+            for (sample, a) in channel.iter_mut().zip(added) {
+                *sample += a * gain;
+            }
+        }
+        Ok(())
+    }
+
+    /// Level over a sliding window, one value per hop
+    ///
+    /// Windows start every `hop_ms` from the top of the buffer and stop at
+    /// the last one that fits, so a buffer of `len` samples gives
+    /// `(len - window) / hop + 1` values; a buffer shorter than one window
+    /// is measured as a single window. Silent windows read negative
+    /// infinity, and an empty buffer gives an empty series.
+    ///
+    /// # Arguments
+    /// * `window_ms` - Window length; must be positive
+    /// * `hop_ms` - Distance between window starts; must be positive and no
+    ///   longer than the window
+    /// * `metric` - Peak, RMS or loudness
+    pub fn level_series(
+        &self,
+        window_ms: f32,
+        hop_ms: f32,
+        metric: LevelMetric,
+    ) -> Result<Vec<f32>> {
+        if !(window_ms > 0.0 && window_ms.is_finite()) {
+            return Err(NuevaError::InvalidParameter {
+                param: "window_ms".to_string(),
+                value: window_ms.to_string(),
+                expected: "greater than 0 ms".to_string(),
+            });
+        }
+        if !(hop_ms > 0.0 && hop_ms <= window_ms) {
+            return Err(NuevaError::InvalidParameter {
+                param: "hop_ms".to_string(),
+                value: hop_ms.to_string(),
+                expected: format!("greater than 0 and at most the {} ms window", window_ms),
+            });
+        }
+
+        let len = self.len();
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let to_samples =
+            |ms: f32| ((ms as f64 * self.sample_rate as f64 / 1000.0).round() as usize).max(1);
+        let window = to_samples(window_ms).min(len);
+        let hop = to_samples(hop_ms);
+        let starts = (0..=(len - window) / hop).map(|i| i * hop);
+
+        let series = match metric {
+            LevelMetric::Peak => starts
+                .map(|start| {
+                    let peak = self
+                        .samples
+                        .iter()
+                        .flat_map(|channel| &channel[start..start + window])
+                        .map(|s| s.abs())
+                        .fold(0.0_f32, f32::max);
+                    linear_to_db(peak)
+                })
+                .collect(),
+            LevelMetric::Rms => {
+                let total = (window * self.num_channels()) as f64;
+                starts
+                    .map(|start| {
+                        let sum_squares: f64 = self
+                            .samples
+                            .iter()
+                            .flat_map(|channel| &channel[start..start + window])
+                            .map(|&s| (s as f64) * (s as f64))
+                            .sum();
+                        linear_to_db((sum_squares / total).sqrt() as f32)
+                    })
+                    .collect()
+            }
+            LevelMetric::Lufs => super::loudness::loudness_series(self, window, hop),
+        };
+        Ok(series)
+    }
+
+    /// Change the duration without changing the pitch
+    ///
+    /// A phase vocoder (the pitch shifter's analysis, with the bins kept at
+    /// their frequencies) resynthesizes each channel `ratio` times as long:
+    /// 2.0 doubles the duration, 0.5 halves it. The result has
+    /// `round(len * ratio)` samples at the same sample rate.
+    ///
+    /// Steady tones keep their pitch exactly. Transients are smeared over
+    /// up to one analysis frame (about 40 ms) and lose some attack, more so
+    /// the further the ratio is from 1; channels are stretched
+    /// independently, which can soften the stereo image.
+    ///
+    /// # Arguments
+    /// * `ratio` - Output length over input length; must be positive and
+    ///   at most [`MAX_TIME_STRETCH_RATIO`]
+    pub fn time_stretch(&self, ratio: f32) -> Result<AudioBuffer> {
+        if !(ratio > 0.0 && ratio <= MAX_TIME_STRETCH_RATIO) {
+            return Err(NuevaError::InvalidParameter {
+                param: "ratio".to_string(),
+                value: ratio.to_string(),
+                expected: format!("greater than 0 and at most {}", MAX_TIME_STRETCH_RATIO),
+            });
+        }
+
+        let fft_size = crate::dsp::frame_size(self.sample_rate as f64);
+        Ok(AudioBuffer {
+            samples: self
+                .samples
+                .iter()
+                .map(|channel| crate::dsp::stretch_channel(channel, ratio as f64, fft_size))
+                .collect(),
+            sample_rate: self.sample_rate,
+        })
+    }
+
+    /// Find clicks and pops
+    ///
+    /// Flags samples whose second difference stands out from the second
+    /// differences around them: by [`CLICK_BASE_RATIO`] at sensitivity 1.0,
+    /// twice that at 0.5, and so on. Sensitivity is capped at 1.0; zero or
+    /// less finds nothing. Returns sorted sample indices, flagged in any
+    /// channel.
+    pub fn detect_clicks(&self, sensitivity: f32) -> Vec<usize> {
+        if sensitivity <= 0.0 || sensitivity.is_nan() {
+            return Vec::new();
+        }
+        let ratio = CLICK_BASE_RATIO / sensitivity.min(1.0);
+
+        let mut clicks = Vec::new();
+        for channel in &self.samples {
+            // d2[j] is the second difference at sample j + 1
+            let d2: Vec<f32> = channel
+                .windows(3)
+                .map(|w| w[2] - 2.0 * w[1] + w[0])
+                .collect();
+            let mut energy = vec![0.0_f64; d2.len() + 1];
+            for (j, &d) in d2.iter().enumerate() {
+                energy[j + 1] = energy[j] + (d as f64) * (d as f64);
+            }
+
+            for (j, &d) in d2.iter().enumerate() {
+                let lo = j.saturating_sub(CLICK_LEVEL_WINDOW);
+                let hi = (j + CLICK_LEVEL_WINDOW + 1).min(d2.len());
+                let ex_lo = j.saturating_sub(CLICK_LEVEL_EXCLUDE);
+                let ex_hi = (j + CLICK_LEVEL_EXCLUDE + 1).min(d2.len());
+                let count = (hi - lo) - (ex_hi - ex_lo);
+                if count == 0 {
+                    continue;
+                }
+                let sum = (energy[hi] - energy[lo]) - (energy[ex_hi] - energy[ex_lo]);
+                let level = (sum.max(0.0) / count as f64).sqrt() as f32;
+                if d.abs() > CLICK_FLOOR.max(ratio * level) {
+                    clicks.push(j + 1);
+                }
+            }
+        }
+
+        clicks.sort_unstable();
+        clicks.dedup();
+        clicks
+    }
+
+    /// Repair clicks by interpolating across them
+    ///
+    /// Every sample within `window` of a position is replaced, in all
+    /// channels, by a cubic curve that meets the audio either side of the
+    /// region with matching value and slope. Overlapping regions are
+    /// merged; the length never changes.
+    pub fn declick(&mut self, positions: &[usize], window: usize) {
+        let len = self.len();
+        let mut sorted: Vec<usize> = positions.iter().copied().filter(|&p| p < len).collect();
+        sorted.sort_unstable();
+
+        let mut regions: Vec<(usize, usize)> = Vec::new();
+        for p in sorted {
+            let start = p.saturating_sub(window);
+            let end = (p + window).min(len - 1);
+            match regions.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => regions.push((start, end)),
+            }
+        }
+
+        for channel in &mut self.samples {
+            for &(start, end) in &regions {
+                bridge(channel, start, end);
+            }
+        }
+    }
+}
+
+/// Replace `start..=end` with a cubic Hermite curve between its neighbours
+///
+/// A region touching either end of the channel is held at the one
+/// neighbour it has.
+fn bridge(channel: &mut [f32], start: usize, end: usize) {
+    let len = channel.len();
+    let left = start.checked_sub(1);
+    let right = (end + 1 < len).then_some(end + 1);
+
+    match (left, right) {
+        (Some(l), Some(r)) => {
+            let (p0, p1) = (channel[l], channel[r]);
+            let m0 = if l >= 1 { p0 - channel[l - 1] } else { 0.0 };
+            let m1 = if r + 1 < len {
+                channel[r + 1] - p1
+            } else {
+                0.0
+            };
+            let span = (r - l) as f32;
+            for (offset, sample) in channel[start..=end].iter_mut().enumerate() {
+                let t = (offset + 1) as f32 / span;
+                let (t2, t3) = (t * t, t * t * t);
+                *sample = (2.0 * t3 - 3.0 * t2 + 1.0) * p0
+                    + (t3 - 2.0 * t2 + t) * m0 * span
+                    + (3.0 * t2 - 2.0 * t3) * p1
+                    + (t3 - t2) * m1 * span;
+            }
+        }
+        (Some(edge), None) | (None, Some(edge)) => {
+            let value = channel[edge];
+            channel[start..=end].fill(value);
+        }
+        (None, None) => {}
+    }
+}
+
+/// SplitMix64: small, fast and good enough for test signals and dither
+struct NoiseRng(u64);
+
+impl NoiseRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [-1, 1)
+    fn next_bipolar(&mut self) -> f32 {
+        // Top 24 bits fill an f32 mantissa exactly
+        (self.next_u64() >> 40) as f32 / (1u32 << 23) as f32 - 1.0
+    }
 }
 
 impl Default for AudioBuffer {
@@ -792,9 +1483,7 @@ mod tests {
     fn test_calculate_clip_ratio_partial() {
         // 10 out of 1000 samples clipped = 1%
         let mut samples = vec![0.5; 1000];
-        for i in 0..10 {
-            samples[i] = 1.0;
-        }
+        samples[..10].fill(1.0);
         let buffer = create_test_buffer(vec![samples]);
         let ratio = calculate_clip_ratio(&buffer);
         assert!((ratio - 0.01).abs() < 1e-6);
@@ -933,6 +1622,65 @@ mod tests {
         assert!(!not_empty.is_empty());
     }
 
+    // ------------------------------------------------------------------------
+    // Channel conversion tests
+    // ------------------------------------------------------------------------
+
+    #[test]
+    fn test_mono_to_stereo_duplicates() {
+        let mono = create_test_buffer(vec![vec![0.1, -0.2, 0.3]]);
+        let stereo = mono.to_stereo();
+
+        assert_eq!(stereo.channel_layout(), Some(ChannelLayout::Stereo));
+        assert_eq!(stereo.samples[0], mono.samples[0]);
+        assert_eq!(stereo.samples[1], mono.samples[0]);
+        assert_eq!(stereo.sample_rate, mono.sample_rate);
+    }
+
+    #[test]
+    fn test_mono_stereo_round_trip() {
+        let mono = create_test_buffer(vec![(0..1000)
+            .map(|i| (i as f32 * 0.037).sin() * 0.8)
+            .collect()]);
+        let round_trip = mono
+            .convert_layout(ChannelLayout::Stereo)
+            .convert_layout(ChannelLayout::Mono);
+
+        assert_eq!(round_trip.channels(), 1);
+        for (a, b) in mono.samples[0].iter().zip(&round_trip.samples[0]) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_stereo_to_mono_pan_law() {
+        // Centre-panned content keeps its level
+        let centred = create_test_buffer(vec![vec![0.5; 100], vec![0.5; 100]]);
+        assert!((calculate_rms(&centred.to_mono()) - calculate_rms(&centred)).abs() < 1e-4);
+
+        // Hard-panned content drops 6 dB
+        let left_only = create_test_buffer(vec![vec![0.5; 100], vec![0.0; 100]]);
+        let mono = left_only.to_mono();
+        assert!((calculate_peak(&mono) - (calculate_peak(&left_only) - 6.02)).abs() < 0.01);
+
+        // Opposite polarity cancels
+        let inverted = create_test_buffer(vec![vec![0.5; 100], vec![-0.5; 100]]);
+        assert!(inverted.to_mono().samples[0].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_convert_to_same_layout_clones() {
+        let stereo = create_test_buffer(vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+        let converted = stereo.convert_layout(ChannelLayout::Stereo);
+        assert_eq!(converted.samples, stereo.samples);
+
+        let mono = create_test_buffer(vec![vec![0.1, 0.2]]);
+        assert_eq!(
+            mono.convert_layout(ChannelLayout::Mono).samples,
+            mono.samples
+        );
+    }
+
     // ------------------------------------------------------------------------
     // AudioValidation tests
     // ------------------------------------------------------------------------
@@ -1011,9 +1759,7 @@ mod tests {
         // 5% clipped samples (above threshold)
         let num_samples = 1000;
         let mut samples = vec![0.5; num_samples];
-        for i in 0..50 {
-            samples[i] = 1.0;
-        }
+        samples[..50].fill(1.0);
         let buffer = create_test_buffer(vec![samples]);
         let validation = buffer.get_validation();
 
@@ -1057,6 +1803,102 @@ mod tests {
         assert_eq!(buffer.get_sample(0, 4), Some(1.0));
     }
 
+    /// Power spectrum of a Hann-windowed 8192-sample frame
+    fn power_spectrum(samples: &[f32]) -> Vec<f64> {
+        const N: usize = 8192;
+        let mut re: Vec<f64> = samples[..N]
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / N as f64).cos();
+                x as f64 * w
+            })
+            .collect();
+        let mut im = vec![0.0; N];
+        crate::dsp::fft(&mut re, &mut im, false);
+        (0..N / 2)
+            .map(|bin| re[bin] * re[bin] + im[bin] * im[bin])
+            .collect()
+    }
+
+    /// A sine centred on FFT bin 75 (about 439 Hz)
+    fn bin_sine(amplitude: f32) -> AudioBuffer {
+        let freq = 75.0 * INTERNAL_SAMPLE_RATE as f32 / 8192.0;
+        let samples = (0..8192)
+            .map(|i| {
+                amplitude
+                    * (2.0 * std::f32::consts::PI * freq * i as f32 / INTERNAL_SAMPLE_RATE as f32)
+                        .sin()
+            })
+            .collect();
+        create_test_buffer(vec![samples])
+    }
+
+    #[test]
+    fn test_soft_clip_leaves_quiet_audio_alone() {
+        for knee in [0.0, 0.5, 1.0] {
+            let mut buffer = bin_sine(0.1);
+            buffer.soft_clip(knee);
+
+            let power = power_spectrum(buffer.channel(0));
+            let fundamental: f64 = power[72..=78].iter().sum();
+            let total: f64 = power.iter().sum();
+            let thd = ((total - fundamental) / fundamental).sqrt();
+            assert!(thd < 0.005, "knee {}: THD {:.4}", knee, thd);
+        }
+    }
+
+    #[test]
+    fn test_soft_clip_bounds_and_smoothness() {
+        let ramp: Vec<f32> = (-400..=400).map(|i| i as f32 / 100.0).collect();
+        for knee in [0.1, 0.5, 1.0] {
+            let mut buffer = create_test_buffer(vec![ramp.clone()]);
+            buffer.soft_clip(knee);
+            let out = buffer.channel(0);
+
+            assert!(out.iter().all(|s| (-1.0..=1.0).contains(s)));
+            // Peaks above full scale land below it, still in order
+            assert!(out[500] < 1.0 && out[500] > 1.0 - knee);
+            assert!(out.windows(2).all(|w| w[1] >= w[0]));
+            // No kinks: neighbouring slopes barely differ
+            let slopes: Vec<f32> = out.windows(2).map(|w| w[1] - w[0]).collect();
+            assert!(slopes.windows(2).all(|s| (s[1] - s[0]).abs() < 2e-3));
+        }
+
+        // A vanishing knee approaches the hard clamp
+        let mut soft = create_test_buffer(vec![ramp.clone()]);
+        soft.soft_clip(1e-4);
+        let mut hard = create_test_buffer(vec![ramp]);
+        hard.clamp();
+        for (s, h) in soft.channel(0).iter().zip(hard.channel(0)) {
+            assert!((s - h).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_soft_clip_is_less_harsh_than_clamp() {
+        // Share of an overdriven sine's energy above 5 kHz
+        let harshness = |buffer: &AudioBuffer| {
+            let power = power_spectrum(buffer.channel(0));
+            let cutoff = (5000.0 * 8192.0 / INTERNAL_SAMPLE_RATE as f64) as usize;
+            let total: f64 = power.iter().sum();
+            10.0 * (power[cutoff..].iter().sum::<f64>() / total).log10()
+        };
+
+        let mut hard = bin_sine(3.0);
+        hard.clamp();
+        let mut soft = bin_sine(3.0);
+        soft.soft_clip(0.5);
+
+        let (hard_db, soft_db) = (harshness(&hard), harshness(&soft));
+        assert!(
+            soft_db < hard_db - 6.0,
+            "soft {:.1} dB vs hard {:.1} dB",
+            soft_db,
+            hard_db
+        );
+    }
+
     #[test]
     fn test_buffer_apply_gain() {
         let mut buffer = create_test_buffer(vec![vec![0.5; 100]]);
@@ -1066,4 +1908,592 @@ mod tests {
         let sample = buffer.get_sample(0, 0).unwrap();
         assert!((sample - 0.25).abs() < 0.01);
     }
+
+    #[test]
+    fn test_slice_bounds() {
+        let buffer = create_test_buffer(vec![vec![0.0; 100], vec![0.0; 100]]);
+
+        let region = buffer.slice(10, 100).unwrap();
+        assert_eq!(region.len(), 90);
+        assert_eq!(region.channels(), 2);
+        assert_eq!(region.sample_rate, buffer.sample_rate);
+
+        assert!(buffer.slice(50, 50).is_err());
+        assert!(buffer.slice(60, 50).is_err());
+        assert!(buffer.slice(0, 101).is_err());
+    }
+
+    #[test]
+    fn test_append_requires_matching_format() {
+        let mut buffer = create_test_buffer(vec![vec![0.1; 10]]);
+
+        let mut other_rate = create_test_buffer(vec![vec![0.2; 10]]);
+        other_rate.sample_rate = 44100;
+        assert!(buffer.append(&other_rate).is_err());
+
+        let stereo = create_test_buffer(vec![vec![0.2; 10], vec![0.2; 10]]);
+        assert!(buffer.append(&stereo).is_err());
+        assert_eq!(buffer.len(), 10);
+
+        let more = create_test_buffer(vec![vec![0.3; 30]]);
+        buffer.append(&more).unwrap();
+        assert_eq!(buffer.len(), 40);
+        assert_eq!(buffer.get_sample(0, 39), Some(0.3));
+    }
+
+    #[test]
+    fn test_mix_requires_matching_format() {
+        let mut buffer = create_test_buffer(vec![vec![0.1; 10]]);
+
+        let mut other_rate = create_test_buffer(vec![vec![0.2; 10]]);
+        other_rate.sample_rate = 96000;
+        assert!(buffer.mix(&other_rate, 1.0).is_err());
+        assert!(buffer
+            .mix(&create_test_buffer(vec![vec![0.2; 10], vec![0.2; 10]]), 1.0)
+            .is_err());
+        assert_eq!(buffer.get_sample(0, 0), Some(0.1));
+
+        buffer
+            .mix(&create_test_buffer(vec![vec![0.4; 4]]), 0.5)
+            .unwrap();
+        assert!((buffer.get_sample(0, 3).unwrap() - 0.3).abs() < 1e-6);
+        assert_eq!(buffer.get_sample(0, 4), Some(0.1));
+        assert_eq!(buffer.len(), 10);
+    }
+
+    #[test]
+    fn test_map_samples_tremolo() {
+        // 5 Hz tremolo at full depth on a constant signal
+        let mut buffer = AudioBuffer::with_sample_rate(48000, ChannelLayout::Stereo, 48000);
+        buffer.map_samples(|_, _, _| 0.5);
+        let rate = 5.0 / buffer.sample_rate as f32;
+        buffer.map_samples(|_, index, value| {
+            let lfo = 0.5 + 0.5 * (std::f32::consts::TAU * rate * index as f32).cos();
+            value * lfo
+        });
+
+        for channel in 0..2 {
+            assert_eq!(buffer.get_sample(channel, 0), Some(0.5));
+            // Half a cycle later the LFO is at its trough
+            assert!(buffer.get_sample(channel, 4800).unwrap().abs() < 1e-4);
+            assert!((buffer.get_sample(channel, 9600).unwrap() - 0.5).abs() < 1e-4);
+        }
+        assert!(buffer.is_finite());
+    }
+
+    #[test]
+    fn test_map_samples_order_and_nan() {
+        let mut buffer = create_test_buffer(vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+        let mut visited = Vec::new();
+        buffer.map_samples(|channel, index, value| {
+            visited.push((channel, index));
+            value
+        });
+        assert_eq!(visited, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(buffer.samples, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+        buffer.map_samples(|channel, index, value| {
+            if (channel, index) == (1, 0) {
+                f32::NAN
+            } else {
+                value
+            }
+        });
+        assert!(!buffer.is_finite());
+    }
+
+    #[test]
+    fn test_bytes_round_trip_is_bit_exact() {
+        let mut buffer = AudioBuffer::with_sample_rate(1000, ChannelLayout::Stereo, 44100);
+        buffer
+            .map_samples(|channel, index, _| ((index * 31 + channel * 7) % 97) as f32 / 97.0 - 0.5);
+        // Values that only survive if the bits are copied exactly
+        buffer.samples[0][0] = f32::from_bits(0x7fc0_1234);
+        buffer.samples[0][1] = -0.0;
+        buffer.samples[1][0] = f32::NEG_INFINITY;
+        buffer.samples[1][1] = f32::from_bits(1);
+
+        let bytes = buffer.to_bytes();
+        assert_eq!(bytes.len(), BUFFER_BYTES_HEADER_LEN + 2 * 1000 * 4);
+        // Little-endian on every host
+        assert_eq!(&bytes[..4], b"NVAB");
+        assert_eq!(bytes[4..6], [1, 0]);
+        assert_eq!(bytes[6..10], 44100u32.to_le_bytes());
+        assert_eq!(bytes[20..24], 0x7fc0_1234u32.to_le_bytes());
+
+        let restored = AudioBuffer::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.sample_rate, 44100);
+        assert_eq!(restored.num_channels(), 2);
+        for (a, b) in buffer.samples.iter().zip(&restored.samples) {
+            let bits = |channel: &Vec<f32>| channel.iter().map(|s| s.to_bits()).collect::<Vec<_>>();
+            assert_eq!(bits(a), bits(b));
+        }
+
+        let empty = AudioBuffer::with_sample_rate(0, ChannelLayout::Mono, 96000);
+        let restored = AudioBuffer::from_bytes(&empty.to_bytes()).unwrap();
+        assert_eq!((restored.num_channels(), restored.len()), (1, 0));
+        assert_eq!(restored.sample_rate, 96000);
+    }
+
+    #[test]
+    fn test_bytes_corruption_is_reported() {
+        let bytes = AudioBuffer::new(100, ChannelLayout::Stereo).to_bytes();
+        let error = |bytes: &[u8]| AudioBuffer::from_bytes(bytes).unwrap_err().to_string();
+
+        assert!(error(&bytes[..10]).contains("truncated header"));
+        assert!(error(&bytes[..bytes.len() - 1]).contains("found 799"));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(error(&trailing).contains("found 801"));
+
+        let mut wav = bytes.clone();
+        wav[..4].copy_from_slice(b"RIFF");
+        assert!(error(&wav).contains("wrong magic"));
+
+        let mut future = bytes.clone();
+        future[4..6].copy_from_slice(&2u16.to_le_bytes());
+        assert!(matches!(
+            AudioBuffer::from_bytes(&future),
+            Err(NuevaError::UnsupportedFormat { .. })
+        ));
+        assert!(error(&future).contains("version 2"));
+
+        // A frame count too large to allocate is caught before reading
+        let mut huge = bytes;
+        huge[12..20].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(error(&huge).contains("too many"));
+    }
+
+    #[test]
+    fn test_invert_polarity_nulls_against_original() {
+        let original = create_test_buffer(vec![
+            vec![0.5, -0.25, 1.0, -1.0, 0.0, 1e-30],
+            vec![0.1, 0.2, -0.3, 0.4, -0.5, f32::MIN_POSITIVE],
+        ]);
+
+        let mut inverted = original.clone();
+        inverted.invert_polarity();
+        assert_eq!(inverted.get_sample(0, 0), Some(-0.5));
+
+        let mut sum = original.clone();
+        sum.mix(&inverted, 1.0).unwrap();
+        assert!(sum.samples.iter().flatten().all(|&s| s == 0.0));
+
+        inverted.invert_polarity();
+        assert_eq!(inverted.samples, original.samples);
+    }
+
+    #[test]
+    fn test_swap_channels_round_trip() {
+        let original = create_test_buffer(vec![vec![0.1; 8], vec![-0.7; 8]]);
+        let mut swapped = original.clone();
+        swapped.swap_channels();
+        assert_eq!(swapped.channel(0), original.channel(1));
+        assert_eq!(swapped.channel(1), original.channel(0));
+        swapped.swap_channels();
+        assert_eq!(swapped.samples, original.samples);
+
+        let mut mono = create_test_buffer(vec![vec![0.3, -0.3]]);
+        mono.swap_channels();
+        assert_eq!(mono.samples, vec![vec![0.3, -0.3]]);
+    }
+
+    /// Mean power per FFT bin (dB) in each octave from 250 Hz to 8 kHz,
+    /// averaged over Hann-windowed frames
+    fn octave_densities(samples: &[f32]) -> Vec<f64> {
+        const N: usize = 4096;
+        let mut power = vec![0.0_f64; N / 2];
+        for frame in samples.chunks_exact(N) {
+            let mut re: Vec<f64> = frame
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / N as f64).cos();
+                    x as f64 * w
+                })
+                .collect();
+            let mut im = vec![0.0; N];
+            crate::dsp::fft(&mut re, &mut im, false);
+            for (bin, p) in power.iter_mut().enumerate() {
+                *p += re[bin] * re[bin] + im[bin] * im[bin];
+            }
+        }
+        let hz_per_bin = INTERNAL_SAMPLE_RATE as f64 / N as f64;
+        (0..6)
+            .map(|octave| {
+                let lo = (250.0 * 2f64.powi(octave) / hz_per_bin) as usize;
+                let band = &power[lo..lo * 2];
+                10.0 * (band.iter().sum::<f64>() / band.len() as f64).log10()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_noise_is_deterministic_and_bounded() {
+        type Generator = fn(f32, ChannelLayout, u64) -> AudioBuffer;
+        let generators: [Generator; 3] = [
+            AudioBuffer::white_noise,
+            AudioBuffer::pink_noise,
+            AudioBuffer::brown_noise,
+        ];
+        for generate in generators {
+            let a = generate(0.5, ChannelLayout::Stereo, 42);
+            assert_eq!(a.len(), 24000);
+            assert_eq!(a.samples, generate(0.5, ChannelLayout::Stereo, 42).samples);
+            assert_ne!(a.samples, generate(0.5, ChannelLayout::Stereo, 43).samples);
+            assert_ne!(a.channel(0), a.channel(1));
+            assert!(a.samples.iter().flatten().all(|s| (-1.0..=1.0).contains(s)));
+            assert!(calculate_peak(&a) > -20.0);
+        }
+    }
+
+    #[test]
+    fn test_noise_spectra() {
+        let slopes = |buffer: AudioBuffer| {
+            let densities = octave_densities(buffer.channel(0));
+            densities
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .collect::<Vec<_>>()
+        };
+
+        for slope in slopes(AudioBuffer::white_noise(10.0, ChannelLayout::Mono, 1)) {
+            assert!(
+                slope.abs() < 0.5,
+                "white noise not flat: {:.2} dB/oct",
+                slope
+            );
+        }
+        for slope in slopes(AudioBuffer::pink_noise(10.0, ChannelLayout::Mono, 1)) {
+            assert!((slope + 3.0).abs() < 0.5, "pink slope {:.2} dB/oct", slope);
+        }
+        // Above the leak's corner brown noise falls 6 dB/oct
+        for slope in &slopes(AudioBuffer::brown_noise(10.0, ChannelLayout::Mono, 1))[2..] {
+            assert!((slope + 6.0).abs() < 1.0, "brown slope {:.2} dB/oct", slope);
+        }
+    }
+
+    #[test]
+    fn test_sample_rate_validation() {
+        assert!(validate_sample_rate(96000).is_ok());
+        assert!(validate_sample_rate(44100).is_ok());
+        assert!(validate_sample_rate(22050).is_err());
+
+        let buffer = AudioBuffer::with_sample_rate(96000, ChannelLayout::Stereo, 96000);
+        assert_eq!(buffer.sample_rate, 96000);
+        assert_eq!(buffer.duration_secs(), 1.0);
+    }
+
+    #[test]
+    fn test_slice_and_append_round_trip() {
+        let samples: Vec<Vec<f32>> = (0..2)
+            .map(|ch| {
+                (0..1000)
+                    .map(|i| ((i * (ch + 3)) as f32 * 0.01).sin())
+                    .collect()
+            })
+            .collect();
+        let original = create_test_buffer(samples);
+
+        let mut rebuilt = original.slice(0, 333).unwrap();
+        for (start, end) in [(333, 334), (334, 900), (900, 1000)] {
+            rebuilt
+                .append(&original.slice(start, end).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(rebuilt.samples, original.samples);
+        assert!((rebuilt.duration_secs() - original.duration_secs()).abs() < 1e-12);
+    }
+
+    /// Mono mix of a 440 Hz and a 3 kHz tone with a little noise
+    fn clean_audio(len: usize) -> AudioBuffer {
+        let mut seed = 7u32;
+        let samples = (0..len)
+            .map(|i| {
+                let t = i as f32 / INTERNAL_SAMPLE_RATE as f32;
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = ((seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 1e-3;
+                0.4 * (std::f32::consts::TAU * 440.0 * t).sin()
+                    + 0.1 * (std::f32::consts::TAU * 3000.0 * t).sin()
+                    + noise
+            })
+            .collect();
+        create_test_buffer(vec![samples])
+    }
+
+    #[test]
+    fn test_clean_audio_has_no_clicks() {
+        assert!(clean_audio(48000).detect_clicks(0.5).is_empty());
+        assert!(create_test_buffer(vec![vec![0.0; 1000]; 2])
+            .detect_clicks(1.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_detect_and_repair_click() {
+        let clean = clean_audio(4800);
+        let mut clicked = clean.clone();
+        clicked.samples[0][2000] += 0.6;
+
+        let clicks = clicked.detect_clicks(0.5);
+        assert!(clicks.contains(&2000), "{:?}", clicks);
+        assert!(
+            clicks.iter().all(|&i| (1999..=2001).contains(&i)),
+            "{:?}",
+            clicks
+        );
+
+        clicked.declick(&clicks, 4);
+        assert_eq!(clicked.len(), 4800);
+        assert!(clicked.detect_clicks(0.5).is_empty());
+        for i in 1990..2010 {
+            assert!(
+                (clicked.samples[0][i] - clean.samples[0][i]).abs() < 0.02,
+                "{}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_repair_splice_discontinuity() {
+        // Two takes joined out of phase
+        let take = clean_audio(4000);
+        let mut spliced = take.slice(0, 1500).unwrap();
+        spliced.append(&take.slice(2030, 4000).unwrap()).unwrap();
+        let clicks = spliced.detect_clicks(0.5);
+        assert!(clicks.contains(&1500), "{:?}", clicks);
+
+        let len = spliced.len();
+        spliced.declick(&clicks, 8);
+        assert_eq!(spliced.len(), len);
+        assert!(spliced.detect_clicks(0.5).is_empty());
+    }
+
+    #[test]
+    fn test_declick_edges_and_stereo() {
+        let mut buffer = create_test_buffer(vec![vec![0.5; 100], vec![-0.5; 100]]);
+        buffer.samples[0][0] = 1.0;
+        buffer.samples[1][99] = 1.0;
+        buffer.declick(&[0, 99, 500], 1);
+        assert_eq!(buffer.samples[0][0], 0.5);
+        assert_eq!(buffer.samples[1][99], -0.5);
+        assert_eq!(buffer.len(), 100);
+    }
+
+    #[test]
+    fn test_level_series_length_and_validation() {
+        let buffer = create_test_buffer(vec![vec![0.5; 48000]; 2]);
+        // (48000 - 19200) / 4800 + 1 windows of 400 ms every 100 ms
+        let series = buffer.level_series(400.0, 100.0, LevelMetric::Rms).unwrap();
+        assert_eq!(series.len(), 7);
+        // A window longer than the buffer measures it once
+        assert_eq!(
+            buffer
+                .level_series(2000.0, 500.0, LevelMetric::Peak)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let empty = create_test_buffer(vec![vec![]; 2]);
+        assert!(empty
+            .level_series(400.0, 100.0, LevelMetric::Lufs)
+            .unwrap()
+            .is_empty());
+
+        assert!(buffer.level_series(0.0, 0.0, LevelMetric::Peak).is_err());
+        assert!(buffer.level_series(100.0, 0.0, LevelMetric::Peak).is_err());
+        assert!(buffer
+            .level_series(100.0, 200.0, LevelMetric::Peak)
+            .is_err());
+        assert!(buffer
+            .level_series(f32::NAN, 10.0, LevelMetric::Rms)
+            .is_err());
+    }
+
+    #[test]
+    fn test_level_series_on_ramp() {
+        let ramp: Vec<f32> = (0..48000).map(|i| i as f32 / 48000.0).collect();
+        let buffer = create_test_buffer(vec![ramp]);
+
+        let peaks = buffer.level_series(100.0, 50.0, LevelMetric::Peak).unwrap();
+        assert_eq!(peaks.len(), 19);
+        for (i, &peak) in peaks.iter().enumerate() {
+            // Each window peaks at its last sample
+            let expected = linear_to_db((i * 2400 + 4799) as f32 / 48000.0);
+            assert!((peak - expected).abs() < 1e-4, "window {}", i);
+        }
+
+        let rms = buffer.level_series(100.0, 50.0, LevelMetric::Rms).unwrap();
+        assert!(rms.windows(2).all(|w| w[1] > w[0]));
+        assert!(rms.iter().zip(&peaks).all(|(r, p)| r < p));
+    }
+
+    #[test]
+    fn test_level_series_on_sine() {
+        let sine: Vec<f32> = (0..96000)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin())
+            .collect();
+        let buffer = create_test_buffer(vec![sine.clone(), sine]);
+
+        for peak in buffer.level_series(50.0, 25.0, LevelMetric::Peak).unwrap() {
+            assert!((peak - linear_to_db(0.5)).abs() < 0.01);
+        }
+        for rms in buffer.level_series(50.0, 25.0, LevelMetric::Rms).unwrap() {
+            assert!((rms - linear_to_db(0.5 / 2f32.sqrt())).abs() < 0.01);
+        }
+
+        // A steady tone reads the same momentary loudness throughout
+        let lufs = buffer
+            .level_series(400.0, 100.0, LevelMetric::Lufs)
+            .unwrap();
+        let integrated = crate::engine::loudness::integrated_loudness(&buffer);
+        for value in &lufs[1..] {
+            assert!(
+                (value - integrated).abs() < 0.1,
+                "{} vs {}",
+                value,
+                integrated
+            );
+        }
+    }
+
+    #[test]
+    fn test_level_series_full_window_matches_whole_buffer() {
+        let buffer = clean_audio(4000);
+        let window_ms = 4000.0 * 1000.0 / buffer.sample_rate as f32;
+
+        let peak = buffer
+            .level_series(window_ms, window_ms, LevelMetric::Peak)
+            .unwrap();
+        assert_eq!(peak, vec![calculate_peak(&buffer)]);
+        let rms = buffer
+            .level_series(window_ms, window_ms, LevelMetric::Rms)
+            .unwrap();
+        assert_eq!(rms.len(), 1);
+        assert!((rms[0] - calculate_rms(&buffer)).abs() < 1e-5);
+    }
+
+    fn sine(frequency: f32, seconds: f32) -> AudioBuffer {
+        let len = (seconds * INTERNAL_SAMPLE_RATE as f32) as usize;
+        let channel = (0..len)
+            .map(|i| {
+                0.5 * (2.0 * std::f32::consts::PI * frequency * i as f32
+                    / INTERNAL_SAMPLE_RATE as f32)
+                    .sin()
+            })
+            .collect();
+        create_test_buffer(vec![channel])
+    }
+
+    /// Strongest frequency in the middle of channel 0, from a Hann-windowed
+    /// FFT with parabolic peak interpolation
+    fn dominant_frequency(buffer: &AudioBuffer) -> f64 {
+        let n = 16384;
+        let start = (buffer.len() - n) / 2;
+        let mut re: Vec<f64> = buffer.samples[0][start..start + n]
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| {
+                let window = 0.5 - 0.5 * (std::f64::consts::TAU * i as f64 / n as f64).cos();
+                s as f64 * window
+            })
+            .collect();
+        let mut im = vec![0.0; n];
+        crate::dsp::fft(&mut re, &mut im, false);
+
+        let magnitude: Vec<f64> = (0..n / 2).map(|k| re[k].hypot(im[k]).ln()).collect();
+        let peak = (1..n / 2 - 1)
+            .max_by(|&a, &b| magnitude[a].total_cmp(&magnitude[b]))
+            .unwrap();
+        let (left, center, right) = (magnitude[peak - 1], magnitude[peak], magnitude[peak + 1]);
+        let offset = 0.5 * (left - right) / (left - 2.0 * center + right);
+        (peak as f64 + offset) * INTERNAL_SAMPLE_RATE as f64 / n as f64
+    }
+
+    #[test]
+    fn test_time_stretch_unity_is_passthrough() {
+        let buffer = sine(440.0, 1.0);
+        let stretched = buffer.time_stretch(1.0).unwrap();
+
+        assert_eq!(stretched.len(), buffer.len());
+        let max_error = buffer.samples[0]
+            .iter()
+            .zip(&stretched.samples[0])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0_f32, f32::max);
+        assert!(max_error < 1e-3, "max error {}", max_error);
+    }
+
+    #[test]
+    fn test_time_stretch_keeps_pitch() {
+        let buffer = sine(440.0, 1.0);
+
+        for ratio in [2.0, 0.5, 1.5] {
+            let stretched = buffer.time_stretch(ratio).unwrap();
+            assert_eq!(
+                stretched.len(),
+                (buffer.len() as f32 * ratio).round() as usize
+            );
+            assert_eq!(stretched.sample_rate, buffer.sample_rate);
+
+            let frequency = dominant_frequency(&stretched);
+            assert!(
+                (frequency - 440.0).abs() < 1.0,
+                "ratio {}: {} Hz",
+                ratio,
+                frequency
+            );
+
+            // The level holds too (RMS in dB)
+            let middle = stretched.slice(stretched.len() / 4, stretched.len() * 3 / 4);
+            let level = calculate_rms(&middle.unwrap());
+            let reference = calculate_rms(&buffer);
+            assert!(
+                (level - reference).abs() < 0.05,
+                "ratio {}: {} dB RMS against {} dB",
+                ratio,
+                level,
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_stretch_bounds_transient_smearing() {
+        // A click half a second into a second of silence
+        let mut buffer = create_test_buffer(vec![vec![0.0; 48000]; 2]);
+        buffer.samples[0][24000] = 1.0;
+        buffer.samples[1][24000] = 1.0;
+        let stretched = buffer.time_stretch(2.0).unwrap();
+
+        let frame = crate::dsp::frame_size(48000.0);
+        let energy = |samples: &[f32]| samples.iter().map(|s| s * s).sum::<f32>();
+        let total = energy(&stretched.samples[0]);
+        let near = energy(&stretched.samples[0][48000 - frame..48000 + frame]);
+        assert!(total > 0.0);
+        assert!(
+            near / total > 0.99,
+            "{}% near the click",
+            near / total * 100.0
+        );
+        assert_eq!(stretched.samples[0], stretched.samples[1]);
+    }
+
+    #[test]
+    fn test_time_stretch_rejects_bad_ratio() {
+        let buffer = sine(440.0, 0.1);
+        for ratio in [0.0, -1.0, f32::NAN, f32::INFINITY, 8.5, 1e9] {
+            assert!(matches!(
+                buffer.time_stretch(ratio),
+                Err(NuevaError::InvalidParameter { .. })
+            ));
+        }
+        assert!(create_test_buffer(vec![vec![]])
+            .time_stretch(2.0)
+            .unwrap()
+            .is_empty());
+    }
 }
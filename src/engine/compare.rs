@@ -0,0 +1,399 @@
+//! Reference Comparison
+//!
+//! A/B measurements of a working mix against a reference track: the
+//! difference in integrated loudness, the difference in spectral tilt
+//! (the slope of the long-term spectrum in dB per octave, from averaged
+//! FFT frames) and the correlation of the two mono downmixes. Only the
+//! region both buffers cover is compared.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::buffer::AudioBuffer;
+use super::loudness::integrated_loudness;
+use crate::dsp::fft;
+use crate::error::{NuevaError, Result};
+
+/// Loudness differences within this many LU count as a match
+pub const LOUDNESS_MATCH_LU: f32 = 0.5;
+
+/// Tilt differences within this many dB per octave count as a match
+pub const TILT_MATCH_DB_PER_OCTAVE: f32 = 0.3;
+
+/// FFT frame length for the long-term spectrum
+const TILT_FFT_SIZE: usize = 4096;
+
+/// Lowest third-octave band centre included in the tilt fit
+const TILT_LOW_HZ: f64 = 100.0;
+
+/// Highest third-octave band centre included in the tilt fit
+const TILT_HIGH_HZ: f64 = 10_000.0;
+
+/// How the working mix's level sits against the reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoudnessVerdict {
+    Louder,
+    Quieter,
+    Matched,
+}
+
+/// How the working mix's tonal balance sits against the reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToneVerdict {
+    Brighter,
+    Darker,
+    Matched,
+}
+
+/// Working mix measured against a reference track
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComparisonReport {
+    /// Integrated loudness of the working mix in LUFS
+    pub working_lufs: f32,
+    /// Integrated loudness of the reference in LUFS
+    pub reference_lufs: f32,
+    /// Working minus reference loudness in LU (positive = louder)
+    pub loudness_difference_lu: f32,
+    /// Spectral tilt of the working mix in dB per octave
+    pub working_tilt: f32,
+    /// Spectral tilt of the reference in dB per octave
+    pub reference_tilt: f32,
+    /// Working minus reference tilt in dB per octave (positive = brighter)
+    pub tilt_difference: f32,
+    /// Correlation of the mono downmixes (-1 to 1)
+    pub correlation: f32,
+    /// Length of the compared region in seconds
+    pub compared_secs: f64,
+    /// Working mix length minus reference length in seconds; anything but
+    /// zero means only the overlap was compared
+    pub length_difference_secs: f64,
+}
+
+impl ComparisonReport {
+    /// Whether the working mix is louder, quieter or level-matched
+    pub fn loudness_verdict(&self) -> LoudnessVerdict {
+        if self.loudness_difference_lu > LOUDNESS_MATCH_LU {
+            LoudnessVerdict::Louder
+        } else if self.loudness_difference_lu < -LOUDNESS_MATCH_LU {
+            LoudnessVerdict::Quieter
+        } else {
+            LoudnessVerdict::Matched
+        }
+    }
+
+    /// Whether the working mix is brighter, darker or tonally matched
+    pub fn tone_verdict(&self) -> ToneVerdict {
+        if self.tilt_difference > TILT_MATCH_DB_PER_OCTAVE {
+            ToneVerdict::Brighter
+        } else if self.tilt_difference < -TILT_MATCH_DB_PER_OCTAVE {
+            ToneVerdict::Darker
+        } else {
+            ToneVerdict::Matched
+        }
+    }
+}
+
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Compared {:.2}s", self.compared_secs)?;
+        if self.length_difference_secs > 0.0 {
+            write!(
+                f,
+                " (the mix runs {:.2}s longer; overlap only)",
+                self.length_difference_secs
+            )?;
+        } else if self.length_difference_secs < 0.0 {
+            write!(
+                f,
+                " (the reference runs {:.2}s longer; overlap only)",
+                -self.length_difference_secs
+            )?;
+        }
+        writeln!(f)?;
+
+        let loudness = match self.loudness_verdict() {
+            LoudnessVerdict::Louder => "louder than the reference",
+            LoudnessVerdict::Quieter => "quieter than the reference",
+            LoudnessVerdict::Matched => "level-matched with the reference",
+        };
+        writeln!(
+            f,
+            "Loudness: {:.1} LUFS vs {:.1} LUFS ({:+.1} LU), {}",
+            self.working_lufs, self.reference_lufs, self.loudness_difference_lu, loudness
+        )?;
+
+        let tone = match self.tone_verdict() {
+            ToneVerdict::Brighter => "brighter than the reference",
+            ToneVerdict::Darker => "darker than the reference",
+            ToneVerdict::Matched => "tonally matched with the reference",
+        };
+        writeln!(
+            f,
+            "Tilt: {:.2} vs {:.2} dB/octave ({:+.2}), {}",
+            self.working_tilt, self.reference_tilt, self.tilt_difference, tone
+        )?;
+
+        writeln!(f, "Correlation: {:.2}", self.correlation)
+    }
+}
+
+/// Compare a working mix against a reference over the region both cover
+///
+/// Both buffers must share a sample rate and neither may be empty.
+pub fn compare_buffers(working: &AudioBuffer, reference: &AudioBuffer) -> Result<ComparisonReport> {
+    if working.sample_rate != reference.sample_rate {
+        return Err(NuevaError::InvalidAudio {
+            reason: format!(
+                "Cannot compare {} Hz audio against a {} Hz reference",
+                working.sample_rate, reference.sample_rate
+            ),
+            source: None,
+        });
+    }
+    let overlap = working.len().min(reference.len());
+    if overlap == 0 {
+        return Err(NuevaError::InvalidAudio {
+            reason: "Nothing to compare: the mix or the reference is empty".to_string(),
+            source: None,
+        });
+    }
+
+    let working_part = working.slice(0, overlap)?;
+    let reference_part = reference.slice(0, overlap)?;
+    let working_mono = downmix(&working_part);
+    let reference_mono = downmix(&reference_part);
+
+    let working_lufs = integrated_loudness(&working_part);
+    let reference_lufs = integrated_loudness(&reference_part);
+    let sample_rate = working.sample_rate as f64;
+    let working_tilt = spectral_tilt(&working_mono, sample_rate);
+    let reference_tilt = spectral_tilt(&reference_mono, sample_rate);
+
+    Ok(ComparisonReport {
+        working_lufs,
+        reference_lufs,
+        loudness_difference_lu: working_lufs - reference_lufs,
+        working_tilt,
+        reference_tilt,
+        tilt_difference: working_tilt - reference_tilt,
+        correlation: correlation(&working_mono, &reference_mono),
+        compared_secs: working_part.duration_secs(),
+        length_difference_secs: working.duration_secs() - reference.duration_secs(),
+    })
+}
+
+/// Average of all channels
+pub(crate) fn downmix(buffer: &AudioBuffer) -> Vec<f64> {
+    let scale = 1.0 / buffer.num_channels().max(1) as f64;
+    (0..buffer.len())
+        .map(|i| {
+            buffer
+                .samples
+                .iter()
+                .map(|channel| channel[i] as f64)
+                .sum::<f64>()
+                * scale
+        })
+        .collect()
+}
+
+/// Slope of the long-term spectrum in dB per octave
+///
+/// A line fitted to [`band_levels`] against octave, so white noise reads
+/// 0 and pink noise -3 dB per octave. Silence reads 0.
+pub fn spectral_tilt(samples: &[f64], sample_rate: f64) -> f32 {
+    let points: Vec<(f64, f64)> = band_levels(samples, sample_rate)
+        .into_iter()
+        .map(|(centre, level)| ((centre / TILT_LOW_HZ).log2(), level))
+        .collect();
+    if points.len() < 2 {
+        return 0.0;
+    }
+
+    // Least-squares slope of level against octave
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    (covariance / variance) as f32
+}
+
+/// Long-term level of each third-octave band, as `(centre Hz, dB)`
+///
+/// Hann-windowed frames (half overlapped) are averaged into a power
+/// spectrum and grouped into third-octave bands between [`TILT_LOW_HZ`]
+/// and [`TILT_HIGH_HZ`]. Levels are mean power per bin, so they follow
+/// the signal level; bands with no energy are left out.
+pub(crate) fn band_levels(samples: &[f64], sample_rate: f64) -> Vec<(f64, f64)> {
+    let size = TILT_FFT_SIZE;
+    let hop = size / 2;
+    let window: Vec<f64> = (0..size)
+        .map(|k| 0.5 - 0.5 * (std::f64::consts::TAU * k as f64 / size as f64).cos())
+        .collect();
+
+    let mut power = vec![0.0; size / 2 + 1];
+    let mut re = vec![0.0; size];
+    let mut im = vec![0.0; size];
+    let mut frames = 0;
+    let mut start = 0;
+    loop {
+        // A clip shorter than one frame is zero-padded
+        for (k, (r, i)) in re.iter_mut().zip(im.iter_mut()).enumerate() {
+            *r = samples.get(start + k).copied().unwrap_or(0.0) * window[k];
+            *i = 0.0;
+        }
+        fft(&mut re, &mut im, false);
+        for (bin, p) in power.iter_mut().enumerate() {
+            *p += re[bin] * re[bin] + im[bin] * im[bin];
+        }
+        frames += 1;
+        start += hop;
+        if start + size > samples.len() {
+            break;
+        }
+    }
+
+    let bin_hz = sample_rate / size as f64;
+    let mut levels = Vec::new();
+    let mut band = 0;
+    loop {
+        let centre = TILT_LOW_HZ * 2f64.powf(band as f64 / 3.0);
+        if centre > TILT_HIGH_HZ.min(sample_rate / 2.0) {
+            break;
+        }
+        let low = (centre * 2f64.powf(-1.0 / 6.0) / bin_hz).ceil() as usize;
+        let high = ((centre * 2f64.powf(1.0 / 6.0) / bin_hz).floor() as usize).min(size / 2);
+        if high >= low {
+            let mean = power[low..=high].iter().sum::<f64>() / ((high - low + 1) * frames) as f64;
+            if mean > 0.0 {
+                levels.push((centre, 10.0 * mean.log10()));
+            }
+        }
+        band += 1;
+    }
+    levels
+}
+
+/// Pearson correlation of two equal-length signals (0 if either is flat)
+fn correlation(a: &[f64], b: &[f64]) -> f32 {
+    let n = a.len().min(b.len());
+    if n == 0 {
+        return 0.0;
+    }
+    let mean_a = a[..n].iter().sum::<f64>() / n as f64;
+    let mean_b = b[..n].iter().sum::<f64>() / n as f64;
+
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (&x, &y) in a[..n].iter().zip(&b[..n]) {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        cov += dx * dy;
+        var_a += dx * dx;
+        var_b += dy * dy;
+    }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return 0.0;
+    }
+    (cov / (var_a * var_b).sqrt()) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::INTERNAL_SAMPLE_RATE;
+
+    /// Seeded white noise
+    fn noise(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                ((state >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 0.5
+            })
+            .collect()
+    }
+
+    /// One-pole low-pass, which darkens by rolling off the highs
+    fn low_passed(samples: &[f32]) -> Vec<f32> {
+        let mut y = 0.0;
+        samples
+            .iter()
+            .map(|&x| {
+                y += 0.2 * (x - y);
+                y
+            })
+            .collect()
+    }
+
+    fn stereo(samples: Vec<f32>) -> AudioBuffer {
+        AudioBuffer {
+            samples: vec![samples.clone(), samples],
+            sample_rate: INTERNAL_SAMPLE_RATE,
+        }
+    }
+
+    #[test]
+    fn test_identical_buffers_match() {
+        let mix = stereo(noise(96000, 1));
+        let report = compare_buffers(&mix, &mix).unwrap();
+        assert!(report.loudness_difference_lu.abs() < 1e-4);
+        assert!(report.tilt_difference.abs() < 1e-4);
+        assert!((report.correlation - 1.0).abs() < 1e-6);
+        assert_eq!(report.loudness_verdict(), LoudnessVerdict::Matched);
+        assert_eq!(report.tone_verdict(), ToneVerdict::Matched);
+        assert!(
+            report.working_tilt.abs() < 0.5,
+            "white noise tilt {}",
+            report.working_tilt
+        );
+    }
+
+    #[test]
+    fn test_louder_and_darker_mix() {
+        let reference = stereo(noise(96000, 2));
+        let dark: Vec<f32> = low_passed(&reference.samples[0])
+            .iter()
+            .map(|s| s * 4.0)
+            .collect();
+        let report = compare_buffers(&stereo(dark), &reference).unwrap();
+
+        assert_eq!(report.tone_verdict(), ToneVerdict::Darker);
+        assert!(report.tilt_difference < -1.0);
+        let text = report.to_string();
+        assert!(text.contains("darker than the reference"), "{}", text);
+        assert!(text.contains("Correlation:"));
+
+        let quiet: Vec<f32> = reference.samples[0].iter().map(|s| s * 0.25).collect();
+        let report = compare_buffers(&stereo(quiet), &reference).unwrap();
+        assert_eq!(report.loudness_verdict(), LoudnessVerdict::Quieter);
+        assert!((report.loudness_difference_lu + 12.04).abs() < 0.1);
+        assert!(report.to_string().contains("quieter than the reference"));
+    }
+
+    #[test]
+    fn test_mismatched_lengths_compare_the_overlap() {
+        let reference = stereo(noise(96000, 3));
+        let short = reference.slice(0, 48000).unwrap();
+
+        let report = compare_buffers(&short, &reference).unwrap();
+        assert!((report.compared_secs - 1.0).abs() < 1e-9);
+        assert!((report.length_difference_secs + 1.0).abs() < 1e-9);
+        // The overlapping region is identical
+        assert!((report.correlation - 1.0).abs() < 1e-6);
+        assert!(report
+            .to_string()
+            .contains("the reference runs 1.00s longer"));
+    }
+
+    #[test]
+    fn test_incompatible_buffers_are_rejected() {
+        let mix = stereo(noise(4800, 4));
+        let mut other_rate = mix.clone();
+        other_rate.sample_rate = 44100;
+        assert!(compare_buffers(&mix, &other_rate).is_err());
+        assert!(compare_buffers(&mix, &stereo(Vec::new())).is_err());
+    }
+}
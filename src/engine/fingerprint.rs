@@ -0,0 +1,229 @@
+//! Perceptual Fingerprint
+//!
+//! A compact spectral hash of a recording, used to tell whether two
+//! files hold the same audio even when their bytes differ (re-encoded at
+//! another bit depth, a small gain change). Every 100 ms, a Hann-windowed
+//! FFT frame of the mono downmix is split into log-spaced bands between
+//! [`LOW_HZ`] and [`HIGH_HZ`] and reduced to 32 bits:
+//!
+//! - the low 16 bits say whether each band is louder than the next one up
+//!   (the shape of the spectrum)
+//! - the high 16 bits say whether that band-to-band difference grew by
+//!   more than [`CHANGE_MARGIN_DB`] since the previous frame (how the
+//!   spectrum moves)
+//!
+//! Band levels are floored [`FLOOR_BELOW_PEAK_DB`] below the loudest band
+//! in the frame, so noise far under the signal can't flip bits.
+//! The fingerprint is the frames' words in hex, eight characters per frame.
+
+use super::buffer::AudioBuffer;
+use super::compare::downmix;
+use crate::dsp::fft;
+
+/// Fingerprints further apart than this (see [`fingerprint_distance`])
+/// are different audio
+pub const FINGERPRINT_CHANGE_THRESHOLD: f32 = 0.25;
+
+/// FFT frame length
+const FFT_SIZE: usize = 4096;
+
+/// Time between frames in seconds
+const HOP_SECS: f64 = 0.1;
+
+/// Number of bands per frame; one bit of each kind per adjacent pair
+const NUM_BANDS: usize = 17;
+
+/// Lower edge of the lowest band
+const LOW_HZ: f64 = 100.0;
+
+/// Upper edge of the highest band
+const HIGH_HZ: f64 = 8000.0;
+
+/// Band levels are never taken lower than this far below the frame's
+/// loudest band
+const FLOOR_BELOW_PEAK_DB: f64 = 60.0;
+
+/// Band levels are never taken lower than this, relative to a full-scale
+/// sine, so near-silence reads as silence
+const ABSOLUTE_FLOOR_DB: f64 = -90.0;
+
+/// How much a band-to-band difference must grow between frames to count
+const CHANGE_MARGIN_DB: f64 = 1.0;
+
+/// Perceptual fingerprint of `buffer`
+///
+/// Deterministic for a given buffer. Audio shorter than one hop (100 ms)
+/// has an empty fingerprint.
+pub fn fingerprint(buffer: &AudioBuffer) -> String {
+    let mono = downmix(buffer);
+    let sample_rate = buffer.sample_rate as f64;
+    let hop = ((sample_rate * HOP_SECS).round() as usize).max(1);
+    let window: Vec<f64> = (0..FFT_SIZE)
+        .map(|k| 0.5 - 0.5 * (std::f64::consts::TAU * k as f64 / FFT_SIZE as f64).cos())
+        .collect();
+    let edges: Vec<usize> = (0..=NUM_BANDS)
+        .map(|b| {
+            let hz = LOW_HZ * (HIGH_HZ / LOW_HZ).powf(b as f64 / NUM_BANDS as f64);
+            ((hz * FFT_SIZE as f64 / sample_rate).round() as usize).min(FFT_SIZE / 2)
+        })
+        .collect();
+    // Peak bin magnitude of a full-scale sine under the Hann window
+    let full_scale = (FFT_SIZE as f64 / 4.0).powi(2);
+
+    let mut re = vec![0.0; FFT_SIZE];
+    let mut im = vec![0.0; FFT_SIZE];
+    let mut previous: Option<[f64; NUM_BANDS - 1]> = None;
+    let mut hex = String::new();
+    let mut start = 0;
+    while start + hop <= mono.len() {
+        // Frames running past the end are zero-padded
+        for (k, (r, i)) in re.iter_mut().zip(im.iter_mut()).enumerate() {
+            *r = mono.get(start + k).copied().unwrap_or(0.0) * window[k];
+            *i = 0.0;
+        }
+        fft(&mut re, &mut im, false);
+
+        let mut levels = [0.0; NUM_BANDS];
+        for (b, level) in levels.iter_mut().enumerate() {
+            let power: f64 = (edges[b]..edges[b + 1].max(edges[b] + 1))
+                .map(|bin| re[bin] * re[bin] + im[bin] * im[bin])
+                .sum();
+            *level = 10.0 * (power / full_scale).max(1e-20).log10();
+        }
+        let peak = levels.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let floor = (peak - FLOOR_BELOW_PEAK_DB).max(ABSOLUTE_FLOOR_DB);
+        for level in levels.iter_mut() {
+            *level = level.max(floor);
+        }
+
+        let mut differences = [0.0; NUM_BANDS - 1];
+        let mut word = 0u32;
+        for b in 0..NUM_BANDS - 1 {
+            differences[b] = levels[b] - levels[b + 1];
+            if differences[b] > 0.0 {
+                word |= 1 << b;
+            }
+            if let Some(previous) = previous {
+                if differences[b] - previous[b] > CHANGE_MARGIN_DB {
+                    word |= 1 << (b + 16);
+                }
+            }
+        }
+        previous = Some(differences);
+        hex.push_str(&format!("{:08x}", word));
+        start += hop;
+    }
+    hex
+}
+
+/// How different two fingerprints are, from 0.0 (identical) to 1.0
+///
+/// The number of differing bits over the number of bits set in either
+/// fingerprint, frame by frame. Frames one fingerprint has and the other
+/// lacks count as all-zero, so a much shorter or longer recording reads
+/// as different. Two fingerprints with no bits set (silence) are
+/// identical. Returns `None` if either string isn't a fingerprint.
+pub fn fingerprint_distance(a: &str, b: &str) -> Option<f32> {
+    let a = parse(a)?;
+    let b = parse(b)?;
+    let mut differing = 0u32;
+    let mut set = 0u32;
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        differing += (x ^ y).count_ones();
+        set += (x | y).count_ones();
+    }
+    if set == 0 {
+        return Some(0.0);
+    }
+    Some(differing as f32 / set as f32)
+}
+
+/// Split a fingerprint into its per-frame words
+fn parse(fingerprint: &str) -> Option<Vec<u32>> {
+    if !fingerprint.len().is_multiple_of(8) || !fingerprint.is_ascii() {
+        return None;
+    }
+    (0..fingerprint.len())
+        .step_by(8)
+        .map(|i| u32::from_str_radix(&fingerprint[i..i + 8], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::ChannelLayout;
+
+    /// A two-second phrase of harmonic notes with a little noise
+    fn phrase(notes: &[f32], seed: u32) -> AudioBuffer {
+        let sample_rate = 44100;
+        let note_len = sample_rate as usize / 4;
+        let mut buffer = AudioBuffer::new(note_len * notes.len(), ChannelLayout::Mono);
+        buffer.sample_rate = sample_rate;
+        let mut state = seed;
+        for (i, sample) in buffer.samples[0].iter_mut().enumerate() {
+            let freq = notes[i / note_len];
+            let t = i as f32 / sample_rate as f32;
+            let tone: f32 = (1..=6)
+                .map(|h| (std::f32::consts::TAU * freq * h as f32 * t).sin() / h as f32)
+                .sum();
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let noise = (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+            *sample = 0.2 * tone + 0.01 * noise;
+        }
+        buffer
+    }
+
+    const MELODY: [f32; 8] = [220.0, 247.0, 262.0, 294.0, 330.0, 294.0, 262.0, 247.0];
+    const OTHER: [f32; 8] = [392.0, 175.0, 523.0, 131.0, 440.0, 698.0, 165.0, 349.0];
+
+    #[test]
+    fn test_same_audio_same_fingerprint() {
+        let a = fingerprint(&phrase(&MELODY, 1));
+        let b = fingerprint(&phrase(&MELODY, 1));
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 20 * 8);
+        assert_eq!(fingerprint_distance(&a, &b), Some(0.0));
+    }
+
+    #[test]
+    fn test_slightly_modified_audio_stays_close() {
+        let original = phrase(&MELODY, 1);
+        // 16-bit re-encode and a 0.5 dB gain change
+        let mut modified = original.clone();
+        let gain = 10f32.powf(-0.5 / 20.0);
+        for sample in modified.samples[0].iter_mut() {
+            *sample = (*sample * gain * 32767.0).round() / 32767.0;
+        }
+
+        let distance =
+            fingerprint_distance(&fingerprint(&original), &fingerprint(&modified)).unwrap();
+        assert!(
+            distance < FINGERPRINT_CHANGE_THRESHOLD / 2.0,
+            "{}",
+            distance
+        );
+    }
+
+    #[test]
+    fn test_different_audio_is_far_apart() {
+        let melody = fingerprint(&phrase(&MELODY, 1));
+        let other = fingerprint(&phrase(&OTHER, 2));
+        let silence = fingerprint(&AudioBuffer::new(88200, ChannelLayout::Mono));
+
+        let distance = fingerprint_distance(&melody, &other).unwrap();
+        assert!(
+            distance > FINGERPRINT_CHANGE_THRESHOLD * 1.5,
+            "{}",
+            distance
+        );
+        assert_eq!(fingerprint_distance(&melody, &silence), Some(1.0));
+        assert_eq!(fingerprint_distance(&silence, &silence), Some(0.0));
+        // Half the recording missing
+        let half = fingerprint_distance(&melody, &melody[..melody.len() / 2]).unwrap();
+        assert!(half > FINGERPRINT_CHANGE_THRESHOLD, "{}", half);
+        assert_eq!(fingerprint_distance(&melody, "not hex!"), None);
+    }
+}
@@ -5,18 +5,39 @@
 //!
 //! All audio is converted to internal 48kHz 32-bit float format on import.
 //! Sample rate conversion uses linear interpolation (TODO: upgrade to sinc).
+//!
+//! Cue points, loops and tempo can be carried through with
+//! [`import_audio_with_metadata`] and [`export_audio_with_metadata`].
 
 use std::path::Path;
 
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
 use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
 
 use crate::engine::buffer::{AudioBuffer, ChannelLayout, INTERNAL_SAMPLE_RATE};
+use crate::engine::transport::LoopRegion;
+use crate::engine::wav_metadata::WavMetadata;
 use crate::error::{NuevaError, Result};
 
 // Duration limits per spec section 3.5
 const MIN_DURATION_SECS: f64 = 0.1;
 const MAX_DURATION_SECS: f64 = 2.0 * 60.0 * 60.0; // 2 hours
 
+/// Dither applied when quantizing to an integer bit depth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DitherType {
+    /// Plain quantization (truncation toward zero)
+    #[default]
+    None,
+    /// Triangular (TPDF) dither of +/-1 LSB, decorrelating the
+    /// quantization error from the signal
+    Tpdf,
+    /// TPDF dither with first-order error feedback, moving the noise
+    /// floor towards high frequencies where it is less audible
+    NoiseShaped,
+}
+
 /// Export format configuration
 #[derive(Debug, Clone)]
 pub struct ExportFormat {
@@ -24,6 +45,11 @@ pub struct ExportFormat {
     pub sample_rate: u32,
     /// Bit depth: 16, 24, or 32 (default: 24)
     pub bit_depth: u16,
+    /// Dither for 16/24-bit output; ignored for 32-bit float (default: None)
+    pub dither: DitherType,
+    /// Crossfade a loop region so the file loops without a click
+    /// (default: None)
+    pub seamless_loop: Option<SeamlessLoop>,
 }
 
 impl Default for ExportFormat {
@@ -31,6 +57,8 @@ impl Default for ExportFormat {
         ExportFormat {
             sample_rate: 48000,
             bit_depth: 24,
+            dither: DitherType::None,
+            seamless_loop: None,
         }
     }
 }
@@ -41,6 +69,8 @@ impl ExportFormat {
         ExportFormat {
             sample_rate,
             bit_depth,
+            dither: DitherType::None,
+            seamless_loop: None,
         }
     }
 
@@ -49,6 +79,8 @@ impl ExportFormat {
         ExportFormat {
             sample_rate: 44100,
             bit_depth: 16,
+            dither: DitherType::None,
+            seamless_loop: None,
         }
     }
 
@@ -57,6 +89,8 @@ impl ExportFormat {
         ExportFormat {
             sample_rate: 48000,
             bit_depth: 24,
+            dither: DitherType::None,
+            seamless_loop: None,
         }
     }
 
@@ -65,8 +99,276 @@ impl ExportFormat {
         ExportFormat {
             sample_rate: 96000,
             bit_depth: 32,
+            dither: DitherType::None,
+            seamless_loop: None,
+        }
+    }
+
+    /// Use the given dither when quantizing
+    pub fn with_dither(mut self, dither: DitherType) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Make the export loop seamlessly (see [`seamless_loop`])
+    pub fn with_seamless_loop(mut self, seamless_loop: SeamlessLoop) -> Self {
+        self.seamless_loop = Some(seamless_loop);
+        self
+    }
+}
+
+/// Loop-aware export settings
+///
+/// The audio leading into the loop start is crossfaded into the end of
+/// the loop, so playback wrapping from the end back to the start carries
+/// on as if it had never jumped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeamlessLoop {
+    /// The loop, at the buffer's sample rate
+    pub region: LoopRegion,
+    /// Crossfade length in milliseconds (default: 10)
+    pub crossfade_ms: f32,
+    /// Export exactly one loop length instead of the whole buffer
+    pub trim_to_loop: bool,
+}
+
+impl SeamlessLoop {
+    /// Seamless loop over `region` with the default crossfade, keeping the
+    /// whole buffer
+    pub fn new(region: LoopRegion) -> Self {
+        Self {
+            region,
+            crossfade_ms: 10.0,
+            trim_to_loop: false,
+        }
+    }
+
+    /// Use a crossfade of `crossfade_ms`
+    pub fn with_crossfade_ms(mut self, crossfade_ms: f32) -> Self {
+        self.crossfade_ms = crossfade_ms;
+        self
+    }
+
+    /// Export only the loop itself
+    pub fn trimmed(mut self) -> Self {
+        self.trim_to_loop = true;
+        self
+    }
+}
+
+/// Crossfade the end of a loop with the audio leading into its start
+///
+/// The last samples of the loop fade out while the same length of audio
+/// before the loop start fades in, with equal-power (sine/cosine) gains so
+/// there is no level dip. The loop then ends on the samples that
+/// originally led into its start, and wraps without a discontinuity. If
+/// there isn't enough audio before the start, the crossfade is shortened
+/// to what there is, and the start of the loop is faded in from the audio
+/// after its end instead when that is longer.
+///
+/// Returns the whole buffer with the loop crossfaded in place, or just
+/// the loop if `trim_to_loop` is set. Errors if the region is empty or
+/// runs past the end of the buffer.
+pub fn seamless_loop(buffer: &AudioBuffer, settings: &SeamlessLoop) -> Result<AudioBuffer> {
+    let region = settings.region;
+    if region.end_sample <= region.start_sample || region.end_sample > buffer.len() as u64 {
+        return Err(NuevaError::InvalidParameter {
+            param: "loop_region".to_string(),
+            value: format!("{}..{}", region.start_sample, region.end_sample),
+            expected: format!("a non-empty region within 0..{}", buffer.len()),
+        });
+    }
+    if !settings.crossfade_ms.is_finite() || settings.crossfade_ms < 0.0 {
+        return Err(NuevaError::InvalidParameter {
+            param: "crossfade_ms".to_string(),
+            value: settings.crossfade_ms.to_string(),
+            expected: "a non-negative number of milliseconds".to_string(),
+        });
+    }
+
+    let start = region.start_sample as usize;
+    let end = region.end_sample as usize;
+    let length = end - start;
+    let requested =
+        (settings.crossfade_ms as f64 * buffer.sample_rate as f64 / 1000.0).round() as usize;
+    let before = start.min(length);
+    let after = (buffer.len() - end).min(length);
+
+    let mut output = buffer.clone();
+    let fade = requested.min(before.max(after));
+    for k in 0..fade {
+        let t = (k as f64 + 0.5) / fade as f64 * std::f64::consts::FRAC_PI_2;
+        let (fade_in, fade_out) = (t.sin() as f32, t.cos() as f32);
+        for (out, original) in output.samples.iter_mut().zip(&buffer.samples) {
+            if before >= after {
+                // End of the loop fades into what led up to its start
+                let i = end - fade + k;
+                out[i] = original[i] * fade_out + original[start - fade + k] * fade_in;
+            } else {
+                // Start of the loop fades in from what followed its end
+                let i = start + k;
+                out[i] = original[i] * fade_in + original[end + k] * fade_out;
+            }
+        }
+    }
+
+    if settings.trim_to_loop {
+        for channel in output.samples.iter_mut() {
+            channel.truncate(end);
+            channel.drain(..start);
+        }
+    }
+    Ok(output)
+}
+
+/// Container for exported files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFileFormat {
+    Wav,
+    Flac,
+}
+
+impl AudioFileFormat {
+    /// Parse a format name ("wav", "flac"), ignoring case
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "wav" | "wave" => Ok(Self::Wav),
+            "flac" => Ok(Self::Flac),
+            _ => Err(NuevaError::UnsupportedFormat {
+                format: format!("{} (expected wav or flac)", name),
+            }),
+        }
+    }
+
+    /// File extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+        }
+    }
+
+    /// Bit depths the container can store
+    pub fn supported_bit_depths(&self) -> &'static [u16] {
+        match self {
+            Self::Wav => &[16, 24, 32],
+            // FLAC is integer-only
+            Self::Flac => &[16, 24],
+        }
+    }
+
+    /// Check that `bit_depth` can be written in this container
+    pub fn validate_bit_depth(&self, bit_depth: u16) -> Result<()> {
+        let supported = self.supported_bit_depths();
+        if supported.contains(&bit_depth) {
+            return Ok(());
+        }
+        let supported = supported
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(NuevaError::UnsupportedFormat {
+            format: format!(
+                "{}-bit {} (supported bit depths: {})",
+                bit_depth,
+                self.extension().to_uppercase(),
+                supported
+            ),
+        })
+    }
+}
+
+/// Export audio in the given container
+pub fn export_audio_as(
+    buffer: &AudioBuffer,
+    path: &Path,
+    file_format: AudioFileFormat,
+    format: ExportFormat,
+) -> Result<()> {
+    file_format.validate_bit_depth(format.bit_depth)?;
+    match file_format {
+        AudioFileFormat::Wav => export_audio(buffer, path, format),
+        AudioFileFormat::Flac => export_flac(buffer, path, format),
+    }
+}
+
+/// Export an AudioBuffer to a FLAC file
+///
+/// Takes the same settings as [`export_audio`], but only 16 or 24-bit
+/// output: FLAC has no float samples.
+pub fn export_flac(buffer: &AudioBuffer, path: &Path, format: ExportFormat) -> Result<()> {
+    AudioFileFormat::Flac.validate_bit_depth(format.bit_depth)?;
+    let channels = buffer.num_channels();
+    let interleaved = export_samples(buffer, &format)?;
+    let samples = quantize_samples(interleaved, &format, channels);
+
+    let encode_error = |reason: String| NuevaError::Io(std::io::Error::other(reason));
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| encode_error(format!("{:?}", e)))?;
+    let source = flacenc::source::MemSource::from_samples(
+        &samples,
+        channels,
+        format.bit_depth as usize,
+        format.sample_rate as usize,
+    );
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| encode_error(format!("{:?}", e)))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| encode_error(format!("{:?}", e)))?;
+    std::fs::write(path, sink.as_slice())?;
+    Ok(())
+}
+
+/// Audio and metadata read from a file
+#[derive(Debug, Clone)]
+pub struct ImportResult {
+    /// The audio in internal format
+    pub buffer: AudioBuffer,
+    /// Cue points, loop and tempo, with positions at the buffer's rate
+    pub metadata: WavMetadata,
+}
+
+/// Import an audio file along with its cue points, loop and tempo
+///
+/// Behaves like [`import_audio`]; files without metadata give an empty
+/// [`WavMetadata`]. Positions are converted to the internal sample rate,
+/// and cue points past the end of the audio are dropped.
+pub fn import_audio_with_metadata(path: &Path) -> Result<ImportResult> {
+    import_audio_with_metadata_at(path, INTERNAL_SAMPLE_RATE)
+}
+
+/// Import an audio file and its metadata, resampling it to `sample_rate`
+///
+/// Like [`import_audio_with_metadata`], for projects that process at a
+/// rate other than the default.
+pub fn import_audio_with_metadata_at(path: &Path, sample_rate: u32) -> Result<ImportResult> {
+    let (buffer, source_sample_rate) = read_wav(path, sample_rate)?;
+    let mut metadata = WavMetadata::read(path)?.rescaled(source_sample_rate, sample_rate);
+
+    let length = buffer.len() as u64;
+    metadata.markers.retain(|marker| {
+        let inside = marker.sample <= length;
+        if !inside {
+            log::warn!(
+                "Dropping WAV cue '{}' past the end of the audio",
+                marker.name
+            );
+        }
+        inside
+    });
+    if let Some(region) = metadata.loop_region {
+        if region.end_sample > length {
+            log::warn!("Dropping WAV loop past the end of the audio");
+            metadata.loop_region = None;
         }
     }
+
+    Ok(ImportResult { buffer, metadata })
 }
 
 /// Import an audio file and convert to internal format
@@ -88,6 +390,32 @@ impl ExportFormat {
 /// * `AudioTooShort` - If duration is less than 0.1 seconds
 /// * `AudioTooLong` - If duration exceeds 2 hours
 pub fn import_audio(path: &Path) -> Result<AudioBuffer> {
+    import_audio_at(path, INTERNAL_SAMPLE_RATE)
+}
+
+/// Import an audio file, resampling it to `sample_rate`
+///
+/// Like [`import_audio`], for projects that process at a rate other than
+/// the default.
+pub fn import_audio_at(path: &Path, sample_rate: u32) -> Result<AudioBuffer> {
+    read_wav(path, sample_rate).map(|(buffer, _)| buffer)
+}
+
+/// Import an audio file at its own sample rate, without resampling
+///
+/// For code that works on whatever rate the file was written at, such as
+/// neural models handed a project's audio.
+pub fn import_audio_native(path: &Path) -> Result<AudioBuffer> {
+    // Files hound can't open fall through so read_wav reports the error
+    let sample_rate = WavReader::open(path)
+        .map(|reader| reader.spec().sample_rate)
+        .unwrap_or(INTERNAL_SAMPLE_RATE);
+    import_audio_at(path, sample_rate)
+}
+
+/// Read a WAV file into internal format at `target_rate`, also returning
+/// its original rate
+fn read_wav(path: &Path, target_rate: u32) -> Result<(AudioBuffer, u32)> {
     // Check file exists
     if !path.exists() {
         return Err(NuevaError::FileNotFound {
@@ -134,9 +462,9 @@ pub fn import_audio(path: &Path) -> Result<AudioBuffer> {
     // De-interleave samples into separate channels
     let channel_data = deinterleave(&samples_f32, channels);
 
-    // Resample to internal sample rate if needed
-    let resampled_data = if source_sample_rate != INTERNAL_SAMPLE_RATE {
-        resample_channels(&channel_data, source_sample_rate, INTERNAL_SAMPLE_RATE)
+    // Resample to the processing rate if needed
+    let resampled_data = if source_sample_rate != target_rate {
+        resample_channels(&channel_data, source_sample_rate, target_rate)
     } else {
         channel_data
     };
@@ -148,7 +476,7 @@ pub fn import_audio(path: &Path) -> Result<AudioBuffer> {
         ChannelLayout::Stereo
     };
 
-    let mut buffer = AudioBuffer::new(resampled_data[0].len(), layout);
+    let mut buffer = AudioBuffer::with_sample_rate(resampled_data[0].len(), layout, target_rate);
 
     // Copy data to buffer
     for (ch, data) in resampled_data.iter().enumerate() {
@@ -163,13 +491,15 @@ pub fn import_audio(path: &Path) -> Result<AudioBuffer> {
         return Err(NuevaError::EmptyAudio);
     }
 
-    Ok(buffer)
+    Ok((buffer, source_sample_rate))
 }
 
 /// Export an AudioBuffer to a WAV file
 ///
 /// Writes the buffer to a WAV file with the specified format.
-/// Resamples if the target sample rate differs from internal rate.
+/// Resamples if the target sample rate differs from the buffer's.
+/// If the format has a [`SeamlessLoop`], the loop is crossfaded (and
+/// optionally trimmed) first.
 ///
 /// # Arguments
 /// * `buffer` - The audio buffer to export
@@ -178,19 +508,11 @@ pub fn import_audio(path: &Path) -> Result<AudioBuffer> {
 ///
 /// # Returns
 /// * `Ok(())` - If the file was written successfully
-/// * `Err(NuevaError)` - If the file cannot be written
+/// * `Err(NuevaError)` - If the file cannot be written, or the loop
+///   region is invalid
 pub fn export_audio(buffer: &AudioBuffer, path: &Path, format: ExportFormat) -> Result<()> {
     let channels = buffer.num_channels() as u16;
-
-    // Resample if needed
-    let export_data = if format.sample_rate != INTERNAL_SAMPLE_RATE {
-        resample_channels(&buffer.samples, INTERNAL_SAMPLE_RATE, format.sample_rate)
-    } else {
-        buffer.samples.clone()
-    };
-
-    // Interleave channels
-    let interleaved = interleave(&export_data);
+    let interleaved = export_samples(buffer, &format)?;
 
     // Create WAV spec
     let spec = WavSpec {
@@ -205,46 +527,31 @@ pub fn export_audio(buffer: &AudioBuffer, path: &Path, format: ExportFormat) ->
     };
 
     // Create writer
-    let mut writer = WavWriter::create(path, spec).map_err(|e| {
-        NuevaError::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            e.to_string(),
-        ))
-    })?;
+    let mut writer = WavWriter::create(path, spec)
+        .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
 
     // Write samples based on bit depth
     match format.bit_depth {
         16 => {
-            for sample in interleaved {
-                let scaled = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-                writer.write_sample(scaled).map_err(|e| {
-                    NuevaError::Io(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        e.to_string(),
-                    ))
-                })?;
+            for sample in quantize_samples(interleaved, &format, channels as usize) {
+                writer
+                    .write_sample(sample as i16)
+                    .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
             }
         }
         24 => {
-            for sample in interleaved {
-                // 24-bit stored as i32 in hound
-                let scaled = (sample * 8388607.0).clamp(-8388608.0, 8388607.0) as i32;
-                writer.write_sample(scaled).map_err(|e| {
-                    NuevaError::Io(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        e.to_string(),
-                    ))
-                })?;
+            // 24-bit stored as i32 in hound
+            for sample in quantize_samples(interleaved, &format, channels as usize) {
+                writer
+                    .write_sample(sample)
+                    .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
             }
         }
         32 => {
             for sample in interleaved {
-                writer.write_sample(sample).map_err(|e| {
-                    NuevaError::Io(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        e.to_string(),
-                    ))
-                })?;
+                writer
+                    .write_sample(sample)
+                    .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
             }
         }
         _ => {
@@ -254,16 +561,120 @@ pub fn export_audio(buffer: &AudioBuffer, path: &Path, format: ExportFormat) ->
         }
     }
 
-    writer.finalize().map_err(|e| {
-        NuevaError::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            e.to_string(),
-        ))
-    })?;
+    writer
+        .finalize()
+        .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
 
     Ok(())
 }
 
+/// Interleaved samples to export: looped and resampled as `format` asks
+fn export_samples(buffer: &AudioBuffer, format: &ExportFormat) -> Result<Vec<f32>> {
+    let looped;
+    let buffer = match &format.seamless_loop {
+        Some(settings) => {
+            looped = seamless_loop(buffer, settings)?;
+            &looped
+        }
+        None => buffer,
+    };
+
+    // Resample if needed
+    let export_data = if format.sample_rate != buffer.sample_rate {
+        resample_channels(&buffer.samples, buffer.sample_rate, format.sample_rate)
+    } else {
+        buffer.samples.clone()
+    };
+
+    Ok(interleave(&export_data))
+}
+
+/// Quantize interleaved samples to 16 or 24-bit integers, dithering as
+/// `format` asks
+fn quantize_samples(interleaved: Vec<f32>, format: &ExportFormat, channels: usize) -> Vec<i32> {
+    let (scale, min) = if format.bit_depth == 16 {
+        (32767.0, -32768.0)
+    } else {
+        (8388607.0, -8388608.0)
+    };
+    let mut ditherer = Ditherer::new(format.dither, channels);
+    interleaved
+        .into_iter()
+        .enumerate()
+        .map(|(i, sample)| match ditherer.as_mut() {
+            Some(d) => d.quantize(sample, i, scale, min, scale) as i32,
+            None => (sample * scale as f32).clamp(min as f32, scale as f32) as i32,
+        })
+        .collect()
+}
+
+/// Export an AudioBuffer to a WAV file with cue points, loop and tempo
+///
+/// Writes the audio exactly as [`export_audio`] does, then appends the
+/// metadata chunks. Positions are given at the buffer's sample rate.
+pub fn export_audio_with_metadata(
+    buffer: &AudioBuffer,
+    path: &Path,
+    format: ExportFormat,
+    metadata: &WavMetadata,
+) -> Result<()> {
+    let sample_rate = format.sample_rate;
+    export_audio(buffer, path, format)?;
+    if metadata.is_empty() {
+        return Ok(());
+    }
+    metadata
+        .rescaled(buffer.sample_rate, sample_rate)
+        .append_to(path, sample_rate)
+}
+
+/// Dither state for one export
+///
+/// Uses a fixed seed so the same buffer always exports to the same file.
+struct Ditherer {
+    shaped: bool,
+    rng: u64,
+    /// Last quantization error per channel (for noise shaping)
+    errors: Vec<f64>,
+}
+
+impl Ditherer {
+    fn new(dither: DitherType, channels: usize) -> Option<Self> {
+        let shaped = match dither {
+            DitherType::None => return None,
+            DitherType::Tpdf => false,
+            DitherType::NoiseShaped => true,
+        };
+        Some(Self {
+            shaped,
+            rng: 0x4E55_4556_415F_4449,
+            errors: vec![0.0; channels.max(1)],
+        })
+    }
+
+    /// Uniform value in [-0.5, 0.5)
+    fn next_uniform(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    }
+
+    /// Quantize interleaved sample `index` to an integer step of `scale`
+    fn quantize(&mut self, sample: f32, index: usize, scale: f64, min: f64, max: f64) -> f64 {
+        let channel = index % self.errors.len();
+        let mut value = sample as f64 * scale;
+        if self.shaped {
+            value -= self.errors[channel];
+        }
+
+        let dither = self.next_uniform() + self.next_uniform();
+        let quantized = (value + dither).round().clamp(min, max);
+        self.errors[channel] = quantized - value;
+        quantized
+    }
+}
+
 /// Generate a test tone (sine wave)
 ///
 /// Creates a mono AudioBuffer containing a sine wave at the specified frequency.
@@ -278,7 +689,7 @@ pub fn export_audio(buffer: &AudioBuffer, path: &Path, format: ExportFormat) ->
 /// An AudioBuffer containing the generated sine wave
 pub fn generate_test_tone(frequency: f32, duration_secs: f32, sample_rate: u32) -> AudioBuffer {
     let num_samples = (duration_secs * sample_rate as f32) as usize;
-    let mut buffer = AudioBuffer::new(num_samples, ChannelLayout::Mono);
+    let mut buffer = AudioBuffer::with_sample_rate(num_samples, ChannelLayout::Mono, sample_rate);
 
     let angular_freq = 2.0 * std::f32::consts::PI * frequency / sample_rate as f32;
 
@@ -309,7 +720,7 @@ pub fn generate_stereo_test_tone(
     sample_rate: u32,
 ) -> AudioBuffer {
     let num_samples = (duration_secs * sample_rate as f32) as usize;
-    let mut buffer = AudioBuffer::new(num_samples, ChannelLayout::Stereo);
+    let mut buffer = AudioBuffer::with_sample_rate(num_samples, ChannelLayout::Stereo, sample_rate);
 
     let angular_freq_l = 2.0 * std::f32::consts::PI * freq_left / sample_rate as f32;
     let angular_freq_r = 2.0 * std::f32::consts::PI * freq_right / sample_rate as f32;
@@ -721,6 +1132,157 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_import_and_export_at_project_rate() {
+        let dir = tempdir().unwrap();
+        let cd_path = dir.path().join("cd.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 44100),
+            &cd_path,
+            ExportFormat::new(44100, 24),
+        )
+        .unwrap();
+
+        let imported = import_audio_at(&cd_path, 96000).unwrap();
+        assert_eq!(imported.sample_rate, 96000);
+        assert!((imported.num_samples() as i64 - 48000).abs() < 10);
+
+        // A buffer already at the export rate is written without resampling
+        let hires_path = dir.path().join("hires.wav");
+        export_audio(&imported, &hires_path, ExportFormat::new(96000, 32)).unwrap();
+        assert_eq!(
+            WavReader::open(&hires_path).unwrap().spec().sample_rate,
+            96000
+        );
+        let reloaded = import_audio_at(&hires_path, 96000).unwrap();
+        assert_eq!(reloaded.samples, imported.samples);
+    }
+
+    fn cue_metadata() -> WavMetadata {
+        WavMetadata {
+            markers: vec![
+                crate::engine::Marker {
+                    name: "Intro".to_string(),
+                    sample: 0,
+                },
+                crate::engine::Marker {
+                    name: "Drop".to_string(),
+                    sample: 24000,
+                },
+            ],
+            loop_region: Some(crate::engine::LoopRegion {
+                start_sample: 4800,
+                end_sample: 19200,
+                enabled: true,
+            }),
+            bpm: Some(128.0),
+        }
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let dir = tempdir().unwrap();
+        let original = generate_test_tone(440.0, 1.0, INTERNAL_SAMPLE_RATE);
+        let metadata = cue_metadata();
+
+        // 44.1 kHz 24-bit mono has an odd-sized data chunk
+        for (name, format) in [
+            ("same_rate.wav", ExportFormat::new(48000, 32)),
+            ("resampled.wav", ExportFormat::new(44100, 24)),
+        ] {
+            let path = dir.path().join(name);
+            export_audio_with_metadata(&original, &path, format, &metadata).unwrap();
+
+            let imported = import_audio_with_metadata(&path).unwrap();
+            assert_eq!(imported.buffer.len(), import_audio(&path).unwrap().len());
+            assert_eq!(imported.metadata.bpm, Some(128.0));
+            let names: Vec<&str> = imported
+                .metadata
+                .markers
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect();
+            assert_eq!(names, vec!["Intro", "Drop"]);
+            // Resampling may move positions by a sample
+            let drop = imported.metadata.markers[1].sample as i64;
+            assert!((drop - 24000).abs() <= 1, "{}: {}", name, drop);
+            let region = imported.metadata.loop_region.unwrap();
+            assert!((region.end_sample as i64 - 19200).abs() <= 1, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_import_without_metadata() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("plain.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, INTERNAL_SAMPLE_RATE),
+            &path,
+            ExportFormat::new(48000, 16),
+        )
+        .unwrap();
+
+        let imported = import_audio_with_metadata(&path).unwrap();
+        assert!(imported.metadata.is_empty());
+        assert!(imported.metadata.loop_region.is_none());
+    }
+
+    #[test]
+    fn test_cues_become_transport_markers() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cues.wav");
+        export_audio_with_metadata(
+            &generate_test_tone(440.0, 1.0, INTERNAL_SAMPLE_RATE),
+            &path,
+            ExportFormat::new(48000, 16),
+            &cue_metadata(),
+        )
+        .unwrap();
+
+        let imported = import_audio_with_metadata(&path).unwrap();
+        let mut transport = crate::engine::TransportManager::new(INTERNAL_SAMPLE_RATE);
+        transport.set_audio_length(imported.buffer.len() as u64);
+        for marker in &imported.metadata.markers {
+            transport.add_marker(&marker.name, marker.sample).unwrap();
+        }
+        transport.seek_to_marker("drop").unwrap();
+        assert_eq!(transport.get_playhead_position_samples(), 24000);
+    }
+
+    #[test]
+    fn test_malformed_metadata_is_skipped() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("broken.wav");
+        export_audio_with_metadata(
+            &generate_test_tone(440.0, 0.5, INTERNAL_SAMPLE_RATE),
+            &path,
+            ExportFormat::new(48000, 16),
+            &WavMetadata {
+                bpm: Some(90.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // A cue chunk claiming 1000 points, and one cut off by the file end
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.extend(b"cue ");
+        bytes.extend(8u32.to_le_bytes());
+        bytes.extend(1000u32.to_le_bytes());
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(b"smpl");
+        bytes.extend(400u32.to_le_bytes());
+        bytes.extend([0u8; 16]);
+        let riff_size = (bytes.len() - 8) as u32;
+        bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        let imported = import_audio_with_metadata(&path).unwrap();
+        assert!(imported.metadata.markers.is_empty());
+        assert!(imported.metadata.loop_region.is_none());
+        assert_eq!(imported.metadata.bpm, Some(90.0));
+    }
+
     #[test]
     fn test_import_nonexistent_file() {
         let result = import_audio(Path::new("/nonexistent/path/audio.wav"));
@@ -739,5 +1301,230 @@ mod tests {
         let format = ExportFormat::default();
         assert_eq!(format.sample_rate, 48000);
         assert_eq!(format.bit_depth, 24);
+        assert_eq!(format.dither, DitherType::None);
+        assert!(format.seamless_loop.is_none());
+    }
+
+    /// Export a -90 dBFS 1 kHz sine to 16-bit and measure the energy at its
+    /// 2nd-10th harmonics
+    fn harmonic_energy_at_16_bit(dither: DitherType) -> f64 {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("quiet.wav");
+
+        let mut tone = generate_test_tone(1000.0, 1.0, INTERNAL_SAMPLE_RATE);
+        let amplitude = 10f32.powf(-90.0 / 20.0);
+        tone.samples[0].iter_mut().for_each(|s| *s *= amplitude);
+        export_audio(
+            &tone,
+            &path,
+            ExportFormat::new(INTERNAL_SAMPLE_RATE, 16).with_dither(dither),
+        )
+        .unwrap();
+
+        // import_audio would reject a signal this quiet, so read it raw
+        let samples: Vec<f64> = WavReader::open(&path)
+            .unwrap()
+            .samples::<i16>()
+            .map(|s| s.unwrap() as f64)
+            .collect();
+
+        (2..=10)
+            .map(|h| {
+                let omega =
+                    2.0 * std::f64::consts::PI * 1000.0 * h as f64 / INTERNAL_SAMPLE_RATE as f64;
+                let (re, im) = samples
+                    .iter()
+                    .enumerate()
+                    .fold((0.0, 0.0), |(re, im), (n, &x)| {
+                        (
+                            re + x * (omega * n as f64).cos(),
+                            im - x * (omega * n as f64).sin(),
+                        )
+                    });
+                re * re + im * im
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_dither_reduces_harmonic_distortion() {
+        let plain = harmonic_energy_at_16_bit(DitherType::None);
+        let tpdf = harmonic_energy_at_16_bit(DitherType::Tpdf);
+        let shaped = harmonic_energy_at_16_bit(DitherType::NoiseShaped);
+
+        assert!(tpdf * 100.0 < plain, "tpdf {} vs plain {}", tpdf, plain);
+        assert!(
+            shaped * 10.0 < plain,
+            "shaped {} vs plain {}",
+            shaped,
+            plain
+        );
+    }
+
+    #[test]
+    fn test_dither_ignored_for_float_export() {
+        let dir = tempdir().unwrap();
+        let plain = dir.path().join("plain.wav");
+        let dithered = dir.path().join("dithered.wav");
+        let tone = generate_test_tone(440.0, 0.5, INTERNAL_SAMPLE_RATE);
+
+        export_audio(&tone, &plain, ExportFormat::new(INTERNAL_SAMPLE_RATE, 32)).unwrap();
+        export_audio(
+            &tone,
+            &dithered,
+            ExportFormat::new(INTERNAL_SAMPLE_RATE, 32).with_dither(DitherType::Tpdf),
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read(plain).unwrap(),
+            std::fs::read(dithered).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_format_bit_depths() {
+        assert_eq!(AudioFileFormat::parse("WAV").unwrap(), AudioFileFormat::Wav);
+        assert!(AudioFileFormat::parse("mp3").is_err());
+
+        for bits in [16, 24, 32] {
+            assert!(AudioFileFormat::Wav.validate_bit_depth(bits).is_ok());
+        }
+        assert!(AudioFileFormat::Wav.validate_bit_depth(8).is_err());
+        assert!(AudioFileFormat::Flac.validate_bit_depth(24).is_ok());
+
+        let err = AudioFileFormat::Flac.validate_bit_depth(32).unwrap_err();
+        assert!(err.to_string().contains("32-bit FLAC"), "{}", err);
+    }
+
+    #[test]
+    fn test_export_flac_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tone.flac");
+        let buffer = generate_stereo_test_tone(440.0, 660.0, 0.1, 48000);
+        export_audio_as(
+            &buffer,
+            &path,
+            AudioFileFormat::Flac,
+            ExportFormat::new(48000, 24),
+        )
+        .unwrap();
+
+        let mut reader = claxon::FlacReader::open(&path).unwrap();
+        assert_eq!(reader.streaminfo().channels, 2);
+        assert_eq!(reader.streaminfo().bits_per_sample, 24);
+        let decoded: Vec<i32> = reader.samples().map(|s| s.unwrap()).collect();
+        assert_eq!(decoded.len(), buffer.len() * 2);
+
+        // Lossless: the decoded samples are exactly the quantized input
+        let expected = quantize_samples(
+            interleave(&buffer.samples),
+            &ExportFormat::new(48000, 24),
+            2,
+        );
+        assert_eq!(decoded, expected);
+    }
+
+    /// Export one loop of `buffer` and play it twice, returning the
+    /// samples and the index where the second pass starts
+    fn play_loop_twice(buffer: &AudioBuffer, settings: SeamlessLoop) -> (AudioBuffer, usize) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("loop.wav");
+        let format = ExportFormat::new(INTERNAL_SAMPLE_RATE, 32).with_seamless_loop(settings);
+        export_audio(buffer, &path, format).unwrap();
+
+        let mut looped = import_audio(&path).unwrap();
+        let seam = looped.len();
+        assert_eq!(seam as u64, settings.region.len());
+        let once = looped.samples[0].clone();
+        looped.samples[0].extend(once);
+        (looped, seam)
+    }
+
+    #[test]
+    fn test_seamless_loop_has_no_click_at_seam() {
+        let tone = generate_test_tone(440.0, 1.0, INTERNAL_SAMPLE_RATE);
+        // 165.9 cycles, so the raw loop jumps in phase when it wraps
+        let region = LoopRegion {
+            start_sample: 12000,
+            end_sample: 30100,
+            enabled: true,
+        };
+
+        let naive = SeamlessLoop::new(region).with_crossfade_ms(0.0).trimmed();
+        let (looped, seam) = play_loop_twice(&tone, naive);
+        assert!(looped.detect_clicks(1.0).contains(&seam));
+
+        let (looped, _) = play_loop_twice(&tone, SeamlessLoop::new(region).trimmed());
+        assert!(looped.detect_clicks(1.0).is_empty());
+
+        // A loop from the very start fades in from the audio after its end
+        let from_start = LoopRegion {
+            start_sample: 0,
+            end_sample: 18100,
+            enabled: true,
+        };
+        let (looped, _) = play_loop_twice(&tone, SeamlessLoop::new(from_start).trimmed());
+        assert!(looped.detect_clicks(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_seamless_loop_crossfade_keeps_level() {
+        // Seeded white noise, so the two sides of the crossfade are
+        // uncorrelated and a linear fade would dip by about 1.8 dB
+        let mut noise = AudioBuffer::new(96000, ChannelLayout::Mono);
+        let mut state = 11u32;
+        for sample in noise.samples[0].iter_mut() {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            *sample = 0.5 * ((state >> 8) as f32 / (1u32 << 23) as f32 - 1.0);
+        }
+        let region = LoopRegion {
+            start_sample: 24000,
+            end_sample: 72000,
+            enabled: true,
+        };
+        let settings = SeamlessLoop::new(region).with_crossfade_ms(100.0);
+        let output = seamless_loop(&noise, &settings).unwrap();
+        assert_eq!(output.len(), noise.len());
+
+        let rms = |samples: &[f32]| {
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        };
+        let faded = rms(&output.samples[0][72000 - 4800..72000]);
+        let original = rms(&noise.samples[0][72000 - 4800..72000]);
+        let change_db = 20.0 * (faded / original).log10();
+        assert!(change_db.abs() < 0.5, "{} dB", change_db);
+
+        // Outside the crossfade nothing changes
+        assert_eq!(
+            output.samples[0][..72000 - 4800],
+            noise.samples[0][..72000 - 4800]
+        );
+        assert_eq!(output.samples[0][72000..], noise.samples[0][72000..]);
+    }
+
+    #[test]
+    fn test_seamless_loop_rejects_bad_region() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bad.wav");
+        let tone = generate_test_tone(440.0, 0.5, INTERNAL_SAMPLE_RATE);
+        let empty = LoopRegion {
+            start_sample: 1000,
+            end_sample: 1000,
+            enabled: true,
+        };
+        let format = ExportFormat::default().with_seamless_loop(SeamlessLoop::new(empty));
+        assert!(matches!(
+            export_audio(&tone, &path, format),
+            Err(NuevaError::InvalidParameter { .. })
+        ));
+        assert!(!path.exists());
+
+        let past_end = LoopRegion {
+            start_sample: 1000,
+            end_sample: 48000,
+            enabled: true,
+        };
+        assert!(seamless_loop(&tone, &SeamlessLoop::new(past_end)).is_err());
     }
 }
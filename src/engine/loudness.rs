@@ -0,0 +1,321 @@
+//! Loudness Measurement
+//!
+//! Integrated loudness per ITU-R BS.1770-4: each channel is K-weighted
+//! (a high shelf followed by a high-pass), mean square power is taken
+//! over 400 ms blocks with 75% overlap, and blocks are gated at -70 LUFS
+//! and then 10 LU below the level of the surviving blocks.
+//!
+//! Loudness range (LRA) follows EBU Tech 3342: 3 s short-term loudness
+//! every 100 ms, gated at -70 LUFS and then 20 LU below the level of the
+//! surviving windows; the range is the spread between their 10th and
+//! 95th percentiles.
+
+use std::f64::consts::PI;
+
+use super::buffer::AudioBuffer;
+
+/// Blocks quieter than this never count towards integrated loudness
+pub const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Relative gate, in LU below the absolute-gated loudness
+pub const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Gating block length in seconds
+const BLOCK_SECS: f64 = 0.4;
+
+/// Hop between gating blocks (75% overlap)
+const BLOCK_STEP_SECS: f64 = 0.1;
+
+/// Short-term loudness window for loudness range, in seconds
+const SHORT_TERM_SECS: f64 = 3.0;
+
+/// Relative gate for loudness range, in LU below the absolute-gated level
+pub const LRA_RELATIVE_GATE_LU: f64 = -20.0;
+
+/// Percentiles of the gated short-term loudness that bound the range
+const LRA_LOW_PERCENTILE: f64 = 0.10;
+const LRA_HIGH_PERCENTILE: f64 = 0.95;
+
+/// Offset in the BS.1770 loudness formula
+const LOUDNESS_OFFSET: f64 = -0.691;
+
+/// Biquad section (transposed direct form II)
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+/// The two K-weighting stages for a sample rate
+///
+/// Coefficients are derived from the filters' analog prototypes so any
+/// sample rate works; at 48 kHz they match the table in the standard.
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    // Stage 1: high shelf modelling the acoustic effect of the head
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (PI * 1_681.974_450_955_533 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let shelf = Biquad::new(
+        [
+            vh + vb * k / q + k * k,
+            2.0 * (k * k - vh),
+            vh - vb * k / q + k * k,
+        ],
+        [
+            1.0 + k / q + k * k,
+            2.0 * (k * k - 1.0),
+            1.0 - k / q + k * k,
+        ],
+    );
+
+    // Stage 2: high-pass (the "RLB" weighting)
+    let q = 0.500_327_037_325_395_3;
+    let k = (PI * 38.135_470_876_139_82 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        [a0, -2.0 * a0, a0],
+        [a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    );
+
+    [shelf, high_pass]
+}
+
+/// Loudness of a mean-square power value
+fn power_to_lufs(power: f64) -> f64 {
+    LOUDNESS_OFFSET + 10.0 * power.log10()
+}
+
+/// Mean-square power of each gating block, summed across channels
+fn block_powers(buffer: &AudioBuffer) -> Vec<f64> {
+    let sample_rate = buffer.sample_rate as f64;
+    if buffer.is_empty() || sample_rate <= 0.0 {
+        return Vec::new();
+    }
+
+    let block_len = ((BLOCK_SECS * sample_rate) as usize).max(1);
+    let step = ((BLOCK_STEP_SECS * sample_rate) as usize).max(1);
+    window_powers(buffer, block_len, step)
+}
+
+/// Ungated loudness of each `window`-sample block, one every `hop` samples
+///
+/// At a 400 ms window this is the standard's momentary loudness.
+pub(crate) fn loudness_series(buffer: &AudioBuffer, window: usize, hop: usize) -> Vec<f32> {
+    window_powers(buffer, window, hop)
+        .into_iter()
+        .map(|power| power_to_lufs(power) as f32)
+        .collect()
+}
+
+/// K-weighted mean-square power of each window, summed across channels
+///
+/// Windows start every `hop` samples and stop at the last one that fits;
+/// a buffer shorter than one window is measured as a single window.
+fn window_powers(buffer: &AudioBuffer, window: usize, hop: usize) -> Vec<f64> {
+    let sample_rate = buffer.sample_rate as f64;
+    let len = buffer.len();
+    if len == 0 || sample_rate <= 0.0 {
+        return Vec::new();
+    }
+
+    let block_len = window.clamp(1, len);
+    let step = hop.max(1);
+    let num_blocks = (len - block_len) / step + 1;
+
+    let mut powers = vec![0.0; num_blocks];
+    for channel in &buffer.samples {
+        let [mut shelf, mut high_pass] = k_weighting(sample_rate);
+        let squared: Vec<f64> = channel
+            .iter()
+            .map(|&x| high_pass.process(shelf.process(x as f64)).powi(2))
+            .collect();
+
+        // Running sums make each block O(1)
+        let mut prefix = Vec::with_capacity(squared.len() + 1);
+        prefix.push(0.0);
+        for value in &squared {
+            prefix.push(prefix.last().unwrap() + value);
+        }
+
+        for (i, power) in powers.iter_mut().enumerate() {
+            let start = i * step;
+            *power += (prefix[start + block_len] - prefix[start]) / block_len as f64;
+        }
+    }
+    powers
+}
+
+/// Integrated loudness in LUFS
+///
+/// Returns negative infinity for silence (or anything gated out entirely).
+pub fn integrated_loudness(buffer: &AudioBuffer) -> f32 {
+    let powers = block_powers(buffer);
+
+    let gated_mean = |threshold: f64| {
+        let kept: Vec<f64> = powers
+            .iter()
+            .copied()
+            .filter(|&p| power_to_lufs(p) > threshold)
+            .collect();
+        match kept.len() {
+            0 => None,
+            n => Some(kept.iter().sum::<f64>() / n as f64),
+        }
+    };
+
+    let Some(absolute) = gated_mean(ABSOLUTE_GATE_LUFS) else {
+        return f32::NEG_INFINITY;
+    };
+    let relative_threshold = power_to_lufs(absolute) + RELATIVE_GATE_LU;
+    match gated_mean(relative_threshold) {
+        Some(power) => power_to_lufs(power) as f32,
+        None => f32::NEG_INFINITY,
+    }
+}
+
+/// Loudness range (LRA) in LU
+///
+/// Returns 0.0 for silence. Audio shorter than the 3 s short-term window
+/// is a single window, so it has no range either.
+pub fn loudness_range(buffer: &AudioBuffer) -> f32 {
+    let sample_rate = buffer.sample_rate as f64;
+    let window = ((SHORT_TERM_SECS * sample_rate) as usize).max(1);
+    let hop = ((BLOCK_STEP_SECS * sample_rate) as usize).max(1);
+    let powers: Vec<f64> = window_powers(buffer, window, hop)
+        .into_iter()
+        .filter(|&p| power_to_lufs(p) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if powers.is_empty() {
+        return 0.0;
+    }
+
+    let mean = powers.iter().sum::<f64>() / powers.len() as f64;
+    let relative_threshold = power_to_lufs(mean) + LRA_RELATIVE_GATE_LU;
+    let mut levels: Vec<f64> = powers
+        .into_iter()
+        .map(power_to_lufs)
+        .filter(|&lufs| lufs > relative_threshold)
+        .collect();
+    levels.sort_by(f64::total_cmp);
+
+    let percentile = |p: f64| levels[((levels.len() - 1) as f64 * p).round() as usize];
+    (percentile(LRA_HIGH_PERCENTILE) - percentile(LRA_LOW_PERCENTILE)) as f32
+}
+
+/// Apply gain so the buffer's integrated loudness hits `target_lufs`
+///
+/// Returns the gain applied in dB, or `None` (leaving the buffer alone)
+/// if the buffer is silent. No limiting is applied, so loud targets can
+/// push peaks past full scale.
+pub fn normalize_loudness(buffer: &mut AudioBuffer, target_lufs: f32) -> Option<f32> {
+    let measured = integrated_loudness(buffer);
+    if !measured.is_finite() {
+        return None;
+    }
+    let gain_db = target_lufs - measured;
+    buffer.apply_gain(gain_db);
+    Some(gain_db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::io::generate_test_tone;
+
+    #[test]
+    fn test_reference_tone() {
+        // BS.1770: a 0 dBFS 997 Hz sine in one channel reads -3.01 LUFS
+        let tone = generate_test_tone(997.0, 2.0, 48000);
+        let lufs = integrated_loudness(&tone);
+        assert!((lufs + 3.01).abs() < 0.05, "measured {} LUFS", lufs);
+    }
+
+    #[test]
+    fn test_other_sample_rates() {
+        let mut tone = generate_test_tone(997.0, 2.0, 44100);
+        tone.sample_rate = 44100;
+        let lufs = integrated_loudness(&tone);
+        assert!((lufs + 3.01).abs() < 0.05, "measured {} LUFS", lufs);
+    }
+
+    #[test]
+    fn test_silence_is_negative_infinity() {
+        let mut silence = generate_test_tone(997.0, 1.0, 48000);
+        silence.apply_gain(-200.0);
+        assert_eq!(integrated_loudness(&silence), f32::NEG_INFINITY);
+        assert_eq!(normalize_loudness(&mut silence, -14.0), None);
+    }
+
+    #[test]
+    fn test_silence_is_gated_out() {
+        let tone = generate_test_tone(997.0, 2.0, 48000);
+        let mut padded = tone.clone();
+        padded.samples[0].extend(vec![0.0; 48000 * 4]);
+
+        // Ungated, two thirds silence would read ~4.8 LU lower; only the
+        // blocks straddling the edge of the tone may still count
+        let difference = integrated_loudness(&padded) - integrated_loudness(&tone);
+        assert!(
+            difference.abs() < 0.5,
+            "padding changed loudness by {}",
+            difference
+        );
+    }
+
+    #[test]
+    fn test_loudness_range() {
+        // Ten seconds at -30 dBFS, then ten at -10: a 20 LU range
+        let mut tone = generate_test_tone(997.0, 20.0, 48000);
+        for (i, sample) in tone.samples[0].iter_mut().enumerate() {
+            *sample *= if i < 480000 { 0.0316 } else { 0.316 };
+        }
+        let range = loudness_range(&tone);
+        assert!((range - 20.0).abs() < 0.5, "range {} LU", range);
+
+        let steady = generate_test_tone(997.0, 10.0, 48000);
+        assert!(loudness_range(&steady) < 0.1);
+
+        let mut silence = steady.clone();
+        silence.apply_gain(-200.0);
+        assert_eq!(loudness_range(&silence), 0.0);
+    }
+
+    #[test]
+    fn test_normalize_hits_target() {
+        let mut tone = generate_test_tone(440.0, 2.0, 48000);
+        tone.apply_gain(-20.0);
+
+        let gain = normalize_loudness(&mut tone, -14.0).unwrap();
+        assert!(gain > 0.0);
+        assert!((integrated_loudness(&tone) + 14.0).abs() < 0.01);
+    }
+}
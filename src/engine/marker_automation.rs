@@ -0,0 +1,374 @@
+//! Marker Automation
+//!
+//! Effect parameter changes tied to timeline markers ("open the filter at
+//! the drop"). A [`MarkerSchedule`] is the stored form: which parameter
+//! takes which value at which marker, by marker name, so it survives
+//! markers being moved. For playback it is resolved against the current
+//! markers and a live effect chain into a [`MarkerPlayback`], which splits
+//! each block at marker positions so changes land on the marker's sample.
+//!
+//! The state at any position is the chain as it was before playback, with
+//! each scheduled parameter set to its value at the latest marker at or
+//! before that position. Seeking recomputes that state, so the result
+//! doesn't depend on how the playhead got there.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::transport::Marker;
+use crate::dsp::{AudioBuffer, EffectChain, ProcessResult};
+use crate::error::{NuevaError, Result};
+
+/// One parameter change at a marker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkerChange {
+    /// Name of the marker (matched ignoring case)
+    pub marker: String,
+    /// ID of the effect in the chain
+    pub effect_id: String,
+    /// Parameter name, as for `Effect::set_param`
+    pub param: String,
+    /// Value the parameter takes from the marker on
+    pub value: Value,
+}
+
+/// Parameter changes scheduled at markers, saved with the project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarkerSchedule {
+    /// In the order they were scheduled
+    changes: Vec<MarkerChange>,
+}
+
+impl MarkerSchedule {
+    /// An empty schedule
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `param` of `effect_id` to `value` at `marker`
+    ///
+    /// Replaces any value already scheduled for that parameter at that
+    /// marker. Values are checked when playback is resolved.
+    pub fn schedule(&mut self, marker: &str, effect_id: &str, param: &str, value: Value) {
+        let marker = marker.trim();
+        match self.changes.iter_mut().find(|c| {
+            c.marker.eq_ignore_ascii_case(marker) && c.effect_id == effect_id && c.param == param
+        }) {
+            Some(change) => change.value = value,
+            None => self.changes.push(MarkerChange {
+                marker: marker.to_string(),
+                effect_id: effect_id.to_string(),
+                param: param.to_string(),
+                value,
+            }),
+        }
+    }
+
+    /// All scheduled changes, in the order they were scheduled
+    pub fn changes(&self) -> &[MarkerChange] {
+        &self.changes
+    }
+
+    /// Changes scheduled at `marker`
+    pub fn changes_at<'a>(&'a self, marker: &'a str) -> impl Iterator<Item = &'a MarkerChange> {
+        self.changes
+            .iter()
+            .filter(move |c| c.marker.eq_ignore_ascii_case(marker.trim()))
+    }
+
+    /// Drop the changes scheduled at `marker`, returning how many there were
+    pub fn remove_marker(&mut self, marker: &str) -> usize {
+        let before = self.changes.len();
+        self.changes
+            .retain(|c| !c.marker.eq_ignore_ascii_case(marker.trim()));
+        before - self.changes.len()
+    }
+
+    /// Drop changes whose marker isn't in `markers`
+    pub fn retain_markers(&mut self, markers: &[Marker]) {
+        self.changes.retain(|c| {
+            markers
+                .iter()
+                .any(|m| m.name.eq_ignore_ascii_case(&c.marker))
+        });
+    }
+
+    /// Drop changes to effects `keep` rejects
+    pub fn retain_effects(&mut self, keep: impl Fn(&str) -> bool) {
+        self.changes.retain(|c| keep(&c.effect_id));
+    }
+
+    /// Whether nothing is scheduled
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// A change resolved to its sample position
+#[derive(Debug, Clone)]
+struct ResolvedChange {
+    sample: u64,
+    /// Index into the playback's parameters
+    param: usize,
+    value: Value,
+}
+
+/// A schedule resolved against markers and a chain, ready for playback
+///
+/// Build a new one whenever the markers or the schedule change.
+#[derive(Debug, Clone)]
+pub struct MarkerPlayback {
+    /// Each scheduled `(effect_id, param)` with its value before any marker
+    params: Vec<(String, String, Value)>,
+    /// Sorted by sample; changes at the same sample keep marker order
+    changes: Vec<ResolvedChange>,
+}
+
+impl MarkerPlayback {
+    /// Resolve `schedule` against `markers`, taking the chain's current
+    /// parameter values as the state before the first marker
+    ///
+    /// Changes at markers that no longer exist are skipped.
+    ///
+    /// # Errors
+    /// Returns `InvalidParameter` if a change names an effect that isn't in
+    /// the chain or a parameter that effect doesn't have.
+    pub fn new(schedule: &MarkerSchedule, markers: &[Marker], chain: &EffectChain) -> Result<Self> {
+        let mut params: Vec<(String, String, Value)> = Vec::new();
+        let mut changes = Vec::new();
+        for marker in markers {
+            for change in schedule.changes_at(&marker.name) {
+                let param = match params
+                    .iter()
+                    .position(|(id, name, _)| *id == change.effect_id && *name == change.param)
+                {
+                    Some(index) => index,
+                    None => {
+                        let base = chain
+                            .get(&change.effect_id)
+                            .and_then(|effect| effect.get_param(&change.param))
+                            .ok_or_else(|| NuevaError::InvalidParameter {
+                                param: format!("{}.{}", change.effect_id, change.param),
+                                value: change.value.to_string(),
+                                expected: "a parameter of an effect in the chain".to_string(),
+                            })?;
+                        params.push((change.effect_id.clone(), change.param.clone(), base));
+                        params.len() - 1
+                    }
+                };
+                changes.push(ResolvedChange {
+                    sample: marker.sample,
+                    param,
+                    value: change.value.clone(),
+                });
+            }
+        }
+        changes.sort_by_key(|c| c.sample);
+        Ok(Self { params, changes })
+    }
+
+    /// Put the chain in the state it has at `sample`
+    ///
+    /// Call after any jump of the playhead (seeks and loop wraps) and
+    /// before the first block.
+    pub fn seek(&self, chain: &mut EffectChain, sample: u64) -> Result<()> {
+        let mut values: Vec<&Value> = self.params.iter().map(|(_, _, base)| base).collect();
+        for change in self.changes.iter().take_while(|c| c.sample <= sample) {
+            values[change.param] = &change.value;
+        }
+        self.apply(chain, (0..self.params.len()).map(|i| (i, values[i])))
+    }
+
+    /// Process a block starting at `start_sample` through `chain`
+    ///
+    /// The block is split at each marker inside it, and that marker's
+    /// changes are applied before its first sample. Assumes the chain is
+    /// already in the state for `start_sample` (see [`Self::seek`]);
+    /// changes at `start_sample` itself are applied again, which is
+    /// harmless. Returns the results of every pass through the chain.
+    pub fn process_block(
+        &self,
+        chain: &mut EffectChain,
+        buffer: &mut AudioBuffer,
+        start_sample: u64,
+    ) -> Result<Vec<ProcessResult>> {
+        let frames = buffer.num_samples();
+        let end_sample = start_sample + frames as u64;
+        let first = self.changes.partition_point(|c| c.sample < start_sample);
+        let last = self.changes.partition_point(|c| c.sample < end_sample);
+        let inside = &self.changes[first..last];
+        if inside.is_empty() {
+            return Ok(chain.process(buffer));
+        }
+
+        let channels = buffer.num_channels();
+        let mut results = Vec::new();
+        let mut offset = 0;
+        let mut index = 0;
+        while offset < frames {
+            // Apply everything due at this sample, then run to the next change
+            while index < inside.len() && (inside[index].sample - start_sample) as usize == offset {
+                let due = inside[index..]
+                    .iter()
+                    .take_while(|c| c.sample == inside[index].sample);
+                let count = due.clone().count();
+                self.apply(chain, due.map(|c| (c.param, &c.value)))?;
+                index += count;
+            }
+            let next = inside
+                .get(index)
+                .map_or(frames, |c| (c.sample - start_sample) as usize);
+
+            let range = offset * channels..next * channels;
+            let mut segment = AudioBuffer::from_interleaved(
+                buffer.samples()[range.clone()].to_vec(),
+                channels,
+                buffer.sample_rate(),
+            )?;
+            results.extend(chain.process(&mut segment));
+            buffer.samples_mut()[range].copy_from_slice(segment.samples());
+            offset = next;
+        }
+        Ok(results)
+    }
+
+    /// Set parameters by index, one batch per effect
+    fn apply<'a>(
+        &self,
+        chain: &mut EffectChain,
+        values: impl Iterator<Item = (usize, &'a Value)>,
+    ) -> Result<()> {
+        let mut batches: Vec<(&str, serde_json::Map<String, Value>)> = Vec::new();
+        for (param, value) in values {
+            let (effect_id, name, _) = &self.params[param];
+            let batch = match batches.iter().position(|(id, _)| id == effect_id) {
+                Some(index) => &mut batches[index].1,
+                None => {
+                    batches.push((effect_id, serde_json::Map::new()));
+                    &mut batches.last_mut().unwrap().1
+                }
+            };
+            batch.insert(name.clone(), value.clone());
+        }
+
+        for (effect_id, batch) in batches {
+            let effect = chain
+                .get_mut(effect_id)
+                .ok_or_else(|| NuevaError::InvalidParameter {
+                    param: "effect_id".to_string(),
+                    value: effect_id.to_string(),
+                    expected: "an effect in the chain".to_string(),
+                })?;
+            effect.set_params_atomic(&Value::Object(batch))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::{Effect, GainEffect};
+    use serde_json::json;
+
+    fn gain_chain() -> EffectChain {
+        let mut gain = GainEffect::new();
+        gain.set_id("gain".to_string());
+        let mut chain = EffectChain::new();
+        chain.add(Box::new(gain));
+        chain.prepare(48000.0, 512);
+        chain
+    }
+
+    fn markers() -> Vec<Marker> {
+        vec![
+            Marker {
+                name: "Verse".to_string(),
+                sample: 0,
+            },
+            Marker {
+                name: "Drop".to_string(),
+                sample: 1000,
+            },
+        ]
+    }
+
+    fn schedule() -> MarkerSchedule {
+        let mut schedule = MarkerSchedule::new();
+        schedule.schedule("drop", "gain", "gain_db", json!(-20.0));
+        schedule
+    }
+
+    fn ones(frames: usize) -> AudioBuffer {
+        AudioBuffer::from_interleaved(vec![1.0; frames * 2], 2, 48000.0).unwrap()
+    }
+
+    #[test]
+    fn test_change_lands_on_marker_sample() {
+        let mut chain = gain_chain();
+        let playback = MarkerPlayback::new(&schedule(), &markers(), &chain).unwrap();
+        playback.seek(&mut chain, 0).unwrap();
+
+        // Blocks of 384 put the marker in the middle of the third block
+        let mut output = Vec::new();
+        for block in 0..6 {
+            let mut buffer = ones(384);
+            playback
+                .process_block(&mut chain, &mut buffer, block * 384)
+                .unwrap();
+            output.extend((0..384).map(|i| buffer.get(i, 1).unwrap()));
+        }
+        assert!(output[..1000].iter().all(|&s| s == 1.0));
+        assert!(output[1000..].iter().all(|&s| (s - 0.1).abs() < 1e-4));
+        assert_eq!(
+            chain.get("gain").unwrap().get_param("gain_db"),
+            Some(json!(-20.0))
+        );
+    }
+
+    #[test]
+    fn test_seek_gives_same_state_either_way() {
+        let mut chain = gain_chain();
+        let playback = MarkerPlayback::new(&schedule(), &markers(), &chain).unwrap();
+
+        // Jumping past the marker applies it without playing through it
+        playback.seek(&mut chain, 5000).unwrap();
+        let gain = |chain: &EffectChain| chain.get("gain").unwrap().get_param("gain_db");
+        assert_eq!(gain(&chain), Some(json!(-20.0)));
+
+        // Back before it, the pre-marker value returns
+        playback.seek(&mut chain, 999).unwrap();
+        assert_eq!(gain(&chain), Some(json!(0.0)));
+        playback.seek(&mut chain, 1000).unwrap();
+        assert_eq!(gain(&chain), Some(json!(-20.0)));
+    }
+
+    #[test]
+    fn test_schedule_edits_and_serializes() {
+        let mut schedule = schedule();
+        schedule.schedule("Drop", "gain", "gain_db", json!(-12.0));
+        schedule.schedule("Verse", "gain", "gain_db", json!(-3.0));
+        assert_eq!(schedule.changes().len(), 2);
+        assert_eq!(
+            schedule.changes_at("DROP").next().unwrap().value,
+            json!(-12.0)
+        );
+
+        let json = serde_json::to_string(&schedule).unwrap();
+        let restored: MarkerSchedule = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, schedule);
+
+        // Removing a marker takes its changes with it
+        let mut remaining = markers();
+        remaining.retain(|m| m.name != "Drop");
+        schedule.retain_markers(&remaining);
+        assert_eq!(schedule.changes().len(), 1);
+        assert_eq!(schedule.remove_marker("verse"), 1);
+        assert!(schedule.is_empty());
+
+        // Effects and parameters are checked when playback is resolved
+        let mut bad = MarkerSchedule::new();
+        bad.schedule("Drop", "no-such-effect", "gain_db", json!(0.0));
+        assert!(MarkerPlayback::new(&bad, &markers(), &gain_chain()).is_err());
+    }
+}
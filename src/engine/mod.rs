@@ -2,15 +2,39 @@
 //!
 //! Core audio processing engine including:
 //! - Audio buffer management
+//! - Gain automation
 //! - Transport state machine
 //! - File I/O operations
+//! - WAV metadata (cue points, loops, tempo)
+//! - Loudness measurement
+//! - A/B comparison against a reference track
+//! - Perceptual fingerprints for change detection
+//! - Effect parameter changes at markers
 
+pub mod automation;
 pub mod buffer;
+pub mod compare;
+pub mod fingerprint;
 pub mod io;
+pub mod loudness;
+pub mod marker_automation;
 pub mod transport;
+pub mod wav_metadata;
 
-pub use buffer::{AudioBuffer, AudioValidation, ChannelLayout};
+pub use automation::{Automation, AutomationCurve, Breakpoint};
+pub use buffer::{
+    validate_sample_rate, AudioBuffer, AudioValidation, ChannelLayout, LevelMetric,
+    SUPPORTED_SAMPLE_RATES,
+};
+pub use compare::{compare_buffers, ComparisonReport, LoudnessVerdict, ToneVerdict};
+pub use fingerprint::{fingerprint, fingerprint_distance, FINGERPRINT_CHANGE_THRESHOLD};
 pub use io::{
-    export_audio, generate_stereo_test_tone, generate_test_tone, import_audio, ExportFormat,
+    export_audio, export_audio_as, export_audio_with_metadata, export_flac,
+    generate_stereo_test_tone, generate_test_tone, import_audio, import_audio_at,
+    import_audio_native, import_audio_with_metadata, import_audio_with_metadata_at, seamless_loop,
+    AudioFileFormat, DitherType, ExportFormat, ImportResult, SeamlessLoop,
 };
-pub use transport::{TransportManager, TransportState};
+pub use loudness::{integrated_loudness, loudness_range, normalize_loudness};
+pub use marker_automation::{MarkerChange, MarkerPlayback, MarkerSchedule};
+pub use transport::{LoopRegion, Marker, TransportManager, TransportState};
+pub use wav_metadata::WavMetadata;
@@ -6,6 +6,13 @@
 //! See spec section 3.0 for full details.
 
 use std::fmt;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::buffer::AudioBuffer;
+use crate::dsp::cubic_hermite;
+use crate::error::{NuevaError, Result};
 
 // No-op logging macros when log crate is not available
 // These macros compile away to nothing, avoiding the need for the log dependency
@@ -45,6 +52,38 @@ impl fmt::Display for TransportState {
     }
 }
 
+/// Loop region in samples (`start_sample..end_sample`, end exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopRegion {
+    /// First sample of the loop
+    pub start_sample: u64,
+    /// Sample after the last one in the loop; playback wraps here
+    pub end_sample: u64,
+    /// Whether playback currently wraps
+    pub enabled: bool,
+}
+
+impl LoopRegion {
+    /// Loop length in samples
+    pub fn len(&self) -> u64 {
+        self.end_sample - self.start_sample
+    }
+
+    /// True for a zero-length region (never constructed by the transport)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A named position on the timeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    /// Display name, e.g. "Chorus"
+    pub name: String,
+    /// Position in samples
+    pub sample: u64,
+}
+
 /// Manages transport state, playhead position, and agent invocation protocol
 ///
 /// The TransportManager handles:
@@ -57,12 +96,16 @@ pub struct TransportManager {
     /// Current transport state
     state: TransportState,
 
-    /// Current playhead position in seconds
+    /// Current playhead position in samples; fractional when playing at a
+    /// rate other than 1.0
     playhead_position: f64,
 
-    /// Saved playhead position for agent invocation resume
+    /// Saved playhead position (samples) for agent invocation resume
     saved_playhead_position: f64,
 
+    /// Samples the playhead moves per frame; negative scrubs backward
+    playback_rate: f32,
+
     /// Sample rate for position calculations (default: 48000 Hz)
     sample_rate: u32,
 
@@ -71,6 +114,15 @@ pub struct TransportManager {
 
     /// Previous state before agent invocation (for logging/debugging)
     state_before_agent: Option<TransportState>,
+
+    /// Loop region, if one is set
+    loop_region: Option<LoopRegion>,
+
+    /// Named markers, sorted by position
+    markers: Vec<Marker>,
+
+    /// Length of the audio in samples, if known (bounds marker positions)
+    audio_length: Option<u64>,
 }
 
 impl Default for TransportManager {
@@ -96,9 +148,13 @@ impl TransportManager {
             state: TransportState::Paused,
             playhead_position: 0.0,
             saved_playhead_position: 0.0,
+            playback_rate: 1.0,
             sample_rate,
             keep_recording_buffer: false,
             state_before_agent: None,
+            loop_region: None,
+            markers: Vec::new(),
+            audio_length: None,
         }
     }
 
@@ -140,7 +196,7 @@ impl TransportManager {
                 self.state = TransportState::Paused;
                 log_debug!(
                     "[AUTO-PAUSE] Stopped recording, saved position: {:.3}s",
-                    self.saved_playhead_position
+                    self.get_saved_playhead_position()
                 );
             }
             TransportState::Playing => {
@@ -149,7 +205,7 @@ impl TransportManager {
                 self.state = TransportState::Paused;
                 log_debug!(
                     "[AUTO-PAUSE] Paused playback, saved position: {:.3}s",
-                    self.saved_playhead_position
+                    self.get_saved_playhead_position()
                 );
             }
             TransportState::Paused => {
@@ -158,7 +214,7 @@ impl TransportManager {
                 self.saved_playhead_position = self.playhead_position;
                 log_debug!(
                     "[AUTO-PAUSE] Already paused at {:.3}s",
-                    self.playhead_position
+                    self.get_playhead_position()
                 );
             }
         }
@@ -168,7 +224,7 @@ impl TransportManager {
     ///
     /// # Arguments
     /// * `should_resume` - If true, seeks to saved position and plays.
-    ///                     If false, stays paused so user can hear the change.
+    ///   If false, stays paused so user can hear the change.
     ///
     /// # Example
     /// ```
@@ -190,7 +246,7 @@ impl TransportManager {
             self.state = TransportState::Playing;
             log_debug!(
                 "[AGENT-COMPLETE] Resumed playback at {:.3}s",
-                self.playhead_position
+                self.get_playhead_position()
             );
         } else {
             // Stay paused so user can hear the change
@@ -237,7 +293,7 @@ impl TransportManager {
         match self.state {
             TransportState::Paused => {
                 self.state = TransportState::Playing;
-                log_debug!("[TRANSPORT] Play from {:.3}s", self.playhead_position);
+                log_debug!("[TRANSPORT] Play from {:.3}s", self.get_playhead_position());
             }
             TransportState::Playing => {
                 // Already playing - no action
@@ -270,7 +326,7 @@ impl TransportManager {
         match self.state {
             TransportState::Playing => {
                 self.state = TransportState::Paused;
-                log_debug!("[TRANSPORT] Paused at {:.3}s", self.playhead_position);
+                log_debug!("[TRANSPORT] Paused at {:.3}s", self.get_playhead_position());
             }
             TransportState::Recording => {
                 // Pausing during recording - keep the buffer
@@ -278,7 +334,7 @@ impl TransportManager {
                 self.state = TransportState::Paused;
                 log_debug!(
                     "[TRANSPORT] Recording paused at {:.3}s (buffer kept)",
-                    self.playhead_position
+                    self.get_playhead_position()
                 );
             }
             TransportState::Paused => {
@@ -336,7 +392,7 @@ impl TransportManager {
                 self.state = TransportState::Recording;
                 log_debug!(
                     "[TRANSPORT] Recording started at {:.3}s",
-                    self.playhead_position
+                    self.get_playhead_position()
                 );
             }
             TransportState::Playing => {
@@ -345,7 +401,7 @@ impl TransportManager {
                 self.state = TransportState::Recording;
                 log_debug!(
                     "[TRANSPORT] Recording (punch-in) at {:.3}s",
-                    self.playhead_position
+                    self.get_playhead_position()
                 );
             }
             TransportState::Recording => {
@@ -355,7 +411,7 @@ impl TransportManager {
         }
     }
 
-    /// Seek to a specific position in seconds
+    /// Seek to a specific position in seconds, snapped to the nearest sample
     ///
     /// # Arguments
     /// * `position` - Target position in seconds (clamped to >= 0)
@@ -369,33 +425,356 @@ impl TransportManager {
     /// ```
     pub fn seek(&mut self, position: f64) {
         // Clamp to non-negative
-        self.playhead_position = position.max(0.0);
-        log_debug!("[TRANSPORT] Seek to {:.3}s", self.playhead_position);
+        self.playhead_position = (position.max(0.0) * self.sample_rate as f64).round();
+        log_debug!("[TRANSPORT] Seek to {:.3}s", self.get_playhead_position());
     }
 
     /// Get the current playhead position in seconds
     pub fn get_playhead_position(&self) -> f64 {
-        self.playhead_position
+        self.playhead_position / self.sample_rate as f64
     }
 
     /// Get the saved playhead position (for agent resume)
     pub fn get_saved_playhead_position(&self) -> f64 {
-        self.saved_playhead_position
+        self.saved_playhead_position / self.sample_rate as f64
     }
 
     /// Get the current playhead position in samples
     pub fn get_playhead_position_samples(&self) -> u64 {
-        (self.playhead_position * self.sample_rate as f64) as u64
+        self.playhead_position as u64
+    }
+
+    /// Get the current playhead position in samples, including the
+    /// fraction left by non-1.0 playback rates
+    pub fn playhead_sample_position(&self) -> f64 {
+        self.playhead_position
     }
 
     /// Update the playhead position (called during playback/recording)
     ///
+    /// The playhead moves by the playback rate per elapsed frame. With an
+    /// enabled loop, a playhead inside the loop wraps from its end back to
+    /// its start (or, scrubbing backward, from its start to its end)
+    /// without skipping or repeating a sample.
+    ///
     /// # Arguments
-    /// * `samples_elapsed` - Number of samples that have elapsed
-    pub fn advance_playhead(&mut self, samples_elapsed: u64) {
-        if self.state == TransportState::Playing || self.state == TransportState::Recording {
-            self.playhead_position += samples_elapsed as f64 / self.sample_rate as f64;
+    /// * `samples_elapsed` - Number of output frames that have elapsed
+    ///
+    /// # Returns
+    /// True if the playhead wrapped around the loop. The transport never
+    /// touches effect state; callers that want effects reset on each pass
+    /// can do so when this returns true.
+    pub fn advance_playhead(&mut self, samples_elapsed: u64) -> bool {
+        if self.state != TransportState::Playing && self.state != TransportState::Recording {
+            return false;
         }
+        self.move_playhead(samples_elapsed as f64 * self.playback_rate as f64)
+    }
+
+    /// Render the next `frames` of `source` at the playback rate
+    ///
+    /// Reads at the fractional playhead with cubic interpolation, so half
+    /// and double speed play an octave down and up, like tape. Advances
+    /// the playhead by the rate each frame, wrapping inside an enabled
+    /// loop. Paused transports and a rate of 0 render silence.
+    pub fn read_resampled(&mut self, source: &AudioBuffer, frames: usize) -> AudioBuffer {
+        let mut output = AudioBuffer {
+            samples: vec![vec![0.0; frames]; source.channels()],
+            sample_rate: source.sample_rate,
+        };
+        if !(self.is_playing() || self.is_recording()) || self.playback_rate == 0.0 {
+            return output;
+        }
+
+        let step = self.playback_rate as f64;
+        for frame in 0..frames {
+            for (out, channel) in output.samples.iter_mut().zip(&source.samples) {
+                out[frame] = read_cubic(channel, self.playhead_position);
+            }
+            self.move_playhead(step);
+        }
+        output
+    }
+
+    /// Move the playhead by `delta` samples, wrapping inside an enabled loop
+    ///
+    /// Returns true if it wrapped. Without a loop, scrubbing backward stops
+    /// at the start.
+    fn move_playhead(&mut self, delta: f64) -> bool {
+        let Some(region) = self.active_loop() else {
+            self.playhead_position = (self.playhead_position + delta).max(0.0);
+            return false;
+        };
+
+        let start = region.start_sample as f64;
+        let position = self.playhead_position + delta;
+        let inside = self.playhead_position >= start;
+        let wrapped = position >= region.end_sample as f64 || (inside && position < start);
+        self.playhead_position = if wrapped {
+            start + (position - start).rem_euclid(region.len() as f64)
+        } else {
+            position.max(0.0)
+        };
+
+        if wrapped {
+            log_debug!(
+                "[TRANSPORT] Loop wrapped to sample {}",
+                self.playhead_position
+            );
+        }
+        wrapped
+    }
+
+    /// Source sample ranges covering the next `frames` samples of playback
+    ///
+    /// Without a loop this is a single range. Inside an enabled loop it is
+    /// split at each wrap, so rendering the ranges in order through the
+    /// same effect instances plays the loop seamlessly.
+    pub fn playback_segments(&self, frames: u64) -> Vec<Range<u64>> {
+        let mut position = self.position_in_samples();
+        let Some(region) = self.active_loop() else {
+            return std::iter::once(position..position + frames).collect();
+        };
+
+        let mut segments = Vec::new();
+        let mut remaining = frames;
+        while remaining > 0 {
+            let take = remaining.min(region.end_sample - position);
+            segments.push(position..position + take);
+            remaining -= take;
+            position += take;
+            if position == region.end_sample {
+                position = region.start_sample;
+            }
+        }
+        segments
+    }
+
+    /// Set how many samples the playhead moves per frame
+    ///
+    /// 1.0 is normal speed, 0.5 half speed and 2.0 double speed; pitch
+    /// follows the speed, like tape. 0 holds the playhead still without
+    /// pausing, and negative rates scrub backward. Non-finite rates are
+    /// ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use nueva::engine::TransportManager;
+    /// let mut transport = TransportManager::new(48000);
+    /// transport.set_playback_rate(0.5);
+    /// transport.play();
+    /// transport.advance_playhead(3);
+    /// assert_eq!(transport.playhead_sample_position(), 1.5);
+    /// ```
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        if rate.is_finite() {
+            self.playback_rate = rate;
+        }
+    }
+
+    /// Samples the playhead moves per frame
+    pub fn playback_rate(&self) -> f32 {
+        self.playback_rate
+    }
+
+    // ========================================================================
+    // Loop Region
+    // ========================================================================
+
+    /// Set and enable a loop from `start_sample` up to (not including)
+    /// `end_sample`
+    ///
+    /// # Errors
+    /// Returns `InvalidParameter` for a zero-length or inverted region.
+    ///
+    /// # Example
+    /// ```
+    /// use nueva::engine::TransportManager;
+    /// let mut transport = TransportManager::new(48000);
+    /// transport.set_loop(48000, 96000).unwrap();
+    /// transport.seek(1.5);
+    /// transport.play();
+    /// transport.advance_playhead(48000);
+    /// assert_eq!(transport.get_playhead_position(), 1.5);
+    /// ```
+    pub fn set_loop(&mut self, start_sample: u64, end_sample: u64) -> Result<()> {
+        if end_sample <= start_sample {
+            return Err(NuevaError::InvalidParameter {
+                param: "loop".to_string(),
+                value: format!("{}..{}", start_sample, end_sample),
+                expected: "end sample after start sample".to_string(),
+            });
+        }
+
+        self.loop_region = Some(LoopRegion {
+            start_sample,
+            end_sample,
+            enabled: true,
+        });
+        log_debug!("[TRANSPORT] Loop set to {}..{}", start_sample, end_sample);
+        Ok(())
+    }
+
+    /// Enable or disable the loop without forgetting its bounds
+    pub fn set_loop_enabled(&mut self, enabled: bool) {
+        if let Some(region) = self.loop_region.as_mut() {
+            region.enabled = enabled;
+        }
+    }
+
+    /// Remove the loop; playback continues linearly from the current position
+    pub fn clear_loop(&mut self) {
+        self.loop_region = None;
+    }
+
+    /// The loop region, if one is set (enabled or not)
+    pub fn loop_region(&self) -> Option<LoopRegion> {
+        self.loop_region
+    }
+
+    // ========================================================================
+    // Markers
+    // ========================================================================
+
+    /// Set the audio length in samples, which bounds marker positions
+    pub fn set_audio_length(&mut self, samples: u64) {
+        self.audio_length = Some(samples);
+    }
+
+    /// The audio length in samples, if known
+    pub fn audio_length(&self) -> Option<u64> {
+        self.audio_length
+    }
+
+    /// Add a named marker at `sample`
+    ///
+    /// Markers stay sorted by position. Names are unique ignoring case.
+    ///
+    /// # Errors
+    /// Returns `InvalidParameter` for an empty or duplicate name, or a
+    /// position beyond the end of the audio.
+    ///
+    /// # Example
+    /// ```
+    /// use nueva::engine::TransportManager;
+    /// let mut transport = TransportManager::new(48000);
+    /// transport.add_marker("Chorus", 96000).unwrap();
+    /// transport.add_marker("Verse", 0).unwrap();
+    /// assert_eq!(transport.markers()[0].name, "Verse");
+    /// ```
+    pub fn add_marker(&mut self, name: &str, sample: u64) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() || self.find_marker(name).is_some() {
+            return Err(NuevaError::InvalidParameter {
+                param: "marker name".to_string(),
+                value: name.to_string(),
+                expected: "a non-empty name not already in use".to_string(),
+            });
+        }
+        if let Some(length) = self.audio_length.filter(|&length| sample > length) {
+            return Err(NuevaError::InvalidParameter {
+                param: "marker position".to_string(),
+                value: sample.to_string(),
+                expected: format!("at most the audio length ({} samples)", length),
+            });
+        }
+
+        // Insert after any marker at the same position, keeping add order
+        let index = self.markers.partition_point(|m| m.sample <= sample);
+        self.markers.insert(
+            index,
+            Marker {
+                name: name.to_string(),
+                sample,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove the marker with the given name (ignoring case)
+    pub fn remove_marker(&mut self, name: &str) -> Option<Marker> {
+        let index = self
+            .markers
+            .iter()
+            .position(|m| m.name.eq_ignore_ascii_case(name.trim()))?;
+        Some(self.markers.remove(index))
+    }
+
+    /// Find a marker by name (ignoring case)
+    pub fn find_marker(&self, name: &str) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Markers with `start_sample <= sample < end_sample`, in order
+    pub fn markers_in_range(&self, start_sample: u64, end_sample: u64) -> &[Marker] {
+        let start = self.markers.partition_point(|m| m.sample < start_sample);
+        let end = self.markers.partition_point(|m| m.sample < end_sample);
+        &self.markers[start..end.max(start)]
+    }
+
+    /// All markers, sorted by position
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    /// Replace all markers (e.g. when loading a project)
+    pub fn set_markers(&mut self, mut markers: Vec<Marker>) {
+        markers.sort_by_key(|m| m.sample);
+        self.markers = markers;
+    }
+
+    /// Move the playhead to a named marker
+    pub fn seek_to_marker(&mut self, name: &str) -> Result<()> {
+        let sample = self.find_marker(name).map(|m| m.sample).ok_or_else(|| {
+            NuevaError::InvalidParameter {
+                param: "marker".to_string(),
+                value: name.to_string(),
+                expected: "the name of an existing marker".to_string(),
+            }
+        })?;
+        self.seek(sample as f64 / self.sample_rate as f64);
+        Ok(())
+    }
+
+    /// Keep markers and the loop in place after the audio is trimmed to
+    /// `start_sample..end_sample`
+    ///
+    /// Positions shift left by `start_sample`; markers in the removed
+    /// parts are clamped to the new start or end. A loop left with no
+    /// length is cleared. Call this from any edit that trims audio.
+    pub fn apply_trim(&mut self, start_sample: u64, end_sample: u64) {
+        let new_length = end_sample.saturating_sub(start_sample);
+        let shift = |sample: u64| sample.saturating_sub(start_sample).min(new_length);
+
+        for marker in self.markers.iter_mut() {
+            marker.sample = shift(marker.sample);
+        }
+        self.loop_region = self.loop_region.and_then(|region| {
+            let start = shift(region.start_sample);
+            let end = shift(region.end_sample);
+            (end > start).then_some(LoopRegion {
+                start_sample: start,
+                end_sample: end,
+                ..region
+            })
+        });
+        self.audio_length = Some(new_length);
+    }
+
+    /// The loop region if it is enabled and the playhead is inside it
+    ///
+    /// A playhead seeked past the loop end plays on linearly.
+    fn active_loop(&self) -> Option<LoopRegion> {
+        let position = self.playhead_position;
+        self.loop_region
+            .filter(|region| region.enabled && position < region.end_sample as f64)
+    }
+
+    /// Playhead position rounded to the nearest sample
+    fn position_in_samples(&self) -> u64 {
+        self.playhead_position.round() as u64
     }
 
     // ========================================================================
@@ -432,10 +811,30 @@ impl TransportManager {
     /// # Arguments
     /// * `sample_rate` - New sample rate in Hz
     pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        // Positions keep their time, not their sample index
+        let scale = sample_rate as f64 / self.sample_rate as f64;
+        self.playhead_position *= scale;
+        self.saved_playhead_position *= scale;
         self.sample_rate = sample_rate;
     }
 }
 
+/// Sample at a fractional position, cubic-interpolated; silence outside
+/// the channel
+fn read_cubic(channel: &[f32], position: f64) -> f32 {
+    let index = position.floor();
+    let frac = (position - index) as f32;
+    let index = index as i64;
+    let tap = |offset: i64| {
+        usize::try_from(index + offset)
+            .ok()
+            .and_then(|i| channel.get(i))
+            .copied()
+            .unwrap_or(0.0)
+    };
+    cubic_hermite(tap(-1), tap(0), tap(1), tap(2), frac)
+}
+
 // ============================================================================
 // Unit Tests
 // ============================================================================
@@ -443,6 +842,7 @@ impl TransportManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::buffer::ChannelLayout;
 
     // ------------------------------------------------------------------------
     // Basic State Tests
@@ -810,6 +1210,300 @@ mod tests {
         assert_eq!(transport.sample_rate(), 44100);
     }
 
+    // ------------------------------------------------------------------------
+    // Loop Region Tests
+    // ------------------------------------------------------------------------
+
+    #[test]
+    fn test_loop_wraps_sample_accurately() {
+        let mut transport = TransportManager::new(48000);
+        transport.set_loop(1000, 1100).unwrap();
+        transport.seek(1090.0 / 48000.0);
+        transport.play();
+
+        // 10 samples reach the end exactly and land back on the start
+        assert!(transport.advance_playhead(10));
+        assert_eq!(transport.get_playhead_position_samples(), 1000);
+
+        // Many small blocks across several wraps never drift
+        for _ in 0..999 {
+            transport.advance_playhead(7);
+        }
+        assert_eq!(transport.get_playhead_position_samples(), 1093);
+    }
+
+    #[test]
+    fn test_playback_segments_split_at_wrap() {
+        let mut transport = TransportManager::new(48000);
+        transport.set_loop(100, 110).unwrap();
+        transport.seek(105.0 / 48000.0);
+
+        let segments = transport.playback_segments(17);
+        assert_eq!(segments, vec![105..110, 100..110, 100..102]);
+
+        // Each sample of the loop appears exactly once per pass
+        let total: u64 = segments.iter().map(|r| r.end - r.start).sum();
+        assert_eq!(total, 17);
+    }
+
+    #[test]
+    fn test_invalid_loop_rejected() {
+        let mut transport = TransportManager::new(48000);
+        assert!(transport.set_loop(500, 500).is_err());
+        assert!(transport.set_loop(600, 500).is_err());
+        assert!(transport.loop_region().is_none());
+    }
+
+    #[test]
+    fn test_clear_loop_resumes_linear_playback() {
+        let mut transport = TransportManager::new(48000);
+        transport.set_loop(0, 48000).unwrap();
+        transport.play();
+        transport.advance_playhead(60000);
+        assert_eq!(transport.get_playhead_position_samples(), 12000);
+
+        transport.clear_loop();
+        assert!(!transport.advance_playhead(48000));
+        assert_eq!(transport.get_playhead_position_samples(), 60000);
+    }
+
+    #[test]
+    fn test_disabled_loop_keeps_bounds() {
+        let mut transport = TransportManager::new(48000);
+        transport.set_loop(0, 100).unwrap();
+        transport.set_loop_enabled(false);
+        transport.play();
+        transport.advance_playhead(150);
+
+        assert_eq!(transport.get_playhead_position_samples(), 150);
+        assert!(!transport.loop_region().unwrap().enabled);
+    }
+
+    #[test]
+    fn test_effect_state_continues_across_wrap() {
+        use crate::dsp::{AudioBuffer as DspBuffer, Delay, Effect};
+
+        // Render two passes of a loop block by block through one delay,
+        // and the same audio in one go through another
+        let source: Vec<f32> = (0..400).map(|i| ((i % 37) as f32 / 37.0) - 0.5).collect();
+        let mut transport = TransportManager::new(48000);
+        transport.set_loop(100, 300).unwrap();
+        transport.seek(100.0 / 48000.0);
+        transport.play();
+
+        let mut looped = Delay::new();
+        looped.set_delay_time(2.0).unwrap();
+        looped.prepare(48000.0, 64);
+        let mut rendered = Vec::new();
+        for _ in 0..(400 / 64 + 1) {
+            let mut block = Vec::new();
+            for range in transport.playback_segments(64) {
+                block.extend_from_slice(&source[range.start as usize..range.end as usize]);
+            }
+            transport.advance_playhead(64);
+            let mut buffer = DspBuffer::from_interleaved(block, 1, 48000.0).unwrap();
+            looped.process(&mut buffer);
+            rendered.extend_from_slice(buffer.samples());
+        }
+
+        let mut expected: Vec<f32> = source[100..300].to_vec();
+        expected.extend_from_slice(&source[100..300]);
+        expected.extend_from_slice(&source[100..148]);
+        let mut linear = Delay::new();
+        linear.set_delay_time(2.0).unwrap();
+        linear.prepare(48000.0, 448);
+        let mut buffer = DspBuffer::from_interleaved(expected, 1, 48000.0).unwrap();
+        linear.process(&mut buffer);
+
+        assert_eq!(rendered.len(), buffer.samples().len());
+        for (a, b) in rendered.iter().zip(buffer.samples()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Playback Rate Tests
+    // ------------------------------------------------------------------------
+
+    #[test]
+    fn test_unit_rate_advances_one_sample_per_frame() {
+        let mut transport = TransportManager::new(48000);
+        transport.play();
+        for frame in 1..=48000 {
+            transport.advance_playhead(1);
+            assert_eq!(transport.playhead_sample_position(), frame as f64);
+        }
+        assert_eq!(transport.get_playhead_position(), 1.0);
+    }
+
+    #[test]
+    fn test_fractional_rates_accumulate() {
+        let mut transport = TransportManager::new(48000);
+        transport.play();
+        transport.set_playback_rate(0.5);
+        transport.advance_playhead(3);
+        assert_eq!(transport.playhead_sample_position(), 1.5);
+        assert_eq!(transport.get_playhead_position_samples(), 1);
+
+        transport.set_playback_rate(2.0);
+        transport.advance_playhead(100);
+        assert_eq!(transport.playhead_sample_position(), 201.5);
+    }
+
+    #[test]
+    fn test_zero_rate_holds_position() {
+        let mut transport = TransportManager::new(48000);
+        transport.seek(1.0);
+        transport.play();
+        transport.set_playback_rate(0.0);
+        assert!(!transport.advance_playhead(480));
+        assert!(transport.is_playing());
+        assert_eq!(transport.get_playhead_position_samples(), 48000);
+
+        let source =
+            AudioBuffer::from_interleaved(&[0.5; 96000], ChannelLayout::Mono, 48000).unwrap();
+        let block = transport.read_resampled(&source, 64);
+        assert!(block.channel(0).iter().all(|&s| s == 0.0));
+
+        transport.set_playback_rate(f32::NAN);
+        assert_eq!(transport.playback_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_negative_rate_scrubs_backward() {
+        let mut transport = TransportManager::new(48000);
+        transport.seek(100.0 / 48000.0);
+        transport.play();
+        transport.set_playback_rate(-1.5);
+        transport.advance_playhead(10);
+        assert_eq!(transport.playhead_sample_position(), 85.0);
+
+        // Without a loop the start stops the scrub
+        transport.advance_playhead(1000);
+        assert_eq!(transport.playhead_sample_position(), 0.0);
+
+        // Inside a loop it wraps back to the end
+        transport.set_loop(1000, 1100).unwrap();
+        transport.seek(1002.0 / 48000.0);
+        assert!(transport.advance_playhead(2));
+        assert_eq!(transport.playhead_sample_position(), 1099.0);
+    }
+
+    #[test]
+    fn test_read_resampled_interpolates() {
+        // A ramp interpolates exactly, so half speed lands between samples
+        let ramp: Vec<f32> = (0..64).map(|n| n as f32).collect();
+        let source = AudioBuffer::from_interleaved(&ramp, ChannelLayout::Mono, 48000).unwrap();
+        let mut transport = TransportManager::new(48000);
+        transport.seek(10.0 / 48000.0);
+        transport.play();
+        transport.set_playback_rate(0.5);
+
+        let block = transport.read_resampled(&source, 4);
+        assert_eq!(block.channel(0), [10.0, 10.5, 11.0, 11.5]);
+        assert_eq!(transport.playhead_sample_position(), 12.0);
+
+        transport.set_playback_rate(-2.0);
+        let block = transport.read_resampled(&source, 3);
+        assert_eq!(block.channel(0), [12.0, 10.0, 8.0]);
+
+        // Double speed plays all 440 cycles of a one-second tone in half a
+        // second: an octave up
+        let tone = crate::engine::io::generate_test_tone(440.0, 1.0, 48000);
+        let mut transport = TransportManager::new(48000);
+        transport.play();
+        transport.set_playback_rate(2.0);
+        let fast = transport.read_resampled(&tone, 24000);
+        let crossings = fast
+            .channel(0)
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        assert!((439..=440).contains(&crossings), "{} crossings", crossings);
+    }
+
+    // ------------------------------------------------------------------------
+    // Marker Tests
+    // ------------------------------------------------------------------------
+
+    fn names(markers: &[Marker]) -> Vec<&str> {
+        markers.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_markers_kept_sorted() {
+        let mut transport = TransportManager::new(48000);
+        transport.add_marker("Chorus", 480000).unwrap();
+        transport.add_marker("Intro", 0).unwrap();
+        transport.add_marker("Verse", 96000).unwrap();
+        transport.add_marker("Drop", 480000).unwrap();
+
+        assert_eq!(
+            names(transport.markers()),
+            ["Intro", "Verse", "Chorus", "Drop"]
+        );
+
+        assert_eq!(transport.remove_marker("verse").unwrap().sample, 96000);
+        assert_eq!(names(transport.markers()), ["Intro", "Chorus", "Drop"]);
+        assert!(transport.remove_marker("Verse").is_none());
+    }
+
+    #[test]
+    fn test_markers_in_range() {
+        let mut transport = TransportManager::new(48000);
+        for (i, name) in ["A", "B", "C", "D"].iter().enumerate() {
+            transport.add_marker(name, i as u64 * 100).unwrap();
+        }
+
+        assert_eq!(names(transport.markers_in_range(100, 300)), ["B", "C"]);
+        assert_eq!(names(transport.markers_in_range(0, 1)), ["A"]);
+        assert!(transport.markers_in_range(301, 400).is_empty());
+        assert!(transport.markers_in_range(300, 100).is_empty());
+        assert_eq!(transport.markers_in_range(0, u64::MAX).len(), 4);
+    }
+
+    #[test]
+    fn test_invalid_markers_rejected() {
+        let mut transport = TransportManager::new(48000);
+        transport.set_audio_length(48000);
+
+        assert!(transport.add_marker("End", 48000).is_ok());
+        assert!(transport.add_marker("Past", 48001).is_err());
+        assert!(transport.add_marker("  ", 10).is_err());
+        assert!(transport.add_marker("end", 10).is_err());
+        assert_eq!(transport.markers().len(), 1);
+    }
+
+    #[test]
+    fn test_seek_to_marker() {
+        let mut transport = TransportManager::new(48000);
+        transport.add_marker("Chorus", 72000).unwrap();
+
+        transport.seek_to_marker("chorus").unwrap();
+        assert_eq!(transport.get_playhead_position(), 1.5);
+        assert!(transport.seek_to_marker("Bridge").is_err());
+    }
+
+    #[test]
+    fn test_trim_shifts_markers() {
+        let mut transport = TransportManager::new(48000);
+        transport.add_marker("Silence", 500).unwrap();
+        transport.add_marker("Verse", 2000).unwrap();
+        transport.add_marker("Outro", 9500).unwrap();
+        transport.set_loop(2000, 3000).unwrap();
+
+        transport.apply_trim(1000, 9000);
+
+        let positions: Vec<u64> = transport.markers().iter().map(|m| m.sample).collect();
+        assert_eq!(positions, [0, 1000, 8000]);
+        assert_eq!(transport.audio_length(), Some(8000));
+        let region = transport.loop_region().unwrap();
+        assert_eq!((region.start_sample, region.end_sample), (1000, 2000));
+
+        transport.apply_trim(5000, 8000);
+        assert!(transport.loop_region().is_none());
+    }
+
     #[test]
     fn test_clear_recording_buffer_flag() {
         let mut transport = TransportManager::new(48000);
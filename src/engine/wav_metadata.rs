@@ -0,0 +1,389 @@
+//! WAV metadata chunks
+//!
+//! Reads and writes the RIFF chunks hound ignores: `cue ` points with
+//! their `LIST`/`adtl` labels, the first `smpl` loop and the tempo from an
+//! `acid` chunk. Positions here are frames at the file's own sample rate;
+//! the import and export functions in `io` rescale them. A malformed chunk
+//! is skipped with a warning instead of failing the import.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::engine::transport::{LoopRegion, Marker};
+use crate::error::Result;
+
+/// Largest metadata chunk read into memory; bigger ones are skipped
+const MAX_CHUNK_BYTES: u32 = 1 << 20;
+
+/// Metadata carried alongside the audio of a WAV file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WavMetadata {
+    /// Cue points, named from their labels, sorted by position
+    pub markers: Vec<Marker>,
+    /// First sampler loop, end exclusive
+    pub loop_region: Option<LoopRegion>,
+    /// Tempo in beats per minute
+    pub bpm: Option<f64>,
+}
+
+impl WavMetadata {
+    /// True when the file carried none of the supported metadata
+    pub fn is_empty(&self) -> bool {
+        self.markers.is_empty() && self.loop_region.is_none() && self.bpm.is_none()
+    }
+
+    /// Copy with every position converted from `from_rate` to `to_rate`
+    pub fn rescaled(&self, from_rate: u32, to_rate: u32) -> Self {
+        let scale = |sample: u64| {
+            if from_rate == to_rate {
+                sample
+            } else {
+                (sample as f64 * to_rate as f64 / from_rate as f64).round() as u64
+            }
+        };
+        Self {
+            markers: self
+                .markers
+                .iter()
+                .map(|m| Marker {
+                    name: m.name.clone(),
+                    sample: scale(m.sample),
+                })
+                .collect(),
+            loop_region: self.loop_region.map(|region| LoopRegion {
+                start_sample: scale(region.start_sample),
+                end_sample: scale(region.end_sample),
+                enabled: region.enabled,
+            }),
+            bpm: self.bpm,
+        }
+    }
+
+    /// Read the metadata chunks of the WAV file at `path`
+    ///
+    /// The audio data is seeked over, not read.
+    pub fn read(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; 12];
+        if file.read_exact(&mut header).is_err()
+            || &header[0..4] != b"RIFF"
+            || &header[8..12] != b"WAVE"
+        {
+            return Ok(Self::default());
+        }
+
+        let mut chunks = Vec::new();
+        let mut chunk_header = [0u8; 8];
+        while file.read_exact(&mut chunk_header).is_ok() {
+            let id: [u8; 4] = chunk_header[0..4].try_into().unwrap_or_default();
+            let size = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap_or_default());
+            let padded = size as i64 + (size & 1) as i64;
+
+            if matches!(&id, b"cue " | b"LIST" | b"smpl" | b"acid") {
+                if size > MAX_CHUNK_BYTES {
+                    log::warn!(
+                        "Skipping oversized WAV '{}' chunk ({} bytes)",
+                        fourcc(&id),
+                        size
+                    );
+                } else {
+                    let mut body = vec![0u8; size as usize];
+                    if file.read_exact(&mut body).is_err() {
+                        log::warn!("WAV '{}' chunk runs past the end of the file", fourcc(&id));
+                        break;
+                    }
+                    chunks.push((id, body));
+                    file.seek(SeekFrom::Current(padded - size as i64))?;
+                    continue;
+                }
+            }
+            file.seek(SeekFrom::Current(padded))?;
+        }
+
+        Ok(Self::from_chunks(&chunks))
+    }
+
+    /// Build metadata from `(id, body)` chunk pairs
+    fn from_chunks(chunks: &[([u8; 4], Vec<u8>)]) -> Self {
+        let mut cues: Vec<(u32, u64)> = Vec::new();
+        let mut labels: Vec<(u32, String)> = Vec::new();
+        let mut metadata = Self::default();
+
+        for (id, body) in chunks {
+            match id {
+                b"cue " => match parse_cues(body) {
+                    Some(points) => cues.extend(points),
+                    None => log::warn!("Skipping malformed WAV cue chunk"),
+                },
+                b"LIST" if body.starts_with(b"adtl") => match parse_labels(&body[4..]) {
+                    Some(found) => labels.extend(found),
+                    None => log::warn!("Skipping malformed WAV adtl list"),
+                },
+                b"smpl" => match parse_loop(body) {
+                    Some(region) => metadata.loop_region = metadata.loop_region.or(region),
+                    None => log::warn!("Skipping malformed WAV smpl chunk"),
+                },
+                b"acid" => match read_f32(body, 20) {
+                    Some(tempo) if tempo.is_finite() && tempo > 0.0 => {
+                        metadata.bpm = Some(tempo as f64)
+                    }
+                    _ => log::warn!("Skipping malformed WAV acid chunk"),
+                },
+                _ => {}
+            }
+        }
+
+        metadata.markers = cues
+            .into_iter()
+            .map(|(cue_id, sample)| Marker {
+                name: labels
+                    .iter()
+                    .find(|(label_id, _)| *label_id == cue_id)
+                    .map(|(_, name)| name.clone())
+                    .unwrap_or_else(|| format!("Cue {}", cue_id)),
+                sample,
+            })
+            .collect();
+        metadata.markers.sort_by_key(|m| m.sample);
+        metadata
+    }
+
+    /// Append this metadata as RIFF chunks to the finished WAV at `path`
+    ///
+    /// Positions must already be at `sample_rate`, the file's rate.
+    pub(crate) fn append_to(&self, path: &Path, sample_rate: u32) -> Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut len = file.seek(SeekFrom::End(0))?;
+        if len % 2 == 1 {
+            file.write_all(&[0])?;
+            len += 1;
+        }
+
+        let chunks = self.to_chunks(sample_rate);
+        file.write_all(&chunks)?;
+        let riff_size = (len + chunks.len() as u64 - 8) as u32;
+        file.seek(SeekFrom::Start(4))?;
+        file.write_all(&riff_size.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Serialize as `cue `, `LIST`/`adtl`, `smpl` and `acid` chunks
+    fn to_chunks(&self, sample_rate: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        let markers: Vec<(u32, &Marker)> = self
+            .markers
+            .iter()
+            .filter(|m| m.sample <= u32::MAX as u64)
+            .enumerate()
+            .map(|(i, m)| (i as u32 + 1, m))
+            .collect();
+
+        if !markers.is_empty() {
+            let mut cue = u32_bytes(&[markers.len() as u32]);
+            for (id, marker) in &markers {
+                let sample = marker.sample as u32;
+                cue.extend(u32_bytes(&[*id, sample]));
+                cue.extend(b"data");
+                cue.extend(u32_bytes(&[0, 0, sample]));
+            }
+            push_chunk(&mut out, b"cue ", &cue);
+
+            let mut list = b"adtl".to_vec();
+            for (id, marker) in &markers {
+                let mut label = u32_bytes(&[*id]);
+                label.extend(marker.name.as_bytes());
+                label.push(0);
+                push_chunk(&mut list, b"labl", &label);
+            }
+            push_chunk(&mut out, b"LIST", &list);
+        }
+
+        if let Some(region) = self
+            .loop_region
+            .filter(|r| !r.is_empty() && r.end_sample <= u32::MAX as u64)
+        {
+            let sample_period = (1e9 / sample_rate as f64).round() as u32;
+            let mut smpl = u32_bytes(&[0, 0, sample_period, 60, 0, 0, 0, 1, 0]);
+            smpl.extend(u32_bytes(&[
+                0,
+                0,
+                region.start_sample as u32,
+                region.end_sample as u32 - 1,
+                0,
+                0,
+            ]));
+            push_chunk(&mut out, b"smpl", &smpl);
+        }
+
+        if let Some(bpm) = self.bpm {
+            let mut acid = u32_bytes(&[0]);
+            acid.extend(60u16.to_le_bytes());
+            acid.extend(0x8000u16.to_le_bytes());
+            acid.extend(0f32.to_le_bytes());
+            acid.extend(u32_bytes(&[0]));
+            acid.extend(4u16.to_le_bytes());
+            acid.extend(4u16.to_le_bytes());
+            acid.extend((bpm as f32).to_le_bytes());
+            push_chunk(&mut out, b"acid", &acid);
+        }
+        out
+    }
+}
+
+/// `(cue id, sample offset)` for each point, or None if truncated
+fn parse_cues(body: &[u8]) -> Option<Vec<(u32, u64)>> {
+    let count = read_u32(body, 0)? as usize;
+    (0..count)
+        .map(|i| {
+            let point = 4 + i * 24;
+            Some((read_u32(body, point)?, read_u32(body, point + 20)? as u64))
+        })
+        .collect()
+}
+
+/// `(cue id, text)` for each `labl` sub-chunk of an `adtl` list
+fn parse_labels(mut body: &[u8]) -> Option<Vec<(u32, String)>> {
+    let mut labels = Vec::new();
+    while body.len() >= 8 {
+        let size = read_u32(body, 4)? as usize;
+        let data = body.get(8..8 + size)?;
+        if &body[0..4] == b"labl" {
+            let text = data.get(4..)?;
+            let end = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+            labels.push((
+                read_u32(data, 0)?,
+                String::from_utf8_lossy(&text[..end]).into_owned(),
+            ));
+        }
+        body = body.get(8 + size + (size & 1)..).unwrap_or_default();
+    }
+    Some(labels)
+}
+
+/// First loop of a `smpl` chunk (Some(None) when it has no loops)
+fn parse_loop(body: &[u8]) -> Option<Option<LoopRegion>> {
+    let count = read_u32(body, 28)?;
+    if count == 0 {
+        return Some(None);
+    }
+    let start = read_u32(body, 36 + 8)? as u64;
+    let end = read_u32(body, 36 + 12)? as u64;
+    if end < start {
+        return None;
+    }
+    Some(Some(LoopRegion {
+        start_sample: start,
+        end_sample: end + 1,
+        enabled: true,
+    }))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> Option<f32> {
+    read_u32(bytes, offset).map(f32::from_bits)
+}
+
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn push_chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend(id);
+    out.extend((body.len() as u32).to_le_bytes());
+    out.extend(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn fourcc(id: &[u8; 4]) -> String {
+    String::from_utf8_lossy(id).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_metadata() -> WavMetadata {
+        WavMetadata {
+            markers: vec![
+                Marker {
+                    name: "Verse".to_string(),
+                    sample: 100,
+                },
+                Marker {
+                    name: "Chorus".to_string(),
+                    sample: 4000,
+                },
+            ],
+            loop_region: Some(LoopRegion {
+                start_sample: 1000,
+                end_sample: 3000,
+                enabled: true,
+            }),
+            bpm: Some(123.5),
+        }
+    }
+
+    /// Split serialized chunks back into `(id, body)` pairs
+    fn split(mut bytes: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        let mut chunks = Vec::new();
+        while bytes.len() >= 8 {
+            let size = read_u32(bytes, 4).unwrap() as usize;
+            chunks.push((bytes[0..4].try_into().unwrap(), bytes[8..8 + size].to_vec()));
+            bytes = &bytes[(8 + size + (size & 1)).min(bytes.len())..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_chunks_round_trip() {
+        let metadata = sample_metadata();
+        let parsed = WavMetadata::from_chunks(&split(&metadata.to_chunks(48000)));
+        assert_eq!(parsed, metadata);
+    }
+
+    #[test]
+    fn test_unlabelled_cues_get_names() {
+        let mut chunks = split(&sample_metadata().to_chunks(48000));
+        chunks.retain(|(id, _)| id == b"cue ");
+        let parsed = WavMetadata::from_chunks(&chunks);
+        assert_eq!(parsed.markers[0].name, "Cue 1");
+        assert_eq!(parsed.markers[1].sample, 4000);
+        assert!(parsed.loop_region.is_none());
+    }
+
+    #[test]
+    fn test_malformed_chunks_are_skipped() {
+        let mut chunks = split(&sample_metadata().to_chunks(48000));
+        for (id, body) in &mut chunks {
+            if id == b"cue " {
+                // Claims more points than it holds
+                body[0] = 9;
+            }
+            if id == b"smpl" {
+                body.truncate(40);
+            }
+        }
+        chunks.push((*b"acid", vec![1, 2, 3]));
+
+        let parsed = WavMetadata::from_chunks(&chunks);
+        assert!(parsed.markers.is_empty());
+        assert!(parsed.loop_region.is_none());
+        assert_eq!(parsed.bpm, Some(123.5));
+    }
+
+    #[test]
+    fn test_rescaled_positions() {
+        let metadata = sample_metadata().rescaled(48000, 44100);
+        assert_eq!(metadata.markers[1].sample, 3675);
+        assert_eq!(metadata.loop_region.unwrap().end_sample, 2756);
+        assert_eq!(metadata.bpm, Some(123.5));
+    }
+}
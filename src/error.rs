@@ -112,6 +112,10 @@ pub enum NuevaError {
 
     #[error("Bridge connection error: {message}")]
     BridgeConnectionError { message: String },
+
+    // Control Flow
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 impl NuevaError {
@@ -145,28 +149,30 @@ impl NuevaError {
             NuevaError::AceStepTimeout { .. } => "ACESTEP_TIMEOUT",
             NuevaError::InsufficientVram { .. } => "INSUFFICIENT_VRAM",
             NuevaError::BridgeConnectionError { .. } => "BRIDGE_CONNECTION_ERROR",
+            NuevaError::Cancelled => "CANCELLED",
         }
     }
 
     /// Check if this error is recoverable
     pub fn is_recoverable(&self) -> bool {
-        match self {
-            NuevaError::DspOverflow { .. } => true,
-            NuevaError::InvalidEffectOutput { .. } => true,
-            NuevaError::OutOfMemory { .. } => true,
-            NuevaError::AmbiguousPrompt { .. } => true,
-            NuevaError::ConflictingRequest { .. } => true,
-            NuevaError::FileNotFound { .. } => true,
-            NuevaError::InvalidAudio { .. } => true,
-            NuevaError::InvalidAudioFile { .. } => true,
-            NuevaError::UnsupportedFormat { .. } => true,
-            NuevaError::InvalidParameter { .. } => true,
-            NuevaError::EffectNotFound { .. } => true,
-            NuevaError::AceStepUnavailable { .. } => true,
-            NuevaError::AceStepTimeout { .. } => true,
-            NuevaError::BridgeConnectionError { .. } => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            NuevaError::DspOverflow { .. }
+                | NuevaError::InvalidEffectOutput { .. }
+                | NuevaError::OutOfMemory { .. }
+                | NuevaError::AmbiguousPrompt { .. }
+                | NuevaError::ConflictingRequest { .. }
+                | NuevaError::FileNotFound { .. }
+                | NuevaError::InvalidAudio { .. }
+                | NuevaError::InvalidAudioFile { .. }
+                | NuevaError::UnsupportedFormat { .. }
+                | NuevaError::InvalidParameter { .. }
+                | NuevaError::EffectNotFound { .. }
+                | NuevaError::AceStepUnavailable { .. }
+                | NuevaError::AceStepTimeout { .. }
+                | NuevaError::BridgeConnectionError { .. }
+                | NuevaError::Cancelled
+        )
     }
 
     /// Get recovery suggestions for this error
@@ -241,7 +247,7 @@ impl NuevaError {
                 "The model may be loading - try again in a moment",
                 "Check GPU memory usage",
                 "Try a shorter audio file",
-                "Increase timeout in NUEVA_ACESTEP_TIMEOUT_MS",
+                "Increase the limit with NUEVA_NEURAL_TIMEOUT_MS",
             ],
             NuevaError::InsufficientVram { .. } => vec![
                 "Close other GPU-intensive applications",
@@ -253,6 +259,10 @@ impl NuevaError {
                 "Check if port 8001 is available",
                 "Verify NUEVA_ACESTEP_API_URL environment variable",
             ],
+            NuevaError::Cancelled => vec![
+                "No changes were applied",
+                "Run the operation again to restart it",
+            ],
             _ => vec![],
         }
     }
@@ -0,0 +1,176 @@
+//! Layer Blending
+//!
+//! Mixes Layer 0 (source) with Layer 1 (AI output) so users can dial in
+//! a fraction of a neural effect, e.g. "50% of the style transfer".
+
+use std::f32::consts::FRAC_PI_2;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::AudioBuffer;
+use crate::error::{NuevaError, Result};
+
+/// Crossfade curve used when blending layers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Gains are `1 - mix` and `mix`
+    #[default]
+    Linear,
+    /// Gains follow cos/sin so perceived loudness stays constant
+    EqualPower,
+}
+
+/// How much of Layer 1 to mix over Layer 0
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayerBlend {
+    /// 0.0 = source only, 1.0 = AI output only
+    pub mix: f32,
+    /// Crossfade curve
+    #[serde(default)]
+    pub mode: BlendMode,
+}
+
+impl Default for LayerBlend {
+    fn default() -> Self {
+        Self {
+            mix: 1.0,
+            mode: BlendMode::Linear,
+        }
+    }
+}
+
+impl LayerBlend {
+    /// Create a blend with the given mix (clamped to [0, 1])
+    pub fn new(mix: f32, mode: BlendMode) -> Self {
+        Self {
+            mix: mix.clamp(0.0, 1.0),
+            mode,
+        }
+    }
+
+    /// Gains applied to (source, ai)
+    pub fn gains(&self) -> (f32, f32) {
+        let mix = self.mix.clamp(0.0, 1.0);
+        match self.mode {
+            BlendMode::Linear => (1.0 - mix, mix),
+            BlendMode::EqualPower => ((mix * FRAC_PI_2).cos(), (mix * FRAC_PI_2).sin()),
+        }
+    }
+
+    /// Blend the two layers
+    ///
+    /// At mix 0 and 1 the respective layer is returned unchanged. In
+    /// between, the result is as long as the shorter layer; if channel
+    /// counts differ, the source layout is kept and the AI output's last
+    /// channel is reused for missing channels. Layers at different sample
+    /// rates are an error.
+    pub fn apply(&self, source: &AudioBuffer, ai: &AudioBuffer) -> Result<AudioBuffer> {
+        if source.sample_rate != ai.sample_rate {
+            return Err(NuevaError::InvalidAudio {
+                reason: format!(
+                    "Cannot blend {} Hz AI output with {} Hz source audio",
+                    ai.sample_rate, source.sample_rate
+                ),
+                source: None,
+            });
+        }
+        if self.mix <= 0.0 {
+            return Ok(source.clone());
+        }
+        if self.mix >= 1.0 {
+            return Ok(ai.clone());
+        }
+
+        let (source_gain, ai_gain) = self.gains();
+        let len = source.len().min(ai.len());
+        let ai_channels = ai.num_channels().max(1);
+
+        let samples = source
+            .samples
+            .iter()
+            .enumerate()
+            .map(|(ch, src)| {
+                let wet = &ai.samples[ch.min(ai_channels - 1)];
+                src[..len]
+                    .iter()
+                    .zip(&wet[..len])
+                    .map(|(s, w)| s * source_gain + w * ai_gain)
+                    .collect()
+            })
+            .collect();
+
+        Ok(AudioBuffer {
+            samples,
+            sample_rate: source.sample_rate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(value: f32, len: usize) -> AudioBuffer {
+        AudioBuffer {
+            samples: vec![vec![value; len]; 2],
+            sample_rate: 48000,
+        }
+    }
+
+    #[test]
+    fn test_endpoints_reproduce_layers() {
+        let source = constant(0.2, 100);
+        let ai = constant(0.8, 80);
+
+        for mode in [BlendMode::Linear, BlendMode::EqualPower] {
+            assert_eq!(
+                LayerBlend::new(0.0, mode)
+                    .apply(&source, &ai)
+                    .unwrap()
+                    .samples,
+                source.samples
+            );
+            assert_eq!(
+                LayerBlend::new(1.0, mode)
+                    .apply(&source, &ai)
+                    .unwrap()
+                    .samples,
+                ai.samples
+            );
+        }
+    }
+
+    #[test]
+    fn test_linear_midpoint() {
+        let blended = LayerBlend::new(0.5, BlendMode::Linear)
+            .apply(&constant(0.2, 100), &constant(0.8, 100))
+            .unwrap();
+        assert!(blended.samples[0].iter().all(|s| (s - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_equal_power_midpoint() {
+        let blend = LayerBlend::new(0.5, BlendMode::EqualPower);
+        let (a, b) = blend.gains();
+        assert!((a * a + b * b - 1.0).abs() < 1e-6);
+        assert!((a - b).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_differing_lengths_use_shorter() {
+        let blended = LayerBlend::new(0.5, BlendMode::Linear)
+            .apply(&constant(0.2, 100), &constant(0.8, 60))
+            .unwrap();
+        assert_eq!(blended.len(), 60);
+    }
+
+    #[test]
+    fn test_differing_sample_rates_rejected() {
+        let mut ai = constant(0.8, 100);
+        ai.sample_rate = 96000;
+        for mix in [0.0, 0.5, 1.0] {
+            let result = LayerBlend::new(mix, BlendMode::Linear).apply(&constant(0.2, 100), &ai);
+            assert!(matches!(result, Err(NuevaError::InvalidAudio { .. })));
+        }
+    }
+}
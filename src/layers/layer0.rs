@@ -10,6 +10,7 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use crate::engine::{fingerprint, fingerprint_distance, import_audio};
 use crate::error::{NuevaError, Result};
 
 /// Audio format information for the original source
@@ -64,6 +65,10 @@ pub struct Layer0 {
     created_at: String,
     /// SHA-256 checksum of the source file for integrity verification
     checksum: String,
+    /// Perceptual fingerprint of the source audio (see
+    /// `engine::fingerprint`); absent for layers saved before fingerprints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<String>,
 }
 
 impl Layer0 {
@@ -77,6 +82,7 @@ impl Layer0 {
     /// - File does not exist
     /// - File is not a valid WAV file
     /// - File cannot be read for checksum calculation
+    /// - Audio cannot be decoded for fingerprinting
     pub fn new(path: PathBuf) -> Result<Self> {
         // Verify file exists
         if !path.exists() {
@@ -92,6 +98,10 @@ impl Layer0 {
         // Calculate checksum
         let checksum = Self::calculate_checksum(&path)?;
 
+        // Fingerprint the audio itself, so re-encodes can be told apart
+        // from real changes
+        let fingerprint = Some(fingerprint(&import_audio(&path)?));
+
         // Get current timestamp
         let created_at = Self::current_timestamp();
 
@@ -100,6 +110,34 @@ impl Layer0 {
             original_format,
             created_at,
             checksum,
+            fingerprint,
+        })
+    }
+
+    /// Restore a Layer 0 saved with a project, keeping its recorded
+    /// checksum, fingerprint and creation time
+    ///
+    /// Only the WAV header is read; whether the file still matches is for
+    /// [`verify_integrity`](Self::verify_integrity) to say.
+    pub fn restore(
+        path: PathBuf,
+        checksum: String,
+        fingerprint: Option<String>,
+        created_at: String,
+    ) -> Result<Self> {
+        if !path.exists() {
+            return Err(NuevaError::FileNotFound {
+                path: path.display().to_string(),
+                source: None,
+            });
+        }
+
+        Ok(Self {
+            original_format: AudioFormat::from_wav(&path)?,
+            source_path: path,
+            created_at,
+            checksum,
+            fingerprint,
         })
     }
 
@@ -123,6 +161,18 @@ impl Layer0 {
         &self.checksum
     }
 
+    /// Get the perceptual fingerprint, if one was computed
+    pub fn get_fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// How different this source sounds from `other`, from 0.0 to 1.0
+    ///
+    /// `None` if either layer has no fingerprint.
+    pub fn fingerprint_distance(&self, other: &Layer0) -> Option<f32> {
+        fingerprint_distance(self.get_fingerprint()?, other.get_fingerprint()?)
+    }
+
     /// Verify the integrity of the source file by comparing checksums
     ///
     /// # Returns
@@ -280,11 +330,7 @@ mod tests {
         assert!(layer0.verify_integrity().unwrap());
 
         // Modify the file
-        let mut file = fs::OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(&wav_path)
-            .unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&wav_path).unwrap();
         file.write_all(b"modified").unwrap();
 
         // Integrity should now fail
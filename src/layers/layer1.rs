@@ -11,22 +11,41 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::layer0::Layer0;
+use crate::engine::{fingerprint_distance, FINGERPRINT_CHANGE_THRESHOLD};
 use crate::error::{NuevaError, Result};
+use crate::neural::{NeuralModelInfo, NeuralModelParams, ProcessingResult};
 
 /// Metadata about AI processing applied to Layer 1
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Layer1Metadata {
     /// Name of the AI model used (e.g., "style-transfer", "denoise", "restore")
     pub model_used: Option<String>,
+    /// Human-readable model name (e.g., "ACE-Step 1.5")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    /// Model version that produced this layer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_version: Option<String>,
     /// The user prompt that triggered the AI processing
     pub prompt: Option<String>,
-    /// Parameters passed to the AI model
+    /// Parameters passed to the AI model (the full `NeuralModelParams`
+    /// when recorded via `Layer1::record_generation`)
     pub processing_params: Value,
     /// ISO 8601 timestamp of when processing was applied
     pub processed_at: Option<String>,
     /// List of intentional artifacts for context-aware processing
     /// (e.g., ["vinyl_crackle", "tape_hiss"] for lo-fi aesthetic)
     pub intentional_artifacts: Vec<String>,
+    /// Seed the model used, so generative output can be reproduced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Checksum of the Layer 0 source this layer was derived from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
+    /// Perceptual fingerprint of the Layer 0 source this layer was
+    /// derived from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_fingerprint: Option<String>,
 }
 
 impl Layer1Metadata {
@@ -34,10 +53,15 @@ impl Layer1Metadata {
     pub fn new() -> Self {
         Self {
             model_used: None,
+            model_name: None,
+            model_version: None,
             prompt: None,
             processing_params: Value::Null,
             processed_at: None,
             intentional_artifacts: Vec::new(),
+            seed: None,
+            source_hash: None,
+            source_fingerprint: None,
         }
     }
 
@@ -46,13 +70,43 @@ impl Layer1Metadata {
         self.model_used.is_some()
     }
 
+    /// The exact neural parameters used, if they were recorded
+    pub fn neural_params(&self) -> Option<NeuralModelParams> {
+        serde_json::from_value(self.processing_params.clone()).ok()
+    }
+
+    /// One-line description of where this layer came from, e.g.
+    /// "Generated by ACE-Step 1.5 v1.5 from prompt 'jazz version' (seed 42)"
+    pub fn provenance(&self) -> Option<String> {
+        let model = self.model_used.as_ref()?;
+        let mut line = format!(
+            "Generated by {}",
+            self.model_name.as_deref().unwrap_or(model)
+        );
+        if let Some(version) = &self.model_version {
+            line.push_str(&format!(" v{}", version));
+        }
+        if let Some(prompt) = &self.prompt {
+            line.push_str(&format!(" from prompt '{}'", prompt));
+        }
+        if let Some(seed) = self.seed {
+            line.push_str(&format!(" (seed {})", seed));
+        }
+        Some(line)
+    }
+
     /// Clear all metadata (reset to pristine)
     pub fn clear(&mut self) {
         self.model_used = None;
+        self.model_name = None;
+        self.model_version = None;
         self.prompt = None;
         self.processing_params = Value::Null;
         self.processed_at = None;
         self.intentional_artifacts.clear();
+        self.seed = None;
+        self.source_hash = None;
+        self.source_fingerprint = None;
     }
 }
 
@@ -177,6 +231,59 @@ impl Layer1 {
         self.is_pristine = false;
     }
 
+    /// Record the full provenance of a neural generation
+    ///
+    /// Stores the model identity, exact parameters, prompt, seed and the
+    /// checksum and fingerprint of the Layer 0 source, and marks this layer
+    /// as processed.
+    pub fn record_generation(
+        &mut self,
+        model: &NeuralModelInfo,
+        prompt: &str,
+        params: &NeuralModelParams,
+        result: &ProcessingResult,
+        source: &Layer0,
+    ) {
+        let params_json = serde_json::to_value(params).unwrap_or(Value::Null);
+        self.mark_processed(&model.id, prompt, params_json);
+        self.metadata.model_name = Some(model.name.clone());
+        self.metadata.model_version = Some(model.version.clone());
+        self.metadata.seed = result.seed.or(params.seed);
+        self.metadata.source_hash = Some(source.get_checksum().to_string());
+        self.metadata.source_fingerprint = source.get_fingerprint().map(str::to_string);
+        for artifact in &result.intentional_artifacts {
+            self.add_intentional_artifact(artifact);
+        }
+    }
+
+    /// Whether Layer 0 changed since this layer was generated
+    ///
+    /// When both fingerprints are known, only a fingerprint distance above
+    /// `FINGERPRINT_CHANGE_THRESHOLD` counts, so re-encoding the source
+    /// doesn't make this layer stale. Otherwise any change to the source
+    /// file does. Layers without a recorded source hash are never
+    /// considered stale.
+    pub fn is_stale(&self, source: &Layer0) -> bool {
+        let distance = self
+            .metadata
+            .source_fingerprint
+            .as_deref()
+            .zip(source.get_fingerprint())
+            .and_then(|(recorded, current)| fingerprint_distance(recorded, current));
+        if let Some(distance) = distance {
+            return distance > FINGERPRINT_CHANGE_THRESHOLD;
+        }
+        self.metadata
+            .source_hash
+            .as_deref()
+            .is_some_and(|hash| hash != source.get_checksum())
+    }
+
+    /// Record the seed reported by the model (see `ProcessingResult::seed`)
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.metadata.seed = seed;
+    }
+
     /// Add intentional artifacts to the metadata
     ///
     /// This is used for context-aware processing, allowing the system
@@ -332,6 +439,25 @@ mod tests {
         assert!(layer1.get_metadata().processed_at.is_some());
     }
 
+    #[test]
+    fn test_seed_recorded_in_metadata() {
+        let dir = tempdir().unwrap();
+        let wav_path = create_test_wav(dir.path(), "source.wav");
+
+        let layer0 = Layer0::new(wav_path).unwrap();
+        let mut layer1 = Layer1::from_layer0(&layer0, dir.path()).unwrap();
+
+        layer1.mark_processed("ace-step", "jazz version", serde_json::json!({}));
+        layer1.set_seed(Some(1234));
+
+        let json = serde_json::to_string(&layer1).unwrap();
+        let restored: Layer1 = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_metadata().seed, Some(1234));
+
+        layer1.reset_to_source(&layer0).unwrap();
+        assert_eq!(layer1.get_metadata().seed, None);
+    }
+
     #[test]
     fn test_reset_to_source() {
         let dir = tempdir().unwrap();
@@ -9,6 +9,19 @@ use serde_json::Value;
 
 use crate::error::{NuevaError, Result};
 
+/// The first ID of the form `{effect_type}-{n}` for which `is_taken` is
+/// false
+pub fn generate_effect_id(effect_type: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    let mut counter = 1;
+    loop {
+        let id = format!("{}-{}", effect_type, counter);
+        if !is_taken(&id) {
+            return id;
+        }
+        counter += 1;
+    }
+}
+
 /// State of a single DSP effect in the chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EffectState {
@@ -239,14 +252,7 @@ impl Layer2 {
 
     /// Generate a unique ID for a new effect of the given type
     pub fn generate_id(&self, effect_type: &str) -> String {
-        let mut counter = 1;
-        loop {
-            let id = format!("{}-{}", effect_type, counter);
-            if self.get_effect(&id).is_none() {
-                return id;
-            }
-            counter += 1;
-        }
+        generate_effect_id(effect_type, |id| self.get_effect(id).is_some())
     }
 
     /// Duplicate an effect and add it after the original
@@ -4,13 +4,17 @@
 //! - Layer 0: Immutable source storage
 //! - Layer 1: AI state buffer
 //! - Layer 2: DSP chain (real-time)
+//!
+//! Layers 0 and 1 can be blended so only part of an AI effect is heard.
 
+mod blend;
 mod layer0;
 mod layer1;
 mod layer2;
 mod project;
 
+pub use blend::{BlendMode, LayerBlend};
 pub use layer0::{AudioFormat, Layer0};
 pub use layer1::{Layer1, Layer1Metadata};
-pub use layer2::{EffectState, Layer2};
+pub use layer2::{generate_effect_id, EffectState, Layer2};
 pub use project::{LayerPreservationPolicy, Project, ProjectStateSummary};
@@ -6,18 +6,23 @@
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
+use super::blend::LayerBlend;
 use super::layer0::Layer0;
 use super::layer1::{Layer1, Layer1Metadata};
 use super::layer2::Layer2;
+use crate::engine::{import_audio_at, AudioBuffer, FINGERPRINT_CHANGE_THRESHOLD};
 use crate::error::{NuevaError, Result};
+use crate::neural::{NeuralModelInfo, NeuralModelParams, ProcessingResult};
 
 /// Policy for handling Layer 2 (DSP chain) during AI processing
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LayerPreservationPolicy {
     /// Keep the existing DSP chain (default behavior)
+    #[default]
     PreserveL2,
     /// Clear the DSP chain
     ResetL2,
@@ -27,12 +32,6 @@ pub enum LayerPreservationPolicy {
     Smart,
 }
 
-impl Default for LayerPreservationPolicy {
-    fn default() -> Self {
-        LayerPreservationPolicy::PreserveL2
-    }
-}
-
 /// Project manifest stored as JSON
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ProjectManifest {
@@ -43,12 +42,16 @@ struct ProjectManifest {
     layer0: Layer0Manifest,
     layer1: Layer1Manifest,
     layer2: Layer2,
+    #[serde(default)]
+    blend: LayerBlend,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Layer0Manifest {
     source_path: PathBuf,
     checksum: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<String>,
     created_at: String,
 }
 
@@ -79,6 +82,15 @@ pub struct Project {
     pub created_at: String,
     /// ISO 8601 timestamp of last modification
     pub modified_at: String,
+    /// How Layer 1 is mixed over Layer 0 for playback and rendering
+    blend: LayerBlend,
+    /// Set when Layer 1 or the blend changed; the cached mix is stale
+    ai_dirty: bool,
+    /// Cached result of the last blend
+    blended: Option<AudioBuffer>,
+    /// Length and modification time of the source file when it last
+    /// matched Layer 0
+    source_stamp: Option<(u64, SystemTime)>,
 }
 
 impl Project {
@@ -137,6 +149,10 @@ impl Project {
             layer2,
             created_at: timestamp.clone(),
             modified_at: timestamp,
+            blend: LayerBlend::default(),
+            ai_dirty: true,
+            blended: None,
+            source_stamp: None,
         };
 
         // Save the initial project state
@@ -147,6 +163,9 @@ impl Project {
 
     /// Load an existing project from disk
     ///
+    /// Layer 0 keeps the fingerprint saved with it unless the source file
+    /// changed since (see [`refresh_source`](Self::refresh_source)).
+    ///
     /// # Arguments
     /// * `project_dir` - Path to the project directory
     pub fn load(project_dir: &Path) -> Result<Self> {
@@ -164,7 +183,12 @@ impl Project {
         let manifest: ProjectManifest = serde_json::from_reader(reader)?;
 
         // Reconstruct Layer 0
-        let layer0 = Layer0::new(manifest.layer0.source_path)?;
+        let layer0 = Layer0::restore(
+            manifest.layer0.source_path,
+            manifest.layer0.checksum,
+            manifest.layer0.fingerprint,
+            manifest.layer0.created_at,
+        )?;
 
         // Reconstruct Layer 1
         let layer1 = Layer1::from_path(
@@ -173,7 +197,7 @@ impl Project {
             manifest.layer1.is_pristine,
         );
 
-        Ok(Self {
+        let mut project = Self {
             name: manifest.name,
             project_dir: project_dir.to_path_buf(),
             layer0,
@@ -181,7 +205,13 @@ impl Project {
             layer2: manifest.layer2,
             created_at: manifest.created_at,
             modified_at: manifest.modified_at,
-        })
+            blend: manifest.blend,
+            ai_dirty: true,
+            blended: None,
+            source_stamp: None,
+        };
+        project.refresh_source()?;
+        Ok(project)
     }
 
     /// Save the project state to disk
@@ -194,6 +224,7 @@ impl Project {
             layer0: Layer0Manifest {
                 source_path: self.layer0.get_source_path().to_path_buf(),
                 checksum: self.layer0.get_checksum().to_string(),
+                fingerprint: self.layer0.get_fingerprint().map(str::to_string),
                 created_at: self.layer0.get_created_at().to_string(),
             },
             layer1: Layer1Manifest {
@@ -202,6 +233,7 @@ impl Project {
                 is_pristine: self.layer1.is_pristine(),
             },
             layer2: self.layer2.clone(),
+            blend: self.blend,
         };
 
         let manifest_path = self.project_dir.join("project.json");
@@ -251,6 +283,7 @@ impl Project {
         self.layer0 = new_layer0;
         self.layer1 = new_layer1;
         self.layer2.clear();
+        self.ai_dirty = true;
         self.modified_at = current_timestamp();
 
         // Save the updated project
@@ -259,9 +292,137 @@ impl Project {
         Ok(())
     }
 
+    /// Store a neural model's output as the new Layer 1
+    ///
+    /// Copies the result audio into the Layer 1 file (if the model wrote
+    /// it elsewhere), records full provenance and saves the project.
+    pub fn apply_ai_result(
+        &mut self,
+        model: &NeuralModelInfo,
+        prompt: &str,
+        params: &NeuralModelParams,
+        result: &ProcessingResult,
+    ) -> Result<()> {
+        if !result.success {
+            return Err(NuevaError::AiProcessingError {
+                reason: result.description.clone(),
+            });
+        }
+
+        if let Some(output) = result.output_path.as_deref().map(Path::new) {
+            if output.exists() && output != self.layer1.get_audio_path() {
+                fs::copy(output, self.layer1.get_audio_path()).map_err(|e| {
+                    NuevaError::LayerError {
+                        reason: format!("Failed to store AI output in Layer 1: {}", e),
+                    }
+                })?;
+            }
+        }
+
+        self.layer1
+            .record_generation(model, prompt, params, result, &self.layer0);
+        self.ai_dirty = true;
+        self.modified_at = current_timestamp();
+        self.save()
+    }
+
+    /// Check if Layer 1 was generated from a different Layer 0 than the
+    /// current one
+    pub fn is_layer1_stale(&self) -> bool {
+        self.layer1.is_stale(&self.layer0)
+    }
+
+    /// Re-read Layer 0 after its file changed on disk
+    ///
+    /// Returns whether the audio itself changed: a fingerprint distance
+    /// above `FINGERPRINT_CHANGE_THRESHOLD`, or any change to the file if
+    /// either fingerprint is missing. Only then is the cached blend
+    /// rebuilt; a re-encode just updates the stored checksum.
+    ///
+    /// A file whose length and modification time are unchanged since the
+    /// last check is not read at all.
+    pub fn refresh_source(&mut self) -> Result<bool> {
+        let path = self.layer0.get_source_path();
+        let stamp = fs::metadata(path)
+            .and_then(|meta| Ok((meta.len(), meta.modified()?)))
+            .map_err(|e| NuevaError::FileNotFound {
+                path: path.display().to_string(),
+                source: Some(e),
+            })?;
+        if self.source_stamp == Some(stamp) {
+            return Ok(false);
+        }
+
+        // Only a changed file is decoded to fingerprint it again
+        if self.layer0.verify_integrity()? {
+            self.source_stamp = Some(stamp);
+            return Ok(false);
+        }
+
+        let current = Layer0::new(self.layer0.get_source_path().to_path_buf())?;
+
+        let changed = current
+            .fingerprint_distance(&self.layer0)
+            .is_none_or(|distance| distance > FINGERPRINT_CHANGE_THRESHOLD);
+        if changed {
+            self.ai_dirty = true;
+        }
+        self.layer0 = current;
+        self.source_stamp = Some(stamp);
+        self.modified_at = current_timestamp();
+        Ok(changed)
+    }
+
+    /// Get the Layer 0 / Layer 1 blend
+    pub fn blend(&self) -> LayerBlend {
+        self.blend
+    }
+
+    /// Change how much of Layer 1 is mixed over Layer 0
+    pub fn set_blend(&mut self, blend: LayerBlend) {
+        if blend != self.blend {
+            self.blend = blend;
+            self.ai_dirty = true;
+            self.modified_at = current_timestamp();
+        }
+    }
+
+    /// Mark Layer 1 as changed so the cached blend is rebuilt
+    ///
+    /// Call this after writing Layer 1 audio directly instead of through
+    /// `apply_ai_result`.
+    pub fn mark_ai_dirty(&mut self) {
+        self.ai_dirty = true;
+    }
+
+    /// The audio the DSP chain should run on: Layer 0 and Layer 1 mixed
+    /// according to the current blend
+    ///
+    /// The mix is cached until Layer 1, the blend or the source audio
+    /// changes; the source file is checked each time the mix is needed.
+    pub fn active_audio(&mut self) -> Result<&AudioBuffer> {
+        self.refresh_source()?;
+        if self.ai_dirty || self.blended.is_none() {
+            // Both layers stay at the source's rate, like the rest of the chain
+            let sample_rate = self.layer0.get_format().sample_rate;
+            let source = import_audio_at(self.layer0.get_source_path(), sample_rate)?;
+            let blended = if self.layer1.is_pristine() {
+                source
+            } else {
+                let ai = import_audio_at(self.layer1.get_audio_path(), sample_rate)?;
+                self.blend.apply(&source, &ai)?
+            };
+            self.blended = Some(blended);
+            self.ai_dirty = false;
+        }
+
+        Ok(self.blended.as_ref().expect("blend cache populated above"))
+    }
+
     /// Reset Layer 1 to match Layer 0 (discard AI processing)
     pub fn reset_ai(&mut self) -> Result<()> {
         self.layer1.reset_to_source(&self.layer0)?;
+        self.ai_dirty = true;
         self.modified_at = current_timestamp();
         self.save()?;
         Ok(())
@@ -370,6 +531,8 @@ impl Project {
             name: self.name.clone(),
             has_ai_processing: self.has_ai_processing(),
             ai_model: self.layer1.get_metadata().model_used.clone(),
+            ai_provenance: self.layer1.get_metadata().provenance(),
+            ai_stale: self.is_layer1_stale(),
             dsp_effect_count: self.layer2.len(),
             enabled_effect_count: self.layer2.enabled_count(),
             created_at: self.created_at.clone(),
@@ -384,6 +547,10 @@ pub struct ProjectStateSummary {
     pub name: String,
     pub has_ai_processing: bool,
     pub ai_model: Option<String>,
+    /// How Layer 1 was generated, if by a neural model
+    pub ai_provenance: Option<String>,
+    /// Layer 0 changed after Layer 1 was generated
+    pub ai_stale: bool,
     pub dsp_effect_count: usize,
     pub enabled_effect_count: usize,
     pub created_at: String,
@@ -459,6 +626,8 @@ fn is_leap_year(year: i32) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::layers::BlendMode;
+    use crate::neural::{MockAceStep, MockDenoise, NeuralModel};
     use tempfile::tempdir;
 
     fn create_test_wav(dir: &Path, name: &str) -> PathBuf {
@@ -538,6 +707,259 @@ mod tests {
         assert!(project.layer1.is_pristine());
     }
 
+    #[test]
+    fn test_ai_provenance_round_trip() {
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = create_test_wav(source_dir.path(), "source.wav");
+        let mut project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+
+        let model = MockAceStep::new();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 4)
+            .with_seed(42);
+        let output = project_dir.path().join("ace_out.wav");
+        let result = model
+            .process(project.layer0.get_source_path(), &output, &params)
+            .unwrap();
+        project
+            .apply_ai_result(model.info(), "jazz version", &params, &result)
+            .unwrap();
+
+        let loaded = Project::load(project_dir.path()).unwrap();
+        let metadata = loaded.layer1.get_metadata();
+        assert_eq!(metadata.model_version.as_deref(), Some("1.5-mock"));
+        assert_eq!(metadata.seed, Some(42));
+        assert_eq!(metadata.neural_params().unwrap().seed, Some(42));
+        assert_eq!(
+            metadata.source_hash.as_deref(),
+            Some(loaded.layer0.get_checksum())
+        );
+
+        let summary = loaded.get_state_summary();
+        let provenance = summary.ai_provenance.unwrap();
+        assert!(provenance.contains("ACE-Step 1.5"));
+        assert!(provenance.contains("jazz version"));
+        assert!(provenance.contains("seed 42"));
+        assert!(!summary.ai_stale);
+    }
+
+    #[test]
+    fn test_layer0_change_marks_layer1_stale() {
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = create_test_wav(source_dir.path(), "source.wav");
+        let mut project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+
+        let model = MockDenoise::new();
+        let params = NeuralModelParams::new();
+        let result =
+            ProcessingResult::success("missing.wav".to_string(), "denoised".to_string(), 0);
+        project
+            .apply_ai_result(model.info(), "clean", &params, &result)
+            .unwrap();
+        assert!(!project.is_layer1_stale());
+
+        // Overwrite the source with different audio
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(project.layer0.get_source_path(), spec).unwrap();
+        for i in 0..44100 {
+            writer.write_sample((i % 100) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let loaded = Project::load(project_dir.path()).unwrap();
+        assert!(loaded.is_layer1_stale());
+        assert!(loaded.get_state_summary().ai_stale);
+    }
+
+    /// Write two seconds of mono `signal` at 44.1kHz and `bits` depth
+    fn write_signal(path: &Path, bits: u16, signal: impl Fn(f32) -> f32) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: bits,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let full_scale = (1i32 << (bits - 1)) as f32 - 1.0;
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..88200 {
+            let sample = signal(i as f32 / 44100.0);
+            writer
+                .write_sample((sample * full_scale).round() as i32)
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_reencoded_source_is_not_a_change() {
+        // A rising sweep, so every frame sounds different
+        let sweep = |t: f32| 0.5 * (std::f32::consts::TAU * (200.0 + 400.0 * t) * t).sin();
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = source_dir.path().join("source.wav");
+        write_signal(&source_wav, 16, sweep);
+        let mut project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+
+        let model = MockDenoise::new();
+        let result =
+            ProcessingResult::success("missing.wav".to_string(), "denoised".to_string(), 0);
+        project
+            .apply_ai_result(model.info(), "clean", &NeuralModelParams::new(), &result)
+            .unwrap();
+        project.active_audio().unwrap();
+        assert!(!project.ai_dirty);
+
+        // Same audio at 24 bits: different bytes, same sound
+        let old_checksum = project.layer0.get_checksum().to_string();
+        write_signal(project.layer0.get_source_path(), 24, sweep);
+        assert!(!project.refresh_source().unwrap());
+        assert_ne!(project.layer0.get_checksum(), old_checksum);
+        assert!(!project.ai_dirty);
+        assert!(!project.is_layer1_stale());
+
+        // A falling sweep is a real change
+        write_signal(project.layer0.get_source_path(), 16, |t| {
+            0.5 * (std::f32::consts::TAU * (1000.0 - 300.0 * t) * t).sin()
+        });
+        assert!(project.refresh_source().unwrap());
+        assert!(project.ai_dirty);
+        assert!(project.is_layer1_stale());
+    }
+
+    #[test]
+    fn test_load_reuses_fingerprint_until_source_changes() {
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = source_dir.path().join("source.wav");
+        write_signal(&source_wav, 16, |t| {
+            0.5 * (std::f32::consts::TAU * 440.0 * t).sin()
+        });
+        let project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+        let created_at = project.layer0.get_created_at().to_string();
+
+        // A stored fingerprint is trusted while the file is unchanged
+        let manifest_path = project_dir.path().join("project.json");
+        let mut manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        manifest["layer0"]["fingerprint"] = "stored".into();
+        fs::write(&manifest_path, manifest.to_string()).unwrap();
+        let mut loaded = Project::load(project_dir.path()).unwrap();
+        assert_eq!(loaded.layer0.get_fingerprint(), Some("stored"));
+        assert_eq!(loaded.layer0.get_created_at(), created_at);
+
+        // Rendering notices an edited source and mixes the new audio
+        loaded.active_audio().unwrap();
+        write_signal(loaded.layer0.get_source_path(), 24, |_| 0.25);
+        let audio = loaded.active_audio().unwrap();
+        assert!((audio.samples[0][100] - 0.25).abs() < 1e-3);
+        assert_ne!(loaded.layer0.get_fingerprint(), Some("stored"));
+    }
+
+    #[test]
+    fn test_unchanged_source_file_is_not_rehashed() {
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = source_dir.path().join("source.wav");
+        write_signal(&source_wav, 16, |t| {
+            0.5 * (std::f32::consts::TAU * 440.0 * t).sin()
+        });
+        let mut project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+        project.active_audio().unwrap();
+
+        // Same length and modification time: taken as unchanged unread
+        let path = project.layer0.get_source_path().to_path_buf();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        write_signal(&path, 16, |_| 0.25);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(!project.refresh_source().unwrap());
+
+        // Any other modification time gets it checked
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(project.refresh_source().unwrap());
+    }
+
+    /// Write a constant 48kHz float layer 1 and mark it processed
+    fn write_layer1(project: &mut Project, value: f32) {
+        let buffer = AudioBuffer {
+            samples: vec![vec![value; 48000]; 2],
+            sample_rate: 48000,
+        };
+        crate::engine::export_audio(
+            &buffer,
+            project.layer1.get_audio_path(),
+            crate::engine::ExportFormat::new(48000, 32),
+        )
+        .unwrap();
+        project
+            .layer1
+            .mark_processed("style-transfer", "vintage", serde_json::json!({}));
+        project.mark_ai_dirty();
+    }
+
+    #[test]
+    fn test_blend_endpoints_and_midpoint() {
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = create_test_wav(source_dir.path(), "source.wav");
+        let mut project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+        write_layer1(&mut project, 0.5);
+
+        // Default blend is fully AI
+        let audio = project.active_audio().unwrap();
+        assert!(audio.samples[0].iter().all(|s| *s == 0.5));
+
+        project.set_blend(LayerBlend::new(0.0, BlendMode::EqualPower));
+        // Both layers are read at the source's own 44.1kHz
+        let source_len = project.layer0.get_format().num_samples as usize;
+        let audio = project.active_audio().unwrap();
+        assert_eq!(audio.sample_rate, 44100);
+        assert_eq!(audio.len(), source_len);
+        assert!(audio.samples[0].iter().all(|s| *s == 0.0));
+
+        project.set_blend(LayerBlend::new(0.5, BlendMode::Linear));
+        let audio = project.active_audio().unwrap();
+        assert_eq!(audio.len(), 44100);
+        assert!(audio.samples[0].iter().all(|s| (s - 0.25).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_blend_cache_invalidated_when_ai_dirty() {
+        let source_dir = tempdir().unwrap();
+        let project_dir = tempdir().unwrap();
+        let source_wav = create_test_wav(source_dir.path(), "source.wav");
+        let mut project = Project::create("TestProject", &source_wav, project_dir.path()).unwrap();
+
+        write_layer1(&mut project, 0.5);
+        assert_eq!(project.active_audio().unwrap().samples[0][0], 0.5);
+
+        write_layer1(&mut project, 0.3);
+        assert_eq!(project.active_audio().unwrap().samples[0][0], 0.3);
+
+        project.set_blend(LayerBlend::new(0.5, BlendMode::Linear));
+        project.save().unwrap();
+        let mut loaded = Project::load(project_dir.path()).unwrap();
+        assert_eq!(loaded.blend(), project.blend());
+        assert!((loaded.active_audio().unwrap().samples[0][0] - 0.15).abs() < 1e-6);
+    }
+
     #[test]
     fn test_reset_dsp() {
         let source_dir = tempdir().unwrap();
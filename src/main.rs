@@ -4,7 +4,9 @@
 //!
 //! Usage:
 //!   nueva-cli --help
+//!   nueva-cli --list-params compressor
 //!   nueva-cli create-project ./my_project --input audio.wav
+//!   nueva-cli create-project ./hires_project --input audio.wav --sample-rate 96000
 
 use clap::Parser;
 use env_logger::Env;
@@ -21,41 +23,52 @@ fn main() -> Result<()> {
 
     info!("Nueva Audio Processor v{}", env!("CARGO_PKG_VERSION"));
 
+    if let Some(effect) = cli.list_params {
+        return nueva::cli::commands::list_params(effect.as_deref());
+    }
+
     match cli.command {
         Some(cmd) => handle_command(cmd),
-        None => {
-            // Interactive mode (not implemented yet)
-            println!("Nueva Audio Processor v{}", env!("CARGO_PKG_VERSION"));
-            println!("Use --help for available commands");
-            Ok(())
-        }
+        None => nueva::cli::repl::run(None),
     }
 }
 
 fn handle_command(cmd: Commands) -> Result<()> {
     match cmd {
-        Commands::CreateProject { path, input } => {
-            nueva::cli::commands::create_project(&path, input.as_deref())
-        }
+        Commands::CreateProject {
+            path,
+            input,
+            sample_rate,
+        } => nueva::cli::commands::create_project(&path, input.as_deref(), sample_rate),
         Commands::LoadProject { path } => nueva::cli::commands::load_project(&path),
         Commands::SaveState { path } => nueva::cli::commands::save_state(&path),
-        Commands::Undo { path } => nueva::cli::commands::undo(&path),
+        Commands::Undo { path, pattern } => nueva::cli::commands::undo(&path, pattern.as_deref()),
         Commands::Redo { path } => nueva::cli::commands::redo(&path),
         Commands::History { path } => nueva::cli::commands::show_history(&path),
-        Commands::Bake { path } => nueva::cli::commands::bake(&path),
-        Commands::PrintState { path } => nueva::cli::commands::print_state(&path),
+        Commands::Branches { path, switch } => {
+            nueva::cli::commands::branches(&path, switch.as_deref())
+        }
+        Commands::Diff { path, from, to } => nueva::cli::commands::diff(&path, &from, &to),
+        Commands::Compare { path, reference } => nueva::cli::commands::compare(&path, &reference),
+        Commands::Bake { path, through } => nueva::cli::commands::bake(&path, through.as_deref()),
+        Commands::Marker { path, action } => nueva::cli::commands::marker(&path, &action),
+        Commands::Trim { path, start, end } => nueva::cli::commands::trim(&path, start, end),
+        Commands::PrintState { path, json } => nueva::cli::commands::print_state(&path, json),
         Commands::Agent {
             path,
             prompt,
             tool,
             dry_run,
         } => nueva::cli::commands::agent_process(&path, &prompt, &tool, dry_run),
-        Commands::Process {
-            input,
-            output,
-            prompt,
-            mode,
-            intensity,
-        } => nueva::cli::commands::process_audio(&input, output.as_deref(), &prompt, &mode, intensity),
+        Commands::Render { path, args } => nueva::cli::commands::render(&path, &args),
+        Commands::Process(args) => nueva::cli::commands::process_audio(
+            &args.input,
+            args.output.as_deref(),
+            &args.prompt,
+            &args.mode,
+            args.intensity,
+        ),
+        Commands::Batch(args) => nueva::cli::commands::batch(&args),
+        Commands::Repl { path } => nueva::cli::repl::run(path.as_deref()),
     }
 }
@@ -10,20 +10,25 @@
 //! - Track extraction
 //! - Audio completion
 
-use super::model::{NeuralModel, NeuralModelInfo, NeuralModelParams, ParamSpec, ParamType, ProcessingResult};
-use super::registry::create_model_info;
+use super::model::{
+    NeuralModel, NeuralModelInfo, NeuralModelParams, ParamSpec, ParamType, ProcessingResult,
+    ProgressCallback, ProgressReporter,
+};
+use super::registry::{create_model_info, ACE_STEP_PARAM_COUNT};
 use crate::error::{NuevaError, Result};
 use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader, Write};
+use std::ops::ControlFlow;
 use std::path::Path;
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
 use std::sync::Mutex;
 use std::time::Instant;
 
 /// ACE-Step processing modes
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum AceStepMode {
+    #[default]
     Transform,
     Repaint,
     Cover,
@@ -32,12 +37,6 @@ pub enum AceStepMode {
     Complete,
 }
 
-impl Default for AceStepMode {
-    fn default() -> Self {
-        Self::Transform
-    }
-}
-
 impl std::fmt::Display for AceStepMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -64,7 +63,10 @@ struct BridgeRequest {
 }
 
 /// Response from the Python AI bridge
+///
+/// Mirrors the full bridge protocol; not every field is consumed yet.
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct BridgeResponse {
     success: bool,
     request_id: Option<String>,
@@ -76,7 +78,24 @@ struct BridgeResponse {
     error_code: Option<String>,
 }
 
+/// Progress update emitted by the bridge once per diffusion step,
+/// before the final response for a request
 #[derive(Debug, Deserialize)]
+struct BridgeProgress {
+    step: usize,
+    total_steps: usize,
+}
+
+/// A line read from the bridge: either a progress update or the final response
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BridgeMessage {
+    Progress(BridgeProgress),
+    Response(Box<BridgeResponse>),
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct NeuralChanges {
     model: Option<String>,
     output_path: Option<String>,
@@ -84,10 +103,20 @@ struct NeuralChanges {
     intentional_artifacts: Option<Vec<String>>,
 }
 
+/// The bridge's pipes, held for the length of a request
+struct BridgeIo {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
 /// ACE-Step 1.5 model processor
 pub struct AceStep {
     info: NeuralModelInfo,
+    /// The bridge process; only ever locked briefly, so [`NeuralModel::abort`]
+    /// can kill it while a request is blocked on its output
     bridge_process: Mutex<Option<Child>>,
+    /// Pipes to the bridge, locked for the length of a request
+    bridge_io: Mutex<Option<BridgeIo>>,
     python_path: String,
     bridge_module: String,
 }
@@ -150,6 +179,7 @@ impl AceStep {
                         description: "Processing mode".to_string(),
                         default: Some(serde_json::json!("transform")),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "prompt".to_string(),
@@ -157,6 +187,7 @@ impl AceStep {
                         description: "Text description of desired output".to_string(),
                         default: None,
                         required: true,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "preserve_melody".to_string(),
@@ -164,6 +195,7 @@ impl AceStep {
                         description: "Whether to preserve the original melody".to_string(),
                         default: Some(serde_json::json!(true)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "intensity".to_string(),
@@ -171,6 +203,7 @@ impl AceStep {
                         description: "Transformation intensity".to_string(),
                         default: Some(serde_json::json!(0.7)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "inference_steps".to_string(),
@@ -178,6 +211,7 @@ impl AceStep {
                         description: "Number of diffusion steps".to_string(),
                         default: Some(serde_json::json!(8)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "guidance_scale".to_string(),
@@ -185,91 +219,164 @@ impl AceStep {
                         description: "How closely to follow the prompt".to_string(),
                         default: Some(serde_json::json!(3.0)),
                         required: false,
+                        unit: None,
                     },
                 ],
-            ),
+            )
+            .with_param_count(ACE_STEP_PARAM_COUNT),
             bridge_process: Mutex::new(None),
+            bridge_io: Mutex::new(None),
             python_path,
             bridge_module,
         }
     }
 
-    /// Start the Python bridge process if not already running
-    fn ensure_bridge(&self) -> Result<()> {
+    /// Start a new Python bridge process, replacing any previous one
+    fn start_bridge(&self) -> Result<BridgeIo> {
+        self.kill_bridge();
+
+        let mut child = Command::new(&self.python_path)
+            .args(["-m", &self.bridge_module])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| NuevaError::AiProcessingError {
+                reason: format!("Failed to start Python bridge: {}", e),
+            })?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(NuevaError::ProcessingError {
+                reason: "Bridge pipes not available".to_string(),
+            });
+        };
+
         let mut guard = self.bridge_process.lock().map_err(|_| NuevaError::ProcessingError {
             reason: "Failed to acquire bridge lock".to_string(),
         })?;
+        *guard = Some(child);
 
-        if guard.is_none() {
-            let child = Command::new(&self.python_path)
-                .args(["-m", &self.bridge_module])
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::inherit())
-                .spawn()
-                .map_err(|e| NuevaError::AiProcessingError {
-                    reason: format!("Failed to start Python bridge: {}", e),
-                })?;
+        Ok(BridgeIo {
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
 
-            *guard = Some(child);
+    /// Kill the bridge process, if running; it is restarted on the next
+    /// request
+    fn kill_bridge(&self) {
+        let child = match self.bridge_process.lock() {
+            Ok(mut guard) => guard.take(),
+            Err(_) => None,
+        };
+        if let Some(mut child) = child {
+            if let Ok(None) = child.try_wait() {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
         }
+    }
 
-        Ok(())
+    /// Whether the bridge process is running
+    fn bridge_running(&self) -> bool {
+        self.bridge_process
+            .lock()
+            .map(|guard| guard.is_some())
+            .unwrap_or(false)
     }
 
     /// Send a request to the Python bridge
     fn send_request(&self, request: &BridgeRequest) -> Result<BridgeResponse> {
-        self.ensure_bridge()?;
-
-        let mut guard = self.bridge_process.lock().map_err(|_| NuevaError::ProcessingError {
-            reason: "Failed to acquire bridge lock".to_string(),
-        })?;
+        let mut ignore = |_| ControlFlow::Continue(());
+        self.send_request_with_progress(request, &mut ProgressReporter::new(&mut ignore))
+    }
 
-        let child = guard.as_mut().ok_or_else(|| NuevaError::ProcessingError {
-            reason: "Bridge process not running".to_string(),
-        })?;
+    /// Send a request to the Python bridge, forwarding progress lines
+    ///
+    /// The bridge is started on first use. On any failure, including
+    /// cancellation and [`NeuralModel::abort`], the bridge process is
+    /// killed, since it cannot be interrupted mid-step and its pipes are
+    /// left mid-request; it is restarted on the next request.
+    fn send_request_with_progress(
+        &self,
+        request: &BridgeRequest,
+        progress: &mut ProgressReporter<'_>,
+    ) -> Result<BridgeResponse> {
+        let mut io = self
+            .bridge_io
+            .lock()
+            .map_err(|_| NuevaError::ProcessingError {
+                reason: "Failed to acquire bridge lock".to_string(),
+            })?;
+
+        // An aborted bridge leaves its pipes behind
+        let bridge = match io.as_mut() {
+            Some(bridge) if self.bridge_running() => bridge,
+            _ => io.insert(self.start_bridge()?),
+        };
 
-        // Write request to stdin
-        let stdin = child.stdin.as_mut().ok_or_else(|| NuevaError::ProcessingError {
-            reason: "Bridge stdin not available".to_string(),
-        })?;
+        let result = Self::exchange(bridge, request, progress);
+        if result.is_err() {
+            *io = None;
+            self.kill_bridge();
+        }
+        result
+    }
 
+    /// Write `request` and read progress lines until its final response
+    fn exchange(
+        bridge: &mut BridgeIo,
+        request: &BridgeRequest,
+        progress: &mut ProgressReporter<'_>,
+    ) -> Result<BridgeResponse> {
         let request_json = serde_json::to_string(request).map_err(|e| NuevaError::ProcessingError {
             reason: format!("Failed to serialize request: {}", e),
         })?;
 
-        writeln!(stdin, "{}", request_json).map_err(|e| NuevaError::ProcessingError {
+        writeln!(bridge.stdin, "{}", request_json).map_err(|e| NuevaError::ProcessingError {
             reason: format!("Failed to write to bridge: {}", e),
         })?;
 
-        stdin.flush().map_err(|e| NuevaError::ProcessingError {
-            reason: format!("Failed to flush bridge stdin: {}", e),
-        })?;
-
-        // Read response from stdout
-        let stdout = child.stdout.as_mut().ok_or_else(|| NuevaError::ProcessingError {
-            reason: "Bridge stdout not available".to_string(),
-        })?;
+        bridge
+            .stdin
+            .flush()
+            .map_err(|e| NuevaError::ProcessingError {
+                reason: format!("Failed to flush bridge stdin: {}", e),
+            })?;
+
+        loop {
+            let mut line = String::new();
+            let read =
+                bridge
+                    .stdout
+                    .read_line(&mut line)
+                    .map_err(|e| NuevaError::ProcessingError {
+                        reason: format!("Failed to read from bridge: {}", e),
+                    })?;
+            if read == 0 {
+                return Err(NuevaError::BridgeConnectionError {
+                    message: "Bridge closed its output".to_string(),
+                });
+            }
 
-        let mut reader = BufReader::new(stdout);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).map_err(|e| NuevaError::ProcessingError {
-            reason: format!("Failed to read from bridge: {}", e),
-        })?;
+            let message: BridgeMessage =
+                serde_json::from_str(&line).map_err(|e| NuevaError::ProcessingError {
+                    reason: format!("Failed to parse bridge response: {}", e),
+                })?;
 
-        let response: BridgeResponse = serde_json::from_str(&response_line).map_err(|e| {
-            NuevaError::ProcessingError {
-                reason: format!("Failed to parse bridge response: {}", e),
+            match message {
+                BridgeMessage::Progress(update) => {
+                    progress.report_step(update.step, update.total_steps)?;
+                }
+                BridgeMessage::Response(response) => return Ok(*response),
             }
-        })?;
-
-        Ok(response)
+        }
     }
 
     /// Check if ACE-Step is available
     pub fn check_availability(&self) -> Result<bool> {
-        self.ensure_bridge()?;
-
         let request = BridgeRequest {
             action: "get_model_info".to_string(),
             request_id: Some(uuid::Uuid::new_v4().to_string()),
@@ -293,6 +400,12 @@ impl Default for AceStep {
     }
 }
 
+impl Drop for AceStep {
+    fn drop(&mut self) {
+        self.kill_bridge();
+    }
+}
+
 impl NeuralModel for AceStep {
     fn info(&self) -> &NeuralModelInfo {
         &self.info
@@ -303,8 +416,27 @@ impl NeuralModel for AceStep {
         input_path: &Path,
         output_path: &Path,
         params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        self.process_with_progress(input_path, output_path, params, &mut |_| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Forwards the bridge's per-diffusion-step progress
+    fn process_with_progress(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+        on_progress: ProgressCallback<'_>,
     ) -> Result<ProcessingResult> {
         let start = Instant::now();
+        let mut progress = ProgressReporter::new(on_progress);
+        progress.report(0.0)?;
+
+        // Pin the seed before sending so a random run can be replayed
+        let seed = params.seed_or_random();
+        let params = &params.clone().with_seed(seed);
 
         let prompt = params.get_string("prompt").unwrap_or_else(|| "transform audio".to_string());
 
@@ -318,7 +450,7 @@ impl NeuralModel for AceStep {
             model_params: serde_json::to_value(params).unwrap_or_default(),
         };
 
-        let response = self.send_request(&request)?;
+        let response = self.send_request_with_progress(&request, &mut progress)?;
 
         if !response.success {
             return Err(NuevaError::AiProcessingError {
@@ -343,12 +475,19 @@ impl NeuralModel for AceStep {
             format!("ACE-Step processed: '{}'", prompt)
         });
 
-        Ok(ProcessingResult::success(
+        let result = ProcessingResult::success(
             output_path.to_string_lossy().to_string(),
             description,
             processing_time,
-        )
-        .with_artifacts(artifacts))
+        );
+        progress.finish();
+
+        Ok(result.with_artifacts(artifacts).with_seed(seed))
+    }
+
+    /// Kills the bridge, ending a request blocked on its output
+    fn abort(&self) {
+        self.kill_bridge();
     }
 
     fn is_available(&self) -> bool {
@@ -386,4 +525,21 @@ mod tests {
         assert_eq!(params.get_string("mode"), Some("cover".to_string()));
         assert_eq!(params.get_f32("intensity"), Some(0.8));
     }
+
+    #[test]
+    fn test_bridge_message_parsing() {
+        let progress: BridgeMessage =
+            serde_json::from_str(r#"{"request_id": "r1", "step": 3, "total_steps": 8}"#).unwrap();
+        assert!(matches!(
+            progress,
+            BridgeMessage::Progress(BridgeProgress {
+                step: 3,
+                total_steps: 8
+            })
+        ));
+
+        let response: BridgeMessage =
+            serde_json::from_str(r#"{"success": true, "message": "done"}"#).unwrap();
+        assert!(matches!(response, BridgeMessage::Response(r) if r.success));
+    }
 }
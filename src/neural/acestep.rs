@@ -188,6 +188,7 @@ impl AceStepModel {
                 "Transient softening on aggressive percussion".to_string(),
             ],
             vram_requirement_gb: 4.0,
+            param_count: Some(ACE_STEP_PARAM_COUNT),
             inference_time: "1-30 seconds depending on GPU".to_string(),
             supported_params: vec![
                 ParamSpec {
@@ -205,6 +206,7 @@ impl AceStepModel {
                     description: "Processing mode".to_string(),
                     default: Some(serde_json::json!("transform")),
                     required: false,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "prompt".to_string(),
@@ -212,6 +214,7 @@ impl AceStepModel {
                     description: "Text description of desired output".to_string(),
                     default: None,
                     required: true,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "intensity".to_string(),
@@ -219,6 +222,7 @@ impl AceStepModel {
                     description: "Transformation intensity (0-1)".to_string(),
                     default: Some(serde_json::json!(0.7)),
                     required: false,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "preserve_melody".to_string(),
@@ -226,6 +230,7 @@ impl AceStepModel {
                     description: "Preserve original melody (cover mode)".to_string(),
                     default: Some(serde_json::json!(true)),
                     required: false,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "preserve_tempo".to_string(),
@@ -233,6 +238,7 @@ impl AceStepModel {
                     description: "Preserve original tempo".to_string(),
                     default: Some(serde_json::json!(true)),
                     required: false,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "preserve_key".to_string(),
@@ -240,6 +246,7 @@ impl AceStepModel {
                     description: "Preserve original key".to_string(),
                     default: Some(serde_json::json!(true)),
                     required: false,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "extract_target".to_string(),
@@ -255,6 +262,7 @@ impl AceStepModel {
                     description: "What to extract (extract mode)".to_string(),
                     default: Some(serde_json::json!("vocals")),
                     required: false,
+                    unit: None,
                 },
                 ParamSpec {
                     name: "seed".to_string(),
@@ -265,6 +273,7 @@ impl AceStepModel {
                     description: "Random seed for reproducibility (-1 for random)".to_string(),
                     default: Some(serde_json::json!(-1)),
                     required: false,
+                    unit: None,
                 },
             ],
         }
@@ -0,0 +1,479 @@
+//! Neural result cache
+//!
+//! Re-running the same model with the same parameters on the same audio
+//! is expensive and pointless. Results are cached on disk next to the
+//! Layer 1 audio, keyed by a hash of the input samples, the model and its
+//! serialized parameters.
+//!
+//! Only seeded runs are cached: a run without a seed draws a random one, so
+//! replaying an earlier result would pass off one random draw as the
+//! answer to every later request.
+//!
+//! The cache remembers the Layer 0 checksum it was built against and
+//! empties itself when the source changes. Unreadable entries are treated
+//! as misses, never as errors.
+
+use super::model::{
+    NeuralModel, NeuralModelInfo, NeuralModelParams, ProcessingResult, ProgressCallback,
+    ProgressReporter,
+};
+use crate::engine::buffer::AudioBuffer;
+use crate::engine::io::import_audio_native;
+use crate::error::Result;
+use crate::layers::{Layer0, Layer1};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+/// Cache directory name (next to the Layer 1 audio)
+pub const NEURAL_CACHE_DIR: &str = "neural_cache";
+
+/// File recording the Layer 0 checksum the cache belongs to
+const SOURCE_CHECKSUM_FILE: &str = "source.sha256";
+
+/// Cached result stored as `<key>.json`, with the output audio as `<key>.wav`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    model_id: String,
+    result: ProcessingResult,
+}
+
+/// On-disk cache of neural processing results
+#[derive(Debug, Clone)]
+pub struct NeuralCache {
+    dir: PathBuf,
+}
+
+impl NeuralCache {
+    /// Open (or create) a cache in an explicit directory
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Open the cache in the project's Layer 1 storage, clearing it if
+    /// Layer 0 has changed since the cache was written
+    pub fn for_layers(layer0: &Layer0, layer1: &Layer1) -> Result<Self> {
+        let base = layer1
+            .get_audio_path()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let cache = Self::new(base.join(NEURAL_CACHE_DIR))?;
+        cache.sync_source(layer0.get_checksum())?;
+        Ok(cache)
+    }
+
+    /// Cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Clear the cache if it was built against a different source checksum
+    ///
+    /// Returns true if entries were invalidated.
+    pub fn sync_source(&self, source_checksum: &str) -> Result<bool> {
+        let marker = self.dir.join(SOURCE_CHECKSUM_FILE);
+        let stored = fs::read_to_string(&marker).ok();
+        if stored.as_deref().map(str::trim) == Some(source_checksum) {
+            return Ok(false);
+        }
+
+        let invalidated = stored.is_some();
+        if invalidated {
+            self.clear()?;
+        }
+        fs::write(&marker, source_checksum)?;
+        Ok(invalidated)
+    }
+
+    /// Remove all cached entries
+    pub fn clear(&self) -> Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let is_entry = path
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "wav");
+            if is_entry {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the cache key for a model run
+    pub fn key(info: &NeuralModelInfo, input: &AudioBuffer, params: &NeuralModelParams) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(info.id.as_bytes());
+        hasher.update([0]);
+        hasher.update(info.version.as_bytes());
+        hasher.update([0]);
+
+        // serde_json maps are sorted, so equal params always serialize equally
+        let params_json = serde_json::to_value(params)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        hasher.update(params_json.as_bytes());
+        hasher.update([0]);
+
+        hasher.update(input.sample_rate.to_le_bytes());
+        for channel in &input.samples {
+            hasher.update((channel.len() as u64).to_le_bytes());
+            for sample in channel {
+                hasher.update(sample.to_le_bytes());
+            }
+        }
+
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Look up a cached result, copying its audio to `output_path`
+    ///
+    /// Returns `None` on a miss or if the entry is unreadable.
+    pub fn get(&self, key: &str, output_path: &Path) -> Option<ProcessingResult> {
+        let entry_path = self.entry_path(key);
+        let audio_path = self.audio_path(key);
+
+        let entry = fs::read_to_string(&entry_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<CacheEntry>(&json).ok())
+            .filter(|entry| entry.key == key && audio_path.exists());
+
+        let Some(entry) = entry else {
+            if entry_path.exists() {
+                log::warn!("Discarding unreadable neural cache entry {}", key);
+                let _ = fs::remove_file(&entry_path);
+                let _ = fs::remove_file(&audio_path);
+            }
+            return None;
+        };
+
+        fs::copy(&audio_path, output_path).ok()?;
+
+        let mut result = entry.result;
+        result.output_path = Some(output_path.to_string_lossy().to_string());
+        result
+            .metadata
+            .insert("cache_hit".to_string(), serde_json::json!(true));
+        Some(result)
+    }
+
+    /// Store a successful result whose audio was written to `output_path`
+    pub fn put(
+        &self,
+        key: &str,
+        model_id: &str,
+        result: &ProcessingResult,
+        output_path: &Path,
+    ) -> Result<()> {
+        fs::copy(output_path, self.audio_path(key))?;
+
+        let entry = CacheEntry {
+            key: key.to_string(),
+            model_id: model_id.to_string(),
+            result: result.clone(),
+        };
+        fs::write(self.entry_path(key), serde_json::to_string_pretty(&entry)?)?;
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn audio_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.wav", key))
+    }
+}
+
+/// Wraps a model so identical requests are served from a [`NeuralCache`]
+pub struct CachedModel<M: NeuralModel> {
+    inner: M,
+    cache: NeuralCache,
+}
+
+impl<M: NeuralModel> CachedModel<M> {
+    pub fn new(inner: M, cache: NeuralCache) -> Self {
+        Self { inner, cache }
+    }
+
+    /// The wrapped model
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// The cache in use
+    pub fn cache(&self) -> &NeuralCache {
+        &self.cache
+    }
+}
+
+impl<M: NeuralModel> NeuralModel for CachedModel<M> {
+    fn info(&self) -> &NeuralModelInfo {
+        self.inner.info()
+    }
+
+    fn process(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        self.process_with_progress(input_path, output_path, params, &mut |_| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// A hit reports completion at once; a miss forwards the wrapped
+    /// model's progress and cancellation
+    fn process_with_progress(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<ProcessingResult> {
+        // Unseeded runs are random, and inputs we can't decode can't be
+        // keyed; just run the model
+        let input = match params.seed {
+            Some(_) => import_audio_native(input_path).ok(),
+            None => None,
+        };
+        let Some(input) = input else {
+            return self
+                .inner
+                .process_with_progress(input_path, output_path, params, on_progress);
+        };
+
+        let key = NeuralCache::key(self.inner.info(), &input, params);
+        if let Some(result) = self.cache.get(&key, output_path) {
+            let mut progress = ProgressReporter::new(on_progress);
+            progress.finish();
+            return Ok(result);
+        }
+
+        let result =
+            self.inner
+                .process_with_progress(input_path, output_path, params, on_progress)?;
+        if result.success && output_path.exists() {
+            if let Err(e) = self.cache.put(&key, self.inner.id(), &result, output_path) {
+                log::warn!("Failed to cache neural result: {}", e);
+            }
+        }
+        Ok(result)
+    }
+
+    fn abort(&self) {
+        self.inner.abort()
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn validate_params(&self, params: &NeuralModelParams) -> Result<()> {
+        self.inner.validate_params(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::io::{export_audio, generate_test_tone, ExportFormat};
+    use crate::neural::registry::create_model_info;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Copies input to output and counts calls
+    struct CountingModel {
+        info: NeuralModelInfo,
+        calls: AtomicUsize,
+    }
+
+    impl CountingModel {
+        fn new() -> Self {
+            Self {
+                info: create_model_info(
+                    "counting",
+                    "Counting",
+                    "1.0",
+                    "Copies input to output",
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    0.0,
+                    "instant",
+                    vec![],
+                ),
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl NeuralModel for CountingModel {
+        fn info(&self) -> &NeuralModelInfo {
+            &self.info
+        }
+
+        fn process(
+            &self,
+            input_path: &Path,
+            output_path: &Path,
+            _params: &NeuralModelParams,
+        ) -> Result<ProcessingResult> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            fs::copy(input_path, output_path)?;
+            Ok(ProcessingResult::success(
+                output_path.to_string_lossy().to_string(),
+                "copied".to_string(),
+                0,
+            ))
+        }
+    }
+
+    fn setup() -> (TempDir, PathBuf, CachedModel<CountingModel>) {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("in.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 48000),
+            &input,
+            ExportFormat::default(),
+        )
+        .unwrap();
+        let cache = NeuralCache::new(temp.path().join(NEURAL_CACHE_DIR)).unwrap();
+        (temp, input, CachedModel::new(CountingModel::new(), cache))
+    }
+
+    fn calls(model: &CachedModel<CountingModel>) -> usize {
+        model.inner().calls.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn test_same_request_hits_cache() {
+        let (temp, input, model) = setup();
+        let params = NeuralModelParams::new()
+            .with_param("intensity", 0.5)
+            .with_seed(1);
+
+        model
+            .process(&input, &temp.path().join("a.wav"), &params)
+            .unwrap();
+        let out = temp.path().join("b.wav");
+        let result = model.process(&input, &out, &params).unwrap();
+
+        assert_eq!(calls(&model), 1);
+        assert!(out.exists());
+        assert_eq!(result.output_path, Some(out.to_string_lossy().to_string()));
+        assert_eq!(
+            result.metadata.get("cache_hit"),
+            Some(&serde_json::json!(true))
+        );
+    }
+
+    #[test]
+    fn test_changed_param_misses_cache() {
+        let (temp, input, model) = setup();
+        let out = temp.path().join("out.wav");
+
+        let params = NeuralModelParams::new()
+            .with_param("intensity", 0.5)
+            .with_seed(1);
+        model.process(&input, &out, &params).unwrap();
+        let params = NeuralModelParams::new()
+            .with_param("intensity", 0.6)
+            .with_seed(1);
+        model.process(&input, &out, &params).unwrap();
+
+        assert_eq!(calls(&model), 2);
+    }
+
+    #[test]
+    fn test_corrupt_entry_recomputes() {
+        let (temp, input, model) = setup();
+        let out = temp.path().join("out.wav");
+        let params = NeuralModelParams::new().with_seed(1);
+        model.process(&input, &out, &params).unwrap();
+
+        for entry in fs::read_dir(model.cache().dir()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::write(path, "{ not json").unwrap();
+            }
+        }
+
+        let result = model.process(&input, &out, &params).unwrap();
+        assert!(result.success);
+        assert_eq!(calls(&model), 2);
+    }
+
+    #[test]
+    fn test_source_change_invalidates() {
+        let (temp, input, model) = setup();
+        let out = temp.path().join("out.wav");
+        let params = NeuralModelParams::new().with_seed(1);
+
+        assert!(!model.cache().sync_source("aaa").unwrap());
+        model.process(&input, &out, &params).unwrap();
+        assert!(!model.cache().sync_source("aaa").unwrap());
+
+        assert!(model.cache().sync_source("bbb").unwrap());
+        model.process(&input, &out, &params).unwrap();
+        assert_eq!(calls(&model), 2);
+    }
+
+    #[test]
+    fn test_unseeded_runs_are_not_cached() {
+        let (temp, input, model) = setup();
+        let out = temp.path().join("out.wav");
+        let params = NeuralModelParams::new().with_param("intensity", 0.5);
+
+        model.process(&input, &out, &params).unwrap();
+        let result = model.process(&input, &out, &params).unwrap();
+        assert_eq!(calls(&model), 2);
+        assert!(!result.metadata.contains_key("cache_hit"));
+    }
+
+    #[test]
+    fn test_progress_and_cancellation_reach_the_model() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("in.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.2, 48000),
+            &input,
+            ExportFormat::default(),
+        )
+        .unwrap();
+        let cache = NeuralCache::new(temp.path().join(NEURAL_CACHE_DIR)).unwrap();
+        let model = CachedModel::new(crate::neural::MockAceStep::new(), cache);
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "test")
+            .with_param("inference_steps", 4)
+            .with_seed(3);
+        let out = temp.path().join("out.wav");
+
+        let mut reports = Vec::new();
+        model
+            .process_with_progress(&input, &out, &params, &mut |p| {
+                reports.push(p);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        // One report per diffusion step, not just start and end
+        assert!(reports.len() > 2, "{:?}", reports);
+
+        let other = params.clone().with_seed(4);
+        let result = model.process_with_progress(&input, &out, &other, &mut |p| {
+            if p > 0.0 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(matches!(result, Err(crate::error::NuevaError::Cancelled)));
+    }
+}
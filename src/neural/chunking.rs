@@ -0,0 +1,372 @@
+//! Chunked processing for long audio
+//!
+//! Neural models usually have a maximum input length. This splits the
+//! input into overlapping windows, runs the model on each one and joins
+//! the outputs with a windowed overlap-add.
+//!
+//! Neighbouring windows are joined with an equal-power crossfade: one
+//! fades out along a cosine while the next fades in along a sine, so the
+//! squared gains add to one at every sample. Uncorrelated chunks keep a
+//! constant level through the seam; correlated material such as a
+//! passthrough swells smoothly by up to 3 dB mid-overlap.
+//!
+//! Every window has the same length and the overlap may be at most half
+//! of it. The last window is aligned to the end of the input, so it may
+//! share more than the overlap with its neighbour; it stays silent until
+//! the crossfade begins. The output always has exactly as many samples as
+//! the input: a chunk that comes back longer is truncated and one that
+//! comes back shorter is padded with silence.
+
+use super::model::{NeuralModel, NeuralModelParams};
+use crate::engine::buffer::{AudioBuffer, ChannelLayout};
+use crate::engine::io::{export_audio, import_audio_at, ExportFormat};
+use crate::error::{NuevaError, Result};
+use std::f32::consts::FRAC_PI_2;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Run `model` over `input` in overlapping chunks and crossfade the results
+pub fn process_chunked<M: NeuralModel + ?Sized>(
+    model: &M,
+    input: &AudioBuffer,
+    chunk_secs: f64,
+    overlap_secs: f64,
+    params: &NeuralModelParams,
+) -> Result<AudioBuffer> {
+    if !(chunk_secs.is_finite() && chunk_secs > 0.0) {
+        return Err(NuevaError::InvalidParameter {
+            param: "chunk_secs".to_string(),
+            value: chunk_secs.to_string(),
+            expected: "a positive number of seconds".to_string(),
+        });
+    }
+    if !(overlap_secs.is_finite() && overlap_secs >= 0.0 && overlap_secs <= chunk_secs / 2.0) {
+        return Err(NuevaError::InvalidParameter {
+            param: "overlap_secs".to_string(),
+            value: overlap_secs.to_string(),
+            expected: format!("0 <= overlap <= half the chunk ({}s)", chunk_secs / 2.0),
+        });
+    }
+
+    let params = &model.prepare_params(params)?;
+
+    let sample_rate = input.sample_rate as f64;
+    let total = input.len();
+    let chunk_len = ((chunk_secs * sample_rate).round() as usize).max(1);
+    let overlap_len = ((overlap_secs * sample_rate).round() as usize).min(chunk_len / 2);
+
+    let work_dir = ChunkDir::create()?;
+
+    // Short input: one call, no crossfading
+    if total <= chunk_len {
+        let output = run_chunk(model, input, params, &work_dir.0, 0)?;
+        return Ok(fit_length(output, input.num_channels(), total));
+    }
+
+    let starts = chunk_starts(total, chunk_len, chunk_len - overlap_len);
+    let mut output = vec![vec![0.0_f32; total]; input.num_channels()];
+
+    for (index, &start) in starts.iter().enumerate() {
+        let chunk = input.slice(start, start + chunk_len)?;
+        let processed = run_chunk(model, &chunk, params, &work_dir.0, index)?;
+        let processed = fit_length(processed, input.num_channels(), chunk_len);
+
+        for (i, w) in chunk_window(&starts, index, chunk_len, overlap_len)
+            .into_iter()
+            .enumerate()
+        {
+            for (out_ch, in_ch) in output.iter_mut().zip(&processed.samples) {
+                out_ch[start + i] += w * in_ch[i];
+            }
+        }
+    }
+
+    Ok(AudioBuffer {
+        samples: output,
+        sample_rate: input.sample_rate,
+    })
+}
+
+/// Run `model` once over a whole buffer
+pub(crate) fn process_buffer<M: NeuralModel + ?Sized>(
+    model: &M,
+    input: &AudioBuffer,
+    params: &NeuralModelParams,
+) -> Result<AudioBuffer> {
+    let work_dir = ChunkDir::create()?;
+    run_chunk(model, input, params, &work_dir.0, 0)
+}
+
+/// Start offsets of fixed-length windows covering `total` samples
+fn chunk_starts(total: usize, chunk_len: usize, hop: usize) -> Vec<usize> {
+    let last = total - chunk_len;
+    let mut starts: Vec<usize> = (0..last).step_by(hop).collect();
+    starts.push(last);
+    starts
+}
+
+/// Per-sample gains for window `index`
+///
+/// Each crossfade covers the last `fade` samples of the earlier window:
+/// the later window is silent before it and rises along a sine while the
+/// earlier one falls along a cosine.
+fn chunk_window(starts: &[usize], index: usize, len: usize, fade: usize) -> Vec<f32> {
+    let start = starts[index];
+    // Where the previous window ends, relative to this one
+    let fade_in_end = index
+        .checked_sub(1)
+        .map_or(0, |prev| starts[prev] + len - start);
+    let fade_out = index + 1 < starts.len();
+    let angle = |pos: usize| (pos as f32 + 0.5) / fade as f32 * FRAC_PI_2;
+
+    (0..len)
+        .map(|i| {
+            let mut gain = 1.0;
+            if i < fade_in_end {
+                let Some(pos) = (i + fade).checked_sub(fade_in_end) else {
+                    return 0.0;
+                };
+                gain *= angle(pos).sin();
+            }
+            if fade_out && i + fade >= len {
+                gain *= angle(i + fade - len).cos();
+            }
+            gain
+        })
+        .collect()
+}
+
+/// Truncate or zero-pad to `len` samples, matching the input channel count
+fn fit_length(mut buffer: AudioBuffer, num_channels: usize, len: usize) -> AudioBuffer {
+    if buffer.samples.len() != num_channels {
+        let layout = ChannelLayout::from_count(num_channels).unwrap_or(ChannelLayout::Stereo);
+        let mono = buffer.samples.first().cloned().unwrap_or_default();
+        let mut converted = AudioBuffer::with_sample_rate(mono.len(), layout, buffer.sample_rate);
+        for ch in converted.samples.iter_mut() {
+            ch.copy_from_slice(&mono);
+        }
+        buffer = converted;
+    }
+    for ch in buffer.samples.iter_mut() {
+        ch.resize(len, 0.0);
+    }
+    buffer
+}
+
+fn run_chunk<M: NeuralModel + ?Sized>(
+    model: &M,
+    chunk: &AudioBuffer,
+    params: &NeuralModelParams,
+    dir: &Path,
+    index: usize,
+) -> Result<AudioBuffer> {
+    let input_path = dir.join(format!("chunk_{:04}_in.wav", index));
+    let output_path = dir.join(format!("chunk_{:04}_out.wav", index));
+
+    // Chunks stay at the input's rate both ways, so nothing is resampled
+    export_audio(chunk, &input_path, ExportFormat::new(chunk.sample_rate, 32))?;
+    let result = model.process(&input_path, &output_path, params)?;
+    if !result.success {
+        return Err(NuevaError::AiProcessingError {
+            reason: format!(
+                "{} failed on chunk {}: {}",
+                model.id(),
+                index,
+                result.description
+            ),
+        });
+    }
+
+    import_audio_at(&output_path, chunk.sample_rate)
+}
+
+/// Scratch directory for chunk files, removed on drop
+struct ChunkDir(PathBuf);
+
+impl ChunkDir {
+    fn create() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("nueva-chunks-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for ChunkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::buffer::INTERNAL_SAMPLE_RATE;
+    use crate::engine::io::generate_test_tone;
+    use crate::neural::model::{NeuralModelInfo, ProcessingResult};
+    use crate::neural::registry::create_model_info;
+    use std::f32::consts::SQRT_2;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Copies input to output unchanged
+    struct Passthrough {
+        info: NeuralModelInfo,
+        calls: AtomicUsize,
+    }
+
+    impl Passthrough {
+        fn new() -> Self {
+            Self {
+                info: create_model_info(
+                    "passthrough",
+                    "Passthrough",
+                    "test",
+                    "Copies input to output",
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    0.0,
+                    "instant",
+                    vec![],
+                ),
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl NeuralModel for Passthrough {
+        fn info(&self) -> &NeuralModelInfo {
+            &self.info
+        }
+
+        fn process(
+            &self,
+            input_path: &Path,
+            output_path: &Path,
+            _params: &NeuralModelParams,
+        ) -> Result<ProcessingResult> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            fs::copy(input_path, output_path)?;
+            Ok(ProcessingResult::success(
+                output_path.to_string_lossy().to_string(),
+                "copied".to_string(),
+                0,
+            ))
+        }
+    }
+
+    /// A passthrough joined with equal-power crossfades: every sample is
+    /// the input lifted by at most 3 dB, and no step between samples is
+    /// bigger than the lifted input's
+    fn assert_smooth_passthrough(input: &AudioBuffer, output: &AudioBuffer) {
+        for (a, b) in input.samples[0].iter().zip(&output.samples[0]) {
+            assert!((b - a).abs() <= a.abs() * (SQRT_2 - 1.0) + 1e-5);
+        }
+        let max_step = |samples: &[f32]| {
+            samples
+                .windows(2)
+                .map(|w| (w[1] - w[0]).abs())
+                .fold(0.0_f32, f32::max)
+        };
+        assert!(max_step(&output.samples[0]) <= max_step(&input.samples[0]) * SQRT_2 + 1e-4);
+    }
+
+    #[test]
+    fn test_passthrough_has_no_seam_artifacts() {
+        let model = Passthrough::new();
+        let input = generate_test_tone(440.0, 2.3, INTERNAL_SAMPLE_RATE);
+
+        let output = model
+            .process_chunked(&input, 0.5, 0.1, &NeuralModelParams::new())
+            .unwrap();
+
+        assert!(model.calls.load(Ordering::SeqCst) > 1);
+        assert_eq!(output.len(), input.len());
+        assert_smooth_passthrough(&input, &output);
+    }
+
+    #[test]
+    fn test_chunks_keep_the_input_sample_rate() {
+        let model = Passthrough::new();
+        let input = generate_test_tone(440.0, 1.3, 44100);
+
+        let output = model
+            .process_chunked(&input, 0.5, 0.1, &NeuralModelParams::new())
+            .unwrap();
+
+        assert!(model.calls.load(Ordering::SeqCst) > 1);
+        assert_eq!(output.sample_rate, 44100);
+        assert_eq!(output.len(), input.len());
+        assert_smooth_passthrough(&input, &output);
+
+        let single = model
+            .process_chunked(&input, 10.0, 1.0, &NeuralModelParams::new())
+            .unwrap();
+        assert_eq!(single.sample_rate, 44100);
+        assert_eq!(single.samples, input.samples);
+    }
+
+    #[test]
+    fn test_chunk_longer_than_input_is_single_call() {
+        let model = Passthrough::new();
+        let input = generate_test_tone(440.0, 0.5, INTERNAL_SAMPLE_RATE);
+
+        let output = model
+            .process_chunked(&input, 10.0, 1.0, &NeuralModelParams::new())
+            .unwrap();
+
+        assert_eq!(model.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(output.len(), input.len());
+    }
+
+    #[test]
+    fn test_default_streaming_emits_final_output_once() {
+        let model = Passthrough::new();
+        let input = generate_test_tone(440.0, 0.2, INTERNAL_SAMPLE_RATE);
+
+        let mut partials = Vec::new();
+        let output = model
+            .process_streaming(&input, &NeuralModelParams::new(), &mut |partial| {
+                partials.push(partial.clone());
+                std::ops::ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert_eq!(model.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(partials.len(), 1);
+        assert_eq!(partials[0].samples, output.samples);
+        assert_eq!(output.samples, input.samples);
+    }
+
+    #[test]
+    fn test_invalid_overlap_rejected() {
+        let model = Passthrough::new();
+        let input = generate_test_tone(440.0, 0.5, INTERNAL_SAMPLE_RATE);
+
+        let result = model.process_chunked(&input, 0.2, 0.2, &NeuralModelParams::new());
+        assert!(matches!(result, Err(NuevaError::InvalidParameter { .. })));
+        let result = model.process_chunked(&input, 0.2, 0.11, &NeuralModelParams::new());
+        assert!(matches!(result, Err(NuevaError::InvalidParameter { .. })));
+    }
+
+    #[test]
+    fn test_windows_have_constant_summed_power() {
+        // The last window overlaps its neighbour by more than the fade
+        for (total, len, fade) in [(100, 20, 5), (107, 20, 10), (61, 20, 0)] {
+            let starts = chunk_starts(total, len, len - fade);
+            let mut power = vec![0.0_f32; total];
+            for (index, &start) in starts.iter().enumerate() {
+                for (i, w) in chunk_window(&starts, index, len, fade).iter().enumerate() {
+                    power[start + i] += w * w;
+                }
+            }
+            assert!(power.iter().all(|p| (p - 1.0).abs() < 1e-5), "{:?}", power);
+        }
+    }
+
+    #[test]
+    fn test_chunk_starts_cover_input() {
+        assert_eq!(chunk_starts(10, 4, 3), vec![0, 3, 6]);
+        assert_eq!(chunk_starts(11, 4, 3), vec![0, 3, 6, 7]);
+    }
+}
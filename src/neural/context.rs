@@ -9,6 +9,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::agent::SafetyIssue;
+
 /// Tracks neural processing context and intentional artifacts
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NeuralContextTracker {
@@ -82,6 +84,22 @@ pub enum IntentionalArtifact {
 }
 
 impl IntentionalArtifact {
+    /// Parse the snake_case name models report in
+    /// `ProcessingResult::intentional_artifacts` (e.g. "different_timbre")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let variant: String = name
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect();
+        serde_json::from_value(serde_json::Value::String(variant)).ok()
+    }
+
     /// Get DSP warning for this artifact
     pub fn get_warning(&self) -> &'static str {
         match self {
@@ -254,6 +272,47 @@ impl NeuralContextTracker {
         self.intentional_artifacts.contains(artifact)
     }
 
+    /// Flag an artifact as intentional (e.g., the user asked for heavy saturation)
+    pub fn add_artifact(&mut self, artifact: IntentionalArtifact) {
+        if !self.has_artifact(&artifact) {
+            self.intentional_artifacts.push(artifact);
+        }
+    }
+
+    /// Whether a safety issue is explained by an intentional artifact
+    ///
+    /// Clipping is intentional under saturation, distortion or bitcrushing,
+    /// and a duration change under a tempo change. Phase, loudness and
+    /// artifact-removal issues are never excused.
+    pub fn is_intentional(&self, issue: &SafetyIssue) -> bool {
+        let excused_by: &[IntentionalArtifact] = match issue {
+            SafetyIssue::Clipping { .. } => &[
+                IntentionalArtifact::Saturation,
+                IntentionalArtifact::Distortion,
+                IntentionalArtifact::Bitcrushing,
+            ],
+            SafetyIssue::DurationMismatch { .. } => &[IntentionalArtifact::TempoChange],
+            SafetyIssue::PhaseCorrelation { .. }
+            | SafetyIssue::ExcessiveLoudness { .. }
+            | SafetyIssue::IntentionalArtifactRemoval { .. } => &[],
+        };
+        excused_by.iter().any(|a| self.has_artifact(a))
+    }
+
+    /// Stop treating an artifact as intentional; returns whether it was flagged
+    pub fn remove_artifact(&mut self, artifact: &IntentionalArtifact) -> bool {
+        let before = self.intentional_artifacts.len();
+        self.intentional_artifacts.retain(|a| a != artifact);
+        self.intentional_artifacts.len() != before
+    }
+
+    /// Whether there is no context or history to keep
+    pub fn is_empty(&self) -> bool {
+        self.last_neural_operation.is_none()
+            && self.intentional_artifacts.is_empty()
+            && self.operation_history.is_empty()
+    }
+
     /// Clear all context (e.g., after user resets neural layer)
     pub fn clear(&mut self) {
         self.last_neural_operation = None;
@@ -367,4 +426,47 @@ mod tests {
         // History should be preserved
         assert!(!tracker.operation_history.is_empty());
     }
+
+    #[test]
+    fn test_add_and_remove_artifact() {
+        let mut tracker = NeuralContextTracker::new();
+        tracker.add_artifact(IntentionalArtifact::Saturation);
+        tracker.add_artifact(IntentionalArtifact::Saturation);
+        assert_eq!(tracker.intentional_artifacts.len(), 1);
+
+        assert!(tracker.remove_artifact(&IntentionalArtifact::Saturation));
+        assert!(!tracker.remove_artifact(&IntentionalArtifact::Saturation));
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_artifact_from_model_name() {
+        assert_eq!(
+            IntentionalArtifact::from_name("different_timbre"),
+            Some(IntentionalArtifact::DifferentTimbre)
+        );
+        assert_eq!(
+            IntentionalArtifact::from_name("intentional_coloration"),
+            Some(IntentionalArtifact::IntentionalColoration)
+        );
+        assert_eq!(IntentionalArtifact::from_name("wobble"), None);
+    }
+
+    #[test]
+    fn test_intentional_saturation_excuses_clipping_only() {
+        let mut tracker = NeuralContextTracker::new();
+        let clipping = SafetyIssue::Clipping {
+            predicted_peak_db: 1,
+        };
+        assert!(!tracker.is_intentional(&clipping));
+
+        tracker.add_artifact(IntentionalArtifact::Saturation);
+        assert!(tracker.is_intentional(&clipping));
+        assert!(!tracker.is_intentional(&SafetyIssue::PhaseCorrelation {
+            predicted_correlation: 10,
+        }));
+
+        tracker.remove_artifact(&IntentionalArtifact::Saturation);
+        assert!(!tracker.is_intentional(&clipping));
+    }
 }
@@ -3,9 +3,19 @@
 //! Detects available GPU hardware and determines capability for running
 //! neural models like ACE-Step.
 
+use super::model::NeuralModelInfo;
+use crate::error::{NuevaError, Result};
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+/// Extra memory on top of the weights for activations and buffers
+const ACTIVATION_OVERHEAD: f32 = 1.2;
+
+const BYTES_PER_GB: f32 = 1024.0 * 1024.0 * 1024.0;
+
+/// Least free VRAM that runs ACE-Step on the GPU (its INT8 footprint)
+const ACE_STEP_MIN_VRAM_GB: f32 = 4.0;
+
 /// Recommended quantization level based on available VRAM
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QuantizationLevel {
@@ -30,6 +40,36 @@ impl QuantizationLevel {
         }
     }
 
+    /// GPU levels from highest to lowest quality
+    pub const GPU_LEVELS: [QuantizationLevel; 3] = [Self::FP32, Self::FP16, Self::INT8];
+
+    /// Bytes per model weight at this level (CPU keeps FP32 weights in RAM)
+    pub fn bytes_per_param(&self) -> f32 {
+        match self {
+            Self::FP32 | Self::CPU => 4.0,
+            Self::FP16 => 2.0,
+            Self::INT8 => 1.0,
+        }
+    }
+
+    /// Estimated VRAM in GB to run `model` at this level
+    ///
+    /// Uses the model's parameter count when known. Otherwise the model's
+    /// `vram_requirement_gb` is taken as its FP16 footprint and scaled.
+    pub fn estimate_vram_gb(&self, model: &NeuralModelInfo) -> f32 {
+        if *self == Self::CPU {
+            return 0.0;
+        }
+        match model.param_count {
+            Some(params) => {
+                params as f32 * self.bytes_per_param() * ACTIVATION_OVERHEAD / BYTES_PER_GB
+            }
+            None => {
+                model.vram_requirement_gb * self.bytes_per_param() / Self::FP16.bytes_per_param()
+            }
+        }
+    }
+
     /// Human-readable description
     pub fn description(&self) -> &'static str {
         match self {
@@ -69,6 +109,49 @@ impl GpuInfo {
         Self::detect_nvidia()
     }
 
+    /// Placeholder for a machine without a usable GPU
+    pub fn cpu_only() -> Self {
+        Self {
+            name: "CPU".to_string(),
+            vram_total_gb: 0.0,
+            vram_available_gb: 0.0,
+            driver_version: String::new(),
+            cuda_version: None,
+            suitable_for_ace_step: false,
+            recommended_quantization: QuantizationLevel::CPU,
+        }
+    }
+
+    /// Detect the GPU, or describe a CPU-only machine if none is found
+    pub fn detect_or_cpu() -> Self {
+        Self::detect().unwrap_or_else(Self::cpu_only)
+    }
+
+    /// Whether this describes an actual GPU
+    pub fn has_gpu(&self) -> bool {
+        self.vram_total_gb > 0.0
+    }
+
+    /// Pick the highest-quality quantization of `model` that fits in the
+    /// available VRAM
+    ///
+    /// Without a GPU this is always `CPU`. With a GPU that cannot fit even
+    /// INT8, returns `InsufficientVram` so the caller can decide whether to
+    /// fall back to the CPU.
+    pub fn recommended_quantization(&self, model: &NeuralModelInfo) -> Result<QuantizationLevel> {
+        if !self.has_gpu() {
+            return Ok(QuantizationLevel::CPU);
+        }
+
+        QuantizationLevel::GPU_LEVELS
+            .into_iter()
+            .find(|level| level.estimate_vram_gb(model) <= self.vram_available_gb)
+            .ok_or_else(|| NuevaError::InsufficientVram {
+                required_gb: QuantizationLevel::INT8.estimate_vram_gb(model),
+                available_gb: self.vram_available_gb,
+            })
+    }
+
     /// Detect NVIDIA GPU using nvidia-smi
     fn detect_nvidia() -> Option<Self> {
         // Try to run nvidia-smi with CSV output
@@ -114,8 +197,7 @@ impl GpuInfo {
             QuantizationLevel::CPU
         };
 
-        // ACE-Step needs at least 4GB VRAM
-        let suitable_for_ace_step = vram_available_gb >= 4.0;
+        let suitable_for_ace_step = vram_available_gb >= ACE_STEP_MIN_VRAM_GB;
 
         Some(Self {
             name,
@@ -176,8 +258,10 @@ pub fn can_run_ace_step() -> (bool, QuantizationLevel, String) {
                     true,
                     QuantizationLevel::CPU,
                     format!(
-                        "GPU {} has insufficient VRAM ({:.1}GB available, 4GB required). Using CPU fallback.",
-                        gpu.name, gpu.vram_available_gb
+                        "GPU {} has insufficient VRAM ({:.1}GB available, {:.0}GB required). Using CPU fallback.",
+                        gpu.name,
+                        gpu.vram_available_gb,
+                        ACE_STEP_MIN_VRAM_GB
                     ),
                 )
             }
@@ -192,7 +276,15 @@ pub fn can_run_ace_step() -> (bool, QuantizationLevel, String) {
 
 /// Get a human-readable summary of GPU status
 pub fn gpu_status_summary() -> String {
-    match GpuInfo::detect() {
+    let registry = super::registry::NeuralModelRegistry::with_defaults();
+    let mut models = registry.list_model_info();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    status_summary(GpuInfo::detect().as_ref(), &models)
+}
+
+/// Summarize GPU status and the quantization each model would use
+fn status_summary(gpu: Option<&GpuInfo>, models: &[&NeuralModelInfo]) -> String {
+    let (mut summary, gpu) = match gpu {
         Some(gpu) => {
             let mut summary = format!(
                 "GPU: {}\n\
@@ -216,15 +308,41 @@ pub fn gpu_status_summary() -> String {
                 gpu.recommended_quantization.description()
             ));
 
-            summary
+            (summary, gpu.clone())
+        }
+        None => (
+            "No compatible GPU detected. Neural models will use CPU inference.".to_string(),
+            GpuInfo::cpu_only(),
+        ),
+    };
+
+    if !models.is_empty() {
+        summary.push_str("\nModel quantization:");
+        for model in models {
+            let line = match gpu.recommended_quantization(model) {
+                Ok(level) => format!(
+                    "\n  {}: {:?} (~{:.1}GB VRAM)",
+                    model.id,
+                    level,
+                    level.estimate_vram_gb(model)
+                ),
+                Err(_) => format!(
+                    "\n  {}: CPU (needs ~{:.1}GB VRAM even at INT8)",
+                    model.id,
+                    QuantizationLevel::INT8.estimate_vram_gb(model)
+                ),
+            };
+            summary.push_str(&line);
         }
-        None => "No compatible GPU detected. Neural models will use CPU inference.".to_string(),
     }
+
+    summary
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::neural::{MockAceStep, NeuralModel};
 
     #[test]
     fn test_quantization_levels() {
@@ -258,4 +376,81 @@ mod tests {
         assert!(!QuantizationLevel::INT8.description().is_empty());
         assert!(!QuantizationLevel::CPU.description().is_empty());
     }
+
+    fn gpu_with_vram(available_gb: f32) -> GpuInfo {
+        GpuInfo {
+            name: "Test GPU".to_string(),
+            vram_total_gb: 24.0,
+            vram_available_gb: available_gb,
+            driver_version: "test".to_string(),
+            cuda_version: None,
+            suitable_for_ace_step: available_gb >= ACE_STEP_MIN_VRAM_GB,
+            recommended_quantization: QuantizationLevel::CPU,
+        }
+    }
+
+    fn ace_step_info() -> NeuralModelInfo {
+        MockAceStep::new().info().clone()
+    }
+
+    #[test]
+    fn test_estimate_uses_param_count() {
+        let info = ace_step_info();
+        let fp32 = QuantizationLevel::FP32.estimate_vram_gb(&info);
+        let fp16 = QuantizationLevel::FP16.estimate_vram_gb(&info);
+        let int8 = QuantizationLevel::INT8.estimate_vram_gb(&info);
+
+        assert!((fp32 - 2.0 * fp16).abs() < 1e-3);
+        assert!((fp16 - 2.0 * int8).abs() < 1e-3);
+        // 3.5B params at one byte plus overhead is just under 4GB
+        assert!(int8 > 3.5 && int8 < 4.0);
+
+        let bigger = info.clone().with_param_count(7_000_000_000);
+        assert!(QuantizationLevel::INT8.estimate_vram_gb(&bigger) > int8);
+    }
+
+    #[test]
+    fn test_plenty_of_vram_picks_full_precision() {
+        let level = gpu_with_vram(20.0)
+            .recommended_quantization(&ace_step_info())
+            .unwrap();
+        assert_eq!(level, QuantizationLevel::FP32);
+    }
+
+    #[test]
+    fn test_tight_vram_picks_lower_precision() {
+        let info = ace_step_info();
+        assert_eq!(
+            gpu_with_vram(10.0).recommended_quantization(&info).unwrap(),
+            QuantizationLevel::FP16
+        );
+        assert_eq!(
+            gpu_with_vram(5.0).recommended_quantization(&info).unwrap(),
+            QuantizationLevel::INT8
+        );
+    }
+
+    #[test]
+    fn test_too_little_vram_errors() {
+        let result = gpu_with_vram(1.0).recommended_quantization(&ace_step_info());
+        assert!(matches!(result, Err(NuevaError::InsufficientVram { .. })));
+    }
+
+    #[test]
+    fn test_no_gpu_recommends_cpu() {
+        let level = GpuInfo::cpu_only()
+            .recommended_quantization(&ace_step_info())
+            .unwrap();
+        assert_eq!(level, QuantizationLevel::CPU);
+    }
+
+    #[test]
+    fn test_summary_lists_model_quantization() {
+        let info = ace_step_info();
+        let summary = status_summary(Some(&gpu_with_vram(5.0)), &[&info]);
+        assert!(summary.contains("ace-step: INT8"));
+
+        let summary = status_summary(None, &[&info]);
+        assert!(summary.contains("ace-step: CPU"));
+    }
 }
@@ -0,0 +1,210 @@
+//! Model manifests
+//!
+//! Installed models are described by JSON manifests in a models
+//! directory, either as `<dir>/<model>/manifest.json` or as a `.json`
+//! file directly in `<dir>`. A manifest holds the model's
+//! [`NeuralModelInfo`] fields plus the model file and backend:
+//!
+//! ```json
+//! {
+//!   "id": "denoise",
+//!   "name": "Studio Denoise",
+//!   "version": "2.1.0",
+//!   "file": "denoise.onnx",
+//!   "backend": "onnx",
+//!   "supported_params": [
+//!     {"name": "strength", "param_type": {"type": "Float", "min": 0.0, "max": 1.0}}
+//!   ]
+//! }
+//! ```
+//!
+//! `file` is relative to the manifest's directory.
+
+use super::model::{NeuralModel, NeuralModelInfo, NeuralModelParams, ProcessingResult};
+use crate::error::{NuevaError, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// File name of a manifest inside a model's own directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// An installed model as described on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelManifest {
+    #[serde(flatten)]
+    pub info: NeuralModelInfo,
+
+    /// Model file (weights or graph)
+    pub file: PathBuf,
+
+    /// Runtime that executes the file, e.g. "onnx"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+}
+
+impl ModelManifest {
+    /// Read a manifest, resolving `file` against the manifest's directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut manifest: ModelManifest =
+            serde_json::from_str(&text).map_err(|e| NuevaError::SerializationError {
+                details: format!("{}: {}", path.display(), e),
+            })?;
+
+        if manifest.info.id.trim().is_empty() {
+            return Err(NuevaError::InvalidParameter {
+                param: "id".to_string(),
+                value: String::new(),
+                expected: "a non-empty model ID".to_string(),
+            });
+        }
+        if manifest.file.is_relative() {
+            if let Some(dir) = path.parent() {
+                manifest.file = dir.join(&manifest.file);
+            }
+        }
+        if !manifest.file.is_file() {
+            return Err(NuevaError::FileNotFound {
+                path: manifest.file.display().to_string(),
+                source: None,
+            });
+        }
+        Ok(manifest)
+    }
+
+    /// Build the model the registry serves for this manifest
+    ///
+    /// ONNX graphs run in-process when the `onnx` feature is enabled;
+    /// anything else is registered as an [`InstalledModel`] that only
+    /// carries metadata.
+    pub fn into_model(self) -> Result<Arc<dyn NeuralModel>> {
+        #[cfg(feature = "onnx")]
+        if self.backend.as_deref() == Some("onnx") {
+            return Ok(Arc::new(super::onnx::OnnxModel::load(
+                &self.file, self.info,
+            )?));
+        }
+
+        Ok(Arc::new(InstalledModel { manifest: self }))
+    }
+}
+
+/// Manifest paths in a models directory, in a stable order
+///
+/// A missing or unreadable directory has no manifests.
+pub fn find_manifests(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut manifests: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter_map(|path| {
+            if path.is_dir() {
+                let manifest = path.join(MANIFEST_FILE_NAME);
+                manifest.is_file().then_some(manifest)
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                Some(path)
+            } else {
+                None
+            }
+        })
+        .collect();
+    manifests.sort();
+    manifests
+}
+
+/// Compare dotted version strings ("1.10.0" > "1.9.2")
+///
+/// Numeric components compare as numbers and missing ones count as zero;
+/// non-numeric components compare as text.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> Vec<String> {
+        v.trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map(str::to_string)
+            .collect()
+    };
+    let (a, b) = (split(a), split(b));
+
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).map_or("0", String::as_str);
+        let y = b.get(i).map_or("0", String::as_str);
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// A discovered model with no in-process backend
+///
+/// Lets the agent and CLI see what is installed; processing fails with a
+/// descriptive error.
+pub struct InstalledModel {
+    manifest: ModelManifest,
+}
+
+impl InstalledModel {
+    /// The manifest this model was discovered from
+    pub fn manifest(&self) -> &ModelManifest {
+        &self.manifest
+    }
+}
+
+impl NeuralModel for InstalledModel {
+    fn info(&self) -> &NeuralModelInfo {
+        &self.manifest.info
+    }
+
+    fn process(
+        &self,
+        _input_path: &Path,
+        _output_path: &Path,
+        _params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        Err(NuevaError::AiProcessingError {
+            reason: format!(
+                "{} ({}) has no available backend for {}",
+                self.manifest.info.id,
+                self.manifest
+                    .backend
+                    .as_deref()
+                    .unwrap_or("unspecified backend"),
+                self.manifest.file.display()
+            ),
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v3", "2.9"), Ordering::Greater);
+        assert_eq!(
+            compare_versions("1.0.0-beta", "1.0.0-alpha"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_find_manifests_in_missing_directory() {
+        assert!(find_manifests(Path::new("/nonexistent/models")).is_empty());
+    }
+}
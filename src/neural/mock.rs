@@ -6,12 +6,20 @@
 //!
 //! Implements Milestone 3.3 from the spec.
 
-use super::model::{NeuralModel, NeuralModelInfo, NeuralModelParams, ParamSpec, ParamType, ProcessingResult};
+use super::model::{
+    NeuralModel, NeuralModelInfo, NeuralModelParams, ParamSpec, ParamType, PartialCallback,
+    ProcessingResult, ProgressCallback, ProgressReporter,
+};
 use super::registry::{
-    create_model_info, DENOISE_NOISE_TYPES, ENHANCE_TARGETS, RESTORE_MODES, STYLE_TRANSFER_PRESETS,
+    create_model_info, ACE_STEP_PARAM_COUNT, DENOISE_NOISE_TYPES, ENHANCE_TARGETS, RESTORE_MODES,
+    STYLE_TRANSFER_PRESETS,
 };
-use crate::error::Result;
+use crate::engine::buffer::AudioBuffer;
+use crate::engine::io::{export_audio, import_audio_native, ExportFormat};
+use crate::error::{NuevaError, Result};
+use std::ops::ControlFlow;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
 /// Mock style transfer model
@@ -52,6 +60,7 @@ impl MockStyleTransfer {
                         description: "Style preset to apply".to_string(),
                         default: Some(serde_json::json!("vintage_analog")),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "intensity".to_string(),
@@ -59,6 +68,7 @@ impl MockStyleTransfer {
                         description: "Intensity of the effect".to_string(),
                         default: Some(serde_json::json!(0.5)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "reference_audio".to_string(),
@@ -66,6 +76,7 @@ impl MockStyleTransfer {
                         description: "Optional path to style reference audio".to_string(),
                         default: None,
                         required: false,
+                        unit: None,
                     },
                 ],
             ),
@@ -91,6 +102,7 @@ impl NeuralModel for MockStyleTransfer {
         params: &NeuralModelParams,
     ) -> Result<ProcessingResult> {
         let start = Instant::now();
+        let seed = params.seed_or_random();
 
         let preset = params
             .get_string("style_preset")
@@ -127,7 +139,8 @@ impl NeuralModel for MockStyleTransfer {
             ),
             elapsed,
         )
-        .with_artifacts(artifacts))
+        .with_artifacts(artifacts)
+        .with_seed(seed))
     }
 }
 
@@ -165,6 +178,7 @@ impl MockDenoise {
                         description: "Noise reduction strength".to_string(),
                         default: Some(serde_json::json!(0.5)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "preserve_transients".to_string(),
@@ -172,6 +186,7 @@ impl MockDenoise {
                         description: "Preserve attack transients".to_string(),
                         default: Some(serde_json::json!(true)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "noise_type".to_string(),
@@ -181,6 +196,7 @@ impl MockDenoise {
                         description: "Type of noise to target".to_string(),
                         default: Some(serde_json::json!("auto")),
                         required: false,
+                        unit: None,
                     },
                 ],
             ),
@@ -206,6 +222,7 @@ impl NeuralModel for MockDenoise {
         params: &NeuralModelParams,
     ) -> Result<ProcessingResult> {
         let start = Instant::now();
+        let seed = params.seed_or_random();
 
         let strength = params.get_f32("strength").unwrap_or(0.5);
         let noise_type = params
@@ -230,7 +247,8 @@ impl NeuralModel for MockDenoise {
             ),
             elapsed,
         )
-        .with_warnings(warnings))
+        .with_warnings(warnings)
+        .with_seed(seed))
     }
 }
 
@@ -274,6 +292,7 @@ impl MockRestore {
                         description: "Restoration mode".to_string(),
                         default: Some(serde_json::json!("auto")),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "aggressiveness".to_string(),
@@ -281,6 +300,7 @@ impl MockRestore {
                         description: "How aggressive the restoration should be".to_string(),
                         default: Some(serde_json::json!(0.5)),
                         required: false,
+                        unit: None,
                     },
                 ],
             ),
@@ -306,6 +326,7 @@ impl NeuralModel for MockRestore {
         params: &NeuralModelParams,
     ) -> Result<ProcessingResult> {
         let start = Instant::now();
+        let seed = params.seed_or_random();
 
         let mode = params
             .get_string("mode")
@@ -324,7 +345,8 @@ impl NeuralModel for MockRestore {
                 aggressiveness * 100.0
             ),
             elapsed,
-        ))
+        )
+        .with_seed(seed))
     }
 }
 
@@ -365,6 +387,7 @@ impl MockEnhance {
                         description: "Enhancement target".to_string(),
                         default: Some(serde_json::json!("clarity")),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "amount".to_string(),
@@ -372,6 +395,7 @@ impl MockEnhance {
                         description: "Enhancement amount (start at 0.3)".to_string(),
                         default: Some(serde_json::json!(0.3)),
                         required: false,
+                        unit: None,
                     },
                 ],
             ),
@@ -397,6 +421,7 @@ impl NeuralModel for MockEnhance {
         params: &NeuralModelParams,
     ) -> Result<ProcessingResult> {
         let start = Instant::now();
+        let seed = params.seed_or_random();
 
         let target = params
             .get_string("target")
@@ -421,13 +446,71 @@ impl NeuralModel for MockEnhance {
             ),
             elapsed,
         )
-        .with_warnings(warnings))
+        .with_warnings(warnings)
+        .with_seed(seed))
+    }
+}
+
+/// Small deterministic PRNG so seeded mock output is reproducible
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in [-1, 1)
+    fn next_bipolar(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+/// Diffusion steps the mock ACE-Step simulates when none are requested
+const MOCK_ACE_STEP_DEFAULT_STEPS: usize = 8;
+
+/// Level of the noise the mock ACE-Step's first partial carries
+const MOCK_ACE_STEP_PARTIAL_NOISE: f32 = 0.1;
+
+/// Diffusion steps requested in `params`
+fn mock_ace_step_steps(params: &NeuralModelParams) -> usize {
+    params
+        .get::<usize>("inference_steps")
+        .unwrap_or(MOCK_ACE_STEP_DEFAULT_STEPS)
+        .max(1)
+}
+
+/// Simulated time per step; a whole run takes 150 ms
+fn mock_ace_step_step_time(steps: usize) -> std::time::Duration {
+    std::time::Duration::from_millis(150) / steps as u32
+}
+
+/// The mock "transformation": seeded noise scaled by intensity
+fn render_mock_ace_step(audio: &mut AudioBuffer, seed: u64, intensity: f32) {
+    let mut rng = SplitMix64::new(seed);
+    let amount = 0.01 * intensity;
+    for channel in audio.samples.iter_mut() {
+        for sample in channel.iter_mut() {
+            *sample += rng.next_bipolar() * amount;
+        }
     }
 }
 
 /// Mock ACE-Step model (the big transformer)
 pub struct MockAceStep {
     info: NeuralModelInfo,
+    hang: Option<std::time::Duration>,
+    /// Set by `abort` to end a simulated hang
+    aborted: AtomicBool,
+    /// Whether a run is stuck in its simulated hang
+    hanging: AtomicBool,
 }
 
 impl MockAceStep {
@@ -481,6 +564,7 @@ impl MockAceStep {
                         description: "Processing mode".to_string(),
                         default: Some(serde_json::json!("transform")),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "prompt".to_string(),
@@ -488,6 +572,7 @@ impl MockAceStep {
                         description: "Text description of desired output".to_string(),
                         default: None,
                         required: true,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "preserve_melody".to_string(),
@@ -495,6 +580,7 @@ impl MockAceStep {
                         description: "Whether to preserve the original melody".to_string(),
                         default: Some(serde_json::json!(true)),
                         required: false,
+                        unit: None,
                     },
                     ParamSpec {
                         name: "intensity".to_string(),
@@ -502,11 +588,37 @@ impl MockAceStep {
                         description: "Transformation intensity".to_string(),
                         default: Some(serde_json::json!(0.7)),
                         required: false,
+                        unit: None,
+                    },
+                    ParamSpec {
+                        name: "inference_steps".to_string(),
+                        param_type: ParamType::Int { min: 4, max: 50 },
+                        description: "Number of diffusion steps".to_string(),
+                        default: Some(serde_json::json!(MOCK_ACE_STEP_DEFAULT_STEPS)),
+                        required: false,
+                        unit: None,
                     },
                 ],
-            ),
+            )
+            .with_param_count(ACE_STEP_PARAM_COUNT),
+            hang: None,
+            aborted: AtomicBool::new(false),
+            hanging: AtomicBool::new(false),
         }
     }
+
+    /// Simulate a stuck backend: block for `duration` before the first
+    /// diffusion step, without checking for cancellation. Only
+    /// [`NeuralModel::abort`] ends the hang early.
+    pub fn with_hang(mut self, duration: std::time::Duration) -> Self {
+        self.hang = Some(duration);
+        self
+    }
+
+    /// Whether a run is stuck in its simulated hang
+    pub fn is_hanging(&self) -> bool {
+        self.hanging.load(Ordering::SeqCst)
+    }
 }
 
 impl Default for MockAceStep {
@@ -522,11 +634,26 @@ impl NeuralModel for MockAceStep {
 
     fn process(
         &self,
-        _input_path: &Path,
+        input_path: &Path,
         output_path: &Path,
         params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        self.process_with_progress(input_path, output_path, params, &mut |_| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Simulates one progress report per diffusion step (`inference_steps`)
+    fn process_with_progress(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+        on_progress: ProgressCallback<'_>,
     ) -> Result<ProcessingResult> {
         let start = Instant::now();
+        let seed = params.seed_or_random();
+        let mut progress = ProgressReporter::new(on_progress);
 
         let mode = params
             .get_string("mode")
@@ -535,8 +662,37 @@ impl NeuralModel for MockAceStep {
             .get_string("prompt")
             .unwrap_or_else(|| "transform audio".to_string());
         let intensity = params.get_f32("intensity").unwrap_or(0.7);
+        let steps = mock_ace_step_steps(params);
+
+        self.aborted.store(false, Ordering::SeqCst);
+        progress.report(0.0)?;
+        if let Some(hang) = self.hang {
+            self.hanging.store(true, Ordering::SeqCst);
+            let until = Instant::now() + hang;
+            let mut aborted = false;
+            while Instant::now() < until && !aborted {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                aborted = self.aborted.load(Ordering::SeqCst);
+            }
+            self.hanging.store(false, Ordering::SeqCst);
+            if aborted {
+                return Err(NuevaError::Cancelled);
+            }
+        }
+        for step in 1..=steps {
+            std::thread::sleep(mock_ace_step_step_time(steps));
+            progress.report_step(step, steps)?;
+        }
 
-        std::thread::sleep(std::time::Duration::from_millis(150));
+        // Only inputs that exist can be rendered; pipeline tests use fake paths
+        if let Ok(mut audio) = import_audio_native(input_path) {
+            render_mock_ace_step(&mut audio, seed, intensity);
+            export_audio(
+                &audio,
+                output_path,
+                ExportFormat::new(audio.sample_rate, 32),
+            )?;
+        }
 
         let mut artifacts = Vec::new();
         if mode == "cover" {
@@ -558,13 +714,72 @@ impl NeuralModel for MockAceStep {
             ),
             elapsed,
         )
-        .with_artifacts(artifacts))
+        .with_artifacts(artifacts)
+        .with_seed(seed))
+    }
+
+    /// Simulates diffusion: one partial per step, each the finished render
+    /// plus residual noise that fades out linearly, so the last partial is
+    /// the render itself
+    fn process_streaming(
+        &self,
+        input: &AudioBuffer,
+        params: &NeuralModelParams,
+        on_partial: PartialCallback<'_>,
+    ) -> Result<AudioBuffer> {
+        let seed = params.seed_or_random();
+        let intensity = params.get_f32("intensity").unwrap_or(0.7);
+        let steps = mock_ace_step_steps(params);
+
+        let mut output = input.clone();
+        render_mock_ace_step(&mut output, seed, intensity);
+
+        // A separate stream, so the residual doesn't repeat the render's noise
+        let mut rng = SplitMix64::new(!seed);
+        let residual: Vec<Vec<f32>> = output
+            .samples
+            .iter()
+            .map(|channel| {
+                channel
+                    .iter()
+                    .map(|_| rng.next_bipolar() * MOCK_ACE_STEP_PARTIAL_NOISE)
+                    .collect()
+            })
+            .collect();
+
+        for step in 1..=steps {
+            std::thread::sleep(mock_ace_step_step_time(steps));
+
+            let remaining = 1.0 - step as f32 / steps as f32;
+            let flow = if step == steps {
+                on_partial(&output)
+            } else {
+                let mut partial = output.clone();
+                for (channel, noise) in partial.samples.iter_mut().zip(&residual) {
+                    for (sample, n) in channel.iter_mut().zip(noise) {
+                        *sample += n * remaining;
+                    }
+                }
+                on_partial(&partial)
+            };
+            if flow.is_break() {
+                return Err(NuevaError::Cancelled);
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::buffer::INTERNAL_SAMPLE_RATE;
+    use crate::engine::io::import_audio;
 
     #[test]
     fn test_mock_style_transfer() {
@@ -656,4 +871,210 @@ mod tests {
         assert!(result.success);
         assert!(result.intentional_artifacts.contains(&"different_timbre".to_string()));
     }
+
+    #[test]
+    fn test_mock_ace_step_staged_progress() {
+        let model = MockAceStep::new();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 4);
+
+        let mut seen = Vec::new();
+        let result = model
+            .process_with_progress(
+                Path::new("/tmp/in.wav"),
+                Path::new("/tmp/out.wav"),
+                &params,
+                &mut |p| {
+                    seen.push(p);
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(seen, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn test_mock_ace_step_cancel() {
+        let model = MockAceStep::new();
+        let params = NeuralModelParams::new().with_param("prompt", "jazz version");
+
+        let mut calls = 0;
+        let result = model.process_with_progress(
+            Path::new("/tmp/in.wav"),
+            Path::new("/tmp/out.wav"),
+            &params,
+            &mut |p| {
+                calls += 1;
+                if p >= 0.5 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        );
+
+        assert!(matches!(result, Err(crate::error::NuevaError::Cancelled)));
+        // 0.0, then steps 1-4 of 8; nothing after the break
+        assert_eq!(calls, 5);
+    }
+
+    #[test]
+    fn test_default_progress_reports_start_and_end() {
+        let model = MockDenoise::new();
+        let mut seen = Vec::new();
+
+        model
+            .process_with_progress(
+                Path::new("/tmp/in.wav"),
+                Path::new("/tmp/out.wav"),
+                &NeuralModelParams::new(),
+                &mut |p| {
+                    seen.push(p);
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(seen, vec![0.0, 1.0]);
+    }
+
+    fn render_ace_step(dir: &Path, name: &str, params: &NeuralModelParams) -> ProcessingResult {
+        let input = dir.join("in.wav");
+        if !input.exists() {
+            let tone = crate::engine::io::generate_test_tone(220.0, 0.25, INTERNAL_SAMPLE_RATE);
+            export_audio(&tone, &input, ExportFormat::default()).unwrap();
+        }
+        MockAceStep::new()
+            .process(&input, &dir.join(name), params)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mock_ace_step_same_seed_is_identical() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 4)
+            .with_seed(42);
+
+        let a = render_ace_step(temp.path(), "a.wav", &params);
+        let b = render_ace_step(temp.path(), "b.wav", &params);
+
+        assert_eq!(a.seed, Some(42));
+        assert_eq!(b.seed, Some(42));
+        assert_eq!(
+            std::fs::read(temp.path().join("a.wav")).unwrap(),
+            std::fs::read(temp.path().join("b.wav")).unwrap()
+        );
+
+        let c = render_ace_step(temp.path(), "c.wav", &params.clone().with_seed(7));
+        assert_eq!(c.seed, Some(7));
+        assert_ne!(
+            std::fs::read(temp.path().join("a.wav")).unwrap(),
+            std::fs::read(temp.path().join("c.wav")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mock_ace_step_random_seed_is_recorded() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 4);
+
+        let first = render_ace_step(temp.path(), "first.wav", &params);
+        let seed = first.seed.expect("random seed should be recorded");
+
+        render_ace_step(temp.path(), "replay.wav", &params.clone().with_seed(seed));
+        assert_eq!(
+            std::fs::read(temp.path().join("first.wav")).unwrap(),
+            std::fs::read(temp.path().join("replay.wav")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mock_ace_step_keeps_44k_input_at_44k() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let input = temp.path().join("in.wav");
+        let output = temp.path().join("out.wav");
+        let tone = crate::engine::io::generate_test_tone(220.0, 0.5, 44100);
+        export_audio(&tone, &input, ExportFormat::new(44100, 32)).unwrap();
+
+        // Through the cache too, which decodes the input to key it
+        let cache = crate::neural::NeuralCache::new(temp.path().join("cache")).unwrap();
+        let model = crate::neural::CachedModel::new(MockAceStep::new(), cache);
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 2)
+            .with_seed(7);
+        assert!(model.process(&input, &output, &params).unwrap().success);
+
+        let spec = hound::WavReader::open(&output).unwrap().spec();
+        assert_eq!(spec.sample_rate, 44100);
+        let rendered = import_audio_native(&output).unwrap();
+        assert_eq!(rendered.sample_rate, 44100);
+        assert_eq!(rendered.len(), tone.len());
+    }
+
+    #[test]
+    fn test_mock_ace_step_streaming_ends_with_process_output() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 5)
+            .with_seed(42);
+        render_ace_step(temp.path(), "out.wav", &params);
+        let processed = import_audio(&temp.path().join("out.wav")).unwrap();
+        let input = import_audio(&temp.path().join("in.wav")).unwrap();
+
+        let mut partials = Vec::new();
+        let streamed = MockAceStep::new()
+            .process_streaming(&input, &params, &mut |partial| {
+                partials.push(partial.clone());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert_eq!(partials.len(), 5);
+        assert_eq!(streamed.samples, processed.samples);
+        assert_eq!(partials.last().unwrap().samples, processed.samples);
+
+        // Each step is closer to the finished render, and all are valid audio
+        let distance = |partial: &AudioBuffer| -> f32 {
+            partial.samples[0]
+                .iter()
+                .zip(&processed.samples[0])
+                .map(|(a, b)| (a - b).abs())
+                .sum()
+        };
+        for pair in partials.windows(2) {
+            assert!(distance(&pair[1]) < distance(&pair[0]));
+        }
+        for partial in &partials {
+            assert_eq!(partial.len(), input.len());
+            assert!(partial.samples.iter().flatten().all(|s| s.is_finite()));
+        }
+    }
+
+    #[test]
+    fn test_mock_ace_step_streaming_cancel() {
+        let input = crate::engine::io::generate_test_tone(220.0, 0.1, INTERNAL_SAMPLE_RATE);
+        let params = NeuralModelParams::new().with_param("prompt", "jazz version");
+
+        let mut calls = 0;
+        let result = MockAceStep::new().process_streaming(&input, &params, &mut |_| {
+            calls += 1;
+            if calls == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        assert!(matches!(result, Err(NuevaError::Cancelled)));
+        assert_eq!(calls, 2);
+    }
 }
@@ -2,19 +2,45 @@
 //!
 //! This module provides:
 //! - `NeuralModel` trait for all neural processors
-//! - Model registry with metadata
+//! - Model registry with metadata and discovery from manifest files
 //! - Context tracking for intentional artifacts
+//! - On-disk result caching keyed by input and parameters
+//! - Chunked processing of long audio with crossfaded seams
+//! - Timeouts for backends that hang
+//! - Models isolated in a child process per run
 //! - Mock implementations for testing
 //! - Real ACE-Step 1.5 integration via Python bridge
+//! - Local ONNX models via ONNX Runtime (`onnx` feature)
 
 mod ace_step;
+mod cache;
+mod chunking;
 mod context;
+mod gpu;
+mod manifest;
 mod mock;
 mod model;
+#[cfg(feature = "onnx")]
+mod onnx;
 mod registry;
+mod subprocess;
+mod timeout;
 
 pub use ace_step::{AceStep, AceStepMode};
-pub use context::NeuralContextTracker;
+pub use cache::{CachedModel, NeuralCache, NEURAL_CACHE_DIR};
+pub use chunking::process_chunked;
+pub use context::{IntentionalArtifact, NeuralContextTracker};
+pub use gpu::{can_run_ace_step, gpu_status_summary, GpuInfo, QuantizationLevel};
+pub use manifest::{compare_versions, InstalledModel, ModelManifest, MANIFEST_FILE_NAME};
 pub use mock::*;
-pub use model::{NeuralModel, NeuralModelInfo, NeuralModelParams, ProcessingResult};
-pub use registry::NeuralModelRegistry;
+pub use model::{
+    NeuralModel, NeuralModelInfo, NeuralModelParams, ParamSpec, ParamType, PartialCallback,
+    ProcessingResult, ProgressCallback, ProgressReporter,
+};
+#[cfg(feature = "onnx")]
+pub use onnx::{OnnxModel, DENOISE_MODEL_ENV};
+pub use registry::{NeuralModelRegistry, MODELS_DIR_ENV};
+pub use subprocess::{
+    SubprocessModel, SUBPROCESS_INPUT_FILE, SUBPROCESS_OUTPUT_FILE, SUBPROCESS_PARAMS_FILE,
+};
+pub use timeout::{neural_timeout, TimeoutModel, DEFAULT_NEURAL_TIMEOUT_MS, NEURAL_TIMEOUT_ENV};
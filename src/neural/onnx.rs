@@ -0,0 +1,372 @@
+//! ONNX Runtime backend
+//!
+//! Runs a local `.onnx` graph as a [`NeuralModel`], without the ACE-Step
+//! bridge. The graph takes one float tensor of audio and returns one of
+//! the same shape. Supported input layouts are `[samples]`, `[channels,
+//! samples]` and `[batch, channels, samples]` (batch 1); dynamic (`-1`)
+//! dimensions take the buffer's size. A graph with a fixed mono channel
+//! dimension is run once per channel.
+//!
+//! ONNX Runtime itself is loaded at runtime: set `ORT_DYLIB_PATH` to the
+//! `libonnxruntime` library, or have it on the library search path.
+
+use super::model::{
+    NeuralModel, NeuralModelInfo, NeuralModelParams, ParamSpec, ParamType, ProcessingResult,
+};
+use super::registry::create_model_info;
+use crate::engine::buffer::AudioBuffer;
+use crate::engine::io::{export_audio, import_audio_native, ExportFormat};
+use crate::error::{NuevaError, Result};
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::{Tensor, ValueType};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Environment variable naming the denoiser graph registered by default
+pub const DENOISE_MODEL_ENV: &str = "NUEVA_ONNX_DENOISE_MODEL";
+
+/// Which input dimensions the graph fixes (`None` = dynamic)
+#[derive(Debug, Clone, PartialEq)]
+struct TensorLayout {
+    /// Declared shape, `-1` for dynamic dimensions
+    dims: Vec<i64>,
+    channels: Option<usize>,
+    samples: Option<usize>,
+}
+
+impl TensorLayout {
+    fn from_dims(dims: &[i64]) -> Result<Self> {
+        let fixed = |d: i64| (d >= 0).then_some(d as usize);
+        let (channels, samples) = match *dims {
+            [samples] => (Some(1), fixed(samples)),
+            [channels, samples] => (fixed(channels), fixed(samples)),
+            [batch, channels, samples] => {
+                if batch > 1 {
+                    return Err(shape_error(format!(
+                        "input batch dimension is {}; only a batch of 1 is supported",
+                        batch
+                    )));
+                }
+                (fixed(channels), fixed(samples))
+            }
+            _ => {
+                return Err(shape_error(format!(
+                    "input has shape {:?}; expected [samples], [channels, samples] or [batch, channels, samples]",
+                    dims
+                )))
+            }
+        };
+        Ok(Self {
+            dims: dims.to_vec(),
+            channels,
+            samples,
+        })
+    }
+
+    /// Concrete shape for one run of `channels` x `samples`
+    fn shape_for(&self, channels: usize, samples: usize) -> Vec<i64> {
+        match self.dims.len() {
+            1 => vec![samples as i64],
+            2 => vec![channels as i64, samples as i64],
+            _ => vec![1, channels as i64, samples as i64],
+        }
+    }
+}
+
+fn shape_error(reason: String) -> NuevaError {
+    NuevaError::AiProcessingError {
+        reason: format!("ONNX tensor shape mismatch: {}", reason),
+    }
+}
+
+fn ort_error(context: &str, error: ort::Error) -> NuevaError {
+    NuevaError::AiProcessingError {
+        reason: format!("{}: {}", context, error),
+    }
+}
+
+/// A neural model backed by an ONNX graph
+pub struct OnnxModel {
+    info: NeuralModelInfo,
+    model_path: PathBuf,
+    layout: TensorLayout,
+    /// Running a session needs exclusive access
+    session: Mutex<Session>,
+}
+
+impl OnnxModel {
+    /// Load a graph, describing it with `info`
+    ///
+    /// Fails if the file is missing, ONNX Runtime cannot be loaded, or the
+    /// graph's first input is not a float tensor in a supported layout.
+    pub fn load(path: impl AsRef<Path>, info: NeuralModelInfo) -> Result<Self> {
+        let model_path = path.as_ref().to_path_buf();
+        if !model_path.is_file() {
+            return Err(NuevaError::FileNotFound {
+                path: model_path.display().to_string(),
+                source: None,
+            });
+        }
+
+        // ort panics when the runtime library is missing or incompatible
+        let session = panic::catch_unwind(AssertUnwindSafe(|| {
+            Session::builder().and_then(|builder| builder.commit_from_file(&model_path))
+        }))
+        .map_err(|payload| NuevaError::AiProcessingError {
+            reason: format!(
+                "ONNX Runtime could not be loaded (set ORT_DYLIB_PATH): {}",
+                panic_message(&payload)
+            ),
+        })?
+        .map_err(|e| ort_error(&format!("Failed to load {}", model_path.display()), e))?;
+
+        let layout = match session.inputs.as_slice() {
+            [input] => TensorLayout::from_dims(&float_tensor_dims(&input.input_type, "input")?)?,
+            inputs => {
+                return Err(shape_error(format!(
+                    "graph has {} inputs; expected exactly one audio tensor",
+                    inputs.len()
+                )))
+            }
+        };
+        if session.outputs.is_empty() {
+            return Err(shape_error("graph has no outputs".to_string()));
+        }
+        float_tensor_dims(&session.outputs[0].output_type, "output")?;
+
+        Ok(Self {
+            info,
+            model_path,
+            layout,
+            session: Mutex::new(session),
+        })
+    }
+
+    /// Load a denoiser graph under the `denoise` model ID
+    ///
+    /// Registered in place of the mock denoiser, so it takes the same
+    /// `strength` parameter (a wet/dry blend of the graph's output).
+    pub fn load_denoiser(path: impl AsRef<Path>) -> Result<Self> {
+        Self::load(
+            path,
+            create_model_info(
+                "denoise",
+                "ONNX Denoise",
+                "onnx",
+                "Noise reduction with a local ONNX model",
+                vec!["noise_removal", "hiss_removal", "room_tone_reduction"],
+                vec!["Noise, hiss, cleanup, clarity issues"],
+                vec!["Quality depends on the loaded model"],
+                vec!["Musical noise at high strength"],
+                0.0,
+                "Real-time capable on CPU",
+                vec![ParamSpec {
+                    name: "strength".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 1.0 },
+                    description: "Blend between the input (0) and the model output (1)".to_string(),
+                    default: Some(serde_json::json!(1.0)),
+                    required: false,
+                    unit: None,
+                }],
+            ),
+        )
+    }
+
+    /// Path of the loaded graph
+    pub fn model_path(&self) -> &Path {
+        &self.model_path
+    }
+
+    /// Declared input shape, `-1` for dynamic dimensions
+    pub fn input_shape(&self) -> &[i64] {
+        &self.layout.dims
+    }
+
+    /// Run the graph over a whole buffer
+    ///
+    /// Graphs with a fixed sample length only accept buffers of exactly
+    /// that length; feed longer audio through
+    /// [`NeuralModel::process_chunked`] with a matching chunk size.
+    pub fn infer(&self, input: &AudioBuffer) -> Result<AudioBuffer> {
+        let channels = input.num_channels();
+        let samples = input.len();
+
+        if samples == 0 {
+            return Err(NuevaError::InvalidParameter {
+                param: "input".to_string(),
+                value: "0 samples".to_string(),
+                expected: "audio with at least one sample".to_string(),
+            });
+        }
+        if let Some(expected) = self.layout.samples {
+            if expected != samples {
+                return Err(shape_error(format!(
+                    "model expects {} samples per run ({:.3}s) but got {}; use process_chunked with that chunk length",
+                    expected,
+                    expected as f64 / input.sample_rate as f64,
+                    samples
+                )));
+            }
+        }
+
+        // A fixed mono input runs once per channel; anything else runs the
+        // whole buffer at once
+        let groups: Vec<Vec<&[f32]>> = match self.layout.channels {
+            Some(1) if channels > 1 => input.samples.iter().map(|ch| vec![ch.as_slice()]).collect(),
+            Some(expected) if expected != channels => {
+                return Err(shape_error(format!(
+                    "model expects {} channels but the audio has {}",
+                    expected, channels
+                )))
+            }
+            _ => vec![input.samples.iter().map(|ch| ch.as_slice()).collect()],
+        };
+
+        let mut output = Vec::with_capacity(channels);
+        for group in groups {
+            output.extend(self.run_group(&group, samples)?);
+        }
+
+        Ok(AudioBuffer {
+            samples: output,
+            sample_rate: input.sample_rate,
+        })
+    }
+
+    /// Run one tensor of `channels.len()` x `samples` and split the result
+    fn run_group(&self, channels: &[&[f32]], samples: usize) -> Result<Vec<Vec<f32>>> {
+        let shape = self.layout.shape_for(channels.len(), samples);
+        let data: Vec<f32> = channels.iter().flat_map(|ch| ch.iter().copied()).collect();
+        let tensor = Tensor::from_array((shape.clone(), data))
+            .map_err(|e| ort_error("Failed to build the input tensor", e))?;
+
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|_| NuevaError::AiProcessingError {
+                reason: "ONNX session lock poisoned".to_string(),
+            })?;
+        let outputs = session
+            .run(ort::inputs![tensor])
+            .map_err(|e| ort_error("ONNX inference failed", e))?;
+        let (out_shape, out_data) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| ort_error("ONNX output is not a float tensor", e))?;
+
+        if out_shape.len() != shape.len() || out_shape[..] != shape[..] {
+            return Err(shape_error(format!(
+                "output has shape {:?} for input {:?}; expected the same shape",
+                &out_shape[..],
+                shape
+            )));
+        }
+
+        Ok(out_data.chunks(samples).map(|ch| ch.to_vec()).collect())
+    }
+}
+
+impl NeuralModel for OnnxModel {
+    fn info(&self) -> &NeuralModelInfo {
+        &self.info
+    }
+
+    /// Inference is deterministic; the seed is only recorded so results
+    /// carry one like every other model's
+    fn process(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let seed = params.seed_or_random();
+        let strength = params.get_f32("strength").unwrap_or(1.0).clamp(0.0, 1.0);
+
+        let input = import_audio_native(input_path)?;
+        let mut output = self.infer(&input)?;
+        if strength < 1.0 {
+            for (out_ch, in_ch) in output.samples.iter_mut().zip(&input.samples) {
+                for (wet, &dry) in out_ch.iter_mut().zip(in_ch) {
+                    *wet = dry + (*wet - dry) * strength;
+                }
+            }
+        }
+        export_audio(
+            &output,
+            output_path,
+            ExportFormat::new(output.sample_rate, 32),
+        )?;
+
+        let file_name = self
+            .model_path
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+        Ok(ProcessingResult::success(
+            output_path.to_string_lossy().to_string(),
+            format!("Ran {} at {:.0}% strength", file_name, strength * 100.0),
+            start.elapsed().as_millis() as u64,
+        )
+        .with_seed(seed))
+    }
+}
+
+/// Dimensions of a float tensor input or output
+fn float_tensor_dims(value_type: &ValueType, what: &str) -> Result<Vec<i64>> {
+    match value_type {
+        ValueType::Tensor {
+            ty: TensorElementType::Float32,
+            shape,
+            ..
+        } => Ok(shape.to_vec()),
+        other => Err(shape_error(format!(
+            "{} is {:?}; expected a float32 tensor",
+            what, other
+        ))),
+    }
+}
+
+fn panic_message(payload: &Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| "unknown error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts() {
+        let layout = TensorLayout::from_dims(&[1, -1, -1]).unwrap();
+        assert_eq!((layout.channels, layout.samples), (None, None));
+        assert_eq!(layout.shape_for(2, 480), vec![1, 2, 480]);
+
+        let layout = TensorLayout::from_dims(&[1, 16000]).unwrap();
+        assert_eq!((layout.channels, layout.samples), (Some(1), Some(16000)));
+
+        let layout = TensorLayout::from_dims(&[-1]).unwrap();
+        assert_eq!(layout.channels, Some(1));
+        assert_eq!(layout.shape_for(1, 64), vec![64]);
+    }
+
+    #[test]
+    fn test_unsupported_layouts_are_errors() {
+        for dims in [&[][..], &[1, 1, 2, 64], &[4, 2, -1]] {
+            let err = TensorLayout::from_dims(dims).unwrap_err();
+            assert!(err.to_string().contains("shape mismatch"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let err = OnnxModel::load_denoiser("/nonexistent/denoise.onnx")
+            .err()
+            .unwrap();
+        assert!(matches!(err, NuevaError::FileNotFound { .. }));
+    }
+}
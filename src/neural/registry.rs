@@ -3,11 +3,18 @@
 //! Manages available neural models and their metadata.
 //! Implements §5.3 from the spec.
 
+use super::gpu::{GpuInfo, QuantizationLevel};
+use super::manifest::{compare_versions, find_manifests, ModelManifest};
 use super::model::{NeuralModel, NeuralModelInfo, ParamSpec};
 use crate::error::{NuevaError, Result};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
+/// Environment variable naming the directory of installed model manifests
+pub const MODELS_DIR_ENV: &str = "NUEVA_MODELS_DIR";
+
 /// Registry of available neural models
 pub struct NeuralModelRegistry {
     models: HashMap<String, Arc<dyn NeuralModel>>,
@@ -52,6 +59,21 @@ impl NeuralModelRegistry {
             registry.register(Arc::new(super::mock::MockAceStep::new()));
         }
 
+        // A local ONNX denoiser replaces the mock when one is configured
+        #[cfg(feature = "onnx")]
+        if let Ok(path) = std::env::var(super::onnx::DENOISE_MODEL_ENV) {
+            match super::onnx::OnnxModel::load_denoiser(&path) {
+                Ok(model) => registry.register(Arc::new(model)),
+                Err(e) => log::warn!("Not loading ONNX denoiser {}: {}", path, e),
+            }
+        }
+
+        // Installed models replace the built-in ones they share an ID with
+        if let Ok(dir) = std::env::var(MODELS_DIR_ENV) {
+            let found = registry.discover(&dir);
+            log::info!("Discovered {} installed model(s) in {}", found, dir);
+        }
+
         registry
     }
 
@@ -68,6 +90,61 @@ impl NeuralModelRegistry {
         registry
     }
 
+    /// Create a registry of the models installed in a directory
+    ///
+    /// See [`NeuralModelRegistry::discover`]. A missing or empty directory
+    /// gives an empty registry.
+    pub fn scan_directory(path: impl AsRef<Path>) -> Self {
+        let mut registry = Self::new();
+        registry.discover(path);
+        registry
+    }
+
+    /// Register every model described by a manifest in `dir`
+    ///
+    /// Malformed manifests, and manifests whose model file is missing or
+    /// can't be loaded, are skipped with a warning before versions are
+    /// compared, so a broken newer install doesn't hide a working older
+    /// one. When several manifests share a model ID the highest version
+    /// wins. Discovered models replace registered ones with the same ID.
+    /// Returns the number of models registered.
+    pub fn discover(&mut self, dir: impl AsRef<Path>) -> usize {
+        let mut latest: HashMap<String, Arc<dyn NeuralModel>> = HashMap::new();
+        for path in find_manifests(dir.as_ref()) {
+            let model = match ModelManifest::load(&path).and_then(ModelManifest::into_model) {
+                Ok(model) => model,
+                Err(e) => {
+                    log::warn!("Skipping model manifest {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let info = model.info();
+            match latest.get(&info.id) {
+                Some(existing)
+                    if compare_versions(&info.version, &existing.info().version)
+                        != Ordering::Greater =>
+                {
+                    log::warn!(
+                        "Skipping {} {} from {}: version {} is already installed",
+                        info.id,
+                        info.version,
+                        path.display(),
+                        existing.info().version
+                    );
+                }
+                _ => {
+                    latest.insert(info.id.clone(), model);
+                }
+            }
+        }
+
+        let registered = latest.len();
+        for model in latest.into_values() {
+            self.register(model);
+        }
+        registered
+    }
+
     /// Register a model
     pub fn register(&mut self, model: Arc<dyn NeuralModel>) {
         let info = model.info().clone();
@@ -106,13 +183,25 @@ impl NeuralModelRegistry {
         self.models.contains_key(id)
     }
 
+    /// Pick the best quantization for a model on the given hardware
+    pub fn recommended_quantization(&self, id: &str, gpu: &GpuInfo) -> Result<QuantizationLevel> {
+        let info = self.get_info(id).ok_or_else(|| NuevaError::ModelNotFound {
+            model: id.to_string(),
+        })?;
+        gpu.recommended_quantization(info)
+    }
+
     /// Get the best model for a given capability
+    ///
+    /// Models that can't run here, such as installed ones without an
+    /// in-process backend, are skipped.
     pub fn find_model_for_capability(&self, capability: &str) -> Option<&str> {
         for (id, info) in &self.model_info {
             if info
                 .capabilities
                 .iter()
                 .any(|c| c.to_lowercase().contains(&capability.to_lowercase()))
+                && self.models.get(id).is_some_and(|m| m.is_available())
             {
                 return Some(id.as_str());
             }
@@ -145,6 +234,7 @@ impl Default for NeuralModelRegistry {
 }
 
 /// Create model info for a standard model from the spec
+#[allow(clippy::too_many_arguments)]
 pub fn create_model_info(
     id: &str,
     name: &str,
@@ -168,11 +258,15 @@ pub fn create_model_info(
         limitations: limitations.into_iter().map(String::from).collect(),
         known_artifacts: known_artifacts.into_iter().map(String::from).collect(),
         vram_requirement_gb: vram_gb,
+        param_count: None,
         inference_time: inference_time.to_string(),
         supported_params: params,
     }
 }
 
+/// ACE-Step 1.5 parameter count (3.5B)
+pub const ACE_STEP_PARAM_COUNT: u64 = 3_500_000_000;
+
 /// Standard style transfer presets from spec §5.3
 pub const STYLE_TRANSFER_PRESETS: &[&str] = &[
     "vintage_analog",
@@ -207,6 +301,8 @@ pub const ENHANCE_TARGETS: &[&str] = &["clarity", "fullness", "presence", "width
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::neural::manifest::MANIFEST_FILE_NAME;
+    use crate::neural::model::ParamType;
 
     #[test]
     fn test_registry_defaults() {
@@ -247,4 +343,172 @@ mod tests {
         assert!(models.contains(&"style-transfer"));
         assert!(models.contains(&"denoise"));
     }
+
+    fn write_manifest(path: &Path, id: &str, version: &str, file: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let manifest = serde_json::json!({
+            "id": id,
+            "name": format!("{} model", id),
+            "version": version,
+            "file": file,
+        });
+        std::fs::write(path, manifest.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_scan_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("declick")).unwrap();
+        std::fs::write(dir.path().join("declick/weights.bin"), b"").unwrap();
+        std::fs::write(
+            dir.path().join("declick/manifest.json"),
+            serde_json::json!({
+                "id": "declick",
+                "name": "Declicker",
+                "version": "1.0.0",
+                "file": "weights.bin",
+                "backend": "torch",
+                "capabilities": ["declick"],
+                "supported_params": [
+                    {
+                        "name": "sensitivity",
+                        "param_type": {"type": "Float", "min": 0.0, "max": 1.0},
+                        "default": 0.5
+                    },
+                    {
+                        "name": "mode",
+                        "param_type": {"type": "Enum", "options": ["soft", "hard"]},
+                        "description": "Detection mode",
+                        "required": true
+                    }
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("hum.bin"), b"").unwrap();
+        write_manifest(&dir.path().join("hum.json"), "dehum", "0.3", "hum.bin");
+
+        let registry = NeuralModelRegistry::scan_directory(dir.path());
+        let mut models = registry.list_models();
+        models.sort();
+        assert_eq!(models, vec!["declick", "dehum"]);
+
+        let info = registry.get_info("declick").unwrap();
+        assert_eq!(info.name, "Declicker");
+        let sensitivity = &info.supported_params[0];
+        assert!(matches!(
+            sensitivity.param_type,
+            ParamType::Float { min, max } if min == 0.0 && max == 1.0
+        ));
+        assert_eq!(sensitivity.default, Some(serde_json::json!(0.5)));
+        assert!(!sensitivity.required);
+        let mode = &info.supported_params[1];
+        assert!(
+            matches!(&mode.param_type, ParamType::Enum { options } if options == &["soft", "hard"])
+        );
+        assert!(mode.required);
+
+        // No in-process backend for this one
+        let model = registry.get("declick").unwrap();
+        assert!(!model.is_available());
+        let err = model
+            .process(
+                Path::new("in.wav"),
+                Path::new("out.wav"),
+                &Default::default(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("torch"), "{}", err);
+        // ...so it isn't offered for its capability
+        assert_eq!(registry.find_model_for_capability("declick"), None);
+    }
+
+    #[test]
+    fn test_scan_skips_malformed_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("model.bin"), b"").unwrap();
+        write_manifest(&dir.path().join("good.json"), "good", "1.0", "model.bin");
+        std::fs::write(dir.path().join("broken.json"), "{ not json").unwrap();
+        write_manifest(
+            &dir.path().join("no_file.json"),
+            "ghost",
+            "1.0",
+            "missing.bin",
+        );
+        write_manifest(&dir.path().join("no_id.json"), " ", "1.0", "model.bin");
+        std::fs::write(
+            dir.path().join("bad_param.json"),
+            serde_json::json!({
+                "id": "bad", "name": "Bad", "version": "1", "file": "model.bin",
+                "supported_params": [{"name": "x", "param_type": {"type": "Complex"}}]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut registry = NeuralModelRegistry::new();
+        assert_eq!(registry.discover(dir.path()), 1);
+        assert_eq!(registry.list_models(), vec!["good"]);
+    }
+
+    #[test]
+    fn test_scan_keeps_highest_version() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("model.bin"), b"").unwrap();
+        for (name, version) in [("a", "1.2.0"), ("b", "1.10.0"), ("c", "1.9")] {
+            write_manifest(
+                &dir.path().join(name).join(MANIFEST_FILE_NAME),
+                "denoise",
+                version,
+                "../model.bin",
+            );
+        }
+
+        let registry = NeuralModelRegistry::scan_directory(dir.path());
+        assert_eq!(registry.list_models(), vec!["denoise"]);
+        assert_eq!(registry.get_info("denoise").unwrap().version, "1.10.0");
+    }
+
+    #[test]
+    fn test_scan_skips_broken_newer_version() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("model.bin"), b"").unwrap();
+        write_manifest(&dir.path().join("a.json"), "denoise", "1.0", "model.bin");
+        write_manifest(&dir.path().join("b.json"), "denoise", "2.0", "missing.bin");
+        std::fs::write(
+            dir.path().join("c.json"),
+            serde_json::json!({"id": "denoise", "version": "3.0"}).to_string(),
+        )
+        .unwrap();
+
+        let registry = NeuralModelRegistry::scan_directory(dir.path());
+        assert_eq!(registry.get_info("denoise").unwrap().version, "1.0");
+    }
+
+    #[test]
+    fn test_scan_empty_or_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(NeuralModelRegistry::scan_directory(dir.path())
+            .list_models()
+            .is_empty());
+        assert!(
+            NeuralModelRegistry::scan_directory(dir.path().join("missing"))
+                .list_models()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_recommended_quantization() {
+        let registry = NeuralModelRegistry::with_mocks();
+
+        let level = registry
+            .recommended_quantization("ace-step", &GpuInfo::cpu_only())
+            .unwrap();
+        assert_eq!(level, QuantizationLevel::CPU);
+        assert!(registry
+            .recommended_quantization("nonexistent", &GpuInfo::cpu_only())
+            .is_err());
+    }
 }
@@ -0,0 +1,508 @@
+//! Neural models run in a child process
+//!
+//! Python-based models can crash, leak memory or hang. [`SubprocessModel`]
+//! starts a fresh process for every run, so none of that reaches the host:
+//! a crash becomes an error, leaked memory goes away with the process, and
+//! a hang is killed at the deadline.
+//!
+//! Each run gets a private scratch directory, which is the child's working
+//! directory. The host copies the input audio there as
+//! [`SUBPROCESS_INPUT_FILE`] and the pinned parameters as
+//! [`SUBPROCESS_PARAMS_FILE`]; the child writes [`SUBPROCESS_OUTPUT_FILE`]
+//! beside them. Over stdout the child prints one JSON object per line:
+//!
+//! - `{"progress": 0.5}` or `{"step": 3, "total_steps": 8}` while it works
+//! - `{"success": true, "description": "...", "intentional_artifacts": [...]}`
+//!   or `{"success": false, "error": "..."}` when it is done
+//!
+//! Other lines are ignored, so a chatty model doesn't break the protocol.
+//! The scratch directory is removed when the run ends, however it ends.
+
+use super::model::{
+    NeuralModel, NeuralModelInfo, NeuralModelParams, ProcessingResult, ProgressCallback,
+    ProgressReporter,
+};
+use super::timeout::neural_timeout;
+use crate::error::{NuevaError, Result};
+use serde::Deserialize;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name of the input audio in the child's working directory
+pub const SUBPROCESS_INPUT_FILE: &str = "input.wav";
+
+/// Name of the parameters JSON in the child's working directory
+pub const SUBPROCESS_PARAMS_FILE: &str = "params.json";
+
+/// Name the child writes its output audio to
+pub const SUBPROCESS_OUTPUT_FILE: &str = "output.wav";
+
+/// How much of the child's stderr is kept for error messages
+const STDERR_TAIL_BYTES: usize = 2048;
+
+/// Distinguishes scratch directories of concurrent runs
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A line the child printed to stdout
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ChildMessage {
+    Progress { progress: f32 },
+    Step { step: usize, total_steps: usize },
+    Done(ChildResponse),
+}
+
+/// The child's final report
+#[derive(Debug, Deserialize)]
+struct ChildResponse {
+    success: bool,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    intentional_artifacts: Vec<String>,
+}
+
+/// A per-run directory that is removed when dropped
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn create(root: &Path) -> Result<Self> {
+        let id = RUN_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = root.join(format!("nueva-subprocess-{}-{}", std::process::id(), id));
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A child process that is killed unless it has already exited
+struct RunningChild(Child);
+
+impl Drop for RunningChild {
+    fn drop(&mut self) {
+        if let Ok(None) = self.0.try_wait() {
+            let _ = self.0.kill();
+        }
+        let _ = self.0.wait();
+    }
+}
+
+/// A neural model that runs a command in a fresh process for each run
+pub struct SubprocessModel {
+    info: NeuralModelInfo,
+    program: PathBuf,
+    args: Vec<String>,
+    timeout: Duration,
+    scratch_root: PathBuf,
+}
+
+impl SubprocessModel {
+    /// Run `program` with `args` for each request, described by `info`
+    ///
+    /// Runs are killed after [`neural_timeout`] unless
+    /// [`with_timeout`](Self::with_timeout) sets another deadline, and
+    /// scratch directories go in the system temp directory.
+    pub fn new(info: NeuralModelInfo, program: impl Into<PathBuf>, args: &[&str]) -> Self {
+        Self {
+            info,
+            program: program.into(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            timeout: neural_timeout(),
+            scratch_root: std::env::temp_dir(),
+        }
+    }
+
+    /// Create each run's scratch directory inside `root`
+    pub fn with_scratch_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.scratch_root = root.into();
+        self
+    }
+
+    /// Kill runs that take longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The deadline for each run
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn crashed(&self, detail: String) -> NuevaError {
+        NuevaError::AiProcessingError {
+            reason: format!("Neural model '{}' crashed: {}", self.info.id, detail),
+        }
+    }
+
+    fn timed_out(&self) -> NuevaError {
+        log::warn!(
+            "Neural model '{}' exceeded {}ms; killing it",
+            self.info.id,
+            self.timeout.as_millis()
+        );
+        NuevaError::AceStepTimeout {
+            timeout_ms: self.timeout.as_millis() as u64,
+        }
+    }
+
+    /// Wait for the child to exit, up to `deadline`
+    fn wait_until(
+        &self,
+        child: &mut RunningChild,
+        deadline: Instant,
+    ) -> Result<std::process::ExitStatus> {
+        loop {
+            if let Some(status) = child.0.try_wait()? {
+                return Ok(status);
+            }
+            if Instant::now() >= deadline {
+                return Err(self.timed_out());
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Wait for the child's final report, forwarding progress
+    fn await_response(
+        &self,
+        child: &mut RunningChild,
+        progress: &mut ProgressReporter<'_>,
+        stderr: thread::JoinHandle<String>,
+    ) -> Result<ChildResponse> {
+        let stdout = child
+            .0
+            .stdout
+            .take()
+            .ok_or_else(|| self.crashed("no stdout".into()))?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if let Ok(message) = serde_json::from_str::<ChildMessage>(&line) {
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(ChildMessage::Progress { progress: p }) => progress.report(p)?,
+                Ok(ChildMessage::Step { step, total_steps }) => {
+                    progress.report_step(step, total_steps)?
+                }
+                Ok(ChildMessage::Done(response)) => {
+                    // Reporting success and then dying may mean the output
+                    // is incomplete
+                    let status = self.wait_until(child, deadline)?;
+                    if response.success && !status.success() {
+                        return Err(self.crashed(exit_detail(status, stderr)));
+                    }
+                    return Ok(response);
+                }
+                Err(RecvTimeoutError::Timeout) => return Err(self.timed_out()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    // Closing stdout doesn't mean it exits; it still only
+                    // gets until the deadline
+                    let status = self.wait_until(child, deadline)?;
+                    return Err(self.crashed(exit_detail(status, stderr)));
+                }
+            }
+        }
+    }
+}
+
+impl NeuralModel for SubprocessModel {
+    fn info(&self) -> &NeuralModelInfo {
+        &self.info
+    }
+
+    fn process(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        self.process_with_progress(input_path, output_path, params, &mut |_| {
+            std::ops::ControlFlow::Continue(())
+        })
+    }
+
+    /// Forwards the child's progress lines
+    ///
+    /// Cancelling from `on_progress`, like a timeout, kills the child. The
+    /// output file is only written once the child has succeeded.
+    fn process_with_progress(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<ProcessingResult> {
+        let start = Instant::now();
+        let mut progress = ProgressReporter::new(on_progress);
+        progress.report(0.0)?;
+
+        // Pin the seed so a run can be replayed
+        let seed = params.seed_or_random();
+        let params = params.clone().with_seed(seed);
+
+        let scratch = ScratchDir::create(&self.scratch_root)?;
+        fs::copy(input_path, scratch.0.join(SUBPROCESS_INPUT_FILE))?;
+        fs::write(
+            scratch.0.join(SUBPROCESS_PARAMS_FILE),
+            serde_json::to_string(&params)?,
+        )?;
+
+        let mut child = RunningChild(
+            Command::new(&self.program)
+                .args(&self.args)
+                .current_dir(&scratch.0)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| NuevaError::AiProcessingError {
+                    reason: format!(
+                        "Failed to start neural model '{}' ({}): {}",
+                        self.info.id,
+                        self.program.display(),
+                        e
+                    ),
+                })?,
+        );
+        let stderr = child.0.stderr.take().map(stderr_tail);
+        let stderr = stderr.unwrap_or_else(|| thread::spawn(String::new));
+
+        let response = self.await_response(&mut child, &mut progress, stderr)?;
+        if !response.success {
+            return Err(NuevaError::AiProcessingError {
+                reason: response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            });
+        }
+
+        let produced = scratch.0.join(SUBPROCESS_OUTPUT_FILE);
+        if !produced.exists() {
+            return Err(self.crashed(format!("it wrote no {}", SUBPROCESS_OUTPUT_FILE)));
+        }
+        // Copy rather than rename: the scratch directory may be on another
+        // filesystem
+        fs::copy(&produced, output_path)?;
+        progress.finish();
+
+        let description = response
+            .description
+            .unwrap_or_else(|| format!("Processed with {}", self.info.name));
+        Ok(ProcessingResult::success(
+            output_path.to_string_lossy().to_string(),
+            description,
+            start.elapsed().as_millis() as u64,
+        )
+        .with_artifacts(response.intentional_artifacts)
+        .with_seed(seed))
+    }
+
+    /// Whether the program exists and is executable, found on `PATH` if
+    /// it is a bare name; nothing is run
+    fn is_available(&self) -> bool {
+        if self.program.components().count() > 1 {
+            return is_executable(&self.program);
+        }
+        std::env::var_os("PATH").is_some_and(|path| {
+            std::env::split_paths(&path).any(|dir| is_executable(&dir.join(&self.program)))
+        })
+    }
+}
+
+/// Whether `path` is a file this process may execute
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+/// Collect the last [`STDERR_TAIL_BYTES`] of a child's stderr
+fn stderr_tail(mut stderr: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = stderr.read_to_end(&mut bytes);
+        let start = bytes.len().saturating_sub(STDERR_TAIL_BYTES);
+        String::from_utf8_lossy(&bytes[start..]).trim().to_string()
+    })
+}
+
+/// How a child exited, with the end of its stderr
+fn exit_detail(status: std::process::ExitStatus, stderr: thread::JoinHandle<String>) -> String {
+    let stderr = stderr.join().unwrap_or_default();
+    if stderr.is_empty() {
+        status.to_string()
+    } else {
+        format!("{} ({})", status, stderr)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::engine::io::{export_audio, generate_test_tone, import_audio, ExportFormat};
+    use crate::neural::mock::MockDenoise;
+    use std::ops::ControlFlow;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("in.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.2, 48000),
+            &input,
+            ExportFormat::default(),
+        )
+        .unwrap();
+        (temp, input)
+    }
+
+    /// A model running `script` under `sh`, with scratch space in `temp`
+    fn shell_model(temp: &TempDir, script: &str) -> SubprocessModel {
+        SubprocessModel::new(MockDenoise::new().info().clone(), "sh", &["-c", script])
+            .with_timeout(Duration::from_secs(10))
+            .with_scratch_root(temp.path().join("scratch"))
+    }
+
+    #[test]
+    fn test_echo_subprocess_round_trips_audio() {
+        let (temp, input) = setup();
+        let out = temp.path().join("out.wav");
+        let model = shell_model(
+            &temp,
+            r#"test -f params.json || exit 3
+               echo "loading weights"
+               echo '{"step": 1, "total_steps": 2}'
+               cp input.wav output.wav
+               echo '{"success": true, "description": "echo", "intentional_artifacts": ["none"]}'"#,
+        );
+
+        let mut reports = Vec::new();
+        let result = model
+            .process_with_progress(&input, &out, &NeuralModelParams::new(), &mut |p| {
+                reports.push(p);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert_eq!(result.description, "echo");
+        assert_eq!(result.intentional_artifacts, ["none"]);
+        assert!(result.seed.is_some());
+        assert_eq!(reports, [0.0, 0.5, 1.0]);
+        assert_eq!(
+            import_audio(&out).unwrap().samples,
+            import_audio(&input).unwrap().samples
+        );
+    }
+
+    #[test]
+    fn test_crash_is_an_error_and_cleans_up() {
+        let (temp, input) = setup();
+        let out = temp.path().join("out.wav");
+        let model = shell_model(&temp, "echo 'out of memory' >&2; kill -9 $$");
+        match model.process(&input, &out, &NeuralModelParams::new()) {
+            Err(NuevaError::AiProcessingError { reason }) => {
+                assert!(reason.contains("crashed"), "{}", reason);
+                assert!(reason.contains("out of memory"), "{}", reason);
+            }
+            other => panic!("expected a crash error, got {:?}", other.map(|r| r.success)),
+        }
+        assert!(!out.exists());
+
+        let failing = shell_model(&temp, r#"echo '{"success": false, "error": "bad prompt"}'"#);
+        assert!(matches!(
+            failing.process(&input, &out, &NeuralModelParams::new()),
+            Err(NuevaError::AiProcessingError { reason }) if reason == "bad prompt"
+        ));
+        let scratch = fs::read_dir(temp.path().join("scratch")).unwrap();
+        assert_eq!(scratch.count(), 0);
+    }
+
+    #[test]
+    fn test_timeout_and_cancel_kill_the_child() {
+        let (temp, input) = setup();
+        let out = temp.path().join("out.wav");
+
+        let hang = shell_model(&temp, "exec sleep 30").with_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+        assert!(matches!(
+            hang.process(&input, &out, &NeuralModelParams::new()),
+            Err(NuevaError::AceStepTimeout { timeout_ms: 100 })
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let slow = shell_model(&temp, r#"echo '{"progress": 0.1}'; exec sleep 30"#);
+        let start = Instant::now();
+        let result =
+            slow.process_with_progress(&input, &out, &NeuralModelParams::new(), &mut |p| {
+                if p > 0.0 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+        assert!(matches!(result, Err(NuevaError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(!out.exists());
+
+        // Closing stdout without exiting still runs into the deadline
+        let silent =
+            shell_model(&temp, "exec >&-; exec sleep 30").with_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+        assert!(matches!(
+            silent.process(&input, &out, &NeuralModelParams::new()),
+            Err(NuevaError::AceStepTimeout { .. })
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_availability_checks_the_program_without_running_it() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let info = MockDenoise::new().info().clone();
+        // Arguments that would fail are irrelevant: nothing is run
+        assert!(SubprocessModel::new(info.clone(), "sh", &["-c", "exit 1"]).is_available());
+        assert!(!SubprocessModel::new(info.clone(), "nueva-no-such-model", &[]).is_available());
+
+        let script = temp.path().join("model.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        let model = SubprocessModel::new(info, &script, &[]);
+        assert!(!model.is_available());
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(model.is_available());
+    }
+}
@@ -0,0 +1,326 @@
+//! Neural processing timeouts
+//!
+//! A backend can hang: a wedged subprocess, a GPU call that never returns.
+//! Most backends block without ever checking for cancellation, so
+//! [`TimeoutModel`] runs the wrapped model on a worker thread and stops
+//! waiting for it once the deadline passes, failing with
+//! `NuevaError::AceStepTimeout` so the caller can fall back to DSP.
+//!
+//! The worker renders into a scratch file next to the output, which is
+//! renamed into place only when the model succeeds in time. A run that
+//! times out never leaves a half-written output behind. Once abandoned,
+//! the model is aborted (see [`NeuralModel::abort`]) so a blocked call
+//! returns, the worker is told to stop at its next progress report, and
+//! it deletes its scratch file when it finishes.
+
+use super::model::{NeuralModel, NeuralModelInfo, NeuralModelParams, ProcessingResult};
+use super::model::{ProgressCallback, ProgressReporter};
+use crate::error::{NuevaError, Result};
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable overriding the neural timeout, in milliseconds
+pub const NEURAL_TIMEOUT_ENV: &str = "NUEVA_NEURAL_TIMEOUT_MS";
+
+/// Default neural timeout (10 minutes)
+pub const DEFAULT_NEURAL_TIMEOUT_MS: u64 = 600_000;
+
+/// Distinguishes scratch files of concurrent runs writing the same output
+static SCRATCH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The configured neural timeout
+///
+/// Reads [`NEURAL_TIMEOUT_ENV`], falling back to
+/// [`DEFAULT_NEURAL_TIMEOUT_MS`] when it is unset or not a positive number.
+pub fn neural_timeout() -> Duration {
+    let ms = std::env::var(NEURAL_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|&ms| ms > 0)
+        .unwrap_or(DEFAULT_NEURAL_TIMEOUT_MS);
+    Duration::from_millis(ms)
+}
+
+/// Messages from the worker thread
+enum WorkerMessage {
+    Progress(f32),
+    Done(Result<ProcessingResult>),
+}
+
+/// Wraps a model so every run is abandoned after a deadline
+pub struct TimeoutModel<M: NeuralModel> {
+    inner: Arc<M>,
+    timeout: Duration,
+}
+
+impl<M: NeuralModel + 'static> TimeoutModel<M> {
+    pub fn new(inner: M, timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            timeout,
+        }
+    }
+
+    /// Wrap a model using the timeout from [`neural_timeout`]
+    pub fn from_env(inner: M) -> Self {
+        Self::new(inner, neural_timeout())
+    }
+
+    /// The wrapped model
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// The deadline for each run
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn timeout_error(&self) -> NuevaError {
+        NuevaError::AceStepTimeout {
+            timeout_ms: self.timeout.as_millis() as u64,
+        }
+    }
+}
+
+impl<M: NeuralModel + 'static> NeuralModel for TimeoutModel<M> {
+    fn info(&self) -> &NeuralModelInfo {
+        self.inner.info()
+    }
+
+    fn process(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+    ) -> Result<ProcessingResult> {
+        self.process_with_progress(input_path, output_path, params, &mut |_| {
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Runs the model on a worker thread, forwarding its progress
+    ///
+    /// Cancelling from `on_progress` returns immediately, like a timeout;
+    /// in both cases the output file is left untouched.
+    fn process_with_progress(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+        params: &NeuralModelParams,
+        on_progress: ProgressCallback<'_>,
+    ) -> Result<ProcessingResult> {
+        let scratch = scratch_path(output_path);
+        let abandoned = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+
+        {
+            let model = Arc::clone(&self.inner);
+            let abandoned = Arc::clone(&abandoned);
+            let input_path = input_path.to_path_buf();
+            let scratch = scratch.clone();
+            let params = params.clone();
+            thread::Builder::new()
+                .name(format!("neural-{}", self.inner.id()))
+                .spawn(move || {
+                    let progress_tx = tx.clone();
+                    let result =
+                        model.process_with_progress(&input_path, &scratch, &params, &mut |p| {
+                            if abandoned.load(Ordering::SeqCst) {
+                                return ControlFlow::Break(());
+                            }
+                            let _ = progress_tx.send(WorkerMessage::Progress(p));
+                            ControlFlow::Continue(())
+                        });
+                    let _ = tx.send(WorkerMessage::Done(result));
+                    if abandoned.load(Ordering::SeqCst) {
+                        let _ = fs::remove_file(&scratch);
+                    }
+                })?;
+        }
+
+        // Set before cleaning up so a worker finishing concurrently also
+        // removes the scratch file
+        let abandon = |error: NuevaError| {
+            abandoned.store(true, Ordering::SeqCst);
+            let _ = fs::remove_file(&scratch);
+            Err(error)
+        };
+
+        let deadline = Instant::now() + self.timeout;
+        let mut progress = ProgressReporter::new(on_progress);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(WorkerMessage::Progress(p)) => {
+                    if let Err(e) = progress.report(p) {
+                        self.inner.abort();
+                        return abandon(e);
+                    }
+                }
+                Ok(WorkerMessage::Done(Ok(mut result))) => {
+                    if scratch.exists() {
+                        fs::rename(&scratch, output_path)?;
+                    }
+                    if result.output_path.is_some() {
+                        result.output_path = Some(output_path.to_string_lossy().to_string());
+                    }
+                    return Ok(result);
+                }
+                Ok(WorkerMessage::Done(Err(e))) => return abandon(e),
+                Err(RecvTimeoutError::Timeout) => {
+                    log::warn!(
+                        "Neural model '{}' exceeded {}ms; abandoning it",
+                        self.inner.id(),
+                        self.timeout.as_millis()
+                    );
+                    self.inner.abort();
+                    return abandon(self.timeout_error());
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return abandon(NuevaError::AiProcessingError {
+                        reason: format!("Neural model '{}' crashed", self.inner.id()),
+                    });
+                }
+            }
+        }
+    }
+
+    fn abort(&self) {
+        self.inner.abort()
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn validate_params(&self, params: &NeuralModelParams) -> Result<()> {
+        self.inner.validate_params(params)
+    }
+}
+
+/// Hidden scratch file beside `output_path`, keeping its extension
+fn scratch_path(output_path: &Path) -> PathBuf {
+    let id = SCRATCH_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = output_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output.wav".to_string());
+    output_path.with_file_name(format!(".partial-{}-{}-{}", std::process::id(), id, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::io::{export_audio, generate_test_tone, ExportFormat};
+    use crate::neural::mock::MockAceStep;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, PathBuf, NeuralModelParams) {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("in.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.2, 48000),
+            &input,
+            ExportFormat::default(),
+        )
+        .unwrap();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "test")
+            .with_param("inference_steps", 4);
+        (temp, input, params)
+    }
+
+    fn leftover_scratch(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(".partial-"))
+            .collect()
+    }
+
+    #[test]
+    fn test_completes_within_timeout() {
+        let (temp, input, params) = setup();
+        let out = temp.path().join("out.wav");
+        let model = TimeoutModel::new(MockAceStep::new(), Duration::from_secs(30));
+
+        let mut reports = Vec::new();
+        let result = model
+            .process_with_progress(&input, &out, &params, &mut |p| {
+                reports.push(p);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert!(out.exists());
+        assert_eq!(result.output_path, Some(out.to_string_lossy().to_string()));
+        assert_eq!(reports.last(), Some(&1.0));
+        assert!(leftover_scratch(temp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_hang_times_out() {
+        let (temp, input, params) = setup();
+        let out = temp.path().join("out.wav");
+        let model = TimeoutModel::new(
+            MockAceStep::new().with_hang(Duration::from_millis(500)),
+            Duration::from_millis(50),
+        );
+
+        let start = Instant::now();
+        let result = model.process(&input, &out, &params);
+        assert!(start.elapsed() < Duration::from_millis(400));
+        match result {
+            Err(NuevaError::AceStepTimeout { timeout_ms: 50 }) => {}
+            other => panic!("expected timeout, got {:?}", other.map(|r| r.success)),
+        }
+        assert!(!out.exists());
+    }
+
+    #[test]
+    fn test_timeout_leaves_existing_output_and_cleans_up() {
+        let (temp, input, params) = setup();
+        let out = temp.path().join("out.wav");
+        fs::write(&out, b"previous render").unwrap();
+        let model = TimeoutModel::new(
+            MockAceStep::new().with_hang(Duration::from_secs(30)),
+            Duration::from_millis(20),
+        );
+
+        assert!(matches!(
+            model.process(&input, &out, &params),
+            Err(NuevaError::AceStepTimeout { .. })
+        ));
+
+        // The model is aborted, so the worker doesn't sit out its hang
+        thread::sleep(Duration::from_millis(200));
+        assert!(!model.inner().is_hanging());
+        assert_eq!(fs::read(&out).unwrap(), b"previous render");
+        assert!(leftover_scratch(temp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_returns_immediately() {
+        let (temp, input, params) = setup();
+        let out = temp.path().join("out.wav");
+        let model = TimeoutModel::new(MockAceStep::new(), Duration::from_secs(30));
+
+        let result = model.process_with_progress(&input, &out, &params, &mut |p| {
+            if p > 0.0 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        assert!(matches!(result, Err(NuevaError::Cancelled)));
+        assert!(!out.exists());
+    }
+}
@@ -3,6 +3,13 @@
 //! Provides automatic periodic saving of project state to prevent data loss.
 //! Autosaves are stored as JSON files in the backups directory and are
 //! automatically rotated to prevent disk space bloat.
+//!
+//! Edits are reported with [`AutosaveManager::mark_dirty`] and flushed with
+//! [`AutosaveManager::maybe_save`]. A write happens once edits have paused
+//! for the debounce period, but never later than the autosave interval after
+//! the first unsaved edit, so bursts of edits don't thrash the disk while the
+//! latest state is still captured within the interval. Autosaves never touch
+//! `project.json`; only crash recovery copies them over it.
 
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -16,6 +23,9 @@ use crate::state::project::Project;
 /// Default autosave interval in seconds.
 const DEFAULT_AUTOSAVE_INTERVAL: u64 = 60;
 
+/// Default quiet period after an edit before autosaving, in seconds.
+const DEFAULT_DEBOUNCE_SECS: u64 = 2;
+
 /// Default maximum number of autosaves to retain.
 const DEFAULT_MAX_AUTOSAVES: usize = 10;
 
@@ -34,8 +44,18 @@ pub struct AutosaveManager {
     /// Maximum number of autosave files to retain.
     pub max_autosaves: usize,
 
+    /// Quiet period after the last edit before autosaving, in seconds.
+    /// Never longer than the autosave interval.
+    pub debounce_seconds: u64,
+
     /// Timestamp of the last successful autosave.
     pub last_save_time: Option<DateTime<Utc>>,
+
+    /// Timestamp of the first edit not yet captured by a save.
+    pub dirty_since: Option<DateTime<Utc>>,
+
+    /// Timestamp of the most recent edit.
+    pub last_edit_time: Option<DateTime<Utc>>,
 }
 
 impl Default for AutosaveManager {
@@ -50,11 +70,7 @@ impl AutosaveManager {
     /// Default interval: 60 seconds
     /// Default max autosaves: 10
     pub fn new() -> Self {
-        Self {
-            autosave_interval_seconds: DEFAULT_AUTOSAVE_INTERVAL,
-            max_autosaves: DEFAULT_MAX_AUTOSAVES,
-            last_save_time: None,
-        }
+        Self::with_interval(DEFAULT_AUTOSAVE_INTERVAL, DEFAULT_MAX_AUTOSAVES)
     }
 
     /// Create a new AutosaveManager with custom interval and max autosaves.
@@ -66,8 +82,72 @@ impl AutosaveManager {
         Self {
             autosave_interval_seconds: interval,
             max_autosaves: max,
+            debounce_seconds: DEFAULT_DEBOUNCE_SECS.min(interval),
             last_save_time: None,
+            dirty_since: None,
+            last_edit_time: None,
+        }
+    }
+
+    /// Set the debounce period (clamped to the autosave interval).
+    pub fn with_debounce(mut self, seconds: u64) -> Self {
+        self.debounce_seconds = seconds.min(self.autosave_interval_seconds);
+        self
+    }
+
+    /// Record that the project changed since the last save.
+    pub fn mark_dirty(&mut self) {
+        let now = Utc::now();
+        self.last_edit_time = Some(now);
+        self.dirty_since.get_or_insert(now);
+    }
+
+    /// Record that the project was saved explicitly, so there is nothing
+    /// left for autosave to capture.
+    pub fn mark_saved(&mut self) {
+        self.dirty_since = None;
+        self.last_edit_time = None;
+    }
+
+    /// Whether there are edits not yet captured by a save.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_since.is_some()
+    }
+
+    /// Check whether pending edits are due to be autosaved.
+    ///
+    /// Due once edits have been quiet for the debounce period (and the last
+    /// autosave is at least an interval old), or once the oldest pending edit
+    /// is an interval old, whichever comes first.
+    pub fn is_save_due(&self, project: &Project) -> bool {
+        let (Some(dirty_since), Some(last_edit)) = (self.dirty_since, self.last_edit_time) else {
+            return false;
+        };
+        if project.is_processing() {
+            return false;
+        }
+
+        let now = Utc::now();
+        let elapsed = |since: DateTime<Utc>| now.signed_duration_since(since).num_seconds();
+        let interval = self.autosave_interval_seconds as i64;
+
+        let overdue = elapsed(dirty_since) >= interval;
+        let settled = elapsed(last_edit) >= self.debounce_seconds as i64
+            && self.last_save_time.is_none_or(|t| elapsed(t) >= interval);
+        overdue || settled
+    }
+
+    /// Autosave if the project is dirty and a save is due.
+    ///
+    /// Returns the path of the written autosave, or `None` if nothing was
+    /// written.
+    pub fn maybe_save(&mut self, project: &Project) -> Result<Option<PathBuf>> {
+        if !self.is_save_due(project) {
+            return Ok(None);
         }
+        let path = self.autosave(project)?;
+        self.mark_saved();
+        Ok(Some(path))
     }
 
     /// Check if an autosave should be performed.
@@ -221,14 +301,32 @@ impl AutosaveManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::crash_recovery::recover_from_crash;
+    use crate::state::project::PROJECT_FILE;
+    use chrono::Duration;
     use tempfile::tempdir;
 
+    fn create_project(dir: &Path) -> Project {
+        let mut project = Project::create(&dir.join("project"), None).unwrap();
+        project.save().unwrap();
+        project
+    }
+
+    /// Pretend the pending edits happened `secs` seconds ago.
+    fn age_edits(manager: &mut AutosaveManager, secs: i64) {
+        let shift = |t: Option<DateTime<Utc>>| t.map(|t| t - Duration::seconds(secs));
+        manager.dirty_since = shift(manager.dirty_since);
+        manager.last_edit_time = shift(manager.last_edit_time);
+    }
+
     #[test]
     fn test_new_creates_defaults() {
         let manager = AutosaveManager::new();
         assert_eq!(manager.autosave_interval_seconds, DEFAULT_AUTOSAVE_INTERVAL);
         assert_eq!(manager.max_autosaves, DEFAULT_MAX_AUTOSAVES);
+        assert_eq!(manager.debounce_seconds, DEFAULT_DEBOUNCE_SECS);
         assert!(manager.last_save_time.is_none());
+        assert!(!manager.is_dirty());
     }
 
     #[test]
@@ -349,4 +447,91 @@ mod tests {
         assert!(names.contains(&"autosave_20240115_100003.json".to_string()));
         assert!(names.contains(&"autosave_20240115_100002.json".to_string()));
     }
+
+    #[test]
+    fn test_maybe_save_skips_when_clean() {
+        let temp = tempdir().unwrap();
+        let project = create_project(temp.path());
+        let mut manager = AutosaveManager::with_interval(60, 5);
+
+        assert!(manager.maybe_save(&project).unwrap().is_none());
+        assert!(AutosaveManager::list_autosaves(&project.backups_dir())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_maybe_save_debounces_rapid_edits() {
+        let temp = tempdir().unwrap();
+        let project = create_project(temp.path());
+        let mut manager = AutosaveManager::with_interval(60, 5).with_debounce(5);
+
+        manager.mark_dirty();
+        assert!(manager.maybe_save(&project).unwrap().is_none());
+
+        age_edits(&mut manager, 5);
+        assert!(manager.maybe_save(&project).unwrap().is_some());
+        assert!(!manager.is_dirty());
+
+        // A new burst right after a save waits for the interval
+        manager.mark_dirty();
+        age_edits(&mut manager, 5);
+        assert!(manager.maybe_save(&project).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_continuous_edits_saved_within_interval() {
+        let temp = tempdir().unwrap();
+        let project = create_project(temp.path());
+        let mut manager = AutosaveManager::with_interval(30, 5).with_debounce(5);
+        manager.last_save_time = Some(Utc::now());
+
+        // Edits keep arriving, so the debounce never settles
+        manager.mark_dirty();
+        age_edits(&mut manager, 29);
+        manager.mark_dirty();
+        assert!(manager.maybe_save(&project).unwrap().is_none());
+
+        age_edits(&mut manager, 1);
+        manager.mark_dirty();
+        assert!(manager.maybe_save(&project).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_explicit_save_is_not_overwritten() {
+        let temp = tempdir().unwrap();
+        let mut project = create_project(temp.path());
+        let project_file = project.project_path.join(PROJECT_FILE);
+        let saved = fs::read_to_string(&project_file).unwrap();
+
+        let mut manager = AutosaveManager::with_interval(60, 5).with_debounce(0);
+        project.conversation.session_count = 7;
+        manager.mark_dirty();
+        let autosave = manager.maybe_save(&project).unwrap().unwrap();
+
+        assert!(autosave.starts_with(project.backups_dir()));
+        assert_eq!(fs::read_to_string(&project_file).unwrap(), saved);
+
+        // Saving explicitly leaves nothing for autosave to do
+        manager.mark_dirty();
+        project.save().unwrap();
+        manager.mark_saved();
+        assert!(manager.maybe_save(&project).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_autosave_is_recoverable_after_crash() {
+        let temp = tempdir().unwrap();
+        let mut project = create_project(temp.path());
+        let mut manager = AutosaveManager::with_interval(60, 5).with_debounce(0);
+
+        project.conversation.session_count = 3;
+        manager.mark_dirty();
+        let autosave = manager.maybe_save(&project).unwrap().unwrap();
+
+        // The lock file from Project::create is still present, as after a crash
+        let result = recover_from_crash(&project.project_path).unwrap();
+        assert!(result.needed && result.success);
+        assert_eq!(result.recovery_state_path, Some(autosave));
+    }
 }
@@ -0,0 +1,168 @@
+//! Conversation Persistence
+//!
+//! Saves the agent's `ConversationContext` into the project's history
+//! directory so references like "make that reverb bigger" keep working
+//! across separate CLI invocations.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::ConversationContext;
+use crate::state::error::{NuevaError, Result};
+
+/// Conversation context file name (inside the history directory).
+pub const CONVERSATION_FILE: &str = "conversation.json";
+
+/// Suffix for a conversation file that could not be parsed.
+pub const CORRUPT_SUFFIX: &str = "corrupt";
+
+/// Result of loading a persisted conversation.
+#[derive(Debug, Clone)]
+pub struct LoadedConversation {
+    /// The loaded (or freshly created) context.
+    pub context: ConversationContext,
+    /// Warning to show the user if the stored context had to be discarded.
+    pub warning: Option<String>,
+}
+
+/// Get the path to the conversation file for a history directory.
+pub fn conversation_path(history_dir: &Path) -> PathBuf {
+    history_dir.join(CONVERSATION_FILE)
+}
+
+/// Load the conversation context from the history directory.
+///
+/// A missing file yields an empty context. A corrupted file is moved aside
+/// (never deleted) and an empty context is returned with a warning.
+pub fn load_conversation(history_dir: &Path) -> Result<LoadedConversation> {
+    let path = conversation_path(history_dir);
+
+    if !path.exists() {
+        return Ok(LoadedConversation {
+            context: ConversationContext::new(),
+            warning: None,
+        });
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| NuevaError::FileReadError {
+        path: path.clone(),
+        source: e,
+    })?;
+
+    match serde_json::from_str::<ConversationContext>(&content) {
+        Ok(context) => Ok(LoadedConversation {
+            context,
+            warning: None,
+        }),
+        Err(e) => {
+            let backup = path.with_extension(format!("json.{}", CORRUPT_SUFFIX));
+            fs::rename(&path, &backup).map_err(|e| NuevaError::FileWriteError {
+                path: backup.clone(),
+                source: e,
+            })?;
+
+            Ok(LoadedConversation {
+                context: ConversationContext::new(),
+                warning: Some(format!(
+                    "Conversation history was corrupted ({}); starting fresh. Old file kept at {}",
+                    e,
+                    backup.display()
+                )),
+            })
+        }
+    }
+}
+
+/// Save the conversation context, trimming history to the user's limit.
+pub fn save_conversation(history_dir: &Path, context: &mut ConversationContext) -> Result<()> {
+    if !history_dir.exists() {
+        fs::create_dir_all(history_dir).map_err(|e| NuevaError::DirectoryCreateError {
+            path: history_dir.to_path_buf(),
+            source: e,
+        })?;
+    }
+
+    let max_messages = context.user_preferences.max_history_messages();
+    context.trim_history(max_messages);
+
+    let path = conversation_path(history_dir);
+    let content = serde_json::to_string_pretty(context)?;
+    fs::write(&path, content).map_err(|e| NuevaError::FileWriteError { path, source: e })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{
+        resolve_reference, ActionType, AgentAction, EffectRef, ResolvedReference, ToolType,
+    };
+    use tempfile::TempDir;
+
+    fn reverb_ref() -> EffectRef {
+        EffectRef {
+            id: "reverb-1".to_string(),
+            effect_type: "reverb".to_string(),
+            display_name: "Reverb".to_string(),
+            chain_index: 0,
+        }
+    }
+
+    #[test]
+    fn test_missing_file_gives_empty_context() {
+        let temp = TempDir::new().unwrap();
+        let loaded = load_conversation(temp.path()).unwrap();
+        assert!(loaded.context.messages.is_empty());
+        assert!(loaded.warning.is_none());
+    }
+
+    #[test]
+    fn test_save_and_reload_resolves_reference() {
+        let temp = TempDir::new().unwrap();
+
+        let mut ctx = ConversationContext::new();
+        ctx.add_user_message("add some reverb");
+        let action = AgentAction::new(ActionType::Add, ToolType::Dsp, "Added reverb")
+            .with_effect(reverb_ref());
+        ctx.add_agent_message_with_action("Added a medium hall reverb", action);
+        save_conversation(temp.path(), &mut ctx).unwrap();
+
+        let loaded = load_conversation(temp.path()).unwrap();
+        assert_eq!(loaded.context.session_id, ctx.session_id);
+        assert_eq!(loaded.context.messages.len(), 2);
+
+        match resolve_reference("make that reverb bigger", &loaded.context, &[reverb_ref()]) {
+            ResolvedReference::Effect(effect) => assert_eq!(effect.id, "reverb-1"),
+            other => panic!("expected reverb, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_corrupted_file_recovers_with_warning() {
+        let temp = TempDir::new().unwrap();
+        fs::write(conversation_path(temp.path()), "{ not json").unwrap();
+
+        let loaded = load_conversation(temp.path()).unwrap();
+        assert!(loaded.context.messages.is_empty());
+        assert!(loaded.warning.is_some());
+        assert!(!conversation_path(temp.path()).exists());
+        assert!(temp.path().join("conversation.json.corrupt").exists());
+    }
+
+    #[test]
+    fn test_save_trims_history() {
+        let temp = TempDir::new().unwrap();
+
+        let mut ctx = ConversationContext::new();
+        ctx.user_preferences.max_history_messages = Some(3);
+        for i in 0..10 {
+            ctx.add_user_message(&format!("message {}", i));
+        }
+        save_conversation(temp.path(), &mut ctx).unwrap();
+
+        let loaded = load_conversation(temp.path()).unwrap();
+        assert_eq!(loaded.context.messages.len(), 3);
+        assert_eq!(loaded.context.messages[0].content, "message 7");
+    }
+}
@@ -2,15 +2,23 @@
 //!
 //! Provides crash detection and recovery functionality for Nueva projects.
 //! Detects potential crashes via lock files and recovers from autosaves.
+//!
+//! Autosaves are checked newest-first before being offered: the JSON must
+//! parse, Layer 0 must still match its recorded checksum and a processed
+//! Layer 1 must decode to finite audio. A snapshot that fails any check
+//! (e.g. a Layer 1 file truncated by the crash) is skipped in favour of
+//! the next older one.
 
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::state::error::{NuevaError, Result};
 use crate::state::project::{BACKUPS_DIR, LOCK_FILE, PROJECT_FILE};
+use crate::state::storage::Layer1StorageManager;
 
 /// Result of a crash recovery check.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +34,24 @@ pub struct RecoveryResult {
 
     /// Path to the autosave file to recover from, if found.
     pub recovery_state_path: Option<PathBuf>,
+
+    /// Timestamp of the autosave chosen for recovery.
+    #[serde(default)]
+    pub snapshot_timestamp: Option<DateTime<Utc>>,
+
+    /// Newer autosaves that failed integrity checks and were skipped.
+    #[serde(default)]
+    pub rejected_snapshots: Vec<RejectedSnapshot>,
+}
+
+/// An autosave that was not offered for recovery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedSnapshot {
+    /// Path to the autosave file.
+    pub path: PathBuf,
+
+    /// Why the autosave was rejected.
+    pub reason: String,
 }
 
 impl RecoveryResult {
@@ -36,6 +62,8 @@ impl RecoveryResult {
             success: false,
             message: "No recovery needed. Project closed cleanly.".to_string(),
             recovery_state_path: None,
+            snapshot_timestamp: None,
+            rejected_snapshots: Vec::new(),
         }
     }
 
@@ -48,20 +76,53 @@ impl RecoveryResult {
                       The project state may be incomplete or corrupted."
                 .to_string(),
             recovery_state_path: None,
+            snapshot_timestamp: None,
+            rejected_snapshots: Vec::new(),
         }
     }
 
-    /// Create a result indicating recovery is needed and autosave was found.
-    fn recovery_available(autosave_path: PathBuf, timestamp: DateTime<Utc>) -> Self {
+    /// Create a result indicating recovery is needed but every autosave
+    /// failed its integrity checks.
+    fn recovery_needed_all_corrupt(rejected: Vec<RejectedSnapshot>) -> Self {
         Self {
             needed: true,
-            success: true,
+            success: false,
             message: format!(
-                "Recovery available from autosave at {}. \
-                 Use 'apply_recovery' to restore this state.",
-                timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+                "Warning: Project may have crashed, and all {} autosave(s) failed \
+                 integrity checks. The project state may be incomplete or corrupted.",
+                rejected.len()
             ),
+            recovery_state_path: None,
+            snapshot_timestamp: None,
+            rejected_snapshots: rejected,
+        }
+    }
+
+    /// Create a result indicating recovery is needed and autosave was found.
+    fn recovery_available(
+        autosave_path: PathBuf,
+        timestamp: DateTime<Utc>,
+        rejected: Vec<RejectedSnapshot>,
+    ) -> Self {
+        let mut message = format!(
+            "Recovery available from autosave at {}. \
+             Use 'apply_recovery' to restore this state.",
+            timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if !rejected.is_empty() {
+            message.push_str(&format!(
+                " Skipped {} newer autosave(s) that failed integrity checks.",
+                rejected.len()
+            ));
+        }
+
+        Self {
+            needed: true,
+            success: true,
+            message,
             recovery_state_path: Some(autosave_path),
+            snapshot_timestamp: Some(timestamp),
+            rejected_snapshots: rejected,
         }
     }
 }
@@ -126,27 +187,81 @@ pub fn recover_from_crash(project_path: &Path) -> Result<RecoveryResult> {
         return Ok(RecoveryResult::recovery_needed_no_autosave());
     }
 
-    // Find the most recent autosave by parsing timestamps
-    let mut most_recent: Option<(PathBuf, DateTime<Utc>)> = None;
+    // Order autosaves newest-first by the timestamp in their filename
+    let mut candidates: Vec<(PathBuf, DateTime<Utc>)> = autosaves
+        .into_iter()
+        .filter_map(|path| parse_timestamp_from_filename(&path).map(|ts| (path, ts)))
+        .collect();
+    candidates.sort_by_key(|(_, ts)| std::cmp::Reverse(*ts));
+
+    if candidates.is_empty() {
+        return Ok(RecoveryResult::recovery_needed_no_autosave());
+    }
 
-    for autosave_path in autosaves {
-        if let Some(timestamp) = parse_timestamp_from_filename(&autosave_path) {
-            match &most_recent {
-                None => {
-                    most_recent = Some((autosave_path, timestamp));
-                }
-                Some((_, existing_ts)) if timestamp > *existing_ts => {
-                    most_recent = Some((autosave_path, timestamp));
-                }
-                _ => {}
+    // Take the newest autosave that passes integrity checks
+    let mut rejected = Vec::new();
+    for (path, timestamp) in candidates {
+        match verify_autosave(project_path, &path) {
+            Ok(()) => {
+                log::info!(
+                    "Recovering from autosave {} ({} newer autosave(s) rejected)",
+                    path.display(),
+                    rejected.len()
+                );
+                return Ok(RecoveryResult::recovery_available(
+                    path, timestamp, rejected,
+                ));
+            }
+            Err(reason) => {
+                log::warn!("Skipping autosave {}: {}", path.display(), reason);
+                rejected.push(RejectedSnapshot { path, reason });
             }
         }
     }
 
-    match most_recent {
-        Some((path, timestamp)) => Ok(RecoveryResult::recovery_available(path, timestamp)),
-        None => Ok(RecoveryResult::recovery_needed_no_autosave()),
+    log::warn!("No autosave passed integrity checks");
+    Ok(RecoveryResult::recovery_needed_all_corrupt(rejected))
+}
+
+/// Check that an autosave and the audio it references are intact.
+///
+/// Returns a description of the first failed check. Layer 0 is only
+/// checked when its file and checksum are both present; a processed
+/// Layer 1 must exist, decode and contain finite samples.
+fn verify_autosave(project_path: &Path, autosave_path: &Path) -> std::result::Result<(), String> {
+    let content =
+        fs::read_to_string(autosave_path).map_err(|e| format!("unreadable autosave: {}", e))?;
+    let state: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("invalid autosave JSON: {}", e))?;
+
+    let layer0_path = state["layer0"]["path"].as_str().unwrap_or_default();
+    let layer0_hash = state["layer0"]["hash_sha256"].as_str().unwrap_or_default();
+    let layer0_file = project_path.join(layer0_path);
+    if !layer0_path.is_empty() && !layer0_hash.is_empty() && layer0_file.is_file() {
+        let bytes = fs::read(&layer0_file).map_err(|e| format!("unreadable Layer 0: {}", e))?;
+        if format!("{:x}", Sha256::digest(&bytes)) != layer0_hash {
+            return Err("Layer 0 checksum mismatch".to_string());
+        }
     }
+
+    let layer1_path = state["layer1"]["path"].as_str().unwrap_or_default();
+    let layer1_processed = state["layer1"]["is_processed"].as_bool().unwrap_or(false);
+    if layer1_processed && !layer1_path.is_empty() {
+        let layer1_file = project_path.join(layer1_path);
+        if !layer1_file.is_file() {
+            return Err(format!("Layer 1 file missing: {}", layer1_path));
+        }
+        let buffer = Layer1StorageManager::load_layer1(&layer1_file)
+            .map_err(|e| format!("Layer 1 failed to load: {}", e))?;
+        if buffer.is_empty() {
+            return Err("Layer 1 contains no samples".to_string());
+        }
+        if !buffer.is_finite() {
+            return Err("Layer 1 contains non-finite samples".to_string());
+        }
+    }
+
+    Ok(())
 }
 
 /// Find all autosave files in the backups directory.
@@ -379,6 +494,142 @@ mod tests {
         assert_eq!(result.recovery_state_path.unwrap(), newer_path);
     }
 
+    /// Write an autosave referencing a processed Layer 1 file
+    fn write_autosave_with_layer1(project: &Path, name: &str, layer1: &str) -> PathBuf {
+        let path = project.join(BACKUPS_DIR).join(name);
+        let state = serde_json::json!({
+            "schema_version": "1.1.0",
+            "layer0": {"path": "audio/layer0_source.wav", "hash_sha256": ""},
+            "layer1": {"path": layer1, "is_processed": true},
+        });
+        fs::write(&path, state.to_string()).unwrap();
+        path
+    }
+
+    fn write_layer1(project: &Path, name: &str) -> PathBuf {
+        use crate::engine::io::{export_audio, generate_test_tone, ExportFormat};
+
+        let path = project.join("audio").join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 48000),
+            &path,
+            ExportFormat::default(),
+        )
+        .unwrap();
+        path
+    }
+
+    fn create_lock(project: &Path) {
+        fs::write(
+            project.join(LOCK_FILE),
+            r#"{"pid": 1234, "started_at": "2024-01-15T10:00:00Z"}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_clean_newest_autosave_selected() {
+        let temp = create_test_project();
+        create_lock(temp.path());
+        write_layer1(temp.path(), "layer1_a.wav");
+        write_layer1(temp.path(), "layer1_b.wav");
+        write_autosave_with_layer1(
+            temp.path(),
+            "autosave_20240115_100000.json",
+            "audio/layer1_a.wav",
+        );
+        let newer = write_autosave_with_layer1(
+            temp.path(),
+            "autosave_20240115_150000.json",
+            "audio/layer1_b.wav",
+        );
+
+        let result = recover_from_crash(temp.path()).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.recovery_state_path, Some(newer));
+        assert!(result.rejected_snapshots.is_empty());
+        assert_eq!(
+            result
+                .snapshot_timestamp
+                .unwrap()
+                .format("%Y%m%d_%H%M%S")
+                .to_string(),
+            "20240115_150000"
+        );
+    }
+
+    #[test]
+    fn test_truncated_layer1_falls_back_to_older_autosave() {
+        let temp = create_test_project();
+        create_lock(temp.path());
+        write_layer1(temp.path(), "layer1_a.wav");
+        let corrupt = write_layer1(temp.path(), "layer1_b.wav");
+        let older = write_autosave_with_layer1(
+            temp.path(),
+            "autosave_20240115_100000.json",
+            "audio/layer1_a.wav",
+        );
+        let newer = write_autosave_with_layer1(
+            temp.path(),
+            "autosave_20240115_150000.json",
+            "audio/layer1_b.wav",
+        );
+
+        // Simulate a crash part-way through writing the newest Layer 1
+        let bytes = fs::read(&corrupt).unwrap();
+        fs::write(&corrupt, &bytes[..bytes.len() / 3]).unwrap();
+
+        let result = recover_from_crash(temp.path()).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.recovery_state_path, Some(older));
+        assert_eq!(result.rejected_snapshots.len(), 1);
+        assert_eq!(result.rejected_snapshots[0].path, newer);
+        assert!(result.rejected_snapshots[0].reason.contains("Layer 1"));
+        assert!(result.message.contains("Skipped 1"));
+    }
+
+    #[test]
+    fn test_layer0_checksum_mismatch_rejected() {
+        let temp = create_test_project();
+        create_lock(temp.path());
+        write_layer1(temp.path(), "layer0_source.wav");
+
+        let autosave = temp
+            .path()
+            .join(BACKUPS_DIR)
+            .join("autosave_20240115_100000.json");
+        let state = serde_json::json!({
+            "layer0": {"path": "audio/layer0_source.wav", "hash_sha256": "deadbeef"},
+        });
+        fs::write(&autosave, state.to_string()).unwrap();
+
+        let result = recover_from_crash(temp.path()).unwrap();
+
+        assert!(result.needed);
+        assert!(!result.success);
+        assert!(result.recovery_state_path.is_none());
+        assert_eq!(result.rejected_snapshots.len(), 1);
+        assert!(result.rejected_snapshots[0].reason.contains("checksum"));
+    }
+
+    #[test]
+    fn test_invalid_json_autosave_skipped() {
+        let temp = create_test_project();
+        create_lock(temp.path());
+        let backups = temp.path().join(BACKUPS_DIR);
+        let older = backups.join("autosave_20240115_100000.json");
+        fs::write(&older, r#"{"schema_version": "1.0"}"#).unwrap();
+        fs::write(backups.join("autosave_20240115_150000.json"), "{\"sche").unwrap();
+
+        let result = recover_from_crash(temp.path()).unwrap();
+
+        assert_eq!(result.recovery_state_path, Some(older));
+        assert_eq!(result.rejected_snapshots.len(), 1);
+    }
+
     #[test]
     fn test_parse_timestamp_from_filename() {
         let path = Path::new("autosave_20240115_143022.json");
@@ -44,6 +44,12 @@ pub enum NuevaError {
     #[error("Invalid project schema version: {version}")]
     InvalidSchemaVersion { version: String },
 
+    #[error(
+        "Project schema version {version} is newer than the newest supported \
+         version ({supported}); upgrade Nueva to open it"
+    )]
+    UnsupportedSchemaVersion { version: String, supported: String },
+
     #[error("Migration failed from {from} to {to}: {reason}")]
     MigrationError {
         from: String,
@@ -74,6 +80,12 @@ pub enum NuevaError {
     #[error("Undo action not found: {action_id}")]
     UndoActionNotFound { action_id: String },
 
+    #[error("No action in the undo history matches")]
+    NoMatchingAction,
+
+    #[error("Cannot selectively undo '{description}': {reason}")]
+    SelectiveUndoBlocked { description: String, reason: String },
+
     // Audio Errors
     #[error("Audio file not found: {path}")]
     AudioNotFound { path: PathBuf },
@@ -91,6 +103,15 @@ pub enum NuevaError {
     #[error("Processing in progress")]
     ProcessingInProgress,
 
+    #[error("Processing failed: {reason}")]
+    ProcessingFailed { reason: String },
+
+    #[error("Effect not found in chain: {effect_id}")]
+    EffectNotFound { effect_id: String },
+
+    #[error("Invalid chain index {index} (chain has {len} effects)")]
+    InvalidChainIndex { index: usize, len: usize },
+
     // Storage Errors
     #[error(
         "Insufficient disk space: needed {needed_bytes} bytes, available {available_bytes} bytes"
@@ -103,6 +124,9 @@ pub enum NuevaError {
     #[error("Storage quota exceeded: Layer 1 using {used_mb:.1} MB")]
     StorageQuotaExceeded { used_mb: f64 },
 
+    #[error("Layer 1 storage corrupted: {reason}")]
+    StorageCorrupted { reason: String },
+
     // Recovery Errors
     #[error("Recovery failed: {reason}")]
     RecoveryFailed { reason: String },
@@ -141,9 +165,21 @@ impl NuevaError {
             }
             NuevaError::NothingToUndo => Some("There are no actions to undo."),
             NuevaError::NothingToRedo => Some("There are no undone actions to redo."),
+            NuevaError::NoMatchingAction => {
+                Some("Run 'nueva history' to see the actions that can be undone.")
+            }
+            NuevaError::SelectiveUndoBlocked { .. } => {
+                Some("Use plain 'undo' to step back through history instead.")
+            }
+            NuevaError::EffectNotFound { .. } => {
+                Some("Run 'nueva print-state' to list the effect IDs in the chain.")
+            }
             NuevaError::StorageQuotaExceeded { .. } => {
                 Some("Consider baking to flatten layers or pruning history.")
             }
+            NuevaError::StorageCorrupted { .. } => {
+                Some("Restore the missing file from a backup, or bake to start a fresh history.")
+            }
             _ => None,
         }
     }
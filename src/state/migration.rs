@@ -6,12 +6,12 @@
 
 use std::collections::HashMap;
 
-use serde_json::Value;
+use serde_json::{json, Value};
 
 use crate::state::error::{NuevaError, Result};
 
 /// Current schema version for project files.
-pub const CURRENT_SCHEMA_VERSION: &str = "1.0.0";
+pub const CURRENT_SCHEMA_VERSION: &str = "1.2.0";
 
 /// Current Nueva application version.
 pub const NUEVA_VERSION: &str = "0.1.0";
@@ -34,13 +34,13 @@ fn get_migration_registry() -> HashMap<(String, String), MigrationFn> {
     //     migrate_1_0_0_to_1_1_0,
     // );
 
-    // Placeholder: Migration from 1.0.0 to 1.1.0 (for future use)
+    // 1.0.0 -> 1.1.0: Layer 1 storage compression
     registry.insert(
         ("1.0.0".to_string(), "1.1.0".to_string()),
         migrate_1_0_0_to_1_1_0,
     );
 
-    // Placeholder: Migration from 1.1.0 to 1.2.0 (for future use)
+    // 1.1.0 -> 1.2.0: explicit effect parameters
     registry.insert(
         ("1.1.0".to_string(), "1.2.0".to_string()),
         migrate_1_1_0_to_1_2_0,
@@ -91,11 +91,19 @@ pub fn migrate_project(mut data: Value) -> Result<Value> {
         return Ok(data);
     }
 
+    // Projects written by a newer Nueva may use fields we would misread;
+    // refuse them rather than guess, whether or not we know the version
+    if parse_version(&current_version) > parse_version(target_version) {
+        return Err(NuevaError::UnsupportedSchemaVersion {
+            version: current_version,
+            supported: target_version.to_string(),
+        });
+    }
+
     // Find migration path
     let path = find_migration_path(&current_version, target_version);
 
     if path.is_empty() && current_version != target_version {
-        // Check if the version is newer than what we support
         let known_versions = get_version_order();
         if !known_versions.contains(&current_version.as_str()) {
             return Err(NuevaError::InvalidSchemaVersion {
@@ -103,25 +111,6 @@ pub fn migrate_project(mut data: Value) -> Result<Value> {
             });
         }
 
-        // Check if we're trying to downgrade (target is older than current)
-        let current_idx = known_versions
-            .iter()
-            .position(|&v| v == current_version)
-            .unwrap_or(0);
-        let target_idx = known_versions
-            .iter()
-            .position(|&v| v == target_version)
-            .unwrap_or(0);
-
-        if current_idx > target_idx {
-            // Project is from a newer version - we can't downgrade
-            return Err(NuevaError::MigrationError {
-                from: current_version,
-                to: target_version.to_string(),
-                reason: "Cannot downgrade project from newer schema version".to_string(),
-            });
-        }
-
         // No migration path found
         return Err(NuevaError::MigrationError {
             from: current_version,
@@ -158,6 +147,17 @@ pub fn migrate_project(mut data: Value) -> Result<Value> {
     Ok(data)
 }
 
+/// Parse a dotted version into numeric components for ordering.
+///
+/// Unparseable components compare as 0, so a malformed version is never
+/// treated as newer than a real one.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
 /// Find the sequence of migrations needed to go from one version to another.
 ///
 /// # Arguments
@@ -200,8 +200,13 @@ pub fn find_migration_path(from: &str, to: &str) -> Vec<(String, String)> {
 
         // Try to find a migration from current version to any later version
         let mut found_next = false;
-        for next_idx in (current_idx + 1)..=to_idx {
-            let next = versions[next_idx].to_string();
+        for (next_idx, next) in versions
+            .iter()
+            .enumerate()
+            .take(to_idx + 1)
+            .skip(current_idx + 1)
+        {
+            let next = next.to_string();
             if registry.contains_key(&(current.clone(), next.clone())) {
                 path.push((current, next));
                 current_idx = next_idx;
@@ -220,45 +225,127 @@ pub fn find_migration_path(from: &str, to: &str) -> Vec<(String, String)> {
 }
 
 // ============================================================================
-// Placeholder Migration Functions
+// Migration Functions
 // ============================================================================
-// These are example migrations for future schema versions.
-// They currently pass through unchanged but demonstrate the pattern.
+// Migrations past the current schema version are placeholders that pass
+// through unchanged but demonstrate the pattern.
 
-/// Placeholder migration from 1.0.0 to 1.1.0.
+/// Migration from 1.0.0 to 1.1.0.
 ///
-/// Future changes might include:
-/// - Adding new fields with default values
-/// - Restructuring existing data
-fn migrate_1_0_0_to_1_1_0(data: Value) -> Result<Value> {
-    // Example: Add a new optional field that didn't exist in 1.0.0
-    // let mut data = data;
-    // if let Some(obj) = data.as_object_mut() {
-    //     obj.entry("new_field").or_insert(Value::Null);
-    // }
-
-    // For now, pass through unchanged
+/// 1.1.0 records how the Layer 1 audio file is stored. Every 1.0.0 project
+/// stored it as an uncompressed WAV.
+fn migrate_1_0_0_to_1_1_0(mut data: Value) -> Result<Value> {
+    if let Some(layer1) = data.get_mut("layer1").and_then(Value::as_object_mut) {
+        layer1
+            .entry("compression")
+            .or_insert_with(|| Value::String("none".to_string()));
+    }
     Ok(data)
 }
 
-/// Placeholder migration from 1.1.0 to 1.2.0.
+/// Migration from 1.1.0 to 1.2.0.
 ///
-/// Future changes might include:
-/// - Renaming fields
-/// - Changing field types
-fn migrate_1_1_0_to_1_2_0(data: Value) -> Result<Value> {
-    // Example: Rename a field
-    // let mut data = data;
-    // if let Some(obj) = data.as_object_mut() {
-    //     if let Some(old_value) = obj.remove("old_field_name") {
-    //         obj.insert("new_field_name".to_string(), old_value);
-    //     }
-    // }
+/// 1.2.0 stores every effect parameter explicitly, so parameters added to
+/// an effect later are never silently missing from older chains. Missing
+/// parameters are filled from [`effect_param_defaults_1_2_0`]; missing
+/// `enabled`, `added_by` and `added_at` fields default to `true`, `"user"`
+/// and the project's creation time.
+fn migrate_1_1_0_to_1_2_0(mut data: Value) -> Result<Value> {
+    let created_at = data
+        .get("created_at")
+        .cloned()
+        .unwrap_or_else(|| json!("1970-01-01T00:00:00Z"));
+
+    let Some(chain) = data
+        .get_mut("layer2")
+        .and_then(|layer2| layer2.get_mut("chain"))
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(data);
+    };
+
+    for effect in chain.iter_mut().filter_map(Value::as_object_mut) {
+        effect.entry("enabled").or_insert(json!(true));
+        effect.entry("added_by").or_insert_with(|| json!("user"));
+        effect
+            .entry("added_at")
+            .or_insert_with(|| created_at.clone());
+
+        let effect_type = effect
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let params = effect.entry("params").or_insert_with(|| json!({}));
+        let Some(params) = params.as_object_mut() else {
+            return Err(NuevaError::InvalidProjectStructure {
+                reason: format!("params of {} effect is not an object", effect_type),
+            });
+        };
+        for (name, default) in effect_param_defaults_1_2_0(&effect_type) {
+            params.entry(name).or_insert(default);
+        }
+    }
 
-    // For now, pass through unchanged
     Ok(data)
 }
 
+/// Parameter defaults recorded by the 1.2.0 migration.
+///
+/// These mirror the DSP defaults at the time 1.2.0 was introduced and must
+/// not change afterwards; a later default change needs its own migration.
+/// Unknown effect types (and the EQ, whose bands have no defaults) get none.
+fn effect_param_defaults_1_2_0(effect_type: &str) -> Vec<(&'static str, Value)> {
+    match effect_type {
+        "gain" => vec![("gain_db", json!(0.0))],
+        "compressor" => vec![
+            ("threshold_db", json!(-18.0)),
+            ("ratio", json!(4.0)),
+            ("attack_ms", json!(10.0)),
+            ("release_ms", json!(100.0)),
+            ("knee_db", json!(0.0)),
+            ("makeup_gain_db", json!(0.0)),
+            ("auto_makeup", json!(false)),
+        ],
+        "gate" => vec![
+            ("threshold_db", json!(-40.0)),
+            ("attack_ms", json!(1.0)),
+            ("hold_ms", json!(10.0)),
+            ("release_ms", json!(50.0)),
+            ("range_db", json!(-80.0)),
+        ],
+        "limiter" => vec![
+            ("ceiling_db", json!(-1.0)),
+            ("lookahead_ms", json!(3.0)),
+            ("release_ms", json!(100.0)),
+            ("true_peak", json!(true)),
+        ],
+        "reverb" => vec![
+            ("room_size", json!(0.5)),
+            ("damping", json!(0.5)),
+            ("wet_level", json!(0.3)),
+            ("dry_level", json!(1.0)),
+            ("pre_delay_ms", json!(0.0)),
+            ("width", json!(1.0)),
+        ],
+        "delay" => vec![
+            ("delay_time_ms", json!(250.0)),
+            ("feedback", json!(0.3)),
+            ("wet_level", json!(0.3)),
+            ("dry_level", json!(1.0)),
+            ("filter_freq", json!(8000.0)),
+            ("ping_pong", json!(false)),
+        ],
+        "saturation" => vec![
+            ("drive", json!(0.3)),
+            ("mix", json!(0.5)),
+            ("outputGain", json!(0.0)),
+            ("saturationType", json!("TAPE")),
+        ],
+        _ => Vec::new(),
+    }
+}
+
 /// Placeholder migration from 1.2.0 to 2.0.0.
 ///
 /// Major version changes might include:
@@ -304,13 +391,84 @@ mod tests {
         });
 
         let result = migrate_project(data).unwrap();
-        // Should be unchanged since 1.0.0 is current
+        // Should be migrated from 1.0.0 up to the current version
         assert_eq!(
             result.get("schema_version").and_then(|v| v.as_str()),
             Some(CURRENT_SCHEMA_VERSION)
         );
     }
 
+    #[test]
+    fn test_migrate_1_0_0_marks_layer1_uncompressed() {
+        let data = json!({
+            "schema_version": "1.0.0",
+            "layer1": {
+                "path": "audio/layer1_ai.wav",
+                "is_processed": false,
+                "identical_to_layer0": true
+            }
+        });
+
+        let result = migrate_project(data).unwrap();
+        assert_eq!(result["schema_version"], json!(CURRENT_SCHEMA_VERSION));
+        assert_eq!(result["layer1"]["compression"], json!("none"));
+    }
+
+    #[test]
+    fn test_migrate_1_1_0_fills_effect_defaults() {
+        let data = json!({
+            "schema_version": "1.1.0",
+            "created_at": "2024-01-15T10:00:00Z",
+            "layer2": {
+                "chain": [
+                    {
+                        "id": "comp-1",
+                        "type": "compressor",
+                        "enabled": false,
+                        "params": {"threshold_db": -24.0, "ratio": 2.0},
+                        "added_at": "2024-01-15T11:00:00Z",
+                        "added_by": "agent"
+                    },
+                    {"id": "custom-1", "type": "flanger"}
+                ]
+            }
+        });
+
+        let result = migrate_project(data).unwrap();
+        assert_eq!(result["schema_version"], json!("1.2.0"));
+
+        let comp = &result["layer2"]["chain"][0];
+        assert_eq!(comp["enabled"], json!(false));
+        assert_eq!(comp["params"]["threshold_db"], json!(-24.0));
+        assert_eq!(comp["params"]["ratio"], json!(2.0));
+        assert_eq!(comp["params"]["knee_db"], json!(0.0));
+        assert_eq!(comp["params"]["auto_makeup"], json!(false));
+        assert_eq!(comp["added_by"], json!("agent"));
+
+        let custom = &result["layer2"]["chain"][1];
+        assert_eq!(custom["enabled"], json!(true));
+        assert_eq!(custom["params"], json!({}));
+        assert_eq!(custom["added_by"], json!("user"));
+        assert_eq!(custom["added_at"], json!("2024-01-15T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_migrate_future_version_rejected() {
+        for version in ["2.0.0", "9.3.1"] {
+            let data = json!({"schema_version": version});
+            match migrate_project(data) {
+                Err(NuevaError::UnsupportedSchemaVersion {
+                    version: v,
+                    supported,
+                }) => {
+                    assert_eq!(v, version);
+                    assert_eq!(supported, CURRENT_SCHEMA_VERSION);
+                }
+                other => panic!("Expected UnsupportedSchemaVersion, got {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_find_migration_path_same_version() {
         let path = find_migration_path("1.0.0", "1.0.0");
@@ -363,7 +521,7 @@ mod tests {
 
     #[test]
     fn test_constants() {
-        assert_eq!(CURRENT_SCHEMA_VERSION, "1.0.0");
+        assert_eq!(CURRENT_SCHEMA_VERSION, "1.2.0");
         assert_eq!(NUEVA_VERSION, "0.1.0");
     }
 }
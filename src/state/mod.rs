@@ -1,9 +1,11 @@
 //! State Management Module
 //!
 //! Provides project state, persistence, undo/redo, autosave,
-//! crash recovery, migrations, and storage management.
+//! crash recovery, migrations, storage management, and persistence of
+//! the agent's conversation context.
 
 pub mod autosave;
+pub mod conversation;
 pub mod crash_recovery;
 pub mod error;
 pub mod migration;
@@ -12,9 +14,10 @@ pub mod storage;
 pub mod undo;
 
 pub use autosave::AutosaveManager;
-pub use crash_recovery::{recover_from_crash, RecoveryResult};
+pub use conversation::{load_conversation, save_conversation, LoadedConversation};
+pub use crash_recovery::{recover_from_crash, RecoveryResult, RejectedSnapshot};
 pub use error::{NuevaError, Result};
 pub use migration::{migrate_project, CURRENT_SCHEMA_VERSION};
 pub use project::Project;
-pub use storage::Layer1StorageManager;
+pub use storage::{Layer1StorageManager, StorageCompression};
 pub use undo::UndoManager;
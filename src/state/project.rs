@@ -10,8 +10,22 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use crate::agent::EffectRef;
+use crate::dsp::{self, get_default_order_priority, ProcessResult};
+use crate::engine::buffer::{validate_sample_rate, INTERNAL_SAMPLE_RATE};
+use crate::engine::compare::{compare_buffers, ComparisonReport};
+use crate::engine::io::{
+    export_audio, export_audio_with_metadata, import_audio_at, import_audio_with_metadata_at,
+    ExportFormat, ImportResult,
+};
+use crate::engine::{Marker, MarkerPlayback, MarkerSchedule, TransportManager, WavMetadata};
+use crate::neural::{
+    IntentionalArtifact, NeuralContextTracker, NeuralModelInfo, NeuralModelParams, ProcessingResult,
+};
 use crate::state::error::{NuevaError, Result};
 use crate::state::migration::{migrate_project, CURRENT_SCHEMA_VERSION, NUEVA_VERSION};
+use crate::state::storage::{Layer1StorageManager, StorageCompression};
+use crate::state::undo::{ActionType, UndoAction, UndoManager};
 
 /// Project directory structure constants.
 pub const PROJECT_FILE: &str = "project.json";
@@ -57,6 +71,14 @@ pub struct Project {
     /// Layer 2 (DSP chain) state.
     pub layer2: Layer2,
 
+    /// Named timeline markers, sorted by position.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<Marker>,
+
+    /// Effect parameter changes scheduled at markers.
+    #[serde(default, skip_serializing_if = "MarkerSchedule::is_empty")]
+    pub marker_schedule: MarkerSchedule,
+
     /// Conversation context (for agent continuity).
     #[serde(default)]
     pub conversation: ConversationContext,
@@ -65,6 +87,10 @@ pub struct Project {
     #[serde(skip)]
     pub project_path: PathBuf,
 
+    /// Reference track for A/B comparison (not serialized).
+    #[serde(skip)]
+    pub reference: Option<crate::engine::AudioBuffer>,
+
     /// Unknown fields preserved for forward compatibility.
     #[serde(flatten)]
     pub unknown_fields: HashMap<String, serde_json::Value>,
@@ -106,7 +132,7 @@ pub struct Layer0 {
     /// Path to the audio file (relative to project).
     pub path: PathBuf,
 
-    /// Sample rate in Hz (standardized to 48000).
+    /// Processing sample rate in Hz, chosen at creation (48000 by default).
     pub sample_rate: u32,
 
     /// Bit depth (standardized to 32-bit float).
@@ -122,6 +148,19 @@ pub struct Layer0 {
     pub hash_sha256: String,
 }
 
+/// One layer of a project rendered on its own, for stem export.
+#[derive(Debug, Clone)]
+pub struct Stem {
+    /// Name identifying the stem in file names (e.g. "layer0-dry").
+    pub name: &'static str,
+
+    /// The stem's audio, or `None` when the layer is empty.
+    pub audio: Option<crate::engine::AudioBuffer>,
+
+    /// Why the layer is empty, when it is.
+    pub note: Option<&'static str>,
+}
+
 /// Layer 1: AI-processed state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Layer1 {
@@ -137,6 +176,18 @@ pub struct Layer1 {
     /// Processing information (if AI was applied).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub processing: Option<Layer1Processing>,
+
+    /// How the Layer 1 audio file is stored.
+    #[serde(default = "uncompressed")]
+    pub compression: StorageCompression,
+
+    /// Intentional artifacts and history of the AI processing.
+    #[serde(default, skip_serializing_if = "NeuralContextTracker::is_empty")]
+    pub neural_context: NeuralContextTracker,
+}
+
+fn uncompressed() -> StorageCompression {
+    StorageCompression::None
 }
 
 /// Information about AI processing applied to Layer 1.
@@ -145,6 +196,10 @@ pub struct Layer1Processing {
     /// Model used for processing.
     pub model: String,
 
+    /// Version of the model, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_version: Option<String>,
+
     /// User prompt that triggered processing.
     pub prompt: String,
 
@@ -157,6 +212,10 @@ pub struct Layer1Processing {
 
     /// How long processing took in milliseconds.
     pub processing_time_ms: u64,
+
+    /// Seed used by the model, for reproducing generative output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
 }
 
 /// Layer 2: DSP effect chain.
@@ -190,6 +249,209 @@ pub struct Effect {
     pub added_by: String,
 }
 
+impl Layer2 {
+    /// Index of the effect with the given ID.
+    pub fn position(&self, effect_id: &str) -> Result<usize> {
+        self.chain
+            .iter()
+            .position(|e| e.id == effect_id)
+            .ok_or_else(|| NuevaError::EffectNotFound {
+                effect_id: effect_id.to_string(),
+            })
+    }
+
+    /// Move an effect to `new_index`, which must be a valid index.
+    pub fn move_effect(&mut self, effect_id: &str, new_index: usize) -> Result<()> {
+        let index = self.position(effect_id)?;
+        if new_index >= self.chain.len() {
+            return Err(NuevaError::InvalidChainIndex {
+                index: new_index,
+                len: self.chain.len(),
+            });
+        }
+        let effect = self.chain.remove(index);
+        self.chain.insert(new_index, effect);
+        Ok(())
+    }
+
+    /// An ID of the form `{effect_type}-{n}` not used in the chain.
+    pub fn generate_id(&self, effect_type: &str) -> String {
+        let mut counter = 1;
+        loop {
+            let id = format!("{}-{}", effect_type, counter);
+            if self.position(&id).is_err() {
+                return id;
+            }
+            counter += 1;
+        }
+    }
+
+    /// All effects of the given type, in chain order.
+    pub fn effects_of_type(&self, effect_type: &str) -> Vec<&Effect> {
+        self.chain
+            .iter()
+            .filter(|e| e.effect_type == effect_type)
+            .collect()
+    }
+
+    /// References to every effect, in chain order, for agent reference
+    /// resolution.
+    pub fn effect_refs(&self) -> Vec<EffectRef> {
+        self.chain
+            .iter()
+            .enumerate()
+            .map(|(chain_index, effect)| EffectRef {
+                id: effect.id.clone(),
+                effect_type: effect.effect_type.clone(),
+                display_name: dsp::create_effect(&effect.effect_type)
+                    .map(|e| e.display_name().to_string())
+                    .unwrap_or_else(|| effect.effect_type.clone()),
+                chain_index,
+            })
+            .collect()
+    }
+
+    /// Sort the chain into the recommended order (spec §4.3).
+    ///
+    /// The sort is stable, so effects of the same kind keep their relative
+    /// order. Returns true if anything moved.
+    pub fn reorder_by_default_priority(&mut self) -> bool {
+        let before: Vec<String> = self.chain.iter().map(|e| e.id.clone()).collect();
+        self.chain
+            .sort_by_key(|e| get_default_order_priority(&e.effect_type));
+        self.chain.iter().zip(&before).any(|(e, id)| &e.id != id)
+    }
+
+    /// Index where an effect of `effect_type` belongs by default order:
+    /// before the first effect with a higher priority.
+    pub fn ordered_position(&self, effect_type: &str) -> usize {
+        let priority = get_default_order_priority(effect_type);
+        self.chain
+            .iter()
+            .position(|e| get_default_order_priority(&e.effect_type) > priority)
+            .unwrap_or(self.chain.len())
+    }
+
+    /// Build the DSP chain these records describe.
+    ///
+    /// Goes through [`dsp::EffectChain::from_json`], so an unknown effect
+    /// type or out-of-range parameter is an error.
+    pub fn effect_chain(&self) -> crate::error::Result<dsp::EffectChain> {
+        dsp::EffectChain::from_json(&chain_json(&self.chain))
+    }
+
+    /// Replace each effect's params with the full state the built chain
+    /// serializes, so every parameter is stored explicitly.
+    fn normalize_params(&mut self) -> crate::error::Result<()> {
+        let json = self.effect_chain()?.to_json()?;
+        let entries = json["effects"].as_array().into_iter().flatten();
+        for (effect, entry) in self.chain.iter_mut().zip(entries) {
+            if let Some(params) = entry["params"].as_object() {
+                effect.params = params.clone().into_iter().collect();
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the whole chain over `audio`.
+    pub fn render(&self, audio: &mut dsp::AudioBuffer) -> Result<()> {
+        self.render_at_markers(audio, &[], &MarkerSchedule::new())
+    }
+
+    /// Run the whole chain over `audio`, changing parameters at `markers`
+    /// as `schedule` says.
+    pub fn render_at_markers(
+        &self,
+        audio: &mut dsp::AudioBuffer,
+        markers: &[Marker],
+        schedule: &MarkerSchedule,
+    ) -> Result<()> {
+        render_effects(&self.chain, audio, markers, schedule)
+    }
+
+    /// Render the chain up to and including `effect_id` into `audio`, then
+    /// remove those effects so only the tail of the chain remains.
+    ///
+    /// Changes scheduled at markers for the baked effects are rendered
+    /// with them. The chain is left untouched if rendering fails. Returns
+    /// the baked effects.
+    pub fn bake_through(
+        &mut self,
+        effect_id: &str,
+        audio: &mut dsp::AudioBuffer,
+        markers: &[Marker],
+        schedule: &MarkerSchedule,
+    ) -> Result<Vec<Effect>> {
+        let end = self.position(effect_id)? + 1;
+        render_effects(&self.chain[..end], audio, markers, schedule)?;
+        Ok(self.chain.drain(..end).collect())
+    }
+}
+
+/// Chain JSON, in the form [`dsp::EffectChain::from_json`] reads, for
+/// stored effect records.
+fn chain_json(effects: &[Effect]) -> serde_json::Value {
+    let entries: Vec<serde_json::Value> = effects
+        .iter()
+        .map(|effect| {
+            serde_json::json!({
+                "effect_type": effect.effect_type,
+                "id": effect.id,
+                "enabled": effect.enabled,
+                "params": effect.params,
+            })
+        })
+        .collect();
+    serde_json::json!({ "effects": entries })
+}
+
+/// Process `audio` through stored effects in order, skipping disabled ones.
+///
+/// The audio is first extended with enough silence to hold the combined
+/// tails of the effects (reverb, delay), so nothing is cut off. Lookahead
+/// latency is rendered past the end too and trimmed from the front, so the
+/// output lines up with the input.
+///
+/// Changes `schedule` has for these effects are applied on the sample of
+/// their marker; changes for other effects are ignored.
+fn render_effects(
+    effects: &[Effect],
+    audio: &mut dsp::AudioBuffer,
+    markers: &[Marker],
+    schedule: &MarkerSchedule,
+) -> Result<()> {
+    let render_error = |reason: String| NuevaError::BakeError { reason };
+
+    let mut chain = dsp::EffectChain::from_json(&chain_json(effects))
+        .map_err(|e| render_error(e.to_string()))?;
+    chain.prepare(audio.sample_rate(), audio.num_samples());
+
+    // Tails run in series: a delay's last echo still feeds the reverb
+    let latency = chain.latency_samples();
+    audio.append_silence(chain.tail_samples() + latency);
+
+    let mut schedule = schedule.clone();
+    schedule.retain_effects(|id| effects.iter().any(|e| e.id == id));
+    let results = if schedule.is_empty() {
+        chain.process(audio)
+    } else {
+        MarkerPlayback::new(&schedule, markers, &chain)
+            .and_then(|playback| {
+                playback.seek(&mut chain, 0)?;
+                playback.process_block(&mut chain, audio, 0)
+            })
+            .map_err(|e| render_error(e.to_string()))?
+    };
+
+    for result in results {
+        if let ProcessResult::Failure(reason) = result {
+            return Err(render_error(reason));
+        }
+    }
+    audio.trim_start(latency);
+    Ok(())
+}
+
 /// Conversation context for agent continuity.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConversationContext {
@@ -229,6 +491,22 @@ pub struct UserPreferences {
 impl Project {
     /// Create a new project at the given path.
     pub fn create(path: &Path, input: Option<&Path>) -> Result<Self> {
+        Self::create_with_sample_rate(path, input, INTERNAL_SAMPLE_RATE)
+    }
+
+    /// Create a new project that processes at `sample_rate`.
+    ///
+    /// Imported audio is resampled to this rate, and effects and renders
+    /// run at it.
+    pub fn create_with_sample_rate(
+        path: &Path,
+        input: Option<&Path>,
+        sample_rate: u32,
+    ) -> Result<Self> {
+        validate_sample_rate(sample_rate).map_err(|e| NuevaError::InvalidAudioFormat {
+            reason: e.to_string(),
+        })?;
+
         // Check if project already exists
         if path.exists() {
             return Err(NuevaError::ProjectAlreadyExists {
@@ -261,7 +539,7 @@ impl Project {
             },
             layer0: Layer0 {
                 path: PathBuf::from(AUDIO_DIR).join(LAYER0_FILE),
-                sample_rate: 48000,
+                sample_rate,
                 bit_depth: 32,
                 channels: 2,
                 duration_seconds: 0.0,
@@ -272,10 +550,15 @@ impl Project {
                 is_processed: false,
                 identical_to_layer0: true,
                 processing: None,
+                compression: StorageCompression::None,
+                neural_context: NeuralContextTracker::new(),
             },
             layer2: Layer2::default(),
+            markers: Vec::new(),
+            marker_schedule: MarkerSchedule::new(),
             conversation: ConversationContext::default(),
             project_path: path.to_path_buf(),
+            reference: None,
             unknown_fields: HashMap::new(),
         };
 
@@ -315,6 +598,14 @@ impl Project {
         let mut project: Project = serde_json::from_value(data)?;
         project.project_path = path.to_path_buf();
 
+        // The stored chain must rebuild into live effects
+        project
+            .layer2
+            .effect_chain()
+            .map_err(|e| NuevaError::InvalidProjectStructure {
+                reason: format!("layer2: {}", e),
+            })?;
+
         // Create/update lock file
         project.create_lock()?;
 
@@ -323,6 +614,11 @@ impl Project {
 
     /// Save the project to disk.
     pub fn save(&mut self) -> Result<()> {
+        self.layer2
+            .normalize_params()
+            .map_err(|e| NuevaError::InvalidProjectStructure {
+                reason: format!("layer2: {}", e),
+            })?;
         self.modified_at = Utc::now();
         self.nueva_version = NUEVA_VERSION.to_string();
 
@@ -402,23 +698,35 @@ impl Project {
     }
 
     /// Import audio file into the project.
-    pub fn import_audio(&mut self, input_path: &Path) -> Result<()> {
+    pub fn import_audio(&mut self, input_path: &Path) -> Result<ImportResult> {
         if !input_path.exists() {
             return Err(NuevaError::AudioNotFound {
                 path: input_path.to_path_buf(),
             });
         }
 
-        // For now, just copy the file (real implementation would convert to 48kHz/32-bit)
-        let layer0_path = self.project_path.join(&self.layer0.path);
-        fs::copy(input_path, &layer0_path).map_err(|e| NuevaError::FileWriteError {
-            path: layer0_path.clone(),
-            source: e,
+        // Convert to 32-bit float at the project rate; the loop and tempo
+        // stay with the source file, cue points become project markers
+        let sample_rate = self.layer0.sample_rate;
+        let imported = import_audio_with_metadata_at(input_path, sample_rate).map_err(|e| {
+            NuevaError::InvalidAudioFormat {
+                reason: e.to_string(),
+            }
         })?;
+        let audio = &imported.buffer;
+        let layer0_path = self.project_path.join(&self.layer0.path);
+        export_audio_with_metadata(
+            audio,
+            &layer0_path,
+            ExportFormat::new(sample_rate, 32),
+            &imported.metadata,
+        )
+        .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
+        self.markers = imported.metadata.markers.clone();
 
         // Copy to Layer 1 as well (initially identical)
         let layer1_path = self.project_path.join(&self.layer1.path);
-        fs::copy(input_path, &layer1_path).map_err(|e| NuevaError::FileWriteError {
+        fs::copy(&layer0_path, &layer1_path).map_err(|e| NuevaError::FileWriteError {
             path: layer1_path,
             source: e,
         })?;
@@ -436,11 +744,25 @@ impl Project {
             .to_string();
         self.source.original_path = input_path.to_path_buf();
 
-        // TODO: Read actual audio metadata (sample rate, duration, etc.)
-        // For now, use placeholder values
-        self.layer0.duration_seconds = 0.0; // Would need audio library to read
+        self.layer0.bit_depth = 32;
+        self.layer0.channels = audio.num_channels() as u8;
+        self.layer0.duration_seconds = audio.duration_secs();
 
-        Ok(())
+        Ok(imported)
+    }
+
+    /// Metadata to write with a render: the project's markers, plus the
+    /// loop and tempo imported with the source audio.
+    pub fn wav_metadata(&self) -> Result<WavMetadata> {
+        let layer0_path = self.project_path.join(&self.layer0.path);
+        let source =
+            WavMetadata::read(&layer0_path).map_err(|e| NuevaError::InvalidAudioFormat {
+                reason: e.to_string(),
+            })?;
+        Ok(WavMetadata {
+            markers: self.markers.clone(),
+            ..source
+        })
     }
 
     /// Validate that the project is ready for bake operation.
@@ -480,16 +802,42 @@ impl Project {
             })?;
         }
 
+        // The loop and tempo belong to the timeline, not the old audio
+        let source_metadata = WavMetadata {
+            markers: Vec::new(),
+            ..self.wav_metadata()?
+        };
+
         // 2. Render L1 → L2 DSP chain → temp file
         // TODO: Implement actual DSP processing
         // For now, just copy Layer 1 to Layer 0 (no DSP rendering yet)
         let layer1_path = self.project_path.join(&self.layer1.path);
 
         // 3. Replace Layer 0 with rendered result
-        fs::copy(&layer1_path, &layer0_path).map_err(|e| NuevaError::FileWriteError {
-            path: layer0_path.clone(),
-            source: e,
-        })?;
+        if self.layer1.compression == StorageCompression::None {
+            fs::copy(&layer1_path, &layer0_path).map_err(|e| NuevaError::FileWriteError {
+                path: layer0_path.clone(),
+                source: e,
+            })?;
+        } else {
+            let audio = self.load_layer1()?;
+            export_audio(
+                &audio,
+                &layer0_path,
+                ExportFormat::new(audio.sample_rate, 32),
+            )
+            .map_err(|e| NuevaError::BakeError {
+                reason: e.to_string(),
+            })?;
+        }
+        let baked_metadata = WavMetadata::read(&layer0_path).unwrap_or_default();
+        if !source_metadata.is_empty() && baked_metadata.is_empty() {
+            source_metadata
+                .append_to(&layer0_path, self.layer0.sample_rate)
+                .map_err(|e| NuevaError::BakeError {
+                    reason: e.to_string(),
+                })?;
+        }
 
         // 4. Update Layer 0 hash
         let content = fs::read(&layer0_path)?;
@@ -497,6 +845,9 @@ impl Project {
         self.layer0.hash_sha256 = format!("{:x}", hash);
 
         // 5. Reset Layer 1 to copy of new Layer 0
+        self.layer1.path = PathBuf::from(AUDIO_DIR).join(LAYER1_FILE);
+        self.layer1.compression = StorageCompression::None;
+        let layer1_path = self.project_path.join(&self.layer1.path);
         fs::copy(&layer0_path, &layer1_path).map_err(|e| NuevaError::FileWriteError {
             path: layer1_path,
             source: e,
@@ -505,6 +856,8 @@ impl Project {
         self.layer1.is_processed = false;
         self.layer1.identical_to_layer0 = true;
         self.layer1.processing = None;
+        // Artifacts of the old Layer 1 no longer excuse anything
+        self.layer1.neural_context.clear();
 
         // 6. Clear Layer 2 DSP chain
         self.layer2.chain.clear();
@@ -515,6 +868,483 @@ impl Project {
         Ok(())
     }
 
+    /// Bake the effect chain up to and including `effect_id` into Layer 1,
+    /// keeping the rest of the chain live on top of it.
+    ///
+    /// The rendered audio goes to a content-addressed (compressed) Layer 1
+    /// file, shared with identical audio already stored, so undo snapshots
+    /// that point at the previous one still resolve. Returns the new
+    /// Layer 1 path (relative to the project).
+    pub fn bake_through(&mut self, effect_id: &str) -> Result<PathBuf> {
+        self.validate_for_bake()?;
+        self.layer2.position(effect_id)?;
+
+        let source = self.load_layer1()?;
+        let mut audio =
+            dsp::AudioBuffer::from_engine(&source).map_err(|e| NuevaError::BakeError {
+                reason: e.to_string(),
+            })?;
+
+        self.layer2
+            .bake_through(effect_id, &mut audio, &self.markers, &self.marker_schedule)?;
+        // Changes to the baked effects are in the audio now
+        let chain = &self.layer2.chain;
+        self.marker_schedule
+            .retain_effects(|id| chain.iter().any(|e| e.id == id));
+
+        let storage = Layer1StorageManager::new(&self.project_path);
+        let written = storage.write_layer1_blob(&audio.to_engine())?;
+        let relative = written
+            .strip_prefix(&self.project_path)
+            .map(Path::to_path_buf)
+            .unwrap_or(written);
+
+        self.layer1.path = relative.clone();
+        self.layer1.compression = storage.compression();
+        self.layer1.is_processed = true;
+        self.layer1.identical_to_layer0 = false;
+
+        self.save()?;
+
+        Ok(relative)
+    }
+
+    /// Length of the project audio in samples.
+    fn timeline_samples(&self) -> u64 {
+        (self.layer0.duration_seconds * f64::from(self.layer0.sample_rate)).round() as u64
+    }
+
+    /// A transport holding the project's markers, bounded by its length.
+    fn transport(&self) -> TransportManager {
+        let mut transport = TransportManager::new(self.layer0.sample_rate);
+        transport.set_audio_length(self.timeline_samples());
+        transport.set_markers(self.markers.clone());
+        transport
+    }
+
+    /// Add a named marker at `sample`.
+    ///
+    /// Names are unique ignoring case, and the marker must lie within the
+    /// audio.
+    pub fn add_marker(&mut self, name: &str, sample: u64) -> Result<()> {
+        let mut transport = self.transport();
+        transport
+            .add_marker(name, sample)
+            .map_err(|e| NuevaError::ProcessingFailed {
+                reason: e.to_string(),
+            })?;
+        self.markers = transport.markers().to_vec();
+        Ok(())
+    }
+
+    /// Remove a marker by name (ignoring case), along with the parameter
+    /// changes scheduled at it.
+    pub fn remove_marker(&mut self, name: &str) -> Result<Marker> {
+        let mut transport = self.transport();
+        let marker = transport
+            .remove_marker(name)
+            .ok_or_else(|| NuevaError::ProcessingFailed {
+                reason: format!("No marker named '{}'", name),
+            })?;
+        self.markers = transport.markers().to_vec();
+        self.marker_schedule.remove_marker(&marker.name);
+        Ok(marker)
+    }
+
+    /// Set `param` of `effect_id` to `value` from `marker` on when
+    /// rendering.
+    ///
+    /// The marker, the effect and the parameter must exist, and the value
+    /// must suit the parameter.
+    pub fn schedule_at_marker(
+        &mut self,
+        marker: &str,
+        effect_id: &str,
+        param: &str,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        if !self
+            .markers
+            .iter()
+            .any(|m| m.name.eq_ignore_ascii_case(marker.trim()))
+        {
+            return Err(NuevaError::ProcessingFailed {
+                reason: format!("No marker named '{}'", marker),
+            });
+        }
+        self.layer2.position(effect_id)?;
+
+        let mut schedule = self.marker_schedule.clone();
+        schedule.schedule(marker, effect_id, param, value);
+        let mut chain = self
+            .layer2
+            .effect_chain()
+            .map_err(|e| NuevaError::ProcessingFailed {
+                reason: e.to_string(),
+            })?;
+        let timeline_end = self.timeline_samples();
+        MarkerPlayback::new(&schedule, &self.markers, &chain)
+            .and_then(|playback| playback.seek(&mut chain, timeline_end))
+            .map_err(|e| NuevaError::ProcessingFailed {
+                reason: e.to_string(),
+            })?;
+
+        self.marker_schedule = schedule;
+        Ok(())
+    }
+
+    /// Trim the project audio to `start_sample..end_sample`.
+    ///
+    /// Layer 0 is written to a new file so undo snapshots that point at
+    /// the old one still resolve, and Layer 1 goes to a content-addressed
+    /// file as for a partial bake. Markers shift with the audio; those in
+    /// the removed parts are clamped to the new start or end. Returns the
+    /// new Layer 1 path (relative to the project).
+    pub fn trim(&mut self, start_sample: u64, end_sample: u64) -> Result<PathBuf> {
+        let length = self.timeline_samples();
+        if start_sample >= end_sample || end_sample > length {
+            return Err(NuevaError::ProcessingFailed {
+                reason: format!(
+                    "Trim range {}..{} must be non-empty and within the audio (0..{} samples)",
+                    start_sample, end_sample, length
+                ),
+            });
+        }
+        let trim = |audio: &mut crate::engine::AudioBuffer| {
+            let end = (end_sample as usize).min(audio.len());
+            let start = (start_sample as usize).min(end);
+            for channel in audio.samples.iter_mut() {
+                channel.truncate(end);
+                channel.drain(..start);
+            }
+        };
+
+        let layer0_path = self.project_path.join(&self.layer0.path);
+        let mut dry = import_audio_at(&layer0_path, self.layer0.sample_rate).map_err(|e| {
+            NuevaError::InvalidAudioFormat {
+                reason: e.to_string(),
+            }
+        })?;
+        let mut processed = self.load_layer1()?;
+        trim(&mut dry);
+        trim(&mut processed);
+
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S%3f");
+        let layer0_relative =
+            PathBuf::from(AUDIO_DIR).join(format!("layer0_trim_{}.wav", timestamp));
+        let layer0_path = self.project_path.join(&layer0_relative);
+        export_audio(&dry, &layer0_path, ExportFormat::new(dry.sample_rate, 32))
+            .map_err(|e| NuevaError::Io(std::io::Error::other(e.to_string())))?;
+
+        let storage = Layer1StorageManager::new(&self.project_path);
+        let written = storage.write_layer1_blob(&processed)?;
+        let layer1_relative = written
+            .strip_prefix(&self.project_path)
+            .map(Path::to_path_buf)
+            .unwrap_or(written);
+
+        let content = fs::read(&layer0_path)?;
+        self.layer0.path = layer0_relative;
+        self.layer0.hash_sha256 = format!("{:x}", Sha256::digest(&content));
+        self.layer0.bit_depth = 32;
+        self.layer0.duration_seconds = dry.duration_secs();
+        self.layer1.path = layer1_relative.clone();
+        self.layer1.compression = storage.compression();
+
+        let mut transport = self.transport();
+        transport.apply_trim(start_sample, end_sample);
+        self.markers = transport.markers().to_vec();
+
+        self.save()?;
+
+        Ok(layer1_relative)
+    }
+
+    /// Store a neural model's output as the new Layer 1.
+    ///
+    /// Like a partial bake, the audio goes to a content-addressed Layer 1
+    /// file so undo snapshots that point at the previous one still
+    /// resolve. The model, its version, the parameters, the seed and the
+    /// time are recorded alongside it. Returns the new Layer 1 path
+    /// (relative to the project).
+    pub fn apply_ai_result(
+        &mut self,
+        model: &NeuralModelInfo,
+        prompt: &str,
+        params: &NeuralModelParams,
+        result: &ProcessingResult,
+        output: &Path,
+    ) -> Result<PathBuf> {
+        if !result.success {
+            return Err(NuevaError::ProcessingFailed {
+                reason: result.description.clone(),
+            });
+        }
+
+        let audio = import_audio_at(output, self.layer0.sample_rate).map_err(|e| {
+            NuevaError::InvalidAudioFormat {
+                reason: e.to_string(),
+            }
+        })?;
+        let storage = Layer1StorageManager::new(&self.project_path);
+        let written = storage.write_layer1_blob(&audio)?;
+        let relative = written
+            .strip_prefix(&self.project_path)
+            .map(Path::to_path_buf)
+            .unwrap_or(written);
+
+        self.layer1.path = relative.clone();
+        self.layer1.compression = storage.compression();
+        self.layer1.is_processed = true;
+        self.layer1.identical_to_layer0 = false;
+        self.layer1.processing = Some(Layer1Processing {
+            model: model.id.clone(),
+            model_version: Some(model.version.clone()),
+            prompt: prompt.to_string(),
+            params: params.params.clone(),
+            processed_at: Utc::now(),
+            processing_time_ms: result.processing_time_ms,
+            seed: result.seed.or(params.seed),
+        });
+        self.layer1.neural_context.record_operation(
+            &model.id,
+            params.params.clone(),
+            &result.description,
+        );
+        for artifact in &result.intentional_artifacts {
+            match IntentionalArtifact::from_name(artifact) {
+                Some(artifact) => self.layer1.neural_context.add_artifact(artifact),
+                None => log::debug!("Unknown intentional artifact '{}'", artifact),
+            }
+        }
+
+        self.save()?;
+
+        Ok(relative)
+    }
+
+    /// Move an effect in the Layer 2 chain, recording the change for undo.
+    pub fn move_effect(
+        &mut self,
+        undo_manager: &mut UndoManager,
+        effect_id: &str,
+        new_index: usize,
+    ) -> Result<()> {
+        let description = format!("Move {} to position {}", effect_id, new_index);
+        self.record_chain_change(undo_manager, description, |layer2| {
+            layer2.move_effect(effect_id, new_index).map(|_| true)
+        })
+        .map(|_| ())
+    }
+
+    /// Append an effect to the Layer 2 chain, recording the change for
+    /// undo.
+    pub fn add_effect(&mut self, undo_manager: &mut UndoManager, effect: Effect) -> Result<()> {
+        let description = format!("Add {}", effect.id);
+        self.record_chain_change(undo_manager, description, |layer2| {
+            layer2.chain.push(effect);
+            Ok(true)
+        })
+        .map(|_| ())
+    }
+
+    /// Remove an effect from the Layer 2 chain, recording the change for
+    /// undo.
+    pub fn remove_effect(&mut self, undo_manager: &mut UndoManager, effect_id: &str) -> Result<()> {
+        let description = format!("Remove {}", effect_id);
+        self.record_chain_change(undo_manager, description, |layer2| {
+            let index = layer2.position(effect_id)?;
+            layer2.chain.remove(index);
+            Ok(true)
+        })
+        .map(|_| ())
+    }
+
+    /// Insert an effect into the Layer 2 chain at its default order
+    /// position (see [`Layer2::ordered_position`]), recording the change
+    /// for undo. Returns the index it was inserted at.
+    pub fn add_effect_ordered(
+        &mut self,
+        undo_manager: &mut UndoManager,
+        effect: Effect,
+    ) -> Result<usize> {
+        let index = self.layer2.ordered_position(&effect.effect_type);
+        let description = format!("Add {} at position {}", effect.id, index);
+        self.record_chain_change(undo_manager, description, |layer2| {
+            layer2.chain.insert(index, effect);
+            Ok(true)
+        })?;
+        Ok(index)
+    }
+
+    /// Sort the Layer 2 chain into the recommended order, recording the
+    /// change for undo. Returns false (and records nothing) if the chain
+    /// was already in order.
+    pub fn reorder_chain_by_default_priority(
+        &mut self,
+        undo_manager: &mut UndoManager,
+    ) -> Result<bool> {
+        self.record_chain_change(undo_manager, "Reorder effect chain", |layer2| {
+            Ok(layer2.reorder_by_default_priority())
+        })
+    }
+
+    /// Set one parameter of a Layer 2 effect, recording the change for
+    /// undo. Returns false (and records nothing) if it already had that
+    /// value.
+    ///
+    /// Rapid changes to the same parameter are coalesced by the undo
+    /// manager into one action.
+    pub fn set_effect_param(
+        &mut self,
+        undo_manager: &mut UndoManager,
+        effect_id: &str,
+        param: &str,
+        value: impl Into<serde_json::Value>,
+    ) -> Result<bool> {
+        let value = value.into();
+        let index = self.layer2.position(effect_id)?;
+        if self.layer2.chain[index].params.get(param) == Some(&value) {
+            return Ok(false);
+        }
+
+        let state_before = serde_json::to_value(&*self)?;
+        let description = format!("Set {} {} to {}", effect_id, param, value);
+        self.layer2.chain[index]
+            .params
+            .insert(param.to_string(), value);
+        undo_manager.push(
+            UndoAction::new(
+                ActionType::DspChange,
+                description,
+                state_before,
+                serde_json::to_value(&*self)?,
+            )
+            .with_parameter(effect_id, param),
+        );
+        Ok(true)
+    }
+
+    /// Apply a chain edit and push a DSP change with before/after
+    /// snapshots if it reports a change.
+    fn record_chain_change<F>(
+        &mut self,
+        undo_manager: &mut UndoManager,
+        description: impl Into<String>,
+        change: F,
+    ) -> Result<bool>
+    where
+        F: FnOnce(&mut Layer2) -> Result<bool>,
+    {
+        let state_before = serde_json::to_value(&*self)?;
+        if !change(&mut self.layer2)? {
+            return Ok(false);
+        }
+        undo_manager.push(UndoAction::new(
+            ActionType::DspChange,
+            description,
+            state_before,
+            serde_json::to_value(&*self)?,
+        ));
+        Ok(true)
+    }
+
+    /// Load the current Layer 1 audio, decompressing it if needed.
+    ///
+    /// A missing file the storage manifest still references is reported as
+    /// `StorageCorrupted`.
+    pub fn load_layer1(&self) -> Result<crate::engine::AudioBuffer> {
+        let path = self.project_path.join(&self.layer1.path);
+        if !path.exists() {
+            Layer1StorageManager::new(&self.project_path).verify_blob(&path)?;
+        }
+        Layer1StorageManager::load_layer1_at(&path, self.layer0.sample_rate)
+    }
+
+    /// Each layer on its own: Layer 0 as imported, Layer 1 as the AI left
+    /// it, and Layer 2 as the chain's output over Layer 1.
+    ///
+    /// A layer that adds nothing (no AI processing, no enabled effects) has
+    /// no audio, so an export can skip it instead of writing a duplicate.
+    pub fn stems(&self) -> Result<Vec<Stem>> {
+        let layer0_path = self.project_path.join(&self.layer0.path);
+        let dry = import_audio_at(&layer0_path, self.layer0.sample_rate).map_err(|e| {
+            NuevaError::InvalidAudioFormat {
+                reason: e.to_string(),
+            }
+        })?;
+        let stem = |name, audio: Option<_>, note| Stem {
+            name,
+            note: audio.is_none().then_some(note),
+            audio,
+        };
+
+        let ai = (!self.layer1.identical_to_layer0)
+            .then(|| self.load_layer1())
+            .transpose()?;
+        let dsp = self
+            .layer2
+            .chain
+            .iter()
+            .any(|e| e.enabled)
+            .then(|| self.render_output())
+            .transpose()?;
+
+        Ok(vec![
+            stem("layer0-dry", Some(dry), ""),
+            stem("layer1-ai", ai, "Layer 1 has no AI processing"),
+            stem("layer2-dsp", dsp, "Layer 2 has no enabled effects"),
+        ])
+    }
+
+    /// Render Layer 2 over the current Layer 1 audio without touching the
+    /// project.
+    pub fn render_output(&self) -> Result<crate::engine::AudioBuffer> {
+        let source = self.load_layer1()?;
+        let mut audio =
+            dsp::AudioBuffer::from_engine(&source).map_err(|e| NuevaError::ProcessingFailed {
+                reason: e.to_string(),
+            })?;
+        self.layer2
+            .render_at_markers(&mut audio, &self.markers, &self.marker_schedule)?;
+        Ok(audio.to_engine())
+    }
+
+    /// Load a reference track to compare the mix against.
+    ///
+    /// The reference is resampled to the project rate and kept beside the
+    /// layers, which are left untouched.
+    pub fn load_reference(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Err(NuevaError::AudioNotFound {
+                path: path.to_path_buf(),
+            });
+        }
+        let audio = import_audio_at(path, self.layer0.sample_rate).map_err(|e| {
+            NuevaError::InvalidAudioFormat {
+                reason: e.to_string(),
+            }
+        })?;
+        self.reference = Some(audio);
+        Ok(())
+    }
+
+    /// Measure the rendered mix against the loaded reference.
+    ///
+    /// If the two differ in length only the overlapping region is compared.
+    pub fn compare_to_reference(&self) -> Result<ComparisonReport> {
+        let reference = self
+            .reference
+            .as_ref()
+            .ok_or_else(|| NuevaError::ProcessingFailed {
+                reason: "No reference track loaded".to_string(),
+            })?;
+        let mix = self.render_output()?;
+        compare_buffers(&mix, reference).map_err(|e| NuevaError::ProcessingFailed {
+            reason: e.to_string(),
+        })
+    }
+
     /// Mark the project as having unsaved changes.
     pub fn has_unsaved_changes(&self) -> bool {
         // In a real implementation, this would track dirty state
@@ -527,3 +1357,830 @@ impl Project {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::io::generate_test_tone;
+    use crate::neural::{IntentionalArtifact, MockAceStep, NeuralModel};
+    use tempfile::TempDir;
+
+    fn gain(id: &str, gain_db: f64) -> Effect {
+        Effect {
+            id: id.to_string(),
+            effect_type: "gain".to_string(),
+            enabled: true,
+            params: [("gain_db".to_string(), serde_json::json!(gain_db))].into(),
+            added_at: Utc::now(),
+            added_by: "user".to_string(),
+        }
+    }
+
+    fn project_with_chain(temp: &TempDir) -> Project {
+        let input = temp.path().join("input.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 48000),
+            &input,
+            ExportFormat::new(48000, 32),
+        )
+        .unwrap();
+
+        let mut project = Project::create(&temp.path().join("project"), Some(&input)).unwrap();
+        project.layer2.chain = vec![gain("gain-1", -6.0), gain("gain-2", -6.0)];
+        project
+    }
+
+    fn layer1_audio(project: &Project) -> dsp::AudioBuffer {
+        dsp::AudioBuffer::from_engine(&project.load_layer1().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_stems_isolate_layers() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+
+        let stems = project.stems().unwrap();
+        let names: Vec<&str> = stems.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["layer0-dry", "layer1-ai", "layer2-dsp"]);
+
+        // The dry stem is Layer 0, untouched by the chain
+        let source = import_audio_at(&temp.path().join("input.wav"), 48000).unwrap();
+        assert_eq!(stems[0].audio.as_ref().unwrap().samples, source.samples);
+        assert!(stems[1].audio.is_none());
+        assert!(stems[1].note.is_some());
+        let mut expected = layer1_audio(&project);
+        project.layer2.render(&mut expected).unwrap();
+        assert_eq!(
+            stems[2].audio.as_ref().unwrap().samples,
+            expected.to_engine().samples
+        );
+
+        // Baking gives Layer 1 content; disabling the rest empties Layer 2
+        project.bake_through("gain-1").unwrap();
+        project.layer2.chain[0].enabled = false;
+        let stems = project.stems().unwrap();
+        assert!(stems[1].audio.is_some());
+        assert!(stems[2].audio.is_none());
+        assert!(stems[2].note.unwrap().contains("no enabled effects"));
+    }
+
+    #[test]
+    fn test_apply_ai_result_records_provenance() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let layer1_before = project.layer1.path.clone();
+
+        let model = MockAceStep::new();
+        let params = NeuralModelParams::new()
+            .with_param("prompt", "jazz version")
+            .with_param("inference_steps", 2);
+        let output = temp.path().join("ai.wav");
+        let result = model
+            .process(
+                &project.project_path.join(&project.layer0.path),
+                &output,
+                &params,
+            )
+            .unwrap();
+
+        let started = Utc::now();
+        project
+            .apply_ai_result(model.info(), "jazz version", &params, &result, &output)
+            .unwrap();
+
+        let reloaded = Project::load(&project.project_path).unwrap();
+        assert_ne!(reloaded.layer1.path, layer1_before);
+        assert!(reloaded.layer1.is_processed);
+        assert!(!reloaded.layer1.identical_to_layer0);
+        let processing = reloaded.layer1.processing.as_ref().unwrap();
+        assert_eq!(processing.model, model.info().id);
+        assert_eq!(
+            processing.model_version.as_deref(),
+            Some(model.info().version.as_str())
+        );
+        assert_eq!(processing.prompt, "jazz version");
+        assert_eq!(processing.params, params.params);
+        assert!(processing.processed_at >= started);
+        // No seed was requested, so the one the model picked is kept
+        assert!(processing.seed.is_some());
+        assert_eq!(processing.seed, result.seed);
+        assert_eq!(reloaded.layer1.neural_context.operation_history.len(), 1);
+        assert!(reloaded.load_layer1().is_ok());
+    }
+
+    #[test]
+    fn test_bake_through_keeps_tail_effect_applied() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+
+        let mut expected = layer1_audio(&project);
+        project.layer2.render(&mut expected).unwrap();
+
+        project.bake_through("gain-1").unwrap();
+        assert_eq!(project.layer2.chain.len(), 1);
+        assert_eq!(project.layer2.chain[0].id, "gain-2");
+        assert!(project.layer1.is_processed);
+
+        let mut actual = layer1_audio(&project);
+        project.layer2.render(&mut actual).unwrap();
+
+        assert_eq!(actual.num_samples(), expected.num_samples());
+        let max_error = actual
+            .samples()
+            .iter()
+            .zip(expected.samples())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0_f32, f32::max);
+        assert!(max_error < 1e-5, "max error: {}", max_error);
+    }
+
+    #[test]
+    fn test_layer2_round_trips_through_effect_chain() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let mut delay = gain("echo", 0.0);
+        delay.effect_type = "delay".to_string();
+        delay.params = [("delay_time_ms".to_string(), serde_json::json!(120.0))].into();
+        delay.enabled = false;
+        project.layer2.chain.insert(1, delay);
+
+        let mut expected = layer1_audio(&project);
+        project.layer2.render(&mut expected).unwrap();
+        project.save().unwrap();
+        project.release_lock().unwrap();
+
+        let loaded = Project::load(&temp.path().join("project")).unwrap();
+        let ids: Vec<&str> = loaded.layer2.chain.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["gain-1", "echo", "gain-2"]);
+        let echo = &loaded.layer2.chain[1];
+        assert!(!echo.enabled);
+        // Saved from the chain's serialization, so defaults are explicit
+        assert_eq!(echo.params["delay_time_ms"], serde_json::json!(120.0));
+        assert!(echo.params.contains_key("feedback"));
+
+        let mut actual = layer1_audio(&loaded);
+        loaded.layer2.render(&mut actual).unwrap();
+        assert_eq!(actual.samples(), expected.samples());
+        loaded.release_lock().unwrap();
+
+        let file = temp.path().join("project").join(PROJECT_FILE);
+        let mut data: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        data["layer2"]["chain"][1]["type"] = serde_json::json!("flanger");
+        fs::write(&file, data.to_string()).unwrap();
+        let err = Project::load(&temp.path().join("project")).unwrap_err();
+        assert!(err.to_string().contains("flanger"), "{}", err);
+    }
+
+    #[test]
+    fn test_render_appends_effect_tails() {
+        let mut layer2 = Layer2::default();
+        let mut audio = dsp::AudioBuffer::new(1, 4800, 48000.0);
+        audio.set(0, 0, 1.0);
+
+        // Effects without a tail leave the length alone
+        layer2.chain = vec![gain("gain-1", -6.0)];
+        let mut dry = audio.clone();
+        layer2.render(&mut dry).unwrap();
+        assert_eq!(dry.num_samples(), 4800);
+
+        // A 200 ms echo would land past the end of a 100 ms input
+        let mut delay = effect("delay-1", "delay");
+        delay.params = [
+            ("delay_time_ms".to_string(), serde_json::json!(200.0)),
+            ("feedback".to_string(), serde_json::json!(0.0)),
+        ]
+        .into();
+        layer2.chain = vec![delay];
+        layer2.render(&mut audio).unwrap();
+
+        assert!(audio.num_samples() > 4800 + 9600);
+        let echo = (4800..audio.num_samples())
+            .map(|i| audio.get(i, 0).unwrap().abs())
+            .fold(0.0_f32, f32::max);
+        assert!(echo > 0.1, "echo missing from the rendered tail");
+    }
+
+    #[test]
+    fn test_render_compensates_effect_latency() {
+        let mut layer2 = Layer2::default();
+        let mut audio = dsp::AudioBuffer::new(1, 4800, 48000.0);
+        audio.set(1000, 0, 1.0);
+
+        // A fully open gate with 5 ms lookahead only delays the signal
+        let mut gate = effect("gate-1", "gate");
+        gate.params = [
+            ("threshold_db".to_string(), serde_json::json!(-80.0)),
+            ("attack_ms".to_string(), serde_json::json!(1.0)),
+            ("release_ms".to_string(), serde_json::json!(50.0)),
+            ("hold_ms".to_string(), serde_json::json!(10.0)),
+            ("range_db".to_string(), serde_json::json!(0.0)),
+            ("lookahead_ms".to_string(), serde_json::json!(5.0)),
+        ]
+        .into();
+        layer2.chain = vec![gate];
+        layer2.render(&mut audio).unwrap();
+
+        assert_eq!(audio.num_samples(), 4800);
+        assert!((audio.get(1000, 0).unwrap() - 1.0).abs() < 1e-4);
+        let stray = (0..audio.num_samples())
+            .filter(|&i| i != 1000)
+            .map(|i| audio.get(i, 0).unwrap().abs())
+            .fold(0.0_f32, f32::max);
+        assert!(stray < 1e-6, "impulse moved or smeared: {}", stray);
+    }
+
+    #[test]
+    fn test_project_sample_rate_is_used_throughout() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("cd.wav");
+        export_audio(
+            &generate_test_tone(440.0, 0.5, 44100),
+            &input,
+            ExportFormat::new(44100, 16),
+        )
+        .unwrap();
+
+        let mut project =
+            Project::create_with_sample_rate(&temp.path().join("project"), Some(&input), 96000)
+                .unwrap();
+        assert_eq!(project.layer0.sample_rate, 96000);
+        assert!((project.layer0.duration_seconds - 0.5).abs() < 1e-3);
+
+        let layer1 = project.load_layer1().unwrap();
+        assert_eq!(layer1.sample_rate, 96000);
+        assert!((layer1.len() as i64 - 48000).abs() < 10);
+
+        project.layer2.chain = vec![gain("gain-1", -6.0)];
+        project.save().unwrap();
+        let reloaded = Project::load(&project.project_path).unwrap();
+        assert_eq!(reloaded.render_output().unwrap().sample_rate, 96000);
+
+        assert!(matches!(
+            Project::create_with_sample_rate(&temp.path().join("bad"), None, 22050),
+            Err(NuevaError::InvalidAudioFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compare_to_reference() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        assert!(project.compare_to_reference().is_err());
+
+        // A longer reference at another rate, as loud as the unprocessed tone
+        let reference = temp.path().join("reference.wav");
+        export_audio(
+            &generate_test_tone(440.0, 1.0, 44100),
+            &reference,
+            ExportFormat::new(44100, 24),
+        )
+        .unwrap();
+
+        let layer0_hash = project.layer0.hash_sha256.clone();
+        let layer1_before = project.load_layer1().unwrap();
+        project.load_reference(&reference).unwrap();
+        assert_eq!(project.layer0.hash_sha256, layer0_hash);
+        assert_eq!(
+            project.load_layer1().unwrap().samples,
+            layer1_before.samples
+        );
+        assert_eq!(project.reference.as_ref().unwrap().sample_rate, 48000);
+
+        // Two -6 dB gains leave the mix 12 dB down
+        let report = project.compare_to_reference().unwrap();
+        assert!(
+            (report.loudness_difference_lu + 12.0).abs() < 0.2,
+            "{}",
+            report
+        );
+        assert!((report.compared_secs - 0.5).abs() < 1e-3);
+        assert!(report.length_difference_secs < -0.49);
+        assert!(report.correlation > 0.99);
+        assert!(report.to_string().contains("quieter than the reference"));
+
+        // The reference is session-only
+        let json = serde_json::to_value(&project).unwrap();
+        assert!(json.get("reference").is_none());
+    }
+
+    #[test]
+    fn test_bake_through_unknown_effect_errors() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let layer1_before = project.layer1.path.clone();
+
+        let result = project.bake_through("reverb-9");
+
+        assert!(matches!(result, Err(NuevaError::EffectNotFound { .. })));
+        assert_eq!(project.layer2.chain.len(), 2);
+        assert_eq!(project.layer1.path, layer1_before);
+    }
+
+    #[test]
+    fn test_bake_through_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let layer1_before = project.layer1.path.clone();
+        let state_before = serde_json::to_value(&project).unwrap();
+
+        project.bake_through("gain-1").unwrap();
+        let mut undo_manager = UndoManager::new(10);
+        undo_manager.push(UndoAction::new(
+            ActionType::Bake,
+            "Bake effects through gain-1 into Layer 1",
+            state_before,
+            serde_json::to_value(&project).unwrap(),
+        ));
+        undo_manager.undo(&mut project).unwrap();
+
+        assert_eq!(project.layer1.path, layer1_before);
+        assert!(project.project_path.join(&layer1_before).exists());
+        let ids: Vec<&str> = project.layer2.chain.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["gain-1", "gain-2"]);
+    }
+
+    #[test]
+    fn test_missing_baked_layer1_reports_corruption() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let baked = project.bake_through("gain-1").unwrap();
+        let path = project.project_path.join(&baked);
+        Layer1StorageManager::new(&project.project_path)
+            .record_new_layer1(&path, "bake-action")
+            .unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            project.load_layer1(),
+            Err(NuevaError::StorageCorrupted { .. })
+        ));
+    }
+
+    fn chain_ids(project: &Project) -> Vec<&str> {
+        project.layer2.chain.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    fn effect(id: &str, effect_type: &str) -> Effect {
+        Effect {
+            effect_type: effect_type.to_string(),
+            params: HashMap::new(),
+            ..gain(id, 0.0)
+        }
+    }
+
+    #[test]
+    fn test_move_effect_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project.layer2.chain.push(gain("gain-3", 0.0));
+        let mut undo_manager = UndoManager::new(10);
+
+        project.move_effect(&mut undo_manager, "gain-3", 0).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-3", "gain-1", "gain-2"]);
+        project.move_effect(&mut undo_manager, "gain-1", 2).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-3", "gain-2", "gain-1"]);
+
+        assert_eq!(undo_manager.undo_count(), 2);
+        assert_eq!(
+            undo_manager.peek_undo().unwrap().action_type,
+            ActionType::DspChange
+        );
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-3", "gain-1", "gain-2"]);
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-1", "gain-2", "gain-3"]);
+    }
+
+    #[test]
+    fn test_add_effect_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let mut undo_manager = UndoManager::new(10);
+
+        let id = project.layer2.generate_id("gain");
+        assert_eq!(id, "gain-3");
+        project
+            .add_effect(&mut undo_manager, gain(&id, 3.0))
+            .unwrap();
+        assert_eq!(project.layer2.position("gain-3").unwrap(), 2);
+        assert_eq!(undo_manager.peek_undo().unwrap().description, "Add gain-3");
+
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-1", "gain-2"]);
+    }
+
+    #[test]
+    fn test_remove_effect_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let mut undo_manager = UndoManager::new(10);
+
+        project.remove_effect(&mut undo_manager, "gain-1").unwrap();
+        assert_eq!(chain_ids(&project), ["gain-2"]);
+        assert!(matches!(
+            project.remove_effect(&mut undo_manager, "gain-1"),
+            Err(NuevaError::EffectNotFound { .. })
+        ));
+        assert_eq!(undo_manager.undo_count(), 1);
+
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-1", "gain-2"]);
+    }
+
+    #[test]
+    fn test_grouped_plan_undoes_in_one_step() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let mut undo_manager = UndoManager::new(10);
+
+        undo_manager.begin_group("Agent: \"make it roomy\"");
+        for (id, effect_type) in [("delay-1", "delay"), ("reverb-1", "reverb")] {
+            project
+                .add_effect_ordered(&mut undo_manager, effect(id, effect_type))
+                .unwrap();
+        }
+        // A later step fails; what was applied stays grouped
+        assert!(project.remove_effect(&mut undo_manager, "eq-1").is_err());
+        let grouped = undo_manager.end_group().unwrap();
+
+        assert_eq!(grouped.description, "Agent: \"make it roomy\"");
+        assert_eq!(grouped.steps.len(), 2);
+        assert_eq!(undo_manager.get_history().len(), 1);
+        assert_eq!(
+            chain_ids(&project),
+            ["gain-1", "gain-2", "delay-1", "reverb-1"]
+        );
+
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), ["gain-1", "gain-2"]);
+        assert!(!undo_manager.can_undo());
+
+        undo_manager.redo(&mut project).unwrap();
+        assert_eq!(
+            chain_ids(&project),
+            ["gain-1", "gain-2", "delay-1", "reverb-1"]
+        );
+    }
+
+    #[test]
+    fn test_add_effect_ordered_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project.layer2.chain = vec![effect("reverb-1", "reverb")];
+        let mut undo_manager = UndoManager::new(10);
+
+        for (id, effect_type) in [
+            ("limiter-1", "limiter"),
+            ("gate-1", "gate"),
+            ("delay-1", "delay"),
+            ("delay-2", "delay"),
+        ] {
+            project
+                .add_effect_ordered(&mut undo_manager, effect(id, effect_type))
+                .unwrap();
+        }
+        assert_eq!(
+            chain_ids(&project),
+            ["gate-1", "delay-1", "delay-2", "reverb-1", "limiter-1"]
+        );
+
+        assert_eq!(undo_manager.undo_count(), 4);
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(
+            chain_ids(&project),
+            ["gate-1", "delay-1", "reverb-1", "limiter-1"]
+        );
+    }
+
+    #[test]
+    fn test_move_effect_invalid_records_nothing() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let mut undo_manager = UndoManager::new(10);
+
+        assert!(matches!(
+            project.move_effect(&mut undo_manager, "gain-1", 2),
+            Err(NuevaError::InvalidChainIndex { index: 2, len: 2 })
+        ));
+        assert!(matches!(
+            project.move_effect(&mut undo_manager, "reverb-9", 0),
+            Err(NuevaError::EffectNotFound { .. })
+        ));
+        assert_eq!(chain_ids(&project), ["gain-1", "gain-2"]);
+        assert_eq!(undo_manager.undo_count(), 0);
+    }
+
+    #[test]
+    fn test_set_effect_param_drag_is_one_undo() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        let mut undo_manager = UndoManager::new(10);
+
+        for step in 1..=12 {
+            project
+                .set_effect_param(&mut undo_manager, "gain-1", "gain_db", -6.0 + step as f64)
+                .unwrap();
+        }
+        assert!(!project
+            .set_effect_param(&mut undo_manager, "gain-1", "gain_db", 6.0)
+            .unwrap());
+        project
+            .set_effect_param(&mut undo_manager, "gain-2", "gain_db", 0.0)
+            .unwrap();
+
+        assert_eq!(undo_manager.undo_count(), 2);
+        undo_manager.undo(&mut project).unwrap();
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(project.layer2.chain[0].params["gain_db"], -6.0);
+        assert_eq!(project.layer2.chain[1].params["gain_db"], -6.0);
+    }
+
+    #[test]
+    fn test_reorder_chain_by_default_priority() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project.layer2.chain = vec![
+            effect("limiter-1", "limiter"),
+            effect("reverb-1", "reverb"),
+            effect("saturation-1", "saturation"),
+            effect("compressor-1", "compressor"),
+            effect("delay-1", "delay"),
+            effect("eq-1", "parametric_eq"),
+            effect("gate-1", "gate"),
+        ];
+        let mut undo_manager = UndoManager::new(10);
+
+        assert!(project
+            .reorder_chain_by_default_priority(&mut undo_manager)
+            .unwrap());
+        assert_eq!(
+            chain_ids(&project),
+            [
+                "gate-1",
+                "eq-1",
+                "compressor-1",
+                "saturation-1",
+                "delay-1",
+                "reverb-1",
+                "limiter-1"
+            ]
+        );
+        assert_eq!(project.layer2.effects_of_type("reverb")[0].id, "reverb-1");
+
+        // Already ordered: no action recorded
+        assert!(!project
+            .reorder_chain_by_default_priority(&mut undo_manager)
+            .unwrap());
+        assert_eq!(undo_manager.undo_count(), 1);
+
+        undo_manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project)[0], "limiter-1");
+    }
+
+    #[test]
+    fn test_load_uncompressed_1_0_0_project() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project.save().unwrap();
+
+        // Rewrite project.json the way 1.0.0 stored it
+        let file = Project::project_file_path(&project.project_path);
+        let mut data: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        data["schema_version"] = serde_json::json!("1.0.0");
+        data["layer1"]
+            .as_object_mut()
+            .unwrap()
+            .remove("compression");
+        fs::write(&file, data.to_string()).unwrap();
+
+        let loaded = Project::load(&project.project_path).unwrap();
+        assert_eq!(loaded.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(loaded.layer1.compression, StorageCompression::None);
+        assert!(!loaded.load_layer1().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_intentional_artifacts_persist_with_project() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project
+            .layer1
+            .neural_context
+            .add_artifact(IntentionalArtifact::Saturation);
+        project.save().unwrap();
+
+        let mut loaded = Project::load(&project.project_path).unwrap();
+        assert!(loaded
+            .layer1
+            .neural_context
+            .has_artifact(&IntentionalArtifact::Saturation));
+
+        loaded
+            .layer1
+            .neural_context
+            .remove_artifact(&IntentionalArtifact::Saturation);
+        loaded.save().unwrap();
+        let reloaded = Project::load(&project.project_path).unwrap();
+        assert!(reloaded
+            .layer1
+            .neural_context
+            .intentional_artifacts
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bake_clears_intentional_artifacts() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project
+            .layer1
+            .neural_context
+            .add_artifact(IntentionalArtifact::Saturation);
+
+        project.bake().unwrap();
+        assert!(!project
+            .layer1
+            .neural_context
+            .has_artifact(&IntentionalArtifact::Saturation));
+        let loaded = Project::load(&project.project_path).unwrap();
+        assert!(loaded
+            .layer1
+            .neural_context
+            .intentional_artifacts
+            .is_empty());
+    }
+
+    /// project.json as written by schema 1.1.0, before effect parameters
+    /// were stored explicitly
+    const PROJECT_1_1_0: &str = r#"{
+        "schema_version": "1.1.0",
+        "created_at": "2024-01-15T10:00:00Z",
+        "modified_at": "2024-01-15T12:00:00Z",
+        "nueva_version": "0.1.0",
+        "source": {
+            "original_filename": "vocals.wav",
+            "original_path": "/home/user/vocals.wav"
+        },
+        "layer0": {
+            "path": "audio/layer0_source.wav",
+            "sample_rate": 48000,
+            "bit_depth": 32,
+            "channels": 2,
+            "duration_seconds": 1.0,
+            "hash_sha256": ""
+        },
+        "layer1": {
+            "path": "audio/layer1_ai.wav",
+            "is_processed": false,
+            "identical_to_layer0": true,
+            "compression": "none"
+        },
+        "layer2": {
+            "chain": [
+                {
+                    "id": "comp-1",
+                    "type": "compressor",
+                    "params": {"threshold_db": -20.0},
+                    "added_at": "2024-01-15T11:00:00Z"
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_load_1_1_0_project_fills_effect_defaults() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join(PROJECT_FILE), PROJECT_1_1_0).unwrap();
+
+        let mut project = Project::load(temp.path()).unwrap();
+        assert_eq!(project.schema_version, CURRENT_SCHEMA_VERSION);
+
+        let comp = &project.layer2.chain[0];
+        assert!(comp.enabled);
+        assert_eq!(comp.added_by, "user");
+        assert_eq!(comp.params["threshold_db"], serde_json::json!(-20.0));
+        assert_eq!(comp.params["ratio"], serde_json::json!(4.0));
+
+        project.save().unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(temp.path().join(PROJECT_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(
+            saved["schema_version"],
+            serde_json::json!(CURRENT_SCHEMA_VERSION)
+        );
+        project.release_lock().unwrap();
+    }
+
+    #[test]
+    fn test_load_future_project_errors() {
+        let temp = TempDir::new().unwrap();
+        let data = PROJECT_1_1_0.replace("\"1.1.0\"", "\"7.0.0\"");
+        fs::write(temp.path().join(PROJECT_FILE), data).unwrap();
+
+        assert!(matches!(
+            Project::load(temp.path()),
+            Err(NuevaError::UnsupportedSchemaVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_output_applies_changes_at_markers() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project.add_marker("drop", 12000).unwrap();
+        project
+            .schedule_at_marker("Drop", "gain-1", "gain_db", serde_json::json!(0.0))
+            .unwrap();
+        assert!(project
+            .schedule_at_marker("drop", "gain-1", "missing", serde_json::json!(0.0))
+            .is_err());
+        assert!(project
+            .schedule_at_marker("chorus", "gain-1", "gain_db", serde_json::json!(0.0))
+            .is_err());
+        project.save().unwrap();
+
+        let mut project = Project::load(&project.project_path).unwrap();
+        assert_eq!(project.markers.len(), 1);
+        assert_eq!(project.marker_schedule.changes().len(), 1);
+
+        let peak = |audio: &crate::engine::AudioBuffer, range: std::ops::Range<usize>| {
+            audio.samples[0][range]
+                .iter()
+                .fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+        let source = project.load_layer1().unwrap();
+        let output = project.render_output().unwrap();
+        let before = peak(&output, 1000..11000) / peak(&source, 1000..11000);
+        let after = peak(&output, 13000..23000) / peak(&source, 13000..23000);
+        // -12 dB until the marker, -6 dB after it
+        assert!((20.0 * before.log10() + 12.0).abs() < 0.1);
+        assert!((20.0 * after.log10() + 6.0).abs() < 0.1);
+
+        // Removing the marker removes what was scheduled at it
+        project.remove_marker("DROP").unwrap();
+        assert!(project.markers.is_empty());
+        assert!(project.marker_schedule.is_empty());
+    }
+
+    #[test]
+    fn test_import_keeps_wav_metadata() {
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("input.wav");
+        let metadata = WavMetadata {
+            markers: vec![Marker {
+                name: "Drop".to_string(),
+                sample: 22050,
+            }],
+            loop_region: None,
+            bpm: Some(128.0),
+        };
+        export_audio_with_metadata(
+            &generate_test_tone(440.0, 1.0, 44100),
+            &input,
+            ExportFormat::new(44100, 24),
+            &metadata,
+        )
+        .unwrap();
+
+        let mut project = Project::create(&temp.path().join("project"), Some(&input)).unwrap();
+        let drop = |project: &Project| project.markers[0].sample as i64;
+        assert_eq!(project.markers[0].name, "Drop");
+        assert!((drop(&project) - 24000).abs() <= 1);
+
+        let imported = project.import_audio(&input).unwrap();
+        assert_eq!(imported.buffer.sample_rate, 48000);
+        assert_eq!(imported.metadata.bpm, Some(128.0));
+
+        // Markers come from the project, the tempo from the source, and
+        // both survive a bake
+        project.add_marker("Outro", 40000).unwrap();
+        project.bake().unwrap();
+        let written = project.wav_metadata().unwrap();
+        assert_eq!(written.markers, project.markers);
+        assert_eq!(written.bpm, Some(128.0));
+    }
+
+    #[test]
+    fn test_trim_shifts_markers_and_shortens_layers() {
+        let temp = TempDir::new().unwrap();
+        let mut project = project_with_chain(&temp);
+        project.add_marker("intro", 3000).unwrap();
+        project.add_marker("outro", 20000).unwrap();
+        let old_layer0 = project.layer0.path.clone();
+
+        assert!(project.trim(6000, 30000).is_err());
+        assert!(project.trim(6000, 6000).is_err());
+        project.trim(6000, 18000).unwrap();
+
+        assert_ne!(project.layer0.path, old_layer0);
+        assert!(project.project_path.join(&old_layer0).exists());
+        assert!((project.layer0.duration_seconds - 0.25).abs() < 1e-6);
+        assert_eq!(project.load_layer1().unwrap().len(), 12000);
+        let positions: Vec<_> = project.markers.iter().map(|m| m.sample).collect();
+        assert_eq!(positions, vec![0, 12000]);
+        assert!(project.add_marker("late", 12001).is_err());
+    }
+}
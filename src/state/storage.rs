@@ -2,17 +2,60 @@
 //!
 //! Manages storage for Layer 1 audio files, including tracking file metadata,
 //! pruning orphaned files, and monitoring storage usage.
+//!
+//! Layer 1 files are content-addressed: a buffer is stored once as
+//! `layer1_<sha256>.<ext>`, where the hash covers the decoded samples, and
+//! every undo action that uses it adds a reference in the manifest. A file
+//! is deleted only when its last reference goes, so undo branches and A/B
+//! states holding the same audio share one file.
+//!
+//! Layer 1 buffers can be stored zstd-compressed. A compressed file is a
+//! zstd frame holding the buffer's binary form (see
+//! [`AudioBuffer::to_bytes`]). Loading detects the format from the magic
+//! bytes, so WAV files written by older versions keep working.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::engine::buffer::INTERNAL_SAMPLE_RATE;
+use crate::engine::io::{export_audio, import_audio_at, ExportFormat};
+use crate::engine::AudioBuffer;
 use crate::state::error::{NuevaError, Result};
 use crate::state::project::Project;
 
+/// Magic bytes at the start of a compressed Layer 1 file (a zstd frame).
+const COMPRESSED_MAGIC: &[u8; 4] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// zstd compression level for Layer 1 audio.
+const ZSTD_LEVEL: i32 = 3;
+
+/// How Layer 1 audio is stored on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCompression {
+    /// Plain 32-bit float WAV.
+    None,
+    /// zstd-compressed raw f32 samples (bit-exact).
+    #[default]
+    Zstd,
+}
+
+impl StorageCompression {
+    /// File extension used for this storage format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StorageCompression::None => "wav",
+            StorageCompression::Zstd => "nvz",
+        }
+    }
+}
+
 /// Storage usage statistics.
 #[derive(Debug, Clone)]
 pub struct StorageUsage {
@@ -24,6 +67,9 @@ pub struct StorageUsage {
     pub total_size_mb: f64,
 }
 
+/// Prefix of content-addressed Layer 1 file names.
+const BLOB_PREFIX: &str = "layer1_";
+
 /// Metadata for a single Layer 1 file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Layer1FileInfo {
@@ -33,6 +79,10 @@ pub struct Layer1FileInfo {
     pub undo_action_id: String,
     /// File size in bytes.
     pub size_bytes: u64,
+    /// Undo actions referencing this file, including the one that created
+    /// it. Manifests written before dedup load with just the creator.
+    #[serde(default)]
+    pub references: BTreeSet<String>,
 }
 
 /// Manifest tracking all Layer 1 files.
@@ -48,6 +98,8 @@ pub struct Layer1StorageManager {
     project_path: PathBuf,
     /// Path to the Layer 1 audio directory.
     audio_dir: PathBuf,
+    /// Format used when writing Layer 1 buffers.
+    compression: StorageCompression,
 }
 
 impl Layer1StorageManager {
@@ -57,9 +109,21 @@ impl Layer1StorageManager {
         Self {
             project_path: project_path.to_path_buf(),
             audio_dir,
+            compression: StorageCompression::default(),
         }
     }
 
+    /// Set the format used when writing Layer 1 buffers.
+    pub fn with_compression(mut self, compression: StorageCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Get the format used when writing Layer 1 buffers.
+    pub fn compression(&self) -> StorageCompression {
+        self.compression
+    }
+
     /// Get the project path.
     pub fn project_path(&self) -> &Path {
         &self.project_path
@@ -89,7 +153,12 @@ impl Layer1StorageManager {
                 source: e,
             })?;
 
-        let manifest: Layer1Manifest = serde_json::from_str(&content)?;
+        let mut manifest: Layer1Manifest = serde_json::from_str(&content)?;
+        for info in manifest.files.values_mut() {
+            if info.references.is_empty() {
+                info.references.insert(info.undo_action_id.clone());
+            }
+        }
         Ok(manifest)
     }
 
@@ -114,7 +183,10 @@ impl Layer1StorageManager {
         Ok(())
     }
 
-    /// Record a new Layer 1 file in the manifest.
+    /// Record that an undo action references a Layer 1 file.
+    ///
+    /// The first reference adds the file to the manifest; later ones only
+    /// add the action to its references.
     pub fn record_new_layer1(&self, audio_path: &Path, undo_action_id: &str) -> Result<()> {
         let mut manifest = self.load_manifest()?;
 
@@ -127,6 +199,11 @@ impl Layer1StorageManager {
             .to_string_lossy()
             .to_string();
 
+        if let Some(info) = manifest.files.get_mut(&filename) {
+            info.references.insert(undo_action_id.to_string());
+            return self.save_manifest(&manifest);
+        }
+
         // Get file size
         let metadata = fs::metadata(audio_path).map_err(|e| NuevaError::FileReadError {
             path: audio_path.to_path_buf(),
@@ -137,6 +214,7 @@ impl Layer1StorageManager {
             created_at: Utc::now(),
             undo_action_id: undo_action_id.to_string(),
             size_bytes: metadata.len(),
+            references: BTreeSet::from([undo_action_id.to_string()]),
         };
 
         manifest.files.insert(filename, file_info);
@@ -145,45 +223,240 @@ impl Layer1StorageManager {
         Ok(())
     }
 
-    /// Prune orphaned Layer 1 files that are not in the reachable action IDs set.
+    /// Add references from undo actions to Layer 1 files they use.
+    ///
+    /// Each entry pairs a file name with an action whose state points at
+    /// it; files not in the manifest are ignored. Lets a later action keep
+    /// a file alive once the action that created it is released.
+    pub fn add_references<'a>(
+        &self,
+        uses: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<()> {
+        let mut manifest = self.load_manifest()?;
+        let mut changed = false;
+        for (filename, undo_action_id) in uses {
+            if let Some(info) = manifest.files.get_mut(filename) {
+                changed |= info.references.insert(undo_action_id.to_string());
+            }
+        }
+        if changed {
+            self.save_manifest(&manifest)?;
+        }
+        Ok(())
+    }
+
+    /// Write a Layer 1 buffer as `<stem>.<ext>` in the audio directory.
+    ///
+    /// Returns the path of the written file. The file is not recorded in the
+    /// manifest; call [`record_new_layer1`](Self::record_new_layer1) once the
+    /// owning undo action exists.
+    pub fn write_layer1(&self, stem: &str, buffer: &AudioBuffer) -> Result<PathBuf> {
+        if !self.audio_dir.exists() {
+            fs::create_dir_all(&self.audio_dir).map_err(|e| NuevaError::DirectoryCreateError {
+                path: self.audio_dir.clone(),
+                source: e,
+            })?;
+        }
+
+        let path = self
+            .audio_dir
+            .join(format!("{}.{}", stem, self.compression.extension()));
+
+        match self.compression {
+            StorageCompression::None => {
+                export_audio(buffer, &path, ExportFormat::new(buffer.sample_rate, 32)).map_err(
+                    |e| NuevaError::InvalidAudioFormat {
+                        reason: e.to_string(),
+                    },
+                )?
+            }
+            StorageCompression::Zstd => {
+                let content = compress_buffer(buffer)?;
+                fs::write(&path, content).map_err(|e| NuevaError::FileWriteError {
+                    path: path.clone(),
+                    source: e,
+                })?;
+            }
+        }
+
+        Ok(path)
+    }
+
+    /// Write a Layer 1 buffer content-addressed, unless identical audio is
+    /// already stored.
+    ///
+    /// Returns the path of the file holding the buffer, new or existing.
+    /// Like [`write_layer1`](Self::write_layer1), this adds no reference;
+    /// call [`record_new_layer1`](Self::record_new_layer1) once the owning
+    /// undo action exists.
+    ///
+    /// # Errors
+    /// `StorageCorrupted` if the manifest lists the blob but its file is
+    /// gone.
+    pub fn write_layer1_blob(&self, buffer: &AudioBuffer) -> Result<PathBuf> {
+        let stem = format!("{}{}", BLOB_PREFIX, content_hash(buffer));
+        let manifest = self.load_manifest()?;
+
+        // Identical audio may have been stored with another compression
+        for compression in [StorageCompression::Zstd, StorageCompression::None] {
+            let filename = format!("{}.{}", stem, compression.extension());
+            let path = self.audio_dir.join(&filename);
+            if manifest.files.contains_key(&filename) {
+                self.verify_blob(&path)?;
+                return Ok(path);
+            }
+            if path.exists() {
+                return Ok(path);
+            }
+        }
+
+        self.write_layer1(&stem, buffer)
+    }
+
+    /// Store a Layer 1 buffer content-addressed and reference it from an
+    /// undo action.
+    ///
+    /// Storing the same buffer again, from any action, keeps one file.
+    pub fn store_layer1(&self, buffer: &AudioBuffer, undo_action_id: &str) -> Result<PathBuf> {
+        let path = self.write_layer1_blob(buffer)?;
+        self.record_new_layer1(&path, undo_action_id)?;
+        Ok(path)
+    }
+
+    /// Check that a Layer 1 file is present.
+    ///
+    /// # Errors
+    /// `StorageCorrupted` if the file is missing while the manifest still
+    /// has references to it, `AudioNotFound` if it was never stored.
+    pub fn verify_blob(&self, path: &Path) -> Result<()> {
+        if path.exists() {
+            return Ok(());
+        }
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match self.load_manifest()?.files.get(&filename) {
+            Some(info) => Err(NuevaError::StorageCorrupted {
+                reason: format!(
+                    "{} is missing but referenced by {}",
+                    filename,
+                    info.references
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }),
+            None => Err(NuevaError::AudioNotFound {
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+
+    /// Check every referenced Layer 1 file is present.
+    ///
+    /// # Errors
+    /// `StorageCorrupted` naming the first missing file.
+    pub fn verify_blobs(&self) -> Result<()> {
+        let manifest = self.load_manifest()?;
+        let mut filenames: Vec<&String> = manifest.files.keys().collect();
+        filenames.sort();
+        for filename in filenames {
+            self.verify_blob(&self.audio_dir.join(filename))?;
+        }
+        Ok(())
+    }
+
+    /// Drop an undo action's references, deleting files left with none.
+    ///
+    /// Returns the total bytes freed.
+    pub fn release_layer1(&self, undo_action_id: &str) -> Result<u64> {
+        self.retain_references(|_, id| id != undo_action_id)
+    }
+
+    /// Drop several undo actions' references, deleting files left with
+    /// none except `in_use`, which keeps its references.
     ///
     /// Returns the total bytes freed.
+    pub fn release_layer1_except(&self, undo_action_ids: &[String], in_use: &Path) -> Result<u64> {
+        let in_use = in_use.file_name().and_then(|name| name.to_str());
+        self.retain_references(|filename, id| {
+            Some(filename) == in_use || !undo_action_ids.iter().any(|dropped| dropped == id)
+        })
+    }
+
+    /// Load a Layer 1 buffer, decompressing it if needed.
+    ///
+    /// Uncompressed files are read as WAV.
+    pub fn load_layer1(path: &Path) -> Result<AudioBuffer> {
+        Self::load_layer1_at(path, INTERNAL_SAMPLE_RATE)
+    }
+
+    /// Load a Layer 1 buffer, reading WAV files at `sample_rate`.
+    ///
+    /// Compressed buffers keep the rate they were written at.
+    pub fn load_layer1_at(path: &Path, sample_rate: u32) -> Result<AudioBuffer> {
+        let mut magic = [0u8; 4];
+        let is_compressed = fs::File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok()
+            && &magic == COMPRESSED_MAGIC;
+
+        if !is_compressed {
+            return import_audio_at(path, sample_rate).map_err(|e| {
+                NuevaError::InvalidAudioFormat {
+                    reason: e.to_string(),
+                }
+            });
+        }
+
+        let content = fs::read(path).map_err(|e| NuevaError::FileReadError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        decompress_buffer(&content)
+    }
+
+    /// Prune orphaned Layer 1 files that are not in the reachable action IDs set.
+    ///
+    /// References from unreachable actions are dropped; a file is deleted
+    /// once no reachable action references it. Returns the total bytes
+    /// freed.
     pub fn prune_orphaned_files(&self, reachable_action_ids: &HashSet<String>) -> Result<u64> {
+        self.retain_references(|_, id| reachable_action_ids.contains(id))
+    }
+
+    /// Keep only the references `keep` accepts, deleting unreferenced files.
+    fn retain_references(&self, keep: impl Fn(&str, &str) -> bool) -> Result<u64> {
         let mut manifest = self.load_manifest()?;
         let mut bytes_freed: u64 = 0;
-        let mut files_to_remove: Vec<String> = Vec::new();
 
-        // Find orphaned files
-        for (filename, file_info) in &manifest.files {
-            if !reachable_action_ids.contains(&file_info.undo_action_id) {
-                files_to_remove.push(filename.clone());
-            }
+        for (filename, info) in manifest.files.iter_mut() {
+            info.references.retain(|id| keep(filename, id));
         }
-
-        // Delete orphaned files
-        for filename in &files_to_remove {
+        let orphaned: Vec<String> = manifest
+            .files
+            .iter()
+            .filter(|(_, info)| info.references.is_empty())
+            .map(|(filename, _)| filename.clone())
+            .collect();
+
+        for filename in &orphaned {
             let file_path = self.audio_dir.join(filename);
+            let info = manifest.files.remove(filename);
 
             if file_path.exists() {
-                // Get file size before deleting
-                if let Ok(metadata) = fs::metadata(&file_path) {
-                    bytes_freed += metadata.len();
-                }
+                // Prefer the size on disk over the manifest's record
+                bytes_freed += fs::metadata(&file_path)
+                    .map(|metadata| metadata.len())
+                    .unwrap_or_else(|_| info.map_or(0, |info| info.size_bytes));
 
-                // Delete the file
                 fs::remove_file(&file_path).map_err(|e| NuevaError::FileWriteError {
                     path: file_path,
                     source: e,
                 })?;
             }
-
-            // Remove from manifest
-            if let Some(file_info) = manifest.files.remove(filename) {
-                // If we couldn't get the size from disk, use manifest value
-                if bytes_freed == 0 {
-                    bytes_freed += file_info.size_bytes;
-                }
-            }
         }
 
         // Save updated manifest
@@ -208,6 +481,32 @@ impl Layer1StorageManager {
     }
 }
 
+/// SHA-256 of a buffer's decoded content, independent of how it is stored.
+fn content_hash(buffer: &AudioBuffer) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(buffer.sample_rate.to_le_bytes());
+    hasher.update((buffer.num_channels() as u16).to_le_bytes());
+    hasher.update((buffer.len() as u64).to_le_bytes());
+    for channel in &buffer.samples {
+        let bytes: Vec<u8> = channel.iter().flat_map(|s| s.to_le_bytes()).collect();
+        hasher.update(&bytes);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Encode a buffer as its zstd-compressed binary form.
+fn compress_buffer(buffer: &AudioBuffer) -> Result<Vec<u8>> {
+    Ok(zstd::encode_all(buffer.to_bytes().as_slice(), ZSTD_LEVEL)?)
+}
+
+/// Decode a buffer written by [`compress_buffer`].
+fn decompress_buffer(content: &[u8]) -> Result<AudioBuffer> {
+    let bytes = zstd::decode_all(content)?;
+    AudioBuffer::from_bytes(&bytes).map_err(|e| NuevaError::InvalidAudioFormat {
+        reason: format!("Compressed Layer 1: {}", e),
+    })
+}
+
 /// Check storage health and return warnings.
 ///
 /// Checks:
@@ -241,6 +540,7 @@ pub fn check_storage_health(project: &Project) -> Result<Vec<String>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::io::generate_test_tone;
     use tempfile::TempDir;
 
     fn create_test_project_path() -> TempDir {
@@ -280,6 +580,7 @@ mod tests {
                 created_at: Utc::now(),
                 undo_action_id: "action-123".to_string(),
                 size_bytes: 1024,
+                references: BTreeSet::from(["action-123".to_string()]),
             },
         );
 
@@ -369,4 +670,213 @@ mod tests {
         assert_eq!(usage.total_size_bytes, 3072);
         assert!((usage.total_size_mb - 0.00293).abs() < 0.001);
     }
+
+    #[test]
+    fn test_zstd_round_trip_is_bit_exact() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let buffer = generate_test_tone(440.0, 0.25, 48000);
+
+        let path = manager.write_layer1("tone", &buffer).unwrap();
+        assert_eq!(path.extension().unwrap(), "nvz");
+
+        let loaded = Layer1StorageManager::load_layer1(&path).unwrap();
+        assert_eq!(loaded.sample_rate, buffer.sample_rate);
+        assert_eq!(loaded.samples, buffer.samples);
+    }
+
+    #[test]
+    fn test_compressible_buffer_stores_smaller_than_raw() {
+        let temp_dir = create_test_project_path();
+        let buffer = AudioBuffer {
+            samples: vec![vec![0.25; 48000]; 2],
+            sample_rate: 48000,
+        };
+
+        let raw = Layer1StorageManager::new(temp_dir.path())
+            .with_compression(StorageCompression::None)
+            .write_layer1("raw", &buffer)
+            .unwrap();
+        let compressed = Layer1StorageManager::new(temp_dir.path())
+            .write_layer1("compressed", &buffer)
+            .unwrap();
+
+        let raw_size = fs::metadata(raw).unwrap().len();
+        let compressed_size = fs::metadata(compressed).unwrap().len();
+        assert!(raw_size >= 2 * 48000 * 4);
+        assert!(compressed_size * 10 < raw_size);
+    }
+
+    #[test]
+    fn test_uncompressed_layer1_still_loads() {
+        let temp_dir = create_test_project_path();
+        let manager =
+            Layer1StorageManager::new(temp_dir.path()).with_compression(StorageCompression::None);
+        let buffer = generate_test_tone(440.0, 0.25, 48000);
+
+        let path = manager.write_layer1("legacy", &buffer).unwrap();
+        assert_eq!(path.extension().unwrap(), "wav");
+
+        let loaded = Layer1StorageManager::load_layer1(&path).unwrap();
+        assert_eq!(loaded.samples, buffer.samples);
+    }
+
+    #[test]
+    fn test_identical_buffers_share_one_blob() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let buffer = generate_test_tone(440.0, 0.25, 48000);
+
+        let first = manager.store_layer1(&buffer, "action-a").unwrap();
+        let second = manager.store_layer1(&buffer.clone(), "action-b").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(
+            Layer1StorageManager::load_layer1(&first).unwrap().samples,
+            buffer.samples
+        );
+
+        let manifest = manager.load_manifest().unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        let info = manifest.files.values().next().unwrap();
+        assert_eq!(info.undo_action_id, "action-a");
+        assert_eq!(
+            info.references,
+            BTreeSet::from(["action-a".to_string(), "action-b".to_string()])
+        );
+
+        // Identical audio is found whatever compression stored it
+        let uncompressed =
+            Layer1StorageManager::new(temp_dir.path()).with_compression(StorageCompression::None);
+        assert_eq!(uncompressed.write_layer1_blob(&buffer).unwrap(), first);
+
+        let other = manager
+            .store_layer1(&generate_test_tone(880.0, 0.25, 48000), "action-c")
+            .unwrap();
+        assert_ne!(other, first);
+        assert_eq!(manager.get_storage_usage().unwrap().file_count, 2);
+    }
+
+    #[test]
+    fn test_blob_deleted_with_last_reference() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let shared = generate_test_tone(440.0, 0.25, 48000);
+        let path = manager.store_layer1(&shared, "branch-a").unwrap();
+        manager.store_layer1(&shared, "branch-b").unwrap();
+        let only_c = manager
+            .store_layer1(&generate_test_tone(220.0, 0.25, 48000), "branch-c")
+            .unwrap();
+
+        // Deleting one branch leaves the blob the other still uses
+        let reachable = HashSet::from(["branch-b".to_string(), "branch-c".to_string()]);
+        assert_eq!(manager.prune_orphaned_files(&reachable).unwrap(), 0);
+        assert!(path.exists());
+
+        let size = fs::metadata(&path).unwrap().len();
+        assert_eq!(manager.release_layer1("branch-b").unwrap(), size);
+        assert!(!path.exists());
+        assert!(only_c.exists());
+
+        let manifest = manager.load_manifest().unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert!(manifest
+            .files
+            .values()
+            .all(|info| info.references.contains("branch-c")));
+    }
+
+    #[test]
+    fn test_legacy_manifest_entry_referenced_by_creator() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        fs::create_dir_all(&manager.audio_dir).unwrap();
+        fs::write(
+            manager.manifest_path(),
+            r#"{"files": {"old.wav": {"created_at": "2024-01-01T00:00:00Z",
+                "undo_action_id": "action-old", "size_bytes": 10}}}"#,
+        )
+        .unwrap();
+
+        let manifest = manager.load_manifest().unwrap();
+        assert_eq!(
+            manifest.files["old.wav"].references,
+            BTreeSet::from(["action-old".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_missing_referenced_blob_is_corruption() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let buffer = generate_test_tone(440.0, 0.25, 48000);
+        let path = manager.store_layer1(&buffer, "action-a").unwrap();
+        manager.verify_blobs().unwrap();
+
+        fs::remove_file(&path).unwrap();
+        for result in [
+            manager.verify_blobs(),
+            manager.verify_blob(&path),
+            manager.write_layer1_blob(&buffer).map(|_| ()),
+        ] {
+            match result {
+                Err(NuevaError::StorageCorrupted { reason }) => {
+                    assert!(reason.contains("referenced by action-a"), "{}", reason);
+                }
+                other => panic!("expected corruption, got {:?}", other),
+            }
+        }
+
+        // A file that was never stored is just missing
+        assert!(matches!(
+            manager.verify_blob(&manager.audio_dir.join("unknown.nvz")),
+            Err(NuevaError::AudioNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_truncated_compressed_file_errors() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let path = manager
+            .write_layer1("tone", &generate_test_tone(440.0, 0.25, 48000))
+            .unwrap();
+
+        let content = fs::read(&path).unwrap();
+        fs::write(&path, &content[..content.len() / 2]).unwrap();
+
+        assert!(Layer1StorageManager::load_layer1(&path).is_err());
+    }
+
+    #[test]
+    fn test_compressed_layer1_holds_buffer_bytes() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let buffer = generate_test_tone(440.0, 0.25, 48000);
+        let path = manager.write_layer1("tone", &buffer).unwrap();
+
+        let content = zstd::decode_all(fs::read(&path).unwrap().as_slice()).unwrap();
+        assert_eq!(content, buffer.to_bytes());
+    }
+
+    #[test]
+    fn test_corrupt_compressed_header_errors() {
+        let temp_dir = create_test_project_path();
+        let manager = Layer1StorageManager::new(temp_dir.path());
+        let buffer = generate_test_tone(440.0, 0.25, 48000);
+        let path = manager.write_layer1("tone", &buffer).unwrap();
+
+        // A frame count no buffer could hold
+        let mut bytes = buffer.to_bytes();
+        bytes[12..20].copy_from_slice(&u64::MAX.to_le_bytes());
+        fs::write(
+            &path,
+            zstd::encode_all(bytes.as_slice(), ZSTD_LEVEL).unwrap(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            Layer1StorageManager::load_layer1(&path),
+            Err(NuevaError::InvalidAudioFormat { .. })
+        ));
+    }
 }
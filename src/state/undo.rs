@@ -3,9 +3,19 @@
 //! Provides action-based undo/redo with state snapshots per spec section 8.
 //! Each action stores complete state_before and state_after snapshots
 //! to enable reliable state restoration.
+//!
+//! Consecutive changes to the same effect parameter that arrive within
+//! the coalescing window (e.g. while dragging a control) are merged into
+//! one action, so a single undo returns to the value before the gesture.
+//!
+//! Changes made between [`UndoManager::begin_group`] and
+//! [`UndoManager::end_group`] (e.g. everything one agent prompt applied)
+//! are recorded as a single action labelled with the group's label.
 
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -13,14 +23,21 @@ use uuid::Uuid;
 
 use crate::state::error::{NuevaError, Result};
 use crate::state::project::Project;
+use crate::state::storage::Layer1StorageManager;
 
 /// Default maximum number of undo levels to keep.
 pub const DEFAULT_MAX_UNDO_LEVELS: usize = 50;
 
-/// File name for the undo stack persistence.
+/// Default window for merging consecutive changes to one parameter.
+pub const DEFAULT_COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// File name for the action tree persistence.
+const ACTION_TREE_FILE: &str = "action_tree.json";
+
+/// File name for the undo stack persistence (before branching history).
 const UNDO_STACK_FILE: &str = "undo_stack.json";
 
-/// File name for the redo stack persistence.
+/// File name for the redo stack persistence (before branching history).
 const REDO_STACK_FILE: &str = "redo_stack.json";
 
 /// File name for the action log persistence.
@@ -44,6 +61,12 @@ pub enum ActionType {
 
     /// Project reset to initial state.
     Reset,
+
+    /// Timeline markers or the changes scheduled at them edited.
+    Marker,
+
+    /// Audio trimmed to a range.
+    Trim,
 }
 
 impl std::fmt::Display for ActionType {
@@ -54,6 +77,8 @@ impl std::fmt::Display for ActionType {
             ActionType::Bake => write!(f, "Bake"),
             ActionType::Import => write!(f, "Import"),
             ActionType::Reset => write!(f, "Reset"),
+            ActionType::Marker => write!(f, "Marker"),
+            ActionType::Trim => write!(f, "Trim"),
         }
     }
 }
@@ -78,6 +103,24 @@ pub struct UndoAction {
 
     /// Complete project state after the action.
     pub state_after: serde_json::Value,
+
+    /// The effect parameter this action changed, if it changed just one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<ParameterChange>,
+
+    /// Descriptions of the changes grouped into this action, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<String>,
+}
+
+/// Identifies the effect parameter a [`UndoAction`] changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    /// ID of the effect in the Layer 2 chain.
+    pub effect_id: String,
+
+    /// Name of the parameter.
+    pub param: String,
 }
 
 impl UndoAction {
@@ -95,6 +138,8 @@ impl UndoAction {
             timestamp: Utc::now(),
             state_before,
             state_after,
+            parameter: None,
+            steps: Vec::new(),
         }
     }
 
@@ -113,24 +158,213 @@ impl UndoAction {
             timestamp: Utc::now(),
             state_before,
             state_after,
+            parameter: None,
+            steps: Vec::new(),
+        }
+    }
+}
+
+impl UndoAction {
+    /// Mark this action as a change to a single effect parameter, making
+    /// it eligible for coalescing.
+    pub fn with_parameter(
+        mut self,
+        effect_id: impl Into<String>,
+        param: impl Into<String>,
+    ) -> Self {
+        self.parameter = Some(ParameterChange {
+            effect_id: effect_id.into(),
+            param: param.into(),
+        });
+        self
+    }
+}
+
+/// A recorded action and the action it was performed on top of.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionNode {
+    /// The recorded action.
+    pub action: UndoAction,
+
+    /// ID of the parent action, or `None` if it was performed on the
+    /// initial state.
+    pub parent: Option<String>,
+}
+
+/// Every recorded action, arranged as a tree.
+///
+/// Undo moves from the current node towards the root and redo follows the
+/// path that was last undone. Recording an action after an undo starts a
+/// sibling branch instead of discarding the undone actions, so alternate
+/// takes can be revisited later.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActionTree {
+    /// Nodes in the order they were recorded.
+    nodes: Vec<ActionNode>,
+
+    /// ID of the most recently applied action (`None` = initial state).
+    current: Option<String>,
+
+    /// IDs of the actions redo will re-apply, next one last.
+    redo_path: Vec<String>,
+}
+
+impl ActionTree {
+    /// Build a linear tree from undo/redo stacks (history written before
+    /// the tree existed).
+    fn from_stacks(undo_stack: Vec<UndoAction>, redo_stack: Vec<UndoAction>) -> Self {
+        let mut tree = Self::default();
+        for action in undo_stack {
+            tree.insert(action);
+        }
+
+        let current = tree.current.clone();
+        let redo_path = redo_stack.iter().map(|a| a.id.clone()).collect();
+        for action in redo_stack.into_iter().rev() {
+            tree.insert(action);
+        }
+        tree.current = current;
+        tree.redo_path = redo_path;
+        tree
+    }
+
+    /// Add an action as a child of the current node and make it current.
+    fn insert(&mut self, action: UndoAction) {
+        let id = action.id.clone();
+        self.nodes.push(ActionNode {
+            action,
+            parent: self.current.take(),
+        });
+        self.current = Some(id);
+    }
+
+    /// Remove a node, attaching its children to its parent.
+    fn remove(&mut self, id: &str) -> Option<ActionNode> {
+        let index = self.nodes.iter().position(|n| n.action.id == id)?;
+        let removed = self.nodes.remove(index);
+
+        for node in &mut self.nodes {
+            if node.parent.as_deref() == Some(id) {
+                node.parent = removed.parent.clone();
+            }
+        }
+        if self.current.as_deref() == Some(id) {
+            self.current = removed.parent.clone();
+        }
+        self.redo_path.retain(|r| r != id);
+
+        Some(removed)
+    }
+
+    /// Remove a node together with everything below it.
+    fn remove_subtree(&mut self, id: &str) -> Vec<ActionNode> {
+        let mut doomed = HashSet::from([id.to_string()]);
+        // Nodes are recorded after their parents, so one pass finds them all
+        for node in &self.nodes {
+            if node.parent.as_ref().is_some_and(|p| doomed.contains(p)) {
+                doomed.insert(node.action.id.clone());
+            }
+        }
+
+        let (removed, kept) = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .partition(|n| doomed.contains(&n.action.id));
+        self.nodes = kept;
+        self.redo_path.retain(|r| !doomed.contains(r));
+        removed
+    }
+
+    /// Number of actions in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the tree holds no actions.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// All nodes in the order they were recorded.
+    pub fn nodes(&self) -> &[ActionNode] {
+        &self.nodes
+    }
+
+    /// Look up a node by action ID.
+    pub fn get(&self, id: &str) -> Option<&ActionNode> {
+        self.nodes.iter().find(|n| n.action.id == id)
+    }
+
+    fn get_mut(&mut self, id: &str) -> Option<&mut ActionNode> {
+        self.nodes.iter_mut().find(|n| n.action.id == id)
+    }
+
+    /// ID of the most recently applied action.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Children of an action (or the roots, for `None`), oldest first.
+    ///
+    /// The first child continues the original line; later children are
+    /// alternate branches.
+    pub fn children<'a>(&'a self, parent: Option<&'a str>) -> impl Iterator<Item = &'a ActionNode> {
+        self.nodes
+            .iter()
+            .filter(move |n| n.parent.as_deref() == parent)
+    }
+
+    /// Action IDs from the root down to `id` (inclusive).
+    pub fn path_to(&self, id: Option<&str>) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut cursor = id.and_then(|id| self.get(id));
+        while let Some(node) = cursor {
+            path.push(node.action.id.clone());
+            cursor = node.parent.as_deref().and_then(|p| self.get(p));
         }
+        path.reverse();
+        path
+    }
+
+    /// Nodes with no children (the tip of every branch), oldest first.
+    pub fn leaves(&self) -> Vec<&ActionNode> {
+        self.nodes
+            .iter()
+            .filter(|n| self.children(Some(&n.action.id)).next().is_none())
+            .collect()
     }
 }
 
+/// The tip of one branch of the action tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch {
+    /// ID of the newest action on the branch.
+    pub tip_id: String,
+
+    /// Description of the newest action on the branch.
+    pub description: String,
+
+    /// Number of actions from the initial state to the tip.
+    pub depth: usize,
+
+    /// Whether undo/redo currently move along this branch.
+    pub is_active: bool,
+}
+
 /// Manages undo/redo operations for a project.
 ///
 /// The undo manager maintains:
-/// - An undo stack of recent actions (limited by max_undo_levels)
-/// - A redo stack of undone actions
+/// - An action tree holding every recorded action, including alternate
+///   branches created by acting after an undo
+/// - The current position in that tree and the path redo will follow
 /// - A complete action log for history viewing
 /// - A list of discarded action IDs when history is trimmed
+///
+/// Undo and redo only ever move along the current path, so they behave
+/// exactly like linear undo/redo stacks.
 #[derive(Debug, Clone)]
 pub struct UndoManager {
-    /// Stack of actions that can be undone.
-    undo_stack: Vec<UndoAction>,
-
-    /// Stack of actions that can be redone.
-    redo_stack: Vec<UndoAction>,
+    /// All recorded actions and the current position.
+    tree: ActionTree,
 
     /// Maximum number of undo levels to keep.
     max_undo_levels: usize,
@@ -140,6 +374,25 @@ pub struct UndoManager {
 
     /// IDs of actions that were discarded due to history trimming.
     discarded_action_ids: Vec<String>,
+
+    /// Window for merging consecutive changes to one parameter.
+    coalesce_window: Duration,
+
+    /// The group being recorded, if any.
+    group: Option<OpenGroup>,
+
+    /// Project whose Layer 1 files discarded actions release, if known.
+    project_path: Option<PathBuf>,
+}
+
+/// A group of changes being recorded as one action.
+#[derive(Debug, Clone)]
+struct OpenGroup {
+    /// Description of the grouped action.
+    label: String,
+
+    /// ID of the grouped action, once the first change is recorded.
+    action_id: Option<String>,
 }
 
 impl Default for UndoManager {
@@ -152,75 +405,67 @@ impl UndoManager {
     /// Create a new undo manager with the specified maximum undo levels.
     pub fn new(max_levels: usize) -> Self {
         Self {
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
+            tree: ActionTree::default(),
             max_undo_levels: max_levels,
             action_log: Vec::new(),
             discarded_action_ids: Vec::new(),
+            coalesce_window: DEFAULT_COALESCE_WINDOW,
+            group: None,
+            project_path: None,
         }
     }
 
+    /// Release the Layer 1 files of discarded actions from `project_path`.
+    ///
+    /// Without a project, trimmed history only forgets the actions.
+    pub fn with_project_path(mut self, project_path: &Path) -> Self {
+        self.project_path = Some(project_path.to_path_buf());
+        self
+    }
+
     /// Load undo manager state from the history directory.
     ///
     /// Expects files:
-    /// - `history/undo_stack.json`
-    /// - `history/redo_stack.json`
+    /// - `history/action_tree.json`
     /// - `history/action_log.json`
+    ///
+    /// Projects saved before branching was supported have
+    /// `history/undo_stack.json` and `history/redo_stack.json` instead;
+    /// these load as a single linear branch. The history directory's parent
+    /// is taken as the project whose Layer 1 files discarded actions
+    /// release.
     pub fn load(history_dir: &Path) -> Result<Self> {
-        let undo_stack_path = history_dir.join(UNDO_STACK_FILE);
-        let redo_stack_path = history_dir.join(REDO_STACK_FILE);
+        let tree_path = history_dir.join(ACTION_TREE_FILE);
         let action_log_path = history_dir.join(ACTION_LOG_FILE);
 
-        // Load undo stack (or empty if not exists)
-        let undo_stack: Vec<UndoAction> = if undo_stack_path.exists() {
-            let content =
-                fs::read_to_string(&undo_stack_path).map_err(|e| NuevaError::FileReadError {
-                    path: undo_stack_path.clone(),
-                    source: e,
-                })?;
-            serde_json::from_str(&content)?
-        } else {
-            Vec::new()
-        };
-
-        // Load redo stack (or empty if not exists)
-        let redo_stack: Vec<UndoAction> = if redo_stack_path.exists() {
-            let content =
-                fs::read_to_string(&redo_stack_path).map_err(|e| NuevaError::FileReadError {
-                    path: redo_stack_path.clone(),
-                    source: e,
-                })?;
-            serde_json::from_str(&content)?
+        let tree = if tree_path.exists() {
+            read_json(&tree_path)?
         } else {
-            Vec::new()
+            let undo_stack: Vec<UndoAction> =
+                read_json_or_default(&history_dir.join(UNDO_STACK_FILE))?;
+            let redo_stack: Vec<UndoAction> =
+                read_json_or_default(&history_dir.join(REDO_STACK_FILE))?;
+            ActionTree::from_stacks(undo_stack, redo_stack)
         };
 
         // Load action log (or empty if not exists)
-        let action_log: Vec<UndoAction> = if action_log_path.exists() {
-            let content =
-                fs::read_to_string(&action_log_path).map_err(|e| NuevaError::FileReadError {
-                    path: action_log_path.clone(),
-                    source: e,
-                })?;
-            serde_json::from_str(&content)?
-        } else {
-            Vec::new()
-        };
+        let action_log: Vec<UndoAction> = read_json_or_default(&action_log_path)?;
 
         Ok(Self {
-            undo_stack,
-            redo_stack,
+            tree,
             max_undo_levels: DEFAULT_MAX_UNDO_LEVELS,
             action_log,
             discarded_action_ids: Vec::new(),
+            coalesce_window: DEFAULT_COALESCE_WINDOW,
+            group: None,
+            project_path: history_dir.parent().map(Path::to_path_buf),
         })
     }
 
     /// Save undo manager state to the history directory.
     ///
     /// Creates files:
-    /// - `history/undo_stack.json`
-    /// - `history/redo_stack.json`
+    /// - `history/action_tree.json`
     /// - `history/action_log.json`
     pub fn save(&self, history_dir: &Path) -> Result<()> {
         // Ensure history directory exists
@@ -231,21 +476,22 @@ impl UndoManager {
             })?;
         }
 
-        // Save undo stack
-        let undo_stack_path = history_dir.join(UNDO_STACK_FILE);
-        let undo_content = serde_json::to_string_pretty(&self.undo_stack)?;
-        fs::write(&undo_stack_path, undo_content).map_err(|e| NuevaError::FileWriteError {
-            path: undo_stack_path,
+        // Save action tree
+        let tree_path = history_dir.join(ACTION_TREE_FILE);
+        let tree_content = serde_json::to_string_pretty(&self.tree)?;
+        fs::write(&tree_path, tree_content).map_err(|e| NuevaError::FileWriteError {
+            path: tree_path,
             source: e,
         })?;
 
-        // Save redo stack
-        let redo_stack_path = history_dir.join(REDO_STACK_FILE);
-        let redo_content = serde_json::to_string_pretty(&self.redo_stack)?;
-        fs::write(&redo_stack_path, redo_content).map_err(|e| NuevaError::FileWriteError {
-            path: redo_stack_path,
-            source: e,
-        })?;
+        // The tree supersedes the old linear stacks
+        for legacy in [UNDO_STACK_FILE, REDO_STACK_FILE] {
+            let path = history_dir.join(legacy);
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| NuevaError::FileWriteError { path, source: e })?;
+            }
+        }
 
         // Save action log
         let action_log_path = history_dir.join(ACTION_LOG_FILE);
@@ -258,116 +504,433 @@ impl UndoManager {
         Ok(())
     }
 
-    /// Push a new action onto the undo stack.
+    /// Record a new action at the current position.
     ///
-    /// This clears the redo stack (since the history has diverged)
-    /// and trims the undo stack if it exceeds max_undo_levels.
-    pub fn push(&mut self, action: UndoAction) {
-        // Clear redo stack since we're adding a new action
-        self.redo_stack.clear();
+    /// Actions that were undone stay in the tree as an alternate branch;
+    /// the redo path now ends here. Trims history if the current path
+    /// exceeds max_undo_levels.
+    ///
+    /// A parameter change arriving within the coalescing window of a
+    /// change to the same parameter is merged into that action instead.
+    /// While a group is open, every change after the first is merged into
+    /// the group's action.
+    pub fn push(&mut self, mut action: UndoAction) {
+        if let Some(group) = &mut self.group {
+            if let Some(id) = group.action_id.clone() {
+                self.extend_group(&id, action);
+                return;
+            }
+            group.action_id = Some(action.id.clone());
+            action.steps = vec![std::mem::replace(
+                &mut action.description,
+                group.label.clone(),
+            )];
+            action.parameter = None;
+        } else if self.coalesce(&action) {
+            return;
+        }
+
+        self.tree.redo_path.clear();
 
         // Add to action log
         self.action_log.push(action.clone());
 
-        // Add to undo stack
-        self.undo_stack.push(action);
+        // Add to the tree as a child of the current action
+        self.tree.insert(action);
 
         // Trim if over max
         self.trim_history();
     }
 
+    /// Merge `action` into the current action if both change the same
+    /// parameter within the coalescing window.
+    ///
+    /// The merged action keeps its original `state_before` and takes the
+    /// new `state_after`, description and timestamp, so the window is
+    /// measured from the latest change and a continuous drag of any length
+    /// stays one action. Actions with redo history after them are never
+    /// extended.
+    fn coalesce(&mut self, action: &UndoAction) -> bool {
+        let Some(parameter) = &action.parameter else {
+            return false;
+        };
+        if self.coalesce_window.is_zero() || !self.tree.redo_path.is_empty() {
+            return false;
+        }
+        let Some(current) = self.tree.current().map(str::to_string) else {
+            return false;
+        };
+        if self.tree.children(Some(&current)).next().is_some() {
+            return false;
+        }
+        let Some(node) = self.tree.get_mut(&current) else {
+            return false;
+        };
+
+        let within_window = (action.timestamp - node.action.timestamp)
+            .to_std()
+            .is_ok_and(|elapsed| elapsed <= self.coalesce_window);
+        if node.action.parameter.as_ref() != Some(parameter) || !within_window {
+            return false;
+        }
+
+        node.action.state_after = action.state_after.clone();
+        node.action.description = action.description.clone();
+        node.action.timestamp = action.timestamp;
+        let merged = node.action.clone();
+
+        if let Some(logged) = self
+            .action_log
+            .iter_mut()
+            .rev()
+            .find(|logged| logged.id == merged.id)
+        {
+            *logged = merged;
+        }
+        true
+    }
+
+    /// Start recording changes as one action described by `label`.
+    ///
+    /// Every action pushed until [`end_group`](Self::end_group) is merged
+    /// into one, so a single undo or redo covers all of them. Grouping
+    /// never coalesces with actions recorded before it. If a group is
+    /// already open, it stays open under its original label.
+    pub fn begin_group(&mut self, label: impl Into<String>) {
+        if self.group.is_none() {
+            self.group = Some(OpenGroup {
+                label: label.into(),
+                action_id: None,
+            });
+        }
+    }
+
+    /// Stop grouping changes.
+    ///
+    /// Returns the grouped action, or `None` if nothing was recorded
+    /// since [`begin_group`](Self::begin_group).
+    pub fn end_group(&mut self) -> Option<UndoAction> {
+        let id = self.group.take()?.action_id?;
+        self.tree.get(&id).map(|node| node.action.clone())
+    }
+
+    /// Check whether changes are currently being grouped.
+    pub fn is_grouping(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// Merge `action` into the open group's action with ID `id`.
+    fn extend_group(&mut self, id: &str, action: UndoAction) {
+        let Some(node) = self.tree.get_mut(id) else {
+            return;
+        };
+        node.action.state_after = action.state_after;
+        node.action.timestamp = action.timestamp;
+        node.action.steps.push(action.description);
+        let merged = node.action.clone();
+
+        if let Some(logged) = self
+            .action_log
+            .iter_mut()
+            .rev()
+            .find(|logged| logged.id == merged.id)
+        {
+            *logged = merged;
+        }
+    }
+
     /// Undo the last action, restoring the project to its previous state.
     ///
     /// Returns the undone action on success.
     pub fn undo(&mut self, project: &mut Project) -> Result<UndoAction> {
-        let action = self.undo_stack.pop().ok_or(NuevaError::NothingToUndo)?;
+        let node = self
+            .tree
+            .current()
+            .and_then(|id| self.tree.get(id))
+            .cloned()
+            .ok_or(NuevaError::NothingToUndo)?;
 
         // Restore project state from state_before
-        let restored_project: Project = serde_json::from_value(action.state_before.clone())?;
-
-        // Copy all serializable fields to the existing project
-        project.schema_version = restored_project.schema_version;
-        project.created_at = restored_project.created_at;
-        project.modified_at = restored_project.modified_at;
-        project.nueva_version = restored_project.nueva_version;
-        project.source = restored_project.source;
-        project.layer0 = restored_project.layer0;
-        project.layer1 = restored_project.layer1;
-        project.layer2 = restored_project.layer2;
-        project.conversation = restored_project.conversation;
-        project.unknown_fields = restored_project.unknown_fields;
-        // Note: project_path is not serialized, so it's preserved
-
-        // Move action to redo stack
-        self.redo_stack.push(action.clone());
+        restore_snapshot(project, &node.action.state_before)?;
 
-        Ok(action)
+        // Step up the tree, remembering the way back for redo
+        self.tree.current = node.parent;
+        self.tree.redo_path.push(node.action.id.clone());
+
+        Ok(node.action)
     }
 
     /// Redo the last undone action, restoring the project to the state after the action.
     ///
     /// Returns the redone action on success.
     pub fn redo(&mut self, project: &mut Project) -> Result<UndoAction> {
-        let action = self.redo_stack.pop().ok_or(NuevaError::NothingToRedo)?;
+        let action = self
+            .tree
+            .redo_path
+            .last()
+            .and_then(|id| self.tree.get(id))
+            .map(|node| node.action.clone())
+            .ok_or(NuevaError::NothingToRedo)?;
 
         // Restore project state from state_after
-        let restored_project: Project = serde_json::from_value(action.state_after.clone())?;
-
-        // Copy all serializable fields to the existing project
-        project.schema_version = restored_project.schema_version;
-        project.created_at = restored_project.created_at;
-        project.modified_at = restored_project.modified_at;
-        project.nueva_version = restored_project.nueva_version;
-        project.source = restored_project.source;
-        project.layer0 = restored_project.layer0;
-        project.layer1 = restored_project.layer1;
-        project.layer2 = restored_project.layer2;
-        project.conversation = restored_project.conversation;
-        project.unknown_fields = restored_project.unknown_fields;
-        // Note: project_path is not serialized, so it's preserved
-
-        // Move action back to undo stack
-        self.undo_stack.push(action.clone());
+        restore_snapshot(project, &action.state_after)?;
+
+        self.tree.redo_path.pop();
+        self.tree.current = Some(action.id.clone());
 
         Ok(action)
     }
 
+    /// Undo a specific past action without undoing everything after it.
+    ///
+    /// Finds the most recent action on the current path matching
+    /// `predicate`, removes it, and rebuilds the Layer 2 chain by replaying
+    /// the chain changes of every later action on top of the state before it.
+    ///
+    /// Snapshots can't simply be swapped in here: each later action's
+    /// snapshot still contains the removed change, so the chain has to be
+    /// reconstructed from per-action deltas. Later actions' snapshots are
+    /// rewritten to match the new history so plain undo keeps working, and
+    /// the redo path is discarded because it no longer applies.
+    ///
+    /// Only DSP changes can be removed this way, and not across a bake,
+    /// import or reset (those replace Layer 0 and invalidate the chain).
+    pub fn undo_action_matching<F>(
+        &mut self,
+        project: &mut Project,
+        mut predicate: F,
+    ) -> Result<UndoAction>
+    where
+        F: FnMut(&UndoAction) -> bool,
+    {
+        let path = self.tree.path_to(self.tree.current());
+        let actions: Vec<&UndoAction> = path
+            .iter()
+            .filter_map(|id| self.tree.get(id).map(|n| &n.action))
+            .collect();
+
+        let index = actions
+            .iter()
+            .rposition(|a| predicate(a))
+            .ok_or(NuevaError::NoMatchingAction)?;
+
+        let target = actions[index];
+        if target.action_type != ActionType::DspChange {
+            return Err(NuevaError::SelectiveUndoBlocked {
+                description: target.description.clone(),
+                reason: format!(
+                    "only DSP changes can be undone selectively, not {}",
+                    target.action_type
+                ),
+            });
+        }
+        if let Some(blocker) = actions[index + 1..].iter().find(|a| {
+            matches!(
+                a.action_type,
+                ActionType::Bake | ActionType::Import | ActionType::Reset
+            )
+        }) {
+            return Err(NuevaError::SelectiveUndoBlocked {
+                description: target.description.clone(),
+                reason: format!("a later {} replaced the source audio", blocker.action_type),
+            });
+        }
+
+        // Replay every later action's chain delta on top of the state before the target
+        let mut chain = chain_of(&target.state_before);
+        for id in &path[index + 1..] {
+            let Some(later) = self.tree.get_mut(id).map(|n| &mut n.action) else {
+                continue;
+            };
+            let before = chain.clone();
+            apply_chain_delta(
+                &mut chain,
+                &chain_of(&later.state_before),
+                &chain_of(&later.state_after),
+            );
+            set_chain(&mut later.state_before, before);
+            set_chain(&mut later.state_after, chain.clone());
+        }
+
+        project.layer2.chain = serde_json::from_value(serde_json::Value::Array(chain))?;
+
+        let removed = self
+            .tree
+            .remove(&path[index])
+            .ok_or(NuevaError::NoMatchingAction)?
+            .action;
+        let mut dropped = vec![removed.id.clone()];
+        for id in std::mem::take(&mut self.tree.redo_path) {
+            if let Some(node) = self.tree.remove(&id) {
+                dropped.push(node.action.id);
+            }
+        }
+
+        if let Some(storage) = self.layer1_storage() {
+            self.adopt_layer1_files(&storage)?;
+            storage.release_layer1_except(&dropped, Path::new(&project.layer1.path))?;
+        }
+        self.discarded_action_ids.extend(dropped);
+
+        Ok(removed)
+    }
+
     /// Get the complete action history log.
     pub fn get_history(&self) -> &[UndoAction] {
         &self.action_log
     }
 
+    /// Get the action tree, including alternate branches.
+    pub fn tree(&self) -> &ActionTree {
+        &self.tree
+    }
+
+    /// List the tip of every branch, oldest first.
+    pub fn branches(&self) -> Vec<Branch> {
+        let active_tip = self
+            .tree
+            .redo_path
+            .first()
+            .map(String::as_str)
+            .or(self.tree.current());
+
+        self.tree
+            .leaves()
+            .into_iter()
+            .map(|node| Branch {
+                tip_id: node.action.id.clone(),
+                description: node.action.description.clone(),
+                depth: self.tree.path_to(Some(&node.action.id)).len(),
+                is_active: active_tip == Some(node.action.id.as_str()),
+            })
+            .collect()
+    }
+
+    /// Jump to any recorded action, restoring the project to the state
+    /// after it.
+    ///
+    /// Undo then walks back along that action's branch. If the action has
+    /// children, redo follows the most recently recorded ones.
+    pub fn switch_branch(&mut self, project: &mut Project, action_id: &str) -> Result<UndoAction> {
+        let action = self
+            .tree
+            .get(action_id)
+            .map(|node| node.action.clone())
+            .ok_or_else(|| NuevaError::UndoActionNotFound {
+                action_id: action_id.to_string(),
+            })?;
+
+        restore_snapshot(project, &action.state_after)?;
+
+        let mut redo_path = Vec::new();
+        let mut cursor = action.id.clone();
+        while let Some(child) = self.tree.children(Some(&cursor)).last() {
+            cursor = child.action.id.clone();
+            redo_path.push(cursor.clone());
+        }
+        redo_path.reverse();
+
+        self.tree.current = Some(action.id.clone());
+        self.tree.redo_path = redo_path;
+
+        Ok(action)
+    }
+
+    /// IDs of every action that undo, redo or a branch switch can still
+    /// reach (e.g. for pruning Layer 1 files).
+    pub fn reachable_action_ids(&self) -> HashSet<String> {
+        self.tree
+            .nodes
+            .iter()
+            .map(|n| n.action.id.clone())
+            .collect()
+    }
+
     /// Get the current position in the undo history.
     ///
     /// Returns the number of actions that have been performed (undo stack size).
     /// Position 0 means at the beginning (nothing to undo).
     pub fn current_position(&self) -> usize {
-        self.undo_stack.len()
+        self.undo_count()
     }
 
     /// Get the number of actions that can be undone.
     pub fn undo_count(&self) -> usize {
-        self.undo_stack.len()
+        self.tree.path_to(self.tree.current()).len()
     }
 
     /// Get the number of actions that can be redone.
     pub fn redo_count(&self) -> usize {
-        self.redo_stack.len()
+        self.tree.redo_path.len()
     }
 
-    /// Trim the undo stack to the maximum allowed levels.
+    /// Trim the undo history to the maximum allowed levels.
+    ///
+    /// When the current path exceeds max_undo_levels, its oldest actions are
+    /// removed and their IDs are tracked in discarded_action_ids. Branches
+    /// that forked from a removed action can no longer be reached from the
+    /// current path's start, so they are discarded with it.
     ///
-    /// When the stack exceeds max_undo_levels, the oldest actions are removed
-    /// and their IDs are tracked in discarded_action_ids.
+    /// With a project path, Layer 1 files only discarded actions used are
+    /// deleted; a failure to do so is logged and leaves the files behind.
     pub fn trim_history(&mut self) {
-        while self.undo_stack.len() > self.max_undo_levels {
-            if let Some(removed) = self.undo_stack.first().cloned() {
-                self.discarded_action_ids.push(removed.id);
-                self.undo_stack.remove(0);
+        let discarded_before = self.discarded_action_ids.len();
+        while self.undo_count() > self.max_undo_levels {
+            let path = self.tree.path_to(self.tree.current());
+            let oldest = &path[0];
+
+            let forks: Vec<String> = self
+                .tree
+                .children(Some(oldest))
+                .map(|n| n.action.id.clone())
+                .filter(|id| !path.contains(id) && !self.tree.redo_path.contains(id))
+                .collect();
+            for fork in forks {
+                let pruned = self.tree.remove_subtree(&fork);
+                self.discarded_action_ids
+                    .extend(pruned.into_iter().map(|n| n.action.id));
+            }
+
+            if let Some(removed) = self.tree.remove(oldest) {
+                self.discarded_action_ids.push(removed.action.id);
+            }
+        }
+
+        if self.discarded_action_ids.len() > discarded_before {
+            if let Some(storage) = self.layer1_storage() {
+                let pruned = self
+                    .adopt_layer1_files(&storage)
+                    .and_then(|()| storage.prune_orphaned_files(&self.reachable_action_ids()));
+                if let Err(e) = pruned {
+                    log::warn!("Could not release trimmed Layer 1 files: {}", e);
+                }
             }
         }
     }
 
+    fn layer1_storage(&self) -> Option<Layer1StorageManager> {
+        self.project_path.as_deref().map(Layer1StorageManager::new)
+    }
+
+    /// Reference each Layer 1 file from every action whose states point at
+    /// it, so releasing the action that stored a file doesn't delete it
+    /// while later actions still undo to it.
+    fn adopt_layer1_files(&self, storage: &Layer1StorageManager) -> Result<()> {
+        let uses: Vec<(&str, &str)> = self
+            .tree
+            .nodes
+            .iter()
+            .flat_map(|n| {
+                [&n.action.state_before, &n.action.state_after]
+                    .into_iter()
+                    .filter_map(|state| state.pointer("/layer1/path")?.as_str())
+                    .filter_map(|path| Path::new(path).file_name()?.to_str())
+                    .map(|filename| (filename, n.action.id.as_str()))
+            })
+            .collect();
+        storage.add_references(uses)
+    }
+
     /// Get the maximum number of undo levels.
     pub fn max_undo_levels(&self) -> usize {
         self.max_undo_levels
@@ -381,6 +944,18 @@ impl UndoManager {
         self.trim_history();
     }
 
+    /// Get the window for merging consecutive changes to one parameter.
+    pub fn coalesce_window(&self) -> Duration {
+        self.coalesce_window
+    }
+
+    /// Set the window for merging consecutive changes to one parameter.
+    ///
+    /// A zero window records every change as its own action.
+    pub fn set_coalesce_window(&mut self, window: Duration) {
+        self.coalesce_window = window;
+    }
+
     /// Get the IDs of actions that were discarded due to history trimming.
     pub fn discarded_action_ids(&self) -> &[String] {
         &self.discarded_action_ids
@@ -388,62 +963,202 @@ impl UndoManager {
 
     /// Check if there are actions that can be undone.
     pub fn can_undo(&self) -> bool {
-        !self.undo_stack.is_empty()
+        self.tree.current().is_some()
     }
 
     /// Check if there are actions that can be redone.
     pub fn can_redo(&self) -> bool {
-        !self.redo_stack.is_empty()
+        !self.tree.redo_path.is_empty()
     }
 
     /// Get the most recent action that can be undone (if any).
     pub fn peek_undo(&self) -> Option<&UndoAction> {
-        self.undo_stack.last()
+        self.tree
+            .current()
+            .and_then(|id| self.tree.get(id))
+            .map(|n| &n.action)
     }
 
     /// Get the most recent action that can be redone (if any).
     pub fn peek_redo(&self) -> Option<&UndoAction> {
-        self.redo_stack.last()
+        self.tree
+            .redo_path
+            .last()
+            .and_then(|id| self.tree.get(id))
+            .map(|n| &n.action)
     }
 
-    /// Clear all undo/redo history.
+    /// Clear all undo/redo history, including alternate branches.
     ///
     /// This is typically called after a bake operation or when starting fresh.
     pub fn clear(&mut self) {
         // Track all discarded actions
-        for action in &self.undo_stack {
-            self.discarded_action_ids.push(action.id.clone());
-        }
-        for action in &self.redo_stack {
-            self.discarded_action_ids.push(action.id.clone());
+        for node in &self.tree.nodes {
+            self.discarded_action_ids.push(node.action.id.clone());
         }
 
-        self.undo_stack.clear();
-        self.redo_stack.clear();
+        self.tree = ActionTree::default();
     }
 
     /// Get a summary of the undo stack for display.
     pub fn undo_stack_summary(&self) -> Vec<(String, ActionType, String)> {
-        self.undo_stack
+        self.tree
+            .path_to(self.tree.current())
             .iter()
             .rev() // Most recent first
-            .map(|a| (a.id.clone(), a.action_type, a.description.clone()))
+            .filter_map(|id| self.tree.get(id))
+            .map(|n| summarize(&n.action))
             .collect()
     }
 
     /// Get a summary of the redo stack for display.
     pub fn redo_stack_summary(&self) -> Vec<(String, ActionType, String)> {
-        self.redo_stack
+        self.tree
+            .redo_path
             .iter()
             .rev() // Most recently undone first
-            .map(|a| (a.id.clone(), a.action_type, a.description.clone()))
+            .filter_map(|id| self.tree.get(id))
+            .map(|n| summarize(&n.action))
             .collect()
     }
 }
 
+fn summarize(action: &UndoAction) -> (String, ActionType, String) {
+    (
+        action.id.clone(),
+        action.action_type,
+        action.description.clone(),
+    )
+}
+
+/// Copy all serializable fields of a project snapshot into `project`.
+fn restore_snapshot(project: &mut Project, state: &serde_json::Value) -> Result<()> {
+    let restored_project: Project = serde_json::from_value(state.clone())?;
+
+    project.schema_version = restored_project.schema_version;
+    project.created_at = restored_project.created_at;
+    project.modified_at = restored_project.modified_at;
+    project.nueva_version = restored_project.nueva_version;
+    project.source = restored_project.source;
+    project.layer0 = restored_project.layer0;
+    project.layer1 = restored_project.layer1;
+    project.layer2 = restored_project.layer2;
+    project.markers = restored_project.markers;
+    project.marker_schedule = restored_project.marker_schedule;
+    project.conversation = restored_project.conversation;
+    project.unknown_fields = restored_project.unknown_fields;
+    // Note: project_path is not serialized, so it's preserved
+
+    Ok(())
+}
+
+/// Read a JSON history file.
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = fs::read_to_string(path).map_err(|e| NuevaError::FileReadError {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Read a JSON history file, or the default value if it doesn't exist.
+fn read_json_or_default<T: serde::de::DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    if path.exists() {
+        read_json(path)
+    } else {
+        Ok(T::default())
+    }
+}
+
+/// Extract the Layer 2 chain from a project snapshot.
+fn chain_of(state: &serde_json::Value) -> Vec<serde_json::Value> {
+    state["layer2"]["chain"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Replace the Layer 2 chain in a project snapshot.
+fn set_chain(state: &mut serde_json::Value, chain: Vec<serde_json::Value>) {
+    if let Some(layer2) = state.get_mut("layer2").and_then(|l| l.as_object_mut()) {
+        layer2.insert("chain".to_string(), serde_json::Value::Array(chain));
+    }
+}
+
+/// Apply the change between two chain snapshots to another chain.
+///
+/// Effects are matched by ID: removals are removed, modifications replace
+/// the effect in place (skipped if it no longer exists), additions are
+/// inserted after the nearest preceding effect that still exists, and effects
+/// present on both sides are reordered to match `after` if their order changed.
+fn apply_chain_delta(
+    chain: &mut Vec<serde_json::Value>,
+    before: &[serde_json::Value],
+    after: &[serde_json::Value],
+) {
+    let find = |list: &[serde_json::Value], id: &serde_json::Value| {
+        list.iter().position(|e| &e["id"] == id)
+    };
+
+    // Removals
+    for effect in before {
+        if find(after, &effect["id"]).is_none() {
+            if let Some(pos) = find(chain, &effect["id"]) {
+                chain.remove(pos);
+            }
+        }
+    }
+
+    for (after_pos, effect) in after.iter().enumerate() {
+        match find(before, &effect["id"]) {
+            // Modifications
+            Some(before_pos) => {
+                if before[before_pos] != *effect {
+                    if let Some(pos) = find(chain, &effect["id"]) {
+                        chain[pos] = effect.clone();
+                    }
+                }
+            }
+            // Additions
+            None => {
+                let insert_at = after[..after_pos]
+                    .iter()
+                    .rev()
+                    .find_map(|prev| find(chain, &prev["id"]))
+                    .map_or(0, |pos| pos + 1);
+                chain.insert(insert_at, effect.clone());
+            }
+        }
+    }
+
+    // Reorders: shared effects keep their slots in the chain but take the
+    // order they have in `after`
+    let shared_ids = |from: &[serde_json::Value], other: &[serde_json::Value]| {
+        from.iter()
+            .filter(|e| find(other, &e["id"]).is_some())
+            .map(|e| e["id"].clone())
+            .collect::<Vec<_>>()
+    };
+    if shared_ids(before, after) != shared_ids(after, before) {
+        let slots: Vec<usize> = chain
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| find(before, &e["id"]).is_some() && find(after, &e["id"]).is_some())
+            .map(|(pos, _)| pos)
+            .collect();
+        let mut moved: Vec<serde_json::Value> =
+            slots.iter().map(|&pos| chain[pos].clone()).collect();
+        moved.sort_by_key(|e| find(after, &e["id"]));
+        for (pos, effect) in slots.into_iter().zip(moved) {
+            chain[pos] = effect;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::AudioBuffer;
     use tempfile::TempDir;
 
     fn create_test_state(name: &str) -> serde_json::Value {
@@ -483,6 +1198,180 @@ mod tests {
         })
     }
 
+    fn test_project() -> Project {
+        serde_json::from_value(create_test_state("test")).unwrap()
+    }
+
+    fn effect_json(id: &str, effect_type: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "type": effect_type,
+            "enabled": true,
+            "params": {},
+            "added_at": "2024-01-01T00:00:00Z",
+            "added_by": "agent"
+        })
+    }
+
+    fn state_with_chain(chain: Vec<serde_json::Value>) -> serde_json::Value {
+        let mut state = create_test_state("test");
+        state["layer2"]["chain"] = serde_json::Value::Array(chain);
+        state
+    }
+
+    /// Build a manager with EQ -> reverb -> delay added in order.
+    fn eq_reverb_delay_history() -> (UndoManager, Project) {
+        let eq = effect_json("eq-1", "parametric_eq");
+        let reverb = effect_json("reverb-1", "reverb");
+        let delay = effect_json("delay-1", "delay");
+
+        let mut manager = UndoManager::new(10);
+        manager.push(UndoAction::new(
+            ActionType::DspChange,
+            "Add EQ",
+            state_with_chain(vec![]),
+            state_with_chain(vec![eq.clone()]),
+        ));
+        manager.push(UndoAction::new(
+            ActionType::DspChange,
+            "Add reverb",
+            state_with_chain(vec![eq.clone()]),
+            state_with_chain(vec![eq.clone(), reverb.clone()]),
+        ));
+        manager.push(UndoAction::new(
+            ActionType::DspChange,
+            "Add delay",
+            state_with_chain(vec![eq.clone(), reverb.clone()]),
+            state_with_chain(vec![eq, reverb, delay.clone()]),
+        ));
+
+        let project: Project =
+            serde_json::from_value(manager.peek_undo().unwrap().state_after.clone()).unwrap();
+        (manager, project)
+    }
+
+    fn chain_ids(project: &Project) -> Vec<&str> {
+        project.layer2.chain.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_group_stands_apart_from_surrounding_actions() {
+        let mut manager = UndoManager::new(10);
+        manager.begin_group("Nothing happened");
+        assert!(manager.is_grouping());
+        assert!(manager.end_group().is_none());
+        assert!(!manager.is_grouping());
+        assert!(!manager.can_undo());
+
+        // Parameter changes inside a group don't coalesce with earlier ones
+        let change = |value: i64| {
+            UndoAction::new(
+                ActionType::DspChange,
+                format!("Set gain to {}", value),
+                create_test_state("before"),
+                create_test_state(&value.to_string()),
+            )
+            .with_parameter("gain-1", "gain_db")
+        };
+        manager.push(change(1));
+        manager.begin_group("Agent: \"louder\"");
+        manager.push(change(2));
+        manager.push(change(3));
+        let grouped = manager.end_group().unwrap();
+        manager.push(change(4));
+
+        assert_eq!(grouped.steps, ["Set gain to 2", "Set gain to 3"]);
+        assert_eq!(grouped.state_after, create_test_state("3"));
+        assert_eq!(manager.undo_count(), 3);
+        assert_eq!(manager.get_history()[1].description, "Agent: \"louder\"");
+    }
+
+    #[test]
+    fn test_undo_action_matching_removes_only_target() {
+        let (mut manager, mut project) = eq_reverb_delay_history();
+
+        let removed = manager
+            .undo_action_matching(&mut project, |a| a.description.contains("reverb"))
+            .unwrap();
+
+        assert_eq!(removed.description, "Add reverb");
+        assert_eq!(chain_ids(&project), vec!["eq-1", "delay-1"]);
+        assert_eq!(manager.undo_count(), 2);
+
+        // Plain undo afterwards steps back consistently through rewritten history
+        manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1"]);
+    }
+
+    #[test]
+    fn test_undo_action_matching_keeps_later_reorder() {
+        let (mut manager, _) = eq_reverb_delay_history();
+        let eq = effect_json("eq-1", "parametric_eq");
+        let reverb = effect_json("reverb-1", "reverb");
+        let delay = effect_json("delay-1", "delay");
+        manager.push(UndoAction::new(
+            ActionType::DspChange,
+            "Move delay before reverb",
+            state_with_chain(vec![eq.clone(), reverb.clone(), delay.clone()]),
+            state_with_chain(vec![eq, delay, reverb]),
+        ));
+        let mut project: Project =
+            serde_json::from_value(manager.peek_undo().unwrap().state_after.clone()).unwrap();
+
+        manager
+            .undo_action_matching(&mut project, |a| a.description == "Add EQ")
+            .unwrap();
+        assert_eq!(chain_ids(&project), vec!["delay-1", "reverb-1"]);
+
+        // The rewritten reorder still undoes and redoes
+        manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), vec!["reverb-1", "delay-1"]);
+        manager.redo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), vec!["delay-1", "reverb-1"]);
+    }
+
+    #[test]
+    fn test_undo_action_matching_is_deterministic() {
+        let (mut manager_a, mut project_a) = eq_reverb_delay_history();
+        let (mut manager_b, mut project_b) = eq_reverb_delay_history();
+
+        manager_a
+            .undo_action_matching(&mut project_a, |a| a.description.contains("reverb"))
+            .unwrap();
+        manager_b
+            .undo_action_matching(&mut project_b, |a| a.description.contains("reverb"))
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&project_a.layer2).unwrap(),
+            serde_json::to_value(&project_b.layer2).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_undo_action_matching_clears_redo() {
+        let (mut manager, mut project) = eq_reverb_delay_history();
+
+        manager.undo(&mut project).unwrap();
+        assert_eq!(manager.redo_count(), 1);
+
+        manager
+            .undo_action_matching(&mut project, |a| a.description.contains("EQ"))
+            .unwrap();
+        assert_eq!(manager.redo_count(), 0);
+        assert_eq!(chain_ids(&project), vec!["reverb-1"]);
+    }
+
+    #[test]
+    fn test_undo_action_matching_no_match() {
+        let (mut manager, mut project) = eq_reverb_delay_history();
+
+        let result =
+            manager.undo_action_matching(&mut project, |a| a.description.contains("chorus"));
+        assert!(matches!(result, Err(NuevaError::NoMatchingAction)));
+        assert_eq!(manager.undo_count(), 3);
+    }
+
     #[test]
     fn test_new_undo_manager() {
         let manager = UndoManager::new(10);
@@ -530,6 +1419,87 @@ mod tests {
         assert_eq!(manager.discarded_action_ids().len(), 2);
     }
 
+    #[test]
+    fn test_trim_history_drops_branches_off_removed_actions() {
+        let action = |id: &str| {
+            UndoAction::with_id(
+                id,
+                ActionType::DspChange,
+                id,
+                create_test_state("before"),
+                create_test_state("after"),
+            )
+        };
+        let mut manager = UndoManager::new(10);
+        let mut project = test_project();
+
+        // a -> b, undone, then a -> c -> d: b is a branch off a
+        manager.push(action("a"));
+        manager.push(action("b"));
+        manager.undo(&mut project).unwrap();
+        manager.push(action("c"));
+        manager.push(action("d"));
+        assert_eq!(manager.tree().len(), 4);
+
+        manager.set_max_undo_levels(2);
+        assert_eq!(manager.undo_count(), 2);
+        assert_eq!(manager.tree().len(), 2);
+        let mut discarded = manager.discarded_action_ids().to_vec();
+        discarded.sort();
+        assert_eq!(discarded, ["a", "b"]);
+        assert_eq!(manager.branches().len(), 1);
+    }
+
+    #[test]
+    fn test_discarded_actions_release_layer1_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Layer1StorageManager::new(temp_dir.path());
+        let blob = |freq: f32| AudioBuffer {
+            samples: vec![(0..480).map(|i| (i as f32 * freq).sin()).collect()],
+            sample_rate: 48000,
+        };
+        let path_a = storage.store_layer1(&blob(0.01), "a").unwrap();
+        let path_b = storage.store_layer1(&blob(0.02), "b").unwrap();
+        let path_e = storage.store_layer1(&blob(0.03), "e").unwrap();
+        let with_layer1 = |path: &Path| {
+            let mut state = create_test_state("layer1");
+            state["layer1"]["path"] = serde_json::json!(path.to_string_lossy());
+            state
+        };
+        let action = |id: &str, before: &Path, after: &Path| {
+            let action_type = if before == after {
+                ActionType::DspChange
+            } else {
+                ActionType::AiProcessing
+            };
+            UndoAction::with_id(id, action_type, id, with_layer1(before), with_layer1(after))
+        };
+        let original = Path::new("audio/layer1_ai.wav");
+        let mut manager = UndoManager::new(10).with_project_path(temp_dir.path());
+        let mut project = test_project();
+
+        // a -> b, undone, then a -> c -> d, where c and d still use a's file
+        manager.push(action("a", original, &path_a));
+        manager.push(action("b", &path_a, &path_b));
+        manager.undo(&mut project).unwrap();
+        manager.push(action("c", &path_a, &path_a));
+        manager.push(action("d", &path_a, &path_a));
+
+        manager.set_max_undo_levels(2);
+        assert!(!path_b.exists());
+        assert!(path_a.exists());
+
+        // Selectively undoing d drops the redo branch to e, releasing the
+        // file only e used
+        manager.push(action("e", &path_a, &path_e));
+        manager.undo(&mut project).unwrap();
+        manager
+            .undo_action_matching(&mut project, |a| a.id == "d")
+            .unwrap();
+        assert!(!path_e.exists());
+        assert!(path_a.exists());
+    }
+
     #[test]
     fn test_save_and_load() {
         let temp_dir = TempDir::new().unwrap();
@@ -575,10 +1545,7 @@ mod tests {
             manager.push(action);
         }
 
-        // Simulate undo by moving to redo stack manually
-        if let Some(action) = manager.undo_stack.pop() {
-            manager.redo_stack.push(action);
-        }
+        manager.undo(&mut test_project()).unwrap();
 
         assert_eq!(manager.redo_count(), 1);
 
@@ -592,6 +1559,9 @@ mod tests {
         manager.push(new_action);
 
         assert_eq!(manager.redo_count(), 0);
+        // ...but the undone action survives as an alternate branch
+        assert_eq!(manager.tree().len(), 4);
+        assert_eq!(manager.branches().len(), 2);
     }
 
     #[test]
@@ -630,9 +1600,7 @@ mod tests {
         }
 
         // Move one to redo
-        if let Some(action) = manager.undo_stack.pop() {
-            manager.redo_stack.push(action);
-        }
+        manager.undo(&mut test_project()).unwrap();
 
         manager.clear();
 
@@ -715,4 +1683,253 @@ mod tests {
         assert_eq!(undo_summary[1].0, "id-1");
         assert_eq!(undo_summary[1].1, ActionType::Import);
     }
+
+    /// Push EQ -> reverb -> delay, undo the delay, then add a chorus instead.
+    fn forked_history() -> (UndoManager, Project) {
+        let (mut manager, mut project) = eq_reverb_delay_history();
+        manager.undo(&mut project).unwrap();
+
+        let eq = effect_json("eq-1", "parametric_eq");
+        let reverb = effect_json("reverb-1", "reverb");
+        let chorus = effect_json("chorus-1", "chorus");
+        manager.push(UndoAction::with_id(
+            "chorus-action",
+            ActionType::DspChange,
+            "Add chorus",
+            state_with_chain(vec![eq.clone(), reverb.clone()]),
+            state_with_chain(vec![eq, reverb, chorus]),
+        ));
+        let project: Project =
+            serde_json::from_value(manager.peek_undo().unwrap().state_after.clone()).unwrap();
+        (manager, project)
+    }
+
+    #[test]
+    fn test_action_after_undo_forks_branch() {
+        let (manager, _) = forked_history();
+
+        let branches = manager.branches();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].description, "Add delay");
+        assert!(!branches[0].is_active);
+        assert_eq!(branches[1].tip_id, "chorus-action");
+        assert!(branches[1].is_active);
+        assert_eq!(branches[0].depth, 3);
+        assert_eq!(branches[1].depth, 3);
+        assert_eq!(manager.undo_count(), 3);
+        assert!(!manager.can_redo());
+    }
+
+    #[test]
+    fn test_switch_between_branches() {
+        let (mut manager, mut project) = forked_history();
+        let delay_tip = manager.branches()[0].tip_id.clone();
+
+        manager.switch_branch(&mut project, &delay_tip).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1", "delay-1"]);
+        assert!(manager.branches()[0].is_active);
+
+        // Undo walks back along the delay branch
+        manager.undo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1"]);
+        manager.redo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1", "delay-1"]);
+
+        manager
+            .switch_branch(&mut project, "chorus-action")
+            .unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1", "chorus-1"]);
+    }
+
+    #[test]
+    fn test_switch_to_fork_point_redoes_newest_branch() {
+        let (mut manager, mut project) = forked_history();
+        let reverb = manager.tree().path_to(manager.tree().current())[1].clone();
+
+        manager.switch_branch(&mut project, &reverb).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1"]);
+        assert_eq!(manager.peek_redo().unwrap().id, "chorus-action");
+    }
+
+    #[test]
+    fn test_switch_branch_unknown_id() {
+        let (mut manager, mut project) = forked_history();
+        let result = manager.switch_branch(&mut project, "missing");
+        assert!(matches!(result, Err(NuevaError::UndoActionNotFound { .. })));
+    }
+
+    #[test]
+    fn test_tree_survives_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, _) = forked_history();
+        manager.save(temp_dir.path()).unwrap();
+
+        let mut loaded = UndoManager::load(temp_dir.path()).unwrap();
+        assert_eq!(loaded.tree().len(), 4);
+        assert_eq!(loaded.branches(), manager.branches());
+        assert_eq!(loaded.tree().current(), Some("chorus-action"));
+
+        let mut project = test_project();
+        let delay_tip = loaded.branches()[0].tip_id.clone();
+        loaded.switch_branch(&mut project, &delay_tip).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1", "delay-1"]);
+    }
+
+    #[test]
+    fn test_load_linear_stacks() {
+        let temp_dir = TempDir::new().unwrap();
+        let (mut manager, mut project) = eq_reverb_delay_history();
+        manager.undo(&mut project).unwrap();
+
+        // Write the history the way it was stored before branching
+        let path = manager.tree().path_to(manager.tree().current());
+        let undo_stack: Vec<&UndoAction> = path
+            .iter()
+            .map(|id| &manager.tree().get(id).unwrap().action)
+            .collect();
+        let redo_stack = vec![manager.peek_redo().unwrap()];
+        fs::write(
+            temp_dir.path().join(UNDO_STACK_FILE),
+            serde_json::to_string(&undo_stack).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(REDO_STACK_FILE),
+            serde_json::to_string(&redo_stack).unwrap(),
+        )
+        .unwrap();
+
+        let mut loaded = UndoManager::load(temp_dir.path()).unwrap();
+        assert_eq!(loaded.undo_count(), 2);
+        assert_eq!(loaded.redo_count(), 1);
+        assert_eq!(loaded.branches().len(), 1);
+
+        loaded.redo(&mut project).unwrap();
+        assert_eq!(chain_ids(&project), vec!["eq-1", "reverb-1", "delay-1"]);
+    }
+
+    fn state_with_gain(gain_db: f64, q: f64) -> serde_json::Value {
+        let mut eq = effect_json("eq-1", "parametric_eq");
+        eq["params"] = serde_json::json!({ "gain_db": gain_db, "q": q });
+        state_with_chain(vec![eq])
+    }
+
+    /// A change to eq-1's `param` from `before` to `after` (gain_db, q),
+    /// `at_ms` after a fixed start time
+    fn param_change(param: &str, before: (f64, f64), after: (f64, f64), at_ms: i64) -> UndoAction {
+        let mut action = UndoAction::new(
+            ActionType::DspChange,
+            format!("Set eq-1 {}", param),
+            state_with_gain(before.0, before.1),
+            state_with_gain(after.0, after.1),
+        )
+        .with_parameter("eq-1", param);
+        action.timestamp = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + chrono::Duration::milliseconds(at_ms);
+        action
+    }
+
+    fn gain_db(project: &Project) -> f64 {
+        project.layer2.chain[0].params["gain_db"].as_f64().unwrap()
+    }
+
+    /// Drag gain_db from 0 to 10 dB in 1 dB steps, `step_ms` apart
+    fn drag_gain(manager: &mut UndoManager, start_ms: i64, step_ms: i64) {
+        for step in 0..10 {
+            let before = (step as f64, 1.0);
+            let after = (step as f64 + 1.0, 1.0);
+            manager.push(param_change(
+                "gain_db",
+                before,
+                after,
+                start_ms + step * step_ms,
+            ));
+        }
+    }
+
+    #[test]
+    fn test_drag_coalesces_into_one_action() {
+        let mut manager = UndoManager::new(50);
+        drag_gain(&mut manager, 0, 50);
+
+        assert_eq!(manager.undo_count(), 1);
+        assert_eq!(manager.tree().len(), 1);
+        assert_eq!(manager.get_history().len(), 1);
+        assert_eq!(
+            manager.get_history()[0].state_after,
+            state_with_gain(10.0, 1.0)
+        );
+
+        let mut project: Project = serde_json::from_value(state_with_gain(10.0, 1.0)).unwrap();
+        manager.undo(&mut project).unwrap();
+        assert_eq!(gain_db(&project), 0.0);
+        assert!(!manager.can_undo());
+
+        manager.redo(&mut project).unwrap();
+        assert_eq!(gain_db(&project), 10.0);
+    }
+
+    #[test]
+    fn test_other_parameter_starts_new_action() {
+        let mut manager = UndoManager::new(50);
+        drag_gain(&mut manager, 0, 50);
+        manager.push(param_change("q", (10.0, 1.0), (10.0, 2.0), 500));
+        manager.push(param_change("q", (10.0, 2.0), (10.0, 3.0), 550));
+        manager.push(param_change("gain_db", (10.0, 3.0), (12.0, 3.0), 600));
+
+        assert_eq!(manager.undo_count(), 3);
+        assert_eq!(manager.get_history().len(), 3);
+
+        let mut project: Project = serde_json::from_value(state_with_gain(12.0, 3.0)).unwrap();
+        manager.undo(&mut project).unwrap();
+        assert_eq!(gain_db(&project), 10.0);
+        manager.undo(&mut project).unwrap();
+        assert_eq!(project.layer2.chain[0].params["q"], 1.0);
+    }
+
+    #[test]
+    fn test_coalesce_window_is_configurable() {
+        // Steps 300 ms apart: each within a 500 ms window of the last
+        let mut manager = UndoManager::new(50);
+        assert_eq!(manager.coalesce_window(), DEFAULT_COALESCE_WINDOW);
+        drag_gain(&mut manager, 0, 300);
+        assert_eq!(manager.undo_count(), 1);
+
+        let mut manager = UndoManager::new(50);
+        manager.set_coalesce_window(Duration::from_millis(200));
+        drag_gain(&mut manager, 0, 300);
+        assert_eq!(manager.undo_count(), 10);
+
+        let mut manager = UndoManager::new(50);
+        manager.set_coalesce_window(Duration::ZERO);
+        drag_gain(&mut manager, 0, 1);
+        assert_eq!(manager.undo_count(), 10);
+    }
+
+    #[test]
+    fn test_pause_between_drags_starts_new_action() {
+        let mut manager = UndoManager::new(50);
+        drag_gain(&mut manager, 0, 50);
+        drag_gain(&mut manager, 2000, 50);
+        assert_eq!(manager.undo_count(), 2);
+    }
+
+    #[test]
+    fn test_change_after_undo_does_not_coalesce() {
+        let mut manager = UndoManager::new(50);
+        manager.push(param_change("gain_db", (0.0, 1.0), (1.0, 1.0), 0));
+        manager.push(param_change("q", (1.0, 1.0), (1.0, 2.0), 1000));
+        manager.push(param_change("gain_db", (1.0, 2.0), (2.0, 2.0), 2000));
+
+        let mut project: Project = serde_json::from_value(state_with_gain(2.0, 2.0)).unwrap();
+        manager.undo(&mut project).unwrap();
+        manager.undo(&mut project).unwrap();
+
+        // Back at the first gain change, which already has a child
+        manager.push(param_change("gain_db", (1.0, 1.0), (3.0, 1.0), 2100));
+        assert_eq!(manager.undo_count(), 2);
+        assert_eq!(manager.branches().len(), 2);
+    }
 }
@@ -8,8 +8,8 @@
 
 use nueva::error::NuevaError;
 use nueva::neural::{
-    can_run_ace_step, gpu_status_summary, GpuInfo, IntentionalArtifact, NeuralContextTracker,
-    NeuralModel, NeuralModelParams, NeuralModelRegistry, QuantizationLevel,
+    can_run_ace_step, gpu_status_summary, IntentionalArtifact, NeuralContextTracker,
+    NeuralModelParams, NeuralModelRegistry, QuantizationLevel,
 };
 
 // ============================================================================
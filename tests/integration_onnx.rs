@@ -0,0 +1,225 @@
+//! Integration tests for the ONNX Runtime backend
+//!
+//! Needs the `onnx` feature and an ONNX Runtime library (see
+//! `ORT_DYLIB_PATH`). The graphs are tiny hand-encoded protobufs so no
+//! model files are checked in.
+
+#![cfg(feature = "onnx")]
+
+use nueva::engine::buffer::{AudioBuffer, ChannelLayout, INTERNAL_SAMPLE_RATE};
+use nueva::engine::io::{export_audio, generate_test_tone, import_audio, ExportFormat};
+use nueva::neural::{
+    NeuralModel, NeuralModelParams, NeuralModelRegistry, OnnxModel, DENOISE_MODEL_ENV,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+// ============================================================================
+// Minimal ONNX protobuf encoding
+// ============================================================================
+
+fn varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn int_field(field: u64, value: u64, out: &mut Vec<u8>) {
+    varint(field << 3, out);
+    varint(value, out);
+}
+
+fn bytes_field(field: u64, bytes: &[u8], out: &mut Vec<u8>) {
+    varint((field << 3) | 2, out);
+    varint(bytes.len() as u64, out);
+    out.extend_from_slice(bytes);
+}
+
+/// A float tensor value; `None` dimensions are named (dynamic)
+fn value_info(name: &str, dims: &[Option<u64>]) -> Vec<u8> {
+    let mut shape = Vec::new();
+    for (i, dim) in dims.iter().enumerate() {
+        let mut d = Vec::new();
+        match dim {
+            Some(size) => int_field(1, *size, &mut d),
+            None => bytes_field(2, format!("{}_{}", name, i).as_bytes(), &mut d),
+        }
+        bytes_field(1, &d, &mut shape);
+    }
+
+    let mut tensor = Vec::new();
+    int_field(1, 1, &mut tensor); // FLOAT
+    bytes_field(2, &shape, &mut tensor);
+
+    let mut type_proto = Vec::new();
+    bytes_field(1, &tensor, &mut type_proto);
+
+    let mut info = Vec::new();
+    bytes_field(1, name.as_bytes(), &mut info);
+    bytes_field(2, &type_proto, &mut info);
+    info
+}
+
+/// A one-node graph `op(audio) -> denoised`
+fn single_op_model(op: &str, input_dims: &[Option<u64>], output_dims: &[Option<u64>]) -> Vec<u8> {
+    let mut node = Vec::new();
+    bytes_field(1, b"audio", &mut node);
+    bytes_field(2, b"denoised", &mut node);
+    bytes_field(4, op.as_bytes(), &mut node);
+
+    let mut graph = Vec::new();
+    bytes_field(1, &node, &mut graph);
+    bytes_field(2, b"test", &mut graph);
+    bytes_field(11, &value_info("audio", input_dims), &mut graph);
+    bytes_field(12, &value_info("denoised", output_dims), &mut graph);
+
+    let mut opset = Vec::new();
+    bytes_field(1, b"", &mut opset);
+    int_field(2, 13, &mut opset);
+
+    let mut model = Vec::new();
+    int_field(1, 8, &mut model); // IR version
+    bytes_field(7, &graph, &mut model);
+    bytes_field(8, &opset, &mut model);
+    model
+}
+
+fn write_model(dir: &Path, name: &str, bytes: &[u8]) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn identity_model(dir: &Path, dims: &[Option<u64>]) -> OnnxModel {
+    let path = write_model(
+        dir,
+        "identity.onnx",
+        &single_op_model("Identity", dims, dims),
+    );
+    OnnxModel::load_denoiser(path).unwrap()
+}
+
+fn stereo_tone(len: usize) -> AudioBuffer {
+    let mut buffer = AudioBuffer::new(len, ChannelLayout::Stereo);
+    for i in 0..len {
+        buffer.samples[0][i] = (i as f32 * 0.01).sin() * 0.5;
+        buffer.samples[1][i] = (i as f32 * 0.023).cos() * 0.25;
+    }
+    buffer
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_identity_graph_round_trips_audio() {
+    let dir = TempDir::new().unwrap();
+    let model = identity_model(dir.path(), &[Some(1), None, None]);
+    assert_eq!(model.input_shape(), &[1, -1, -1]);
+
+    let input = stereo_tone(INTERNAL_SAMPLE_RATE as usize / 2);
+    let input_path = dir.path().join("in.wav");
+    let output_path = dir.path().join("out.wav");
+    export_audio(
+        &input,
+        &input_path,
+        ExportFormat::new(INTERNAL_SAMPLE_RATE, 32),
+    )
+    .unwrap();
+
+    let params = NeuralModelParams::new().with_seed(7);
+    let result = model.process(&input_path, &output_path, &params).unwrap();
+    assert!(result.success);
+    assert_eq!(result.seed, Some(7));
+
+    let output = import_audio(&output_path).unwrap();
+    assert_eq!(output.samples, input.samples);
+}
+
+#[test]
+fn test_mono_graph_runs_per_channel() {
+    let dir = TempDir::new().unwrap();
+    let model = identity_model(dir.path(), &[Some(1), Some(1), None]);
+
+    let input = stereo_tone(1000);
+    assert_eq!(model.infer(&input).unwrap().samples, input.samples);
+}
+
+#[test]
+fn test_fixed_length_graph_needs_chunking() {
+    let dir = TempDir::new().unwrap();
+    let chunk = INTERNAL_SAMPLE_RATE as u64 / 10;
+    let model = identity_model(dir.path(), &[Some(1), Some(1), Some(chunk)]);
+
+    let input = generate_test_tone(440.0, 1.0, INTERNAL_SAMPLE_RATE);
+    let err = model.infer(&input).unwrap_err().to_string();
+    assert!(err.contains("shape mismatch"), "{}", err);
+    assert!(err.contains(&chunk.to_string()), "{}", err);
+
+    let params = NeuralModelParams::new().with_seed(1);
+    let output = model.process_chunked(&input, 0.1, 0.0, &params).unwrap();
+    assert_eq!(output.len(), input.len());
+    for (a, b) in output.samples[0].iter().zip(&input.samples[0]) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_channel_mismatch_is_descriptive() {
+    let dir = TempDir::new().unwrap();
+    let model = identity_model(dir.path(), &[Some(2), None]);
+
+    let mono = generate_test_tone(440.0, 0.1, INTERNAL_SAMPLE_RATE);
+    let err = model.infer(&mono).unwrap_err().to_string();
+    assert!(err.contains("expects 2 channels"), "{}", err);
+}
+
+#[test]
+fn test_empty_input_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let model = identity_model(dir.path(), &[Some(1), None, None]);
+
+    let err = model.infer(&stereo_tone(0)).unwrap_err().to_string();
+    assert!(err.contains("at least one sample"), "{}", err);
+}
+
+#[test]
+fn test_output_shape_mismatch_is_descriptive() {
+    let dir = TempDir::new().unwrap();
+    // Transpose without a permutation reverses the axes
+    let path = write_model(
+        dir.path(),
+        "transpose.onnx",
+        &single_op_model("Transpose", &[Some(1), None, None], &[None, None, None]),
+    );
+    let model = OnnxModel::load_denoiser(path).unwrap();
+
+    let err = model.infer(&stereo_tone(64)).unwrap_err().to_string();
+    assert!(err.contains("output has shape [64, 2, 1]"), "{}", err);
+}
+
+#[test]
+fn test_invalid_graph_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let path = write_model(dir.path(), "garbage.onnx", b"not a model");
+    assert!(OnnxModel::load_denoiser(path).is_err());
+}
+
+#[test]
+fn test_registry_uses_configured_denoiser() {
+    let dir = TempDir::new().unwrap();
+    let path = write_model(
+        dir.path(),
+        "identity.onnx",
+        &single_op_model("Identity", &[None], &[None]),
+    );
+
+    std::env::set_var(DENOISE_MODEL_ENV, &path);
+    let registry = NeuralModelRegistry::with_defaults();
+    std::env::remove_var(DENOISE_MODEL_ENV);
+
+    assert_eq!(registry.get_info("denoise").unwrap().name, "ONNX Denoise");
+}
@@ -7,6 +7,14 @@ use nueva::dsp::GainEffect;
 use nueva::dsp::ParametricEQ;
 use nueva::dsp::Compressor;
 use nueva::dsp::Limiter;
+use nueva::cli::commands::{format_comparison, format_param_list, format_state_diff, render};
+use nueva::cli::RenderArgs;
+use nueva::engine::{
+    export_audio, generate_test_tone, import_audio, integrated_loudness, ExportFormat,
+};
+use nueva::state::project::Effect as ProjectEffect;
+use nueva::state::undo::{ActionType, UndoAction};
+use nueva::state::{Project, UndoManager};
 
 /// Helper to create a test sine wave buffer
 fn create_sine_buffer(frequency: f64, sample_rate: f64, duration_secs: f64) -> AudioBuffer {
@@ -14,9 +22,9 @@ fn create_sine_buffer(frequency: f64, sample_rate: f64, duration_secs: f64) -> A
     let mut buffer = AudioBuffer::new(1, num_samples, sample_rate);
     let samples = buffer.samples_mut();
 
-    for i in 0..num_samples {
+    for (i, sample) in samples.iter_mut().enumerate() {
         let t = i as f64 / sample_rate;
-        samples[i] = (2.0 * std::f64::consts::PI * frequency * t).sin() as f32;
+        *sample = (2.0 * std::f64::consts::PI * frequency * t).sin() as f32;
     }
 
     buffer
@@ -246,3 +254,303 @@ fn test_no_nan_after_processing() {
         "Processing produced invalid samples (NaN/Inf)"
     );
 }
+
+// === Render Tests ===
+
+/// A saved project on a half-second tone with a -6 dB gain in the chain
+fn create_tiny_project(dir: &std::path::Path) -> std::path::PathBuf {
+    let input = dir.join("tone.wav");
+    export_audio(
+        &generate_test_tone(440.0, 0.5, 48000),
+        &input,
+        ExportFormat::default(),
+    )
+    .unwrap();
+
+    let path = dir.join("project");
+    let mut project = Project::create(&path, Some(&input)).unwrap();
+    project.layer2.chain.push(ProjectEffect {
+        id: "gain-1".to_string(),
+        effect_type: "gain".to_string(),
+        enabled: true,
+        params: [("gain_db".to_string(), serde_json::json!(-6.0))].into(),
+        added_at: chrono::Utc::now(),
+        added_by: "user".to_string(),
+    });
+    project.save().unwrap();
+    path
+}
+
+fn render_args(output: std::path::PathBuf, format: &str, bit_depth: u16) -> RenderArgs {
+    RenderArgs {
+        output: Some(output),
+        stems: None,
+        format: format.to_string(),
+        bit_depth,
+        normalize_lufs: None,
+    }
+}
+
+#[test]
+fn test_render_applies_chain_without_changing_project() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = create_tiny_project(temp.path());
+    let project_json = std::fs::read(path.join("project.json")).unwrap();
+
+    let output = temp.path().join("render.wav");
+    render(&path, &render_args(output.clone(), "wav", 24)).unwrap();
+
+    let source = Project::load(&path).unwrap().load_layer1().unwrap();
+    let rendered = import_audio(&output).unwrap();
+    assert_eq!(
+        rendered.len(),
+        source.len(),
+        "Rendered duration differs from source"
+    );
+
+    let source_peak = source.samples[0].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let rendered_peak = rendered.samples[0]
+        .iter()
+        .fold(0.0f32, |m, s| m.max(s.abs()));
+    let change_db = 20.0 * (rendered_peak / source_peak).log10();
+    assert!(
+        (change_db + 6.0).abs() < 0.1,
+        "Expected -6 dB, got {:.2} dB",
+        change_db
+    );
+
+    assert_eq!(
+        std::fs::read(path.join("project.json")).unwrap(),
+        project_json,
+        "Render modified the project"
+    );
+}
+
+#[test]
+fn test_render_stems_per_layer() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let input = temp.path().join("stereo.wav");
+    let tone = generate_test_tone(440.0, 0.5, 48000);
+    let stereo = nueva::engine::AudioBuffer {
+        samples: vec![
+            tone.samples[0].clone(),
+            tone.samples[0].iter().map(|s| s * 0.5).collect(),
+        ],
+        ..tone
+    };
+    export_audio(&stereo, &input, ExportFormat::new(48000, 32)).unwrap();
+
+    let path = temp.path().join("song");
+    let mut project = Project::create(&path, Some(&input)).unwrap();
+    project.layer2.chain.push(ProjectEffect {
+        id: "gain-1".to_string(),
+        effect_type: "gain".to_string(),
+        enabled: true,
+        params: [("gain_db".to_string(), serde_json::json!(-6.0))].into(),
+        added_at: chrono::Utc::now(),
+        added_by: "user".to_string(),
+    });
+    project.save().unwrap();
+
+    let stems = temp.path().join("stems");
+    let args = RenderArgs {
+        output: None,
+        stems: Some(stems.clone()),
+        ..render_args(temp.path().join("unused.wav"), "wav", 32)
+    };
+    render(&path, &args).unwrap();
+
+    // No AI processing yet, so there is no Layer 1 stem
+    assert!(!stems.join("song-layer1-ai.wav").exists());
+    assert!(!temp.path().join("unused.wav").exists());
+
+    let dry = import_audio(&stems.join("song-layer0-dry.wav")).unwrap();
+    assert_eq!(dry.num_channels(), 2);
+    assert_eq!(dry.samples, stereo.samples);
+
+    let dsp = import_audio(&stems.join("song-layer2-dsp.wav")).unwrap();
+    assert_eq!(dsp.num_channels(), 2);
+    for (wet, dry) in dsp.samples[1].iter().zip(&stereo.samples[1]) {
+        assert!((wet - dry * 0.501).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_render_normalizes_loudness() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = create_tiny_project(temp.path());
+
+    let output = temp.path().join("loud.wav");
+    let mut args = render_args(output.clone(), "wav", 32);
+    args.normalize_lufs = Some(-20.0);
+    render(&path, &args).unwrap();
+
+    let lufs = integrated_loudness(&import_audio(&output).unwrap());
+    assert!(
+        (lufs + 20.0).abs() < 0.1,
+        "Expected -20 LUFS, got {:.2}",
+        lufs
+    );
+}
+
+#[test]
+fn test_render_exports_at_project_rate() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let input = temp.path().join("cd.wav");
+    export_audio(
+        &generate_test_tone(440.0, 0.5, 44100),
+        &input,
+        ExportFormat::new(44100, 24),
+    )
+    .unwrap();
+    let path = temp.path().join("project");
+    nueva::cli::commands::create_project(&path, Some(&input), 96000).unwrap();
+
+    let output = temp.path().join("render.wav");
+    render(&path, &render_args(output.clone(), "wav", 24)).unwrap();
+    assert_eq!(
+        hound::WavReader::open(&output).unwrap().spec().sample_rate,
+        96000
+    );
+}
+
+#[test]
+fn test_render_flac() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = create_tiny_project(temp.path());
+    let project_json = std::fs::read(path.join("project.json")).unwrap();
+
+    let output = temp.path().join("render.flac");
+    render(&path, &render_args(output.clone(), "flac", 16)).unwrap();
+
+    let reader = claxon::FlacReader::open(&output).unwrap();
+    let info = reader.streaminfo();
+    assert_eq!(info.bits_per_sample, 16);
+    assert_eq!(info.sample_rate, 48000);
+
+    let source = Project::load(&path).unwrap().load_layer1().unwrap();
+    assert_eq!(
+        info.samples,
+        Some(source.len() as u64),
+        "Rendered duration differs from source"
+    );
+    assert_eq!(
+        std::fs::read(path.join("project.json")).unwrap(),
+        project_json
+    );
+}
+
+#[test]
+fn test_render_rejects_invalid_format() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = create_tiny_project(temp.path());
+
+    for (format, bit_depth) in [("flac", 32), ("wav", 12), ("mp3", 16)] {
+        let output = temp.path().join("bad");
+        let err = render(&path, &render_args(output.clone(), format, bit_depth)).unwrap_err();
+        assert!(err.to_string().contains("Invalid audio format"), "{}", err);
+        assert!(!output.exists());
+    }
+}
+
+#[test]
+fn test_list_params_comes_from_effect_specs() {
+    let listing = format_param_list(Some("compressor")).unwrap();
+    assert!(
+        listing.starts_with("compressor (Compressor)"),
+        "{}",
+        listing
+    );
+    assert!(listing.contains("threshold_db"));
+    assert!(listing.contains("-60 to 0 dB (default -18)"), "{}", listing);
+    assert!(
+        listing.contains("true or false (default false)"),
+        "{}",
+        listing
+    );
+
+    let all = format_param_list(None).unwrap();
+    for effect_type in nueva::dsp::EFFECT_TYPES {
+        let heading = format!("{} (", effect_type);
+        assert!(
+            all.lines().any(|line| line.starts_with(&heading)),
+            "{}",
+            effect_type
+        );
+    }
+    assert!(
+        all.contains("sine | triangle | square (default sine)"),
+        "{}",
+        all
+    );
+
+    assert!(format_param_list(Some("flanger")).is_err());
+}
+
+#[test]
+fn test_diff_between_history_snapshots() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = create_tiny_project(temp.path());
+    let mut project = Project::load(&path).unwrap();
+    let mut undo_manager = UndoManager::new(50);
+
+    let mut limiter = project.layer2.chain[0].clone();
+    limiter.id = "limiter-1".to_string();
+    limiter.effect_type = "limiter".to_string();
+    limiter.params.clear();
+    project.layer2.chain.push(limiter);
+    project
+        .move_effect(&mut undo_manager, "limiter-1", 0)
+        .unwrap();
+    let move_id = undo_manager.tree().current().unwrap().to_string();
+
+    let state_before = serde_json::to_value(&project).unwrap();
+    project.layer2.chain[1]
+        .params
+        .insert("gain_db".to_string(), serde_json::json!(-3.0));
+    undo_manager.push(UndoAction::with_id(
+        "gain-change",
+        ActionType::DspChange,
+        "Set gain to -3 dB",
+        state_before,
+        serde_json::to_value(&project).unwrap(),
+    ));
+
+    let moved =
+        format_state_diff(&project, &undo_manager, &format!("{}^", move_id), &move_id).unwrap();
+    assert_eq!(
+        moved,
+        "Effect chain:\n  > limiter-1 (limiter) moved from position 2 to 1\n"
+    );
+
+    let changed = format_state_diff(&project, &undo_manager, &move_id[..8], "current").unwrap();
+    assert!(
+        changed.contains("gain-1 (gain) gain_db: -6 dB -> -3 dB"),
+        "{}",
+        changed
+    );
+
+    let same = format_state_diff(&project, &undo_manager, "gain-change", "current").unwrap();
+    assert_eq!(same, "No changes.\n");
+
+    assert!(format_state_diff(&project, &undo_manager, "no-such-action", "current").is_err());
+}
+
+#[test]
+fn test_compare_against_reference() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = create_tiny_project(temp.path());
+    let mut project = Project::load(&path).unwrap();
+    let state = serde_json::to_value(&project).unwrap();
+
+    // The unprocessed source as the reference: the mix is only 6 dB down
+    let report = format_comparison(&mut project, &temp.path().join("tone.wav")).unwrap();
+    assert!(report.contains("quieter than the reference"), "{}", report);
+    assert!(report.contains("(-6.0 LU)"), "{}", report);
+    assert!(report.contains("tonally matched"), "{}", report);
+    assert!(report.contains("Correlation: 1.00"), "{}", report);
+
+    assert_eq!(serde_json::to_value(&project).unwrap(), state);
+
+    assert!(format_comparison(&mut project, &temp.path().join("missing.wav")).is_err());
+}